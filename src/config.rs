@@ -1,13 +1,155 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
+
+use crate::lifecycle::LifecycleEvent;
+use crate::pod::{IconSet, OnDone, PaneLayout, PodPriority, ResourceUsage, ViewMode};
 use std::path::PathBuf;
+use std::time::Duration;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 #[serde(default)]
 pub struct Config {
     pub polling: PollingConfig,
     pub notification: NotificationConfig,
     pub detection: DetectionConfig,
+    pub ui: UiConfig,
+    pub trash: TrashConfig,
+    pub discovery: DiscoveryConfig,
+    pub resources: ResourceConfig,
+    pub gc: GcConfig,
+    pub reaper: ReaperConfig,
+    pub lifecycle: LifecycleConfig,
+    pub budget: BudgetConfig,
+    pub rate_limit: RateLimitConfig,
+    pub redaction: RedactionConfig,
+    pub logs: LogsConfig,
+    pub projects: ProjectsConfig,
+    /// `[templates.<name>]`: `apiary create --template <name>` でまとめて指定する Pod の設定
+    pub templates: std::collections::BTreeMap<String, PodTemplate>,
+    /// `[[highlight]]`: カードのプレビューと Detail で正規表現に一致した部分に色を付ける
+    #[serde(deserialize_with = "deserialize_highlights")]
+    pub highlight: Vec<HighlightRule>,
+    /// `[[watch]]`: 新しい出力が正規表現に一致したら通知 / webhook / Error にする
+    #[serde(deserialize_with = "deserialize_watchers")]
+    pub watch: Vec<WatchRule>,
+}
+
+/// `t` でアタッチするときのやり方 (tmux の中で動かしているときだけ意味がある)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachMode {
+    /// switch-client でセッションを切り替える (apiary から離れる)
+    #[default]
+    Switch,
+    /// display-popup の中でアタッチする (apiary は下に見えたまま、Esc で閉じる)
+    Popup,
+}
+
+/// 出力の watcher が一致したときにすること
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchAction {
+    /// デスクトップ通知
+    Notify,
+    /// url に JSON を POST
+    Webhook,
+    /// メンバーを Error にする (Detail を開くと解除)
+    Error,
+}
+
+/// 出力の watcher。group / project を指定するとその Pod だけを見る
+#[derive(Debug, Clone)]
+pub struct WatchRule {
+    pub name: String,
+    pub regex: regex::Regex,
+    pub group: Option<String>,
+    pub project: Option<String>,
+    pub action: WatchAction,
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawWatchRule {
+    #[serde(default)]
+    name: Option<String>,
+    pattern: String,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default = "default_watch_action")]
+    action: WatchAction,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+fn default_watch_action() -> WatchAction {
+    WatchAction::Notify
+}
+
+/// 正規表現が不正なもの、url のない webhook は警告して読み飛ばす
+fn deserialize_watchers<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<WatchRule>, D::Error> {
+    let raw = Vec::<RawWatchRule>::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .filter_map(|rule| {
+            let regex = regex::Regex::new(&rule.pattern)
+                .map_err(|e| tracing::warn!("Ignoring watch pattern {:?}: {}", rule.pattern, e))
+                .ok()?;
+            if rule.action == WatchAction::Webhook && rule.url.as_deref().is_none_or(|u| u.trim().is_empty()) {
+                tracing::warn!("Ignoring webhook watcher {:?} without url", rule.pattern);
+                return None;
+            }
+            Some(WatchRule {
+                name: rule.name.unwrap_or_else(|| rule.pattern.clone()),
+                regex,
+                group: rule.group,
+                project: rule.project,
+                action: rule.action,
+                url: rule.url,
+            })
+        })
+        .collect())
+}
+
+/// プレビュー / Detail の強調表示ルール
+#[derive(Debug, Clone)]
+pub struct HighlightRule {
+    pub regex: regex::Regex,
+    pub color: (u8, u8, u8),
+    pub bold: bool,
+}
+
+#[derive(Deserialize)]
+struct RawHighlightRule {
+    pattern: String,
+    /// 色名 (red, yellow, ...) か #rrggbb
+    #[serde(default = "default_highlight_color")]
+    color: String,
+    #[serde(default)]
+    bold: bool,
+}
+
+fn default_highlight_color() -> String {
+    "yellow".to_string()
+}
+
+/// 正規表現や色が不正なルールは警告して読み飛ばす (設定全体は捨てない)
+fn deserialize_highlights<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<HighlightRule>, D::Error> {
+    let raw = Vec::<RawHighlightRule>::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .filter_map(|rule| {
+            let regex = regex::Regex::new(&rule.pattern)
+                .map_err(|e| tracing::warn!("Ignoring highlight pattern {:?}: {}", rule.pattern, e))
+                .ok()?;
+            let Some(color) = crate::pod::parse_accent(&rule.color) else {
+                tracing::warn!("Ignoring highlight color {:?} for {:?}", rule.color, rule.pattern);
+                return None;
+            };
+            Some(HighlightRule { regex, color, bold: rule.bold })
+        })
+        .collect())
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -23,6 +165,12 @@ pub struct PollingConfig {
     pub idle_interval_ms: u64,
     /// Error 状態のポーリング間隔 (ms)
     pub error_interval_ms: u64,
+    /// RateLimited 状態のポーリング間隔 (ms)。リセットまでは何も起きないので長め
+    pub rate_limited_interval_ms: u64,
+    /// 優先度 High の Pod の間隔に掛ける倍率
+    pub high_priority_multiplier: f64,
+    /// 優先度 Low の Pod の間隔に掛ける倍率
+    pub low_priority_multiplier: f64,
 }
 
 impl Default for PollingConfig {
@@ -33,10 +181,25 @@ impl Default for PollingConfig {
             working_interval_ms: 3000,
             idle_interval_ms: 10000,
             error_interval_ms: 5000,
+            rate_limited_interval_ms: 60000,
+            high_priority_multiplier: 0.5,
+            low_priority_multiplier: 3.0,
         }
     }
 }
 
+impl PollingConfig {
+    /// 状態から決めた間隔を Pod の優先度で伸縮する (倍率は 0.1 未満にしない)
+    pub fn scaled(&self, interval_ms: u64, priority: PodPriority) -> Duration {
+        let multiplier = match priority {
+            PodPriority::High => self.high_priority_multiplier,
+            PodPriority::Normal => 1.0,
+            PodPriority::Low => self.low_priority_multiplier,
+        };
+        Duration::from_millis((interval_ms as f64 * multiplier.max(0.1)) as u64)
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(default)]
 pub struct NotificationConfig {
@@ -44,6 +207,12 @@ pub struct NotificationConfig {
     pub enabled: bool,
     /// 通知音を鳴らすか
     pub sound: bool,
+    /// Permission / Error になったとき端末に BEL を送るか (tmux のベルフラグや端末のバッジが付く)
+    pub bell: bool,
+    /// Permission / Error になったときステータスバーを一瞬反転させるか
+    pub flash: bool,
+    /// `m` で Pod の通知を止める時間 (分)
+    pub snooze_minutes: u64,
 }
 
 impl Default for NotificationConfig {
@@ -51,6 +220,9 @@ impl Default for NotificationConfig {
         Self {
             enabled: true,
             sound: false,
+            bell: false,
+            flash: false,
+            snooze_minutes: 60,
         }
     }
 }
@@ -64,6 +236,16 @@ pub struct DetectionConfig {
     pub error_patterns: Vec<String>,
     /// 追加の Idle 検出パターン (正規表現)
     pub idle_patterns: Vec<String>,
+    /// 追加の RateLimited 検出パターン (正規表現)
+    pub rate_limit_patterns: Vec<String>,
+    /// ペイン配下で探すエージェントのコマンド名 (空ならプロセス検出を無効化)
+    pub agent_processes: Vec<String>,
+    /// 外部検出スクリプト (sh -c で実行)。stdin にペインの内容を受け取り、状態名
+    /// (permission / error / rate_limited / awaiting_input / working / idle / done) を出力すると組み込み検出より優先される。
+    /// ただし組み込み検出の Permission / AwaitingInput / Error はそれより優先度の低い状態で上書きしない
+    pub detector_command: Option<String>,
+    /// 外部検出スクリプトのタイムアウト (ms)
+    pub detector_timeout_ms: u64,
 }
 
 impl Default for DetectionConfig {
@@ -72,20 +254,531 @@ impl Default for DetectionConfig {
             permission_patterns: Vec::new(),
             error_patterns: Vec::new(),
             idle_patterns: Vec::new(),
+            rate_limit_patterns: Vec::new(),
+            agent_processes: vec!["claude".to_string()],
+            detector_command: None,
+            detector_timeout_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Pod カードの幅 (カラム数)
+    pub card_width: u16,
+    /// Pod カードの高さ (行数、ボーダー込み)
+    pub card_height: u16,
+    /// カードに表示するプレビュー行数 (省略時はカードの高さいっぱい)
+    pub preview_lines: Option<usize>,
+    /// カードタイトルに経過時間を表示するか
+    pub show_elapsed: bool,
+    /// カードと Detail のタイトルに作業時間 (Working だった時間、進行中の分を含む) を表示するか
+    pub show_working_time: bool,
+    /// カードタイトルに subagent 数を表示するか
+    pub show_subagents: bool,
+    /// カード下端にプロジェクト名と git ブランチを表示するか
+    pub show_project: bool,
+    /// コンパクト表示 (カードの高さを半分にして 2 倍の Pod を表示)
+    pub compact: bool,
+    /// 起動時の表示形式 ("grid" | "list" | "kanban")
+    pub view: ViewMode,
+    /// グループ未指定の Pod をプロジェクト名でまとめて表示する
+    pub group_by_project: bool,
+    /// 左ペインの幅 (画面幅に対する %)。horizontal レイアウトでは下段の高さ %
+    pub left_pane_percent: u16,
+    /// 起動時のペイン配置 ("vertical" = 左右 | "horizontal" = 上下)
+    pub layout: PaneLayout,
+    /// ステータスアイコンの字形 ("emoji" | "nerdfont" | "ascii" | "text")
+    pub icons: IconSet,
+    /// スクリーンリーダー / dumb 端末向け: 罫線を ASCII に、状態は単語で表示し、
+    /// 状態変化を最下行に 1 行で通知する
+    pub accessible: bool,
+    /// `t` のアタッチ方法 ("switch" | "popup")。`T` は常に popup
+    pub attach: AttachMode,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            card_width: 20,
+            card_height: 8,
+            preview_lines: None,
+            show_elapsed: true,
+            show_working_time: true,
+            show_subagents: true,
+            show_project: true,
+            compact: false,
+            view: ViewMode::Grid,
+            group_by_project: false,
+            left_pane_percent: 35,
+            layout: PaneLayout::Vertical,
+            icons: IconSet::Emoji,
+            accessible: false,
+            attach: AttachMode::Switch,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// drop した Pod をゴミ箱に残す日数 (0 なら自動削除しない)
+    pub ttl_days: u64,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self { ttl_days: 7 }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DiscoveryConfig {
+    /// 管理外の Claude Code セッションを自動で Pod として取り込むか
+    pub auto_adopt: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ResourceConfig {
+    /// CPU / メモリのサンプリング間隔 (秒)
+    pub sample_interval_secs: u64,
+    /// カード下端に使用量を表示するか
+    pub show_on_cards: bool,
+    /// この CPU% (プロセスツリー合計) を超えたら警告
+    pub cpu_threshold: Option<f32>,
+    /// この RSS (MB) を超えたら警告
+    pub mem_threshold_mb: Option<u64>,
+}
+
+impl Default for ResourceConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval_secs: 5,
+            show_on_cards: false,
+            cpu_threshold: None,
+            mem_threshold_mb: None,
+        }
+    }
+}
+
+impl ResourceConfig {
+    /// 使用量がしきい値を超えているか
+    pub fn exceeds(&self, usage: &ResourceUsage) -> bool {
+        self.cpu_threshold.is_some_and(|t| usage.cpu_percent > t)
+            || self.mem_threshold_mb.is_some_and(|t| usage.rss_kb / 1024 > t)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct GcConfig {
+    /// `apiary gc` が Dead Pod を片付けるまでの時間 (時間)
+    pub dead_pod_ttl_hours: u64,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self { dead_pod_ttl_hours: 24 }
+    }
+}
+
+/// 放置された Pod を TUI が定期的に片付ける設定 (どちらのしきい値も 0 なら何もしない)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ReaperConfig {
+    /// Dead のままこの時間 (時間) が過ぎた Pod を片付ける
+    pub dead_after_hours: u64,
+    /// Done のまま、アタッチも入力もされずにこの日数が過ぎた Pod を片付ける
+    pub done_after_days: u64,
+    /// 片付け方
+    pub action: ReapAction,
+    /// 対象になってから実際に片付けるまでの猶予 (分)。この間に触れば残る
+    pub grace_minutes: u64,
+    /// 片付けない Pod の名前、または `#タグ` (ピン留めした Pod も片付けない)
+    pub keep: Vec<String>,
+    /// 確認間隔 (秒)
+    pub check_interval_secs: u64,
+}
+
+impl Default for ReaperConfig {
+    fn default() -> Self {
+        Self {
+            dead_after_hours: 0,
+            done_after_days: 0,
+            action: ReapAction::Archive,
+            grace_minutes: 60,
+            keep: Vec::new(),
+            check_interval_secs: 300,
+        }
+    }
+}
+
+impl ReaperConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.dead_after_hours > 0 || self.done_after_days > 0
+    }
+
+    /// keep に名前か `#タグ` が入っている、またはピン留めされている
+    pub fn keeps(&self, pod: &crate::pod::Pod) -> bool {
+        pod.pinned
+            || self.keep.iter().any(|k| match k.strip_prefix('#') {
+                Some(tag) => pod.tags.iter().any(|t| t == tag),
+                None => *k == pod.name,
+            })
+    }
+}
+
+/// 放置された Pod の片付け方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReapAction {
+    /// drop してゴミ箱に入れる (`apiary restore` で戻せる)
+    #[default]
+    Archive,
+    /// drop してゴミ箱にも残さない
+    Drop,
+}
+
+/// 1 日あたりの推定コスト (USD) の予算
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// 全プロジェクト合計の予算
+    pub daily_usd: Option<f64>,
+    /// プロジェクト名ごとの予算
+    pub projects: std::collections::BTreeMap<String, f64>,
+    /// 予算のこの % を超えたら警告
+    pub warn_percent: u8,
+    /// 予算を超えている間は auto_adopt を止める
+    pub pause_auto_adopt: bool,
+    /// 集計間隔 (秒、transcript を読むので長め)
+    pub check_interval_secs: u64,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            daily_usd: None,
+            projects: std::collections::BTreeMap::new(),
+            warn_percent: 80,
+            pause_auto_adopt: false,
+            check_interval_secs: 60,
         }
     }
 }
 
-impl Default for Config {
+impl BudgetConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.daily_usd.is_some() || !self.projects.is_empty()
+    }
+}
+
+/// 利用上限に達した Pod の自動再開
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// リセット時刻を過ぎたら自動で再開する (Pod ごとに `apiary auto-resume` で上書きできる)
+    pub auto_resume: bool,
+    /// 再開時に送るメッセージ
+    pub message: String,
+    /// message の代わりに Pod に最後に送った指示を送り直す
+    pub resubmit_prompt: bool,
+    /// リセット時刻から再開までの余裕 (秒)
+    pub delay_secs: u64,
+}
+
+impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
-            polling: PollingConfig::default(),
-            notification: NotificationConfig::default(),
-            detection: DetectionConfig::default(),
+            auto_resume: false,
+            message: "continue".to_string(),
+            resubmit_prompt: false,
+            delay_secs: 60,
         }
     }
 }
 
+/// Pod のテンプレート。作成時のフラグのほうが優先される
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PodTemplate {
+    /// tmux セッションに渡す環境変数 (API キー、機能フラグ、CLAUDE_CONFIG_DIR など)
+    pub env: std::collections::BTreeMap<String, String>,
+    /// Claude Code の permission mode (default / acceptEdits / plan / bypassPermissions)
+    pub permission_mode: Option<String>,
+    /// 確認なしで使わせるツール (`Bash(npm test:*)` のような Claude Code の書式)
+    pub allowed_tools: Vec<String>,
+    /// 使わせないツール
+    pub denied_tools: Vec<String>,
+    /// 無効にする MCP サーバー (.mcp.json のサーバー名)
+    pub disabled_mcp_servers: Vec<String>,
+    /// `claude --model` で使うモデル (opus / sonnet / フルネーム)
+    pub model: Option<String>,
+    /// 作業完了時のアクション (`{ run = "cargo test" }` / `"commit"` / `{ prompt = "..." }`)
+    pub on_done: Option<OnDone>,
+}
+
+impl PodTemplate {
+    /// `claude --settings` に渡す設定。Claude Code の設定を何も指定していなければ None
+    pub fn claude_settings(&self) -> Option<serde_json::Value> {
+        let mut permissions = serde_json::Map::new();
+        if !self.allowed_tools.is_empty() {
+            permissions.insert("allow".to_string(), self.allowed_tools.clone().into());
+        }
+        if !self.denied_tools.is_empty() {
+            permissions.insert("deny".to_string(), self.denied_tools.clone().into());
+        }
+        if let Some(ref mode) = self.permission_mode {
+            permissions.insert("defaultMode".to_string(), mode.clone().into());
+        }
+        let mut settings = serde_json::Map::new();
+        if !permissions.is_empty() {
+            settings.insert("permissions".to_string(), permissions.into());
+        }
+        if !self.disabled_mcp_servers.is_empty() {
+            settings.insert("disabledMcpjsonServers".to_string(), self.disabled_mcp_servers.clone().into());
+        }
+        (!settings.is_empty()).then_some(settings.into())
+    }
+}
+
+/// ペイン出力に出たトークン類の伏せ字化 (プレビュー・Chat 履歴・ゴミ箱・録画)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// false なら組み込みのパターンも含めて伏せない
+    pub enabled: bool,
+    /// 組み込み (AWS キー、GitHub トークン、.env 形式の代入など) に加える正規表現。
+    /// キャプチャグループがあればその部分だけを伏せる
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self { enabled: true, patterns: Vec::new() }
+    }
+}
+
+/// メンバーのペイン出力を ~/.local/share/apiary/logs に書き続ける
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LogsConfig {
+    /// すべての Pod で記録する (Pod ごとに `apiary logs` で上書きできる)
+    pub enabled: bool,
+    /// 1 ファイルの上限 (MB)。超えたら <role>.log.1 にずらす
+    pub max_size_mb: u64,
+    /// 残す古いファイルの数
+    pub keep: usize,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self { enabled: false, max_size_mb: 10, keep: 3 }
+    }
+}
+
+/// `apiary project scan` でリポジトリを探す場所
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ProjectsConfig {
+    /// 探すディレクトリ (~/ 可)
+    pub scan_roots: Vec<String>,
+    /// root から何階層下まで探すか
+    pub scan_depth: usize,
+}
+
+impl Default for ProjectsConfig {
+    fn default() -> Self {
+        Self { scan_roots: Vec::new(), scan_depth: 3 }
+    }
+}
+
+/// Pod のイベントで実行するシェルコマンド (sh -c、$POD / $PROJECT / $STATUS などを参照できる)
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LifecycleConfig {
+    /// Pod を作成・取り込んだとき
+    pub on_create: Option<String>,
+    /// Permission 待ちになったとき
+    pub on_permission: Option<String>,
+    /// 作業が終わったとき (Working → Idle / Done)
+    pub on_done: Option<String>,
+    /// Pod を drop したとき
+    pub on_drop: Option<String>,
+}
+
+impl LifecycleConfig {
+    pub fn command(&self, event: LifecycleEvent) -> Option<&str> {
+        match event {
+            LifecycleEvent::Create => self.on_create.as_deref(),
+            LifecycleEvent::Permission => self.on_permission.as_deref(),
+            LifecycleEvent::Done => self.on_done.as_deref(),
+            LifecycleEvent::Drop => self.on_drop.as_deref(),
+        }
+        .filter(|c| !c.trim().is_empty())
+    }
+}
+
+impl UiConfig {
+    /// compact プリセットを反映したカードサイズ (width, height)
+    pub fn card_size(&self) -> (u16, u16) {
+        // タイトル + プレビュー 1 行 + ボーダーを確保できる最小サイズ
+        let width = self.card_width.max(12);
+        let height = self.card_height.max(3);
+        if self.compact {
+            (width, (height / 2).max(3))
+        } else {
+            (width, height)
+        }
+    }
+
+    /// 実際に使う字形セット (アクセシビリティモードでは状態名)
+    pub fn icon_set(&self) -> IconSet {
+        if self.accessible { IconSet::Text } else { self.icons }
+    }
+
+    /// 左ペインの幅 % (どちらのペインも潰れないよう 15〜70 に丸める)
+    pub fn left_pane_percent(&self) -> u16 {
+        self.left_pane_percent.clamp(15, 70)
+    }
+
+    /// カード内に表示するプレビュー行数
+    pub fn preview_lines(&self) -> usize {
+        let (_, height) = self.card_size();
+        let available = height.saturating_sub(2) as usize;
+        match self.preview_lines {
+            Some(n) => n.min(available),
+            None => available,
+        }
+    }
+}
+
+/// `apiary setup` が書き出す設定ファイルのひな形 (値はすべてデフォルト)
+const DEFAULT_CONFIG_TEMPLATE: &str = r#"# Apiary configuration. Uncomment a line to override its default.
+
+[polling]
+# focused_interval_ms = 1000
+# permission_interval_ms = 1000
+# working_interval_ms = 3000
+# idle_interval_ms = 10000
+# error_interval_ms = 5000
+# rate_limited_interval_ms = 60000
+# high_priority_multiplier = 0.5
+# low_priority_multiplier = 3.0
+
+[notification]
+# enabled = true
+# sound = false
+# bell = false
+# flash = false
+# snooze_minutes = 60
+
+[detection]
+# permission_patterns = []
+# error_patterns = []
+# idle_patterns = []
+# rate_limit_patterns = []
+# agent_processes = ["claude"]
+# detector_command = "~/.config/apiary/detect.sh"
+# detector_timeout_ms = 1000
+
+[ui]
+# card_width = 20
+# card_height = 8
+# preview_lines = 4
+# show_elapsed = true
+# show_working_time = true
+# show_subagents = true
+# show_project = true
+# compact = false
+# view = "grid"
+# group_by_project = false
+# left_pane_percent = 35
+# layout = "vertical"
+# icons = "emoji"
+# accessible = false
+# attach = "switch"
+
+[trash]
+# ttl_days = 7
+
+[discovery]
+# auto_adopt = false
+
+[resources]
+# sample_interval_secs = 5
+# show_on_cards = false
+# cpu_threshold = 200.0
+# mem_threshold_mb = 4096
+
+[gc]
+# dead_pod_ttl_hours = 24
+
+[reaper]
+# dead_after_hours = 0
+# done_after_days = 0
+# action = "archive"
+# grace_minutes = 60
+# keep = []
+
+[lifecycle]
+# on_create = "echo \"$POD created in $PROJECT\" >> ~/apiary.log"
+# on_permission = ""
+# on_done = ""
+# on_drop = ""
+
+[budget]
+# daily_usd = 20.0
+# warn_percent = 80
+# pause_auto_adopt = false
+# check_interval_secs = 60
+# projects = { apiary = 5.0 }
+
+[rate_limit]
+# auto_resume = false
+# message = "continue"
+# resubmit_prompt = false
+# delay_secs = 60
+
+[redaction]
+# enabled = true
+# patterns = ["internal-token-[0-9a-f]{32}", "PASSCODE=(\\S+)"]
+
+[logs]
+# enabled = false
+# max_size_mb = 10
+# keep = 3
+
+[projects]
+# scan_roots = ["~/src"]
+# scan_depth = 3
+
+# [templates.work]
+# env = { CLAUDE_CONFIG_DIR = "/home/me/.claude-work", FEATURE_X = "1" }
+# model = "opus"
+# on_done = { run = "cargo test" }   # or "commit", or { prompt = "Write a summary" }
+
+# [templates.locked]
+# permission_mode = "plan"
+# allowed_tools = ["Read", "Grep", "Bash(npm test:*)"]
+# denied_tools = ["WebFetch"]
+# disabled_mcp_servers = ["github"]
+
+# [[highlight]]
+# pattern = "FAIL|error:"
+# color = "red"
+# bold = true
+
+# [[watch]]
+# name = "destructive sql"
+# pattern = "(?i)drop table"
+# action = "notify"
+# group = "backend"
+"#;
+
 impl Config {
     /// ~/.config/apiary/config.toml を読み込む。なければデフォルト。
     pub fn load() -> Result<Self> {
@@ -108,10 +801,186 @@ impl Config {
         Ok(config)
     }
 
-    fn config_path() -> Result<PathBuf> {
+    /// 全項目をコメントアウトしたデフォルト設定ファイルを書き出す (既にあれば何もしない)
+    pub fn write_default() -> Result<PathBuf> {
+        let path = Self::config_path()?;
+        if path.exists() {
+            return Ok(path);
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create config directory: {:?}", dir))?;
+        }
+        std::fs::write(&path, DEFAULT_CONFIG_TEMPLATE)
+            .with_context(|| format!("Failed to write config: {:?}", path))?;
+        Ok(path)
+    }
+
+    pub fn config_path() -> Result<PathBuf> {
         let dir = dirs::config_dir()
             .context("Failed to determine config directory")?
             .join("apiary");
         Ok(dir.join("config.toml"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_template_parses_to_defaults() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG_TEMPLATE).unwrap();
+        assert_eq!(config.ui.card_size(), UiConfig::default().card_size());
+        assert_eq!(config.polling.idle_interval_ms, 10000);
+        assert!(!config.discovery.auto_adopt);
+        assert_eq!(config.projects.scan_depth, 3);
+    }
+
+    #[test]
+    fn test_template_claude_settings() {
+        let config: Config = toml::from_str(
+            "[templates.locked]\npermission_mode = \"plan\"\nallowed_tools = [\"Read\"]\ndisabled_mcp_servers = [\"github\"]\n\n[templates.env_only]\nenv = { A = \"1\" }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.templates["locked"].claude_settings().unwrap(),
+            serde_json::json!({
+                "permissions": { "allow": ["Read"], "defaultMode": "plan" },
+                "disabledMcpjsonServers": ["github"],
+            })
+        );
+        assert!(config.templates["env_only"].claude_settings().is_none());
+    }
+
+    #[test]
+    fn test_resource_thresholds() {
+        let usage = ResourceUsage { cpu_percent: 150.0, rss_kb: 2048 * 1024 };
+        assert!(!ResourceConfig::default().exceeds(&usage));
+        let config: Config = toml::from_str("[resources]\ncpu_threshold = 100.0\n").unwrap();
+        assert!(config.resources.exceeds(&usage));
+        let config: Config = toml::from_str("[resources]\nmem_threshold_mb = 4096\n").unwrap();
+        assert!(!config.resources.exceeds(&usage));
+    }
+
+    #[test]
+    fn test_ui_defaults() {
+        let ui = UiConfig::default();
+        assert_eq!(ui.card_size(), (20, 8));
+        assert_eq!(ui.preview_lines(), 6);
+    }
+
+    #[test]
+    fn test_ui_compact_halves_height() {
+        let config: Config = toml::from_str("[ui]\ncompact = true\n").unwrap();
+        assert_eq!(config.ui.card_size(), (20, 4));
+        assert_eq!(config.ui.preview_lines(), 2);
+    }
+
+    #[test]
+    fn test_ui_preview_lines_clamped_to_card() {
+        let config: Config = toml::from_str("[ui]\ncard_height = 6\npreview_lines = 10\n").unwrap();
+        assert_eq!(config.ui.preview_lines(), 4);
+        let config: Config = toml::from_str("[ui]\npreview_lines = 2\n").unwrap();
+        assert_eq!(config.ui.preview_lines(), 2);
+    }
+
+    #[test]
+    fn test_ui_layout_and_split() {
+        let config: Config = toml::from_str("[ui]\nlayout = \"horizontal\"\nleft_pane_percent = 90\n").unwrap();
+        assert_eq!(config.ui.layout, PaneLayout::Horizontal);
+        assert_eq!(config.ui.left_pane_percent(), 70);
+        assert_eq!(UiConfig::default().layout, PaneLayout::Vertical);
+    }
+
+    #[test]
+    fn test_ui_attach_mode() {
+        let config: Config = toml::from_str("[ui]\nattach = \"popup\"\n").unwrap();
+        assert_eq!(config.ui.attach, AttachMode::Popup);
+        assert_eq!(UiConfig::default().attach, AttachMode::Switch);
+    }
+
+    #[test]
+    fn test_reaper() {
+        assert!(!ReaperConfig::default().is_enabled());
+        let config: Config =
+            toml::from_str("[reaper]\ndone_after_days = 3\naction = \"drop\"\nkeep = [\"main\", \"#keep\"]\n").unwrap();
+        assert!(config.reaper.is_enabled());
+        assert_eq!(config.reaper.action, ReapAction::Drop);
+        assert_eq!(config.reaper.grace_minutes, 60);
+        assert_eq!(config.reaper.keep, vec!["main", "#keep"]);
+    }
+
+    #[test]
+    fn test_lifecycle_commands() {
+        let config: Config = toml::from_str("[lifecycle]\non_done = \"make notify\"\non_drop = \" \"\n").unwrap();
+        assert_eq!(config.lifecycle.command(LifecycleEvent::Done), Some("make notify"));
+        assert_eq!(config.lifecycle.command(LifecycleEvent::Drop), None);
+        assert_eq!(config.lifecycle.command(LifecycleEvent::Create), None);
+    }
+
+    #[test]
+    fn test_budget() {
+        assert!(!BudgetConfig::default().is_enabled());
+        let config: Config = toml::from_str("[budget]\nprojects = { web = 5.0 }\n").unwrap();
+        assert!(config.budget.is_enabled());
+        assert_eq!(config.budget.projects.get("web"), Some(&5.0));
+        assert_eq!(config.budget.warn_percent, 80);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let config: Config = toml::from_str("[rate_limit]\nauto_resume = true\n").unwrap();
+        assert!(config.rate_limit.auto_resume);
+        assert_eq!(config.rate_limit.message, "continue");
+        assert_eq!(config.rate_limit.delay_secs, 60);
+    }
+
+    #[test]
+    fn test_priority_multiplier() {
+        let polling = PollingConfig::default();
+        assert_eq!(polling.scaled(3000, PodPriority::Normal), Duration::from_millis(3000));
+        assert_eq!(polling.scaled(3000, PodPriority::High), Duration::from_millis(1500));
+        assert_eq!(polling.scaled(3000, PodPriority::Low), Duration::from_millis(9000));
+        let config: Config = toml::from_str("[polling]\nhigh_priority_multiplier = 0.0\n").unwrap();
+        assert_eq!(config.polling.scaled(3000, PodPriority::High), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_highlight_rules() {
+        assert!(Config::default().highlight.is_empty());
+        let config: Config = toml::from_str(
+            "[[highlight]]\npattern = \"FAIL\"\ncolor = \"red\"\nbold = true\n\n[[highlight]]\npattern = \"(\"\n\n[[highlight]]\npattern = \"warning:\"\n",
+        )
+        .unwrap();
+        // 不正な正規表現のルールだけを捨てる
+        assert_eq!(config.highlight.len(), 2);
+        assert!(config.highlight[0].regex.is_match("2 FAIL"));
+        assert!(config.highlight[0].bold);
+        assert_eq!(Some(config.highlight[1].color), crate::pod::parse_accent("yellow"));
+    }
+
+    #[test]
+    fn test_watch_rules() {
+        let config: Config = toml::from_str(
+            "[[watch]]\npattern = \"DROP TABLE\"\n\n[[watch]]\nname = \"ci\"\npattern = \"FAIL\"\naction = \"webhook\"\n\n[[watch]]\nname = \"hook\"\npattern = \"x\"\naction = \"webhook\"\nurl = \"http://localhost/hook\"\nproject = \"api\"\n",
+        )
+        .unwrap();
+        // url のない webhook は捨てる
+        assert_eq!(config.watch.len(), 2);
+        assert_eq!(config.watch[0].name, "DROP TABLE");
+        assert_eq!(config.watch[0].action, WatchAction::Notify);
+        assert_eq!(config.watch[1].action, WatchAction::Webhook);
+        assert_eq!(config.watch[1].project.as_deref(), Some("api"));
+    }
+
+    #[test]
+    fn test_ui_icons() {
+        assert_eq!(UiConfig::default().icons, IconSet::Emoji);
+        let config: Config = toml::from_str("[ui]\nicons = \"nerdfont\"\n").unwrap();
+        assert_eq!(config.ui.icons, IconSet::Nerdfont);
+        assert_eq!(config.ui.icon_set(), IconSet::Nerdfont);
+        let config: Config = toml::from_str("[ui]\nicons = \"nerdfont\"\naccessible = true\n").unwrap();
+        assert_eq!(config.ui.icon_set(), IconSet::Text);
+    }
+}
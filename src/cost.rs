@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// 1 メッセージ分のトークン使用量 (Claude Code の transcript の message.usage)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+/// モデル系列ごとの 100 万トークンあたりの USD (input, output)。概算用
+fn price_per_mtok(model: &str) -> (f64, f64) {
+    let model = model.to_ascii_lowercase();
+    if model.contains("opus") {
+        (15.0, 75.0)
+    } else if model.contains("haiku") {
+        (0.8, 4.0)
+    } else {
+        (3.0, 15.0)
+    }
+}
+
+impl TokenUsage {
+    /// 推定コスト (USD)。キャッシュ書き込みは input の 1.25 倍、読み込みは 0.1 倍で数える
+    pub fn estimate_usd(&self, model: &str) -> f64 {
+        let (input, output) = price_per_mtok(model);
+        (self.input_tokens as f64 * input
+            + self.cache_creation_input_tokens as f64 * input * 1.25
+            + self.cache_read_input_tokens as f64 * input * 0.1
+            + self.output_tokens as f64 * output)
+            / 1_000_000.0
+    }
+}
+
+#[derive(Deserialize)]
+struct TranscriptLine {
+    #[serde(rename = "type")]
+    kind: String,
+    timestamp: DateTime<Utc>,
+    message: TranscriptMessage,
+}
+
+#[derive(Deserialize)]
+struct TranscriptMessage {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    model: String,
+    usage: TokenUsage,
+}
+
+/// transcript の 1 行から (メッセージ ID, 時刻, 推定 USD) を取り出す。assistant の応答以外は None
+fn parse_line(line: &str) -> Option<(String, DateTime<Utc>, f64)> {
+    let line: TranscriptLine = serde_json::from_str(line).ok()?;
+    if line.kind != "assistant" {
+        return None;
+    }
+    let usd = line.message.usage.estimate_usd(&line.message.model);
+    Some((line.message.id, line.timestamp, usd))
+}
+
+/// ~/.claude/projects (Claude Code がセッションの transcript を置く場所)
+pub fn transcripts_root() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".claude").join("projects"))
+}
+
+/// プロジェクトのパスに対応する transcript ディレクトリ名 (英数字以外を '-' に置き換えたもの)
+pub fn transcript_dir_name(project_path: &str) -> String {
+    project_path
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// ディレクトリ内の transcript (*.jsonl) から since 以降の推定コスト (USD) を合計する。
+/// 同じメッセージが複数行に分かれて記録されるので ID で重複を除く
+pub fn spend_in_dir(dir: &Path, since: DateTime<Utc>) -> f64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0.0;
+    };
+    let mut seen = HashSet::new();
+    let mut total = 0.0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|e| e != "jsonl") {
+            continue;
+        }
+        // since より前に更新が止まったファイルは読まない
+        let modified = entry.metadata().and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from);
+        if modified.is_some_and(|m| m < since) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        for (id, at, usd) in content.lines().filter_map(parse_line) {
+            if at >= since && (id.is_empty() || seen.insert(id)) {
+                total += usd;
+            }
+        }
+    }
+    total
+}
+
+/// プロジェクトの since 以降の推定コスト (USD)
+pub fn project_spend(project_path: &str, since: DateTime<Utc>) -> f64 {
+    transcripts_root()
+        .map(|root| spend_in_dir(&root.join(transcript_dir_name(project_path)), since))
+        .unwrap_or(0.0)
+}
+
+/// セッションの transcript のパス
+pub fn transcript_path(project_path: &str, session_id: &str) -> Option<PathBuf> {
+    transcripts_root().map(|root| root.join(transcript_dir_name(project_path)).join(format!("{}.jsonl", session_id)))
+}
+
+/// last_model が読む transcript の末尾のバイト数
+const MODEL_TAIL_BYTES: u64 = 64 * 1024;
+
+#[derive(Deserialize)]
+struct ModelLine {
+    #[serde(rename = "type")]
+    kind: String,
+    message: ModelMessage,
+}
+
+#[derive(Deserialize)]
+struct ModelMessage {
+    #[serde(default)]
+    model: String,
+}
+
+/// transcript の末尾から、最後の応答に使われたモデルを読む ("<synthetic>" などは除く)
+pub fn last_model(path: &Path) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(MODEL_TAIL_BYTES))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    // 途中から読んだ最初の行は壊れているので JSON として読めずに飛ばされる
+    String::from_utf8_lossy(&tail).lines().rev().find_map(|line| {
+        let line: ModelLine = serde_json::from_str(line).ok()?;
+        let model = line.message.model;
+        (line.kind == "assistant" && model.starts_with("claude")).then_some(model)
+    })
+}
+
+/// すべてのプロジェクトの since 以降の推定コスト (USD)
+pub fn total_spend(since: DateTime<Utc>) -> f64 {
+    let Some(Ok(entries)) = transcripts_root().map(std::fs::read_dir) else {
+        return 0.0;
+    };
+    entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| spend_in_dir(&e.path(), since))
+        .sum()
+}
+
+/// 予算に対する消化状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BudgetLevel {
+    Ok,
+    /// 警告しきい値 (warn_percent) を超えた
+    Warning,
+    /// 予算を超えた
+    Exceeded,
+}
+
+impl BudgetLevel {
+    pub fn of(spent: f64, limit: f64, warn_percent: u8) -> Self {
+        if limit <= 0.0 {
+            BudgetLevel::Ok
+        } else if spent >= limit {
+            BudgetLevel::Exceeded
+        } else if spent >= limit * f64::from(warn_percent) / 100.0 {
+            BudgetLevel::Warning
+        } else {
+            BudgetLevel::Ok
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn line(id: &str, at: &str, model: &str, input: u64, output: u64) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{}","message":{{"id":"{}","model":"{}","usage":{{"input_tokens":{},"output_tokens":{}}}}}}}"#,
+            at, id, model, input, output
+        )
+    }
+
+    #[test]
+    fn test_last_model() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("s.jsonl");
+        let content = [
+            line("a", "2024-05-01T10:00:00Z", "claude-sonnet-4-20250514", 1, 1),
+            line("b", "2024-05-01T10:01:00Z", "claude-opus-4-1-20250805", 1, 1),
+            line("c", "2024-05-01T10:02:00Z", "<synthetic>", 0, 0),
+            r#"{"type":"user","timestamp":"2024-05-01T10:03:00Z","message":{"role":"user"}}"#.to_string(),
+        ];
+        std::fs::write(&path, content.join("\n")).unwrap();
+        assert_eq!(last_model(&path).as_deref(), Some("claude-opus-4-1-20250805"));
+        assert_eq!(last_model(&dir.path().join("missing.jsonl")), None);
+    }
+
+    #[test]
+    fn test_spend_in_dir() {
+        let dir = TempDir::new().unwrap();
+        let content = [
+            line("a", "2024-05-01T10:00:00Z", "claude-sonnet-4", 1_000_000, 0),
+            // 同じメッセージの続きの行は数えない
+            line("a", "2024-05-01T10:00:01Z", "claude-sonnet-4", 1_000_000, 0),
+            line("b", "2024-05-01T11:00:00Z", "claude-opus-4", 0, 100_000),
+            line("c", "2024-04-30T23:00:00Z", "claude-sonnet-4", 1_000_000, 0),
+            r#"{"type":"user","timestamp":"2024-05-01T10:00:00Z","message":{"role":"user"}}"#.to_string(),
+            "not json".to_string(),
+        ]
+        .join("\n");
+        std::fs::write(dir.path().join("session.jsonl"), content).unwrap();
+
+        let since = "2024-05-01T00:00:00Z".parse().unwrap();
+        let spent = spend_in_dir(dir.path(), since);
+        assert!((spent - (3.0 + 7.5)).abs() < 1e-9, "spent = {}", spent);
+    }
+
+    #[test]
+    fn test_transcript_dir_name() {
+        assert_eq!(transcript_dir_name("/home/me/src/apiary"), "-home-me-src-apiary");
+        assert_eq!(transcript_dir_name("/home/me/my.app/"), "-home-me-my-app");
+    }
+
+    #[test]
+    fn test_budget_level() {
+        assert_eq!(BudgetLevel::of(5.0, 10.0, 80), BudgetLevel::Ok);
+        assert_eq!(BudgetLevel::of(8.0, 10.0, 80), BudgetLevel::Warning);
+        assert_eq!(BudgetLevel::of(10.0, 10.0, 80), BudgetLevel::Exceeded);
+        assert_eq!(BudgetLevel::of(10.0, 0.0, 80), BudgetLevel::Ok);
+    }
+}
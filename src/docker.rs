@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::pod::Sandbox;
+use crate::tmux::shell_quote;
+
+/// `apiary create --docker` / `--docker-exec` / `--devcontainer` で指定するコンテナ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxSpec {
+    /// イメージから新しいコンテナを `docker run` する
+    Run(String),
+    /// 起動済みのコンテナに `docker exec` で入る
+    Exec(String),
+    /// プロジェクトの devcontainer を `devcontainer up` して `devcontainer exec` で入る
+    Devcontainer,
+}
+
+/// プロジェクトに devcontainer の設定があるか
+pub fn has_devcontainer(project_path: &str) -> bool {
+    let root = Path::new(project_path);
+    root.join(".devcontainer").join("devcontainer.json").is_file() || root.join(".devcontainer.json").is_file()
+}
+
+/// 作成前の確認とコンテナの準備をして、Pod に記録するコンテナを返す
+/// (run は "apiary-<pod>" という名前で起動する)
+pub fn prepare(spec: &SandboxSpec, pod_name: &str, project_path: &str) -> Result<Sandbox> {
+    match spec {
+        SandboxSpec::Run(image) => {
+            check_docker()?;
+            Ok(Sandbox { container: format!("apiary-{}", pod_name), image: Some(image.clone()), devcontainer: false })
+        }
+        SandboxSpec::Exec(container) => {
+            check_docker()?;
+            if !is_running(container) {
+                anyhow::bail!("Container '{}' is not running", container);
+            }
+            Ok(Sandbox { container: container.clone(), image: None, devcontainer: false })
+        }
+        SandboxSpec::Devcontainer => {
+            if !has_devcontainer(project_path) {
+                anyhow::bail!("No .devcontainer/devcontainer.json in {}", project_path);
+            }
+            let container = devcontainer_up(project_path)?;
+            Ok(Sandbox { container, image: None, devcontainer: true })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevcontainerUp {
+    outcome: String,
+    #[serde(default)]
+    container_id: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// `devcontainer up` の出力 (最後の JSON 行) からコンテナ ID を取り出す
+fn parse_devcontainer_up(stdout: &str) -> Result<String> {
+    let result: DevcontainerUp = stdout
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str(line.trim()).ok())
+        .context("Unexpected output from devcontainer up")?;
+    if result.outcome != "success" || result.container_id.is_empty() {
+        anyhow::bail!("devcontainer up failed: {}", result.message);
+    }
+    Ok(result.container_id)
+}
+
+/// プロジェクトの devcontainer を起動 (起動済みならそのまま) してコンテナ ID を返す
+pub fn devcontainer_up(project_path: &str) -> Result<String> {
+    let output = Command::new("devcontainer")
+        .args(["up", "--workspace-folder", project_path])
+        .output()
+        .context("Failed to run devcontainer (is @devcontainers/cli installed?)")?;
+    parse_devcontainer_up(&String::from_utf8_lossy(&output.stdout)).map_err(|e| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match stderr.trim().lines().last() {
+            Some(last) if !output.status.success() => anyhow::anyhow!("{} ({})", e, last),
+            _ => e,
+        }
+    })
+}
+
+/// settings (ホストの settings/<pod>.json) をコンテナの中で読む場所
+fn container_settings_path(settings: &Path) -> String {
+    let file = settings.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+    format!("/tmp/apiary-settings-{}", file)
+}
+
+/// ペインで実行するコマンド。run はプロジェクトを同じパスにマウントし、終了時にコンテナを消す。
+/// env はキーだけ渡し、値は tmux セッションの環境から引き継ぐ (ps に値を出さない)。
+/// agent はコンテナの中で実行する claude のコマンドライン。settings があれば、run ならマウントし、
+/// exec / devcontainer なら起動前に docker cp でコンテナに入れて `--settings` で渡す
+pub fn launch_command(
+    sandbox: &Sandbox,
+    project_path: Option<&str>,
+    env: &BTreeMap<String, String>,
+    agent: &str,
+    settings: Option<&Path>,
+) -> String {
+    let mut args = Vec::new();
+    let in_container = settings.map(container_settings_path);
+    if let (Some(host), Some(path), None) = (settings, &in_container, &sandbox.image) {
+        args.extend(["docker", "cp"].map(String::from));
+        args.push(shell_quote(&host.to_string_lossy()));
+        args.push(shell_quote(&format!("{}:{}", sandbox.container, path)));
+        args.push("&&".to_string());
+    }
+    match (&sandbox.image, project_path) {
+        (None, Some(path)) if sandbox.devcontainer => {
+            args.extend(["devcontainer", "exec", "--workspace-folder"].map(String::from));
+            args.push(shell_quote(path));
+            for key in env.keys() {
+                args.push(format!("--remote-env \"{}=${}\"", key, key));
+            }
+        }
+        (Some(image), _) => {
+            args.extend(["docker", "run", "-it", "--rm", "--name"].map(String::from));
+            args.push(shell_quote(&sandbox.container));
+            args.extend(env.keys().map(|key| format!("-e {}", key)));
+            if let Some(path) = project_path {
+                args.extend(["-v".to_string(), shell_quote(&format!("{}:{}", path, path))]);
+                args.extend(["-w".to_string(), shell_quote(path)]);
+            }
+            if let (Some(host), Some(path)) = (settings, &in_container) {
+                args.extend(["-v".to_string(), shell_quote(&format!("{}:{}:ro", host.to_string_lossy(), path))]);
+            }
+            args.push(shell_quote(image));
+        }
+        (None, _) => {
+            args.extend(["docker", "exec", "-it"].map(String::from));
+            args.extend(env.keys().map(|key| format!("-e {}", key)));
+            if let Some(path) = project_path {
+                args.extend(["-w".to_string(), shell_quote(path)]);
+            }
+            args.push(shell_quote(&sandbox.container));
+        }
+    }
+    args.push(agent.to_string());
+    if let Some(path) = in_container {
+        args.extend(["--settings".to_string(), shell_quote(&path)]);
+    }
+    args.join(" ")
+}
+
+/// コンテナが起動しているか
+pub fn is_running(container: &str) -> bool {
+    Command::new("docker")
+        .args(["inspect", "-f", "{{.State.Running}}", container])
+        .output()
+        .map(|o| o.status.success() && String::from_utf8_lossy(&o.stdout).trim() == "true")
+        .unwrap_or(false)
+}
+
+/// docker が使えるか
+fn check_docker() -> Result<()> {
+    let version = Command::new("docker")
+        .args(["version", "--format", "{{.Server.Version}}"])
+        .output()
+        .context("Failed to run docker (is Docker installed?)")?;
+    if !version.status.success() {
+        anyhow::bail!("docker is not available: {}", String::from_utf8_lossy(&version.stderr).trim());
+    }
+    Ok(())
+}
+
+/// コンテナを止める (`docker run --rm` で起動したものはこれで削除もされる)
+pub fn stop(container: &str) -> Result<()> {
+    let output = Command::new("docker")
+        .args(["stop", container])
+        .output()
+        .context("Failed to run docker stop")?;
+    if !output.status.success() {
+        anyhow::bail!("docker stop {} failed: {}", container, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_command() {
+        let run = Sandbox { container: "apiary-fix-login".to_string(), image: Some("node:20".to_string()), devcontainer: false };
+        assert_eq!(
+            launch_command(&run, Some("/src/my app"), &BTreeMap::new(), "claude", None),
+            "docker run -it --rm --name 'apiary-fix-login' -v '/src/my app:/src/my app' -w '/src/my app' 'node:20' claude"
+        );
+        // 設定ファイルは読み取り専用でマウントする
+        let settings = Path::new("/home/me/.config/apiary/settings/fix-login.json");
+        assert_eq!(
+            launch_command(&run, None, &BTreeMap::new(), "claude", Some(settings)),
+            "docker run -it --rm --name 'apiary-fix-login' \
+             -v '/home/me/.config/apiary/settings/fix-login.json:/tmp/apiary-settings-fix-login.json:ro' 'node:20' \
+             claude --settings '/tmp/apiary-settings-fix-login.json'"
+        );
+
+        let exec = Sandbox { container: "dev".to_string(), image: None, devcontainer: false };
+        assert_eq!(launch_command(&exec, None, &BTreeMap::new(), "claude", None), "docker exec -it 'dev' claude");
+        // 値はコマンドラインに載せず、tmux セッションの環境から引き継ぐ
+        let env = BTreeMap::from([("API_KEY".to_string(), "secret".to_string())]);
+        assert_eq!(launch_command(&exec, None, &env, "claude", None), "docker exec -it -e API_KEY 'dev' claude");
+
+        let devcontainer = Sandbox { container: "abc123".to_string(), image: None, devcontainer: true };
+        assert_eq!(
+            launch_command(&devcontainer, Some("/src/app"), &env, "claude", None),
+            "devcontainer exec --workspace-folder '/src/app' --remote-env \"API_KEY=$API_KEY\" claude"
+        );
+        // 既存のコンテナには起動前にコピーする
+        let settings = Path::new("/cfg/settings/api.json");
+        assert_eq!(
+            launch_command(&devcontainer, Some("/src/app"), &BTreeMap::new(), "claude", Some(settings)),
+            "docker cp '/cfg/settings/api.json' 'abc123:/tmp/apiary-settings-api.json' && \
+             devcontainer exec --workspace-folder '/src/app' claude --settings '/tmp/apiary-settings-api.json'"
+        );
+    }
+
+    #[test]
+    fn test_parse_devcontainer_up() {
+        let stdout = "[1 ms] @devcontainers/cli 0.58.0\n{\"outcome\":\"success\",\"containerId\":\"abc123\",\"remoteUser\":\"node\"}\n";
+        assert_eq!(parse_devcontainer_up(stdout).unwrap(), "abc123");
+        let failed = "{\"outcome\":\"error\",\"message\":\"Command failed\"}";
+        assert!(parse_devcontainer_up(failed).unwrap_err().to_string().contains("Command failed"));
+        assert!(parse_devcontainer_up("no json").is_err());
+    }
+}
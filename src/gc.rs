@@ -0,0 +1,268 @@
+//! `apiary gc`: 溜まった一時ファイル・Dead Pod・worktree の掃除
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::ReaperConfig;
+use crate::pod::{Pod, PodStatus};
+use crate::project::ProjectStore;
+use crate::store::trash::TrashedPod;
+
+/// hooks JSONL をローテートするサイズ
+pub const HOOKS_ROTATE_BYTES: u64 = 1024 * 1024;
+
+/// 掃除した内容
+#[derive(Debug, Default)]
+pub struct GcReport {
+    pub pty_files: Vec<PathBuf>,
+    /// ローテートした hooks ファイルのサイズ (bytes)
+    pub hooks_rotated: Option<u64>,
+    pub dead_pods: Vec<String>,
+    pub worktrees: Vec<String>,
+    /// 掃除できなかったもの (理由付き)
+    pub skipped: Vec<String>,
+}
+
+impl GcReport {
+    pub fn is_empty(&self) -> bool {
+        self.pty_files.is_empty()
+            && self.hooks_rotated.is_none()
+            && self.dead_pods.is_empty()
+            && self.worktrees.is_empty()
+    }
+
+    /// 人間向けのサマリー行
+    pub fn lines(&self, dry_run: bool) -> Vec<String> {
+        let verb = |done: &str, planned: &str| if dry_run { planned.to_string() } else { done.to_string() };
+        let mut lines = Vec::new();
+        for path in &self.pty_files {
+            lines.push(format!("{} PTY stream file {}", verb("Removed", "Would remove"), path.display()));
+        }
+        if let Some(bytes) = self.hooks_rotated {
+            lines.push(format!("{} hooks log ({} KB)", verb("Rotated", "Would rotate"), bytes / 1024));
+        }
+        for name in &self.dead_pods {
+            lines.push(format!("{} dead pod '{}' (restorable with apiary restore)", verb("Dropped", "Would drop"), name));
+        }
+        for path in &self.worktrees {
+            lines.push(format!("{} worktree {}", verb("Removed", "Would remove"), path));
+        }
+        for reason in &self.skipped {
+            lines.push(format!("Skipped {}", reason));
+        }
+        lines
+    }
+}
+
+/// 生きていないペインの PTY ストリームファイル (apiary-pty-<id>.raw)
+pub fn orphan_pty_files(dir: &Path, live_panes: &HashSet<String>) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            match name.strip_prefix("apiary-pty-").and_then(|n| n.strip_suffix(".raw")) {
+                Some(id) => !live_panes.contains(&format!("%{}", id)),
+                None => false,
+            }
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// hooks JSONL が max_bytes を超えていれば <file>.1 にローテートし、元のサイズを返す
+pub fn rotate_hooks_file(path: &Path, max_bytes: u64, dry_run: bool) -> Result<Option<u64>> {
+    let size = match std::fs::metadata(path) {
+        Ok(m) => m.len(),
+        Err(_) => return Ok(None),
+    };
+    if size <= max_bytes {
+        return Ok(None);
+    }
+    if !dry_run {
+        let rotated = PathBuf::from(format!("{}.1", path.display()));
+        std::fs::rename(path, &rotated)
+            .with_context(|| format!("Failed to rotate {:?}", path))?;
+        std::fs::File::create(path).with_context(|| format!("Failed to recreate {:?}", path))?;
+    }
+    Ok(Some(size))
+}
+
+/// Dead のまま ttl を過ぎた Pod 名 (tmux サーバーごと失ったものは resurrect できるよう残す)
+pub fn expired_dead_pods(pods: &[Pod], ttl: Duration) -> Vec<String> {
+    let cutoff = Utc::now() - ttl;
+    pods.iter()
+        .filter(|p| p.status == PodStatus::Dead && !p.server_lost && p.last_activity() < cutoff)
+        .map(|p| p.name.clone())
+        .collect()
+}
+
+/// `[reaper]` で片付ける Pod 名: Dead のまま dead_after_hours、
+/// または Done のまま触られずに done_after_days が過ぎたもの (keep とピン留め、tmux サーバーごと失ったものは除く)
+pub fn stale_pods(pods: &[Pod], config: &ReaperConfig, now: DateTime<Utc>) -> Vec<String> {
+    let dead_cutoff = (config.dead_after_hours > 0).then(|| now - Duration::hours(config.dead_after_hours as i64));
+    let done_cutoff = (config.done_after_days > 0).then(|| now - Duration::days(config.done_after_days as i64));
+    pods.iter()
+        .filter(|p| !config.keeps(p))
+        .filter(|p| match p.status {
+            PodStatus::Dead => !p.server_lost && dead_cutoff.is_some_and(|c| p.last_activity() < c),
+            PodStatus::Done => done_cutoff.is_some_and(|c| p.last_activity().max(p.last_used().unwrap_or(p.created_at)) < c),
+            _ => false,
+        })
+        .map(|p| p.name.clone())
+        .collect()
+}
+
+/// drop 済み Pod のプロジェクトとして登録された git worktree のうち、
+/// 生きている Pod がもう使っていないものを削除する (未コミットの変更があれば git がスキップ)
+pub fn remove_stale_worktrees(
+    trashed: &[TrashedPod],
+    live_pods: &[Pod],
+    projects: &ProjectStore,
+    dry_run: bool,
+    report: &mut GcReport,
+) {
+    let live_projects: HashSet<&str> = live_pods.iter().filter_map(|p| p.project.as_deref()).collect();
+    let mut seen = HashSet::new();
+
+    for entry in trashed {
+        let project_name = match entry.pod.project.as_deref() {
+            Some(p) if !live_projects.contains(p) && seen.insert(p.to_string()) => p,
+            _ => continue,
+        };
+        let project = match projects.find_by_name(project_name) {
+            Ok(Some(p)) => p,
+            _ => continue,
+        };
+        let main = match main_worktree(&project.path) {
+            Some(m) => m,
+            None => continue,
+        };
+        // メインの作業ツリー自体は消さない
+        if Path::new(&main) == Path::new(&project.path) {
+            continue;
+        }
+
+        if dry_run {
+            report.worktrees.push(project.path.clone());
+            continue;
+        }
+        let output = Command::new("git")
+            .args(["-C", &main, "worktree", "remove", &project.path])
+            .output();
+        match output {
+            Ok(o) if o.status.success() => {
+                let _ = projects.unregister(&project.name);
+                report.worktrees.push(project.path.clone());
+            }
+            Ok(o) => report.skipped.push(format!(
+                "worktree {}: {}",
+                project.path,
+                String::from_utf8_lossy(&o.stderr).trim()
+            )),
+            Err(e) => report.skipped.push(format!("worktree {}: {}", project.path, e)),
+        }
+    }
+}
+
+/// path が git worktree ならメイン作業ツリーのパスを返す
+fn main_worktree(path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["-C", path, "worktree", "list", "--porcelain"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .and_then(|l| l.strip_prefix("worktree "))
+        .map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_pod(name: &str, status: PodStatus, age_hours: i64) -> Pod {
+        Pod {
+            name: name.to_string(),
+            status,
+            tmux_session: name.to_string(),
+            created_at: Utc::now() - Duration::hours(age_hours),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_orphan_pty_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["apiary-pty-1.raw", "apiary-pty-2.raw", "other.raw"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let live: HashSet<String> = ["%1".to_string()].into_iter().collect();
+
+        let orphans = orphan_pty_files(dir.path(), &live);
+        assert_eq!(orphans, vec![dir.path().join("apiary-pty-2.raw")]);
+    }
+
+    #[test]
+    fn test_rotate_hooks_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hooks.jsonl");
+        std::fs::write(&path, "x".repeat(100)).unwrap();
+
+        assert_eq!(rotate_hooks_file(&path, 1000, false).unwrap(), None);
+        assert_eq!(rotate_hooks_file(&path, 10, true).unwrap(), Some(100));
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 100);
+
+        assert_eq!(rotate_hooks_file(&path, 10, false).unwrap(), Some(100));
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+        assert!(dir.path().join("hooks.jsonl.1").exists());
+    }
+
+    #[test]
+    fn test_expired_dead_pods() {
+        let mut pods = vec![
+            make_pod("old-dead", PodStatus::Dead, 48),
+            make_pod("new-dead", PodStatus::Dead, 1),
+            make_pod("old-idle", PodStatus::Idle, 48),
+            make_pod("rebooted", PodStatus::Dead, 48),
+        ];
+        pods[3].server_lost = true;
+        assert_eq!(expired_dead_pods(&pods, Duration::hours(24)), vec!["old-dead".to_string()]);
+    }
+
+    #[test]
+    fn test_stale_pods() {
+        let mut pods = vec![
+            make_pod("old-dead", PodStatus::Dead, 48),
+            make_pod("new-dead", PodStatus::Dead, 1),
+            make_pod("old-done", PodStatus::Done, 24 * 5),
+            make_pod("touched-done", PodStatus::Done, 24 * 5),
+            make_pod("pinned-done", PodStatus::Done, 24 * 5),
+            make_pod("kept-done", PodStatus::Done, 24 * 5),
+            make_pod("old-idle", PodStatus::Idle, 24 * 5),
+        ];
+        pods[3].last_attached = Some(Utc::now() - Duration::hours(2));
+        pods[4].pinned = true;
+        pods[5].tags.push("keep".to_string());
+
+        let mut config = ReaperConfig::default();
+        assert!(stale_pods(&pods, &config, Utc::now()).is_empty());
+
+        config.dead_after_hours = 24;
+        config.done_after_days = 3;
+        config.keep = vec!["#keep".to_string()];
+        assert_eq!(stale_pods(&pods, &config, Utc::now()), vec!["old-dead", "old-done"]);
+    }
+}
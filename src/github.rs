@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::{Command, Stdio};
+
+/// GitHub issue の参照 ("owner/repo#123" / issue の URL / "#123")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueRef {
+    /// "owner/repo" (None ならカレントディレクトリのリポジトリ)
+    pub repo: Option<String>,
+    pub number: u64,
+}
+
+impl IssueRef {
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let invalid = || anyhow::anyhow!("Invalid issue reference '{}' (expected owner/repo#123, #123 or an issue URL)", input);
+
+        if let Some(rest) = input
+            .strip_prefix("https://github.com/")
+            .or_else(|| input.strip_prefix("http://github.com/"))
+        {
+            let parts: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+            return match parts.as_slice() {
+                [owner, repo, "issues", number, ..] => Ok(Self {
+                    repo: Some(format!("{}/{}", owner, repo)),
+                    number: number.parse().map_err(|_| invalid())?,
+                }),
+                _ => Err(invalid()),
+            };
+        }
+
+        let (repo, number) = match input.rsplit_once('#') {
+            Some((repo, number)) => (repo, number),
+            None => ("", input),
+        };
+        let number = number.parse().map_err(|_| invalid())?;
+        let repo = match repo {
+            "" => None,
+            r if r.split('/').count() == 2 && !r.starts_with('/') && !r.ends_with('/') => Some(r.to_string()),
+            _ => return Err(invalid()),
+        };
+        Ok(Self { repo, number })
+    }
+}
+
+impl std::fmt::Display for IssueRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.repo {
+            Some(ref repo) => write!(f, "{}#{}", repo, self.number),
+            None => write!(f, "#{}", self.number),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+impl Issue {
+    /// Pod 名: "issue-123-fix-login-redirect" (タイトルから英数字のみ、長すぎる分は切り詰め)
+    pub fn pod_name(&self) -> String {
+        let mut slug = String::new();
+        for c in self.title.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+            if slug.len() >= 32 {
+                break;
+            }
+        }
+        let slug = slug.trim_end_matches('-');
+        if slug.is_empty() {
+            format!("issue-{}", self.number)
+        } else {
+            format!("issue-{}-{}", self.number, slug)
+        }
+    }
+
+    /// Claude に渡す最初のプロンプト
+    pub fn prompt(&self, issue_ref: &IssueRef) -> String {
+        let mut prompt = format!("Work on GitHub issue {}: {}", issue_ref, self.title);
+        if !self.url.is_empty() {
+            prompt.push_str(&format!("\n{}", self.url));
+        }
+        if !self.body.trim().is_empty() {
+            prompt.push_str(&format!("\n\n{}", self.body.trim()));
+        }
+        prompt
+    }
+}
+
+/// `gh issue view` で issue を取得する
+pub fn fetch_issue(issue_ref: &IssueRef) -> Result<Issue> {
+    let number = issue_ref.number.to_string();
+    let mut args = vec!["issue", "view", number.as_str(), "--json", "number,title,body,url"];
+    if let Some(ref repo) = issue_ref.repo {
+        args.extend(["--repo", repo.as_str()]);
+    }
+    let output = Command::new("gh")
+        .args(&args)
+        .output()
+        .context("Failed to run gh (is the GitHub CLI installed?)")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "gh issue view {} failed: {}",
+            issue_ref,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("Failed to parse gh issue view output")
+}
+
+/// PR の URL (".../pull/123") から番号を取り出す
+pub fn pr_number(url: &str) -> Option<u64> {
+    url.trim_end_matches('/').rsplit_once("/pull/")?.1.parse().ok()
+}
+
+/// 認証などを端末で聞かれて止まらないよう、stdin を閉じてプロンプトを無効にした git / gh
+fn non_interactive(program: &str, dir: &str) -> Command {
+    let mut command = Command::new(program);
+    command
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GH_PROMPT_DISABLED", "1");
+    command
+}
+
+/// `dir` のブランチを push して `gh pr create --fill` で PR を作り、URL を返す。
+/// issue があれば生成された本文の末尾に "Closes <issue>" を足す。PR が既にあればその URL を返す
+pub fn create_pr(dir: &str, issue: Option<&str>) -> Result<String> {
+    let push = non_interactive("git", dir)
+        .args(["push", "-u", "origin", "HEAD"])
+        .output()
+        .context("Failed to run git push")?;
+    if !push.status.success() {
+        anyhow::bail!("git push failed: {}", String::from_utf8_lossy(&push.stderr).trim());
+    }
+
+    let output = non_interactive("gh", dir)
+        .args(["pr", "create", "--fill"])
+        .output()
+        .context("Failed to run gh (is the GitHub CLI installed?)")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // 成功時は stdout、既に PR がある場合は stderr に URL が出る
+    let url = stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|l| l.starts_with("https://") && pr_number(l).is_some());
+    let Some(url) = url else {
+        anyhow::bail!("gh pr create failed: {}", stderr.trim());
+    };
+    if let (Some(issue), true) = (issue, output.status.success()) {
+        append_closes(dir, url, issue)?;
+    }
+    Ok(url.to_string())
+}
+
+/// 作った PR の本文 (--fill でコミットから生成されたもの) の末尾に "Closes <issue>" を足す
+fn append_closes(dir: &str, url: &str, issue: &str) -> Result<()> {
+    let view = non_interactive("gh", dir)
+        .args(["pr", "view", url, "--json", "body", "--jq", ".body"])
+        .output()
+        .context("Failed to run gh pr view")?;
+    if !view.status.success() {
+        anyhow::bail!("gh pr view failed: {}", String::from_utf8_lossy(&view.stderr).trim());
+    }
+    let Some(body) = body_with_closes(&String::from_utf8_lossy(&view.stdout), issue) else {
+        return Ok(());
+    };
+    let edit = non_interactive("gh", dir)
+        .args(["pr", "edit", url, "--body", body.as_str()])
+        .output()
+        .context("Failed to run gh pr edit")?;
+    if !edit.status.success() {
+        anyhow::bail!("gh pr edit failed: {}", String::from_utf8_lossy(&edit.stderr).trim());
+    }
+    Ok(())
+}
+
+/// body の末尾に "Closes <issue>" を足したもの (既に書いてあれば None)
+fn body_with_closes(body: &str, issue: &str) -> Option<String> {
+    let closes = format!("Closes {}", issue);
+    let body = body.trim_end();
+    if body.lines().any(|l| l.trim() == closes) {
+        return None;
+    }
+    Some(if body.is_empty() { closes } else { format!("{}\n\n{}", body, closes) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(title: &str) -> Issue {
+        Issue { number: 42, title: title.to_string(), body: String::new(), url: String::new() }
+    }
+
+    #[test]
+    fn test_parse_issue_ref() {
+        let r = IssueRef::parse("Ischca/apiary#12").unwrap();
+        assert_eq!(r, IssueRef { repo: Some("Ischca/apiary".to_string()), number: 12 });
+        assert_eq!(r.to_string(), "Ischca/apiary#12");
+        let r = IssueRef::parse("https://github.com/Ischca/apiary/issues/7").unwrap();
+        assert_eq!(r, IssueRef { repo: Some("Ischca/apiary".to_string()), number: 7 });
+        assert_eq!(IssueRef::parse("#3").unwrap(), IssueRef { repo: None, number: 3 });
+        assert_eq!(IssueRef::parse("3").unwrap(), IssueRef { repo: None, number: 3 });
+        assert!(IssueRef::parse("apiary#x").is_err());
+        assert!(IssueRef::parse("a/b/c#1").is_err());
+    }
+
+    #[test]
+    fn test_pr_number() {
+        assert_eq!(pr_number("https://github.com/Ischca/apiary/pull/15"), Some(15));
+        assert_eq!(pr_number("https://github.com/Ischca/apiary/pull/15/"), Some(15));
+        assert_eq!(pr_number("https://github.com/Ischca/apiary/issues/15"), None);
+    }
+
+    #[test]
+    fn test_body_with_closes() {
+        assert_eq!(body_with_closes("Fix the redirect\n", "#42").as_deref(), Some("Fix the redirect\n\nCloses #42"));
+        assert_eq!(body_with_closes("", "#42").as_deref(), Some("Closes #42"));
+        assert_eq!(body_with_closes("Fix\n\nCloses #42\n", "#42"), None);
+    }
+
+    #[test]
+    fn test_pod_name() {
+        assert_eq!(issue("Fix login: redirect loop!").pod_name(), "issue-42-fix-login-redirect-loop");
+        assert_eq!(issue("ログインの修正").pod_name(), "issue-42");
+        assert!(issue(&"very long title ".repeat(10)).pod_name().len() <= 42);
+    }
+}
@@ -1,14 +1,15 @@
 use crate::pod::MemberStatus;
+use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::fs;
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
 
-const HOOKS_FILE: &str = "/tmp/apiary-hooks.jsonl";
+pub const HOOKS_FILE: &str = "/tmp/apiary-hooks.jsonl";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct HookEvent {
-    pub event: String,       // "tool_start", "tool_end", "permission", "error", "subagent_start", "subagent_stop"
+    pub event: String,       // "tool_start", "tool_end", "permission", "error", "stop", "subagent_start", "subagent_stop"
     pub tool: Option<String>,
     pub session: Option<String>,
     #[serde(default)]
@@ -19,6 +20,12 @@ pub struct HookEvent {
     /// Subagent のタイプ: "Explore", "Plan", "general-purpose", etc.
     #[serde(default)]
     pub agent_type: Option<String>,
+    /// イベントを出した Claude のペイン ($TMUX_PANE。Stop のみ)
+    #[serde(default)]
+    pub pane: Option<String>,
+    /// Claude Code のセッション ID (Stop のみ。~/.claude の transcript / TODO のファイル名)
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
 impl HookEvent {
@@ -29,6 +36,8 @@ impl HookEvent {
             "tool_end" => Some(MemberStatus::Working), // ツール終了後もまだ処理中
             "permission" => Some(MemberStatus::Permission),
             "error" => Some(MemberStatus::Error),
+            // 応答を終えた: まず Idle にして、次のポーリングで質問 (AwaitingInput) かを見る
+            "stop" => Some(MemberStatus::Idle),
             "subagent_start" | "subagent_stop" => Some(MemberStatus::Working),
             _ => None,
         }
@@ -45,6 +54,12 @@ pub struct HooksReceiver {
     last_position: u64,
 }
 
+impl Default for HooksReceiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HooksReceiver {
     pub fn new() -> Self {
         Self {
@@ -53,6 +68,11 @@ impl HooksReceiver {
         }
     }
 
+    /// カスタムパスで HooksReceiver を作成（テスト用）
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path, last_position: 0 }
+    }
+
     /// 初期化: 現在のファイル末尾位置を記録
     pub fn init(&mut self) {
         if let Ok(metadata) = fs::metadata(&self.path) {
@@ -108,34 +128,167 @@ impl HooksReceiver {
     }
 }
 
+/// apiary の hooks イベントを書き出すシェルコマンド
+fn hook_command(json: &str) -> String {
+    format!("echo '{}' >> {}", json, HOOKS_FILE)
+}
+
+/// Stop の hooks: 応答を終えたペインと、標準入力の JSON にあるセッション ID も書き出す
+/// (同じディレクトリで複数の Pod が動いていても transcript をペインに結びつけられる)
+fn stop_hook_command() -> String {
+    format!(
+        r#"sid=$(sed -n 's/.*"session_id" *: *"\([^"]*\)".*/\1/p' | head -n 1); echo '{{"event":"stop","pane":"'"$TMUX_PANE"'","session_id":"'"$sid"'"}}' >> {}"#,
+        HOOKS_FILE
+    )
+}
+
+/// ~/.claude/settings.json に追加する hooks 設定
+pub fn hooks_settings() -> serde_json::Value {
+    let subagent = |event: &str| {
+        hook_command(&format!(
+            r#"{{"event":"{}","agent_id":"'"$CLAUDE_AGENT_ID"'","agent_type":"'"$CLAUDE_AGENT_TYPE"'"}}"#,
+            event
+        ))
+    };
+    serde_json::json!({
+        "preToolUse": [{
+            "type": "command",
+            "command": hook_command(r#"{"event":"tool_start","tool":"$TOOL_NAME"}"#),
+        }],
+        "postToolUse": [{
+            "type": "command",
+            "command": hook_command(r#"{"event":"tool_end","tool":"$TOOL_NAME"}"#),
+        }],
+        "Stop": [{
+            "hooks": [{ "type": "command", "command": stop_hook_command() }],
+        }],
+        "SubagentStart": [{
+            "matcher": "*",
+            "hooks": [{ "type": "command", "command": subagent("subagent_start") }],
+        }],
+        "SubagentStop": [{
+            "matcher": "*",
+            "hooks": [{ "type": "command", "command": subagent("subagent_stop") }],
+        }],
+    })
+}
+
+/// 既存の settings に apiary の hooks をマージ (登録済みのイベントはスキップ)
+/// 変更があれば true を返す
+pub fn merge_hooks_settings(settings: &mut serde_json::Value) -> Result<bool> {
+    let root = settings
+        .as_object_mut()
+        .context("settings.json is not a JSON object")?;
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .context("\"hooks\" in settings.json is not a JSON object")?;
+
+    let mut changed = false;
+    if let serde_json::Value::Object(template) = hooks_settings() {
+        for (event, entries) in template {
+            let existing = hooks.entry(event.as_str()).or_insert_with(|| serde_json::json!([]));
+            let list = existing
+                .as_array_mut()
+                .with_context(|| format!("hooks.{} in settings.json is not an array", event))?;
+            if list.iter().any(|e| e.to_string().contains(HOOKS_FILE)) {
+                continue;
+            }
+            if let serde_json::Value::Array(new_entries) = entries {
+                list.extend(new_entries);
+                changed = true;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// ~/.claude/settings.json に hooks をインストールし、書き込んだパスを返す
+/// 変更前の内容は settings.json.bak に退避する
+pub fn install_hooks() -> Result<PathBuf> {
+    let path = dirs::home_dir()
+        .context("Failed to determine home directory")?
+        .join(".claude")
+        .join("settings.json");
+
+    let mut settings = if path.exists() {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        if content.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))?
+        }
+    } else {
+        serde_json::json!({})
+    };
+
+    if !merge_hooks_settings(&mut settings)? {
+        return Ok(path);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    if path.exists() {
+        fs::copy(&path, path.with_extension("json.bak"))
+            .with_context(|| format!("Failed to back up {:?}", path))?;
+    }
+    let content = serde_json::to_string_pretty(&settings).context("Failed to serialize settings")?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(path)
+}
+
 /// hooks 設定テンプレートを出力
 pub fn print_hooks_setup() {
     println!("Add the following to ~/.claude/settings.json to enable hooks integration:");
     println!();
-    println!(r#"{{
-  "hooks": {{
-    "preToolUse": [{{
-      "type": "command",
-      "command": "echo '{{\"event\":\"tool_start\",\"tool\":\"$TOOL_NAME\"}}' >> /tmp/apiary-hooks.jsonl"
-    }}],
-    "postToolUse": [{{
-      "type": "command",
-      "command": "echo '{{\"event\":\"tool_end\",\"tool\":\"$TOOL_NAME\"}}' >> /tmp/apiary-hooks.jsonl"
-    }}],
-    "SubagentStart": [{{
-      "matcher": "*",
-      "hooks": [{{
-        "type": "command",
-        "command": "echo '{{\"event\":\"subagent_start\",\"agent_id\":\"'\"$CLAUDE_AGENT_ID\"'\",\"agent_type\":\"'\"$CLAUDE_AGENT_TYPE\"'\"}}' >> /tmp/apiary-hooks.jsonl"
-      }}]
-    }}],
-    "SubagentStop": [{{
-      "matcher": "*",
-      "hooks": [{{
-        "type": "command",
-        "command": "echo '{{\"event\":\"subagent_stop\",\"agent_id\":\"'\"$CLAUDE_AGENT_ID\"'\",\"agent_type\":\"'\"$CLAUDE_AGENT_TYPE\"'\"}}' >> /tmp/apiary-hooks.jsonl"
-      }}]
-    }}]
-  }}
-}}"#);
+    let settings = serde_json::json!({ "hooks": hooks_settings() });
+    println!("{}", serde_json::to_string_pretty(&settings).unwrap_or_default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_hooks_into_empty_settings() {
+        let mut settings = serde_json::json!({ "model": "opus" });
+        assert!(merge_hooks_settings(&mut settings).unwrap());
+        assert_eq!(settings["model"], "opus");
+        assert_eq!(settings["hooks"]["preToolUse"].as_array().unwrap().len(), 1);
+        assert!(settings["hooks"]["SubagentStop"][0]["hooks"][0]["command"]
+            .as_str()
+            .unwrap()
+            .contains("subagent_stop"));
+    }
+
+    #[test]
+    fn test_merge_hooks_is_idempotent_and_keeps_user_hooks() {
+        let mut settings = serde_json::json!({
+            "hooks": { "preToolUse": [{ "type": "command", "command": "my-hook" }] }
+        });
+        assert!(merge_hooks_settings(&mut settings).unwrap());
+        assert!(!merge_hooks_settings(&mut settings).unwrap());
+        let pre = settings["hooks"]["preToolUse"].as_array().unwrap();
+        assert_eq!(pre.len(), 2);
+        assert_eq!(pre[0]["command"], "my-hook");
+    }
+
+    #[test]
+    fn test_merge_hooks_rejects_non_object() {
+        let mut settings = serde_json::json!([1, 2]);
+        assert!(merge_hooks_settings(&mut settings).is_err());
+    }
+
+    #[test]
+    fn test_stop_hook_carries_pane_and_session() {
+        let command = stop_hook_command();
+        assert!(command.contains(r#""pane":"'"$TMUX_PANE"'""#));
+        let event: HookEvent = serde_json::from_str(r#"{"event":"stop","pane":"%3","session_id":"abc"}"#).unwrap();
+        assert_eq!(event.pane.as_deref(), Some("%3"));
+        assert_eq!(event.session_id.as_deref(), Some("abc"));
+        assert_eq!(event.inferred_status(), Some(MemberStatus::Idle));
+    }
 }
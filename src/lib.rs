@@ -1,8 +1,21 @@
 pub mod config;
+pub mod cost;
+pub mod docker;
+pub mod gc;
+pub mod github;
 pub mod hooks;
+pub mod lifecycle;
+pub mod logs;
 pub mod notify;
 pub mod pod;
 pub mod project;
+pub mod record;
+pub mod redact;
+pub mod report;
+pub mod serve;
+pub mod setup;
 pub mod store;
 pub mod tmux;
+pub mod todos;
 pub mod tui;
+pub mod watch;
@@ -0,0 +1,96 @@
+use std::process::{Command, Stdio};
+
+use crate::config::LifecycleConfig;
+use crate::pod::{Pod, PodStatus};
+
+/// `[lifecycle]` のコマンドを起動する Pod のイベント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// Pod を作成・取り込んだ
+    Create,
+    /// Permission 待ちになった
+    Permission,
+    /// 作業が終わった (Working → Idle / Done)
+    Done,
+    /// Pod を drop した
+    Drop,
+}
+
+impl LifecycleEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            LifecycleEvent::Create => "create",
+            LifecycleEvent::Permission => "permission",
+            LifecycleEvent::Done => "done",
+            LifecycleEvent::Drop => "drop",
+        }
+    }
+
+    /// 状態遷移に対応するイベント (なければ None)
+    pub fn from_transition(before: &PodStatus, after: &PodStatus) -> Option<Self> {
+        match (before, after) {
+            (b, PodStatus::Permission) if *b != PodStatus::Permission => Some(LifecycleEvent::Permission),
+            (PodStatus::Working, PodStatus::Idle | PodStatus::Done) => Some(LifecycleEvent::Done),
+            _ => None,
+        }
+    }
+}
+
+/// コマンドに渡す環境変数 ($EVENT, $POD, $PROJECT, $GROUP, $SESSION, $STATUS, $PREV_STATUS)
+pub fn hook_env(event: LifecycleEvent, pod: &Pod, previous: Option<&PodStatus>) -> Vec<(&'static str, String)> {
+    vec![
+        ("EVENT", event.name().to_string()),
+        ("POD", pod.name.clone()),
+        ("PROJECT", pod.project.clone().unwrap_or_default()),
+        ("GROUP", pod.group.clone().unwrap_or_default()),
+        ("SESSION", pod.tmux_session.clone()),
+        ("STATUS", pod.status.label().to_lowercase()),
+        ("PREV_STATUS", previous.map(|s| s.label().to_lowercase()).unwrap_or_default()),
+    ]
+}
+
+/// イベントに設定されたコマンドを `sh -c` で非同期に実行する (ベストエフォート、終了は待たない)
+pub fn fire(config: &LifecycleConfig, event: LifecycleEvent, pod: &Pod, previous: Option<&PodStatus>) {
+    let Some(command) = config.command(event) else {
+        return;
+    };
+    let spawned = Command::new("sh")
+        .args(["-c", command])
+        .envs(hook_env(event, pod, previous))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        // ゾンビにならないよう別スレッドで回収する
+        Ok(mut child) => {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+        Err(e) => tracing::warn!("lifecycle on_{} failed to start: {}", event.name(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_transition() {
+        assert_eq!(
+            LifecycleEvent::from_transition(&PodStatus::Working, &PodStatus::Permission),
+            Some(LifecycleEvent::Permission)
+        );
+        assert_eq!(
+            LifecycleEvent::from_transition(&PodStatus::Working, &PodStatus::Idle),
+            Some(LifecycleEvent::Done)
+        );
+        assert_eq!(
+            LifecycleEvent::from_transition(&PodStatus::Working, &PodStatus::Done),
+            Some(LifecycleEvent::Done)
+        );
+        assert_eq!(LifecycleEvent::from_transition(&PodStatus::Idle, &PodStatus::Working), None);
+        assert_eq!(LifecycleEvent::from_transition(&PodStatus::Permission, &PodStatus::Idle), None);
+    }
+}
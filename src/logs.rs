@@ -0,0 +1,174 @@
+//! ペイン出力のログ (`[logs]` / `apiary logs`)。
+//! tmux pipe-pane で `apiary log-sink` を起動し、~/.local/share/apiary/logs/<pod>/<role>.log に追記する。
+//! max_size_mb を超えたら <role>.log.1, .2 ... にずらし、keep 世代より古いものは消す
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::LogsConfig;
+use crate::redact::{LineRedactor, Redactor};
+
+/// ログの置き場所 (~/.local/share/apiary/logs)
+pub fn logs_dir() -> Result<PathBuf> {
+    Ok(dirs::data_local_dir()
+        .context("Failed to determine data directory")?
+        .join("apiary")
+        .join("logs"))
+}
+
+/// メンバーのログファイル
+pub fn log_path(pod: &str, role: &str) -> Result<PathBuf> {
+    Ok(logs_dir()?.join(pod.replace('/', "-")).join(format!("{}.log", role.replace('/', "-"))))
+}
+
+/// pipe-pane に渡す `apiary log-sink <path>`
+pub fn sink_command(pod: &str, role: &str) -> Result<String> {
+    let exe = std::env::current_exe().context("Failed to locate the apiary executable")?;
+    Ok(format!(
+        "{} log-sink {}",
+        crate::tmux::shell_quote(&exe.to_string_lossy()),
+        crate::tmux::shell_quote(&log_path(pod, role)?.to_string_lossy())
+    ))
+}
+
+/// 大きくなったらずらしていく追記専用のログ
+pub struct RotatingLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingLog {
+    pub fn open(path: &Path, config: &LogsConfig) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+        let file = Self::open_file(path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes: config.max_size_mb.max(1) * 1024 * 1024,
+            keep: config.keep,
+            file,
+            size,
+        })
+    }
+
+    fn open_file(path: &Path) -> Result<File> {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log: {:?}", path))
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> Result<()> {
+        if self.size > 0 && self.size + data.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(data)?;
+        self.file.flush()?;
+        self.size += data.len() as u64;
+        Ok(())
+    }
+
+    /// <role>.log → .1 → .2 ... (keep を超える分は消す)
+    fn rotate(&mut self) -> Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        if self.keep == 0 {
+            let _ = std::fs::remove_file(&self.path);
+        } else {
+            let _ = std::fs::remove_file(rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = std::fs::rename(rotated(n), rotated(n + 1));
+            }
+            std::fs::rename(&self.path, rotated(1))
+                .with_context(|| format!("Failed to rotate log: {:?}", self.path))?;
+        }
+        self.file = Self::open_file(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// input が閉じるまで読み、伏せ字にしてログに書く。tee があれば読んだバイトをそのまま流す
+/// (Detail のストリームや録画と pipe-pane を分け合うため)。ログには行がそろってから伏せて書く
+pub fn ship(mut input: impl Read, log: &mut RotatingLog, redactor: &Redactor, mut tee: Option<&mut dyn Write>) -> Result<()> {
+    let mut buf = [0u8; 16384];
+    let mut pending = Vec::new();
+    let mut lines = LineRedactor::new(redactor);
+    loop {
+        let n = input.read(&mut buf).context("Failed to read pane output")?;
+        if n == 0 {
+            break;
+        }
+        if let Some(out) = tee.as_mut() {
+            // 読み手が先に終わってもログは続ける
+            if out.write_all(&buf[..n]).and_then(|_| out.flush()).is_err() {
+                tee = None;
+            }
+        }
+        pending.extend_from_slice(&buf[..n]);
+        if let Some(data) = lines.push(&crate::record::take_utf8(&mut pending)) {
+            log.write(data.as_bytes())?;
+        }
+    }
+    lines.push(&String::from_utf8_lossy(&pending));
+    if let Some(data) = lines.finish() {
+        log.write(data.as_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_generations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api").join("claude.log");
+        let config = LogsConfig { enabled: true, max_size_mb: 1, keep: 2 };
+        let mut log = RotatingLog::open(&path, &config).unwrap();
+        let chunk = vec![b'x'; 700 * 1024];
+        for _ in 0..4 {
+            log.write(&chunk).unwrap();
+        }
+        let len = |p: &str| std::fs::metadata(dir.path().join("api").join(p)).map(|m| m.len()).ok();
+        assert_eq!(len("claude.log"), Some(chunk.len() as u64));
+        assert_eq!(len("claude.log.1"), Some(chunk.len() as u64));
+        assert_eq!(len("claude.log.2"), Some(chunk.len() as u64));
+        assert_eq!(len("claude.log.3"), None);
+
+        // 開き直しても既存のサイズから数える
+        let mut log = RotatingLog::open(&path, &config).unwrap();
+        log.write(&chunk).unwrap();
+        assert_eq!(len("claude.log"), Some(chunk.len() as u64));
+    }
+
+    #[test]
+    fn test_ship_redacts_and_tees() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("claude.log");
+        let mut log = RotatingLog::open(&path, &LogsConfig::default()).unwrap();
+        let input: &[u8] = b"\x1b[1mok\x1b[0m GITHUB_TOKEN=ghp_abcdef\r\n";
+        let mut tee = Vec::new();
+        // 1 バイトずつ届いてもトークンを伏せる
+        struct Bytes<'a>(&'a [u8]);
+        impl Read for Bytes<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.0.len().min(1);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        ship(Bytes(input), &mut log, &Redactor::new(&Default::default()), Some(&mut tee)).unwrap();
+        assert_eq!(tee, input);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "\x1b[1mok\x1b[0m GITHUB_TOKEN=[REDACTED]\r\n");
+    }
+}
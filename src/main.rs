@@ -1,43 +1,223 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use crossterm::{
     cursor,
-    event::{self, Event, EnableBracketedPaste, DisableBracketedPaste},
+    event::{self, Event, EnableBracketedPaste, DisableBracketedPaste, EnableMouseCapture, DisableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::prelude::*;
-use std::io;
+use std::io::{self, Write};
 use std::time::{Duration, Instant};
 
+use apiary::pod::{parse_tags, status_exit_code, IconSet, OnDone, PodPriority, PodStatus};
+use apiary::docker::SandboxSpec;
+use apiary::github;
 use apiary::project;
+use apiary::setup;
+use apiary::serve;
 use apiary::store::PodStore;
 use apiary::tmux;
-use apiary::tui::app::App;
-use apiary::tui::handler::{handle_key_event, handle_paste_event, Action};
+use apiary::tui::app::{App, CreateOptions};
+use apiary::tui::handler::{handle_key_event, handle_mouse_event, handle_paste_event, Action};
+use apiary::tui::popup::{self, Popup, PopupAction};
+use apiary::tui::replay::{self, ReplayAction};
 use apiary::tui::ui::draw;
 
 const TICK_RATE_MS: u64 = 250;
 
+/// `apiary wait` のポーリング間隔
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 #[derive(Parser)]
 #[command(name = "apiary", bin_name = "apiary", version, about = "Claude Code Multi-Session Manager")]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Plain ASCII output without emoji or colors (also enabled by NO_COLOR)
+    #[arg(long, global = true)]
+    plain: bool,
+}
+
+/// `apiary wait --for` で待つ状態
+#[derive(Clone, Copy, ValueEnum)]
+enum WaitTarget {
+    /// Finished its task
+    Done,
+    /// Not working (idle, done or waiting for an answer)
+    Idle,
+    /// Waiting for a permission answer
+    Permission,
+    /// Asked a question and waiting for an answer
+    Input,
+}
+
+/// `apiary create --priority` で指定する優先度
+#[derive(Clone, Copy, ValueEnum)]
+enum PriorityArg {
+    /// Poll more often, sort first and notify when work finishes
+    High,
+    Normal,
+    /// Poll less often, sort last and only notify for permission prompts
+    Low,
+}
+
+impl From<PriorityArg> for PodPriority {
+    fn from(arg: PriorityArg) -> Self {
+        match arg {
+            PriorityArg::High => PodPriority::High,
+            PriorityArg::Normal => PodPriority::Normal,
+            PriorityArg::Low => PodPriority::Low,
+        }
+    }
+}
+
+/// `apiary project set --worktree` で指定する worktree の使い方
+#[derive(Clone, Copy, ValueEnum)]
+enum WorktreeArg {
+    /// Pods work in the project directory itself
+    Shared,
+    /// Each pod gets its own git worktree and branch next to the project
+    PerPod,
+}
+
+impl From<WorktreeArg> for project::WorktreePolicy {
+    fn from(arg: WorktreeArg) -> Self {
+        match arg {
+            WorktreeArg::Shared => project::WorktreePolicy::Shared,
+            WorktreeArg::PerPod => project::WorktreePolicy::PerPod,
+        }
+    }
+}
+
+impl WaitTarget {
+    fn reached(self, status: &PodStatus) -> bool {
+        match self {
+            WaitTarget::Done => *status == PodStatus::Done,
+            WaitTarget::Idle => matches!(status, PodStatus::Idle | PodStatus::Done | PodStatus::AwaitingInput),
+            WaitTarget::Permission => *status == PodStatus::Permission,
+            WaitTarget::Input => *status == PodStatus::AwaitingInput,
+        }
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Create a new pod with a tmux session and Claude Code
     Create {
-        /// Pod name
-        name: String,
+        /// Pod name (defaults to one derived from the issue with --from-issue)
+        #[arg(required_unless_present = "from_issue")]
+        name: Option<String>,
         /// Project name or path (defaults to cwd)
         #[arg(long, alias = "worktree")]
         project: Option<String>,
         /// Group name (optional)
         #[arg(long)]
         group: Option<String>,
+        /// Tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Shell command to run in the pod's directory once it finishes its task
+        /// (on-done actions fire only while the apiary TUI is running)
+        #[arg(long, value_name = "COMMAND", conflicts_with_all = ["on_done_commit", "on_done_prompt"])]
+        on_done: Option<String>,
+        /// Ask Claude to commit its changes once it finishes its task
+        #[arg(long, conflicts_with = "on_done_prompt")]
+        on_done_commit: bool,
+        /// Follow-up prompt to send once the pod finishes its task
+        #[arg(long, value_name = "PROMPT")]
+        on_done_prompt: Option<String>,
+        /// Start from a GitHub issue (owner/repo#123, #123 or its URL): the issue
+        /// title names the pod and its body becomes the initial prompt (needs gh)
+        #[arg(long, value_name = "ISSUE")]
+        from_issue: Option<String>,
+        /// Run Claude in a new Docker container of this image, with the project
+        /// mounted at the same path (the container is stopped when the pod is dropped)
+        #[arg(long, value_name = "IMAGE", conflicts_with = "docker_exec")]
+        docker: Option<String>,
+        /// Run Claude inside an already running Docker container (docker exec)
+        #[arg(long, value_name = "CONTAINER", conflicts_with = "devcontainer")]
+        docker_exec: Option<String>,
+        /// Run Claude inside the project's dev container (devcontainer up / exec)
+        #[arg(long, conflicts_with = "docker")]
+        devcontainer: bool,
+        /// Send "continue" automatically once a usage limit resets (see [rate_limit])
+        #[arg(long)]
+        auto_resume: bool,
+        /// Polling, notification and ordering priority
+        #[arg(long, value_enum, default_value = "normal")]
+        priority: PriorityArg,
+        /// Apply a [templates.<name>] section from the config (env, ...)
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Environment variable for the pod's tmux session, e.g. CLAUDE_CONFIG_DIR=~/.claude-work
+        /// (repeatable; overrides the template's env)
+        #[arg(long = "env", value_name = "KEY=VAL", value_parser = apiary::pod::parse_env_var)]
+        env: Vec<(String, String)>,
+        /// Model to start Claude with (claude --model; overrides the template's model)
+        #[arg(long)]
+        model: Option<String>,
+    },
+    /// Print a Markdown summary of pod activity (for standup notes)
+    Report {
+        /// Start of the period: today, yesterday, 12h, 3d, 1w or YYYY-MM-DD
+        #[arg(long, default_value = "yesterday")]
+        since: String,
+    },
+    /// Export working-time statistics
+    Stats {
+        #[command(subcommand)]
+        action: StatsAction,
+    },
+    /// Work with Chat mode conversations
+    Chat {
+        #[command(subcommand)]
+        action: ChatAction,
+    },
+    /// Merge the members of one pod into another as a team pod
+    /// (panes from another session are moved into the target's session)
+    Merge {
+        /// Pod that receives the members
+        target: String,
+        /// Pod whose members are moved (removed afterwards)
+        source: String,
+    },
+    /// Push a pod's branch and open a pull request with `gh pr create --fill`
+    Pr {
+        /// Pod name
+        pod: String,
+    },
+    /// Resume a pod automatically once its usage limit resets
+    AutoResume {
+        /// Pod name
+        pod: String,
+        /// Never resume this pod automatically
+        #[arg(long, conflicts_with = "default")]
+        off: bool,
+        /// Follow `auto_resume` under [rate_limit] in the config again
+        #[arg(long)]
+        default: bool,
+    },
+    /// Kill Claude in a pod's pane (tmux respawn-pane) and start it again in the same pane,
+    /// keeping the tmux session and worktree
+    Respawn {
+        /// Pod name
+        pod: String,
+        /// Member to restart (defaults to the lead)
+        #[arg(long)]
+        member: Option<String>,
+        /// Continue the previous conversation (claude --continue)
+        #[arg(long = "continue")]
+        resume: bool,
+    },
+    /// Set a pod's accent color, used for its card border and in the switcher
+    Color {
+        /// Pod name
+        pod: String,
+        /// A color name (red, orange, yellow, lime, green, teal, cyan, blue, purple,
+        /// magenta, pink, brown) or #rrggbb; omit to go back to the color derived from the name
+        color: Option<String>,
     },
     /// Adopt an existing tmux session as a pod
     Adopt {
@@ -49,21 +229,284 @@ enum Commands {
         /// Group name (optional)
         #[arg(long)]
         group: Option<String>,
+        /// Tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
     },
     /// Drop a pod and kill its tmux session
     Drop {
         /// Pod name
         name: String,
     },
+    /// Restore a dropped pod from the trash (lists the trash without a name)
+    Restore {
+        /// Pod name
+        name: Option<String>,
+    },
+    /// Recreate the tmux sessions of dead pods in their project directories and start
+    /// Claude again (without names: every pod lost when the tmux server stopped)
+    Resurrect {
+        /// Pod names
+        pods: Vec<String>,
+        /// Continue the previous conversation (claude --continue)
+        #[arg(long = "continue")]
+        resume: bool,
+    },
     /// List all pods
-    List,
-    /// Show status summary of all pods
-    Status,
+    List {
+        /// Show project, group and note for each pod
+        #[arg(long)]
+        long: bool,
+    },
+    /// Show status summary of all pods. Exit code: 0 = all idle/done,
+    /// 2 = pods working or rate limited, 3 = permissions or questions pending, 4 = errors/dead pods
+    Status {
+        /// Print nothing; only set the exit code
+        #[arg(long, short)]
+        quiet: bool,
+    },
+    /// Clean up stale PTY files, hooks log, dead pods and worktrees
+    Gc {
+        /// Only report what would be cleaned
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run the first-run setup wizard again
+    Setup,
+    /// Block until a pod reaches a state (exit 0), or fail on timeout / dead pod (exit 1)
+    Wait {
+        /// Pod name
+        pod: String,
+        /// State to wait for
+        #[arg(long = "for", value_enum)]
+        target: WaitTarget,
+        /// Give up after this many seconds (default: wait forever)
+        #[arg(long)]
+        timeout: Option<u64>,
+    },
+    /// Run a shell command in a pod's working directory (temporary split pane)
+    Exec {
+        /// Pod name
+        pod: String,
+        /// Type the command into this member's pane instead of opening a split
+        #[arg(long, conflicts_with_all = ["wait", "timeout"])]
+        member: Option<String>,
+        /// Wait for the command, print its output and exit with its status
+        #[arg(long)]
+        wait: bool,
+        /// With --wait, give up after this many seconds
+        #[arg(long, requires = "wait")]
+        timeout: Option<u64>,
+        /// Command to run (after --)
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Minimal pod picker for a tmux popup (bind-key ... display-popup -E apiary popup)
+    Popup,
+    /// Add or remove the tmux key bindings that go back from a pod's session
+    /// to the session running apiary
+    TmuxIntegration {
+        #[command(subcommand)]
+        action: TmuxIntegrationAction,
+    },
+    /// Record a pod's pane output with timestamps to an asciicast v2 file
+    /// (runs in the background until `apiary record <pod> --stop`)
+    Record {
+        /// Pod name
+        pod: String,
+        /// Member to record (defaults to the lead)
+        #[arg(long)]
+        member: Option<String>,
+        /// Output file (defaults to ~/.config/apiary/recordings/<pod>-<time>.cast)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+        /// Stop the running recording
+        #[arg(long, conflicts_with = "output")]
+        stop: bool,
+    },
+    /// Play back an asciicast recording in the terminal
+    Replay {
+        /// Recording (.cast)
+        file: std::path::PathBuf,
+        /// Playback speed
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// Only print the recording's absolute path (e.g. for asciinema play)
+        #[arg(long)]
+        print: bool,
+    },
+    /// Write pane output from stdin to a recording (started by `apiary record` via tmux pipe-pane)
+    #[command(hide = true)]
+    RecordSink {
+        output: std::path::PathBuf,
+    },
+    /// Keep appending each member's pane output to ~/.local/share/apiary/logs/<pod>/<role>.log
+    /// (rotated by [logs] max_size_mb / keep) while apiary is running
+    Logs {
+        /// Pod name
+        pod: String,
+        /// Never log this pod
+        #[arg(long, conflicts_with = "default")]
+        off: bool,
+        /// Follow `enabled` under [logs] in the config again
+        #[arg(long)]
+        default: bool,
+    },
+    /// Switch the model of a running pod (sends /model to the lead pane)
+    Model {
+        /// Pod name
+        pod: String,
+        /// Model name or alias (opus, sonnet, haiku, claude-...)
+        model: String,
+    },
+    /// Append pane output from stdin to a rotated log (started by apiary via tmux pipe-pane)
+    #[command(hide = true)]
+    LogSink {
+        output: std::path::PathBuf,
+        /// Also copy the output to stdout (to share the pipe with the Detail view or a recording)
+        #[arg(long)]
+        tee: bool,
+    },
+    /// Serve pod status over HTTP (JSON at /api/pods, live updates at /api/events)
+    Serve {
+        /// Address to listen on (use 0.0.0.0 to reach it from a phone on the LAN)
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Port to listen on
+        #[arg(long, default_value_t = 7878)]
+        port: u16,
+        /// Also serve a dashboard page at / with live status, pane previews and approve/deny buttons
+        #[arg(long)]
+        web: bool,
+        /// Token required as ?token=<TOKEN> (or Authorization: Bearer) on every request (default: a random one, printed in the URL)
+        #[arg(long)]
+        token: Option<String>,
+    },
     /// Manage project registry
     Project {
         #[command(subcommand)]
         action: ProjectAction,
     },
+    /// Manage pod groups
+    Group {
+        #[command(subcommand)]
+        action: GroupAction,
+    },
+    /// Manage pod members
+    Member {
+        #[command(subcommand)]
+        action: MemberAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum MemberAction {
+    /// Add a tmux pane (%12 or session:window.pane) as a member of a pod
+    Add {
+        /// Pod name
+        pod: String,
+        /// Pane id (%12) or target (session:window.pane)
+        pane: String,
+        /// Member role (defaults to member-N)
+        #[arg(long)]
+        role: Option<String>,
+    },
+    /// Rename a member (a child pod named <parent>/<old> becomes <parent>/<new>)
+    Rename {
+        /// Pod name
+        pod: String,
+        /// Current member name
+        old: String,
+        /// New member name
+        new: String,
+    },
+    /// Promote a member of a team pod into its own pod (sharing the session)
+    Split {
+        /// Pod name
+        pod: String,
+        /// Member role
+        role: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupAction {
+    /// List groups and their pods
+    List,
+    /// Add pods to a group (creating it)
+    Add {
+        /// Group name
+        group: String,
+        /// Pod names
+        #[arg(required = true)]
+        pods: Vec<String>,
+    },
+    /// Rename a group
+    Rename {
+        /// Current group name
+        old: String,
+        /// New group name
+        new: String,
+    },
+    /// Move a pod to another group (ungroups it when no group is given)
+    Move {
+        /// Pod name
+        pod: String,
+        /// Target group name
+        group: Option<String>,
+    },
+    /// Ungroup all pods of a group (pods are kept)
+    Dissolve {
+        /// Group name
+        group: String,
+    },
+    /// Drop every pod of a group and kill their sessions
+    Drop {
+        /// Group name
+        group: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TmuxIntegrationAction {
+    /// Bind prefix+<key> (and prefix+d outside apiary's session) to switch back to apiary,
+    /// via ~/.config/apiary/tmux.conf sourced from your tmux.conf
+    Install {
+        /// Key pressed after the tmux prefix
+        #[arg(long, default_value = "A")]
+        key: String,
+    },
+    /// Remove the bindings and the source-file line again
+    Uninstall {
+        /// Key given to install
+        #[arg(long, default_value = "A")]
+        key: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsAction {
+    /// Export one row per pod per day (working and wall-clock seconds)
+    Export {
+        /// Write CSV to stdout
+        #[arg(long)]
+        csv: bool,
+        /// Start of the period: today, yesterday, 12h, 3d, 1w or YYYY-MM-DD
+        #[arg(long, default_value = "30d")]
+        since: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChatAction {
+    /// Write a pod's chat history (with timestamps and senders) as Markdown
+    Export {
+        /// Pod name
+        pod: String,
+        /// Output file (stdout if omitted)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -83,13 +526,50 @@ enum ProjectAction {
         /// Project name
         name: String,
     },
+    /// Set defaults for pods created in a project (the template and explicit create flags win)
+    Set {
+        /// Project name
+        name: String,
+        /// Group for new pods (instead of the project name)
+        #[arg(long)]
+        group: Option<String>,
+        /// [templates.<name>] section to apply
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Model to start Claude with
+        #[arg(long)]
+        model: Option<String>,
+        /// Share the project directory or give each pod its own git worktree
+        #[arg(long, value_enum)]
+        worktree: Option<WorktreeArg>,
+        /// Environment variable for the pod's tmux session (repeatable)
+        #[arg(long = "env", value_name = "KEY=VAL", value_parser = apiary::pod::parse_env_var)]
+        env: Vec<(String, String)>,
+        /// Drop the current defaults before applying the given ones
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Find git repositories under directories and register them (skips names already registered)
+    Scan {
+        /// Directories to search (defaults to scan_roots under [projects])
+        roots: Vec<String>,
+        /// How many directories deep to look (defaults to scan_depth under [projects])
+        #[arg(long)]
+        depth: Option<usize>,
+        /// Only print what would be registered
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    // https://no-color.org/: 空でない NO_COLOR があれば色・絵文字なし
+    let plain = cli.plain || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
 
     // ログ初期化
     tracing_subscriber::fmt()
+        .with_ansi(!plain)
         .with_env_filter(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive("apiary=info".parse().unwrap()),
@@ -97,6 +577,11 @@ fn main() -> Result<()> {
         .with_writer(std::io::stderr)
         .init();
 
+    // 録画の再生は tmux なしでも動く
+    if let Some(Commands::Replay { file, speed, print }) = &cli.command {
+        return run_replay(file, *speed, *print);
+    }
+
     // tmux チェック
     if !tmux::Tmux::is_available() {
         eprintln!("Error: tmux is not installed or not in PATH.");
@@ -114,70 +599,437 @@ fn main() -> Result<()> {
     }
 
     match cli.command {
-        Some(cmd) => run_cli(cmd),
+        Some(Commands::Popup) => run_popup(),
+        Some(Commands::TmuxIntegration { action }) => run_tmux_integration(action),
+        Some(Commands::RecordSink { output }) => run_record_sink(&output),
+        Some(Commands::LogSink { output, tee }) => run_log_sink(&output, tee),
+        Some(cmd) => {
+            // 後片付け (ジョブ待ち・保存) を済ませてから終了コードを返す
+            let code = run_cli(cmd, plain)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+            Ok(())
+        }
         None => run_tui(),
     }
 }
 
-fn run_cli(cmd: Commands) -> Result<()> {
+/// サブコマンドを実行し、プロセスの終了コードを返す
+fn run_cli(cmd: Commands, plain: bool) -> Result<i32> {
     let store = PodStore::new()?;
     let mut app = App::new(store)?;
+    let arrow = if plain { "->" } else { "→" };
+    let icons = if plain { IconSet::Ascii } else { app.config.ui.icons };
+    let mut exit_code = 0;
 
     match cmd {
-        Commands::Create { name, project, group } => {
-            app.create_pod(&name, project.as_deref(), group.as_deref(), None)?;
+        Commands::Create { name, project, group, mut tags, on_done, on_done_commit, on_done_prompt, from_issue, docker, docker_exec, devcontainer, auto_resume, priority, template, env, model } => {
+            let issue = match from_issue {
+                Some(ref input) => {
+                    let issue_ref = github::IssueRef::parse(input)?;
+                    let issue = github::fetch_issue(&issue_ref)?;
+                    tags.push(format!("issue-{}", issue.number));
+                    Some((issue_ref, issue))
+                }
+                None => None,
+            };
+            let name = match (name, &issue) {
+                (Some(name), _) => name,
+                (None, Some((_, issue))) => issue.pod_name(),
+                (None, None) => unreachable!("clap requires a name without --from-issue"),
+            };
+            let prompt = issue.as_ref().map(|(issue_ref, issue)| issue.prompt(issue_ref));
+            let sandboxed = docker.is_some() || docker_exec.is_some() || devcontainer;
+            let sandbox = match (docker, docker_exec, devcontainer) {
+                (Some(image), _, _) => Some(SandboxSpec::Run(image)),
+                (_, Some(container), _) => Some(SandboxSpec::Exec(container)),
+                (_, _, true) => Some(SandboxSpec::Devcontainer),
+                _ => None,
+            };
+            let options = CreateOptions { prompt, sandbox, template, env, model };
+            app.create_pod_with(&name, project.as_deref(), group.as_deref(), &options)?;
+            if !tags.is_empty() {
+                app.set_tags(&name, parse_tags(&tags.join(",")))?;
+            }
+            if let Some((ref issue_ref, _)) = issue {
+                app.set_issue(&name, Some(issue_ref.to_string()))?;
+            }
+            let action = match (on_done, on_done_commit, on_done_prompt) {
+                (Some(cmd), _, _) => Some(OnDone::Run(cmd)),
+                (_, true, _) => Some(OnDone::Commit),
+                (_, _, Some(text)) => Some(OnDone::Prompt(text)),
+                _ => None,
+            };
+            if action.is_some() {
+                app.set_on_done(&name, action)?;
+            }
+            if auto_resume {
+                app.set_auto_resume(&name, Some(true))?;
+            }
+            let priority = PodPriority::from(priority);
+            if priority != PodPriority::Normal {
+                app.set_priority(&name, priority)?;
+            }
             println!("Pod '{}' created", name);
+            if let Some((ref issue_ref, _)) = issue {
+                println!("When it's done, open a PR that closes {} with: apiary pr {}", issue_ref, name);
+            }
+            if !sandboxed {
+                let project_path = app
+                    .state
+                    .pods
+                    .iter()
+                    .find(|p| p.name == name)
+                    .and_then(|p| p.project.as_deref())
+                    .and_then(|p| app.project_store.find_by_name(p).ok().flatten())
+                    .map(|p| p.path);
+                if project_path.is_some_and(|p| apiary::docker::has_devcontainer(&p)) {
+                    println!("This project has a dev container; pass --devcontainer to run Claude inside it");
+                }
+            }
         }
-        Commands::Adopt { session, name, group } => {
+        Commands::Adopt { session, name, group, tags } => {
             app.adopt_session(&session, name.as_deref(), group.as_deref())?;
+            if !tags.is_empty() {
+                app.set_tags(name.as_deref().unwrap_or(&session), parse_tags(&tags.join(",")))?;
+            }
             println!("Session '{}' adopted as pod", session);
         }
         Commands::Drop { name } => {
             app.drop_pod(&name)?;
-            println!("Pod '{}' dropped", name);
+            println!("Pod '{}' dropped (apiary restore {} to undo)", name, name);
+        }
+        Commands::Restore { name: Some(name) } => {
+            app.restore_pod(&name)?;
+            println!("Pod '{}' restored", name);
+        }
+        Commands::Restore { name: None } => {
+            let trashed = app.trash.list()?;
+            if trashed.is_empty() {
+                println!("Trash is empty");
+            }
+            for t in &trashed {
+                println!(
+                    "  {} (dropped {}, project: {})",
+                    t.pod.name,
+                    t.dropped_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                    t.pod.project.as_deref().unwrap_or("-"),
+                );
+            }
+        }
+        Commands::Resurrect { pods, resume } => {
+            let results = if pods.is_empty() {
+                app.resurrect_lost_pods(resume)
+            } else {
+                pods.into_iter()
+                    .map(|name| {
+                        let result = app.resurrect_pod(&name, resume);
+                        (name, result)
+                    })
+                    .collect()
+            };
+            if results.is_empty() {
+                println!("No pods lost with the tmux server");
+            }
+            let mut failed = false;
+            for (name, result) in results {
+                match result {
+                    Ok(()) => println!("Pod '{}' resurrected", name),
+                    Err(e) => {
+                        eprintln!("Pod '{}': {}", name, e);
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                exit_code = 1;
+            }
         }
-        Commands::List => {
+        Commands::List { long } => {
             app.refresh_pod_states();
             if app.state.pods.is_empty() {
                 println!("No pods");
             } else {
                 for pod in &app.state.pods {
+                    let tags: String = pod.tags.iter().map(|t| format!(" #{}", t)).collect();
                     println!(
-                        "{} {} ({}, {} members, {})",
-                        pod.status_icon(),
+                        "{} {} ({}, {} members, {}){}",
+                        pod.status_icon(icons),
                         pod.name,
                         format!("{:?}", pod.pod_type).to_lowercase(),
                         pod.members.len(),
                         pod.elapsed_time(),
+                        tags,
                     );
+                    if long {
+                        println!(
+                            "    project: {}  group: {}",
+                            pod.project.as_deref().unwrap_or("-"),
+                            pod.group.as_deref().unwrap_or("-"),
+                        );
+                        if !pod.note.is_empty() {
+                            println!("    note: {}", pod.note);
+                        }
+                        if let Some(ref action) = pod.on_done {
+                            println!("    on-done: {}", action.describe());
+                        }
+                        if let Some(ref issue) = pod.issue {
+                            println!("    issue: {}", issue);
+                        }
+                        if let Some(ref url) = pod.pr_url {
+                            println!("    pr: {}", url);
+                        }
+                        if let Some(ref sandbox) = pod.sandbox {
+                            println!("    container: {}", sandbox.describe());
+                        }
+                        if let Some(enabled) = pod.auto_resume {
+                            println!("    auto-resume: {}", if enabled { "on" } else { "off" });
+                        }
+                        if let Some(ref template) = pod.template {
+                            println!("    template: {}", template);
+                        }
+                        if let Some(ref model) = pod.model {
+                            println!("    model: {}", model);
+                        }
+                        if !pod.env.is_empty() {
+                            println!("    env: {}", pod.env.keys().cloned().collect::<Vec<_>>().join(", "));
+                        }
+                        if let Some(enabled) = pod.logs {
+                            println!("    logs: {}", if enabled { "on" } else { "off" });
+                        }
+                        if pod.priority != PodPriority::Normal {
+                            println!("    priority: {}", pod.priority.label());
+                        }
+                        if pod.server_lost {
+                            println!("    lost with the tmux server (apiary resurrect {})", pod.name);
+                        }
+                        let ago = |t: Option<chrono::DateTime<chrono::Utc>>| {
+                            t.map_or("never".to_string(), |t| format!("{} ago", apiary::pod::format_elapsed(t)))
+                        };
+                        println!(
+                            "    attached: {}  input: {}  status changed: {}",
+                            ago(pod.last_attached),
+                            ago(pod.last_input),
+                            ago(Some(pod.last_activity())),
+                        );
+                    }
                 }
             }
         }
-        Commands::Status => {
+        Commands::Status { quiet } => {
             app.refresh_pod_states();
-            let (total, warnings, members) = app.state.pods_summary();
-            println!(
-                "Pods: {} | Warnings: {} | Members: {}",
-                total, warnings, members
-            );
-            for pod in &app.state.pods {
+            exit_code = status_exit_code(&app.state.pods);
+            if !quiet {
+                let (total, warnings, members) = app.state.pods_summary();
                 println!(
-                    "  {} {} [{:?}] - {} members",
-                    pod.status_icon(),
-                    pod.name,
-                    pod.status,
-                    pod.members.len(),
+                    "Pods: {} | Warnings: {} | Members: {}",
+                    total, warnings, members
                 );
-                for member in &pod.members {
+                for pod in &app.state.pods {
                     println!(
-                        "    {} {} ({})",
-                        member.status_icon(),
-                        member.role,
-                        member.elapsed(),
+                        "  {} {} [{:?}] - {} members",
+                        pod.status_icon(icons),
+                        pod.name,
+                        pod.status,
+                        pod.members.len(),
                     );
+                    for member in &pod.members {
+                        println!(
+                            "    {} {} ({})",
+                            member.status_icon(icons),
+                            member.role,
+                            member.elapsed(),
+                        );
+                    }
                 }
             }
         }
+        Commands::Report { since } => {
+            let since = apiary::report::parse_since(&since, chrono::Local::now())?;
+            let events = app.events.load_since(since)?;
+            let report = apiary::report::Report::build(&events, since, chrono::Utc::now());
+            print!("{}", report.to_markdown());
+        }
+        Commands::Stats { action } => match action {
+            StatsAction::Export { csv, since } => {
+                if !csv {
+                    anyhow::bail!("Specify an export format (--csv)");
+                }
+                let since = apiary::report::parse_since(&since, chrono::Local::now())?;
+                let events = app.events.load_since(since)?;
+                let rows = apiary::report::daily_rows(&events, &app.state.pods, since, chrono::Utc::now())?;
+                print!("{}", apiary::report::to_csv(&rows));
+            }
+        },
+        Commands::Chat { action } => match action {
+            ChatAction::Export { pod, output: Some(path) } => {
+                let count = app.export_chat(&pod, &path)?;
+                println!("Exported {} messages to {}", count, path.display());
+            }
+            ChatAction::Export { pod, output: None } => {
+                if !app.state.chat_history.iter().any(|m| m.pod == pod) {
+                    anyhow::bail!("No chat history for pod '{}'", pod);
+                }
+                print!("{}", apiary::store::chat::transcript_markdown(&pod, &app.state.chat_history));
+            }
+        },
+        Commands::Merge { target, source } => {
+            app.merge_pods(&target, &source)?;
+            println!("Pod '{}' merged into '{}'", source, target);
+        }
+        Commands::Pr { pod } => {
+            let url = app.create_pr(&pod)?;
+            println!("{}", url);
+        }
+        Commands::AutoResume { pod, off, default } => {
+            let enabled = if default { None } else { Some(!off) };
+            app.set_auto_resume(&pod, enabled)?;
+            match enabled {
+                Some(true) => println!("Pod '{}' will resume automatically after a usage limit resets", pod),
+                Some(false) => println!("Pod '{}' will not resume automatically", pod),
+                None => println!("Pod '{}' follows [rate_limit] auto_resume again", pod),
+            }
+        }
+        Commands::Respawn { pod, member, resume } => {
+            let pane = app.respawn_agent(&pod, member.as_deref(), resume)?;
+            println!("Restarted Claude in '{}' ({})", pod, pane);
+        }
+        Commands::Color { pod, color } => {
+            app.set_accent(&pod, color.as_deref())?;
+            match color {
+                Some(color) => println!("Pod '{}' is now {}", pod, color),
+                None => println!("Pod '{}' uses the color derived from its name", pod),
+            }
+        }
+        Commands::Gc { dry_run } => {
+            let report = app.gc(dry_run)?;
+            if report.is_empty() && report.skipped.is_empty() {
+                println!("Nothing to clean");
+            }
+            for line in report.lines(dry_run) {
+                println!("{}", line);
+            }
+        }
+        Commands::Setup => {
+            setup::run_wizard(&app.project_store)?;
+        }
+        Commands::Popup | Commands::TmuxIntegration { .. } | Commands::RecordSink { .. } | Commands::LogSink { .. } | Commands::Replay { .. } => unreachable!("handled in main"),
+        Commands::Record { pod, member, output, stop: false } => {
+            let (pane, path) = app.start_recording(&pod, member.as_deref(), output.as_deref())?;
+            println!("Recording '{}' ({}) to {}", pod, pane, path.display());
+            println!("Stop with: apiary record {} --stop", pod);
+        }
+        Commands::Record { pod, member, stop: true, .. } => {
+            let pane = app.stop_recording(&pod, member.as_deref())?;
+            println!("Stopped recording '{}' ({})", pod, pane);
+        }
+        Commands::Logs { pod, off, default } => {
+            let enabled = if default { None } else { Some(!off) };
+            app.set_logs(&pod, enabled)?;
+            let logging = app.state.pods.iter().find(|p| p.name == pod).is_some_and(|p| app.logs_enabled(p));
+            if logging {
+                println!("Logging '{}' to {}", pod, apiary::logs::logs_dir()?.join(&pod).display());
+            } else {
+                println!("Pod '{}' is not logged", pod);
+            }
+        }
+        Commands::Model { pod, model } => {
+            app.switch_model(&pod, &model)?;
+            println!("Sent /model {} to '{}'", model, pod);
+        }
+        Commands::Serve { bind, port, web, token } => {
+            let token = match token {
+                Some(token) => token,
+                None => serve::generate_token()?,
+            };
+            serve::run(&mut app, &serve::ServeOptions { bind, port, web, token })?;
+        }
+        Commands::Exec { pod, member, wait, timeout, command } => {
+            let command = command.join(" ");
+            if let Some(member) = member {
+                let pane = app.exec_in_member(&pod, &member, &command)?;
+                println!("Sent to {}/{} ({})", pod, member, pane);
+            } else if let Some((output, code)) = app.exec_in_split(&pod, &command, wait, timeout.map(Duration::from_secs))? {
+                print!("{}", output);
+                exit_code = code;
+            } else {
+                println!("Running in a split pane of '{}'", pod);
+            }
+        }
+        Commands::Wait { pod, target, timeout } => {
+            let started = Instant::now();
+            exit_code = loop {
+                app.refresh_pod_states();
+                let status = app.state.pods.iter()
+                    .find(|p| p.name == pod)
+                    .map(|p| p.status.clone())
+                    .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod))?;
+                if target.reached(&status) {
+                    println!("Pod '{}' is {}", pod, status.label());
+                    break 0;
+                }
+                if status == PodStatus::Dead {
+                    eprintln!("Pod '{}' is dead", pod);
+                    break 1;
+                }
+                if timeout.is_some_and(|t| started.elapsed() >= Duration::from_secs(t)) {
+                    eprintln!("Timed out waiting for '{}' (still {})", pod, status.label());
+                    break 1;
+                }
+                std::thread::sleep(WAIT_POLL_INTERVAL);
+            };
+        }
+        Commands::Member { action } => match action {
+            MemberAction::Add { pod, pane, role } => {
+                let role = app.add_member(&pod, &pane, role.as_deref())?;
+                println!("Added {} to '{}' as {}", pane, pod, role);
+            }
+            MemberAction::Rename { pod, old, new } => match app.rename_member(&pod, &old, &new)? {
+                Some(child) => println!("Renamed {} to {} (pod is now '{}')", old, new, child),
+                None => println!("Renamed {} to {}", old, new),
+            },
+            MemberAction::Split { pod, role } => {
+                let child = app.split_member(&pod, &role)?;
+                println!("Pod '{}' created from {} of '{}'", child, role, pod);
+            }
+        },
+        Commands::Group { action } => match action {
+            GroupAction::List => {
+                let mut groups: Vec<&str> = app.state.pods.iter().filter_map(|p| p.group.as_deref()).collect();
+                groups.sort();
+                groups.dedup();
+                if groups.is_empty() {
+                    println!("No groups");
+                }
+                for group in groups {
+                    println!("  {}: {}", group, app.pods_in_group(group).join(", "));
+                }
+            }
+            GroupAction::Add { group, pods } => {
+                let count = app.add_to_group(&group, &pods)?;
+                println!("{} pods added to group '{}'", count, group);
+            }
+            GroupAction::Rename { old, new } => {
+                let count = app.rename_group(&old, &new)?;
+                println!("Group '{}' renamed to '{}' ({} pods)", old, new, count);
+            }
+            GroupAction::Move { pod, group } => {
+                app.set_group(&pod, group.clone())?;
+                match group {
+                    Some(group) => println!("Pod '{}' moved to group '{}'", pod, group),
+                    None => println!("Pod '{}' ungrouped", pod),
+                }
+            }
+            GroupAction::Dissolve { group } => {
+                let count = app.dissolve_group(&group)?;
+                println!("Group '{}' dissolved ({} pods kept)", group, count);
+            }
+            GroupAction::Drop { group } => {
+                let names = app.drop_group(&group)?;
+                println!("Group '{}' dropped: {} (apiary restore <name> to undo)", group, names.join(", "));
+            }
+        },
         Commands::Project { action } => {
             let project_store = project::ProjectStore::new()?;
             match action {
@@ -187,21 +1039,21 @@ fn run_cli(cmd: Commands) -> Result<()> {
                         println!("No projects registered");
                     } else {
                         for p in &projects {
-                            println!("  {} → {}", p.name, p.path);
+                            println!("  {} {} {}", p.name, arrow, p.path);
+                            if !p.defaults.is_empty() {
+                                println!("    defaults: {}", p.defaults.describe());
+                            }
                         }
                     }
                 }
                 ProjectAction::Add { path, name } => {
                     if let Some(name) = name {
-                        let project = project::Project {
-                            name: name.clone(),
-                            path: path.clone(),
-                        };
+                        let project = project::Project::new(name.clone(), path.clone());
                         project_store.register(&project)?;
-                        println!("Project '{}' registered → {}", name, path);
+                        println!("Project '{}' registered {} {}", name, arrow, path);
                     } else {
                         let project = project::resolve_project(&project_store, &path)?;
-                        println!("Project '{}' registered → {}", project.name, project.path);
+                        println!("Project '{}' registered {} {}", project.name, arrow, project.path);
                     }
                 }
                 ProjectAction::Remove { name } => {
@@ -211,10 +1063,74 @@ fn run_cli(cmd: Commands) -> Result<()> {
                         println!("Project '{}' not found", name);
                     }
                 }
+                ProjectAction::Set { name, group, template, model, worktree, env, clear } => {
+                    let current = project_store
+                        .find_by_name(&name)?
+                        .ok_or_else(|| anyhow::anyhow!("Project '{}' not found", name))?;
+                    if let Some(ref template) = template {
+                        if !app.config.templates.contains_key(template) {
+                            anyhow::bail!("Template '{}' not found (define it under [templates.{}])", template, template);
+                        }
+                    }
+                    let mut defaults = if clear { Default::default() } else { current.defaults };
+                    defaults.group = group.or(defaults.group);
+                    defaults.template = template.or(defaults.template);
+                    defaults.model = model.or(defaults.model);
+                    defaults.worktree = worktree.map(Into::into).unwrap_or(defaults.worktree);
+                    defaults.env.extend(env);
+                    project_store.set_defaults(&name, defaults.clone())?;
+                    if defaults.is_empty() {
+                        println!("Project '{}' has no defaults", name);
+                    } else {
+                        println!("Project '{}' defaults: {}", name, defaults.describe());
+                    }
+                }
+                ProjectAction::Scan { roots, depth, dry_run } => {
+                    let roots = if roots.is_empty() { app.config.projects.scan_roots.clone() } else { roots };
+                    if roots.is_empty() {
+                        anyhow::bail!("No directories to scan: pass one or set scan_roots under [projects]");
+                    }
+                    // 相対パスは resolve_project と同じくカレントディレクトリから (登録するパスを絶対にする)
+                    let cwd = std::env::current_dir()?;
+                    let roots: Vec<_> = roots.iter().map(|r| cwd.join(project::expand_home(r))).collect();
+                    let depth = depth.unwrap_or(app.config.projects.scan_depth);
+                    let discovered = project::discover_projects(&project_store, &roots, depth)?;
+                    for p in &discovered {
+                        if !dry_run {
+                            project_store.register(p)?;
+                        }
+                        println!("  {} {} {}", p.name, arrow, p.path);
+                    }
+                    match (discovered.len(), dry_run) {
+                        (0, _) => println!("No new repositories found"),
+                        (n, true) => println!("{} project(s) would be registered (dry run)", n),
+                        (n, false) => println!("{} project(s) registered", n),
+                    }
+                }
             }
         }
     }
+    app.finish_jobs();
     app.save()?;
+    Ok(exit_code)
+}
+
+fn run_tmux_integration(action: TmuxIntegrationAction) -> Result<()> {
+    match action {
+        TmuxIntegrationAction::Install { key } => {
+            let (conf, tmux_conf) = tmux::integration::install(&key)?;
+            println!("Wrote {}", conf.display());
+            println!("Sourced from {}", tmux_conf.display());
+            println!("In a pod's session, prefix+{} or prefix+d goes back to apiary", key);
+        }
+        TmuxIntegrationAction::Uninstall { key } => {
+            if tmux::integration::uninstall(&key)? {
+                println!("Removed the apiary tmux integration");
+            } else {
+                println!("The apiary tmux integration is not installed");
+            }
+        }
+    }
     Ok(())
 }
 
@@ -222,11 +1138,25 @@ fn run_tui() -> Result<()> {
     // PodStore 初期化
     let store = PodStore::new()?;
 
-    // App 初期化
+    // 初回起動ならセットアップウィザード
+    let project_store = project::ProjectStore::new()?;
+    if setup::needs_setup(&store, &project_store) {
+        setup::run_wizard(&project_store)?;
+    }
+
+    // App 初期化 (前回終了時のモードを復元)
     let mut app = App::new(store)?;
+    let saved_mode = app.ui_state_store.load().mode;
+    app.restore_mode(saved_mode);
 
-    // Terminal 初期化
+    // tmux の中ならこのセッションを tmux-integration の戻り先にし、Pod のセッションに目印を付ける
+    let apiary_session = tmux::integration::mark_apiary_session();
+    app.mark_pod_sessions();
+
+    // Terminal 初期化 (パニック・エラー時もガードで復元する)
+    install_panic_hook();
     enable_raw_mode()?;
+    let guard = TerminalGuard;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
@@ -236,11 +1166,15 @@ fn run_tui() -> Result<()> {
     let result = run_app(&mut terminal, &mut app);
 
     // Terminal 復元
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableBracketedPaste)?;
-    terminal.show_cursor()?;
+    drop(guard);
+    if let Some(session) = &apiary_session {
+        tmux::integration::unmark_apiary_session(session);
+    }
 
-    // 状態を保存
+    // drop したコンテナの停止などを待ってから状態を保存 (UI 状態は Detail の window サイズを戻す前に取る)
+    app.finish_jobs();
+    let _ = app.save_ui_state();
+    app.restore_detail_window_size();
     let _ = app.save();
 
     if let Err(e) = result {
@@ -251,10 +1185,142 @@ fn run_tui() -> Result<()> {
     Ok(())
 }
 
+/// `apiary popup`: display-popup 内で Pod を選んで switch-client / Permission 応答して閉じる
+fn run_popup() -> Result<()> {
+    let store = PodStore::new()?;
+    let mut app = App::new(store)?;
+    app.refresh_pod_states();
+
+    install_panic_hook();
+    enable_raw_mode()?;
+    let guard = TerminalGuard;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let mut popup = Popup::default();
+    let mut last_refresh = Instant::now();
+    let mut switch_to = None;
+    terminal.draw(|frame| popup::draw(frame, &app, &popup))?;
+    loop {
+        if event::poll(Duration::from_millis(TICK_RATE_MS))? {
+            if let Event::Key(key) = event::read()? {
+                match popup.handle_key(&mut app, key) {
+                    PopupAction::Close => break,
+                    PopupAction::Switch(session) => {
+                        switch_to = Some(session);
+                        break;
+                    }
+                    PopupAction::Render => {
+                        terminal.draw(|frame| popup::draw(frame, &app, &popup))?;
+                    }
+                    PopupAction::None => {}
+                }
+            }
+        }
+        if last_refresh.elapsed() >= Duration::from_secs(2) {
+            last_refresh = Instant::now();
+            app.refresh_pod_states();
+            terminal.draw(|frame| popup::draw(frame, &app, &popup))?;
+        }
+    }
+
+    drop(guard);
+    if let Some(session) = &switch_to {
+        let _ = app.mark_attached(session);
+    }
+    let _ = app.save();
+    if let Some(session) = switch_to {
+        tmux::Tmux::attach_session(&session)?;
+    }
+    Ok(())
+}
+
+/// pipe-pane から渡されたペイン出力を録画ファイルに追記する (pipe が閉じると終わる)
+fn run_record_sink(output: &std::path::Path) -> Result<()> {
+    let file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(output)
+        .with_context(|| format!("Failed to open recording: {:?}", output))?;
+    let config = apiary::config::Config::load().unwrap_or_default();
+    let redactor = apiary::redact::Redactor::new(&config.redaction);
+    apiary::record::record_stream(io::stdin().lock(), io::BufWriter::new(file), Instant::now(), &redactor)
+}
+
+/// pipe-pane から届いたペイン出力をローテーションするログに追記する
+fn run_log_sink(output: &std::path::Path, tee: bool) -> Result<()> {
+    let config = apiary::config::Config::load().unwrap_or_default();
+    let redactor = apiary::redact::Redactor::new(&config.redaction);
+    let mut log = apiary::logs::RotatingLog::open(output, &config.logs)?;
+    let mut stdout = io::stdout().lock();
+    let tee: Option<&mut dyn Write> = if tee { Some(&mut stdout) } else { None };
+    apiary::logs::ship(io::stdin().lock(), &mut log, &redactor, tee)
+}
+
+/// 録画を端末で再生する
+fn run_replay(file: &std::path::Path, speed: f64, print: bool) -> Result<()> {
+    let cast = apiary::record::Cast::load(file)?;
+    if print {
+        println!("{}", std::fs::canonicalize(file)?.display());
+        return Ok(());
+    }
+    let mut player = apiary::record::Player::new(cast, speed);
+
+    install_panic_hook();
+    enable_raw_mode()?;
+    let guard = TerminalGuard;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let frame = Duration::from_millis(50);
+    let mut last = Instant::now();
+    loop {
+        if event::poll(frame)? {
+            if let Event::Key(key) = event::read()? {
+                if replay::handle_key(&mut player, key) == ReplayAction::Close {
+                    break;
+                }
+            }
+        }
+        player.advance(last.elapsed().as_secs_f64());
+        last = Instant::now();
+        terminal.draw(|f| replay::draw(f, &player))?;
+    }
+
+    drop(guard);
+    Ok(())
+}
+
+/// 端末を通常状態 (cooked mode / メイン画面 / カーソル表示) に戻す。何度呼んでもよい
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableBracketedPaste, DisableMouseCapture, cursor::Show);
+}
+
+/// スコープを抜けるとき (エラーやパニックの巻き戻しを含む) に端末を復元する
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// パニック時はメッセージを出す前に端末を復元する (raw mode のままだと読めず `reset` が必要になる)
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+}
+
 fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<()> {
     let tick_rate = Duration::from_millis(TICK_RATE_MS);
     let mut last_tick = Instant::now();
     let mut last_refresh = Instant::now();
+    let mut mouse_captured = false;
 
     // 初回描画
     terminal.draw(|frame| draw(frame, app))?;
@@ -264,6 +1330,17 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
     terminal.draw(|frame| draw(frame, app))?;
 
     loop {
+        // マウスは Chat モードの間だけキャプチャする (ほかのモードでは端末の範囲選択を邪魔しない)
+        let want_mouse = app.state.mode == apiary::pod::Mode::Chat;
+        if want_mouse != mouse_captured {
+            if want_mouse {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            } else {
+                execute!(terminal.backend_mut(), DisableMouseCapture)?;
+            }
+            mouse_captured = want_mouse;
+        }
+
         // イベント待ち (tick_rate でタイムアウト)
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
@@ -281,13 +1358,25 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
                     Action::Render => {
                         terminal.draw(|frame| draw(frame, app))?;
                     }
-                    Action::AttachTmux(session) => {
+                    Action::AttachPopup(session) if std::env::var("TMUX").is_ok() => {
+                        if !tmux::Tmux::session_exists(&session) {
+                            app.state.status_message = Some(format!("Session '{}' not found", session));
+                        } else if let Err(e) = tmux::Tmux::attach_popup(&session) {
+                            app.state.status_message = Some(format!("Popup error: {}", e));
+                        } else {
+                            let _ = app.mark_attached(&session);
+                        }
+                        terminal.draw(|frame| draw(frame, app))?;
+                    }
+                    // tmux の外ではポップアップを出せないので普通にアタッチする
+                    Action::AttachTmux(session) | Action::AttachPopup(session) => {
                         if !tmux::Tmux::session_exists(&session) {
                             app.state.status_message = Some(format!("Session '{}' not found", session));
                             terminal.draw(|frame| draw(frame, app))?;
                             continue;
                         }
 
+                        let _ = app.mark_attached(&session);
                         let is_inside_tmux = std::env::var("TMUX").is_ok();
 
                         if !is_inside_tmux {
@@ -322,9 +1411,30 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
                             terminal.draw(|frame| draw(frame, app))?;
                         }
                     }
+                    Action::EditInput => {
+                        // TUI 一時停止 → エディタ → 復帰
+                        disable_raw_mode()?;
+                        execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableBracketedPaste, cursor::Show)?;
+
+                        let result = app.edit_input_in_editor();
+
+                        enable_raw_mode()?;
+                        execute!(terminal.backend_mut(), EnterAlternateScreen, cursor::Hide, EnableBracketedPaste)?;
+                        terminal.clear()?;
+
+                        if let Err(e) = result {
+                            app.state.status_message = Some(format!("Error: {}", e));
+                        }
+                        terminal.draw(|frame| draw(frame, app))?;
+                    }
                     Action::None => {}
                 }
                 }
+                Event::Mouse(mouse) => {
+                    if let Action::Render = handle_mouse_event(app, mouse) {
+                        terminal.draw(|frame| draw(frame, app))?;
+                    }
+                }
                 Event::Paste(text) => {
                     handle_paste_event(app, &text);
                     terminal.draw(|frame| draw(frame, app))?;
@@ -337,10 +1447,13 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
 
-            // グリッドカラム数を更新
+            // グリッドカラム数と狭幅レイアウトを更新
             let size = terminal.size()?;
-            let grid_width = (size.width as f32 * 0.65) as usize;
-            app.state.grid_columns = (grid_width / 23).max(1);
+            app.state.narrow = apiary::tui::ui::is_narrow(size.width);
+            let grid_width = apiary::tui::ui::pods_pane_width(app, size.width) as usize;
+            let (card_width, _) = app.config.ui.card_size();
+            let cell_width = (card_width + apiary::tui::ui::CARD_GAP) as usize + 2;
+            app.state.grid_columns = (grid_width / cell_width).max(1);
 
             // Detail モード: PTY ストリームから drain して再描画
             if app.state.mode == apiary::pod::Mode::Detail {
@@ -363,6 +1476,13 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
         if last_refresh.elapsed() >= Duration::from_millis(500) {
             last_refresh = Instant::now();
             app.selective_refresh();
+            app.handle_status_changes();
+            if app.take_bell() {
+                // tmux のベルフラグや端末のバッジを立てる
+                let backend = terminal.backend_mut();
+                backend.write_all(b"\x07")?;
+                Write::flush(backend)?;
+            }
             terminal.draw(|frame| draw(frame, app))?;
         }
     }
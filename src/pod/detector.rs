@@ -1,7 +1,15 @@
+use std::io::{Read as _, Write as _};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
 use regex::Regex;
 
+use crate::config::DetectionConfig;
+use crate::pod::ContextPressure;
 use crate::pod::MemberStatus;
 use crate::pod::SubAgent;
+use crate::pod::TaskProgress;
 
 /// 許可リクエストの詳細
 #[derive(Debug, Clone)]
@@ -34,6 +42,14 @@ const ERROR_PATTERNS: &[&str] = &[
     r"(?i)thread\s+'.*'\s+panicked",
 ];
 
+/// RateLimited 検出パターン (利用上限・API のレート制限)
+const RATE_LIMIT_PATTERNS: &[&str] = &[
+    r"(?i)usage limit reached",
+    r"(?i)\blimit reached\b.*\bresets?\b",
+    r"(?i)limit will reset at",
+    r"(?i)rate[ _]limit(ed|_error| exceeded)",
+];
+
 /// Done 検出パターン
 const DONE_PATTERNS: &[&str] = &[
     r"(?i)session ended",
@@ -65,6 +81,7 @@ pub fn detect_member_status_with_config(
     extra_permission: &[String],
     extra_error: &[String],
     extra_idle: &[String],
+    extra_rate_limit: &[String],
 ) -> MemberStatus {
     let trimmed = output.trim();
     if trimmed.is_empty() {
@@ -81,35 +98,115 @@ pub fn detect_member_status_with_config(
         return MemberStatus::Permission;
     }
 
-    // 2. Error 検出
+    // 2. RateLimited 検出 ("API Error: 429 ... rate_limit_error" を Error にしないよう先に見る)
+    if matches_any(&tail_text, RATE_LIMIT_PATTERNS) || matches_any_dynamic(&tail_text, extra_rate_limit) {
+        return MemberStatus::RateLimited;
+    }
+
+    // 3. Error 検出
     if matches_any(&tail_text, ERROR_PATTERNS) || matches_any_dynamic(&tail_text, extra_error) {
         return MemberStatus::Error;
     }
 
-    // 3. Done 検出
+    // 4. Done 検出
     if matches_any(&tail_text, DONE_PATTERNS) {
         return MemberStatus::Done;
     }
 
-    // 4. Idle 検出 (最終行がプロンプト)
+    // 5. Idle 検出 (最終行がプロンプト)。直前の応答が質問なら返事待ち
     if let Some(last) = tail.last() {
         if matches_any(last, IDLE_PATTERNS) || matches_any_dynamic(last, extra_idle) {
-            return MemberStatus::Idle;
+            return idle_or_awaiting(&tail[..tail.len() - 1]);
         }
     }
 
-    // 5. デフォルト: Working
+    // 6. デフォルト: Working
     MemberStatus::Working
 }
 
+/// 設定 (`[detection]`) に従ってメンバーの状態を検出する。
+///
+/// external は `detector_command` の直近の結果 (ポーリングを止めないよう、スクリプトは呼び出し側が裏で回す)。
+/// 状態名が返ってきていればそれを採用し、何も出力しない・失敗した・タイムアウトした場合は組み込みのパターン検出にフォールバックする。
+/// ただし組み込み検出が Permission / AwaitingInput / Error を見つけていれば、外部の結果はそれより優先度が高いときだけ使う
+/// (スクリプトが working と答えても承認待ちを隠さない)。
+pub fn detect_member_status_for(output: &str, config: &DetectionConfig, external: Option<MemberStatus>) -> MemberStatus {
+    let builtin = detect_member_status_with_config(
+        output,
+        &config.permission_patterns,
+        &config.error_patterns,
+        &config.idle_patterns,
+        &config.rate_limit_patterns,
+    );
+    match external.filter(|_| config.detector_command.is_some()) {
+        Some(status) if builtin.priority() >= MemberStatus::Error.priority() && status.priority() <= builtin.priority() => builtin,
+        Some(status) => status,
+        None => builtin,
+    }
+}
+
+/// 外部検出スクリプトを `sh -c` で実行し、stdout の最初の単語を状態として解釈する。
+/// stdin にはペインの内容、環境変数 APIARY_PANE には tmux のペイン ID を渡す。
+pub fn run_external_detector(command: &str, output: &str, pane: &str, timeout: Duration) -> Option<MemberStatus> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .env("APIARY_PANE", pane)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| tracing::warn!("detector_command failed to start: {}", e))
+        .ok()?;
+
+    // スクリプトが stdin を読まずに終わっても詰まらないよう、書き込みは別スレッドで
+    let mut stdin = child.stdin.take()?;
+    let input = output.to_string();
+    std::thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+            _ => {
+                tracing::warn!("detector_command timed out after {:?}", timeout);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let mut stdout = String::new();
+    child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+    parse_status_word(&stdout)
+}
+
+/// 外部検出スクリプトの出力 ("working" など、大文字小文字は問わない) を状態に変換する
+pub fn parse_status_word(text: &str) -> Option<MemberStatus> {
+    match text.split_whitespace().next()?.to_lowercase().as_str() {
+        "permission" => Some(MemberStatus::Permission),
+        "error" => Some(MemberStatus::Error),
+        "rate_limited" | "ratelimited" | "rate-limited" => Some(MemberStatus::RateLimited),
+        "awaiting_input" | "awaitinginput" | "awaiting-input" | "question" => Some(MemberStatus::AwaitingInput),
+        "working" => Some(MemberStatus::Working),
+        "idle" => Some(MemberStatus::Idle),
+        "done" => Some(MemberStatus::Done),
+        _ => None,
+    }
+}
+
 /// capture-pane の出力からメンバーの状態を検出する。
 ///
 /// 検出優先度:
 ///   1. Permission (最優先) -- 許可プロンプトが表示されている
-///   2. Error              -- エラーメッセージが出ている
-///   3. Done               -- 空出力やセッション終了
-///   4. Idle               -- プロンプト待ち状態
-///   5. Working (デフォルト)
+///   2. RateLimited        -- 利用上限・レート制限に達した
+///   3. Error              -- エラーメッセージが出ている
+///   4. Done               -- 空出力やセッション終了
+///   5. Idle               -- プロンプト待ち状態 (直前の応答が質問なら AwaitingInput)
+///   6. Working (デフォルト)
 pub fn detect_member_status(output: &str) -> MemberStatus {
     // 空出力 = プロセスが終了している可能性が高い
     let trimmed = output.trim();
@@ -128,24 +225,29 @@ pub fn detect_member_status(output: &str) -> MemberStatus {
         return MemberStatus::Permission;
     }
 
-    // --- 2. Error 検出 ---
+    // --- 2. RateLimited 検出 ---
+    if matches_any(&tail_text, RATE_LIMIT_PATTERNS) {
+        return MemberStatus::RateLimited;
+    }
+
+    // --- 3. Error 検出 ---
     if matches_any(&tail_text, ERROR_PATTERNS) {
         return MemberStatus::Error;
     }
 
-    // --- 3. Done 検出 ---
+    // --- 4. Done 検出 ---
     if matches_any(&tail_text, DONE_PATTERNS) {
         return MemberStatus::Done;
     }
 
-    // --- 4. Idle 検出 (最終行がプロンプト) ---
+    // --- 5. Idle 検出 (最終行がプロンプト、直前の応答が質問なら AwaitingInput) ---
     if let Some(last) = tail.last() {
         if matches_any(last, IDLE_PATTERNS) {
-            return MemberStatus::Idle;
+            return idle_or_awaiting(&tail[..tail.len() - 1]);
         }
     }
 
-    // --- 5. デフォルト: Working ---
+    // --- 6. デフォルト: Working ---
     MemberStatus::Working
 }
 
@@ -209,10 +311,13 @@ pub fn rollup_status(statuses: &[MemberStatus]) -> MemberStatus {
 /// capture-pane 出力から実行中の Subagent (Task ツール) を検出する。
 ///
 /// Claude Code の実際の表示パターン:
-///   * Worked for 54s · 3 agents running in the background
-///   ►► accept edits on · 3 local agents · ctrl+t to hide task
-///   ● Running 3 Task agents… (ctrl+o to expand)
-///     ├─ description · N tool uses · Nk tokens
+///
+/// ```text
+/// * Worked for 54s · 3 agents running in the background
+/// ►► accept edits on · 3 local agents · ctrl+t to hide task
+/// ● Running 3 Task agents… (ctrl+o to expand)
+///   ├─ description · N tool uses · Nk tokens
+/// ```
 pub fn parse_sub_agents(output: &str) -> Vec<SubAgent> {
     let trimmed = output.trim();
     if trimmed.is_empty() {
@@ -304,6 +409,184 @@ pub fn parse_sub_agent_count(output: &str) -> usize {
     max_count
 }
 
+/// 利用上限のメッセージからリセット時刻の表記を取り出す
+/// ("Your limit will reset at 3pm (Asia/Tokyo)" → "3pm (Asia/Tokyo)")。
+/// 古い "Claude AI usage limit reached|<unix 秒>" 形式はローカル時刻 "HH:MM" にする
+pub fn parse_rate_limit_reset(output: &str) -> Option<String> {
+    let epoch_re = Regex::new(r"(?i)usage limit reached\|(\d{9,11})").ok()?;
+    if let Some(caps) = epoch_re.captures_iter(output).last() {
+        let secs: i64 = caps[1].parse().ok()?;
+        let at = DateTime::from_timestamp(secs, 0)?;
+        return Some(at.with_timezone(&Local).format("%H:%M").to_string());
+    }
+    let text_re = Regex::new(r"(?i)\bresets?\s+(?:at\s+)?(\d{1,2}(?::\d{2})?\s*(?:am|pm)?(?:\s*\([^)]*\))?)").ok()?;
+    let caps = text_re.captures_iter(output).last()?;
+    Some(caps[1].trim().to_string())
+}
+
+/// 利用上限のリセット時刻 (now は上限を検出した時刻)。
+/// "3pm" のような時刻だけの表記はローカル時刻の今日のその時刻と解釈する (括弧内のタイムゾーンは見ない)。
+/// それが now より前でも半日以内なら、もうリセットされた (すぐ再開してよい) ものとしてそのまま返し、
+/// それより前なら翌日のこととする ("23 時に resets 2am")
+pub fn parse_rate_limit_reset_at(output: &str, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+    let epoch_re = Regex::new(r"(?i)usage limit reached\|(\d{9,11})").ok()?;
+    if let Some(caps) = epoch_re.captures_iter(output).last() {
+        return DateTime::from_timestamp(caps[1].parse().ok()?, 0);
+    }
+    let text = parse_rate_limit_reset(output)?;
+    let time_re = Regex::new(r"(?i)^(\d{1,2})(?::(\d{2}))?\s*(am|pm)?").ok()?;
+    let caps = time_re.captures(&text)?;
+    let mut hour: u32 = caps[1].parse().ok()?;
+    let minute: u32 = caps.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+    match caps.get(3).map(|m| m.as_str().to_ascii_lowercase()).as_deref() {
+        Some("am") if hour == 12 => hour = 0,
+        Some("pm") if hour < 12 => hour += 12,
+        _ => {}
+    }
+    let mut at = now.date_naive().and_hms_opt(hour, minute, 0)?.and_local_timezone(Local).earliest()?;
+    if at <= now - chrono::Duration::hours(12) {
+        at += chrono::Duration::days(1);
+    }
+    Some(at.with_timezone(&Utc))
+}
+
+// ---------------------------------------------------------------------------
+// 質問の検出
+// ---------------------------------------------------------------------------
+
+/// プロンプトより前の行から、最後の応答が質問で終わっているかを見る
+fn idle_or_awaiting(before_prompt: &[&str]) -> MemberStatus {
+    if question_in(before_prompt).is_some() {
+        MemberStatus::AwaitingInput
+    } else {
+        MemberStatus::Idle
+    }
+}
+
+/// 入力欄の枠・ヒント・空行など、応答の本文ではない行か
+fn is_chrome_line(line: &str) -> bool {
+    let content = line.trim().trim_matches(|c: char| "\u{2502}\u{2500}\u{256d}\u{256e}\u{2570}\u{256f}".contains(c) || c.is_whitespace());
+    content.is_empty() || matches_any(content, IDLE_PATTERNS) || content.contains("for shortcuts")
+}
+
+/// 最後の応答 (⏺ / ● で始まるブロック) の末尾 6 行以内に "?" で終わる行があればその行
+fn question_in(lines: &[&str]) -> Option<String> {
+    let end = lines.iter().rposition(|l| !is_chrome_line(l))? + 1;
+    let mut content = lines[..end].iter().rev().take(6);
+    content.find_map(|line| {
+        let text = line.trim().trim_start_matches(['\u{23fa}', '\u{25cf}']).trim();
+        (text.ends_with('?') || text.ends_with('\u{ff1f}')).then(|| text.to_string())
+    })
+}
+
+/// 画面の最後の応答が質問ならその文 (例: "Which approach do you prefer?")
+pub fn parse_question(output: &str) -> Option<String> {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    question_in(&lines[lines.len().saturating_sub(15)..])
+}
+
+// ---------------------------------------------------------------------------
+// コンテキストの逼迫
+// ---------------------------------------------------------------------------
+
+/// 画面下部のコンテキスト残量の警告と compact 中の表示を読み取る (出ていなければ None)。
+///
+/// ```text
+/// Context left until auto-compact: 12%
+/// Context low (8% remaining) · Run /compact to compact & continue
+/// ✻ Compacting conversation… (12s · esc to interrupt)
+/// ```
+pub fn parse_context_pressure(output: &str) -> Option<ContextPressure> {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    let tail = lines[lines.len().saturating_sub(15)..].join("\n");
+    if Regex::new(r"(?i)compacting conversation").ok()?.is_match(&tail) {
+        return Some(ContextPressure::Compacting);
+    }
+    let re = Regex::new(r"(?i)context (?:left until auto-compact|low)\D{0,4}(\d{1,3})%").ok()?;
+    let caps = re.captures_iter(&tail).last()?;
+    Some(ContextPressure::Low(caps[1].parse::<u8>().ok()?.min(100)))
+}
+
+// ---------------------------------------------------------------------------
+// タスク進捗の検出
+// ---------------------------------------------------------------------------
+
+/// チェックリスト 1 行の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TodoMark {
+    Done,
+    InProgress,
+    Pending,
+}
+
+/// "⎿  ✔ Read the code" のような TODO 行を (状態, タスク名) に分解する
+fn parse_todo_line(line: &str) -> Option<(TodoMark, &str)> {
+    let line = line.trim_start().trim_start_matches('\u{23bf}').trim_start();
+    let mut chars = line.chars();
+    let mark = match chars.next()? {
+        '\u{2714}' | '\u{2713}' | '\u{2612}' => TodoMark::Done,
+        '\u{25fc}' | '\u{25a0}' => TodoMark::InProgress,
+        '\u{25fb}' | '\u{25a1}' | '\u{2610}' => TodoMark::Pending,
+        _ => return None,
+    };
+    let title = chars.as_str().trim();
+    (!title.is_empty()).then_some((mark, title))
+}
+
+/// capture-pane 出力から Claude Code のタスク進捗を読み取る (見つからなければ None)。
+///
+/// 対応する表示:
+///
+/// ```text
+/// ⎿  ✔ Read the code          (新しい表示: ✔ 完了 / ◼ 進行中 / ◻ 未着手)
+///    ◼ Implement the parser
+///    ◻ Write tests
+/// ⎿  ☒ Read the code          (古い表示: ☒ 完了 / ☐ 未完了)
+/// 3 tasks (1 done, 1 in progress, 1 open)
+/// ✻ Implementing the parser… (12s · esc to interrupt)
+/// ```
+///
+/// チェックリストは最後に表示されたものを使い、進行中のタスクがなければスピナー行の文言を使う
+pub fn parse_task_progress(output: &str) -> Option<TaskProgress> {
+    // 最後に現れた連続するチェックリスト
+    let mut block: Vec<(TodoMark, &str)> = Vec::new();
+    let mut last_block: Vec<(TodoMark, &str)> = Vec::new();
+    for line in output.lines() {
+        match parse_todo_line(line) {
+            Some(item) => block.push(item),
+            None if !block.is_empty() => last_block = std::mem::take(&mut block),
+            None => {}
+        }
+    }
+    if !block.is_empty() {
+        last_block = block;
+    }
+
+    let spinner_re = Regex::new(r"^\s*\S\s+(.+?)(?:…|\.\.\.)\s*\(.*(?:esc|ctrl\+c) to interrupt").ok();
+    let spinner = spinner_re.and_then(|re| {
+        output
+            .lines()
+            .rev()
+            .find_map(|l| re.captures(l).and_then(|c| c.get(1)).map(|m| m.as_str().trim().to_string()))
+    });
+
+    if !last_block.is_empty() {
+        let done = last_block.iter().filter(|(m, _)| *m == TodoMark::Done).count();
+        let current = last_block
+            .iter()
+            .find(|(m, _)| *m == TodoMark::InProgress)
+            .map(|(_, t)| t.to_string())
+            .or(spinner);
+        return Some(TaskProgress { done, total: last_block.len(), current });
+    }
+
+    let summary_re = Regex::new(r"(\d+)\s+tasks?\s+\(([^)]*)\)").ok()?;
+    let caps = summary_re.captures_iter(output).last()?;
+    let total = caps[1].parse().ok()?;
+    let done = extract_first_match(&caps[2], &[r"(\d+)\s+done"]).and_then(|n| n.parse().ok()).unwrap_or(0);
+    Some(TaskProgress { done, total, current: spinner })
+}
+
 // ---------------------------------------------------------------------------
 // ヘルパー関数
 // ---------------------------------------------------------------------------
@@ -362,6 +645,143 @@ fn extract_code_block(text: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_detect_rate_limited() {
+        let output = "\u{23fa} Working on it\n\n  \u{23bf}  5-hour limit reached \u{2219} resets 3pm\n     /upgrade to increase your usage limit.\n\n> ";
+        assert_eq!(detect_member_status(output), MemberStatus::RateLimited);
+        let output = "Claude usage limit reached. Your limit will reset at 3pm (Asia/Tokyo).\n> ";
+        assert_eq!(detect_member_status(output), MemberStatus::RateLimited);
+        let output = "API Error: 429 {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\"}}";
+        assert_eq!(detect_member_status(output), MemberStatus::RateLimited);
+        assert_eq!(parse_status_word("rate_limited\n"), Some(MemberStatus::RateLimited));
+    }
+
+    #[test]
+    fn test_parse_rate_limit_reset() {
+        assert_eq!(
+            parse_rate_limit_reset("Claude usage limit reached. Your limit will reset at 3pm (Asia/Tokyo).").as_deref(),
+            Some("3pm (Asia/Tokyo)")
+        );
+        assert_eq!(parse_rate_limit_reset("5-hour limit reached \u{2219} resets 10:30am").as_deref(), Some("10:30am"));
+        assert!(parse_rate_limit_reset("Claude AI usage limit reached|1717000000").is_some());
+        assert_eq!(parse_rate_limit_reset("nothing here"), None);
+    }
+
+    #[test]
+    fn test_parse_rate_limit_reset_at() {
+        use chrono::TimeZone;
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 13, 0, 0).unwrap();
+        let at = |h, m| Local.with_ymd_and_hms(2024, 5, 1, h, m, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(parse_rate_limit_reset_at("limit reached \u{2219} resets 3pm", now), Some(at(15, 0)));
+        assert_eq!(parse_rate_limit_reset_at("Your limit will reset at 1:30pm (UTC).", now), Some(at(13, 30)));
+        // 少し前に過ぎた時刻はもうリセット済み (すぐ再開する)
+        assert_eq!(parse_rate_limit_reset_at("limit reached \u{2219} resets 10am", now), Some(at(10, 0)));
+        // 半日以上前の時刻は翌日
+        let late = Local.with_ymd_and_hms(2024, 5, 1, 23, 0, 0).unwrap();
+        assert_eq!(
+            parse_rate_limit_reset_at("limit reached \u{2219} resets 2am", late),
+            Some(at(2, 0) + chrono::Duration::days(1))
+        );
+        assert_eq!(
+            parse_rate_limit_reset_at("Claude AI usage limit reached|1717000000", now),
+            DateTime::from_timestamp(1_717_000_000, 0)
+        );
+        assert_eq!(parse_rate_limit_reset_at("usage limit reached", now), None);
+    }
+
+    #[test]
+    fn test_detect_awaiting_input() {
+        let output = "\u{23fa} I can fix this two ways.\n  1. Patch the parser\n  2. Rewrite it\n  Which approach do you prefer?\n\n>";
+        assert_eq!(detect_member_status(output), MemberStatus::AwaitingInput);
+        assert_eq!(parse_question(output).as_deref(), Some("Which approach do you prefer?"));
+
+        // 選択肢が質問の後に続いても拾う
+        let output = "\u{23fa} Which one should I use?\n  - serde\n  - miniserde\n\n>";
+        assert_eq!(detect_member_status(output), MemberStatus::AwaitingInput);
+
+        let output = "\u{23fa} Done. All tests pass.\n\n>";
+        assert_eq!(detect_member_status(output), MemberStatus::Idle);
+        assert_eq!(parse_question("\u{23fa} Done.\n  ? for shortcuts"), None);
+    }
+
+    #[test]
+    fn test_parse_context_pressure() {
+        let output = "> \n  ? for shortcuts                     Context left until auto-compact: 12%";
+        assert_eq!(parse_context_pressure(output), Some(ContextPressure::Low(12)));
+        let output = "> \n  Context low (8% remaining) \u{b7} Run /compact to compact & continue";
+        assert_eq!(parse_context_pressure(output), Some(ContextPressure::Low(8)));
+        let output = "\u{273b} Compacting conversation\u{2026} (12s \u{b7} esc to interrupt)";
+        assert_eq!(parse_context_pressure(output), Some(ContextPressure::Compacting));
+        assert_eq!(parse_context_pressure("> \n  ? for shortcuts"), None);
+    }
+
+    #[test]
+    fn test_parse_task_progress_checklist() {
+        let output = "\u{23fa} Update Todos\n  \u{23bf}  \u{2714} Read the code\n     \u{25fc} Implement the parser\n     \u{25fb} Write tests\n\n\u{273b} Implementing the parser\u{2026} (12s \u{b7} esc to interrupt)";
+        let progress = parse_task_progress(output).unwrap();
+        assert_eq!((progress.done, progress.total), (1, 3));
+        assert_eq!(progress.current.as_deref(), Some("Implement the parser"));
+
+        // 古い表示 (☒ / ☐): 進行中の印がなければスピナー行の文言
+        let output = "  \u{23bf}  \u{2612} Read the code\n     \u{2610} Write tests\n\u{273b} Writing tests\u{2026} (3s \u{b7} esc to interrupt)";
+        let progress = parse_task_progress(output).unwrap();
+        assert_eq!((progress.done, progress.total), (1, 2));
+        assert_eq!(progress.current.as_deref(), Some("Writing tests"));
+    }
+
+    #[test]
+    fn test_parse_task_progress_summary() {
+        let output = "some output\n3 tasks (1 done, 1 in progress, 1 open)";
+        let progress = parse_task_progress(output).unwrap();
+        assert_eq!((progress.done, progress.total), (1, 3));
+        assert_eq!(progress.current, None);
+        assert!(parse_task_progress("just some output\n> ").is_none());
+    }
+
+    #[test]
+    fn test_parse_status_word() {
+        assert_eq!(parse_status_word("Working\n"), Some(MemberStatus::Working));
+        assert_eq!(parse_status_word("  permission  extra"), Some(MemberStatus::Permission));
+        assert_eq!(parse_status_word(""), None);
+        assert_eq!(parse_status_word("unknown"), None);
+    }
+
+    #[test]
+    fn test_external_detector_overrides_builtin() {
+        let config = DetectionConfig {
+            detector_command: Some("grep -q 'BUILD FAILED' && echo error".to_string()),
+            ..DetectionConfig::default()
+        };
+        let command = config.detector_command.as_deref().unwrap();
+        let timeout = Duration::from_millis(config.detector_timeout_ms);
+        let external = run_external_detector(command, "make\nBUILD FAILED\n❯", "%1", timeout);
+        assert_eq!(detect_member_status_for("make\nBUILD FAILED\n❯", &config, external), MemberStatus::Error);
+        // 何も出力しなければ組み込み検出
+        let external = run_external_detector(command, "make\nok\n❯", "%1", timeout);
+        assert_eq!(external, None);
+        assert_eq!(detect_member_status_for("make\nok\n❯", &config, external), MemberStatus::Idle);
+    }
+
+    #[test]
+    fn test_external_detector_never_hides_a_warning() {
+        let config = DetectionConfig {
+            detector_command: Some("echo working".to_string()),
+            ..DetectionConfig::default()
+        };
+        let prompt = "Do you want to proceed?\n❯ 1. Yes\n  2. No";
+        assert_eq!(detect_member_status_for(prompt, &config, Some(MemberStatus::Working)), MemberStatus::Permission);
+        // 組み込み検出が Working なら外部の結果で下げられる
+        assert_eq!(detect_member_status_for("compiling...", &config, Some(MemberStatus::Idle)), MemberStatus::Idle);
+        // より優先度の高い状態には上げられる
+        assert_eq!(detect_member_status_for("make\nBUILD FAILED\nError: x", &config, Some(MemberStatus::Permission)), MemberStatus::Permission);
+    }
+
+    #[test]
+    fn test_external_detector_timeout_falls_back() {
+        let status = run_external_detector("sleep 5; echo error", "", "%1", Duration::from_millis(100));
+        assert_eq!(status, None);
+    }
+
     #[test]
     fn test_empty_output_is_done() {
         assert_eq!(detect_member_status(""), MemberStatus::Done);
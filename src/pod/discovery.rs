@@ -1,6 +1,8 @@
-use crate::pod::{Member, MemberStatus, Pod, PodStatus, PodType};
-use crate::tmux::Tmux;
-use chrono::Utc;
+use crate::pod::{Member, MemberStatus, Pod, PodStatus, PodType, Sandbox};
+use crate::pod::process::AgentProbe;
+use crate::tmux::{TmuxBackend, TmuxPane, POPUP_SESSION_PREFIX};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
 use regex::Regex;
 
 /// Claude Code の特徴的なパターン
@@ -16,8 +18,8 @@ const CLAUDE_CODE_PATTERNS: &[&str] = &[
 
 /// Pod 内の tmux セッションから新しい member を検出する
 /// all_pods: 同じ session を共有する全 Pod の pane を重複検出しないために使用
-pub fn discover_new_members(pod: &Pod, all_pods: &[Pod]) -> Vec<Member> {
-    let panes = match Tmux::list_panes(&pod.tmux_session) {
+pub fn discover_new_members(tmux: &dyn TmuxBackend, pod: &Pod, all_pods: &[Pod]) -> Vec<Member> {
+    let panes = match tmux.list_panes(&pod.tmux_session) {
         Ok(p) => p,
         Err(_) => return Vec::new(),
     };
@@ -37,7 +39,7 @@ pub fn discover_new_members(pod: &Pod, all_pods: &[Pod]) -> Vec<Member> {
         }
 
         // capture-pane で出力を確認
-        let output = match Tmux::capture_pane(&pane.id) {
+        let output = match tmux.capture_pane(&pane.id) {
             Ok(o) => o,
             Err(_) => continue,
         };
@@ -50,22 +52,124 @@ pub fn discover_new_members(pod: &Pod, all_pods: &[Pod]) -> Vec<Member> {
         let role = detect_role_name(&output, new_members.len() + pod.members.len());
 
         new_members.push(Member {
-            role,
             status: MemberStatus::Working,
-            tmux_pane: pane.id.clone(),
-            last_change: Utc::now(),
             last_output: output,
-            last_output_ansi: String::new(),
-            pane_size: (80, 24),
-            last_polled: None,
-            working_secs: 0,
-            sub_agents: Vec::new(),
+            ..Member::new(&role, &pane.id)
         });
     }
 
     new_members
 }
 
+/// どの Pod にも属していない Claude Code セッション
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmanagedSession {
+    pub session: String,
+    /// Claude Code と判定されたペイン ID
+    pub panes: Vec<String>,
+    /// 先頭ペインのカレントディレクトリ
+    pub path: Option<String>,
+}
+
+/// Pod 管理外のセッションのうち Claude Code が動いているものを検出
+/// 出力パターンに加えて、ペイン配下のプロセスにエージェントがいるかも確認する
+pub fn discover_unmanaged_sessions(tmux: &dyn TmuxBackend, pods: &[Pod], probe: &mut AgentProbe) -> Vec<UnmanagedSession> {
+    let panes = match tmux.list_all_panes() {
+        Ok(p) => p,
+        Err(_) => return Vec::new(),
+    };
+
+    unmanaged_panes(&panes, pods)
+        .into_iter()
+        .filter_map(|(session, candidates)| {
+            let claude_panes: Vec<&TmuxPane> = candidates
+                .into_iter()
+                .filter(|pane| {
+                    let by_output = tmux.capture_pane(&pane.id)
+                        .map(|output| is_claude_code_pane(&output))
+                        .unwrap_or(false);
+                    by_output || pane.pid.and_then(|pid| probe.pid_has_agent(pid)) == Some(true)
+                })
+                .collect();
+            let first = claude_panes.first()?;
+            Some(UnmanagedSession {
+                session,
+                path: first.current_path.clone(),
+                panes: claude_panes.iter().map(|p| p.id.clone()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// `a` のピッカーに出す、Pod になっていない tmux セッション
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdoptCandidate {
+    pub session: String,
+    pub windows: usize,
+    /// セッションの作成時刻 (分からなければ None)
+    pub created: Option<DateTime<Utc>>,
+    pub panes: usize,
+    /// Claude Code が動いていそうなペインの数
+    pub claude_panes: usize,
+    /// 先頭ペインのカレントディレクトリ
+    pub path: Option<String>,
+    /// アクティブなペイン (ピッカーのプレビューに使う)
+    pub active_pane: Option<String>,
+}
+
+/// Pod になっていない全 tmux セッションを、Claude Code らしいものを先に (同じなら名前順) 並べて返す
+pub fn list_adoptable_sessions(tmux: &dyn TmuxBackend, pods: &[Pod], probe: &mut AgentProbe) -> Result<Vec<AdoptCandidate>> {
+    let sessions = tmux.list_sessions()?;
+    let panes = tmux.list_all_panes().unwrap_or_default();
+    let unmanaged = unmanaged_panes(&panes, pods);
+    let mut candidates: Vec<AdoptCandidate> = sessions
+        .into_iter()
+        .filter(|session| {
+            !session.name.starts_with(POPUP_SESSION_PREFIX) && !pods.iter().any(|p| p.tmux_session == session.name)
+        })
+        .map(|session| {
+            let session_panes: &[&TmuxPane] =
+                unmanaged.iter().find(|(name, _)| *name == session.name).map(|(_, p)| p.as_slice()).unwrap_or(&[]);
+            let claude_panes = session_panes
+                .iter()
+                .filter(|pane| {
+                    let by_output = tmux.capture_pane(&pane.id).map(|output| is_claude_code_pane(&output)).unwrap_or(false);
+                    by_output || pane.pid.and_then(|pid| probe.pid_has_agent(pid)) == Some(true)
+                })
+                .count();
+            AdoptCandidate {
+                created: session.created.parse::<i64>().ok().and_then(|secs| DateTime::from_timestamp(secs, 0)),
+                windows: session.windows,
+                panes: session_panes.len(),
+                claude_panes,
+                path: session_panes.first().and_then(|p| p.current_path.clone()),
+                active_pane: session_panes.iter().find(|p| p.active).or(session_panes.first()).map(|p| p.id.clone()),
+                session: session.name,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| (b.claude_panes > 0).cmp(&(a.claude_panes > 0)).then_with(|| a.session.cmp(&b.session)));
+    Ok(candidates)
+}
+
+/// 既存 Pod のセッションに属さないペインをセッション名順にまとめる
+/// (Pod のセッション内の新しいペインは discover_new_members が拾う)
+fn unmanaged_panes<'a>(panes: &'a [TmuxPane], pods: &[Pod]) -> Vec<(String, Vec<&'a TmuxPane>)> {
+    let known_sessions: std::collections::HashSet<&str> =
+        pods.iter().map(|p| p.tmux_session.as_str()).collect();
+
+    let mut sessions: std::collections::BTreeMap<String, Vec<&TmuxPane>> =
+        std::collections::BTreeMap::new();
+    for pane in panes {
+        // ポップアップでアタッチ中のグループセッションは Pod のセッションと同じウィンドウを見ている
+        if known_sessions.contains(pane.session.as_str()) || pane.session.starts_with(POPUP_SESSION_PREFIX) {
+            continue;
+        }
+        sessions.entry(pane.session.clone()).or_default().push(pane);
+    }
+    sessions.into_iter().collect()
+}
+
 /// capture-pane 出力から Claude Code が動いているペインかどうか判定
 pub fn is_claude_code_pane(output: &str) -> bool {
     if output.trim().is_empty() {
@@ -167,13 +271,92 @@ pub fn create_child_pods(parent: &mut Pod, discovered: Vec<Member>) -> Vec<Pod>
                 group: Some(group_name.clone()),
                 status: PodStatus::Idle,
                 members: vec![member],
-                created_at: Utc::now(),
-                total_working_secs: 0,
+                ..Default::default()
             }
         })
         .collect()
 }
 
+/// Team Pod の member を、同じセッションを共有する独立した子 Pod にする (create_child_pods の手動版)。
+/// 残りが 1 人になった親は Solo に戻る
+pub fn split_member(parent: &mut Pod, role: &str) -> anyhow::Result<Pod> {
+    let idx = parent
+        .members
+        .iter()
+        .position(|m| m.role == role)
+        .ok_or_else(|| anyhow::anyhow!("'{}' has no member named '{}'", parent.name, role))?;
+    if parent.members.len() < 2 {
+        anyhow::bail!("'{}' has only one member", parent.name);
+    }
+    let member = parent.members.remove(idx);
+    if parent.members.len() == 1 {
+        parent.pod_type = PodType::Solo;
+    }
+    parent.rollup_status();
+    let child = create_child_pods(parent, vec![member]).pop().expect("one member gives one child");
+    Ok(child)
+}
+
+/// member の role を変更する。Pod が "<親>/<old>" という子 Pod なら、その新しい名前 "<親>/<new>" を返す
+/// (Pod 名の変更は呼び出し側で行う)
+pub fn rename_member(pod: &mut Pod, old: &str, new: &str) -> anyhow::Result<Option<String>> {
+    if new.is_empty() || new.contains('/') || new.contains(char::is_whitespace) {
+        anyhow::bail!("Invalid member name '{}' (no spaces or '/')", new);
+    }
+    if pod.members.iter().any(|m| m.role == new) {
+        anyhow::bail!("'{}' already has a member named '{}'", pod.name, new);
+    }
+    let member = pod
+        .members
+        .iter_mut()
+        .find(|m| m.role == old)
+        .ok_or_else(|| anyhow::anyhow!("'{}' has no member named '{}'", pod.name, old))?;
+    member.role = new.to_string();
+    let child_name = match pod.name.rsplit_once('/') {
+        Some((parent, role)) if role == old && pod.members.len() == 1 => Some(format!("{}/{}", parent, new)),
+        _ => None,
+    };
+    Ok(child_name)
+}
+
+/// source の member を target に移して Team Pod にする (role が重なる member は "<source>-<role>" に改名)。
+/// タグは和集合、メモ・on-done・issue・PR・自動再開・サンドボックスは target に無ければ source のものを引き継ぐ。
+/// target が別のサンドボックスを持っていて引き継げなかった source のサンドボックスを返す
+pub fn merge_members(target: &mut Pod, source: Pod) -> Option<Sandbox> {
+    for mut member in source.members {
+        if target.members.iter().any(|m| m.role == member.role) {
+            member.role = format!("{}-{}", source.name.replace('/', "-"), member.role);
+        }
+        target.members.push(member);
+    }
+    for tag in source.tags {
+        if !target.tags.contains(&tag) {
+            target.tags.push(tag);
+        }
+    }
+    if target.note.is_empty() {
+        target.note = source.note;
+    }
+    target.on_done = target.on_done.take().or(source.on_done);
+    target.issue = target.issue.take().or(source.issue);
+    target.pr_url = target.pr_url.take().or(source.pr_url);
+    target.auto_resume = target.auto_resume.or(source.auto_resume);
+    let leftover = match (&target.sandbox, source.sandbox) {
+        (None, sandbox) => {
+            target.sandbox = sandbox;
+            None
+        }
+        (Some(ours), Some(theirs)) if *ours != theirs => Some(theirs),
+        _ => None,
+    };
+    target.total_working_secs += source.total_working_secs;
+    if target.members.len() > 1 {
+        target.pod_type = PodType::Team;
+    }
+    target.rollup_status();
+    leftover
+}
+
 /// member が 0 の子 Pod を除去する
 ///
 /// - group が Some で、同じ group に他の Pod が存在する子 Pod のみ対象
@@ -214,10 +397,11 @@ pub fn remove_orphan_child_pods(pods: &mut Vec<Pod>) {
 }
 
 /// Pod から消えたペインを検出して member を除外
-pub fn remove_stale_members(pod: &mut Pod) {
-    let panes = match Tmux::list_panes(&pod.tmux_session) {
-        Ok(p) => p,
-        Err(_) => return,
+/// (`apiary member add` で他のセッションのペインも member になりうるので全ペインと照合する)
+pub fn remove_stale_members(tmux: &dyn TmuxBackend, pod: &mut Pod) {
+    let panes = match tmux.list_all_panes() {
+        Ok(p) if !p.is_empty() => p,
+        _ => return,
     };
 
     let active_pane_ids: std::collections::HashSet<String> = panes.iter()
@@ -235,16 +419,8 @@ mod tests {
     /// テスト用 Member を作成するヘルパー
     fn make_member(role: &str, pane: &str) -> Member {
         Member {
-            role: role.to_string(),
             status: MemberStatus::Working,
-            tmux_pane: pane.to_string(),
-            last_change: Utc::now(),
-            last_output: String::new(),
-            last_output_ansi: String::new(),
-            pane_size: (80, 24),
-            last_polled: None,
-            working_secs: 0,
-            sub_agents: Vec::new(),
+            ..Member::new(role, pane)
         }
     }
 
@@ -258,8 +434,7 @@ mod tests {
             tmux_session: session.to_string(),
             project: Some("my-project".to_string()),
             group: group.map(|s| s.to_string()),
-            created_at: Utc::now(),
-            total_working_secs: 0,
+            ..Default::default()
         }
     }
 
@@ -267,6 +442,26 @@ mod tests {
     // create_child_pods
     // -----------------------------------------------------------------------
 
+    #[test]
+    fn test_discover_and_remove_members_with_fake_tmux() {
+        let tmux = crate::tmux::fake::FakeTmux::new();
+        let lead = tmux.add_session("team", None);
+        let mate = tmux.add_pane("team");
+        let shell = tmux.add_pane("team");
+        tmux.set_output(&mate, "@reviewer\n✻ Thinking…\n❯ ");
+        tmux.set_output(&shell, "$ ls\nREADME.md");
+        let mut pod = make_pod("team", "team", vec![make_member("lead", &lead)], None);
+
+        let found = discover_new_members(&tmux, &pod, std::slice::from_ref(&pod));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].tmux_pane, mate);
+
+        pod.members.extend(found);
+        crate::tmux::TmuxBackend::kill_pane(&tmux, &mate).unwrap();
+        remove_stale_members(&tmux, &mut pod);
+        assert_eq!(pod.members.len(), 1);
+    }
+
     #[test]
     fn test_create_child_pods_basic() {
         let mut parent = make_pod("auth", "auth-session", vec![make_member("lead", "%0")], None);
@@ -334,6 +529,74 @@ mod tests {
         assert!(parent.group.is_none());
     }
 
+    #[test]
+    fn test_split_member() {
+        let mut parent = make_pod(
+            "auth",
+            "auth-session",
+            vec![make_member("lead", "%0"), make_member("tester", "%1")],
+            None,
+        );
+
+        let child = split_member(&mut parent, "tester").unwrap();
+
+        assert_eq!(child.name, "auth/tester");
+        assert_eq!(child.tmux_session, "auth-session");
+        assert_eq!(child.group, Some("auth".to_string()));
+        assert_eq!(child.members[0].tmux_pane, "%1");
+        assert_eq!(parent.members.len(), 1);
+        assert_eq!(parent.pod_type, PodType::Solo);
+        // 最後の 1 人や存在しない member は分割できない
+        assert!(split_member(&mut parent, "lead").is_err());
+        assert!(split_member(&mut parent, "nobody").is_err());
+    }
+
+    #[test]
+    fn test_rename_member() {
+        let mut parent = make_pod("auth", "s", vec![make_member("lead", "%0"), make_member("member-1", "%1")], None);
+        assert_eq!(rename_member(&mut parent, "member-1", "reviewer").unwrap(), None);
+        assert_eq!(parent.members[1].role, "reviewer");
+        assert!(rename_member(&mut parent, "lead", "reviewer").is_err());
+        assert!(rename_member(&mut parent, "nobody", "x").is_err());
+        assert!(rename_member(&mut parent, "lead", "a/b").is_err());
+
+        // 子 Pod は Pod 名も追従する
+        let mut child = make_pod("auth/member-2", "s", vec![make_member("member-2", "%2")], Some("auth"));
+        assert_eq!(rename_member(&mut child, "member-2", "tester").unwrap(), Some("auth/tester".to_string()));
+    }
+
+    #[test]
+    fn test_merge_members() {
+        let mut target = make_pod("api", "api", vec![make_member("lead", "%0")], None);
+        target.tags = vec!["backend".to_string()];
+        let mut source = make_pod("web", "web", vec![make_member("lead", "%5"), make_member("tester", "%6")], None);
+        source.tags = vec!["backend".to_string(), "ui".to_string()];
+        source.note = "frontend work".to_string();
+        source.issue = Some("Ischca/apiary#7".to_string());
+        source.pr_url = Some("https://github.com/Ischca/apiary/pull/8".to_string());
+        source.auto_resume = Some(true);
+        source.sandbox = Some(Sandbox { container: "apiary-web".to_string(), image: Some("node".to_string()), devcontainer: false });
+        target.pr_url = Some("https://github.com/Ischca/apiary/pull/1".to_string());
+
+        assert_eq!(merge_members(&mut target, source), None);
+
+        assert_eq!(target.pod_type, PodType::Team);
+        let roles: Vec<&str> = target.members.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["lead", "web-lead", "tester"]);
+        assert_eq!(target.tags, vec!["backend".to_string(), "ui".to_string()]);
+        assert_eq!(target.note, "frontend work");
+        // target に無いものだけ引き継ぐ
+        assert_eq!(target.issue.as_deref(), Some("Ischca/apiary#7"));
+        assert_eq!(target.pr_url.as_deref(), Some("https://github.com/Ischca/apiary/pull/1"));
+        assert_eq!(target.auto_resume, Some(true));
+        assert_eq!(target.sandbox.as_ref().map(|s| s.container.as_str()), Some("apiary-web"));
+
+        // 別のサンドボックスは引き継げないので返す
+        let mut other = make_pod("db", "db", vec![make_member("lead", "%7")], None);
+        other.sandbox = Some(Sandbox { container: "apiary-db".to_string(), image: Some("postgres".to_string()), devcontainer: false });
+        assert_eq!(merge_members(&mut target, other).map(|s| s.container), Some("apiary-db".to_string()));
+    }
+
     // -----------------------------------------------------------------------
     // remove_orphan_child_pods
     // -----------------------------------------------------------------------
@@ -399,6 +662,60 @@ mod tests {
     }
 
     // -----------------------------------------------------------------------
+    // unmanaged_panes
+
+    fn make_pane(id: &str, session: &str) -> TmuxPane {
+        TmuxPane {
+            id: id.to_string(),
+            session: session.to_string(),
+            window_index: 0,
+            pane_index: 0,
+            active: true,
+            title: String::new(),
+            pid: None,
+            current_path: None,
+        }
+    }
+
+    #[test]
+    fn test_unmanaged_panes_skips_pod_sessions() {
+        let pod = make_pod("known", "known", vec![make_member("claude", "%1")], None);
+        let panes = vec![
+            make_pane("%1", "known"),
+            make_pane("%9", "known"),
+            make_pane("%5", "zeta"),
+            make_pane("%2", "alpha"),
+            make_pane("%3", "alpha"),
+        ];
+
+        let result = unmanaged_panes(&panes, &[pod]);
+        let summary: Vec<(&str, usize)> = result.iter().map(|(s, p)| (s.as_str(), p.len())).collect();
+        assert_eq!(summary, vec![("alpha", 2), ("zeta", 1)]);
+    }
+
+    #[test]
+    fn test_list_adoptable_sessions() {
+        let tmux = crate::tmux::fake::FakeTmux::new();
+        let known = tmux.add_session("known", None);
+        tmux.add_session("shell", Some("/src/shell"));
+        let claude = tmux.add_session("work", Some("/src/work"));
+        tmux.add_pane("work");
+        tmux.set_output(&claude, "\u{276f} ");
+        let popup = tmux.add_session("apiary-popup-known", None);
+        tmux.set_output(&popup, "\u{276f} ");
+        let pod = make_pod("known", "known", vec![make_member("lead", &known)], None);
+
+        let mut probe = AgentProbe::new(&[]);
+        let candidates = list_adoptable_sessions(&tmux, &[pod], &mut probe).unwrap();
+        let summary: Vec<(&str, usize, usize)> =
+            candidates.iter().map(|c| (c.session.as_str(), c.panes, c.claude_panes)).collect();
+        // Claude Code らしいセッションが先
+        assert_eq!(summary, [("work", 2, 1), ("shell", 1, 0)]);
+        assert_eq!(candidates[0].path.as_deref(), Some("/src/work"));
+        assert_eq!(candidates[0].created, None);
+        assert_eq!(candidates[0].active_pane.as_deref(), Some(claude.as_str()));
+    }
+
     // is_claude_code_pane — Agent Teams patterns
     // -----------------------------------------------------------------------
 
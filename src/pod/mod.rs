@@ -1,11 +1,13 @@
 pub mod detector;
 pub mod discovery;
+pub mod process;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
 
 /// Task ツールで起動される Subagent の情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +16,87 @@ pub struct SubAgent {
     pub description: String, // short description from pane output
 }
 
+/// Claude Code のタスク一覧 / TODO チェックリストから読み取った進捗
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskProgress {
+    pub done: usize,
+    pub total: usize,
+    /// 進行中のタスク名
+    pub current: Option<String>,
+}
+
+impl TaskProgress {
+    /// "▰▰▱▱ 2/4" 形式のミニプログレスバー (width はバーのマス数)
+    pub fn bar(&self, width: usize, ascii: bool) -> String {
+        let (full, empty) = if ascii { ('#', '-') } else { ('\u{25b0}', '\u{25b1}') };
+        let filled = (self.done * width).checked_div(self.total).unwrap_or(0).min(width);
+        let bar: String = std::iter::repeat_n(full, filled).chain(std::iter::repeat_n(empty, width - filled)).collect();
+        format!("{} {}/{}", bar, self.done, self.total)
+    }
+}
+
+/// Claude Code が表示するコンテキストの逼迫状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextPressure {
+    /// "Context left until auto-compact: 12%" (自動 compact までの残り %)
+    Low(u8),
+    /// 会話を compact している最中
+    Compacting,
+}
+
+impl ContextPressure {
+    /// カードのタイトルに付ける短い表示 ("◔12%" / "ctx12%" など)
+    pub fn badge(&self, ascii: bool) -> String {
+        match (self, ascii) {
+            (ContextPressure::Low(percent), false) => format!("\u{25d4}{}%", percent),
+            (ContextPressure::Low(percent), true) => format!("ctx{}%", percent),
+            (ContextPressure::Compacting, false) => "\u{267b}".to_string(),
+            (ContextPressure::Compacting, true) => "compacting".to_string(),
+        }
+    }
+}
+
+/// ステータスアイコンの字形セット
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IconSet {
+    /// 絵文字 (端末によっては幅がずれる)
+    #[default]
+    Emoji,
+    /// Nerd Font のグリフ (パッチ済みフォントが必要)
+    Nerdfont,
+    /// ASCII のみ ("[WORK]" など)
+    Ascii,
+    /// 状態名そのもの ("Working" など。アクセシビリティモードで使う)
+    Text,
+}
+
+impl IconSet {
+    /// ASCII だけで描画するセットか (プログレスバーなどの字形を合わせる)
+    pub fn is_ascii(self) -> bool {
+        matches!(self, IconSet::Ascii | IconSet::Text)
+    }
+
+    /// このセットのアイコンの最大表示幅 (列を揃えるのに使う)
+    pub fn width(self) -> usize {
+        KANBAN_COLUMNS.iter().map(|s| s.icon(self).width()).max().unwrap_or(1)
+    }
+
+    /// アイコンを最大表示幅まで空白で埋める
+    pub fn pad(self, icon: &str) -> String {
+        format!("{}{}", icon, " ".repeat(self.width().saturating_sub(icon.width())))
+    }
+
+    /// 作業時間の前に付ける印
+    pub fn working_marker(self) -> &'static str {
+        match self {
+            IconSet::Emoji => "\u{23f1}",
+            IconSet::Nerdfont => "\u{f017}",
+            IconSet::Ascii => "W:",
+            IconSet::Text => "worked ",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum MemberStatus {
@@ -21,26 +104,69 @@ pub enum MemberStatus {
     Working,
     Permission,
     Error,
+    /// 利用上限・レート制限に達してリセットを待っている
+    RateLimited,
+    /// Claude が質問で終わって返事を待っている
+    AwaitingInput,
     Done,
     Dead,
 }
 
 impl MemberStatus {
-    pub fn icon(&self) -> &str {
-        match self {
-            MemberStatus::Permission => "\u{26a0}",
-            MemberStatus::Error => "\u{274c}",
-            MemberStatus::Working => "\u{1f504}",
-            MemberStatus::Idle => "\u{23f8}",
-            MemberStatus::Done => "\u{2705}",
-            MemberStatus::Dead => "\u{1f480}",
+    /// 設定 (`ui.icons`) の字形セットでのステータスアイコン
+    pub fn icon(&self, set: IconSet) -> &'static str {
+        match set {
+            IconSet::Emoji => match self {
+                MemberStatus::Permission => "\u{26a0}",
+                MemberStatus::Error => "\u{274c}",
+                MemberStatus::RateLimited => "\u{23f3}",
+                MemberStatus::AwaitingInput => "\u{2753}",
+                MemberStatus::Working => "\u{1f504}",
+                MemberStatus::Idle => "\u{23f8}",
+                MemberStatus::Done => "\u{2705}",
+                MemberStatus::Dead => "\u{1f480}",
+            },
+            // Nerd Font (Font Awesome / Material Design 領域) の 1 セル幅グリフ
+            IconSet::Nerdfont => match self {
+                MemberStatus::Permission => "\u{f071}",
+                MemberStatus::Error => "\u{f057}",
+                MemberStatus::RateLimited => "\u{f252}",
+                MemberStatus::AwaitingInput => "\u{f059}",
+                MemberStatus::Working => "\u{f021}",
+                MemberStatus::Idle => "\u{f04c}",
+                MemberStatus::Done => "\u{f058}",
+                MemberStatus::Dead => "\u{f068c}",
+            },
+            // 絵文字を表示できない端末・ログ向け (`--plain` / NO_COLOR でも使う)
+            IconSet::Ascii => match self {
+                MemberStatus::Permission => "[PERM]",
+                MemberStatus::Error => "[ERR]",
+                MemberStatus::RateLimited => "[RATE]",
+                MemberStatus::AwaitingInput => "[ASK]",
+                MemberStatus::Working => "[WORK]",
+                MemberStatus::Idle => "[IDLE]",
+                MemberStatus::Done => "[DONE]",
+                MemberStatus::Dead => "[DEAD]",
+            },
+            IconSet::Text => match self {
+                MemberStatus::Permission => "Permission",
+                MemberStatus::Error => "Error",
+                MemberStatus::RateLimited => "Rate limited",
+                MemberStatus::AwaitingInput => "Awaiting input",
+                MemberStatus::Working => "Working",
+                MemberStatus::Idle => "Idle",
+                MemberStatus::Done => "Done",
+                MemberStatus::Dead => "Dead",
+            },
         }
     }
 
     pub fn priority(&self) -> u8 {
         match self {
-            MemberStatus::Permission => 4,
-            MemberStatus::Error => 3,
+            MemberStatus::Permission => 6,
+            MemberStatus::AwaitingInput => 5,
+            MemberStatus::Error => 4,
+            MemberStatus::RateLimited => 3,
             MemberStatus::Working => 2,
             MemberStatus::Idle => 1,
             MemberStatus::Done => 0,
@@ -55,10 +181,249 @@ pub enum PodStatus {
     Working,
     Permission,
     Error,
+    RateLimited,
+    AwaitingInput,
     Done,
     Dead,
 }
 
+/// 作業完了時のアクション
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnDone {
+    /// 作業ディレクトリでシェルコマンドを実行 (リードペインの隣に一時ペインを開く)
+    Run(String),
+    /// 変更をコミットするよう Claude に依頼
+    Commit,
+    /// 追加のプロンプトを Claude に送る
+    Prompt(String),
+}
+
+impl OnDone {
+    /// Commit で Claude に送るプロンプト
+    pub const COMMIT_PROMPT: &'static str = "Commit the current changes with a concise, descriptive commit message.";
+
+    pub fn describe(&self) -> String {
+        match self {
+            OnDone::Run(cmd) => format!("run `{}`", cmd),
+            OnDone::Commit => "commit".to_string(),
+            OnDone::Prompt(text) => format!("prompt \"{}\"", text),
+        }
+    }
+}
+
+/// Pod の優先度 (ポーリング間隔・通知・グリッドの並び順に効く)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PodPriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl PodPriority {
+    /// 並び順 (High が先頭)
+    pub fn rank(self) -> u8 {
+        match self {
+            PodPriority::High => 0,
+            PodPriority::Normal => 1,
+            PodPriority::Low => 2,
+        }
+    }
+
+    /// TUI で切り替えるときの次の優先度 (Normal → High → Low → Normal)
+    pub fn cycle(self) -> Self {
+        match self {
+            PodPriority::Normal => PodPriority::High,
+            PodPriority::High => PodPriority::Low,
+            PodPriority::Low => PodPriority::Normal,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "high" => Some(PodPriority::High),
+            "normal" => Some(PodPriority::Normal),
+            "low" => Some(PodPriority::Low),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PodPriority::High => "high",
+            PodPriority::Normal => "normal",
+            PodPriority::Low => "low",
+        }
+    }
+
+    /// カードのタイトルに付ける印 (Normal は付けない)
+    pub fn marker(self, ascii: bool) -> Option<&'static str> {
+        match (self, ascii) {
+            (PodPriority::High, false) => Some("↑"),
+            (PodPriority::High, true) => Some("!"),
+            (PodPriority::Low, false) => Some("↓"),
+            (PodPriority::Low, true) => Some("_"),
+            (PodPriority::Normal, _) => None,
+        }
+    }
+}
+
+/// Pod のアクセントカラーに使える名前付きの色
+pub const ACCENT_COLORS: &[(&str, (u8, u8, u8))] = &[
+    ("red", (215, 95, 95)),
+    ("orange", (215, 135, 75)),
+    ("yellow", (205, 180, 80)),
+    ("lime", (150, 200, 90)),
+    ("green", (95, 175, 110)),
+    ("teal", (80, 175, 160)),
+    ("cyan", (90, 185, 215)),
+    ("blue", (100, 140, 220)),
+    ("purple", (150, 115, 215)),
+    ("magenta", (200, 100, 190)),
+    ("pink", (225, 130, 160)),
+    ("brown", (165, 120, 85)),
+];
+
+/// アクセントカラーの指定 (ACCENT_COLORS の名前か #rrggbb) を RGB にする
+pub fn parse_accent(s: &str) -> Option<(u8, u8, u8)> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some((channel(0)?, channel(2)?, channel(4)?));
+    }
+    ACCENT_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        .map(|(_, rgb)| *rgb)
+}
+
+/// Claude を Docker コンテナの中で動かす Pod のコンテナ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Sandbox {
+    /// コンテナ名 (または ID)
+    pub container: String,
+    /// `docker run` したイメージ。None なら `docker exec` で入った既存のコンテナ (drop しても止めない)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// プロジェクトの devcontainer (`devcontainer exec` で入る、drop しても止めない)
+    #[serde(default)]
+    pub devcontainer: bool,
+}
+
+impl Sandbox {
+    pub fn describe(&self) -> String {
+        match self.image {
+            Some(ref image) => format!("{} ({})", self.container, image),
+            None if self.devcontainer => format!("{} (devcontainer)", short_id(&self.container)),
+            None => format!("{} (exec)", self.container),
+        }
+    }
+}
+
+/// コンテナ ID を docker ps と同じ 12 文字に縮める
+fn short_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
+
+/// Kanban 表示のカラム順 (左から対応が必要な順)
+pub const KANBAN_COLUMNS: &[PodStatus] = &[
+    PodStatus::Permission,
+    PodStatus::AwaitingInput,
+    PodStatus::Error,
+    PodStatus::RateLimited,
+    PodStatus::Working,
+    PodStatus::Idle,
+    PodStatus::Done,
+    PodStatus::Dead,
+];
+
+impl PodStatus {
+    /// 設定 (`ui.icons`) の字形セットでのステータスアイコン
+    pub fn icon(&self, set: IconSet) -> &'static str {
+        match set {
+            IconSet::Emoji => match self {
+                PodStatus::Permission => "\u{26a0}",
+                PodStatus::Error => "\u{274c}",
+                PodStatus::RateLimited => "\u{23f3}",
+                PodStatus::AwaitingInput => "\u{2753}",
+                PodStatus::Working => "\u{1f504}",
+                PodStatus::Idle => "\u{23f8}",
+                PodStatus::Done => "\u{2705}",
+                PodStatus::Dead => "\u{1f480}",
+            },
+            // Nerd Font (Font Awesome / Material Design 領域) の 1 セル幅グリフ
+            IconSet::Nerdfont => match self {
+                PodStatus::Permission => "\u{f071}",
+                PodStatus::Error => "\u{f057}",
+                PodStatus::RateLimited => "\u{f252}",
+                PodStatus::AwaitingInput => "\u{f059}",
+                PodStatus::Working => "\u{f021}",
+                PodStatus::Idle => "\u{f04c}",
+                PodStatus::Done => "\u{f058}",
+                PodStatus::Dead => "\u{f068c}",
+            },
+            // 絵文字を表示できない端末・ログ向け (`--plain` / NO_COLOR でも使う)
+            IconSet::Ascii => match self {
+                PodStatus::Permission => "[PERM]",
+                PodStatus::Error => "[ERR]",
+                PodStatus::RateLimited => "[RATE]",
+                PodStatus::AwaitingInput => "[ASK]",
+                PodStatus::Working => "[WORK]",
+                PodStatus::Idle => "[IDLE]",
+                PodStatus::Done => "[DONE]",
+                PodStatus::Dead => "[DEAD]",
+            },
+            IconSet::Text => match self {
+                PodStatus::Permission => "Permission",
+                PodStatus::Error => "Error",
+                PodStatus::RateLimited => "Rate limited",
+                PodStatus::AwaitingInput => "Awaiting input",
+                PodStatus::Working => "Working",
+                PodStatus::Idle => "Idle",
+                PodStatus::Done => "Done",
+                PodStatus::Dead => "Dead",
+            },
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PodStatus::Idle => "Idle",
+            PodStatus::Working => "Working",
+            PodStatus::Permission => "Permission",
+            PodStatus::Error => "Error",
+            PodStatus::RateLimited => "Rate limited",
+            PodStatus::AwaitingInput => "Awaiting input",
+            PodStatus::Done => "Done",
+            PodStatus::Dead => "Dead",
+        }
+    }
+
+    /// 対応の優先度 (MemberStatus::priority と同じ並び)
+    pub fn priority(&self) -> u8 {
+        match self {
+            PodStatus::Permission => 6,
+            PodStatus::AwaitingInput => 5,
+            PodStatus::Error => 4,
+            PodStatus::RateLimited => 3,
+            PodStatus::Working => 2,
+            PodStatus::Idle => 1,
+            PodStatus::Done => 0,
+            PodStatus::Dead => 0,
+        }
+    }
+
+    /// 人の対応が必要な状態 (Permission / AwaitingInput / Error)
+    pub fn is_warning(&self) -> bool {
+        self.priority() >= 4
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PodType {
     Solo,
@@ -84,11 +449,71 @@ pub struct Member {
     /// pane 出力から検出された実行中の Subagent (Task ツール)
     #[serde(skip)]
     pub sub_agents: Vec<SubAgent>,
+    /// pane 配下のプロセスツリーの CPU / メモリ使用量 (最新サンプル)
+    #[serde(skip)]
+    pub usage: Option<ResourceUsage>,
+    /// 画面から読み取ったタスクの進捗 (最後に見えたもの)
+    #[serde(skip)]
+    pub progress: Option<TaskProgress>,
+    /// コンテキストの残り (画面に警告が出ているときだけ)
+    #[serde(skip)]
+    pub context: Option<ContextPressure>,
+    /// 進行中の Working 区間の開始時刻 (クラッシュしても作業時間を失わないよう保存する)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_since: Option<DateTime<Utc>>,
+    /// 最後に Working を確認した時刻 (定期的に保存し、次の起動時に区間の終わりとみなす)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_seen: Option<DateTime<Utc>>,
+    /// `[[watch]]` (action = "error") が一致した watcher の名前。Detail を開くまで Error 扱いにする
+    #[serde(skip)]
+    pub alert: Option<String>,
+}
+
+/// プロセスツリー全体の CPU / メモリ使用量
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceUsage {
+    pub cpu_percent: f32,
+    pub rss_kb: u64,
+}
+
+impl ResourceUsage {
+    /// "12% 340M" 形式
+    pub fn format(&self) -> String {
+        let mb = self.rss_kb / 1024;
+        let mem = if mb >= 1024 {
+            format!("{:.1}G", mb as f64 / 1024.0)
+        } else {
+            format!("{}M", mb)
+        };
+        format!("{:.0}% {}", self.cpu_percent, mem)
+    }
 }
 
 impl Member {
-    pub fn status_icon(&self) -> &str {
-        self.status.icon()
+    /// ペインを担当する Idle のメンバー (出力・作業時間などは空から始める)
+    pub fn new(role: &str, pane: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            status: MemberStatus::Idle,
+            tmux_pane: pane.to_string(),
+            last_change: Utc::now(),
+            last_output: String::new(),
+            last_output_ansi: String::new(),
+            pane_size: (80, 24),
+            last_polled: None,
+            working_secs: 0,
+            sub_agents: Vec::new(),
+            usage: None,
+            progress: None,
+            context: None,
+            working_since: None,
+            working_seen: None,
+            alert: None,
+        }
+    }
+
+    pub fn status_icon(&self, set: IconSet) -> &'static str {
+        self.status.icon(set)
     }
 
     pub fn elapsed(&self) -> String {
@@ -98,6 +523,57 @@ impl Member {
     pub fn sub_agent_count(&self) -> usize {
         self.sub_agents.len()
     }
+
+    /// 確定した作業時間 + 進行中の Working 区間 (秒)
+    pub fn live_working_secs(&self) -> u64 {
+        let current = if self.status == MemberStatus::Working {
+            let since = self.working_since.unwrap_or(self.last_change);
+            Utc::now().signed_duration_since(since).num_seconds().max(0) as u64
+        } else {
+            0
+        };
+        self.working_secs + current
+    }
+
+    /// 状態を変える。Working を抜けるときは区間の長さを working_secs に加算し、
+    /// Working に入るときは区間の開始時刻を記録する
+    pub fn set_status(&mut self, status: MemberStatus) {
+        if status == self.status {
+            return;
+        }
+        let now = Utc::now();
+        if self.status == MemberStatus::Working {
+            let since = self.working_since.unwrap_or(self.last_change);
+            self.working_secs += now.signed_duration_since(since).num_seconds().max(0) as u64;
+            self.working_since = None;
+            self.working_seen = None;
+        }
+        if status == MemberStatus::Working {
+            self.working_since = Some(now);
+            self.working_seen = Some(now);
+        }
+        self.status = status;
+        self.last_change = now;
+    }
+
+    /// 前回のプロセスから引き継いだ Working 区間を確定する (起動時の読み込みで呼ぶ)。
+    /// 最後に Working を確認した時刻までを作業時間に加え、まだ Working なら今から数え直す。
+    /// 止まっていた間は数えない
+    pub fn reconcile_working_span(&mut self, now: DateTime<Utc>) -> bool {
+        let Some(since) = self.working_since else {
+            return false;
+        };
+        let seen = self.working_seen.unwrap_or(since).max(since);
+        self.working_secs += seen.signed_duration_since(since).num_seconds().max(0) as u64;
+        if self.status == MemberStatus::Working {
+            self.working_since = Some(now);
+            self.working_seen = Some(now);
+        } else {
+            self.working_since = None;
+            self.working_seen = None;
+        }
+        true
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,9 +590,213 @@ pub struct Pod {
     pub created_at: DateTime<Utc>,
     #[serde(default)]
     pub total_working_secs: u64,
+    /// グリッド先頭行に固定表示する
+    #[serde(default)]
+    pub pinned: bool,
+    /// 自由形式のタグ (グループを横断するラベル)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 用途などの自由メモ
+    #[serde(default)]
+    pub note: String,
+    /// 次に作業が終わったときに一度だけ実行するアクション (`apiary create --on-done`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_done: Option<OnDone>,
+    /// 作成元の GitHub issue ("owner/repo#123")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// `apiary pr` / `P` で作成した PR の URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr_url: Option<String>,
+    /// Docker コンテナの中で Claude を動かしている場合のコンテナ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<Sandbox>,
+    /// リードメンバーの作業ディレクトリの git ブランチ (定期的に更新、保存しない)
+    #[serde(skip)]
+    pub branch: Option<String>,
+    /// リードメンバーの Claude Code のセッション ID (最新の transcript から特定する)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// セッションの TODO リスト (~/.claude/todos、定期的に更新、保存しない)
+    #[serde(skip)]
+    pub todos: Option<crate::todos::TodoList>,
+    /// 最後に送った指示 (作成時のプロンプトや Chat のメッセージ)。カードの 2 行目に出す
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_prompt: Option<String>,
+    /// 利用上限のリセット後に自動で再開するか (None なら設定 `[rate_limit] auto_resume` に従う)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_resume: Option<bool>,
+    /// 利用上限のリセット時刻 (RateLimited の間だけ。再起動しても自動再開できるよう保存する)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_reset: Option<DateTime<Utc>>,
+    /// 優先度 (`apiary create --priority`、TUI の `o` で切り替え)
+    #[serde(default, skip_serializing_if = "is_normal_priority")]
+    pub priority: PodPriority,
+    /// アクセントカラー (ACCENT_COLORS の名前か #rrggbb)。None なら名前から決める
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    /// この時刻まで通知を出さない (TUI の `m`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub muted_until: Option<DateTime<Utc>>,
+    /// ペイン出力をログに書き続けるか (None なら設定 `[logs] enabled` に従う)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<bool>,
+    /// tmux セッションに渡した環境変数 (`apiary create --env` / テンプレートの env)。restore でも使う。
+    /// 値は秘密情報でありうるので pods.json にはキーだけを書き、値は pod-env.json (0600) に置く
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty", serialize_with = "crate::store::secrets::keys_only")]
+    pub env: BTreeMap<String, String>,
+    /// 作成に使った `[templates.<name>]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// テンプレートから生成した `claude --settings` のファイル
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<PathBuf>,
+    /// 使っているモデル (`--model` / `/model` で指定したもの、または transcript から読んだもの)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// tmux サーバーごと止まって (再起動など) セッションを失った Dead Pod。
+    /// gc / reaper では片付けず、`apiary resurrect` (TUI の U) で作り直せる
+    #[serde(default)]
+    pub server_lost: bool,
+    /// 最後にアタッチした時刻 (TUI の t / T / Enter、apiary popup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_attached: Option<DateTime<Utc>>,
+    /// 最後に入力を送った時刻 (指示、Detail のキー転送、許可への応答、スラッシュコマンド)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_input: Option<DateTime<Utc>>,
+}
+
+fn is_normal_priority(p: &PodPriority) -> bool {
+    *p == PodPriority::Normal
+}
+
+/// メンバーのいない Idle の Solo Pod (テストのフィクスチャは必要なフィールドだけ上書きする)
+impl Default for Pod {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            pod_type: PodType::Solo,
+            members: Vec::new(),
+            status: PodStatus::Idle,
+            tmux_session: String::new(),
+            project: None,
+            group: None,
+            created_at: Utc::now(),
+            total_working_secs: 0,
+            pinned: false,
+            tags: Vec::new(),
+            note: String::new(),
+            on_done: None,
+            issue: None,
+            pr_url: None,
+            sandbox: None,
+            branch: None,
+            session_id: None,
+            todos: None,
+            last_prompt: None,
+            auto_resume: None,
+            rate_limit_reset: None,
+            priority: PodPriority::Normal,
+            accent: None,
+            muted_until: None,
+            logs: None,
+            env: BTreeMap::new(),
+            template: None,
+            settings: None,
+            model: None,
+            server_lost: false,
+            last_attached: None,
+            last_input: None,
+        }
+    }
 }
 
+#[cfg(test)]
 impl Pod {
+    /// テスト用: 同名の tmux セッションで動く Pod
+    pub(crate) fn named(name: &str) -> Self {
+        Self { name: name.to_string(), tmux_session: name.to_string(), ..Default::default() }
+    }
+}
+
+impl Pod {
+    /// 通知を消音中か
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        self.muted_until.is_some_and(|t| now < t)
+    }
+
+    /// カードの枠や switcher に使うアクセントカラー。指定が無ければ名前のハッシュでパレットから選ぶ
+    pub fn accent_rgb(&self) -> (u8, u8, u8) {
+        if let Some(rgb) = self.accent.as_deref().and_then(parse_accent) {
+            return rgb;
+        }
+        // FNV-1a (実行ごと・バージョンごとに変わらないように std のハッシュは使わない)
+        let hash = self.name.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+        ACCENT_COLORS[(hash % ACCENT_COLORS.len() as u64) as usize].1
+    }
+
+    /// 最後に状態が変化した時刻 (メンバーがいなければ作成時刻)
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        self.members
+            .iter()
+            .map(|m| m.last_change)
+            .max()
+            .unwrap_or(self.created_at)
+    }
+
+    /// セッションが無くなった Pod を Dead にする。server_up でなければ server_lost も付ける。
+    /// 状態が変わったら true
+    pub fn mark_dead(&mut self, server_up: bool) -> bool {
+        if self.status == PodStatus::Dead {
+            return false;
+        }
+        self.status = PodStatus::Dead;
+        for member in &mut self.members {
+            member.status = MemberStatus::Dead;
+        }
+        self.server_lost = !server_up;
+        true
+    }
+
+    /// 最後に使った時刻 (アタッチか入力の新しい方)。switcher の「最近使った順」に使う
+    pub fn last_used(&self) -> Option<DateTime<Utc>> {
+        self.last_attached.max(self.last_input)
+    }
+
+    /// 入力を送ったことを記録する
+    pub fn touch_input(&mut self) {
+        self.last_input = Some(Utc::now());
+    }
+
+    /// 送った指示を記録する (改行や連続する空白は 1 つの空白にまとめる)
+    pub fn record_prompt(&mut self, prompt: &str) {
+        let prompt = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !prompt.is_empty() {
+            self.last_prompt = Some(prompt);
+        }
+        self.touch_input();
+    }
+
+    /// リードメンバーの出力のうち最後の空でない行 (前後の空白は除く)
+    pub fn last_output_line(&self) -> &str {
+        self.members
+            .first()
+            .and_then(|m| m.last_output.lines().rev().find(|l| !l.trim().is_empty()))
+            .unwrap_or("")
+            .trim()
+    }
+
+    /// 全メンバーの使用量合計 (未サンプルなら None)
+    pub fn total_usage(&self) -> Option<ResourceUsage> {
+        self.members
+            .iter()
+            .filter_map(|m| m.usage)
+            .reduce(|a, b| ResourceUsage {
+                cpu_percent: a.cpu_percent + b.cpu_percent,
+                rss_kb: a.rss_kb + b.rss_kb,
+            })
+    }
+
     pub fn rollup_status(&mut self) {
         if self.members.is_empty() {
             self.status = PodStatus::Idle;
@@ -137,8 +817,10 @@ impl Pod {
             .unwrap_or(0);
 
         self.status = match max_priority {
-            4 => PodStatus::Permission,
-            3 => PodStatus::Error,
+            6 => PodStatus::Permission,
+            5 => PodStatus::AwaitingInput,
+            4 => PodStatus::Error,
+            3 => PodStatus::RateLimited,
             2 => PodStatus::Working,
             1 => PodStatus::Idle,
             _ => PodStatus::Done,
@@ -153,15 +835,8 @@ impl Pod {
         self.members.push(member);
     }
 
-    pub fn status_icon(&self) -> &str {
-        match self.status {
-            PodStatus::Permission => "\u{26a0}",
-            PodStatus::Error => "\u{274c}",
-            PodStatus::Working => "\u{1f504}",
-            PodStatus::Idle => "\u{23f8}",
-            PodStatus::Done => "\u{2705}",
-            PodStatus::Dead => "\u{1f480}",
-        }
+    pub fn status_icon(&self, set: IconSet) -> &'static str {
+        self.status.icon(set)
     }
 
     /// 全 member の working 秒数の合計
@@ -169,6 +844,11 @@ impl Pod {
         self.members.iter().map(|m| m.working_secs).sum::<u64>() + self.total_working_secs
     }
 
+    /// 進行中の Working 区間も含めた作業時間 (描画のたびに進む)
+    pub fn live_working_time(&self) -> u64 {
+        self.members.iter().map(|m| m.live_working_secs()).sum::<u64>() + self.total_working_secs
+    }
+
     /// 全 member の sub_agents 合計数
     pub fn total_sub_agents(&self) -> usize {
         self.members.iter().map(|m| m.sub_agent_count()).sum()
@@ -189,6 +869,65 @@ pub enum Mode {
     Help,
 }
 
+/// 右ペインの Pod 表示形式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    /// カードグリッド (プレビュー付き)
+    #[default]
+    Grid,
+    /// 1 Pod 1 行のテーブル
+    List,
+    /// ステータス別カラムのボード
+    Kanban,
+}
+
+impl ViewMode {
+    /// 次の表示形式 (v キーで巡回)
+    pub fn next(self) -> Self {
+        match self {
+            ViewMode::Grid => ViewMode::List,
+            ViewMode::List => ViewMode::Kanban,
+            ViewMode::Kanban => ViewMode::Grid,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewMode::Grid => "grid",
+            ViewMode::List => "list",
+            ViewMode::Kanban => "kanban",
+        }
+    }
+}
+
+/// Context Panel と Pod 表示の並べ方
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PaneLayout {
+    /// 左右に並べる (左: Context Panel)
+    #[default]
+    Vertical,
+    /// 上下に並べる (下: Context Panel)。横長・背の低い端末向け
+    Horizontal,
+}
+
+impl PaneLayout {
+    pub fn toggle(self) -> Self {
+        match self {
+            PaneLayout::Vertical => PaneLayout::Horizontal,
+            PaneLayout::Horizontal => PaneLayout::Vertical,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaneLayout::Vertical => "side by side",
+            PaneLayout::Horizontal => "stacked",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PaneFocus {
     Left,
@@ -198,15 +937,48 @@ pub enum PaneFocus {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InlinePrompt {
     None,
-    AdoptSession,
     DropConfirm(String),
     Browse,
+    /// Pod のタグ編集 (Pod 名)
+    EditTags(String),
+    /// タグフィルタ入力
+    TagFilter,
+    /// Pod のメモ編集 (Pod 名)
+    EditNote(String),
+    /// Pod のグループ変更 (Pod 名)
+    MovePod(String),
+    /// グループの全 Pod を drop する確認 (グループ名)
+    DropGroupConfirm(String),
+    /// 選択中の Pod をまとめて drop する確認
+    BulkDropConfirm,
+    /// 選択中の Pod をまとめて forget する確認
+    BulkForgetConfirm,
+    /// 選択中の Pod をフォーカス中の Pod に合流させる確認
+    BulkMergeConfirm,
+    /// 選択中の Pod 全てにメッセージを送信
+    Broadcast,
+    /// 選択中の Pod のグループを変更 (空で解除)
+    MoveToGroup,
+    /// ペインを Pod の member として追加 (Pod 名)
+    AddMember(String),
+    /// member を独立した Pod にする (Pod 名)
+    SplitMember(String),
+    /// member の名前を変更 (Pod 名)
+    RenameMember(String),
+    /// リードメンバーに /clear を送る確認 (Pod 名)
+    ClearConfirm(String),
+    /// リードメンバーに /model を送ってモデルを切り替え (Pod 名)
+    SwitchModel(String),
 }
 
 #[derive(Debug, Clone)]
 pub struct BrowserEntry {
     pub name: String,
     pub is_dir: bool,
+    /// git リポジトリ (か worktree) のトップ
+    pub is_repo: bool,
+    /// このディレクトリを登録しているプロジェクト名
+    pub project: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -215,13 +987,36 @@ pub struct BrowserState {
     pub entries: Vec<BrowserEntry>,
     pub selected: usize,
     pub scroll_offset: usize,
+    /// ドットで始まるエントリも出す (. で切り替え)
+    pub show_hidden: bool,
+    /// 下端の入力欄 (n の新規ディレクトリ / : のパス入力)
+    pub input: Option<BrowserInput>,
+    pub input_text: crate::tui::input::LineEditor,
+    /// 最近のワークスペース (1-9 で選ぶ)
+    pub recent: Vec<crate::project::Project>,
+}
+
+/// ブラウザの入力欄で入力しているもの
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserInput {
+    /// 今のディレクトリに作るディレクトリ名
+    NewDir,
+    /// 移動先のパス (~/ と今のディレクトリからの相対パス可、Tab で補完)
+    GoTo,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
+    /// "you" か、応答したメンバーの role
     pub sender: String,
     pub content: String,
     pub timestamp: DateTime<Utc>,
+    /// やり取りした Pod
+    #[serde(default)]
+    pub pod: String,
+    /// やり取りしたメンバーの role
+    #[serde(default)]
+    pub member: String,
 }
 
 #[derive(Debug, Clone)]
@@ -231,17 +1026,63 @@ pub struct AppState {
     pub selected_member: Option<usize>,
     pub mode: Mode,
     pub command_input: String,
-    pub chat_input: String,
+    pub chat_input: crate::tui::input::LineEditor,
     pub chat_history: Vec<ChatMessage>,
-    pub capture_snapshot: Option<String>,
+    /// Chat で送信した時点のメンバーごとの画面 (ペイン ID → capture、応答の差分検出に使う)
+    pub chat_snapshots: HashMap<String, String>,
+    /// Chat の応答を Markdown として描画せず原文のまま出す (Ctrl+R で切り替え)
+    pub chat_raw: bool,
+    /// Chat 履歴を末尾から何行さかのぼって表示しているか (0 なら最新に追従)
+    pub chat_scroll: usize,
+    /// さかのぼっている間に届いた応答の数 ("N new messages" 表示用)
+    pub chat_unread: usize,
     pub grid_columns: usize,
+    pub view_mode: ViewMode,
+    /// 端末幅が狭い (左右ペインを並べず、Pod は 1 列リストで表示する)
+    pub narrow: bool,
+    /// 左ペインを隠して Pod 表示に全幅を使う (\ で切り替え)
+    pub left_pane_hidden: bool,
+    /// おやすみモード: すべての Pod の通知・ベルを止める (TUI の `Q`)
+    pub dnd: bool,
+    /// ペインの並べ方 (L で切り替え)
+    pub layout: PaneLayout,
+    /// g を押して次の数字 (グループ番号) を待っている
+    pub pending_group_jump: bool,
+    /// グリッドで折りたたまれているグループ名
+    pub collapsed_groups: HashSet<String>,
+    /// タグフィルタ (設定中はこのタグを持つ Pod のみ表示)
+    pub tag_filter: Option<String>,
+    /// グリッドをワークスペース (current_project) の Pod に絞る
+    pub workspace_filter: bool,
+    /// 最近ワークスペースにしたプロジェクト名 (新しい順、`w` と `p` の先頭に出す)
+    pub recent_workspaces: Vec<String>,
+    /// どの Pod にも属さない Claude Code セッション
+    pub unmanaged_sessions: Vec<discovery::UnmanagedSession>,
     pub should_quit: bool,
     pub status_message: Option<String>,
+    /// アクセシビリティモードで最下行に出す直近の状態変化
+    pub announcement: Option<String>,
+    /// 予算の警告 (しきい値を超えている間ステータスバーに出し続ける)
+    pub budget_warning: Option<String>,
     pub current_permission: Option<crate::pod::detector::PermissionRequest>,
     pub previous_permission_pods: HashSet<String>,
     pub previous_mode: Option<Mode>,
     pub inline_prompt: InlinePrompt,
-    pub inline_input: String,
+    pub inline_input: crate::tui::input::LineEditor,
+    /// 左ペイン入力の補完ポップアップ (/command, @project)
+    pub completion: Option<crate::tui::input::Completion>,
+    /// グループ未指定の Pod をプロジェクト名でまとめる (config の ui.group_by_project)
+    pub group_by_project: bool,
+    /// 一括操作用に選択中の Pod 名 (Space で切り替え)
+    pub selected_pods: HashSet<String>,
+    /// Ctrl+P のコマンドパレット (開いている間は全キーをここで処理)
+    pub palette: Option<crate::tui::palette::Palette>,
+    /// Ctrl+F の Pod スイッチャー (開いている間は全キーをここで処理)
+    pub switcher: Option<crate::tui::switcher::PodSwitcher>,
+    /// `w` のワークスペーススイッチャー (開いている間は全キーをここで処理)
+    pub workspace_switcher: Option<crate::tui::workspaces::WorkspaceSwitcher>,
+    /// `a` のセッションピッカー (開いている間は全キーをここで処理)
+    pub adopt_picker: Option<crate::tui::adopt::AdoptPicker>,
     pub pane_focus: PaneFocus,
     pub browser_state: Option<BrowserState>,
     pub current_project: Option<crate::project::Project>,
@@ -251,6 +1092,12 @@ pub struct AppState {
     pub detail_just_resized: bool,
 }
 
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AppState {
     pub fn new() -> Self {
         Self {
@@ -259,17 +1106,40 @@ impl AppState {
             selected_member: None,
             mode: Mode::Home,
             command_input: String::new(),
-            chat_input: String::new(),
+            chat_input: crate::tui::input::LineEditor::new(),
             chat_history: Vec::new(),
-            capture_snapshot: None,
+            chat_snapshots: HashMap::new(),
+            chat_raw: false,
+            chat_scroll: 0,
+            chat_unread: 0,
             grid_columns: 3,
+            view_mode: ViewMode::Grid,
+            narrow: false,
+            left_pane_hidden: false,
+            dnd: false,
+            layout: PaneLayout::Vertical,
+            pending_group_jump: false,
+            collapsed_groups: HashSet::new(),
+            tag_filter: None,
+            workspace_filter: false,
+            recent_workspaces: Vec::new(),
+            unmanaged_sessions: Vec::new(),
             should_quit: false,
             status_message: None,
+            announcement: None,
+            budget_warning: None,
             current_permission: None,
             previous_permission_pods: HashSet::new(),
             previous_mode: None,
             inline_prompt: InlinePrompt::None,
-            inline_input: String::new(),
+            inline_input: crate::tui::input::LineEditor::new(),
+            completion: None,
+            group_by_project: false,
+            selected_pods: HashSet::new(),
+            palette: None,
+            switcher: None,
+            workspace_switcher: None,
+            adopt_picker: None,
             pane_focus: PaneFocus::Right,
             browser_state: None,
             current_project: None,
@@ -278,6 +1148,11 @@ impl AppState {
         }
     }
 
+    /// 実際に描画する表示形式 (狭い端末では常に List)
+    pub fn effective_view_mode(&self) -> ViewMode {
+        if self.narrow { ViewMode::List } else { self.view_mode }
+    }
+
     pub fn focused_pod(&self) -> Option<&Pod> {
         self.focus.and_then(|i| self.pods.get(i))
     }
@@ -292,6 +1167,147 @@ impl AppState {
             .position(|p| p.status == PodStatus::Permission)
     }
 
+    /// タグフィルタとワークスペースの絞り込みに一致するか (どちらも未設定なら常に true)
+    pub fn is_visible(&self, idx: usize) -> bool {
+        let Some(pod) = self.pods.get(idx) else {
+            return false;
+        };
+        self.tag_filter.as_ref().is_none_or(|tag| pod.tags.iter().any(|t| t == tag))
+            && (!self.workspace_filter || self.in_workspace(pod))
+    }
+
+    /// Pod がワークスペースのプロジェクトのものか (ワークスペース未設定なら true)
+    pub fn in_workspace(&self, pod: &Pod) -> bool {
+        self.current_project.as_ref().is_none_or(|p| pod.project.as_deref() == Some(p.name.as_str()))
+    }
+
+    /// 表示対象の Pod index を表示順に返す (優先度の高い順、同じ優先度なら追加順)
+    pub fn visible_pods(&self) -> Vec<usize> {
+        let mut visible: Vec<usize> = (0..self.pods.len()).filter(|&i| self.is_visible(i)).collect();
+        visible.sort_by_key(|&i| self.pods[i].priority.rank());
+        visible
+    }
+
+    /// Pod が折りたたまれたグループに属しているか (Dead / ピン留め Pod はグループ外に表示されるので対象外)
+    pub fn is_in_collapsed_group(&self, idx: usize) -> bool {
+        self.pods.get(idx).is_some_and(|p| {
+            p.status != PodStatus::Dead
+                && !p.pinned
+                && self.display_group(p).is_some_and(|g| self.collapsed_groups.contains(g))
+        })
+    }
+
+    /// グリッド上でフォーカス可能な Pod index を表示順に返す
+    /// (ピン留め → グループ → 非グループ → Dead。折りたたまれたグループは先頭 Pod のみ)
+    pub fn grid_navigable_pods(&self) -> Vec<usize> {
+        let mut pinned = Vec::new();
+        let mut group_order: Vec<&str> = Vec::new();
+        let mut grouped: Vec<(usize, &str)> = Vec::new();
+        let mut ungrouped = Vec::new();
+        let mut dead = Vec::new();
+        for i in self.visible_pods() {
+            let pod = &self.pods[i];
+            if pod.pinned {
+                pinned.push(i);
+            } else if pod.status == PodStatus::Dead {
+                dead.push(i);
+            } else if let Some(group) = self.display_group(pod) {
+                if !group_order.contains(&group) {
+                    group_order.push(group);
+                }
+                grouped.push((i, group));
+            } else {
+                ungrouped.push(i);
+            }
+        }
+
+        let mut result = pinned;
+        for group in group_order {
+            let members = grouped.iter().filter(|(_, g)| *g == group).map(|(i, _)| *i);
+            if self.collapsed_groups.contains(group) {
+                result.extend(members.take(1));
+            } else {
+                result.extend(members);
+            }
+        }
+        result.extend(ungrouped);
+        result.extend(dead);
+        result
+    }
+
+    /// グリッドに表示されるグループ名を表示順に返す (ピン留め・Dead Pod は含めない)
+    pub fn group_order(&self) -> Vec<String> {
+        let mut order: Vec<String> = Vec::new();
+        for i in self.visible_pods() {
+            let pod = &self.pods[i];
+            if pod.pinned || pod.status == PodStatus::Dead {
+                continue;
+            }
+            if let Some(group) = self.display_group(pod) {
+                if !order.iter().any(|g| g == group) {
+                    order.push(group.to_string());
+                }
+            }
+        }
+        order
+    }
+
+    /// 数字キー (1-9) で飛べる Pod index を表示順に返す (先頭 9 件)
+    pub fn quick_focus_targets(&self) -> Vec<usize> {
+        let targets = match self.effective_view_mode() {
+            ViewMode::Grid => self.grid_navigable_pods(),
+            ViewMode::List => self.visible_pods(),
+            ViewMode::Kanban => self.kanban_columns().into_iter().flat_map(|(_, pods)| pods).collect(),
+        };
+        targets.into_iter().take(9).collect()
+    }
+
+    /// Kanban 表示用: `KANBAN_COLUMNS` の順に各ステータスの Pod index を返す
+    pub fn kanban_columns(&self) -> Vec<(PodStatus, Vec<usize>)> {
+        KANBAN_COLUMNS
+            .iter()
+            .map(|status| {
+                let indices = self
+                    .visible_pods()
+                    .into_iter()
+                    .filter(|&i| self.pods[i].status == *status)
+                    .collect();
+                (status.clone(), indices)
+            })
+            .collect()
+    }
+
+    /// 表示上のグループ: 明示的なグループ、なければ (group_by_project 有効時) プロジェクト名
+    pub fn display_group<'a>(&self, pod: &'a Pod) -> Option<&'a str> {
+        pod.group
+            .as_deref()
+            .or_else(|| pod.project.as_deref().filter(|_| self.group_by_project))
+    }
+
+    /// グループのロールアップ状態: メンバー Pod の最も優先度の高い状態
+    /// (全て Dead なら Dead、該当 Pod が無ければ None)
+    pub fn group_status(&self, group: &str) -> Option<PodStatus> {
+        let mut statuses = self
+            .pods
+            .iter()
+            .filter(|p| self.display_group(p) == Some(group))
+            .map(|p| &p.status)
+            .peekable();
+        statuses.peek()?;
+        statuses
+            .filter(|s| **s != PodStatus::Dead)
+            .max_by_key(|s| s.priority())
+            .cloned()
+            .or(Some(PodStatus::Dead))
+    }
+
+    /// グループ内で最初の要対応 Pod (Permission / Error) の index
+    pub fn first_warning_in_group(&self, group: &str) -> Option<usize> {
+        self.pods
+            .iter()
+            .position(|p| self.display_group(p) == Some(group) && p.status.is_warning())
+    }
+
     pub fn pods_summary(&self) -> (usize, usize, usize) {
         let total_pods = self.pods.len();
         let permission_count = self
@@ -304,6 +1320,62 @@ impl AppState {
     }
 }
 
+/// 前回の状態から変わった Pod と、その前回の状態を列挙する (前回に無かった Pod は対象外)
+pub fn status_changes<'a>(previous: &HashMap<String, PodStatus>, pods: &'a [Pod]) -> Vec<(&'a Pod, PodStatus)> {
+    pods.iter()
+        .filter_map(|pod| {
+            let before = previous.get(&pod.name)?;
+            (*before != pod.status).then(|| (pod, before.clone()))
+        })
+        .collect()
+}
+
+/// `apiary status` の終了コード: 0 = 全て Idle / Done (または Pod なし), 2 = Working / RateLimited あり,
+/// 3 = Permission 待ち・返事待ちあり, 4 = Error / Dead あり (複数該当時は大きい方)
+pub fn status_exit_code(pods: &[Pod]) -> i32 {
+    pods.iter()
+        .map(|p| match p.status {
+            PodStatus::Idle | PodStatus::Done => 0,
+            PodStatus::Working | PodStatus::RateLimited => 2,
+            PodStatus::Permission | PodStatus::AwaitingInput => 3,
+            PodStatus::Error | PodStatus::Dead => 4,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// タグ入力をパース (カンマ/空白区切り、先頭の # は除去、重複は除外)
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for raw in input.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = raw.trim().trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// カードに出すモデル名 ("claude-opus-4-1-20250805" → "opus-4-1")
+pub fn short_model_name(model: &str) -> &str {
+    let model = model.strip_prefix("claude-").unwrap_or(model);
+    match model.rsplit_once('-') {
+        Some((name, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => model,
+    }
+}
+
+/// `KEY=VAL` をパース (キーは英数字と _ で、数字から始まらない)
+pub fn parse_env_var(input: &str) -> Result<(String, String), String> {
+    let (key, value) = input.split_once('=').ok_or_else(|| format!("expected KEY=VALUE, got '{}'", input))?;
+    let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("invalid environment variable name '{}'", key));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
 pub fn format_duration(secs: u64) -> String {
     if secs < 60 {
         format!("{}s", secs)
@@ -318,7 +1390,7 @@ pub fn format_duration(secs: u64) -> String {
     }
 }
 
-fn format_elapsed(since: DateTime<Utc>) -> String {
+pub fn format_elapsed(since: DateTime<Utc>) -> String {
     let duration = Utc::now().signed_duration_since(since);
     let seconds = duration.num_seconds();
 
@@ -332,3 +1404,239 @@ fn format_elapsed(since: DateTime<Utc>) -> String {
         format!("{}d", seconds / 86400)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod(name: &str, group: Option<&str>, status: PodStatus) -> Pod {
+        Pod {
+            name: name.to_string(),
+            status,
+            tmux_session: name.to_string(),
+            group: group.map(|g| g.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_prompt() {
+        let mut p = pod("a", None, PodStatus::Idle);
+        p.record_prompt("Fix the\n  login   bug");
+        assert_eq!(p.last_prompt.as_deref(), Some("Fix the login bug"));
+        // 空の入力では上書きしない
+        p.record_prompt("  \n");
+        assert_eq!(p.last_prompt.as_deref(), Some("Fix the login bug"));
+    }
+
+    #[test]
+    fn test_live_working_time() {
+        let member = |status: MemberStatus, working_secs: u64| Member {
+            status,
+            last_change: Utc::now() - chrono::Duration::seconds(90),
+            working_secs,
+            ..Member::new("lead", "%0")
+        };
+        let mut p = pod("a", None, PodStatus::Working);
+        p.total_working_secs = 10;
+        p.members = vec![member(MemberStatus::Working, 60), member(MemberStatus::Idle, 30)];
+        // 進行中の 90 秒も数える (確定済みの合計は 100 秒)
+        assert_eq!(p.total_working_time(), 100);
+        assert!((190..195).contains(&p.live_working_time()));
+    }
+
+    #[test]
+    fn test_working_span_survives_restart() {
+        let now = Utc::now();
+        let mut member = Member {
+            status: MemberStatus::Working,
+            last_change: now - chrono::Duration::hours(2),
+            working_secs: 10,
+            working_since: Some(now - chrono::Duration::hours(2)),
+            working_seen: Some(now - chrono::Duration::hours(2) + chrono::Duration::seconds(300)),
+            ..Member::new("lead", "%0")
+        };
+        // 保存された区間 (300 秒) だけ数え、止まっていた間は数えない
+        assert!(member.reconcile_working_span(now));
+        assert_eq!(member.working_secs, 310);
+        assert_eq!(member.working_since, Some(now));
+        assert!(member.live_working_secs() < 320);
+
+        member.set_status(MemberStatus::Idle);
+        assert_eq!(member.working_since, None);
+        assert!(!member.reconcile_working_span(now));
+        member.set_status(MemberStatus::Working);
+        assert!(member.working_since.is_some());
+    }
+
+    #[test]
+    fn test_group_status_rollup() {
+        let mut state = AppState::new();
+        state.pods = vec![
+            pod("a", Some("auth"), PodStatus::Idle),
+            pod("b", Some("auth"), PodStatus::Error),
+            pod("c", Some("auth"), PodStatus::Permission),
+            pod("d", Some("web"), PodStatus::Dead),
+            pod("e", Some("web"), PodStatus::Done),
+            pod("f", Some("old"), PodStatus::Dead),
+        ];
+
+        assert_eq!(state.group_status("auth"), Some(PodStatus::Permission));
+        assert_eq!(state.group_status("web"), Some(PodStatus::Done));
+        assert_eq!(state.group_status("old"), Some(PodStatus::Dead));
+        assert_eq!(state.group_status("none"), None);
+
+        assert_eq!(state.first_warning_in_group("auth"), Some(1));
+        assert_eq!(state.first_warning_in_group("web"), None);
+    }
+
+    #[test]
+    fn test_quick_focus_targets_and_group_order() {
+        let mut state = AppState::new();
+        state.pods = vec![
+            pod("solo", None, PodStatus::Idle),
+            pod("a1", Some("alpha"), PodStatus::Working),
+            pod("b1", Some("beta"), PodStatus::Idle),
+            pod("a2", Some("alpha"), PodStatus::Idle),
+        ];
+        assert_eq!(state.group_order(), vec!["alpha".to_string(), "beta".to_string()]);
+        // グリッドの表示順: グループ (alpha, beta) → 非グループ
+        assert_eq!(state.quick_focus_targets(), vec![1, 3, 2, 0]);
+
+        state.view_mode = ViewMode::List;
+        assert_eq!(state.quick_focus_targets(), vec![0, 1, 2, 3]);
+
+        state.pods = (0..12).map(|i| pod(&format!("p{}", i), None, PodStatus::Idle)).collect();
+        assert_eq!(state.quick_focus_targets().len(), 9);
+    }
+
+    #[test]
+    fn test_accent() {
+        assert_eq!(parse_accent("Teal"), Some((80, 175, 160)));
+        assert_eq!(parse_accent("#0a0B10"), Some((10, 11, 16)));
+        assert_eq!(parse_accent("#12345"), None);
+        assert_eq!(parse_accent("chartreuse"), None);
+
+        let mut p = pod("api", None, PodStatus::Idle);
+        let auto = p.accent_rgb();
+        assert_eq!(auto, pod("api", None, PodStatus::Working).accent_rgb());
+        assert!(ACCENT_COLORS.iter().any(|(_, rgb)| *rgb == auto));
+        p.accent = Some("#ffffff".to_string());
+        assert_eq!(p.accent_rgb(), (255, 255, 255));
+    }
+
+    #[test]
+    fn test_priority_ordering() {
+        let mut state = AppState::new();
+        state.pods = vec![
+            pod("solo", None, PodStatus::Idle),
+            pod("a1", Some("alpha"), PodStatus::Working),
+            pod("b1", Some("beta"), PodStatus::Idle),
+            pod("later", None, PodStatus::Idle),
+        ];
+        state.pods[2].priority = PodPriority::High;
+        state.pods[0].priority = PodPriority::Low;
+        assert_eq!(state.visible_pods(), vec![2, 1, 3, 0]);
+        // High の Pod を含むグループが先に来る
+        assert_eq!(state.group_order(), vec!["beta".to_string(), "alpha".to_string()]);
+        assert_eq!(state.grid_navigable_pods(), vec![2, 1, 3, 0]);
+        assert_eq!(PodPriority::Normal.cycle().cycle().cycle(), PodPriority::Normal);
+    }
+
+    #[test]
+    fn test_icon_sets() {
+        for status in KANBAN_COLUMNS {
+            assert!(status.icon(IconSet::Ascii).is_ascii());
+            assert_eq!(status.icon(IconSet::Nerdfont).width(), 1);
+        }
+        assert_eq!(MemberStatus::Working.icon(IconSet::Ascii), PodStatus::Working.icon(IconSet::Ascii));
+        assert_eq!(IconSet::Ascii.width(), 6);
+        assert_eq!(IconSet::Ascii.pad("[ERR]"), "[ERR] ");
+    }
+
+    #[test]
+    fn test_status_changes() {
+        let previous: HashMap<String, PodStatus> = [
+            ("a".to_string(), PodStatus::Working),
+            ("b".to_string(), PodStatus::Idle),
+        ]
+        .into_iter()
+        .collect();
+        let pods = vec![
+            pod("a", None, PodStatus::Permission),
+            pod("b", None, PodStatus::Idle),
+            pod("new", None, PodStatus::Working),
+        ];
+        let changes = status_changes(&previous, &pods);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0.name, "a");
+        assert_eq!(changes[0].1, PodStatus::Working);
+    }
+
+    #[test]
+    fn test_on_done_serde() {
+        let json = serde_json::to_string(&OnDone::Run("cargo test".to_string())).unwrap();
+        assert_eq!(json, r#"{"run":"cargo test"}"#);
+        assert_eq!(serde_json::from_str::<OnDone>(r#""commit""#).unwrap(), OnDone::Commit);
+        // 未設定の Pod は on_done を書き出さない
+        let json = serde_json::to_string(&pod("a", None, PodStatus::Idle)).unwrap();
+        assert!(!json.contains("on_done"));
+    }
+
+    #[test]
+    fn test_status_exit_code() {
+        assert_eq!(status_exit_code(&[]), 0);
+        assert_eq!(status_exit_code(&[pod("a", None, PodStatus::Idle), pod("b", None, PodStatus::Done)]), 0);
+        assert_eq!(status_exit_code(&[pod("a", None, PodStatus::Idle), pod("b", None, PodStatus::Working)]), 2);
+        assert_eq!(status_exit_code(&[pod("a", None, PodStatus::Permission), pod("b", None, PodStatus::Working)]), 3);
+        assert_eq!(status_exit_code(&[pod("a", None, PodStatus::Permission), pod("b", None, PodStatus::Dead)]), 4);
+    }
+
+    #[test]
+    fn test_effective_view_mode_is_list_when_narrow() {
+        let mut state = AppState::new();
+        state.view_mode = ViewMode::Kanban;
+        assert_eq!(state.effective_view_mode(), ViewMode::Kanban);
+        state.narrow = true;
+        assert_eq!(state.effective_view_mode(), ViewMode::List);
+    }
+
+    #[test]
+    fn test_display_group_falls_back_to_project() {
+        let mut state = AppState::new();
+        let mut web = pod("web-1", None, PodStatus::Idle);
+        web.project = Some("web".to_string());
+        state.pods = vec![web, pod("api-1", Some("api"), PodStatus::Idle), pod("loose", None, PodStatus::Idle)];
+
+        assert_eq!(state.display_group(&state.pods[0]), None);
+        state.group_by_project = true;
+        assert_eq!(state.display_group(&state.pods[0]), Some("web"));
+        assert_eq!(state.display_group(&state.pods[1]), Some("api"));
+        assert_eq!(state.display_group(&state.pods[2]), None);
+
+        state.collapsed_groups.insert("web".to_string());
+        assert!(state.is_in_collapsed_group(0));
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags("urgent, #experiment  urgent"), vec!["urgent", "experiment"]);
+        assert!(parse_tags(" , # ").is_empty());
+    }
+
+    #[test]
+    fn test_short_model_name() {
+        assert_eq!(short_model_name("claude-opus-4-1-20250805"), "opus-4-1");
+        assert_eq!(short_model_name("claude-3-5-haiku-20241022"), "3-5-haiku");
+        assert_eq!(short_model_name("sonnet"), "sonnet");
+    }
+
+    #[test]
+    fn test_parse_env_var() {
+        assert_eq!(parse_env_var("CLAUDE_CONFIG_DIR=/tmp/a=b"), Ok(("CLAUDE_CONFIG_DIR".to_string(), "/tmp/a=b".to_string())));
+        assert_eq!(parse_env_var("EMPTY="), Ok(("EMPTY".to_string(), String::new())));
+        assert!(parse_env_var("NOVALUE").is_err());
+        assert!(parse_env_var("1X=y").is_err());
+        assert!(parse_env_var("A-B=y").is_err());
+    }
+}
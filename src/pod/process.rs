@@ -0,0 +1,215 @@
+//! プロセスツリーによるエージェント検出
+//!
+//! 出力の正規表現だけでは静かなペイン (起動直後・長時間 Idle) を取りこぼすため、
+//! `#{pane_pid}` 配下の子プロセスを `ps` で辿り、`claude` などのエージェントが
+//! 実際に動いているかを確認する。
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::pod::ResourceUsage;
+use crate::tmux::Tmux;
+
+/// インタプリタ経由で起動される場合 (`node /path/to/claude`) に第 2 引数も見る
+const INTERPRETERS: &[&str] = &["node", "bun", "deno", "python", "python3"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub ppid: u32,
+    pub cpu_percent: f32,
+    pub rss_kb: u64,
+    pub args: String,
+}
+
+/// `ps -A -o pid=,ppid=,%cpu=,rss=,args=` の出力をパース
+pub fn parse_ps(output: &str) -> Vec<ProcessEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse().ok()?;
+            let ppid = parts.next()?.parse().ok()?;
+            let cpu_percent = parts.next()?.parse().ok()?;
+            let rss_kb = parts.next()?.parse().ok()?;
+            let args = parts.collect::<Vec<_>>().join(" ");
+            Some(ProcessEntry { pid, ppid, cpu_percent, rss_kb, args })
+        })
+        .collect()
+}
+
+/// root 自身とその子孫プロセス
+pub fn descendants(table: &[ProcessEntry], root: u32) -> Vec<&ProcessEntry> {
+    let mut children: HashMap<u32, Vec<&ProcessEntry>> = HashMap::new();
+    for entry in table {
+        children.entry(entry.ppid).or_default().push(entry);
+    }
+
+    let mut result: Vec<&ProcessEntry> = table.iter().filter(|e| e.pid == root).collect();
+    let mut stack = vec![root];
+    while let Some(pid) = stack.pop() {
+        for child in children.get(&pid).into_iter().flatten() {
+            // ppid == pid の異常エントリでループしないように
+            if child.pid != pid && !result.iter().any(|e| e.pid == child.pid) {
+                result.push(child);
+                stack.push(child.pid);
+            }
+        }
+    }
+    result
+}
+
+/// コマンドラインがエージェント (names のいずれか) の実行か
+pub fn is_agent_command(args: &str, names: &[String]) -> bool {
+    let basename = |token: &str| token.rsplit('/').next().unwrap_or(token).to_string();
+    let mut tokens = args.split_whitespace();
+    let first = match tokens.next() {
+        Some(t) => basename(t),
+        None => return false,
+    };
+    if names.contains(&first) {
+        return true;
+    }
+    if INTERPRETERS.contains(&first.as_str()) {
+        if let Some(script) = tokens.next() {
+            let script = basename(script);
+            return names.contains(&script);
+        }
+    }
+    false
+}
+
+/// root 配下でエージェントが動いているか
+pub fn has_agent_process(table: &[ProcessEntry], root: u32, names: &[String]) -> bool {
+    descendants(table, root)
+        .iter()
+        .any(|e| is_agent_command(&e.args, names))
+}
+
+/// root 配下のプロセスツリー全体の CPU / メモリ使用量
+pub fn tree_usage(table: &[ProcessEntry], root: u32) -> ResourceUsage {
+    descendants(table, root)
+        .iter()
+        .fold(ResourceUsage::default(), |acc, e| ResourceUsage {
+            cpu_percent: acc.cpu_percent + e.cpu_percent,
+            rss_kb: acc.rss_kb + e.rss_kb,
+        })
+}
+
+/// プロセス表・ペイン PID を必要になった時点で 1 回だけ取得するプローブ
+pub struct AgentProbe<'a> {
+    names: &'a [String],
+    table: Option<Option<Vec<ProcessEntry>>>,
+    pane_pids: Option<HashMap<String, u32>>,
+}
+
+impl<'a> AgentProbe<'a> {
+    pub fn new(names: &'a [String]) -> Self {
+        Self { names, table: None, pane_pids: None }
+    }
+
+    /// pid 配下でエージェントが動いているか (ps が使えない場合は None)
+    pub fn pid_has_agent(&mut self, pid: u32) -> Option<bool> {
+        if self.names.is_empty() {
+            return None;
+        }
+        let table = self.table.get_or_insert_with(snapshot).as_deref()?;
+        Some(has_agent_process(table, pid, self.names))
+    }
+
+    /// ペインでエージェントが動いているか (ペイン PID が分からなければ None)
+    pub fn pane_has_agent(&mut self, pane_id: &str) -> Option<bool> {
+        let pid = self.pane_pid(pane_id)?;
+        self.pid_has_agent(pid)
+    }
+
+    /// ペイン配下のプロセスツリーの使用量
+    pub fn pane_usage(&mut self, pane_id: &str) -> Option<ResourceUsage> {
+        let pid = self.pane_pid(pane_id)?;
+        let table = self.table.get_or_insert_with(snapshot).as_deref()?;
+        Some(tree_usage(table, pid))
+    }
+
+    fn pane_pid(&mut self, pane_id: &str) -> Option<u32> {
+        let pane_pids = self.pane_pids.get_or_insert_with(|| {
+            Tmux::list_all_panes()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|p| p.pid.map(|pid| (p.id, pid)))
+                .collect()
+        });
+        pane_pids.get(pane_id).copied()
+    }
+}
+
+/// 現在のプロセス表を取得
+fn snapshot() -> Option<Vec<ProcessEntry>> {
+    let output = Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,%cpu=,rss=,args="])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS_OUTPUT: &str = "    1     0  0.0   1024 /sbin/init
+  100     1  0.1   4096 tmux new-session -d -s work
+  200   100  0.0   2048 -zsh
+  300   200 12.5 204800 node /usr/local/lib/node_modules/@anthropic-ai/claude-code/cli.js
+  301   200 80.0 102400 /home/me/.claude/local/claude --resume
+  400   100  0.0   2048 -bash
+  401   400  0.0   8192 vim claude.md
+";
+
+    fn names() -> Vec<String> {
+        vec!["claude".to_string()]
+    }
+
+    #[test]
+    fn test_parse_ps() {
+        let table = parse_ps(PS_OUTPUT);
+        assert_eq!(table.len(), 7);
+        assert_eq!(
+            table[3],
+            ProcessEntry {
+                pid: 300,
+                ppid: 200,
+                cpu_percent: 12.5,
+                rss_kb: 204800,
+                args: "node /usr/local/lib/node_modules/@anthropic-ai/claude-code/cli.js".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_is_agent_command() {
+        assert!(is_agent_command("/home/me/.claude/local/claude --resume", &names()));
+        assert!(is_agent_command("node /usr/local/bin/claude", &names()));
+        assert!(!is_agent_command("vim claude.md", &names()));
+        assert!(!is_agent_command("", &names()));
+    }
+
+    #[test]
+    fn test_has_agent_process_walks_children() {
+        let table = parse_ps(PS_OUTPUT);
+        assert!(has_agent_process(&table, 200, &names()));
+        assert!(has_agent_process(&table, 301, &names()));
+        assert!(!has_agent_process(&table, 400, &names()));
+        assert!(!has_agent_process(&table, 999, &names()));
+    }
+
+    #[test]
+    fn test_tree_usage_sums_descendants() {
+        let table = parse_ps(PS_OUTPUT);
+        let usage = tree_usage(&table, 200);
+        assert!((usage.cpu_percent - 92.5).abs() < 0.01);
+        assert_eq!(usage.rss_kb, 2048 + 204800 + 102400);
+        assert_eq!(tree_usage(&table, 999), ResourceUsage::default());
+    }
+}
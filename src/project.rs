@@ -1,12 +1,82 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::store::secrets;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
     pub name: String,
     pub path: String,
+    /// Defaults for pods created in this project (`apiary project set`)
+    #[serde(default, skip_serializing_if = "ProjectDefaults::is_empty")]
+    pub defaults: ProjectDefaults,
+    /// Project this one is a git worktree of, when apiary created it for a pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_of: Option<String>,
+}
+
+/// Whether pods of a project share its working tree or each get a git worktree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorktreePolicy {
+    #[default]
+    Shared,
+    /// `git worktree add` a sibling directory and branch named after the pod
+    PerPod,
+}
+
+/// Settings every pod created for a project starts with.
+/// Layered under the template and explicit `create` flags
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectDefaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "is_shared")]
+    pub worktree: WorktreePolicy,
+    /// Values may be secrets: projects.json only keeps the keys, the values live in project-env.json (0600)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", serialize_with = "crate::store::secrets::keys_only")]
+    pub env: BTreeMap<String, String>,
+}
+
+fn is_shared(policy: &WorktreePolicy) -> bool {
+    *policy == WorktreePolicy::Shared
+}
+
+impl ProjectDefaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// One-line summary for `apiary project list` ("group: x, model: opus, ...")
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (key, value) in [("group", &self.group), ("template", &self.template), ("model", &self.model)] {
+            if let Some(value) = value {
+                parts.push(format!("{}: {}", key, value));
+            }
+        }
+        if self.worktree == WorktreePolicy::PerPod {
+            parts.push("worktree: per-pod".to_string());
+        }
+        if !self.env.is_empty() {
+            parts.push(format!("env: {}", self.env.keys().cloned().collect::<Vec<_>>().join(", ")));
+        }
+        parts.join(", ")
+    }
+}
+
+impl Project {
+    pub fn new(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self { name: name.into(), path: path.into(), defaults: ProjectDefaults::default(), worktree_of: None }
+    }
 }
 
 pub struct ProjectStore {
@@ -28,6 +98,16 @@ impl ProjectStore {
         Ok(Self { path })
     }
 
+    /// カスタムパスで ProjectStore を作成（テスト用）
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Where the values of the projects' default env live (projects.json only keeps the keys)
+    fn env_path(&self) -> PathBuf {
+        self.path.with_file_name("project-env.json")
+    }
+
     pub fn load(&self) -> Result<Vec<Project>> {
         if !self.path.exists() {
             return Ok(Vec::new());
@@ -40,13 +120,26 @@ impl ProjectStore {
             return Ok(Vec::new());
         }
 
-        let projects: Vec<Project> = serde_json::from_str(&content)
+        let mut projects: Vec<Project> = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse projects file: {:?}", self.path))?;
 
+        // Older projects.json kept the values inline; move them out
+        let inline_env = projects.iter().any(|p| secrets::has_values(&p.defaults.env));
+        let saved_env = secrets::load(&self.env_path());
+        for project in &mut projects {
+            secrets::fill(&mut project.defaults.env, saved_env.get(&project.name));
+        }
+        if inline_env {
+            self.save(&projects)?;
+        }
+
         Ok(projects)
     }
 
     pub fn save(&self, projects: &[Project]) -> Result<()> {
+        let env = projects.iter().map(|p| (p.name.clone(), p.defaults.env.clone())).collect();
+        secrets::save(&self.env_path(), &env)?;
+
         let content = serde_json::to_string_pretty(projects)
             .context("Failed to serialize projects")?;
 
@@ -75,6 +168,17 @@ impl ProjectStore {
         self.save(&projects)
     }
 
+    /// Replace the pod defaults of a registered project. Returns false if it is not registered
+    pub fn set_defaults(&self, name: &str, defaults: ProjectDefaults) -> Result<bool> {
+        let mut projects = self.load()?;
+        let Some(project) = projects.iter_mut().find(|p| p.name == name) else {
+            return Ok(false);
+        };
+        project.defaults = defaults;
+        self.save(&projects)?;
+        Ok(true)
+    }
+
     pub fn unregister(&self, name: &str) -> Result<bool> {
         let mut projects = self.load()?;
         let before = projects.len();
@@ -111,6 +215,155 @@ fn detect_git_root(path: &str) -> Option<String> {
     }
 }
 
+/// Create a git worktree of `repo` at `path` on `branch` (created from HEAD unless it exists)
+pub fn add_worktree(repo: &str, path: &Path, branch: &str) -> Result<()> {
+    let exists = Command::new("git")
+        .args(["-C", repo, "rev-parse", "--verify", "--quiet"])
+        .arg(format!("refs/heads/{}", branch))
+        .output()
+        .is_ok_and(|o| o.status.success());
+    let mut git = Command::new("git");
+    git.args(["-C", repo, "worktree", "add"]);
+    if exists {
+        git.arg(path).arg(branch);
+    } else {
+        git.args(["-b", branch]).arg(path);
+    }
+    let output = git.output().context("Failed to run git worktree add")?;
+    if !output.status.success() {
+        anyhow::bail!("git worktree add failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Remove the git worktree of `repo` at `path` (its branch is kept)
+pub fn remove_worktree(repo: &str, path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .args(["-C", repo, "worktree", "remove", "--force"])
+        .arg(path)
+        .output()
+        .context("Failed to run git worktree remove")?;
+    if !output.status.success() {
+        anyhow::bail!("git worktree remove failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Number of files with uncommitted changes (None if `path` is not a git repository)
+pub fn dirty_files(path: &str) -> Option<usize> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).lines().filter(|l| !l.trim().is_empty()).count())
+}
+
+/// Current git branch of the repository at `path` (short commit hash when detached)
+pub fn current_branch(path: &str) -> Option<String> {
+    let git = |args: &[&str]| -> Option<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let out = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if out.is_empty() { None } else { Some(out) }
+    };
+
+    match git(&["rev-parse", "--abbrev-ref", "HEAD"])?.as_str() {
+        "HEAD" => git(&["rev-parse", "--short", "HEAD"]),
+        branch => Some(branch.to_string()),
+    }
+}
+
+/// Whether `path` is the top of a git repository or worktree (has a `.git` directory or file)
+pub fn is_git_repo(path: &Path) -> bool {
+    path.join(".git").exists()
+}
+
+/// Branch checked out at `path`, read from `.git/HEAD` without running git so it is cheap
+/// enough to call while drawing (short commit hash when detached)
+pub fn head_branch(path: &Path) -> Option<String> {
+    let dot_git = path.join(".git");
+    let git_dir = if dot_git.is_file() {
+        // Worktrees and submodules point at their real git directory
+        let content = std::fs::read_to_string(&dot_git).ok()?;
+        path.join(content.trim().strip_prefix("gitdir:")?.trim())
+    } else {
+        dot_git
+    };
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string()),
+        None => Some(head.chars().take(7).collect()).filter(|h: &String| !h.is_empty()),
+    }
+}
+
+/// Expand a leading `~/` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if path == "~" => dirs::home_dir().unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Find git repositories under `root`, looking at most `depth` directories deep.
+/// Does not descend into a repository once found, nor into hidden directories or symlinks
+pub fn scan_repositories(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    scan_dir(root, depth, &mut found);
+    found.sort();
+    found
+}
+
+fn scan_dir(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if dir.join(".git").exists() {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type().is_ok_and(|t| t.is_dir()) {
+            scan_dir(&entry.path(), depth - 1, found);
+        }
+    }
+}
+
+/// Projects for the repositories found under `roots` that are not registered yet
+/// (by name or by path). Names come from the directory name; the first one found wins
+pub fn discover_projects(store: &ProjectStore, roots: &[PathBuf], depth: usize) -> Result<Vec<Project>> {
+    let mut projects = store.load()?;
+    let mut discovered = Vec::new();
+    for root in roots {
+        for repo in scan_repositories(root, depth) {
+            let path = repo.to_string_lossy().to_string();
+            let name = project_name_from_path(&path);
+            if projects.iter().any(|p| p.name == name || p.path == path) {
+                continue;
+            }
+            let project = Project::new(name, path);
+            projects.push(project.clone());
+            discovered.push(project);
+        }
+    }
+    Ok(discovered)
+}
+
 /// Derive project name from a directory path (last component)
 fn project_name_from_path(path: &str) -> String {
     std::path::Path::new(path)
@@ -160,10 +413,7 @@ pub fn resolve_project(store: &ProjectStore, input: &str) -> Result<Project> {
     }
 
     // Auto-register new project
-    let project = Project {
-        name: name.clone(),
-        path: project_path,
-    };
+    let project = Project::new(name, project_path);
     store.register(&project)?;
 
     Ok(project)
@@ -189,21 +439,74 @@ pub fn resolve_project_or_cwd(store: &ProjectStore, input: Option<&str>) -> Resu
                 }
                 // Same name, different path — use path-based name with suffix
                 let unique_name = format!("{}-{}", name, &project_path.len());
-                let project = Project {
-                    name: unique_name,
-                    path: project_path,
-                };
+                let project = Project::new(unique_name, project_path);
                 store.register(&project)?;
                 return Ok(project);
             }
 
-            let project = Project {
-                name,
-                path: project_path,
-            };
+            let project = Project::new(name, project_path);
             store.register(&project)?;
 
             Ok(project)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("src");
+        for repo in ["api/.git", "web/.git", "web/vendor/lib/.git", "org/tool/.git", ".cache/hidden/.git", "a/b/c/deep/.git"] {
+            std::fs::create_dir_all(root.join(repo)).unwrap();
+        }
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+
+        let found = scan_repositories(&root, 3);
+        let names: Vec<String> = found.iter().map(|p| p.strip_prefix(&root).unwrap().to_string_lossy().to_string()).collect();
+        assert_eq!(names, ["api", "org/tool", "web"]);
+
+        let store = ProjectStore::with_path(dir.path().join("projects.json"));
+        store.register(&Project::new("api", "/elsewhere/api")).unwrap();
+        store.register(&Project::new("webapp", root.join("web").to_string_lossy())).unwrap();
+        let discovered = discover_projects(&store, std::slice::from_ref(&root), 3).unwrap();
+        let names: Vec<&str> = discovered.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["tool"]);
+        assert_eq!(discovered[0].path, root.join("org/tool").to_string_lossy());
+    }
+
+    #[test]
+    fn test_add_worktree_reuses_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&["branch", "feature"]);
+
+        let repo_path = repo.to_string_lossy();
+        add_worktree(&repo_path, &dir.path().join("repo-feature"), "feature").unwrap();
+        assert_eq!(current_branch(&dir.path().join("repo-feature").to_string_lossy()).as_deref(), Some("feature"));
+        // A branch already checked out elsewhere cannot be used again
+        assert!(add_worktree(&repo_path, &dir.path().join("again"), "feature").is_err());
+
+        // Reading HEAD directly agrees with git, in the main checkout and in the worktree
+        assert_eq!(head_branch(&repo), current_branch(&repo_path));
+        assert_eq!(head_branch(&dir.path().join("repo-feature")).as_deref(), Some("feature"));
+        assert!(is_git_repo(&dir.path().join("repo-feature")));
+        assert!(!is_git_repo(dir.path()));
+        assert_eq!(head_branch(dir.path()), None);
+    }
+}
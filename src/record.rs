@@ -0,0 +1,258 @@
+//! ペイン出力の録画 (asciicast v2) と再生。
+//! `apiary record` が tmux pipe-pane で `apiary record-sink` を起動し、
+//! 届いた出力に経過時間を付けて .cast に追記する。`apiary replay` は vt100 に流して再生する
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::redact::{LineRedactor, Redactor};
+
+/// 再生時に詰める無出力の間隔 (秒)。エージェントの長い待ち時間を飛ばす
+pub const MAX_IDLE_SECS: f64 = 2.0;
+
+/// .cast の 1 行目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u32,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default)]
+    pub timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl CastHeader {
+    pub fn new(width: u16, height: u16, title: &str) -> Self {
+        Self {
+            version: 2,
+            width,
+            height,
+            timestamp: chrono::Utc::now().timestamp(),
+            title: Some(title.to_string()),
+        }
+    }
+}
+
+/// 録画の既定の置き場所 (~/.config/apiary/recordings)
+pub fn recordings_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("Failed to determine config directory")?
+        .join("apiary")
+        .join("recordings"))
+}
+
+/// ヘッダーと最初の画面 (経過 0 秒の出力) で .cast を作る
+pub fn create_cast(path: &Path, header: &CastHeader, initial: &str) -> Result<()> {
+    let mut file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create recording: {:?}", path))?;
+    writeln!(file, "{}", serde_json::to_string(header)?)?;
+    if !initial.is_empty() {
+        write_event(&mut file, 0.0, initial)?;
+    }
+    Ok(())
+}
+
+fn write_event(out: &mut impl Write, at: f64, data: &str) -> Result<()> {
+    let line = serde_json::to_string(&(at, "o", data))?;
+    writeln!(out, "{}", line)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// input が閉じるまで読み、started からの経過時間付きの出力イベントとして out に書く
+/// (pipe-pane を止めると input が EOF になる)。書く前に redactor で伏せる。
+/// 読み込みの切れ目でトークンが分かれないよう、イベントは行がそろってから書く
+pub fn record_stream(mut input: impl Read, mut out: impl Write, started: Instant, redactor: &Redactor) -> Result<()> {
+    let mut buf = [0u8; 16384];
+    let mut pending = Vec::new();
+    let mut lines = LineRedactor::new(redactor);
+    loop {
+        let n = input.read(&mut buf).context("Failed to read pane output")?;
+        if n == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buf[..n]);
+        if let Some(data) = lines.push(&take_utf8(&mut pending)) {
+            write_event(&mut out, started.elapsed().as_secs_f64(), &data)?;
+        }
+    }
+    lines.push(&String::from_utf8_lossy(&pending));
+    if let Some(data) = lines.finish() {
+        write_event(&mut out, started.elapsed().as_secs_f64(), &data)?;
+    }
+    Ok(())
+}
+
+/// pending から文字列として取り出せる分を取り出す。末尾で途切れたマルチバイト文字は次の読み込みまで残す
+pub(crate) fn take_utf8(pending: &mut Vec<u8>) -> String {
+    match std::str::from_utf8(pending) {
+        Ok(s) => {
+            let s = s.to_string();
+            pending.clear();
+            s
+        }
+        Err(e) if e.error_len().is_none() => {
+            let valid = e.valid_up_to();
+            let s = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+            s
+        }
+        Err(_) => {
+            let s = String::from_utf8_lossy(pending).into_owned();
+            pending.clear();
+            s
+        }
+    }
+}
+
+/// 読み込んだ .cast (出力イベントだけ)
+#[derive(Debug, Clone)]
+pub struct Cast {
+    pub header: CastHeader,
+    pub events: Vec<(f64, String)>,
+}
+
+impl Cast {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recording: {:?}", path))?;
+        Self::parse(&content).with_context(|| format!("Failed to parse recording: {:?}", path))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+        let header: CastHeader = serde_json::from_str(lines.next().context("Empty recording")?)
+            .context("Invalid asciicast header")?;
+        if header.version != 2 {
+            anyhow::bail!("Unsupported asciicast version {}", header.version);
+        }
+        // 書きかけの最終行などは読み飛ばす
+        let events = lines
+            .filter_map(|l| serde_json::from_str::<(f64, String, String)>(l).ok())
+            .filter(|(_, kind, _)| kind == "o")
+            .map(|(at, _, data)| (at, data))
+            .collect();
+        Ok(Self { header, events })
+    }
+}
+
+/// 再生の進行状態。時刻は録画上の秒 (無出力の間隔は MAX_IDLE_SECS に詰める)
+pub struct Player {
+    pub header: CastHeader,
+    events: Vec<(f64, String)>,
+    parser: vt100::Parser,
+    next: usize,
+    position: f64,
+    pub speed: f64,
+    pub paused: bool,
+}
+
+impl Player {
+    pub fn new(cast: Cast, speed: f64) -> Self {
+        let mut events = Vec::with_capacity(cast.events.len());
+        let (mut last, mut shift) = (0.0, 0.0);
+        for (at, data) in cast.events {
+            shift += (at - last - MAX_IDLE_SECS).max(0.0);
+            last = at;
+            events.push((at - shift, data));
+        }
+        let parser = vt100::Parser::new(cast.header.height, cast.header.width, 0);
+        Self { header: cast.header, events, parser, next: 0, position: 0.0, speed, paused: false }
+    }
+
+    /// 実時間で secs 進め、その間のイベントを画面に流す
+    pub fn advance(&mut self, secs: f64) {
+        if self.paused {
+            return;
+        }
+        self.position += secs * self.speed;
+        while let Some((at, data)) = self.events.get(self.next) {
+            if *at > self.position {
+                break;
+            }
+            self.parser.process(data.as_bytes());
+            self.next += 1;
+        }
+    }
+
+    pub fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+
+    pub fn position(&self) -> f64 {
+        self.position.min(self.duration())
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0.0, |(at, _)| *at)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_stream_keeps_split_characters() {
+        // "é" (0xC3 0xA9) が読み込みの境目で割れても 1 文字として書き、割れたトークンも伏せる
+        struct Chunks(Vec<Vec<u8>>);
+        impl Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() {
+                    return Ok(0);
+                }
+                let chunk = self.0.remove(0);
+                buf[..chunk.len()].copy_from_slice(&chunk);
+                Ok(chunk.len())
+            }
+        }
+        let input = Chunks(vec![b"caf\xC3".to_vec(), b"\xA9\r\n".to_vec(), b"export GITHUB_TO".to_vec(), b"KEN=ghp_x1y2z3\r\n".to_vec()]);
+        let mut out = Vec::new();
+        record_stream(input, &mut out, Instant::now(), &Redactor::new(&Default::default())).unwrap();
+
+        let cast = Cast::parse(&format!("{}\n{}", serde_json::to_string(&CastHeader::new(80, 24, "t")).unwrap(), String::from_utf8(out).unwrap())).unwrap();
+        let text: String = cast.events.iter().map(|(_, d)| d.as_str()).collect();
+        assert_eq!(text, "café\r\nexport GITHUB_TOKEN=[REDACTED]\r\n");
+        assert_eq!(cast.events[0].1, "café\r\n");
+    }
+
+    #[test]
+    fn test_parse_and_play() {
+        let content = r#"{"version": 2, "width": 20, "height": 3, "timestamp": 0}
+[0.0, "o", "hello"]
+[0.5, "i", "ignored"]
+[1.0, "o", " world"]
+[60.0, "o", "\r\nlater"]
+[61.0, "o", "trunc"#;
+        let cast = Cast::parse(content).unwrap();
+        assert_eq!(cast.events.len(), 3);
+
+        let mut player = Player::new(cast, 1.0);
+        // 1 分の待ちは MAX_IDLE_SECS に詰める
+        assert_eq!(player.duration(), 1.0 + MAX_IDLE_SECS);
+        player.advance(0.1);
+        assert_eq!(player.screen().contents(), "hello");
+        player.paused = true;
+        player.advance(10.0);
+        assert!(!player.finished());
+        player.paused = false;
+        player.advance(10.0);
+        assert!(player.finished());
+        assert_eq!(player.screen().contents(), "hello world\nlater");
+        assert_eq!(player.position(), player.duration());
+    }
+
+    #[test]
+    fn test_parse_rejects_other_versions() {
+        assert!(Cast::parse(r#"{"version": 1, "width": 80, "height": 24}"#).is_err());
+        assert!(Cast::parse("").is_err());
+    }
+}
@@ -0,0 +1,363 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use std::collections::BTreeMap;
+
+use crate::pod::{format_duration, Pod, PodStatus};
+use crate::store::events::{EventKind, EventRecord};
+
+/// ローカル時刻でその日の 0 時
+fn midnight(date: NaiveDate) -> Result<DateTime<Utc>> {
+    let naive = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| anyhow::anyhow!("Invalid local time for {}", date))
+}
+
+/// `--since` の解釈: "today" / "yesterday" (ローカル時刻の 0 時)、"12h" / "3d" / "1w"、"2024-05-01"
+pub fn parse_since(input: &str, now: DateTime<Local>) -> Result<DateTime<Utc>> {
+    let input = input.trim();
+    match input {
+        "today" => return midnight(now.date_naive()),
+        "yesterday" => return midnight(now.date_naive() - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return midnight(date);
+    }
+    let invalid = || anyhow::anyhow!("Invalid --since '{}' (use today, yesterday, 12h, 3d, 1w or YYYY-MM-DD)", input);
+    let unit = input.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = input[..input.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    let span = match unit {
+        'h' => Duration::hours(amount),
+        'd' => Duration::days(amount),
+        'w' => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok((now - span).with_timezone(&Utc))
+}
+
+/// イベントログから集計した期間内のまとめ
+#[derive(Debug, Default)]
+pub struct Report {
+    pub since: Option<DateTime<Utc>>,
+    pub created: Vec<String>,
+    pub completed: Vec<String>,
+    pub dropped: Vec<String>,
+    /// プロジェクト名 (なしは "-") ごとの Working 秒数
+    pub working_secs: BTreeMap<String, u64>,
+    pub approved: usize,
+    pub denied: usize,
+    /// Error になった Pod と回数
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl Report {
+    /// since 以降のイベント (古い順) から集計する。now 時点でまだ Working の Pod は now までを数える
+    pub fn build(events: &[EventRecord], since: DateTime<Utc>, now: DateTime<Utc>) -> Self {
+        let mut report = Report { since: Some(since), ..Report::default() };
+        // Pod ごとの Working 開始時刻とプロジェクト
+        let mut working: BTreeMap<String, (DateTime<Utc>, String)> = BTreeMap::new();
+        let push_unique = |list: &mut Vec<String>, name: &str| {
+            if !list.iter().any(|n| n == name) {
+                list.push(name.to_string());
+            }
+        };
+
+        for event in events.iter().filter(|e| e.at >= since) {
+            let project = event.project.clone().unwrap_or_else(|| "-".to_string());
+            match event.kind {
+                EventKind::Created => push_unique(&mut report.created, &event.pod),
+                EventKind::Dropped => push_unique(&mut report.dropped, &event.pod),
+                EventKind::PermissionApproved => report.approved += 1,
+                EventKind::PermissionDenied => report.denied += 1,
+                EventKind::Status { ref from, ref to } => {
+                    if *from == PodStatus::Working {
+                        // 期間の前から Working だった分は since から数える
+                        let (start, _) = working.remove(&event.pod).unwrap_or((since, project.clone()));
+                        *report.working_secs.entry(project.clone()).or_default() +=
+                            (event.at - start).num_seconds().max(0) as u64;
+                        if matches!(to, PodStatus::Idle | PodStatus::Done) {
+                            push_unique(&mut report.completed, &event.pod);
+                        }
+                    }
+                    if *to == PodStatus::Working {
+                        working.insert(event.pod.clone(), (event.at, project.clone()));
+                    }
+                    if *to == PodStatus::Error {
+                        *report.errors.entry(event.pod.clone()).or_default() += 1;
+                    }
+                }
+            }
+        }
+        for (start, project) in working.into_values() {
+            *report.working_secs.entry(project).or_default() += (now - start).num_seconds().max(0) as u64;
+        }
+        report
+    }
+
+    /// スタンドアップのメモに貼れる Markdown
+    pub fn to_markdown(&self) -> String {
+        let list = |names: &[String]| {
+            if names.is_empty() { String::new() } else { format!(" ({})", names.join(", ")) }
+        };
+        let mut out = String::new();
+        match self.since {
+            Some(since) => out.push_str(&format!(
+                "# Apiary report (since {})\n\n",
+                since.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            )),
+            None => out.push_str("# Apiary report\n\n"),
+        }
+
+        out.push_str("## Pods\n\n");
+        out.push_str(&format!("- Created: {}{}\n", self.created.len(), list(&self.created)));
+        out.push_str(&format!("- Completed: {}{}\n", self.completed.len(), list(&self.completed)));
+        out.push_str(&format!("- Dropped: {}{}\n", self.dropped.len(), list(&self.dropped)));
+
+        out.push_str("\n## Working time\n\n");
+        if self.working_secs.is_empty() {
+            out.push_str("No recorded work.\n");
+        } else {
+            out.push_str("| Project | Time |\n|---|---|\n");
+            for (project, secs) in &self.working_secs {
+                out.push_str(&format!("| {} | {} |\n", project, format_duration(*secs)));
+            }
+            let total: u64 = self.working_secs.values().sum();
+            out.push_str(&format!("| **Total** | **{}** |\n", format_duration(total)));
+        }
+
+        out.push_str("\n## Permissions\n\n");
+        out.push_str(&format!("- Approved: {}\n- Denied: {}\n", self.approved, self.denied));
+
+        out.push_str("\n## Errors\n\n");
+        if self.errors.is_empty() {
+            out.push_str("None.\n");
+        } else {
+            for (pod, count) in &self.errors {
+                out.push_str(&format!("- {} ({}x)\n", pod, count));
+            }
+        }
+        out
+    }
+}
+
+/// `apiary stats export` の 1 行 (Pod × ローカル日付)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyRow {
+    pub date: NaiveDate,
+    pub pod: String,
+    pub project: String,
+    pub group: String,
+    /// その日に Working だった秒数
+    pub working_secs: u64,
+    /// その日に Pod が存在していた秒数
+    pub elapsed_secs: u64,
+}
+
+/// Pod ごとの存在期間と Working 区間
+struct Track {
+    project: String,
+    group: String,
+    alive: (DateTime<Utc>, DateTime<Utc>),
+    working: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    working_since: Option<DateTime<Utc>>,
+}
+
+fn overlap_secs(span: (DateTime<Utc>, DateTime<Utc>), day: (DateTime<Utc>, DateTime<Utc>)) -> u64 {
+    let start = span.0.max(day.0);
+    let end = span.1.min(day.1);
+    (end - start).num_seconds().max(0) as u64
+}
+
+/// イベントログ (古い順) と現在の Pod から、since 以降の Pod × 日ごとの作業時間を集計する
+pub fn daily_rows(events: &[EventRecord], pods: &[Pod], since: DateTime<Utc>, now: DateTime<Utc>) -> Result<Vec<DailyRow>> {
+    let mut tracks: BTreeMap<String, Track> = BTreeMap::new();
+    for pod in pods {
+        tracks.insert(pod.name.clone(), Track {
+            project: pod.project.clone().unwrap_or_default(),
+            group: pod.group.clone().unwrap_or_default(),
+            alive: (pod.created_at.max(since), now),
+            working: Vec::new(),
+            working_since: None,
+        });
+    }
+
+    for event in events.iter().filter(|e| e.at >= since) {
+        // 今はもう無い Pod: 最初のイベントが Created でなければ期間の前から存在していた
+        let track = tracks.entry(event.pod.clone()).or_insert_with(|| Track {
+            project: event.project.clone().unwrap_or_default(),
+            group: event.group.clone().unwrap_or_default(),
+            alive: (if event.kind == EventKind::Created { event.at } else { since }, now),
+            working: Vec::new(),
+            working_since: None,
+        });
+        match event.kind {
+            EventKind::Created => track.alive.0 = event.at,
+            EventKind::Dropped => {
+                track.alive.1 = event.at;
+                if let Some(start) = track.working_since.take() {
+                    track.working.push((start, event.at));
+                }
+            }
+            EventKind::Status { ref from, ref to } => {
+                if *from == PodStatus::Working {
+                    let start = track.working_since.take().unwrap_or(track.alive.0);
+                    track.working.push((start, event.at));
+                }
+                if *to == PodStatus::Working {
+                    track.working_since = Some(event.at);
+                }
+            }
+            EventKind::PermissionApproved | EventKind::PermissionDenied => {}
+        }
+    }
+    for track in tracks.values_mut() {
+        if let Some(start) = track.working_since.take() {
+            track.working.push((start, track.alive.1));
+        }
+    }
+
+    let mut rows = Vec::new();
+    let last_day = now.with_timezone(&Local).date_naive();
+    let mut date = since.with_timezone(&Local).date_naive();
+    while date <= last_day {
+        let day = (midnight(date)?.max(since), midnight(date + Duration::days(1))?.min(now));
+        for (name, track) in &tracks {
+            let elapsed_secs = overlap_secs(track.alive, day);
+            let working_secs: u64 = track.working.iter().map(|w| overlap_secs(*w, day)).sum();
+            if elapsed_secs > 0 || working_secs > 0 {
+                rows.push(DailyRow {
+                    date,
+                    pod: name.clone(),
+                    project: track.project.clone(),
+                    group: track.group.clone(),
+                    working_secs,
+                    elapsed_secs,
+                });
+            }
+        }
+        date += Duration::days(1);
+    }
+    Ok(rows)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// スプレッドシート向けの CSV (ヘッダ付き)
+pub fn to_csv(rows: &[DailyRow]) -> String {
+    let mut out = String::from("date,pod,project,group,working_secs,elapsed_secs\n");
+    for row in rows {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.date.format("%Y-%m-%d"),
+            csv_field(&row.pod),
+            csv_field(&row.project),
+            csv_field(&row.group),
+            row.working_secs,
+            row.elapsed_secs,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(mins: i64, pod: &str, project: Option<&str>, kind: EventKind) -> EventRecord {
+        EventRecord {
+            at: base() + Duration::minutes(mins),
+            pod: pod.to_string(),
+            project: project.map(str::to_string),
+            group: None,
+            kind,
+        }
+    }
+
+    fn base() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap()
+    }
+
+    fn status(from: PodStatus, to: PodStatus) -> EventKind {
+        EventKind::Status { from, to }
+    }
+
+    #[test]
+    fn test_build_report() {
+        let events = vec![
+            event(0, "a", Some("web"), EventKind::Created),
+            event(1, "a", Some("web"), status(PodStatus::Idle, PodStatus::Working)),
+            event(31, "a", Some("web"), status(PodStatus::Working, PodStatus::Permission)),
+            event(32, "a", Some("web"), EventKind::PermissionApproved),
+            event(32, "a", Some("web"), status(PodStatus::Permission, PodStatus::Working)),
+            event(62, "a", Some("web"), status(PodStatus::Working, PodStatus::Idle)),
+            // 期間の前から Working だった Pod は since から数える
+            event(10, "b", None, status(PodStatus::Working, PodStatus::Error)),
+            event(20, "c", None, status(PodStatus::Idle, PodStatus::Working)),
+            event(25, "c", None, EventKind::PermissionDenied),
+        ];
+        let report = Report::build(&events, base(), base() + Duration::minutes(80));
+        assert_eq!(report.created, vec!["a"]);
+        assert_eq!(report.completed, vec!["a"]);
+        assert_eq!(report.working_secs.get("web"), Some(&(60 * 60)));
+        // b: 10 分 + c: まだ Working なので now まで 60 分
+        assert_eq!(report.working_secs.get("-"), Some(&(70 * 60)));
+        assert_eq!((report.approved, report.denied), (1, 1));
+        assert_eq!(report.errors.get("b"), Some(&1));
+
+        let md = report.to_markdown();
+        assert!(md.contains("- Created: 1 (a)"));
+        assert!(md.contains("| web | 1h |"));
+        assert!(md.contains("- b (1x)"));
+    }
+
+    #[test]
+    fn test_daily_rows_split_at_midnight() {
+        let day1 = Local.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap().with_timezone(&Utc);
+        let at = |h: i64| day1 + Duration::hours(h);
+        let record = |h: i64, kind: EventKind| EventRecord {
+            at: at(h),
+            pod: "a".to_string(),
+            project: Some("web".to_string()),
+            group: Some("team, x".to_string()),
+            kind,
+        };
+        let events = vec![
+            record(20, EventKind::Created),
+            record(22, status(PodStatus::Idle, PodStatus::Working)),
+            record(26, status(PodStatus::Working, PodStatus::Idle)),
+            record(30, EventKind::Dropped),
+        ];
+        let rows = daily_rows(&events, &[], day1, at(36)).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].working_secs, rows[0].elapsed_secs), (2 * 3600, 4 * 3600));
+        assert_eq!((rows[1].working_secs, rows[1].elapsed_secs), (2 * 3600, 6 * 3600));
+        assert_eq!(rows[1].date, NaiveDate::from_ymd_opt(2024, 5, 2).unwrap());
+
+        let csv = to_csv(&rows);
+        assert!(csv.starts_with("date,pod,project,group,working_secs,elapsed_secs\n"));
+        assert!(csv.contains("2024-05-01,a,web,\"team, x\",7200,14400\n"));
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Local.with_ymd_and_hms(2024, 5, 10, 15, 30, 0).unwrap();
+        let midnight = Local.with_ymd_and_hms(2024, 5, 9, 0, 0, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(parse_since("yesterday", now).unwrap(), midnight);
+        assert_eq!(parse_since("2024-05-09", now).unwrap(), midnight);
+        assert_eq!(parse_since("12h", now).unwrap(), (now - Duration::hours(12)).with_timezone(&Utc));
+        assert_eq!(parse_since("1w", now).unwrap(), (now - Duration::weeks(1)).with_timezone(&Utc));
+        assert!(parse_since("soon", now).is_err());
+        assert!(parse_since("h", now).is_err());
+        assert!(parse_since("3日", now).is_err());
+        assert!(parse_since("", now).is_err());
+    }
+}
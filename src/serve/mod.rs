@@ -0,0 +1,459 @@
+//! `apiary serve`: Pod の状態を HTTP で公開する。
+//! /api/pods (JSON)、/api/events (SSE)、/api/pods/<name>/approve|deny (POST) を持ち、
+//! --web のときは / でスマホからも見られる 1 ページのダッシュボードを返す
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::pod::detector::parse_permission_request;
+use crate::pod::{MemberStatus, Pod, PodStatus};
+use crate::tui::app::App;
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+/// Pod の状態を読み直して SSE に流す間隔 (TUI のメインループと同じ)
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+/// 変化が無くても SSE に送るコメント行の間隔 (切れた接続を見つける)
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// プレビューに載せるリードのペインの末尾の行数
+const PREVIEW_LINES: usize = 12;
+
+/// `apiary serve` の設定
+pub struct ServeOptions {
+    pub bind: String,
+    pub port: u16,
+    /// / でダッシュボードを返す
+    pub web: bool,
+    /// 全リクエストに要求する ?token= (または Authorization: Bearer)。
+    /// --token が無ければ [`generate_token`] で作る
+    pub token: String,
+}
+
+/// ランダムなトークン (16 バイトの 16 進)
+pub fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .context("Failed to read /dev/urandom for the serve token")?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[derive(Debug, Serialize)]
+pub struct PodView {
+    pub name: String,
+    pub status: PodStatus,
+    pub label: &'static str,
+    pub group: Option<String>,
+    pub project: Option<String>,
+    pub priority: &'static str,
+    pub elapsed: String,
+    pub members: Vec<MemberView>,
+    /// リードのペインの末尾
+    pub preview: String,
+    pub permission: Option<PermissionView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberView {
+    pub role: String,
+    pub status: MemberStatus,
+    pub elapsed: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionView {
+    pub member: String,
+    pub tool: String,
+    pub command: String,
+}
+
+/// ダッシュボードに出す Pod の一覧 (優先度の高い順、同じ優先度なら元の順)
+pub fn snapshot(pods: &[Pod]) -> Vec<PodView> {
+    let mut pods: Vec<&Pod> = pods.iter().collect();
+    pods.sort_by_key(|p| p.priority.rank());
+    pods.iter()
+        .map(|pod| {
+            let preview = pod.members.first().map_or(String::new(), |m| {
+                let lines: Vec<&str> = m.last_output.trim_end().lines().collect();
+                lines[lines.len().saturating_sub(PREVIEW_LINES)..].join("\n")
+            });
+            let permission = pod.members.iter().find(|m| m.status == MemberStatus::Permission).map(|m| {
+                let request = parse_permission_request(&m.last_output);
+                PermissionView {
+                    member: m.role.clone(),
+                    tool: request.as_ref().map_or(String::new(), |r| r.tool.clone()),
+                    command: request.map_or(String::new(), |r| r.command),
+                }
+            });
+            PodView {
+                name: pod.name.clone(),
+                status: pod.status.clone(),
+                label: pod.status.label(),
+                group: pod.group.clone(),
+                project: pod.project.clone(),
+                priority: pod.priority.label(),
+                elapsed: pod.elapsed_time(),
+                members: pod
+                    .members
+                    .iter()
+                    .map(|m| MemberView { role: m.role.clone(), status: m.status.clone(), elapsed: m.elapsed() })
+                    .collect(),
+                preview,
+                permission,
+            }
+        })
+        .collect()
+}
+
+fn snapshot_json(app: &App) -> String {
+    serde_json::to_string(&snapshot(&app.state.pods)).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 受け取ったリクエストの最初の行とトークン、Host / Origin ヘッダー
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    token: Option<String>,
+    host: Option<String>,
+    origin: Option<String>,
+}
+
+impl Request {
+    /// "GET /api/pods?token=x HTTP/1.1" とヘッダーを読む
+    fn parse(head: &str) -> Option<Request> {
+        let mut lines = head.lines();
+        let mut parts = lines.next()?.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut token = query
+            .split('&')
+            .find_map(|kv| kv.strip_prefix("token="))
+            .map(percent_decode);
+        let mut host = None;
+        let mut origin = None;
+        for line in lines {
+            if let Some((name, value)) = line.split_once(':') {
+                let value = value.trim();
+                if name.eq_ignore_ascii_case("authorization") {
+                    if let Some(t) = value.strip_prefix("Bearer ") {
+                        token = Some(t.to_string());
+                    }
+                } else if name.eq_ignore_ascii_case("host") {
+                    host = Some(value.to_string());
+                } else if name.eq_ignore_ascii_case("origin") {
+                    origin = Some(value.to_string());
+                }
+            }
+        }
+        Some(Request { method, path: percent_decode(path), token, host, origin })
+    }
+}
+
+/// "host:port" / "[::1]:port" をホストとポートに分ける
+fn split_host_port(authority: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = authority.strip_prefix('[') {
+        return match rest.split_once(']') {
+            Some((host, port)) => (host, port.strip_prefix(':')),
+            None => (rest, None),
+        };
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host, Some(port)),
+        _ => (authority, None),
+    }
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost") || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Host (または Origin のホスト部分) が待ち受けているアドレスを指しているか。
+/// 0.0.0.0 で待ち受けるときは IP アドレスだけを許す (DNS リバインディングはホスト名で来る)
+fn is_bound_authority(authority: &str, options: &ServeOptions) -> bool {
+    let (host, port) = split_host_port(authority);
+    if port.is_some_and(|p| p != options.port.to_string()) {
+        return false;
+    }
+    let bind = options.bind.as_str();
+    if host.eq_ignore_ascii_case(bind) || (is_loopback(bind) && is_loopback(host)) {
+        return true;
+    }
+    bind.parse::<IpAddr>().is_ok_and(|ip| ip.is_unspecified()) && (is_loopback(host) || host.parse::<IpAddr>().is_ok())
+}
+
+/// CSRF と DNS リバインディングを防ぐため、Host と (あれば) Origin が待ち受けアドレスか確かめる
+fn is_allowed_origin(request: &Request, options: &ServeOptions) -> bool {
+    let host_ok = request.host.as_deref().is_some_and(|h| is_bound_authority(h, options));
+    let origin_ok = request.origin.as_deref().is_none_or(|o| {
+        o.strip_prefix("http://").is_some_and(|authority| is_bound_authority(authority, options))
+    });
+    host_ok && origin_ok
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                Some(b) => {
+                    out.push(b);
+                    i += 3;
+                    continue;
+                }
+                None => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[derive(Debug, PartialEq)]
+enum Response {
+    Json(String),
+    Html(&'static str),
+    /// 接続を SSE の購読者として残す
+    Events,
+    Status(u16, String),
+}
+
+/// リクエストを処理して返す内容を決める
+fn route(app: &mut App, request: &Request, options: &ServeOptions) -> Response {
+    if !is_allowed_origin(request, options) {
+        return Response::Status(403, "Host or Origin does not match the bind address".to_string());
+    }
+    if request.token.as_deref() != Some(options.token.as_str()) {
+        return Response::Status(401, "Missing or wrong token".to_string());
+    }
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", [""]) if options.web => Response::Html(DASHBOARD_HTML),
+        ("GET", [""]) => Response::Status(404, "Dashboard disabled (run apiary serve --web)".to_string()),
+        ("GET", ["api", "pods"]) => Response::Json(snapshot_json(app)),
+        ("GET", ["api", "events"]) => Response::Events,
+        ("POST", ["api", "pods", name, action @ ("approve" | "deny")]) => {
+            match app.answer_permission(name, *action == "approve") {
+                Ok(()) => Response::Json(snapshot_json(app)),
+                Err(e) => Response::Status(409, e.to_string()),
+            }
+        }
+        _ => Response::Status(404, "Not found".to_string()),
+    }
+}
+
+fn reason(code: u16) -> &'static str {
+    match code {
+        200 => "OK",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Bad Request",
+    }
+}
+
+fn write_response(stream: &mut TcpStream, code: u16, content_type: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason(code),
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+fn sse_event(json: &str) -> String {
+    format!("data: {}\n\n", json)
+}
+
+/// ヘッダーの終わり (空行) まで読む
+fn read_head(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < 16 * 1024 {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// 1 つの接続を処理する。SSE の購読なら接続を返す
+fn handle_connection(app: &mut App, mut stream: TcpStream, options: &ServeOptions) -> std::io::Result<Option<TcpStream>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(2)))?;
+    let Some(request) = Request::parse(&read_head(&mut stream)?) else {
+        write_response(&mut stream, 400, "text/plain", "Bad request")?;
+        return Ok(None);
+    };
+    match route(app, &request, options) {
+        Response::Json(body) => write_response(&mut stream, 200, "application/json", &body)?,
+        Response::Html(body) => write_response(&mut stream, 200, "text/html; charset=utf-8", body)?,
+        Response::Status(code, message) => write_response(&mut stream, code, "text/plain; charset=utf-8", &message)?,
+        Response::Events => {
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\n\r\n")?;
+            stream.write_all(sse_event(&snapshot_json(app)).as_bytes())?;
+            return Ok(Some(stream));
+        }
+    }
+    Ok(None)
+}
+
+/// サーバーを動かし続ける (Ctrl+C で終了)。ポーリングは TUI と同じように行うが、
+/// フックや通知は TUI と二重に出さないよう TUI に任せる
+pub fn run(app: &mut App, options: &ServeOptions) -> Result<()> {
+    let listener = TcpListener::bind((options.bind.as_str(), options.port))
+        .with_context(|| format!("Failed to listen on {}:{}", options.bind, options.port))?;
+    listener.set_nonblocking(true)?;
+    if options.web {
+        println!("Dashboard: http://{}:{}/?token={}", options.bind, options.port, options.token);
+    } else {
+        println!("Serving the pod API on http://{}:{}/api/pods?token={}", options.bind, options.port, options.token);
+    }
+
+    let mut subscribers: Vec<TcpStream> = Vec::new();
+    let mut last_refresh: Option<Instant> = None;
+    let mut last_heartbeat = Instant::now();
+    let mut last_sent = String::new();
+    loop {
+        if last_refresh.is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL) {
+            last_refresh = Some(Instant::now());
+            app.selective_refresh();
+            let json = snapshot_json(app);
+            let message = if json != last_sent {
+                last_sent = json;
+                Some(sse_event(&last_sent))
+            } else if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+                Some(": ping\n\n".to_string())
+            } else {
+                None
+            };
+            if let Some(message) = message {
+                last_heartbeat = Instant::now();
+                subscribers.retain_mut(|s| s.write_all(message.as_bytes()).and_then(|_| s.flush()).is_ok());
+            }
+        }
+        match listener.accept() {
+            Ok((stream, peer)) => match handle_connection(app, stream, options) {
+                Ok(Some(subscriber)) => {
+                    info!("Dashboard subscriber connected from {}", peer);
+                    subscribers.push(subscriber);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Request from {} failed: {}", peer, e),
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => warn!("Accept failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+    use tempfile::TempDir;
+
+    fn options(web: bool) -> ServeOptions {
+        ServeOptions { bind: "127.0.0.1".to_string(), port: 7878, web, token: "t".to_string() }
+    }
+
+    fn get(path: &str, token: Option<&str>) -> Request {
+        Request {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            token: token.map(str::to_string),
+            host: Some("127.0.0.1:7878".to_string()),
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request = Request::parse("POST /api/pods/fix%20login/approve?x=1&token=s%3Dcret HTTP/1.1\r\nHost: a\r\n\r\n").unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/pods/fix login/approve");
+        assert_eq!(request.token.as_deref(), Some("s=cret"));
+        assert_eq!(request.host.as_deref(), Some("a"));
+        let request = Request::parse("GET / HTTP/1.1\r\nauthorization: Bearer abc\r\nOrigin: http://x\r\n\r\n").unwrap();
+        assert_eq!(request.token.as_deref(), Some("abc"));
+        assert_eq!(request.origin.as_deref(), Some("http://x"));
+        assert!(Request::parse("").is_none());
+    }
+
+    #[test]
+    fn test_routes_and_approve() {
+        let dir = TempDir::new().unwrap();
+        let fake = FakeTmux::new();
+        let mut app = App::for_test(dir.path(), Box::new(fake.clone()));
+        let project = dir.path().to_string_lossy().to_string();
+        app.create_pod("web", Some(&project), None, None, None).unwrap();
+        let pane = app.state.pods[0].members[0].tmux_pane.clone();
+        fake.set_output(&pane, "Bash(cargo test)\nDo you want to proceed?\n❯ 1. Yes\n  2. No");
+        app.refresh_pod_states();
+
+        let web = options(true);
+        assert_eq!(route(&mut app, &get("/", Some("t")), &web), Response::Html(DASHBOARD_HTML));
+        assert!(matches!(route(&mut app, &get("/", None), &web), Response::Status(401, _)));
+        assert!(matches!(route(&mut app, &get("/", Some("t")), &options(false)), Response::Status(404, _)));
+        assert_eq!(route(&mut app, &get("/api/events", Some("t")), &web), Response::Events);
+
+        let Response::Json(json) = route(&mut app, &get("/api/pods", Some("t")), &web) else {
+            panic!("expected JSON");
+        };
+        let pods: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(pods[0]["status"], "Permission");
+        assert_eq!(pods[0]["permission"]["tool"], "Bash");
+        assert!(pods[0]["preview"].as_str().unwrap().contains("Do you want to proceed?"));
+
+        let approve = Request { method: "POST".to_string(), ..get("/api/pods/web/approve", Some("t")) };
+        assert!(matches!(route(&mut app, &approve, &web), Response::Json(_)));
+        assert_eq!(fake.sent_to(&pane).last().map(String::as_str), Some("y"));
+        let missing = Request { method: "POST".to_string(), ..get("/api/pods/nope/deny", Some("t")) };
+        assert!(matches!(route(&mut app, &missing, &web), Response::Status(409, _)));
+    }
+
+    #[test]
+    fn test_rejects_foreign_host_and_origin() {
+        let dir = TempDir::new().unwrap();
+        let mut app = App::for_test(dir.path(), Box::new(FakeTmux::new()));
+        let local = options(false);
+        let with = |host: Option<&str>, origin: Option<&str>| Request {
+            host: host.map(str::to_string),
+            origin: origin.map(str::to_string),
+            ..get("/api/pods", Some("t"))
+        };
+        assert!(matches!(route(&mut app, &with(Some("localhost:7878"), None), &local), Response::Json(_)));
+        assert!(matches!(route(&mut app, &with(Some("127.0.0.1:7878"), Some("http://127.0.0.1:7878")), &local), Response::Json(_)));
+        // DNS リバインディング (他人のドメインが 127.0.0.1 を指す) と CSRF (他のサイトからの POST)
+        assert!(matches!(route(&mut app, &with(Some("evil.example:7878"), None), &local), Response::Status(403, _)));
+        assert!(matches!(route(&mut app, &with(Some("127.0.0.1:7878"), Some("https://evil.example")), &local), Response::Status(403, _)));
+        assert!(matches!(route(&mut app, &with(Some("127.0.0.1:9999"), None), &local), Response::Status(403, _)));
+        assert!(matches!(route(&mut app, &with(None, None), &local), Response::Status(403, _)));
+
+        let lan = ServeOptions { bind: "0.0.0.0".to_string(), ..options(false) };
+        assert!(matches!(route(&mut app, &with(Some("192.168.1.20:7878"), None), &lan), Response::Json(_)));
+        assert!(matches!(route(&mut app, &with(Some("[::1]:7878"), None), &lan), Response::Json(_)));
+        assert!(matches!(route(&mut app, &with(Some("evil.example:7878"), None), &lan), Response::Status(403, _)));
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let a = generate_token().unwrap();
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, generate_token().unwrap());
+    }
+}
@@ -0,0 +1,142 @@
+//! 初回起動時のセットアップウィザード
+//!
+//! 設定ファイル / Pod / プロジェクトがすべて無い状態で起動したとき、
+//! 空のグリッドを表示する前に依存コマンドの確認・hooks のインストール・
+//! プロジェクト登録・主要キーの説明を対話形式で行う。
+
+use anyhow::Result;
+use std::io::{self, BufRead, Write};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::hooks;
+use crate::project::{self, ProjectStore};
+use crate::store::PodStore;
+
+/// 初回起動か (設定ファイル・Pod・プロジェクトがいずれも無い)
+pub fn needs_setup(store: &PodStore, project_store: &ProjectStore) -> bool {
+    let config_missing = Config::config_path().is_ok_and(|p| !p.exists());
+    config_missing
+        && store.load().map(|p| p.is_empty()).unwrap_or(false)
+        && project_store.list().map(|p| p.is_empty()).unwrap_or(false)
+}
+
+/// セットアップウィザードを実行
+pub fn run_wizard(project_store: &ProjectStore) -> Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    println!("Welcome to Apiary! Let's get you set up.");
+    println!();
+
+    // 1. 依存コマンドの確認
+    println!("Checking dependencies:");
+    let tmux = command_version("tmux", "-V");
+    let claude = command_version("claude", "--version");
+    print_check("tmux", tmux.as_deref());
+    print_check("claude", claude.as_deref());
+    if claude.is_none() {
+        println!("  Install Claude Code: https://docs.anthropic.com/en/docs/claude-code");
+    }
+    println!();
+
+    // 2. hooks のインストール
+    println!("Claude Code hooks push tool and subagent events to Apiary for faster status updates.");
+    if confirm(&mut input, "Install hooks into ~/.claude/settings.json?", true)? {
+        match hooks::install_hooks() {
+            Ok(path) => println!("  \u{2713} Hooks installed in {}", path.display()),
+            Err(e) => {
+                println!("  \u{2717} Failed to install hooks: {:#}", e);
+                hooks::print_hooks_setup();
+            }
+        }
+    }
+    println!();
+
+    // 3. カレントディレクトリをプロジェクト登録
+    if let Ok(cwd) = std::env::current_dir() {
+        let question = format!("Register {} as a project?", cwd.display());
+        if confirm(&mut input, &question, true)? {
+            match project::resolve_project_or_cwd(project_store, None) {
+                Ok(p) => println!("  \u{2713} Project '{}' registered \u{2192} {}", p.name, p.path),
+                Err(e) => println!("  \u{2717} Failed to register project: {:#}", e),
+            }
+        }
+        println!();
+    }
+
+    // 4. 設定ファイルを作成 (次回以降ウィザードを出さない)
+    let config_path = Config::write_default()?;
+    println!("Config written to {}", config_path.display());
+    println!();
+
+    // 5. 主要キーの説明
+    println!("Main keys:");
+    for (key, desc) in [
+        ("n / Tab", "Type an instruction to start a new Claude pod"),
+        ("hjkl / arrows", "Move between pods"),
+        ("Enter", "Open the focused pod (live terminal view)"),
+        ("t", "Attach to the pod's tmux session"),
+        ("a / d", "Adopt an existing tmux session / drop a pod"),
+        ("p", "Browse directories to pick a workspace"),
+        ("?", "Show all keys"),
+        ("q", "Quit"),
+    ] {
+        println!("  {:<14} {}", key, desc);
+    }
+    println!();
+    print!("Press Enter to open the dashboard...");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+
+    Ok(())
+}
+
+/// `cmd arg` を実行してバージョン文字列を返す (見つからなければ None)
+fn command_version(cmd: &str, arg: &str) -> Option<String> {
+    let output = Command::new(cmd).arg(arg).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn print_check(name: &str, version: Option<&str>) {
+    match version {
+        Some(v) => println!("  \u{2713} {:<7} {}", name, v),
+        None => println!("  \u{2717} {:<7} not found in PATH", name),
+    }
+}
+
+/// y/n の確認プロンプト (空入力はデフォルト)
+fn confirm(input: &mut impl BufRead, question: &str, default_yes: bool) -> Result<bool> {
+    let hint = if default_yes { "[Y/n]" } else { "[y/N]" };
+    print!("{} {} ", question, hint);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(parse_answer(&line, default_yes))
+}
+
+fn parse_answer(line: &str, default_yes: bool) -> bool {
+    match line.trim().to_lowercase().as_str() {
+        "" => default_yes,
+        "y" | "yes" => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answer() {
+        assert!(parse_answer("\n", true));
+        assert!(!parse_answer("", false));
+        assert!(parse_answer("Yes\n", false));
+        assert!(!parse_answer("n", true));
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::pod::ChatMessage;
+
+/// 保持する Chat メッセージの最大件数 (全 Pod 合計)
+pub const MAX_CHAT_MESSAGES: usize = 2000;
+
+/// Chat モードのやり取り。TUI を閉じても残り、`apiary chat export` から読める
+pub struct ChatStore {
+    path: PathBuf,
+}
+
+impl ChatStore {
+    /// 新しい ChatStore を作成。パスは ~/.config/apiary/chats.json
+    pub fn new() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .context("Failed to determine config directory")?
+            .join("apiary");
+
+        if !config_dir.exists() {
+            std::fs::create_dir_all(&config_dir)
+                .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+        }
+
+        Ok(Self { path: config_dir.join("chats.json") })
+    }
+
+    /// カスタムパスで ChatStore を作成（テスト用）
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// TUI から書き出すトランスクリプトの置き場所 (chats.json の隣の transcripts/)
+    pub fn transcript_dir(&self) -> PathBuf {
+        self.path.with_file_name("transcripts")
+    }
+
+    /// chats.json を読み込む。存在しない・壊れている場合は空
+    pub fn load(&self) -> Vec<ChatMessage> {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 新しい方から MAX_CHAT_MESSAGES 件を保存 (アトミック: tmp → rename)
+    pub fn save(&self, messages: &[ChatMessage]) -> Result<()> {
+        let start = messages.len().saturating_sub(MAX_CHAT_MESSAGES);
+        let content = serde_json::to_string_pretty(&messages[start..])
+            .context("Failed to serialize chat history")?;
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, &content)
+            .with_context(|| format!("Failed to write temp chat file: {:?}", tmp_path))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to rename temp chat file: {:?}", tmp_path))?;
+
+        Ok(())
+    }
+}
+
+/// pod とのやり取りを Markdown のトランスクリプトにする (時刻はローカル時刻)
+pub fn transcript_markdown(pod: &str, messages: &[ChatMessage]) -> String {
+    let mut out = format!("# Chat: {}\n", pod);
+    for msg in messages.iter().filter(|m| m.pod == pod) {
+        let who = if msg.sender == "you" && !msg.member.is_empty() {
+            format!("you \u{2192} {}", msg.member)
+        } else {
+            msg.sender.clone()
+        };
+        let when = msg.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S");
+        out.push_str(&format!("\n### {} \u{00b7} {}\n\n{}\n", who, when, msg.content.trim_end()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn message(pod: &str, sender: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            sender: sender.to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            pod: pod.to_string(),
+            member: "reviewer".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        let store = ChatStore::with_path(dir.path().join("chats.json"));
+        assert!(store.load().is_empty());
+
+        let messages = vec![message("api", "you", "hi"), message("api", "reviewer", "hello")];
+        store.save(&messages).unwrap();
+        let loaded = store.load();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].content, "hello");
+        assert_eq!(store.transcript_dir(), dir.path().join("transcripts"));
+    }
+
+    #[test]
+    fn test_transcript_markdown() {
+        let messages = vec![
+            message("api", "you", "review the diff"),
+            message("web", "you", "unrelated"),
+            message("api", "reviewer", "Looks good\n- one nit\n"),
+        ];
+        let md = transcript_markdown("api", &messages);
+        assert!(md.starts_with("# Chat: api\n"));
+        assert!(md.contains("### you \u{2192} reviewer \u{00b7} "));
+        assert!(md.contains("### reviewer \u{00b7} "));
+        assert!(md.ends_with("\n\nLooks good\n- one nit\n"));
+        assert!(!md.contains("unrelated"));
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use crate::pod::{Pod, PodStatus};
+
+/// イベントログに記録する出来事
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    Dropped,
+    Status { from: PodStatus, to: PodStatus },
+    PermissionApproved,
+    PermissionDenied,
+}
+
+/// events.jsonl の 1 行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub at: DateTime<Utc>,
+    pub pod: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+impl EventRecord {
+    pub fn new(pod: &Pod, kind: EventKind) -> Self {
+        Self {
+            at: Utc::now(),
+            pod: pod.name.clone(),
+            project: pod.project.clone(),
+            group: pod.group.clone(),
+            kind,
+        }
+    }
+}
+
+/// Pod の出来事を追記していくログ (`apiary report` / `apiary stats export` の集計元)
+#[derive(Clone)]
+pub struct EventStore {
+    path: PathBuf,
+}
+
+impl EventStore {
+    /// 新しい EventStore を作成。パスは ~/.config/apiary/events.jsonl
+    pub fn new() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .context("Failed to determine config directory")?
+            .join("apiary");
+
+        if !config_dir.exists() {
+            std::fs::create_dir_all(&config_dir)
+                .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+        }
+
+        Ok(Self { path: config_dir.join("events.jsonl") })
+    }
+
+    /// カスタムパスで EventStore を作成（テスト用）
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 1 件追記する
+    pub fn append(&self, record: &EventRecord) -> Result<()> {
+        let line = serde_json::to_string(record).context("Failed to serialize event")?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open events file: {:?}", self.path))?;
+        writeln!(file, "{}", line)
+            .with_context(|| format!("Failed to write events file: {:?}", self.path))
+    }
+
+    /// since 以降のイベントを古い順に読む (読めない行は飛ばす)
+    pub fn load_since(&self, since: DateTime<Utc>) -> Result<Vec<EventRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read events file: {:?}", self.path))?;
+        let mut events: Vec<EventRecord> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<EventRecord>(line).ok())
+            .filter(|e| e.at >= since)
+            .collect();
+        events.sort_by_key(|e| e.at);
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_load_since() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::with_path(dir.path().join("events.jsonl"));
+        let old = EventRecord {
+            at: Utc::now() - chrono::Duration::days(3),
+            pod: "a".to_string(),
+            project: None,
+            group: None,
+            kind: EventKind::Created,
+        };
+        let recent = EventRecord {
+            at: Utc::now(),
+            pod: "a".to_string(),
+            project: Some("apiary".to_string()),
+            group: None,
+            kind: EventKind::Status { from: PodStatus::Working, to: PodStatus::Idle },
+        };
+        store.append(&old).unwrap();
+        store.append(&recent).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("events.jsonl"))
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let events = store.load_since(Utc::now() - chrono::Duration::days(1)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, recent.kind);
+        assert_eq!(events[0].project.as_deref(), Some("apiary"));
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// 保持する履歴の最大件数
+pub const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// 左ペイン入力 (指示・スラッシュコマンド) の履歴
+///
+/// entries は古い順。Up/Down で辿っている間は index が選択中の位置を指し、
+/// 辿り始める前の入力を draft に退避しておく。
+#[derive(Debug, Clone, Default)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    index: Option<usize>,
+    draft: String,
+}
+
+impl InputHistory {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self { entries, index: None, draft: String::new() }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// 確定した入力を追加 (直前と同じなら追加しない)。辿っている位置はリセット
+    pub fn push(&mut self, entry: &str) {
+        self.reset();
+        let entry = entry.trim();
+        if entry.is_empty() || self.entries.last().map(String::as_str) == Some(entry) {
+            return;
+        }
+        self.entries.push(entry.to_string());
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            let excess = self.entries.len() - MAX_HISTORY_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Up: 1 つ古い履歴。辿り始めなら current を draft として退避
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let idx = match self.index {
+            None => {
+                if self.entries.is_empty() {
+                    return None;
+                }
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => 0,
+            Some(i) => i - 1,
+        };
+        self.index = Some(idx);
+        self.entries.get(idx).map(String::as_str)
+    }
+
+    /// Down: 1 つ新しい履歴。最新を越えたら退避していた draft に戻る
+    pub fn newer(&mut self) -> Option<&str> {
+        let idx = self.index?;
+        if idx + 1 < self.entries.len() {
+            self.index = Some(idx + 1);
+            self.entries.get(idx + 1).map(String::as_str)
+        } else {
+            self.index = None;
+            Some(self.draft.as_str())
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.index = None;
+        self.draft.clear();
+    }
+}
+
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// 新しい HistoryStore を作成。パスは ~/.config/apiary/history.json
+    pub fn new() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .context("Failed to determine config directory")?
+            .join("apiary");
+
+        if !config_dir.exists() {
+            std::fs::create_dir_all(&config_dir)
+                .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+        }
+
+        Ok(Self { path: config_dir.join("history.json") })
+    }
+
+    /// カスタムパスで HistoryStore を作成（テスト用）
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// history.json を読み込む。存在しない・壊れている場合は空
+    pub fn load(&self) -> InputHistory {
+        let entries = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        InputHistory::new(entries)
+    }
+
+    /// 履歴を保存 (アトミック: tmp → rename)
+    pub fn save(&self, history: &InputHistory) -> Result<()> {
+        let content = serde_json::to_string_pretty(history.entries())
+            .context("Failed to serialize input history")?;
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, &content)
+            .with_context(|| format!("Failed to write temp history file: {:?}", tmp_path))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to rename temp history file: {:?}", tmp_path))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_older_newer_restores_draft() {
+        let mut history = InputHistory::new(vec!["first".to_string(), "second".to_string()]);
+
+        assert_eq!(history.older("typing"), Some("second"));
+        assert_eq!(history.older("second"), Some("first"));
+        assert_eq!(history.older("first"), Some("first"));
+        assert_eq!(history.newer(), Some("second"));
+        assert_eq!(history.newer(), Some("typing"));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn test_push_skips_duplicates_and_caps() {
+        let mut history = InputHistory::default();
+        history.push("fix bug");
+        history.push("fix bug");
+        history.push("  ");
+        assert_eq!(history.entries(), ["fix bug".to_string()]);
+
+        for i in 0..MAX_HISTORY_ENTRIES + 10 {
+            history.push(&format!("task {}", i));
+        }
+        assert_eq!(history.entries().len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.entries()[0], "task 10");
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = HistoryStore::with_path(tmp.path().to_path_buf());
+
+        let mut history = InputHistory::default();
+        history.push("/list");
+        history.push("add tests @apiary");
+        store.save(&history).unwrap();
+
+        assert_eq!(store.load().entries(), history.entries());
+    }
+}
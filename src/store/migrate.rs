@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::{Map, Value};
+
+/// pods.json の現在のスキーマバージョン
+pub const CURRENT_VERSION: u32 = 1;
+
+/// バージョン n から n + 1 に上げる変換 (index = 元のバージョン)
+const MIGRATIONS: &[fn(&mut Value)] = &[v0_to_v1];
+
+/// 読み込んだ pods.json を現在のスキーマに上げ、(Pod の配列, 元のバージョン) を返す。
+/// バージョンの無い配列は v0 とみなす。この apiary より新しいファイルはエラーにする
+pub fn upgrade(value: Value) -> Result<(Value, u32)> {
+    let (version, mut pods) = match value {
+        Value::Array(_) => (0, value),
+        Value::Object(mut file) => {
+            let version = file
+                .get("version")
+                .and_then(Value::as_u64)
+                .context("Missing schema version")? as u32;
+            (version, file.remove("pods").unwrap_or_else(|| Value::Array(Vec::new())))
+        }
+        _ => anyhow::bail!("Expected a list of pods"),
+    };
+    if version > CURRENT_VERSION {
+        anyhow::bail!(
+            "Schema version {} is newer than this apiary supports ({}); please upgrade apiary",
+            version,
+            CURRENT_VERSION
+        );
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut pods);
+    }
+    Ok((pods, version))
+}
+
+/// from の名前のフィールドを to に移す (to が既にあれば何もしない)
+fn rename(object: &mut Map<String, Value>, from: &str, to: &str) {
+    if !object.contains_key(to) {
+        if let Some(value) = object.remove(from) {
+            object.insert(to.to_string(), value);
+        }
+    }
+}
+
+/// v0 → v1: 必須フィールドの欠けた古い Pod / Member を既定値で埋め、worktree を project に改名する
+fn v0_to_v1(pods: &mut Value) {
+    let now = Value::String(Utc::now().to_rfc3339());
+    for pod in pods.as_array_mut().into_iter().flatten() {
+        let Some(pod) = pod.as_object_mut() else {
+            continue;
+        };
+        rename(pod, "worktree", "project");
+        let created_at = pod.entry("created_at").or_insert_with(|| now.clone()).clone();
+        if let Some(name) = pod.get("name").cloned() {
+            pod.entry("tmux_session").or_insert(name);
+        }
+        pod.entry("status").or_insert_with(|| "Idle".into());
+        let members = pod.entry("members").or_insert_with(|| Value::Array(Vec::new()));
+        let count = members.as_array().map_or(0, Vec::len);
+        for member in members.as_array_mut().into_iter().flatten().filter_map(Value::as_object_mut) {
+            member.entry("role").or_insert_with(|| "claude".into());
+            member.entry("status").or_insert_with(|| "Idle".into());
+            // pane が無い member は読み込み後の照合で取り除かれる
+            member.entry("tmux_pane").or_insert_with(|| "".into());
+            member.entry("last_change").or_insert_with(|| created_at.clone());
+            member.entry("last_output").or_insert_with(|| "".into());
+        }
+        pod.entry("pod_type").or_insert_with(|| if count > 1 { "Team" } else { "Solo" }.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pod::Pod;
+    use serde_json::json;
+
+    #[test]
+    fn test_upgrade_v0() {
+        let legacy = json!([{
+            "name": "old",
+            "worktree": "/src/app",
+            "members": [{"tmux_pane": "%1"}, {"role": "impl", "tmux_pane": "%2", "status": "Working"}]
+        }]);
+        let (pods, from) = upgrade(legacy).unwrap();
+        assert_eq!(from, 0);
+        let pods: Vec<Pod> = serde_json::from_value(pods).unwrap();
+        assert_eq!(pods[0].project.as_deref(), Some("/src/app"));
+        assert_eq!(pods[0].tmux_session, "old");
+        assert_eq!(pods[0].members.len(), 2);
+        assert_eq!(pods[0].members[0].role, "claude");
+        assert_eq!(pods[0].members[0].last_change, pods[0].created_at);
+        assert_eq!(pods[0].pod_type, crate::pod::PodType::Team);
+    }
+
+    #[test]
+    fn test_upgrade_current_and_newer() {
+        let (pods, from) = upgrade(json!({"version": CURRENT_VERSION, "pods": []})).unwrap();
+        assert_eq!((pods, from), (json!([]), CURRENT_VERSION));
+        let err = upgrade(json!({"version": CURRENT_VERSION + 1, "pods": []})).unwrap_err();
+        assert!(err.to_string().contains("upgrade apiary"));
+        assert!(upgrade(json!("pods")).is_err());
+    }
+}
@@ -1,14 +1,30 @@
+pub mod chat;
+pub mod events;
+pub mod history;
+pub mod migrate;
+pub mod secrets;
+pub mod trash;
+pub mod ui_state;
+
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
-use crate::pod::{MemberStatus, Pod, PodStatus};
-use crate::tmux::Tmux;
+use crate::pod::Pod;
+use crate::tmux::TmuxBackend;
 
 pub struct PodStore {
     path: PathBuf,
 }
 
+/// pods.json の中身 (スキーマバージョン付き)
+#[derive(Serialize)]
+struct StoreFile<'a> {
+    version: u32,
+    pods: &'a [Pod],
+}
+
 impl PodStore {
     /// 新しい PodStore を作成。パスは ~/.config/apiary/pods.json
     pub fn new() -> Result<Self> {
@@ -30,6 +46,16 @@ impl PodStore {
         Self { path }
     }
 
+    /// pods.json を置いているディレクトリ (Pod ごとに生成するファイルもここに置く)
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// Pod の環境変数の値を置くファイル (pods.json にはキーだけを書く)
+    fn env_path(&self) -> PathBuf {
+        self.dir().join("pod-env.json")
+    }
+
     /// pods.json を読み込んで Pod の Vec を返す
     /// ファイルが存在しない場合は空 Vec を返す
     pub fn load(&self) -> Result<Vec<Pod>> {
@@ -44,15 +70,52 @@ impl PodStore {
             return Ok(Vec::new());
         }
 
-        let pods: Vec<Pod> = serde_json::from_str(&content)
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse pods file: {:?}", self.path))?;
+        let (value, version) = migrate::upgrade(value)
+            .with_context(|| format!("Failed to migrate pods file: {:?}", self.path))?;
+        let mut pods: Vec<Pod> = serde_json::from_value(value)
             .with_context(|| format!("Failed to parse pods file: {:?}", self.path))?;
 
+        // 以前の pods.json は env の値を直接持っていたので、値を別ファイルに移して書き直す
+        let inline_env = pods.iter().any(|p| secrets::has_values(&p.env));
+        let saved_env = secrets::load(&self.env_path());
+        for pod in &mut pods {
+            secrets::fill(&mut pod.env, saved_env.get(&pod.name));
+        }
+
+        // 古いスキーマなら元のファイルを残してから新しい形式で書き直す
+        if version < migrate::CURRENT_VERSION {
+            let backup = self.backup_path(version);
+            std::fs::copy(&self.path, &backup)
+                .with_context(|| format!("Failed to back up pods file: {:?}", backup))?;
+            info!(
+                from = version,
+                to = migrate::CURRENT_VERSION,
+                backup = %backup.display(),
+                "Migrated pods file"
+            );
+            self.save(&pods)?;
+        } else if inline_env {
+            self.save(&pods)?;
+        }
+
         Ok(pods)
     }
 
-    /// Pod の Vec を pods.json に保存 (アトミック: tmp → rename)
+    /// マイグレーション前のファイルの退避先 (pods.json.v0.bak など)
+    pub fn backup_path(&self, version: u32) -> PathBuf {
+        self.path.with_extension(format!("json.v{}.bak", version))
+    }
+
+    /// Pod の Vec を pods.json に保存 (アトミック: tmp → rename)。
+    /// env の値は先に pod-env.json (0600) へ書く。いなくなった Pod の値はここで消える
     pub fn save(&self, pods: &[Pod]) -> Result<()> {
-        let content = serde_json::to_string_pretty(pods)
+        let env = pods.iter().map(|p| (p.name.clone(), p.env.clone())).collect();
+        secrets::save(&self.env_path(), &env)?;
+
+        let file = StoreFile { version: migrate::CURRENT_VERSION, pods };
+        let content = serde_json::to_string_pretty(&file)
             .context("Failed to serialize pods")?;
 
         let tmp_path = self.path.with_extension("json.tmp");
@@ -66,32 +129,39 @@ impl PodStore {
 
     /// 読み込んだ Pod を tmux の実態と照合
     /// セッションが存在しない Pod は削除せず Dead マークして保持
-    pub fn load_and_reconcile(&self) -> Result<Vec<Pod>> {
+    /// (tmux サーバーごと止まっていれば server_lost を付け、resurrect で作り直せるようにする)
+    pub fn load_and_reconcile(&self, tmux: &dyn TmuxBackend) -> Result<Vec<Pod>> {
         let mut pods = self.load()?;
+        let server_up = tmux.has_server();
 
-        // 存在する全ペインの ID を取得（一括取得で tmux 呼び出しを減らす）
-        let all_panes = Tmux::list_all_panes().unwrap_or_default();
-        let pane_ids: std::collections::HashSet<String> =
-            all_panes.iter().map(|p| p.id.clone()).collect();
+        // 存在する全ペインの ID を取得（一括取得で tmux 呼び出しを減らす）。
+        // 取れなかったときはメンバーを消さない
+        let pane_ids: Option<std::collections::HashSet<String>> =
+            tmux.list_all_panes().ok().map(|panes| panes.into_iter().map(|p| p.id).collect());
 
         let mut changed = false;
+        let now = chrono::Utc::now();
         for pod in &mut pods {
-            if !Tmux::session_exists(&pod.tmux_session) {
+            // 前回のプロセスが残した作業中の区間を確定 (落ちていた間は数えない)
+            for member in &mut pod.members {
+                changed |= member.reconcile_working_span(now);
+            }
+            if !tmux.session_exists(&pod.tmux_session) {
                 // セッションが存在しない → Dead マーク（削除しない）
-                if pod.status != PodStatus::Dead {
+                if pod.mark_dead(server_up) {
                     info!(
                         session = %pod.tmux_session,
                         pod = %pod.name,
+                        server_up,
                         "Marking pod as Dead: tmux session no longer exists"
                     );
-                    pod.status = PodStatus::Dead;
-                    for member in &mut pod.members {
-                        member.status = MemberStatus::Dead;
-                    }
                     changed = true;
                 }
                 continue; // Dead pod の member チェックはスキップ
             }
+            let Some(pane_ids) = &pane_ids else {
+                continue;
+            };
 
             // 生きている pod のみ member の pane 存在チェック
             let before_count = pod.members.len();
@@ -143,33 +213,16 @@ impl PodStore {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pod::{Member, MemberStatus, PodStatus, PodType};
-    use chrono::Utc;
+    use crate::pod::{Member, PodStatus};
     use std::fs;
     use tempfile::NamedTempFile;
 
     fn make_test_pod(name: &str) -> Pod {
         Pod {
             name: name.to_string(),
-            pod_type: PodType::Solo,
-            members: vec![Member {
-                role: "leader".to_string(),
-                status: MemberStatus::Idle,
-                tmux_pane: "%0".to_string(),
-                last_change: Utc::now(),
-                last_output: String::new(),
-                last_output_ansi: String::new(),
-                pane_size: (80, 24),
-                last_polled: None,
-                working_secs: 0,
-                sub_agents: Vec::new(),
-            }],
-            status: PodStatus::Idle,
+            members: vec![Member::new("leader", "%0")],
             tmux_session: format!("apiary-{}", name),
-            project: None,
-            group: None,
-            created_at: Utc::now(),
-            total_working_secs: 0,
+            ..Default::default()
         }
     }
 
@@ -195,6 +248,49 @@ mod tests {
         assert_eq!(loaded[1].name, "test2");
     }
 
+    #[test]
+    fn test_env_values_live_outside_pods_json() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = PodStore::with_path(dir.path().join("pods.json"));
+        let mut pod = make_test_pod("api");
+        pod.env.insert("API_TOKEN".to_string(), "s3cret".to_string());
+        store.save(&[pod, make_test_pod("web")]).unwrap();
+
+        let pods_json = fs::read_to_string(dir.path().join("pods.json")).unwrap();
+        assert!(pods_json.contains("API_TOKEN") && !pods_json.contains("s3cret"));
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded[0].env["API_TOKEN"], "s3cret");
+
+        // drop で pods.json からいなくなれば値も消える
+        store.save(&loaded[1..]).unwrap();
+        assert!(!dir.path().join("pod-env.json").exists());
+
+        // 値を直接持っていた以前の pods.json は読み込むときに移す
+        let mut legacy = make_test_pod("old");
+        legacy.env.insert("API_TOKEN".to_string(), "s3cret".to_string());
+        let inline = serde_json::json!({ "version": migrate::CURRENT_VERSION, "pods": [legacy] }).to_string().replace("\"API_TOKEN\":\"\"", "\"API_TOKEN\":\"s3cret\"");
+        fs::write(dir.path().join("pods.json"), inline).unwrap();
+        assert_eq!(store.load().unwrap()[0].env["API_TOKEN"], "s3cret");
+        assert!(!fs::read_to_string(dir.path().join("pods.json")).unwrap().contains("s3cret"));
+        assert_eq!(store.load().unwrap()[0].env["API_TOKEN"], "s3cret");
+    }
+
+    #[test]
+    fn test_load_migrates_legacy_file_with_backup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pods.json");
+        let legacy = serde_json::to_string(&vec![make_test_pod("old")]).unwrap();
+        fs::write(&path, &legacy).unwrap();
+
+        let store = PodStore::with_path(path.clone());
+        let pods = store.load().unwrap();
+        assert_eq!(pods[0].name, "old");
+        assert_eq!(fs::read_to_string(store.backup_path(0)).unwrap(), legacy);
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], migrate::CURRENT_VERSION);
+        assert_eq!(store.load().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_load_empty_file() {
         let tmp = NamedTempFile::new().unwrap();
@@ -244,4 +340,39 @@ mod tests {
         assert!(!removed);
         assert_eq!(pods.len(), 2);
     }
+
+    #[test]
+    fn test_load_and_reconcile_with_fake_tmux() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = PodStore::with_path(tmp.path().to_path_buf());
+        let tmux = crate::tmux::fake::FakeTmux::new();
+        let pane = tmux.add_session("apiary-alive", None);
+
+        let mut alive = make_test_pod("alive");
+        alive.members[0].tmux_pane = pane;
+        let mut gone_member = make_test_pod("alive").members.remove(0);
+        gone_member.tmux_pane = "%99".to_string();
+        alive.members.push(gone_member);
+        store.save(&[alive, make_test_pod("gone")]).unwrap();
+
+        let pods = store.load_and_reconcile(&tmux).unwrap();
+        assert_eq!(pods[0].members.len(), 1);
+        assert_eq!(pods[1].status, PodStatus::Dead);
+        // 照合結果は保存される
+        assert_eq!(store.load().unwrap()[1].status, PodStatus::Dead);
+    }
+
+    #[test]
+    fn test_load_and_reconcile_without_server() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = PodStore::with_path(tmp.path().to_path_buf());
+        let tmux = crate::tmux::fake::FakeTmux::new();
+        store.save(&[make_test_pod("a"), make_test_pod("b")]).unwrap();
+
+        // 再起動などでサーバーごと止まっていても Pod は消さず、resurrect できるよう印を付ける
+        let pods = store.load_and_reconcile(&tmux).unwrap();
+        assert_eq!(pods.len(), 2);
+        assert!(pods.iter().all(|p| p.status == PodStatus::Dead && p.server_lost));
+        assert!(store.load().unwrap().iter().all(|p| p.server_lost));
+    }
 }
@@ -0,0 +1,147 @@
+//! 環境変数の値 (API キーなど) の置き場所。
+//! pods.json / projects.json / ゴミ箱にはキーだけを書き、値は隣の 0600 のファイルに名前ごとにまとめて置く
+
+use anyhow::{Context, Result};
+use serde::Serializer;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::warn;
+
+/// 環境変数 (キー → 値)
+pub type EnvVars = BTreeMap<String, String>;
+
+/// `serialize_with` 用: 値を空にしてキーだけを書く
+pub fn keys_only<S: Serializer>(env: &EnvVars, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(env.keys().map(|key| (key, "")))
+}
+
+/// 値が入っているか (古いファイルは pods.json などに値を直接持っている)
+pub fn has_values(env: &EnvVars) -> bool {
+    env.values().any(|v| !v.is_empty())
+}
+
+/// キーだけ読んだ env に、保存してある値を入れる (値を持っている古いファイルはそのまま)
+pub fn fill(env: &mut EnvVars, saved: Option<&EnvVars>) {
+    let Some(saved) = saved else {
+        return;
+    };
+    for (key, value) in env.iter_mut() {
+        if value.is_empty() {
+            if let Some(saved) = saved.get(key) {
+                value.clone_from(saved);
+            }
+        }
+    }
+}
+
+/// 1 つの env を読む (無い・読めなければ空)
+pub fn load_one(path: &Path) -> EnvVars {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable env file {:?}: {}", path, e);
+            EnvVars::new()
+        }),
+        Err(_) => EnvVars::new(),
+    }
+}
+
+/// 名前ごとの env を読む (無い・読めなければ空)
+pub fn load(path: &Path) -> BTreeMap<String, EnvVars> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable env file {:?}: {}", path, e);
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// 名前ごとの env を 0600 で書く。値が 1 つも無ければファイルを消す。中身が同じなら書かない
+pub fn save(path: &Path, values: &BTreeMap<String, EnvVars>) -> Result<()> {
+    let values: BTreeMap<&String, &EnvVars> = values.iter().filter(|(_, env)| has_values(env)).collect();
+    if values.is_empty() {
+        return remove(path);
+    }
+    let content = serde_json::to_string_pretty(&values).context("Failed to serialize env values")?;
+    write_private(path, &content)
+}
+
+/// 1 つの env を 0600 で書く。値が無ければファイルを消す
+pub fn save_one(path: &Path, env: &EnvVars) -> Result<()> {
+    if !has_values(env) {
+        return remove(path);
+    }
+    let content = serde_json::to_string_pretty(env).context("Failed to serialize env values")?;
+    write_private(path, &content)
+}
+
+/// env のファイルを消す (無ければ何もしない)
+pub fn remove(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove env file: {:?}", path)),
+    }
+}
+
+/// 自分だけが読める (0600) ファイルに書く。既存のファイルも権限を絞り直す
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).with_context(|| format!("Failed to open env file: {:?}", path))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to restrict permissions of {:?}", path))?;
+    }
+    std::io::Write::write_all(&mut file, content.as_bytes()).with_context(|| format!("Failed to write env file: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_load_and_fill() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pod-env.json");
+        let env = EnvVars::from([("TOKEN".to_string(), "s3cret".to_string())]);
+        save(&path, &BTreeMap::from([("api".to_string(), env.clone()), ("web".to_string(), EnvVars::new())])).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let saved = load(&path);
+        assert_eq!(saved.keys().collect::<Vec<_>>(), ["api"]);
+
+        let mut keys = EnvVars::from([("TOKEN".to_string(), String::new()), ("OTHER".to_string(), String::new())]);
+        fill(&mut keys, saved.get("api"));
+        assert_eq!(keys["TOKEN"], "s3cret");
+        assert_eq!(keys["OTHER"], "");
+
+        save(&path, &BTreeMap::new()).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_keys_only() {
+        #[derive(serde::Serialize)]
+        struct Holder {
+            #[serde(serialize_with = "keys_only")]
+            env: EnvVars,
+        }
+        let holder = Holder { env: EnvVars::from([("TOKEN".to_string(), "s3cret".to_string())]) };
+        assert_eq!(serde_json::to_string(&holder).unwrap(), r#"{"env":{"TOKEN":""}}"#);
+    }
+}
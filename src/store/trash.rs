@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::pod::Pod;
+use crate::store::secrets;
+
+/// drop された Pod の退避レコード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedPod {
+    pub pod: Pod,
+    pub dropped_at: DateTime<Utc>,
+    /// drop 時点の各メンバーの scrollback (members と同じ順)
+    #[serde(default)]
+    pub scrollback: Vec<String>,
+    /// テンプレートから作った設定 (settings/<pod>.json) の中身。エントリの隣のファイルに置く
+    #[serde(skip)]
+    pub settings: Option<String>,
+}
+
+/// drop された Pod を保持するゴミ箱 (~/.config/apiary/trash/*.json)
+pub struct TrashStore {
+    dir: PathBuf,
+}
+
+impl TrashStore {
+    /// 新しい TrashStore を作成。ディレクトリは ~/.config/apiary/trash
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("Failed to determine config directory")?
+            .join("apiary")
+            .join("trash");
+        Ok(Self { dir })
+    }
+
+    /// カスタムディレクトリで TrashStore を作成（テスト用）
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Pod をゴミ箱に入れる
+    pub fn put(&self, entry: &TrashedPod) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create trash directory: {:?}", self.dir))?;
+
+        let safe_name: String = entry
+            .pod
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let path = self
+            .dir
+            .join(format!("{}-{}.json", safe_name, entry.dropped_at.timestamp_millis()));
+
+        // env の値はエントリの隣の 0600 のファイルに (エントリの JSON にはキーだけ)
+        secrets::save_one(&env_path(&path), &entry.pod.env)?;
+        if let Some(ref content) = entry.settings {
+            std::fs::write(settings_path(&path), content)
+                .with_context(|| format!("Failed to write trash file: {:?}", settings_path(&path)))?;
+        }
+        let content = serde_json::to_string_pretty(entry)
+            .context("Failed to serialize trashed pod")?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write trash file: {:?}", path))?;
+        Ok(path)
+    }
+
+    /// ゴミ箱の中身を新しい順に返す (読めないファイルは無視)
+    pub fn list(&self) -> Result<Vec<TrashedPod>> {
+        let mut entries: Vec<TrashedPod> = self
+            .entries()?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.dropped_at));
+        Ok(entries)
+    }
+
+    /// 指定した名前の最新エントリをゴミ箱から取り出す
+    pub fn take(&self, name: &str) -> Result<Option<TrashedPod>> {
+        let latest = self
+            .entries()?
+            .into_iter()
+            .filter(|(_, entry)| entry.pod.name == name)
+            .max_by_key(|(_, entry)| entry.dropped_at);
+
+        match latest {
+            Some((path, mut entry)) => {
+                secrets::fill(&mut entry.pod.env, Some(&secrets::load_one(&env_path(&path))));
+                entry.settings = std::fs::read_to_string(settings_path(&path)).ok();
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove trash file: {:?}", path))?;
+                secrets::remove(&env_path(&path))?;
+                remove_if_exists(&settings_path(&path))?;
+                Ok(Some(entry))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// ttl より古いエントリを削除し、削除件数を返す
+    pub fn purge(&self, ttl: Duration) -> Result<usize> {
+        let cutoff = Utc::now() - ttl;
+        let mut purged = 0;
+        for (path, entry) in self.entries()? {
+            if entry.dropped_at < cutoff {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove trash file: {:?}", path))?;
+                secrets::remove(&env_path(&path))?;
+                remove_if_exists(&settings_path(&path))?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+
+    fn entries(&self) -> Result<Vec<(PathBuf, TrashedPod)>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let read_dir = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read trash directory: {:?}", self.dir))?;
+
+        Ok(read_dir
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|p| read_entry(&p).map(|entry| (p, entry)))
+            .collect())
+    }
+}
+
+/// エントリの env の値を置くファイル (<name>-<ms>.env)
+fn env_path(entry_path: &Path) -> PathBuf {
+    entry_path.with_extension("env")
+}
+
+/// エントリの Pod の設定ファイルの写し (<name>-<ms>.settings)
+fn settings_path(entry_path: &Path) -> PathBuf {
+    entry_path.with_extension("settings")
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove trash file: {:?}", path))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn read_entry(path: &Path) -> Option<TrashedPod> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(name: &str, dropped_at: DateTime<Utc>) -> TrashedPod {
+        TrashedPod {
+            pod: Pod {
+                name: name.to_string(),
+                tmux_session: name.to_string(),
+                ..Default::default()
+            },
+            dropped_at,
+            scrollback: vec!["$ cargo test".to_string()],
+            settings: None,
+        }
+    }
+
+    #[test]
+    fn test_put_and_take_latest() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashStore::with_dir(dir.path().to_path_buf());
+
+        trash.put(&make_entry("auth/impl", Utc::now() - Duration::hours(2))).unwrap();
+        trash.put(&make_entry("auth/impl", Utc::now())).unwrap();
+        assert_eq!(trash.list().unwrap().len(), 2);
+
+        let taken = trash.take("auth/impl").unwrap().unwrap();
+        assert!(taken.dropped_at > Utc::now() - Duration::hours(1));
+        assert_eq!(taken.scrollback, vec!["$ cargo test".to_string()]);
+        assert_eq!(trash.list().unwrap().len(), 1);
+        assert!(trash.take("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_env_values_are_kept_beside_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashStore::with_dir(dir.path().to_path_buf());
+        let mut entry = make_entry("api", Utc::now());
+        entry.pod.env.insert("API_TOKEN".to_string(), "s3cret".to_string());
+        let path = trash.put(&entry).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("s3cret"));
+        assert!(env_path(&path).exists());
+
+        let taken = trash.take("api").unwrap().unwrap();
+        assert_eq!(taken.pod.env["API_TOKEN"], "s3cret");
+        assert!(!env_path(&path).exists());
+
+        let mut old = make_entry("old", Utc::now() - Duration::days(10));
+        old.pod.env.insert("API_TOKEN".to_string(), "s3cret".to_string());
+        let path = trash.put(&old).unwrap();
+        trash.purge(Duration::days(7)).unwrap();
+        assert!(!env_path(&path).exists());
+    }
+
+    #[test]
+    fn test_purge_removes_expired_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashStore::with_dir(dir.path().to_path_buf());
+
+        trash.put(&make_entry("old", Utc::now() - Duration::days(10))).unwrap();
+        trash.put(&make_entry("new", Utc::now())).unwrap();
+
+        assert_eq!(trash.purge(Duration::days(7)).unwrap(), 1);
+        let names: Vec<String> = trash.list().unwrap().into_iter().map(|e| e.pod.name).collect();
+        assert_eq!(names, vec!["new".to_string()]);
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::pod::{Mode, PaneLayout, ViewMode};
+
+/// 再起動をまたいで保持する UI 状態
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiState {
+    /// 折りたたまれているグループ名
+    #[serde(default)]
+    pub collapsed_groups: Vec<String>,
+    /// フォーカスしていた Pod 名 (並び順が変わっても復元できるよう名前で持つ)
+    #[serde(default)]
+    pub focused_pod: Option<String>,
+    /// タグフィルタ
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    /// 表示形式 (未保存なら config の ui.view)
+    #[serde(default)]
+    pub view_mode: Option<ViewMode>,
+    /// ワークスペースにしていたプロジェクト名
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// グリッドをワークスペースの Pod に絞っていたか
+    #[serde(default)]
+    pub workspace_filter: bool,
+    /// 最近ワークスペースにしたプロジェクト名 (新しい順)
+    #[serde(default)]
+    pub recent_workspaces: Vec<String>,
+    /// 終了時のモード (Home / Detail / Chat のみ復元)
+    #[serde(default)]
+    pub mode: Option<Mode>,
+    /// 左ペインを隠していたか
+    #[serde(default)]
+    pub left_pane_hidden: bool,
+    /// ペイン配置 (未保存なら config の ui.layout)
+    #[serde(default)]
+    pub layout: Option<PaneLayout>,
+    /// おやすみモード
+    #[serde(default)]
+    pub dnd: bool,
+    /// forget した・取り込めなかったため自動取り込みしない tmux セッション
+    #[serde(default)]
+    pub auto_adopt_ignored: Vec<String>,
+}
+
+pub struct UiStateStore {
+    path: PathBuf,
+}
+
+impl UiStateStore {
+    /// 新しい UiStateStore を作成。パスは ~/.config/apiary/ui_state.json
+    pub fn new() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .context("Failed to determine config directory")?
+            .join("apiary");
+
+        if !config_dir.exists() {
+            std::fs::create_dir_all(&config_dir)
+                .with_context(|| format!("Failed to create config directory: {:?}", config_dir))?;
+        }
+
+        Ok(Self { path: config_dir.join("ui_state.json") })
+    }
+
+    /// カスタムパスで UiStateStore を作成（テスト用）
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// ui_state.json を読み込む。存在しない・壊れている場合はデフォルト
+    pub fn load(&self) -> UiState {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// UI 状態を保存 (アトミック: tmp → rename)
+    pub fn save(&self, state: &UiState) -> Result<()> {
+        let content = serde_json::to_string_pretty(state)
+            .context("Failed to serialize UI state")?;
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, &content)
+            .with_context(|| format!("Failed to write temp UI state file: {:?}", tmp_path))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to rename temp UI state file: {:?}", tmp_path))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_load_missing_file_is_default() {
+        let store = UiStateStore::with_path(PathBuf::from("/tmp/apiary_test_ui_state_nonexistent.json"));
+        assert!(store.load().collapsed_groups.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_collapsed_groups() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = UiStateStore::with_path(tmp.path().to_path_buf());
+
+        let state = UiState { collapsed_groups: vec!["auth".to_string()], ..Default::default() };
+        store.save(&state).unwrap();
+
+        assert_eq!(store.load().collapsed_groups, vec!["auth".to_string()]);
+    }
+
+    #[test]
+    fn test_save_and_load_full_state() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = UiStateStore::with_path(tmp.path().to_path_buf());
+
+        let state = UiState {
+            collapsed_groups: Vec::new(),
+            focused_pod: Some("fix-login".to_string()),
+            tag_filter: Some("urgent".to_string()),
+            view_mode: Some(ViewMode::Kanban),
+            workspace: Some("apiary".to_string()),
+            workspace_filter: true,
+            recent_workspaces: vec!["apiary".to_string(), "web".to_string()],
+            mode: Some(Mode::Detail),
+            left_pane_hidden: true,
+            layout: Some(PaneLayout::Horizontal),
+            dnd: true,
+            auto_adopt_ignored: vec!["scratch".to_string()],
+        };
+        store.save(&state).unwrap();
+
+        let loaded = store.load();
+        assert_eq!(loaded.focused_pod.as_deref(), Some("fix-login"));
+        assert_eq!(loaded.tag_filter.as_deref(), Some("urgent"));
+        assert_eq!(loaded.view_mode, Some(ViewMode::Kanban));
+        assert_eq!(loaded.workspace.as_deref(), Some("apiary"));
+        assert!(loaded.workspace_filter);
+        assert_eq!(loaded.recent_workspaces, ["apiary", "web"]);
+        assert_eq!(loaded.mode, Some(Mode::Detail));
+        assert!(loaded.left_pane_hidden);
+        assert_eq!(loaded.layout, Some(PaneLayout::Horizontal));
+        assert!(loaded.dnd);
+        assert_eq!(loaded.auto_adopt_ignored, ["scratch"]);
+    }
+
+    #[test]
+    fn test_load_legacy_file() {
+        let tmp = NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), r#"{"collapsed_groups":["auth"]}"#).unwrap();
+        let store = UiStateStore::with_path(tmp.path().to_path_buf());
+
+        let loaded = store.load();
+        assert_eq!(loaded.collapsed_groups, vec!["auth".to_string()]);
+        assert!(loaded.focused_pod.is_none());
+        assert!(loaded.mode.is_none());
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::Result;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use super::{TmuxBackend, TmuxPane, TmuxSession};
+
+/// テスト用のメモリ上の tmux。セッション / ペイン / 送ったキー / 画面の出力を持つ。
+/// clone したものは同じ状態を共有するので、App に渡した後もテストから操作・確認できる
+#[derive(Clone, Default)]
+pub struct FakeTmux {
+    state: Rc<RefCell<FakeState>>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    panes: Vec<TmuxPane>,
+    /// ペインごとの capture-pane の結果
+    output: HashMap<String, String>,
+    /// (ペイン, 送った内容)。send_keys は Enter を別の要素として記録する
+    sent: Vec<(String, String)>,
+    /// new_session で渡された環境変数
+    env: HashMap<String, BTreeMap<String, String>>,
+    /// respawn_pane されたペイン (順に)
+    respawned: Vec<String>,
+    /// (セッション, オプション) ごとのユーザーオプション
+    options: HashMap<(String, String), String>,
+    /// pipe-pane しているペイン → (持ち主, コマンド)
+    pipes: HashMap<String, (String, String)>,
+    next_pane: usize,
+}
+
+impl FakeState {
+    fn add_pane(&mut self, session: &str, path: Option<&str>) -> String {
+        let id = format!("%{}", self.next_pane);
+        self.next_pane += 1;
+        let pane_index = self.panes.iter().filter(|p| p.session == session).count();
+        self.panes.push(TmuxPane {
+            id: id.clone(),
+            session: session.to_string(),
+            window_index: 0,
+            pane_index,
+            active: pane_index == 0,
+            title: String::new(),
+            pid: None,
+            current_path: path.map(str::to_string),
+        });
+        id
+    }
+
+    /// ペイン ID かセッション名 (最初のペイン) から対象のペインを探す
+    fn resolve(&self, target: &str) -> Result<String> {
+        let session = target.trim_start_matches('=').split(':').next().unwrap_or(target);
+        self.panes
+            .iter()
+            .find(|p| p.id == target || p.session == session)
+            .map(|p| p.id.clone())
+            .ok_or_else(|| anyhow::anyhow!("can't find pane: {}", target))
+    }
+
+    fn send(&mut self, target: &str, keys: &str) -> Result<()> {
+        let pane = self.resolve(target)?;
+        self.sent.push((pane, keys.to_string()));
+        Ok(())
+    }
+}
+
+impl FakeTmux {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// セッションを作って最初のペインの ID を返す
+    pub fn add_session(&self, name: &str, path: Option<&str>) -> String {
+        self.state.borrow_mut().add_pane(name, path)
+    }
+
+    /// セッションにペインを足してその ID を返す
+    pub fn add_pane(&self, session: &str) -> String {
+        self.state.borrow_mut().add_pane(session, None)
+    }
+
+    /// new_session でセッションに渡された環境変数
+    pub fn session_env(&self, name: &str) -> BTreeMap<String, String> {
+        self.state.borrow().env.get(name).cloned().unwrap_or_default()
+    }
+
+    /// ペインの画面に出ている内容を決める
+    pub fn set_output(&self, pane_id: &str, output: &str) {
+        self.state.borrow_mut().output.insert(pane_id.to_string(), output.to_string());
+    }
+
+    /// tmux の外でセッションが終了したことにする
+    pub fn remove_session(&self, name: &str) {
+        self.state.borrow_mut().panes.retain(|p| p.session != name);
+    }
+
+    pub fn sessions(&self) -> Vec<String> {
+        let mut sessions: Vec<String> = self.state.borrow().panes.iter().map(|p| p.session.clone()).collect();
+        sessions.sort();
+        sessions.dedup();
+        sessions
+    }
+
+    /// これまでに送られたキー (ペイン, 内容)
+    pub fn sent(&self) -> Vec<(String, String)> {
+        self.state.borrow().sent.clone()
+    }
+
+    /// set_session_option で設定されたセッションのオプション
+    pub fn session_option(&self, session: &str, option: &str) -> Option<String> {
+        self.state.borrow().options.get(&(session.to_string(), option.to_string())).cloned()
+    }
+
+    /// respawn_pane でシェルを起動し直したペイン
+    pub fn respawned(&self) -> Vec<String> {
+        self.state.borrow().respawned.clone()
+    }
+
+    /// ペインの pipe-pane のコマンド (止まっていれば None)
+    pub fn pipe_command(&self, pane_id: &str) -> Option<String> {
+        self.state.borrow().pipes.get(pane_id).map(|(_, cmd)| cmd.clone())
+    }
+
+    /// ペインに送られた内容だけ
+    pub fn sent_to(&self, pane_id: &str) -> Vec<String> {
+        self.state.borrow().sent.iter().filter(|(p, _)| p == pane_id).map(|(_, k)| k.clone()).collect()
+    }
+}
+
+impl TmuxBackend for FakeTmux {
+    fn has_server(&self) -> bool {
+        !self.state.borrow().panes.is_empty()
+    }
+
+    fn session_exists(&self, name: &str) -> bool {
+        self.state.borrow().panes.iter().any(|p| p.session == name)
+    }
+
+    /// 作成時刻は持たないので空
+    fn list_sessions(&self) -> Result<Vec<TmuxSession>> {
+        let state = self.state.borrow();
+        Ok(self
+            .sessions()
+            .into_iter()
+            .map(|name| {
+                let mut windows: Vec<usize> =
+                    state.panes.iter().filter(|p| p.session == name).map(|p| p.window_index).collect();
+                windows.dedup();
+                TmuxSession { name, windows: windows.len(), created: String::new() }
+            })
+            .collect())
+    }
+
+    fn list_panes(&self, session: &str) -> Result<Vec<TmuxPane>> {
+        let panes: Vec<TmuxPane> = self.state.borrow().panes.iter().filter(|p| p.session == session).cloned().collect();
+        if panes.is_empty() {
+            anyhow::bail!("can't find session: {}", session);
+        }
+        Ok(panes)
+    }
+
+    fn list_all_panes(&self) -> Result<Vec<TmuxPane>> {
+        Ok(self.state.borrow().panes.clone())
+    }
+
+    fn capture_pane_lines(&self, pane_id: &str, lines: i32) -> Result<String> {
+        let state = self.state.borrow();
+        let pane = state.resolve(pane_id)?;
+        let output = state.output.get(&pane).cloned().unwrap_or_default();
+        let all: Vec<&str> = output.lines().collect();
+        let start = all.len().saturating_sub(lines.max(0) as usize);
+        Ok(all[start..].join("\n"))
+    }
+
+    fn send_keys(&self, pane_id: &str, keys: &str) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        state.send(pane_id, keys)?;
+        state.send(pane_id, "Enter")
+    }
+
+    fn send_keys_literal(&self, pane_id: &str, text: &str) -> Result<()> {
+        self.state.borrow_mut().send(pane_id, text)
+    }
+
+    fn send_keys_raw(&self, pane_id: &str, keys: &str) -> Result<()> {
+        self.state.borrow_mut().send(pane_id, keys)
+    }
+
+    fn paste_text(&self, pane_id: &str, text: &str) -> Result<()> {
+        self.state.borrow_mut().send(pane_id, text)
+    }
+
+    fn resize_window(&self, pane_id: &str, _width: u16, _height: u16) -> Result<()> {
+        self.state.borrow().resolve(pane_id).map(|_| ())
+    }
+
+    fn get_window_size(&self, pane_id: &str) -> Result<(u16, u16)> {
+        self.state.borrow().resolve(pane_id).map(|_| (80, 24))
+    }
+
+    fn get_pane_size(&self, pane_id: &str) -> Result<(u16, u16)> {
+        self.get_window_size(pane_id)
+    }
+
+    /// 画面の最終行の先頭
+    fn get_cursor_position(&self, pane_id: &str) -> Result<(u16, u16)> {
+        self.state.borrow().resolve(pane_id).map(|_| (0, 23))
+    }
+
+    /// 色は持たないので capture_pane と同じ
+    fn capture_pane_ansi(&self, pane_id: &str) -> Result<String> {
+        self.capture_pane_lines(pane_id, 24)
+    }
+
+    fn pipe_pane_owned(&self, pane_id: &str, cmd: &str, owner: &str) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let pane = state.resolve(pane_id)?;
+        state.pipes.insert(pane, (owner.to_string(), cmd.to_string()));
+        Ok(())
+    }
+
+    fn pane_pipe_owner(&self, pane_id: &str) -> Option<String> {
+        let state = self.state.borrow();
+        let pane = state.resolve(pane_id).ok()?;
+        state.pipes.get(&pane).map(|(owner, _)| owner.clone())
+    }
+
+    fn pipe_pane_stop(&self, pane_id: &str) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let pane = state.resolve(pane_id)?;
+        state.pipes.remove(&pane);
+        Ok(())
+    }
+
+    fn new_session(&self, name: &str, start_dir: Option<&str>, env: &BTreeMap<String, String>) -> Result<String> {
+        if self.session_exists(name) {
+            anyhow::bail!("duplicate session: {}", name);
+        }
+        self.add_session(name, start_dir);
+        self.state.borrow_mut().env.insert(name.to_string(), env.clone());
+        Ok(name.to_string())
+    }
+
+    fn start_agent_in_session(&self, session: &str, command: &str, prompt: Option<&str>) -> Result<()> {
+        self.send_keys(session, command)?;
+        match prompt {
+            Some(p) => self.send_keys(session, p),
+            None => Ok(()),
+        }
+    }
+
+    fn split_window(&self, target: &str, _argv: &[&str]) -> Result<String> {
+        let mut state = self.state.borrow_mut();
+        let pane = state.resolve(target)?;
+        let session = state.panes.iter().find(|p| p.id == pane).map(|p| p.session.clone()).unwrap_or_default();
+        Ok(state.add_pane(&session, None))
+    }
+
+    fn kill_pane(&self, pane_id: &str) -> Result<()> {
+        self.state.borrow_mut().panes.retain(|p| p.id != pane_id);
+        Ok(())
+    }
+
+    /// 画面を消して記録する
+    fn respawn_pane(&self, pane_id: &str, _start_dir: Option<&str>) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let pane = state.resolve(pane_id)?;
+        state.output.remove(&pane);
+        state.respawned.push(pane);
+        Ok(())
+    }
+
+    fn move_pane(&self, pane_id: &str, target: &str) -> Result<()> {
+        let mut state = self.state.borrow_mut();
+        let session = state.resolve(target).ok().and_then(|id| state.panes.iter().find(|p| p.id == id).map(|p| p.session.clone()));
+        let session = session.unwrap_or_else(|| target.split(':').next().unwrap_or(target).to_string());
+        let pane = state.panes.iter_mut().find(|p| p.id == pane_id).ok_or_else(|| anyhow::anyhow!("can't find pane: {}", pane_id))?;
+        pane.session = session;
+        Ok(())
+    }
+
+    fn kill_session(&self, name: &str) -> Result<()> {
+        if !self.session_exists(name) {
+            anyhow::bail!("can't find session: {}", name);
+        }
+        self.remove_session(name);
+        Ok(())
+    }
+
+    fn set_session_option(&self, session: &str, option: &str, value: Option<&str>) -> Result<()> {
+        if !self.session_exists(session) {
+            anyhow::bail!("can't find session: {}", session);
+        }
+        let key = (session.to_string(), option.to_string());
+        let mut state = self.state.borrow_mut();
+        match value {
+            Some(value) => state.options.insert(key, value.to_string()),
+            None => state.options.remove(&key),
+        };
+        Ok(())
+    }
+
+    fn can_stream(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,213 @@
+//! `apiary tmux-integration`: Pod のセッションから apiary を動かしているセッションへ戻るキーバインド。
+//! バインドは ~/.config/apiary/tmux.conf に書き、tmux.conf からは source-file の 1 行だけで読み込む。
+//! apiary の TUI は起動時に自分のセッション名を `@apiary-session` に入れ、終了時に消す。
+//! Pod のセッションには `@apiary-pod` を付け、prefix+d はそのセッションでだけ apiary に戻る
+
+use anyhow::{Context, Result};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{shell_quote, POPUP_SESSION_PREFIX};
+
+/// apiary を動かしているセッション名を入れる tmux のグローバルなユーザーオプション
+pub const SESSION_OPTION: &str = "@apiary-session";
+
+/// Pod のセッションに付けるユーザーオプション (値は Pod 名)
+pub const POD_OPTION: &str = "@apiary-pod";
+
+/// tmux.conf に足す source-file 行の目印
+const SOURCE_MARKER: &str = "# apiary tmux-integration";
+
+/// apiary のバインドを書くファイル (~/.config/apiary/tmux.conf)
+pub fn conf_path() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("Failed to determine config directory")?
+        .join("apiary")
+        .join("tmux.conf"))
+}
+
+/// ユーザーの tmux.conf ($XDG_CONFIG_HOME/tmux/tmux.conf があればそれ、なければ ~/.tmux.conf)
+pub fn tmux_conf_path() -> Result<PathBuf> {
+    let xdg = dirs::config_dir().map(|d| d.join("tmux").join("tmux.conf"));
+    if let Some(path) = xdg.filter(|p| p.exists()) {
+        return Ok(path);
+    }
+    Ok(dirs::home_dir().context("Failed to determine home directory")?.join(".tmux.conf"))
+}
+
+/// バインドの内容。key は prefix の後に押すキー。
+/// ポップアップ (T) の中では戻る代わりにポップアップを閉じる
+pub fn integration_conf(key: &str) -> String {
+    let back = format!("run-shell -C \"switch-client -t '=#{{{}}}'\"", SESSION_OPTION);
+    let in_popup = format!("#{{m:{}*,#{{session_name}}}}", POPUP_SESSION_PREFIX);
+    format!(
+        "# Generated by `apiary tmux-integration install`; remove with `apiary tmux-integration uninstall`.\n\
+         # prefix+{key}: go back to the session running apiary\n\
+         bind-key {key} {{\n\
+         \x20 if-shell -F '{in_popup}' {{ detach-client }} {{\n\
+         \x20   if-shell -F '#{{{opt}}}' {{ {back} }} {{ display-message \"apiary is not running inside tmux\" }}\n\
+         \x20 }}\n\
+         }}\n\
+         # prefix+d in a Pod's session goes back to apiary instead of detaching\n\
+         bind-key d {{\n\
+         \x20 if-shell -F '#{{&&:#{{{opt}}},#{{{pod}}}}}' {{ {back} }} {{ detach-client }}\n\
+         }}\n",
+        key = key,
+        opt = SESSION_OPTION,
+        pod = POD_OPTION,
+        in_popup = in_popup,
+        back = back,
+    )
+}
+
+/// tmux.conf の内容に source-file 行を足す (もうあれば None)
+fn add_source_line(content: &str, conf: &Path) -> Option<String> {
+    if content.lines().any(|l| l.contains(SOURCE_MARKER)) {
+        return None;
+    }
+    let mut content = content.to_string();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("source-file -q {}  {}\n", shell_quote(&conf.to_string_lossy()), SOURCE_MARKER));
+    Some(content)
+}
+
+/// tmux.conf の内容から source-file 行を除く (無ければ None)
+fn remove_source_line(content: &str) -> Option<String> {
+    if !content.lines().any(|l| l.contains(SOURCE_MARKER)) {
+        return None;
+    }
+    Some(content.lines().filter(|l| !l.contains(SOURCE_MARKER)).map(|l| format!("{}\n", l)).collect())
+}
+
+/// tmux.conf を読む (まだ無ければ空)。読めないときは書き換えずにエラーにする
+fn read_tmux_conf(path: &Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    }
+}
+
+/// tmux.conf を書き換える。元のファイルは <path>.bak に残す
+fn rewrite_tmux_conf(path: &Path, content: &str) -> Result<()> {
+    if path.exists() {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        std::fs::copy(path, &backup).with_context(|| format!("Failed to back up {:?}", path))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// 動いている tmux サーバーで prefix+key が apiary のバインドになっているか
+fn bound_by_apiary(key: &str) -> bool {
+    Command::new("tmux")
+        .args(["list-keys", "-T", "prefix", key])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains(SESSION_OPTION))
+        .unwrap_or(false)
+}
+
+/// バインドを書き、tmux.conf から読み込むようにして、動いている tmux サーバーにも反映する。
+/// 書いたファイル (apiary の conf, tmux.conf) を返す
+pub fn install(key: &str) -> Result<(PathBuf, PathBuf)> {
+    let conf = conf_path()?;
+    if let Some(dir) = conf.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    std::fs::write(&conf, integration_conf(key)).with_context(|| format!("Failed to write {:?}", conf))?;
+
+    let tmux_conf = tmux_conf_path()?;
+    let content = read_tmux_conf(&tmux_conf)?;
+    if let Some(updated) = add_source_line(&content, &conf) {
+        rewrite_tmux_conf(&tmux_conf, &updated)?;
+    }
+
+    if super::Tmux::has_server() {
+        let output = Command::new("tmux")
+            .args(["source-file", &conf.to_string_lossy()])
+            .output()
+            .context("Failed to execute tmux source-file")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux source-file failed: {}", stderr.trim());
+        }
+    }
+    Ok((conf, tmux_conf))
+}
+
+/// install を取り消す。key と d のバインドは apiary のものになっているときだけ外す
+/// (d は既定の detach-client に戻す)。何か消したら true
+pub fn uninstall(key: &str) -> Result<bool> {
+    let mut removed = false;
+    let conf = conf_path()?;
+    if conf.exists() {
+        std::fs::remove_file(&conf).with_context(|| format!("Failed to remove {:?}", conf))?;
+        removed = true;
+    }
+    let tmux_conf = tmux_conf_path()?;
+    let content = read_tmux_conf(&tmux_conf)?;
+    if let Some(updated) = remove_source_line(&content) {
+        rewrite_tmux_conf(&tmux_conf, &updated)?;
+        removed = true;
+    }
+    if super::Tmux::has_server() {
+        if bound_by_apiary(key) {
+            let _ = Command::new("tmux").args(["unbind-key", key]).output();
+        }
+        if bound_by_apiary("d") {
+            let _ = Command::new("tmux").args(["bind-key", "d", "detach-client"]).output();
+        }
+    }
+    Ok(removed)
+}
+
+/// tmux の中で動いていれば、今のセッション名を `@apiary-session` に入れて返す
+pub fn mark_apiary_session() -> Option<String> {
+    std::env::var("TMUX").ok()?;
+    let output = Command::new("tmux").args(["display-message", "-p", "#{session_name}"]).output().ok()?;
+    let session = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || session.is_empty() {
+        return None;
+    }
+    Command::new("tmux").args(["set-option", "-g", SESSION_OPTION, &session]).output().ok()?;
+    Some(session)
+}
+
+/// 終了時に `@apiary-session` を消す (別の apiary が上書きしていたらそのまま)
+pub fn unmark_apiary_session(session: &str) {
+    let current = Command::new("tmux")
+        .args(["show-option", "-gqv", SESSION_OPTION])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    if current == session {
+        let _ = Command::new("tmux").args(["set-option", "-gu", SESSION_OPTION]).output();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integration_conf() {
+        let conf = integration_conf("A");
+        assert!(conf.contains("bind-key A {\n  if-shell -F '#{m:apiary-popup-*,#{session_name}}' { detach-client } {\n"));
+        assert!(conf.contains("{ run-shell -C \"switch-client -t '=#{@apiary-session}'\" } { detach-client }"));
+        assert!(conf.contains("bind-key d {\n  if-shell -F '#{&&:#{@apiary-session},#{@apiary-pod}}' {"));
+    }
+
+    #[test]
+    fn test_source_line_is_idempotent() {
+        let conf = Path::new("/home/me/.config/apiary/tmux.conf");
+        let added = add_source_line("set -g mouse on", conf).unwrap();
+        assert_eq!(added, "set -g mouse on\nsource-file -q '/home/me/.config/apiary/tmux.conf'  # apiary tmux-integration\n");
+        assert!(add_source_line(&added, conf).is_none());
+
+        assert_eq!(remove_source_line(&added).as_deref(), Some("set -g mouse on\n"));
+        assert!(remove_source_line("set -g mouse on\n").is_none());
+    }
+}
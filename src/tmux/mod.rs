@@ -1,6 +1,11 @@
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
 use std::process::Command;
 
+#[cfg(any(test, feature = "test-util"))]
+pub mod fake;
+pub mod integration;
+
 #[derive(Debug, Clone)]
 pub struct TmuxSession {
     pub name: String,
@@ -17,8 +22,37 @@ pub struct TmuxPane {
     pub active: bool,
     pub title: String,
     pub pid: Option<u32>,
+    /// ペインのカレントディレクトリ
+    pub current_path: Option<String>,
+}
+
+/// シェルに渡す 1 引数としてシングルクォートで囲む
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
 }
 
+/// ポップアップでアタッチするときに作るグループセッションの名前の接頭辞 (Pod / 管理外セッションとして扱わない)
+pub const POPUP_SESSION_PREFIX: &str = "apiary-popup-";
+
+/// ポップアップ用セッションのキーテーブル (Esc で detach、それ以外はペインへ)
+const POPUP_KEY_TABLE: &str = "apiary-popup";
+
+/// display-popup の中で走らせるコマンド。対象と同じウィンドウを共有するグループセッションを作ってアタッチし、
+/// そのセッションだけキーテーブルを切り替える。detach すると destroy-unattached でセッションごと消える
+pub fn popup_attach_command(session: &str) -> String {
+    let popup_session = format!("{}{}", POPUP_SESSION_PREFIX, session);
+    format!(
+        "TMUX= tmux new-session -t {} -s {} \\; set-option key-table {} \\; set-option status off \\; set-option destroy-unattached on",
+        shell_quote(&format!("={}", session)),
+        shell_quote(&popup_session),
+        POPUP_KEY_TABLE,
+    )
+}
+
+/// list-panes のフォーマット (タイトルは `|` を含みうるので末尾に置く)
+const PANE_FORMAT: &str =
+    "#{pane_id}|#{session_name}|#{window_index}|#{pane_index}|#{pane_active}|#{pane_pid}|#{pane_current_path}|#{pane_title}";
+
 pub struct Tmux;
 
 impl Tmux {
@@ -81,7 +115,7 @@ impl Tmux {
                 "list-panes",
                 "-t", session,
                 "-s",
-                "-F", "#{pane_id}|#{session_name}|#{window_index}|#{pane_index}|#{pane_active}|#{pane_title}|#{pane_pid}",
+                "-F", PANE_FORMAT,
             ])
             .output()
             .context("Failed to execute tmux list-panes")?;
@@ -100,7 +134,7 @@ impl Tmux {
             .args([
                 "list-panes",
                 "-a",
-                "-F", "#{pane_id}|#{session_name}|#{window_index}|#{pane_index}|#{pane_active}|#{pane_title}|#{pane_pid}",
+                "-F", PANE_FORMAT,
             ])
             .output()
             .context("Failed to execute tmux list-panes -a")?;
@@ -155,8 +189,18 @@ impl Tmux {
 
     /// ANSI エスケープ付きで pane の可視領域をキャプチャ (描画用)
     pub fn capture_pane_ansi(pane_id: &str) -> Result<String> {
+        Self::capture_pane_ansi_lines(pane_id, 0)
+    }
+
+    /// ANSI エスケープ付きで可視領域 + 直前の履歴 history 行をキャプチャ
+    pub fn capture_pane_ansi_lines(pane_id: &str, history: i32) -> Result<String> {
+        let mut args = vec!["capture-pane", "-e", "-p", "-t", pane_id];
+        let start = format!("-{}", history);
+        if history > 0 {
+            args.extend(["-S", &start]);
+        }
         let output = Command::new("tmux")
-            .args(["capture-pane", "-e", "-p", "-t", pane_id])
+            .args(&args)
             .output()
             .with_context(|| format!("Failed to capture pane '{}'", pane_id))?;
         if !output.status.success() {
@@ -251,6 +295,24 @@ impl Tmux {
         Ok((cols, rows))
     }
 
+    /// pane 内のカーソル位置 (x, y) を取得 (0 始まり)
+    pub fn get_cursor_position(pane_id: &str) -> Result<(u16, u16)> {
+        let output = Command::new("tmux")
+            .args(["display-message", "-t", pane_id, "-p", "#{cursor_x}|#{cursor_y}"])
+            .output()
+            .with_context(|| format!("Failed to get cursor position '{}'", pane_id))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("display-message failed for '{}': {}", pane_id, stderr.trim());
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        let (x, y) = text
+            .trim()
+            .split_once('|')
+            .with_context(|| format!("Unexpected cursor position format: {}", text.trim()))?;
+        Ok((x.parse().unwrap_or(0), y.parse().unwrap_or(0)))
+    }
+
     /// リテラルテキスト送信 (-l フラグで特殊文字をエスケープせずそのまま送信)
     pub fn send_keys_literal(pane_id: &str, text: &str) -> Result<()> {
         let output = Command::new("tmux")
@@ -264,6 +326,41 @@ impl Tmux {
         Ok(())
     }
 
+    /// テキストを 1 回の貼り付けとして送信。
+    /// load-buffer → paste-buffer -p で、アプリが bracketed paste を有効にしていれば
+    /// ESC[200~ … ESC[201~ で囲まれて届く (複数行でも個別のキー入力にならない)
+    pub fn paste_text(pane_id: &str, text: &str) -> Result<()> {
+        use std::io::Write;
+
+        let buffer = format!("apiary-paste-{}", pane_id.replace('%', ""));
+        let mut child = Command::new("tmux")
+            .args(["load-buffer", "-b", &buffer, "-"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .context("Failed to run tmux load-buffer")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).context("Failed to write paste buffer")?;
+        }
+        let output = child.wait_with_output().context("Failed to wait for tmux load-buffer")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("load-buffer failed: {}", stderr.trim());
+        }
+
+        // -d: 貼り付け後にバッファを削除
+        let output = Command::new("tmux")
+            .args(["paste-buffer", "-p", "-d", "-b", &buffer, "-t", pane_id])
+            .output()
+            .with_context(|| format!("Failed to paste into '{}'", pane_id))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("paste-buffer failed for '{}': {}", pane_id, stderr.trim());
+        }
+        Ok(())
+    }
+
     /// ペインにキー入力を送信 (Enter なし)
     pub fn send_keys_raw(pane_id: &str, keys: &str) -> Result<()> {
         let output = Command::new("tmux")
@@ -280,13 +377,16 @@ impl Tmux {
     }
 
     /// 新しいセッションを作成
-    pub fn new_session(name: &str, start_dir: Option<&str>) -> Result<String> {
+    pub fn new_session(name: &str, start_dir: Option<&str>, env: &BTreeMap<String, String>) -> Result<String> {
         let mut cmd = Command::new("tmux");
         cmd.args(["new-session", "-d", "-s", name]);
 
         if let Some(dir) = start_dir {
             cmd.args(["-c", dir]);
         }
+        for (key, value) in env {
+            cmd.arg("-e").arg(format!("{}={}", key, value));
+        }
 
         let output = cmd
             .output()
@@ -302,14 +402,63 @@ impl Tmux {
 
     /// セッション内で Claude Code を起動
     pub fn start_claude_in_session(session: &str, prompt: Option<&str>) -> Result<()> {
-        Self::send_keys(session, "claude")?;
+        Self::start_agent_in_session(session, "claude", prompt)
+    }
+
+    /// セッション内で command (Claude を起動するシェルコマンド) を実行し、prompt があれば送る
+    pub fn start_agent_in_session(session: &str, command: &str, prompt: Option<&str>) -> Result<()> {
+        Self::send_keys(session, command)?;
 
         if let Some(p) = prompt {
             // Claude の起動を待つために少し遅延
             std::thread::sleep(std::time::Duration::from_secs(2));
-            Self::send_keys(session, p)?;
+            if p.contains('\n') {
+                // 複数行は 1 回の貼り付けとして送り、途中の改行で送信されないようにする
+                Self::paste_text(session, p)?;
+                Self::send_keys_raw(session, "Enter")?;
+            } else {
+                Self::send_keys(session, p)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// target のペインを分割して argv を実行 (フォーカスは移さない)。新しいペインの ID を返す
+    pub fn split_window(target: &str, argv: &[&str]) -> Result<String> {
+        let command = argv.iter().map(|a| shell_quote(a)).collect::<Vec<_>>().join(" ");
+        let output = Command::new("tmux")
+            .args([
+                "split-window", "-d", "-t", target,
+                "-c", "#{pane_current_path}",
+                "-P", "-F", "#{pane_id}",
+                &command,
+            ])
+            .output()
+            .with_context(|| format!("Failed to split tmux pane '{}'", target))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux split-window failed for '{}': {}", target, stderr.trim());
         }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
 
+    /// ペインのプロセスを (子プロセスごと) 止め、同じペインでログインシェルを起動し直す
+    pub fn respawn_pane(pane_id: &str, start_dir: Option<&str>) -> Result<()> {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+        let mut cmd = Command::new("tmux");
+        cmd.args(["respawn-pane", "-k", "-t", pane_id]);
+        if let Some(dir) = start_dir {
+            cmd.args(["-c", dir]);
+        }
+        let output = cmd
+            .arg(&shell)
+            .output()
+            .with_context(|| format!("Failed to respawn tmux pane '{}'", pane_id))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux respawn-pane failed for '{}': {}", pane_id, stderr.trim());
+        }
         Ok(())
     }
 
@@ -326,6 +475,29 @@ impl Tmux {
         Ok(())
     }
 
+    /// ペインを target (ペインまたは "session:") の隣に移す。分割する余地がなければ
+    /// target のセッションの新しいウィンドウへ移す (どちらもペイン ID は変わらない)
+    pub fn move_pane(pane_id: &str, target: &str) -> Result<()> {
+        let joined = Command::new("tmux")
+            .args(["move-pane", "-d", "-s", pane_id, "-t", target])
+            .output()
+            .with_context(|| format!("Failed to move tmux pane '{}'", pane_id))?;
+        if joined.status.success() {
+            return Ok(());
+        }
+        let session = target.split(':').next().unwrap_or(target);
+        let window = format!("{}:", session);
+        let output = Command::new("tmux")
+            .args(["break-pane", "-d", "-s", pane_id, "-t", &window])
+            .output()
+            .with_context(|| format!("Failed to move tmux pane '{}'", pane_id))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux move-pane failed for '{}': {}", pane_id, stderr.trim());
+        }
+        Ok(())
+    }
+
     /// セッションを終了
     pub fn kill_session(name: &str) -> Result<()> {
         let exact = format!("={}", name);
@@ -342,6 +514,25 @@ impl Tmux {
         Ok(())
     }
 
+    /// セッションのユーザーオプション (@...) を設定する。value が None なら外す
+    pub fn set_session_option(session: &str, option: &str, value: Option<&str>) -> Result<()> {
+        // set-option の -t はペインとして解決されるので "=name:" でセッションを完全一致させる
+        let exact = format!("={}:", session);
+        let mut cmd = Command::new("tmux");
+        match value {
+            Some(value) => cmd.args(["set-option", "-t", &exact, option, value]),
+            None => cmd.args(["set-option", "-u", "-t", &exact, option]),
+        };
+        let output = cmd
+            .output()
+            .with_context(|| format!("Failed to set {} on tmux session '{}'", option, session))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux set-option failed for '{}': {}", session, stderr.trim());
+        }
+        Ok(())
+    }
+
     /// 現在の tmux prefix キーを取得 (例: "C-b", "C-a")
     pub fn get_prefix() -> String {
         Command::new("tmux")
@@ -353,14 +544,13 @@ impl Tmux {
             .unwrap_or_else(|| "C-b".to_string())
     }
 
-    /// pipe-pane でペインの PTY 出力をファイルにストリーム開始
-    pub fn pipe_pane_start(pane_id: &str, output_path: &str) -> Result<()> {
+    /// pipe-pane でペインの PTY 出力をシェルコマンドの標準入力に流す (既存の pipe は止める)
+    pub fn pipe_pane_command(pane_id: &str, cmd: &str) -> Result<()> {
         // 既存の pipe を停止
         let _ = Self::pipe_pane_stop(pane_id);
 
-        let cmd = format!("cat >> {}", output_path);
         let output = Command::new("tmux")
-            .args(["pipe-pane", "-O", "-t", pane_id, &cmd])
+            .args(["pipe-pane", "-O", "-t", pane_id, cmd])
             .output()
             .with_context(|| format!("Failed to start pipe-pane for '{}'", pane_id))?;
         if !output.status.success() {
@@ -370,6 +560,28 @@ impl Tmux {
         Ok(())
     }
 
+    /// pipe-pane を始め、誰が使っているかをペインのオプション @apiary_pipe に残す
+    /// ("log" / "record" / "detail")。ほかの用途と pipe を分け合うときに pane_pipe_owner で見分ける
+    pub fn pipe_pane_owned(pane_id: &str, cmd: &str, owner: &str) -> Result<()> {
+        Self::pipe_pane_command(pane_id, cmd)?;
+        let _ = Command::new("tmux")
+            .args(["set-option", "-p", "-t", pane_id, "@apiary_pipe", owner])
+            .output();
+        Ok(())
+    }
+
+    /// pipe-pane が動いていればその持ち主 (apiary 以外が始めたものは "")、止まっていれば None
+    pub fn pane_pipe_owner(pane_id: &str) -> Option<String> {
+        let output = Command::new("tmux")
+            .args(["display-message", "-t", pane_id, "-p", "#{pane_pipe} #{@apiary_pipe}"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let (piped, owner) = text.trim_end().split_once(' ').unwrap_or((text.trim(), ""));
+        (piped == "1").then(|| owner.to_string())
+    }
+
     /// pipe-pane を停止
     pub fn pipe_pane_stop(pane_id: &str) -> Result<()> {
         let output = Command::new("tmux")
@@ -415,6 +627,34 @@ impl Tmux {
         }
     }
 
+    /// tmux 内で display-popup を開き、その中でセッションにアタッチする (apiary は下に残る)。
+    /// ポップアップが閉じるのは待たない
+    pub fn attach_popup(name: &str) -> Result<()> {
+        // 前回のポップアップ用セッションが残っていたら片付ける
+        let popup_session = format!("={}{}", POPUP_SESSION_PREFIX, name);
+        let _ = Command::new("tmux").args(["kill-session", "-t", &popup_session]).output();
+        let output = Command::new("tmux")
+            .args(["bind-key", "-T", POPUP_KEY_TABLE, "Escape", "detach-client"])
+            .output()
+            .context("Failed to execute tmux bind-key")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("tmux bind-key failed: {}", stderr.trim());
+        }
+
+        let title = format!(" {} (Esc to close) ", name);
+        let mut child = Command::new("tmux")
+            .args(["display-popup", "-E", "-w", "90%", "-h", "90%", "-T", &title, &popup_attach_command(name)])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to open a tmux popup for '{}'", name))?;
+        // 閉じるまで戻らないので別スレッドで回収する
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+
     /// セッションが存在するか確認
     pub fn session_exists(name: &str) -> bool {
         // "=" プレフィックスで完全一致（tmux はデフォルトでプレフィックスマッチする）
@@ -460,6 +700,155 @@ impl Tmux {
     }
 }
 
+/// App / discovery / store が使う tmux 操作。本物は [`Tmux`]、テストでは `fake::FakeTmux`
+/// (`test-util` feature でクレートの外からも使える) を使う
+pub trait TmuxBackend {
+    /// tmux サーバーが動いているか (セッションが 1 つも無ければサーバーも止まる)
+    fn has_server(&self) -> bool;
+    fn session_exists(&self, name: &str) -> bool;
+    fn list_sessions(&self) -> Result<Vec<TmuxSession>>;
+    fn list_panes(&self, session: &str) -> Result<Vec<TmuxPane>>;
+    fn list_all_panes(&self) -> Result<Vec<TmuxPane>>;
+    fn capture_pane_lines(&self, pane_id: &str, lines: i32) -> Result<String>;
+    /// ペインの出力をキャプチャ (最新50行)
+    fn capture_pane(&self, pane_id: &str) -> Result<String> {
+        self.capture_pane_lines(pane_id, 50)
+    }
+    fn send_keys(&self, pane_id: &str, keys: &str) -> Result<()>;
+    fn send_keys_literal(&self, pane_id: &str, text: &str) -> Result<()>;
+    fn send_keys_raw(&self, pane_id: &str, keys: &str) -> Result<()>;
+    fn paste_text(&self, pane_id: &str, text: &str) -> Result<()>;
+    fn resize_window(&self, pane_id: &str, width: u16, height: u16) -> Result<()>;
+    fn get_window_size(&self, pane_id: &str) -> Result<(u16, u16)>;
+    fn get_pane_size(&self, pane_id: &str) -> Result<(u16, u16)>;
+    fn get_cursor_position(&self, pane_id: &str) -> Result<(u16, u16)>;
+    /// ANSI エスケープ付きで可視領域をキャプチャ
+    fn capture_pane_ansi(&self, pane_id: &str) -> Result<String>;
+    /// owner は "log" / "record" / "detail"
+    fn pipe_pane_owned(&self, pane_id: &str, cmd: &str, owner: &str) -> Result<()>;
+    fn pane_pipe_owner(&self, pane_id: &str) -> Option<String>;
+    fn pipe_pane_stop(&self, pane_id: &str) -> Result<()>;
+    /// env はセッションの環境変数 (new-session -e)
+    fn new_session(&self, name: &str, start_dir: Option<&str>, env: &BTreeMap<String, String>) -> Result<String>;
+    fn start_agent_in_session(&self, session: &str, command: &str, prompt: Option<&str>) -> Result<()>;
+    fn split_window(&self, target: &str, argv: &[&str]) -> Result<String>;
+    fn kill_pane(&self, pane_id: &str) -> Result<()>;
+    fn respawn_pane(&self, pane_id: &str, start_dir: Option<&str>) -> Result<()>;
+    fn move_pane(&self, pane_id: &str, target: &str) -> Result<()>;
+    fn kill_session(&self, name: &str) -> Result<()>;
+    fn set_session_option(&self, session: &str, option: &str, value: Option<&str>) -> Result<()>;
+    /// pipe-pane でペインの出力を流せるか (Detail の PTY ストリームに使う。偽物は false)
+    fn can_stream(&self) -> bool {
+        true
+    }
+}
+
+impl TmuxBackend for Tmux {
+    fn has_server(&self) -> bool {
+        Tmux::has_server()
+    }
+
+    fn session_exists(&self, name: &str) -> bool {
+        Tmux::session_exists(name)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<TmuxSession>> {
+        Tmux::list_sessions()
+    }
+
+    fn list_panes(&self, session: &str) -> Result<Vec<TmuxPane>> {
+        Tmux::list_panes(session)
+    }
+
+    fn list_all_panes(&self) -> Result<Vec<TmuxPane>> {
+        Tmux::list_all_panes()
+    }
+
+    fn capture_pane_lines(&self, pane_id: &str, lines: i32) -> Result<String> {
+        Tmux::capture_pane_lines(pane_id, lines)
+    }
+
+    fn send_keys(&self, pane_id: &str, keys: &str) -> Result<()> {
+        Tmux::send_keys(pane_id, keys)
+    }
+
+    fn send_keys_literal(&self, pane_id: &str, text: &str) -> Result<()> {
+        Tmux::send_keys_literal(pane_id, text)
+    }
+
+    fn send_keys_raw(&self, pane_id: &str, keys: &str) -> Result<()> {
+        Tmux::send_keys_raw(pane_id, keys)
+    }
+
+    fn paste_text(&self, pane_id: &str, text: &str) -> Result<()> {
+        Tmux::paste_text(pane_id, text)
+    }
+
+    fn resize_window(&self, pane_id: &str, width: u16, height: u16) -> Result<()> {
+        Tmux::resize_window(pane_id, width, height)
+    }
+
+    fn get_window_size(&self, pane_id: &str) -> Result<(u16, u16)> {
+        Tmux::get_window_size(pane_id)
+    }
+
+    fn get_pane_size(&self, pane_id: &str) -> Result<(u16, u16)> {
+        Tmux::get_pane_size(pane_id)
+    }
+
+    fn get_cursor_position(&self, pane_id: &str) -> Result<(u16, u16)> {
+        Tmux::get_cursor_position(pane_id)
+    }
+
+    fn capture_pane_ansi(&self, pane_id: &str) -> Result<String> {
+        Tmux::capture_pane_ansi(pane_id)
+    }
+
+    fn pipe_pane_owned(&self, pane_id: &str, cmd: &str, owner: &str) -> Result<()> {
+        Tmux::pipe_pane_owned(pane_id, cmd, owner)
+    }
+
+    fn pane_pipe_owner(&self, pane_id: &str) -> Option<String> {
+        Tmux::pane_pipe_owner(pane_id)
+    }
+
+    fn pipe_pane_stop(&self, pane_id: &str) -> Result<()> {
+        Tmux::pipe_pane_stop(pane_id)
+    }
+
+    fn new_session(&self, name: &str, start_dir: Option<&str>, env: &BTreeMap<String, String>) -> Result<String> {
+        Tmux::new_session(name, start_dir, env)
+    }
+
+    fn start_agent_in_session(&self, session: &str, command: &str, prompt: Option<&str>) -> Result<()> {
+        Tmux::start_agent_in_session(session, command, prompt)
+    }
+
+    fn split_window(&self, target: &str, argv: &[&str]) -> Result<String> {
+        Tmux::split_window(target, argv)
+    }
+
+    fn kill_pane(&self, pane_id: &str) -> Result<()> {
+        Tmux::kill_pane(pane_id)
+    }
+
+    fn respawn_pane(&self, pane_id: &str, start_dir: Option<&str>) -> Result<()> {
+        Tmux::respawn_pane(pane_id, start_dir)
+    }
+
+    fn move_pane(&self, pane_id: &str, target: &str) -> Result<()> {
+        Tmux::move_pane(pane_id, target)
+    }
+
+    fn kill_session(&self, name: &str) -> Result<()> {
+        Tmux::kill_session(name)
+    }
+
+    fn set_session_option(&self, session: &str, option: &str, value: Option<&str>) -> Result<()> {
+        Tmux::set_session_option(session, option, value)
+    }
+}
+
 /// git worktree を作成 (branch名 = name)
 pub fn create_worktree(path: &str, branch: &str) -> Result<()> {
     // まず branch が存在するか確認
@@ -504,8 +893,8 @@ fn parse_panes(stdout: &str) -> Result<Vec<TmuxPane>> {
     let mut panes = Vec::new();
 
     for line in stdout.lines() {
-        let parts: Vec<&str> = line.splitn(7, '|').collect();
-        if parts.len() < 7 {
+        let parts: Vec<&str> = line.splitn(8, '|').collect();
+        if parts.len() < 8 {
             continue;
         }
 
@@ -515,10 +904,65 @@ fn parse_panes(stdout: &str) -> Result<Vec<TmuxPane>> {
             window_index: parts[2].parse().unwrap_or(0),
             pane_index: parts[3].parse().unwrap_or(0),
             active: parts[4] == "1",
-            title: parts[5].to_string(),
-            pid: parts[6].trim().parse().ok(),
+            pid: parts[5].trim().parse().ok(),
+            current_path: Some(parts[6].to_string()).filter(|p| !p.is_empty()),
+            title: parts[7].to_string(),
         });
     }
 
     Ok(panes)
 }
+
+/// ペインの指定 ("%12" / "12" / "session:window.pane") に一致するペインを探す
+pub fn find_pane<'a>(panes: &'a [TmuxPane], target: &str) -> Option<&'a TmuxPane> {
+    let target = target.trim();
+    if let Some((session, rest)) = target.rsplit_once(':') {
+        let (window, pane) = rest.split_once('.').unwrap_or((rest, "0"));
+        let (window, pane): (usize, usize) = (window.parse().ok()?, pane.parse().ok()?);
+        return panes
+            .iter()
+            .find(|p| p.session == session && p.window_index == window && p.pane_index == pane);
+    }
+    let id = if target.starts_with('%') { target.to_string() } else { format!("%{}", target) };
+    panes.iter().find(|p| p.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_pane() {
+        let panes = parse_panes("%3|work|0|0|1|1|/a|\n%7|work|1|2|0|2|/b|\n").unwrap();
+        assert_eq!(find_pane(&panes, "%7").map(|p| p.id.as_str()), Some("%7"));
+        assert_eq!(find_pane(&panes, "3").map(|p| p.id.as_str()), Some("%3"));
+        assert_eq!(find_pane(&panes, "work:1.2").map(|p| p.id.as_str()), Some("%7"));
+        assert_eq!(find_pane(&panes, "work:0").map(|p| p.id.as_str()), Some("%3"));
+        assert!(find_pane(&panes, "%9").is_none());
+        assert!(find_pane(&panes, "other:0.0").is_none());
+    }
+
+    #[test]
+    fn test_parse_panes_title_with_separator() {
+        let panes = parse_panes("%3|work|0|1|1|4242|/home/me/app|✳ fix | refactor\n").unwrap();
+        assert_eq!(panes.len(), 1);
+        assert_eq!(panes[0].pid, Some(4242));
+        assert_eq!(panes[0].current_path.as_deref(), Some("/home/me/app"));
+        assert_eq!(panes[0].title, "✳ fix | refactor");
+    }
+
+    #[test]
+    fn test_popup_attach_command() {
+        assert_eq!(
+            popup_attach_command("fix auth"),
+            "TMUX= tmux new-session -t '=fix auth' -s 'apiary-popup-fix auth' \\; set-option key-table apiary-popup \\; \
+             set-option status off \\; set-option destroy-unattached on"
+        );
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/tmp/a b"), "'/tmp/a b'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,140 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::pod::TaskProgress;
+
+/// TODO 1 件の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+/// Claude Code の TodoWrite が ~/.claude/todos に書き出す TODO 1 件
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub content: String,
+    pub status: TodoStatus,
+    /// 進行中に表示される文言 ("Writing tests" など)
+    #[serde(default)]
+    pub active_form: Option<String>,
+}
+
+/// セッションの TODO リスト
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoList {
+    pub items: Vec<TodoItem>,
+}
+
+impl TodoList {
+    /// すべて完了しているか (空のリストは完了扱いにしない)
+    pub fn all_completed(&self) -> bool {
+        !self.items.is_empty() && self.items.iter().all(|i| i.status == TodoStatus::Completed)
+    }
+
+    /// カードや Detail に出す進捗 (進行中のタスク名は activeForm を優先)
+    pub fn progress(&self) -> TaskProgress {
+        let done = self.items.iter().filter(|i| i.status == TodoStatus::Completed).count();
+        let current = self
+            .items
+            .iter()
+            .find(|i| i.status == TodoStatus::InProgress)
+            .map(|i| i.active_form.clone().unwrap_or_else(|| i.content.clone()));
+        TaskProgress { done, total: self.items.len(), current }
+    }
+}
+
+/// ~/.claude/todos (Claude Code がセッションごとの TODO を置く場所)
+pub fn todos_root() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".claude").join("todos"))
+}
+
+/// transcript ディレクトリで最後に更新されたセッションの ID (<session>.jsonl のファイル名)
+pub fn latest_session_in(dir: &Path) -> Option<String> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|e| {
+            let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+            let stem = e.path().file_stem()?.to_str()?.to_string();
+            Some((modified, stem))
+        })
+        .max()
+        .map(|(_, stem)| stem)
+}
+
+/// 作業ディレクトリで動いている Claude Code の最新セッション ID
+pub fn latest_session(project_path: &str) -> Option<String> {
+    let root = crate::cost::transcripts_root()?;
+    latest_session_in(&root.join(crate::cost::transcript_dir_name(project_path)))
+}
+
+/// dir からセッションの TODO を読む。メインのエージェントの
+/// "<session>-agent-<session>.json" を優先し、無ければ "<session>-" で始まる最新のファイルを使う
+pub fn load_in(dir: &Path, session_id: &str) -> Option<TodoList> {
+    let main = dir.join(format!("{0}-agent-{0}.json", session_id));
+    let path = if main.is_file() {
+        main
+    } else {
+        let prefix = format!("{}-", session_id);
+        std::fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with(&prefix) && name.ends_with(".json")
+            })
+            .filter_map(|e| Some((e.metadata().and_then(|m| m.modified()).ok()?, e.path())))
+            .max()?
+            .1
+    };
+    let items = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+    Some(TodoList { items })
+}
+
+/// セッションの TODO を ~/.claude/todos から読む
+pub fn load(session_id: &str) -> Option<TodoList> {
+    load_in(&todos_root()?, session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_in() {
+        let dir = TempDir::new().unwrap();
+        let json = r#"[
+            {"content":"Read the code","status":"completed","activeForm":"Reading the code","id":"1"},
+            {"content":"Write tests","status":"in_progress","activeForm":"Writing tests","id":"2"},
+            {"content":"Update README","status":"pending","id":"3"}
+        ]"#;
+        std::fs::write(dir.path().join("abc-agent-abc.json"), json).unwrap();
+        std::fs::write(dir.path().join("other-agent-other.json"), "[]").unwrap();
+
+        let list = load_in(dir.path(), "abc").unwrap();
+        assert_eq!(list.items.len(), 3);
+        assert!(!list.all_completed());
+        let progress = list.progress();
+        assert_eq!((progress.done, progress.total), (1, 3));
+        assert_eq!(progress.current.as_deref(), Some("Writing tests"));
+
+        assert!(!load_in(dir.path(), "other").unwrap().all_completed());
+        assert!(load_in(dir.path(), "missing").is_none());
+    }
+
+    #[test]
+    fn test_all_completed() {
+        let item = |status| TodoItem { content: "x".to_string(), status, active_form: None };
+        let list = |items| TodoList { items };
+        assert!(list(vec![item(TodoStatus::Completed), item(TodoStatus::Completed)]).all_completed());
+        assert!(!list(vec![item(TodoStatus::Completed), item(TodoStatus::Pending)]).all_completed());
+        assert!(!list(vec![]).all_completed());
+    }
+}
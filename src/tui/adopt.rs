@@ -0,0 +1,89 @@
+use crate::pod::discovery::AdoptCandidate;
+use crate::tui::input::LineEditor;
+use crate::tui::palette::fuzzy_score;
+
+/// `a` のセッションピッカー。Pod になっていない tmux セッションを選んで取り込む
+#[derive(Debug, Clone, Default)]
+pub struct AdoptPicker {
+    pub query: LineEditor,
+    pub selected: usize,
+    /// 開いたときのセッション (Claude Code らしいものが先)
+    pub sessions: Vec<AdoptCandidate>,
+    /// 選択中のセッションのアクティブペインの画面 (ペイン ID, 出力)。App が定期的に取り直す
+    pub preview: Option<(String, String)>,
+}
+
+impl AdoptPicker {
+    pub fn new(sessions: Vec<AdoptCandidate>) -> Self {
+        Self { sessions, ..Default::default() }
+    }
+
+    /// クエリに合うセッションの index を返す。スコア順、同点は元の並び順
+    pub fn matches(&self) -> Vec<usize> {
+        let query = self.query.as_str();
+        let mut scored: Vec<(i64, usize)> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| {
+                let haystack = format!("{} {}", s.session, s.path.as_deref().unwrap_or(""));
+                fuzzy_score(query, &haystack).map(|score| (score, i))
+            })
+            .collect();
+        scored.sort_by(|(sa, a), (sb, b)| sb.cmp(sa).then(a.cmp(b)));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    pub fn selected_session(&self) -> Option<&AdoptCandidate> {
+        self.matches().get(self.selected).map(|&i| &self.sessions[i])
+    }
+
+    pub fn select_next(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(session: &str, path: &str, claude_panes: usize) -> AdoptCandidate {
+        AdoptCandidate {
+            session: session.to_string(),
+            windows: 1,
+            created: None,
+            panes: 1,
+            claude_panes,
+            path: Some(path.to_string()),
+            active_pane: None,
+        }
+    }
+
+    #[test]
+    fn test_matches_session_and_path() {
+        let mut picker = AdoptPicker::new(vec![
+            candidate("work", "/src/api", 1),
+            candidate("main", "/home/me", 0),
+            candidate("logs", "/var/log", 0),
+        ]);
+        assert_eq!(picker.selected_session().map(|s| s.session.as_str()), Some("work"));
+        picker.select_prev();
+        assert_eq!(picker.selected_session().map(|s| s.session.as_str()), Some("logs"));
+
+        picker.selected = 0;
+        picker.query.set("api");
+        assert_eq!(picker.matches(), vec![0]);
+        picker.query.set("zzz");
+        assert!(picker.selected_session().is_none());
+    }
+}
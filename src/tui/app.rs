@@ -1,15 +1,113 @@
-use crate::pod::detector::{detect_member_status_with_config, parse_permission_request, parse_sub_agents};
+use crate::pod::detector::{detect_member_status_for, parse_context_pressure, parse_permission_request, parse_question, parse_rate_limit_reset, parse_rate_limit_reset_at, parse_sub_agents, parse_task_progress};
+use crate::cost::BudgetLevel;
+use crate::config::ReapAction;
+use crate::docker::SandboxSpec;
+use crate::lifecycle::{self, LifecycleEvent};
 use crate::pod::discovery;
-use crate::pod::{AppState, BrowserEntry, BrowserState, ChatMessage, InlinePrompt, Member, MemberStatus, Mode, PaneFocus, Pod, PodStatus, PodType};
-use crate::project::ProjectStore;
+use crate::pod::{AppState, BrowserEntry, BrowserState, ChatMessage, InlinePrompt, Member, MemberStatus, Mode, OnDone, PaneFocus, Pod, PodPriority, PodStatus, PodType, ViewMode};
+use crate::project::{Project, ProjectStore, WorktreePolicy};
+use crate::pod::process::AgentProbe;
+use crate::store::chat::ChatStore;
+use crate::store::events::{EventKind, EventRecord, EventStore};
+use crate::store::history::{HistoryStore, InputHistory};
+use crate::store::trash::{TrashStore, TrashedPod};
+use crate::store::ui_state::{UiState, UiStateStore};
 use crate::store::PodStore;
-use crate::tmux::Tmux;
+use crate::tmux::{Tmux, TmuxBackend};
+use crate::tui::input::Completion;
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::io::Read as _;
 use std::path::{Path, PathBuf};
 
+/// PTY ストリームファイル (apiary-pty-<pane>.raw) を置くディレクトリ
+/// 生の端末出力には秘密情報が含まれうるため、共有の /tmp ではなく
+/// $XDG_RUNTIME_DIR/apiary (なければユーザーのキャッシュディレクトリ) を使う
+pub fn pty_stream_dir() -> PathBuf {
+    dirs::runtime_dir()
+        .map(|d| d.join("apiary"))
+        .or_else(|| dirs::cache_dir().map(|d| d.join("apiary").join("pty")))
+        .unwrap_or_else(|| std::env::temp_dir().join("apiary-pty"))
+}
+
+/// ディレクトリを 0700 で用意する (既存なら権限を絞り直し、シンボリックリンクは拒否)
+fn ensure_private_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create private directory: {:?}", dir))?;
+    let meta = std::fs::symlink_metadata(dir)
+        .with_context(|| format!("Failed to stat private directory: {:?}", dir))?;
+    if !meta.is_dir() {
+        anyhow::bail!("Private directory is not a directory: {:?}", dir);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if meta.permissions().mode() & 0o777 != 0o700 {
+            // 他ユーザー所有のディレクトリなら chmod が失敗するのでここで止まる
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+                .with_context(|| format!("Failed to restrict permissions of {:?}", dir))?;
+        }
+    }
+    Ok(())
+}
+
+/// 前回の異常終了で残った PTY ストリームファイルを削除 (旧バージョンの /tmp 配置も対象)
+fn sweep_orphan_pty_files() {
+    let live_panes: std::collections::HashSet<String> =
+        Tmux::list_all_panes().unwrap_or_default().into_iter().map(|p| p.id).collect();
+    for dir in [pty_stream_dir(), PathBuf::from("/tmp")] {
+        for path in crate::gc::orphan_pty_files(&dir, &live_panes) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 所有者のみ読み書きできるファイルを作成 (既存なら truncate)
+fn create_private_file(path: &Path) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .with_context(|| format!("Failed to create PTY stream file: {:?}", path))?;
+    Ok(())
+}
+
+/// 所有者のみ読み書きできるファイルを新しく作る (既にあれば失敗し、他人の置いたファイルやリンクは使わない)
+fn create_new_private_file(path: &Path) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .with_context(|| format!("Failed to create {:?}", path))?;
+    Ok(())
+}
+
+/// Detail 開始時にパーサーへ流し込む履歴行数 (pipe-pane 開始前の画面を即表示するため)
+const DETAIL_SEED_HISTORY_LINES: i32 = 200;
+
+/// capture-pane -e の出力を vt100 に流せるバイト列へ変換
+/// (改行は CR+LF に、最後にカーソルを元の位置へ戻す)。
+/// cursor は (x, 画面下端から数えた行: 最下行 = 1) で、pane とパーサーの高さが違っても下端基準で合わせる
+fn seed_bytes(capture: &str, rows: u16, cursor: Option<(u16, u16)>) -> Vec<u8> {
+    let mut bytes = capture.trim_end_matches('\n').replace('\n', "\r\n").into_bytes();
+    if let Some((x, from_bottom)) = cursor {
+        let row = rows.saturating_sub(from_bottom) + 1;
+        bytes.extend(format!("\x1b[{};{}H", row, x + 1).into_bytes());
+    }
+    bytes
+}
+
 /// pipe-pane ストリーミング + 永続 vt100 パーサー
 pub struct DetailPtyStream {
     parser: vt100::Parser,
@@ -18,18 +116,37 @@ pub struct DetailPtyStream {
     pane_id: String,
     cols: u16,
     rows: u16,
+    /// ログを取っているペインなら、止めるときに戻す log-sink のコマンド
+    log_cmd: Option<String>,
 }
 
 impl DetailPtyStream {
-    pub fn start(pane_id: &str, cols: u16, rows: u16) -> Result<Self> {
-        let file_path = PathBuf::from(format!("/tmp/apiary-pty-{}.raw", pane_id.replace('%', "")));
-
-        // ファイルを作成 (既存を truncate)
-        std::fs::File::create(&file_path)
-            .with_context(|| format!("Failed to create PTY stream file: {:?}", file_path))?;
+    /// log_cmd があれば `log-sink --tee` を挟み、ログを途切れさせずに pipe-pane を分け合う
+    pub fn start(pane_id: &str, cols: u16, rows: u16, log_cmd: Option<String>) -> Result<Self> {
+        let dir = pty_stream_dir();
+        ensure_private_dir(&dir)?;
+        let file_path = dir.join(format!("apiary-pty-{}.raw", pane_id.replace('%', "")));
+
+        // ファイルを作成 (既存を truncate、0600)
+        create_private_file(&file_path)?;
+
+        // 現在の画面 (+ 履歴) で初期化: 新しい出力を待たずに内容が見える
+        let mut parser = vt100::Parser::new(rows, cols, DETAIL_SEED_HISTORY_LINES as usize);
+        if let Ok(capture) = Tmux::capture_pane_ansi_lines(pane_id, DETAIL_SEED_HISTORY_LINES) {
+            let cursor = Tmux::get_cursor_position(pane_id)
+                .ok()
+                .zip(Tmux::get_pane_size(pane_id).ok())
+                .map(|((x, y), (_, height))| (x, height.saturating_sub(y)));
+            parser.process(&seed_bytes(&capture, rows, cursor));
+        }
 
         // pipe-pane 開始
-        Tmux::pipe_pane_start(pane_id, file_path.to_str().unwrap())?;
+        let sink = crate::tmux::shell_quote(&file_path.to_string_lossy());
+        let cmd = match log_cmd {
+            Some(ref log) => format!("{} --tee >> {}", log, sink),
+            None => format!("cat >> {}", sink),
+        };
+        Tmux::pipe_pane_owned(pane_id, &cmd, "detail")?;
 
         // resize して SIGWINCH → アプリが全画面再描画 → pipe がキャプチャ
         let _ = Tmux::resize_window(pane_id, cols, rows);
@@ -38,8 +155,6 @@ impl DetailPtyStream {
         let file = std::fs::File::open(&file_path)
             .with_context(|| format!("Failed to open PTY stream file: {:?}", file_path))?;
 
-        let parser = vt100::Parser::new(rows, cols, 0);
-
         Ok(Self {
             parser,
             file,
@@ -47,6 +162,7 @@ impl DetailPtyStream {
             pane_id: pane_id.to_string(),
             cols,
             rows,
+            log_cmd,
         })
     }
 
@@ -85,101 +201,518 @@ impl DetailPtyStream {
         (self.cols, self.rows)
     }
 
-    /// pipe-pane 停止 + ファイル削除
-    pub fn stop(self) {
-        let _ = Tmux::pipe_pane_stop(&self.pane_id);
+    /// pipe-pane 停止 + ファイル削除 (実処理は Drop)
+    pub fn stop(self) {}
+}
+
+/// panic 時の unwind や App の破棄でも pipe-pane とファイルが残らないように Drop で後始末
+impl Drop for DetailPtyStream {
+    fn drop(&mut self) {
+        match self.log_cmd {
+            Some(ref cmd) => {
+                let _ = Tmux::pipe_pane_owned(&self.pane_id, cmd, "log");
+            }
+            None => {
+                let _ = Tmux::pipe_pane_stop(&self.pane_id);
+            }
+        }
         let _ = std::fs::remove_file(&self.file_path);
     }
 }
 
+/// drop 時にゴミ箱へ退避する scrollback の行数
+const TRASH_SCROLLBACK_LINES: i32 = 2000;
+
+/// transcript から読んだモデル。transcript が伸びたときだけ読み直す
+#[derive(Debug, Default)]
+struct TranscriptModel {
+    size: u64,
+    model: Option<String>,
+    /// `/model` で切り替えた直後。transcript が前と同じモデルを返す間は Pod の表示を変えない
+    switched: bool,
+}
+
+/// ブラウザのパス入力を解決する (~ は $HOME、相対パスは base から)。`..` などはそのまま残す
+fn resolve_browser_path(base: &Path, input: &str) -> PathBuf {
+    let path = crate::project::expand_home(input);
+    if path.is_absolute() { path } else { base.join(path) }
+}
+
+/// ブラウザのパス入力の Tab 補完。最後の `/` より後ろを前方一致するディレクトリ名で補う
+/// (1 つなら末尾に `/`、複数なら共通部分まで)。補えなければ None
+pub fn complete_dir_path(base: &Path, input: &str) -> Option<String> {
+    let (dir, prefix) = match input.rfind('/') {
+        Some(i) => (&input[..=i], &input[i + 1..]),
+        None => ("", input),
+    };
+    let names: Vec<String> = std::fs::read_dir(resolve_browser_path(base, if dir.is_empty() { "." } else { dir }))
+        .ok()?
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|n| n.starts_with(prefix) && (prefix.starts_with('.') || !n.starts_with('.')))
+        .collect();
+    let first = names.first()?;
+    let common = names.iter().fold(first.as_str(), |acc, n| {
+        let len = acc.chars().zip(n.chars()).take_while(|(a, b)| a == b).map(|(c, _)| c.len_utf8()).sum();
+        &acc[..len]
+    });
+    let completed = if names.len() == 1 { format!("{}{}/", dir, common) } else { format!("{}{}", dir, common) };
+    (completed != input).then_some(completed)
+}
+
+/// `create_pod_with` の追加の指定 (`apiary create` のフラグ)
+#[derive(Debug, Clone, Default)]
+pub struct CreateOptions {
+    /// 起動した Claude に最初に送る指示
+    pub prompt: Option<String>,
+    pub sandbox: Option<SandboxSpec>,
+    /// `[templates.<name>]` の名前
+    pub template: Option<String>,
+    /// `--env KEY=VAL` (テンプレートの env より優先)
+    pub env: Vec<(String, String)>,
+    /// `--model` (テンプレートの model より優先)
+    pub model: Option<String>,
+}
+
+/// 左ペインのスラッシュコマンド (名前, 説明)。補完ポップアップにも使う
+pub const SLASH_COMMANDS: &[(&str, &str)] = &[
+    ("create", "Create a pod"),
+    ("adopt", "Adopt a tmux session"),
+    ("drop", "Drop a pod (to the trash)"),
+    ("restore", "Restore a dropped pod"),
+    ("forget", "Stop managing a pod"),
+    ("list", "List pods"),
+    ("project", "list / add / remove projects"),
+    ("browse", "Browse directories"),
+];
+
+fn slash_command_names() -> String {
+    SLASH_COMMANDS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+}
+
+/// Pod のペインで Claude を起動するコマンド (sandbox ならコンテナの中で)。
+/// テンプレートから作った設定は --settings で渡す (sandbox ならファイルをコンテナに見せて渡す)
+/// drop で消したテンプレートの設定ファイルを、ゴミ箱の写しから書き戻す
+fn restore_settings(entry: &TrashedPod) -> Result<()> {
+    let (Some(path), Some(content)) = (&entry.pod.settings, &entry.settings) else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to restore settings: {:?}", path))
+}
+
+/// sandbox が devcontainer なら (止まっていれば) 起動し直してコンテナ ID を返す (ID は変わりうる)。
+/// 終わるまで待つので、TUI からは裏のスレッドで呼ぶ
+fn start_devcontainer(pod: &Pod, project_path: Option<&str>) -> Option<String> {
+    let path = project_path?;
+    if !pod.sandbox.as_ref().is_some_and(|s| s.devcontainer) {
+        return None;
+    }
+    crate::docker::devcontainer_up(path)
+        .inspect_err(|e| tracing::warn!("Failed to start devcontainer for '{}': {}", pod.name, e))
+        .ok()
+}
+
+fn launch_command(pod: &Pod, project_path: Option<&str>) -> String {
+    let mut agent = "claude".to_string();
+    if let Some(ref model) = pod.model {
+        agent = format!("{} --model {}", agent, crate::tmux::shell_quote(model));
+    }
+    let settings = pod.settings.as_deref().filter(|path| {
+        let exists = path.is_file();
+        if !exists {
+            tracing::warn!("Ignoring missing settings for {}: {:?}", pod.name, path);
+        }
+        exists
+    });
+    match pod.sandbox {
+        Some(ref sandbox) => crate::docker::launch_command(sandbox, project_path, &pod.env, &agent, settings),
+        None => match settings {
+            Some(path) => format!("{} --settings {}", agent, crate::tmux::shell_quote(&path.to_string_lossy())),
+            None => agent,
+        },
+    }
+}
+
+/// 新しく作る Pod の名前を確かめる (settings/<pod>.json などのファイル名にも使うので '/' や ".." は不可)
+fn validate_pod_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains('/') || name.contains("..") {
+        anyhow::bail!("Invalid pod name '{}' (no '/' or '..')", name);
+    }
+    Ok(())
+}
+
+/// drop した Pod の設定ファイルを消す (中身はゴミ箱のエントリに入れてあり、restore で書き戻す)
+fn remove_pod_settings(pod: &Pod) {
+    if let Some(ref path) = pod.settings {
+        if let Err(e) = std::fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove settings of {}: {}", pod.name, e);
+            }
+        }
+    }
+}
+
+/// 管理外セッションの検出間隔 (全ペインを capture するので pods.json 再読み込みより長め)
+const UNMANAGED_SCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// TODO リストがすべて完了している Pod のリードメンバーの pane (入力待ちなら Done とみなす)
+fn todos_done_pane(pod: &Pod) -> Option<String> {
+    pod.todos
+        .as_ref()
+        .filter(|t| t.all_completed())
+        .and_then(|_| pod.members.first())
+        .map(|m| m.tmux_pane.clone())
+}
+
+/// カードに表示する git ブランチの更新間隔 (Pod ごとに git を起動するので長め)
+const BRANCH_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// ~/.claude/todos を読み直す間隔
+const TODOS_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// ログを取るペインで log-sink が動いているかを確かめる間隔
+const LOG_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Home のプロジェクト概要を集め直す間隔 (git status を起動するので長め)
+const HEALTH_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// セッションピッカーのプレビューに取り込む行数
+const ADOPT_PREVIEW_LINES: i32 = 60;
+
+/// 覚えておく最近のワークスペースの数 (ブラウザで 1-9 で選べる分)
+const RECENT_WORKSPACES: usize = 9;
+
+/// Working 中のメンバーの作業時間を pods.json に書き出す間隔 (クラッシュ時に失うのは最大でこの分)
+const WORKING_CHECKPOINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Permission / Error になったときにステータスバーを反転表示する時間
+const FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(1500);
+
+/// バックグラウンドの処理が終わったときに UI スレッドで App へ反映する内容
+type JobResult = Box<dyn FnOnce(&mut App) + Send>;
+
+/// App が使う保存先
+struct Stores {
+    pods: PodStore,
+    projects: ProjectStore,
+    ui_state: UiStateStore,
+    trash: TrashStore,
+    history: HistoryStore,
+    events: EventStore,
+    chats: ChatStore,
+}
+
 pub struct App {
     pub state: AppState,
     pub store: PodStore,
     pub project_store: ProjectStore,
+    pub ui_state_store: UiStateStore,
+    pub trash: TrashStore,
+    pub history_store: HistoryStore,
+    /// `apiary report` 用のイベントログ
+    pub events: EventStore,
+    /// Chat モードのやり取り (`apiary chat export` の読み出し元)
+    pub chat_store: ChatStore,
+    /// 左ペイン入力の履歴 (Up/Down で呼び出し)
+    pub input_history: InputHistory,
     pub config: crate::config::Config,
+    /// `[redaction]` をコンパイルしたもの。取り込んだ出力は保存・表示の前にこれを通す
+    pub redactor: crate::redact::Redactor,
     pub hooks: crate::hooks::HooksReceiver,
     pub detail_pty_stream: Option<DetailPtyStream>,
+    /// tmux の操作 (テストではメモリ上の偽物に差し替える)
+    pub tmux: Box<dyn TmuxBackend>,
     last_store_reload: std::time::Instant,
+    last_unmanaged_scan: Option<std::time::Instant>,
+    last_resource_sample: Option<std::time::Instant>,
+    last_branch_refresh: Option<std::time::Instant>,
+    last_todos_refresh: Option<std::time::Instant>,
+    last_working_checkpoint: Option<std::time::Instant>,
+    last_log_check: Option<std::time::Instant>,
+    /// Home に出すワークスペースのプロジェクトの概要
+    pub project_health: Option<crate::tui::health::ProjectHealth>,
+    last_health_refresh: Option<std::time::Instant>,
+    /// プロジェクトの概要を裏で集めている最中か
+    health_refresh_running: bool,
+    /// Pod ごとの transcript から読んだモデル
+    transcript_models: std::collections::HashMap<String, TranscriptModel>,
+    /// RateLimited の Pod ごとのリセット時刻 (上限を検出したときに出力から読む)
+    last_budget_check: Option<std::time::Instant>,
+    /// 予算の集計を裏で実行中か
+    budget_check_running: bool,
+    /// 予算ごと ("" は全体、それ以外はプロジェクト名) の前回の消化状況
+    budget_levels: std::collections::HashMap<String, BudgetLevel>,
+    last_reap_check: Option<std::time::Instant>,
+    /// `[reaper]` の猶予中の Pod と片付ける時刻。それまでに触られれば外れる
+    pub reap_pending: std::collections::HashMap<String, chrono::DateTime<Utc>>,
+    /// Stop の hooks で分かった、ペインごとの Claude Code のセッション ID
+    hook_sessions: std::collections::HashMap<String, String>,
+    /// ペインごとの `detector_command` の直近の結果 (None は組み込み検出に任せる)
+    external_statuses: std::collections::HashMap<String, Option<MemberStatus>>,
+    /// `detector_command` を裏で実行中のペイン
+    detectors_running: std::collections::HashSet<String>,
+    /// 前回 handle_status_changes した時点の各 Pod の状態
+    last_statuses: std::collections::HashMap<String, PodStatus>,
+    /// forget された・取り込めなかったため自動取り込みしない tmux セッション (ui_state.json に保存)
+    auto_adopt_ignored: std::collections::HashSet<String>,
+    /// 次の描画で端末に BEL を送る (`[notification] bell`)
+    bell_pending: bool,
+    /// この時刻までステータスバーを反転表示する (`[notification] flash`)
+    pub flash_until: Option<std::time::Instant>,
+    /// spawn_job で動かした処理の結果の受け渡し
+    job_tx: std::sync::mpsc::Sender<JobResult>,
+    job_rx: std::sync::mpsc::Receiver<JobResult>,
+    /// 結果をまだ反映していないバックグラウンドの処理の数
+    running_jobs: usize,
 }
 
 impl App {
     pub fn new(store: PodStore) -> Result<Self> {
+        Self::with_tmux(store, Box::new(Tmux))
+    }
+
+    /// tmux の操作を差し替えて App を作る (設定・その他のストアは ~/.config/apiary)
+    pub fn with_tmux(store: PodStore, tmux: Box<dyn TmuxBackend>) -> Result<Self> {
         let config = crate::config::Config::load().unwrap_or_default();
-        let project_store = ProjectStore::new()?;
-        let pods = store.load_and_reconcile().unwrap_or_default();
-        let mut state = AppState::new();
-        state.pods = pods;
-        // 起動時に cwd からワークスペースを初期化
-        state.current_project = crate::project::resolve_project_or_cwd(&project_store, None).ok();
+        let stores = Stores {
+            pods: store,
+            projects: ProjectStore::new()?,
+            ui_state: UiStateStore::new()?,
+            trash: TrashStore::new()?,
+            history: HistoryStore::new()?,
+            events: EventStore::new()?,
+            chats: ChatStore::new()?,
+        };
+        if config.trash.ttl_days > 0 {
+            let ttl = chrono::Duration::days(config.trash.ttl_days as i64);
+            if let Err(e) = stores.trash.purge(ttl) {
+                tracing::warn!("Failed to purge trash: {}", e);
+            }
+        }
+        let saved_ui_state = stores.ui_state.load();
         let mut hooks = crate::hooks::HooksReceiver::new();
         hooks.init();
-        Ok(Self { state, store, project_store, config, hooks, detail_pty_stream: None, last_store_reload: std::time::Instant::now() })
+        sweep_orphan_pty_files();
+
+        let mut app = Self::build(stores, config, hooks, tmux);
+        // 起動時に cwd からワークスペースを初期化 (前回の UI 状態があれば restore_ui_state で上書き)
+        app.state.current_project = crate::project::resolve_project_or_cwd(&app.project_store, None).ok();
+        app.restore_ui_state(saved_ui_state);
+        Ok(app)
+    }
+
+    /// テスト用: 保存先をすべて dir の下にし、設定は既定値で App を作る
+    #[cfg(test)]
+    pub(crate) fn for_test(dir: &std::path::Path, tmux: Box<dyn TmuxBackend>) -> Self {
+        let stores = Stores {
+            pods: PodStore::with_path(dir.join("pods.json")),
+            projects: ProjectStore::with_path(dir.join("projects.json")),
+            ui_state: UiStateStore::with_path(dir.join("ui_state.json")),
+            trash: TrashStore::with_dir(dir.join("trash")),
+            history: HistoryStore::with_path(dir.join("history.json")),
+            events: EventStore::with_path(dir.join("events.jsonl")),
+            chats: ChatStore::with_path(dir.join("chats.json")),
+        };
+        let hooks = crate::hooks::HooksReceiver::with_path(dir.join("hooks.jsonl"));
+        Self::build(stores, crate::config::Config::default(), hooks, tmux)
     }
 
-    /// Pod を作成
-    pub fn create_pod(&mut self, name: &str, project_input: Option<&str>, group: Option<&str>, prompt: Option<&str>) -> Result<()> {
+    fn build(stores: Stores, config: crate::config::Config, hooks: crate::hooks::HooksReceiver, tmux: Box<dyn TmuxBackend>) -> Self {
+        let Stores { pods: store, projects: project_store, ui_state: ui_state_store, trash, history: history_store, events, chats: chat_store } = stores;
+        let input_history = history_store.load();
+        let mut state = AppState::new();
+        state.chat_history = chat_store.load();
+        state.pods = store.load_and_reconcile(tmux.as_ref()).unwrap_or_default();
+        let lost = state.pods.iter().filter(|p| p.server_lost).count();
+        if lost > 0 {
+            state.status_message = Some(format!(
+                "{} Pod(s) lost their sessions when the tmux server stopped; U (or apiary resurrect) brings them back",
+                lost
+            ));
+        }
+        state.view_mode = config.ui.view;
+        state.layout = config.ui.layout;
+        state.group_by_project = config.ui.group_by_project;
+        let redactor = crate::redact::Redactor::new(&config.redaction);
+        let (job_tx, job_rx) = std::sync::mpsc::channel();
+        Self { state, store, project_store, ui_state_store, trash, history_store, events, chat_store, input_history, config, redactor, hooks, detail_pty_stream: None, tmux, last_store_reload: std::time::Instant::now(), last_unmanaged_scan: None, last_resource_sample: None, last_branch_refresh: None, last_todos_refresh: None, last_working_checkpoint: None, last_log_check: None, project_health: None, last_health_refresh: None, health_refresh_running: false, transcript_models: std::collections::HashMap::new(), last_budget_check: None, budget_check_running: false, budget_levels: std::collections::HashMap::new(), last_reap_check: None, reap_pending: std::collections::HashMap::new(), hook_sessions: std::collections::HashMap::new(), external_statuses: std::collections::HashMap::new(), detectors_running: std::collections::HashSet::new(), last_statuses: std::collections::HashMap::new(), auto_adopt_ignored: std::collections::HashSet::new(), bell_pending: false, flash_until: None, job_tx, job_rx, running_jobs: 0 }
+    }
+
+    /// Pod を作成 (sandbox があれば Claude をそのコンテナの中で起動する)
+    pub fn create_pod(&mut self, name: &str, project_input: Option<&str>, group: Option<&str>, prompt: Option<&str>, sandbox: Option<&SandboxSpec>) -> Result<()> {
+        let options = CreateOptions { prompt: prompt.map(String::from), sandbox: sandbox.cloned(), ..Default::default() };
+        self.create_pod_with(name, project_input, group, &options)
+    }
+
+    /// options (テンプレートや環境変数) を指定して Pod を作成
+    pub fn create_pod_with(&mut self, name: &str, project_input: Option<&str>, group: Option<&str>, options: &CreateOptions) -> Result<()> {
+        let prompt = options.prompt.as_deref();
+
+        validate_pod_name(name)?;
         // 同名チェック
         if self.state.pods.iter().any(|p| p.name == name) {
             anyhow::bail!("Pod '{}' already exists", name);
         }
 
         // プロジェクト解決: @project 指定 > current_project > cwd フォールバック
-        let project = if let Some(input) = project_input {
+        let mut project = if let Some(input) = project_input {
             crate::project::resolve_project(&self.project_store, input)?
         } else if let Some(ref cp) = self.state.current_project {
             cp.clone()
         } else {
             crate::project::resolve_project_or_cwd(&self.project_store, None)?
         };
+        // プロジェクトの既定値 < テンプレート < 明示した指定 の順に重ねる
+        let defaults = project.defaults.clone();
+        let template_name = options.template.clone().or_else(|| defaults.template.clone());
+        let template = match template_name {
+            Some(ref template) => Some(
+                self.config
+                    .templates
+                    .get(template)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Template '{}' not found (define it under [templates.{}])", template, template))?,
+            ),
+            None => None,
+        };
+        let mut env = defaults.env.clone();
+        env.extend(template.as_ref().map(|t| t.env.clone()).unwrap_or_default());
+        env.extend(options.env.iter().cloned());
+        let model = options.model.clone()
+            .or_else(|| template.as_ref().and_then(|t| t.model.clone()))
+            .or_else(|| defaults.model.clone());
+        let group = group.map(|s| s.to_string())
+            .or_else(|| defaults.group.clone())
+            .unwrap_or_else(|| project.name.clone());
+
+        // worktree = "per-pod" なら Pod ごとの worktree を作り、それを Pod のプロジェクトにする
+        let worktree_repo = if defaults.worktree == WorktreePolicy::PerPod && project.worktree_of.is_none() {
+            let repo = project.path.clone();
+            project = self.create_worktree_project(&project, name)?;
+            Some(repo)
+        } else {
+            None
+        };
 
-        // tmux セッションを作成 (プロジェクトパスを start_dir に)
-        Tmux::new_session(name, Some(project.path.as_str()))?;
+        // ここから先で失敗したら、作った worktree・設定ファイル・セッションを片付けてから返す
+        // (devcontainer は他の Pod と共有しうるので、drop と同じく止めない)
+        let mut sandbox = None;
+        let mut settings = None;
+        let mut session_created = false;
+        let started = (|| -> Result<String> {
+            if let Some(ref spec) = options.sandbox {
+                sandbox = Some(crate::docker::prepare(spec, name, &project.path)?);
+            }
+            if let Some(claude_settings) = template.as_ref().and_then(|t| t.claude_settings()) {
+                settings = Some(self.write_pod_settings(name, &claude_settings)?);
+            }
 
-        // Pod を作成 (Solo, 1 member "claude")
-        let panes = Tmux::list_panes(name)?;
-        let pane_id = panes
-            .first()
-            .map(|p| p.id.clone())
-            .unwrap_or_else(|| format!("%0"));
-
-        let member = Member {
-            role: "claude".to_string(),
-            status: MemberStatus::Idle,
-            tmux_pane: pane_id,
-            last_change: Utc::now(),
-            last_output: String::new(),
-            last_output_ansi: String::new(),
-            pane_size: (80, 24),
-            last_polled: None,
-            working_secs: 0,
-            sub_agents: Vec::new(),
+            // tmux セッションを作成 (プロジェクトパスを start_dir に)
+            self.tmux.new_session(name, Some(project.path.as_str()), &env)?;
+            session_created = true;
+            self.mark_pod_session(name, name);
+
+            let panes = self.tmux.list_panes(name)?;
+            Ok(panes
+                .first()
+                .map(|p| p.id.clone())
+                .unwrap_or_else(|| "%0".to_string()))
+        })();
+        let pane_id = match started {
+            Ok(pane_id) => pane_id,
+            Err(e) => {
+                if session_created {
+                    let _ = self.tmux.kill_session(name);
+                }
+                if let Some(ref path) = settings {
+                    let _ = std::fs::remove_file(path);
+                }
+                if let Some(repo) = worktree_repo {
+                    self.discard_worktree_project(&repo, &project);
+                }
+                return Err(e);
+            }
         };
 
-        let pod = Pod {
+        // Pod を作成 (Solo, 1 member "claude")
+
+        let member = Member::new("claude", &pane_id);
+
+        let mut pod = Pod {
             name: name.to_string(),
-            pod_type: PodType::Solo,
             members: vec![member],
-            status: PodStatus::Idle,
             tmux_session: name.to_string(),
             project: Some(project.name.clone()),
-            group: group.map(|s| s.to_string())
-                .or_else(|| Some(project.name.clone())),
-            created_at: Utc::now(),
-            total_working_secs: 0,
+            group: Some(group),
+            on_done: template.as_ref().and_then(|t| t.on_done.clone()),
+            sandbox,
+            env,
+            template: template_name,
+            settings,
+            model,
+            ..Default::default()
         };
+        if let Some(prompt) = prompt {
+            pod.record_prompt(prompt);
+        }
 
+        let command = launch_command(&pod, Some(project.path.as_str()));
+        lifecycle::fire(&self.config.lifecycle, LifecycleEvent::Create, &pod, None);
+        self.record_event(&pod, EventKind::Created);
         self.state.pods.push(pod);
         self.save()?;
 
         // Claude を起動
-        Tmux::start_claude_in_session(name, prompt)?;
+        self.tmux.start_agent_in_session(name, &command, prompt)?;
 
         Ok(())
     }
 
+    /// project の隣に `<project>-<pod>` の worktree (ブランチは Pod 名) を作り、プロジェクトとして登録する。
+    /// 不要になったら `apiary gc` が片付ける
+    fn create_worktree_project(&self, project: &Project, pod: &str) -> Result<Project> {
+        let name = format!("{}-{}", project.name, pod);
+        if self.project_store.find_by_name(&name)?.is_some() {
+            anyhow::bail!("Project '{}' already exists", name);
+        }
+        let path = Path::new(&project.path).with_file_name(&name);
+        crate::project::add_worktree(&project.path, &path, pod)?;
+        let worktree = Project {
+            worktree_of: Some(project.name.clone()),
+            ..Project::new(name, path.to_string_lossy())
+        };
+        self.project_store.register(&worktree)?;
+        Ok(worktree)
+    }
+
+    /// create_worktree_project で作った worktree を、Pod の作成に失敗したときに取り消す
+    fn discard_worktree_project(&self, repo: &str, worktree: &Project) {
+        if let Err(e) = crate::project::remove_worktree(repo, Path::new(&worktree.path)) {
+            tracing::warn!("Failed to remove worktree {}: {}", worktree.path, e);
+        }
+        if let Err(e) = self.project_store.unregister(&worktree.name) {
+            tracing::warn!("Failed to unregister project '{}': {}", worktree.name, e);
+        }
+    }
+
+    /// テンプレートから作った Claude Code の設定を settings/<pod>.json に書く (`claude --settings` で渡す)
+    fn write_pod_settings(&self, name: &str, settings: &serde_json::Value) -> Result<PathBuf> {
+        validate_pod_name(name)?;
+        let dir = self.store.dir().join("settings");
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        let path = dir.join(format!("{}.json", name));
+        std::fs::write(&path, serde_json::to_string_pretty(settings)?)
+            .with_context(|| format!("Failed to write settings: {:?}", path))?;
+        Ok(path)
+    }
+
     /// 既存 tmux セッションを Pod として取り込み
     pub fn adopt_session(&mut self, session: &str, name: Option<&str>, group: Option<&str>) -> Result<()> {
-        if !Tmux::session_exists(session) {
+        if !self.tmux.session_exists(session) {
             anyhow::bail!("tmux session '{}' does not exist", session);
         }
 
@@ -189,147 +722,2023 @@ impl App {
             anyhow::bail!("Pod '{}' already exists", pod_name);
         }
 
-        let panes = Tmux::list_panes(session)?;
+        let panes = self.tmux.list_panes(session)?;
         let members: Vec<Member> = panes
             .iter()
             .enumerate()
-            .map(|(i, pane)| Member {
-                role: if i == 0 {
-                    "lead".to_string()
-                } else {
-                    format!("member-{}", i)
-                },
-                status: MemberStatus::Idle,
-                tmux_pane: pane.id.clone(),
-                last_change: Utc::now(),
-                last_output: String::new(),
-                last_output_ansi: String::new(),
-                pane_size: (80, 24),
-                last_polled: None,
-                working_secs: 0,
-                sub_agents: Vec::new(),
+            .map(|(i, pane)| {
+                let role = if i == 0 { "lead".to_string() } else { format!("member-{}", i) };
+                Member::new(&role, &pane.id)
+            })
+            .collect();
+
+        let pod_type = if members.len() > 1 {
+            PodType::Team
+        } else {
+            PodType::Solo
+        };
+
+        let pod = Pod {
+            name: pod_name.to_string(),
+            pod_type,
+            members,
+            tmux_session: session.to_string(),
+            group: group.map(|s| s.to_string()),
+            ..Default::default()
+        };
+
+        lifecycle::fire(&self.config.lifecycle, LifecycleEvent::Create, &pod, None);
+        self.record_event(&pod, EventKind::Created);
+        self.mark_pod_session(&pod.name, session);
+        self.state.pods.push(pod);
+        self.save()?;
+
+        Ok(())
+    }
+
+    /// tmux-integration の prefix+d が apiary に戻るよう、セッションに `@apiary-pod` を付ける
+    fn mark_pod_session(&self, pod: &str, session: &str) {
+        if let Err(e) = self.tmux.set_session_option(session, crate::tmux::integration::POD_OPTION, Some(pod)) {
+            tracing::warn!("Failed to mark session '{}' of '{}': {}", session, pod, e);
+        }
+    }
+
+    /// 生きている Pod のセッションすべてに `@apiary-pod` を付ける (TUI の起動時。以前に作ったセッションの分)
+    pub fn mark_pod_sessions(&self) {
+        for pod in self.state.pods.iter().filter(|p| p.status != PodStatus::Dead) {
+            self.mark_pod_session(&pod.name, &pod.tmux_session);
+        }
+    }
+
+    /// 任意のペイン ("%12" / "session:window.pane") を Pod の member として追加し、role を返す
+    pub fn add_member(&mut self, pod_name: &str, pane: &str, role: Option<&str>) -> Result<String> {
+        let panes = self.tmux.list_all_panes()?;
+        let pane = crate::tmux::find_pane(&panes, pane)
+            .ok_or_else(|| anyhow::anyhow!("tmux pane '{}' does not exist", pane))?;
+        if let Some(owner) = self.state.pods.iter().find(|p| p.members.iter().any(|m| m.tmux_pane == pane.id)) {
+            anyhow::bail!("Pane {} is already a member of '{}'", pane.id, owner.name);
+        }
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+
+        let role = match role {
+            Some(r) if pod.members.iter().any(|m| m.role == r) => {
+                anyhow::bail!("'{}' already has a member named '{}'", pod_name, r)
+            }
+            Some(r) => r.to_string(),
+            None => (pod.members.len()..)
+                .map(|i| format!("member-{}", i))
+                .find(|r| !pod.members.iter().any(|m| &m.role == r))
+                .expect("unbounded range"),
+        };
+        pod.members.push(Member::new(&role, &pane.id));
+        if pod.members.len() > 1 {
+            pod.pod_type = PodType::Team;
+        }
+        self.save()?;
+        Ok(role)
+    }
+
+    /// Team Pod の member を同じセッションを共有する子 Pod として独立させ、新しい Pod 名を返す
+    pub fn split_member(&mut self, pod_name: &str, role: &str) -> Result<String> {
+        let child_name = format!("{}/{}", pod_name, role);
+        if self.state.pods.iter().any(|p| p.name == child_name) {
+            anyhow::bail!("Pod '{}' already exists", child_name);
+        }
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+        let mut child = discovery::split_member(pod, role)?;
+        // `member add` で他のセッションから来たペインはそのセッションの Pod にする
+        if let Some(pane) = self.tmux.list_all_panes()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|p| p.id == child.members[0].tmux_pane)
+        {
+            child.tmux_session = pane.session;
+        }
+        lifecycle::fire(&self.config.lifecycle, LifecycleEvent::Create, &child, None);
+        self.record_event(&child, EventKind::Created);
+        self.state.pods.push(child);
+        self.save()?;
+        Ok(child_name)
+    }
+
+    /// member の role を変更する。子 Pod ("<親>/<role>") なら Pod 名も合わせて変え、新しい Pod 名を返す
+    pub fn rename_member(&mut self, pod_name: &str, old: &str, new: &str) -> Result<Option<String>> {
+        let idx = self
+            .state
+            .pods
+            .iter()
+            .position(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+        let mut pod = self.state.pods[idx].clone();
+        let child_name = discovery::rename_member(&mut pod, old, new)?;
+        if let Some(ref child_name) = child_name {
+            if self.state.pods.iter().any(|p| &p.name == child_name) {
+                anyhow::bail!("Pod '{}' already exists", child_name);
+            }
+            if let Some(status) = self.last_statuses.remove(pod_name) {
+                self.last_statuses.insert(child_name.clone(), status);
+            }
+            if self.state.selected_pods.remove(pod_name) {
+                self.state.selected_pods.insert(child_name.clone());
+            }
+            pod.name = child_name.clone();
+        }
+        self.state.pods[idx] = pod;
+        self.save()?;
+        Ok(child_name)
+    }
+
+    /// source の member を target に合流させて Team Pod にする。セッションが違えば
+    /// attach で一緒に見えるよう source のペインを target のセッションへ移す
+    pub fn merge_pods(&mut self, target: &str, source: &str) -> Result<()> {
+        if target == source {
+            anyhow::bail!("Cannot merge '{}' into itself", target);
+        }
+        let find = |name: &str| {
+            self.state
+                .pods
+                .iter()
+                .position(|p| p.name == name)
+                .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))
+        };
+        let (target_idx, source_idx) = (find(target)?, find(source)?);
+        if self.state.pods[target_idx].status == PodStatus::Dead || self.state.pods[source_idx].status == PodStatus::Dead {
+            anyhow::bail!("Cannot merge a dead pod");
+        }
+
+        let session = self.state.pods[target_idx].tmux_session.clone();
+        let anchor = self.state.pods[target_idx]
+            .members
+            .last()
+            .map(|m| m.tmux_pane.clone())
+            .unwrap_or_else(|| format!("={}:", session));
+        let panes = self.tmux.list_all_panes()?;
+        for member in &self.state.pods[source_idx].members {
+            let elsewhere = panes.iter().any(|p| p.id == member.tmux_pane && p.session != session);
+            if elsewhere {
+                self.tmux.move_pane(&member.tmux_pane, &anchor)?;
+            }
+        }
+
+        let source_pod = self.state.pods.remove(source_idx);
+        self.record_event(&source_pod, EventKind::Dropped);
+        let target_pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == target)
+            .expect("target pod exists");
+        let leftover = discovery::merge_members(target_pod, source_pod);
+        // 引き継げなかったサンドボックスは、apiary が docker run したものなら drop と同じく止める
+        if let Some(sandbox) = leftover.filter(|s| s.image.is_some()) {
+            self.stop_container(source, &sandbox.container);
+        }
+        self.state.selected_pods.remove(source);
+        if self.state.focus.is_some_and(|f| f >= self.state.pods.len()) {
+            self.state.focus = Some(self.state.pods.len().saturating_sub(1));
+        }
+        self.save()
+    }
+
+    /// 選択中の Pod をフォーカス中の Pod にまとめて合流させる
+    pub fn merge_selected_into_focused(&mut self) -> String {
+        let Some(target) = self.state.focused_pod().map(|p| p.name.clone()) else {
+            return "No focused pod".to_string();
+        };
+        self.state.selected_pods.remove(&target);
+        let message = self.apply_to_selected(&format!("merged into '{}'", target), |app, name| app.merge_pods(&target, name));
+        if let Some(idx) = self.state.pods.iter().position(|p| p.name == target) {
+            self.state.focus = Some(idx);
+        }
+        message
+    }
+
+    /// どの Pod の member でもないペインの ID (`M` のプリフィル用)
+    pub fn first_unowned_pane(&self) -> Option<String> {
+        let panes = self.tmux.list_all_panes().ok()?;
+        panes
+            .into_iter()
+            .find(|p| !self.state.pods.iter().any(|pod| pod.members.iter().any(|m| m.tmux_pane == p.id)))
+            .map(|p| p.id)
+    }
+
+    /// Pod を削除 (同一 session を共有する Pod がなければ session ごと kill、あれば pane 単位で kill)
+    pub fn drop_pod(&mut self, name: &str) -> Result<()> {
+        let idx = self
+            .state
+            .pods
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+
+        let pod = &self.state.pods[idx];
+        let session = pod.tmux_session.clone();
+        let pane_ids: Vec<String> = pod.members.iter().map(|m| m.tmux_pane.clone()).collect();
+
+        // kill する前に scrollback ごとゴミ箱へ退避 (apiary restore で復元)
+        let scrollback = pane_ids
+            .iter()
+            .map(|id| {
+                let lines = self.tmux.capture_pane_lines(id, TRASH_SCROLLBACK_LINES).unwrap_or_default();
+                self.redactor.redact(&lines).into_owned()
             })
             .collect();
+        self.trash.put(&TrashedPod {
+            pod: pod.clone(),
+            dropped_at: Utc::now(),
+            scrollback,
+            settings: pod.settings.as_ref().and_then(|path| std::fs::read_to_string(path).ok()),
+        })?;
+        remove_pod_settings(pod);
+
+        // 同一 session を使う他の Pod があるか
+        let shared = self.state.pods.iter()
+            .any(|p| p.name != name && p.tmux_session == session);
+
+        if shared {
+            // pane 単位で kill（session は残す）
+            for pane_id in &pane_ids {
+                let _ = self.tmux.kill_pane(pane_id);
+            }
+        } else {
+            // 最後の Pod → session ごと kill
+            if self.tmux.session_exists(&session) {
+                self.tmux.kill_session(&session)?;
+            }
+        }
+
+        let pod = self.state.pods.remove(idx);
+        self.save()?;
+        // apiary が docker run したコンテナは止める (exec で入った既存のコンテナはそのまま)。
+        // docker stop は最大 10 秒かかるのでバックグラウンドで
+        if let Some(ref sandbox) = pod.sandbox {
+            if sandbox.image.is_some() {
+                self.stop_container(&pod.name, &sandbox.container);
+            }
+        }
+        lifecycle::fire(&self.config.lifecycle, LifecycleEvent::Drop, &pod, None);
+        self.record_event(&pod, EventKind::Dropped);
+
+        // focus の調整
+        if let Some(focus) = self.state.focus {
+            if focus >= self.state.pods.len() {
+                self.state.focus = if self.state.pods.is_empty() {
+                    None
+                } else {
+                    Some(self.state.pods.len() - 1)
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// サンドボックスのコンテナをバックグラウンドで止め、失敗したらステータスバーに出す
+    fn stop_container(&mut self, pod: &str, container: &str) {
+        let pod = pod.to_string();
+        let container = container.to_string();
+        self.spawn_job(move || {
+            let result = crate::docker::stop(&container);
+            Box::new(move |app: &mut App| {
+                if let Err(e) = result {
+                    tracing::warn!("Failed to stop container of '{}': {}", pod, e);
+                    app.state.status_message = Some(format!("Failed to stop the container of '{}': {}", pod, e));
+                }
+            })
+        });
+    }
+
+    /// 時間のかかる処理 (docker / git / gh など) を UI スレッドの外で動かす。
+    /// work が返した関数は次の poll_jobs で App に適用される
+    fn spawn_job<F>(&mut self, work: F)
+    where
+        F: FnOnce() -> JobResult + Send + 'static,
+    {
+        let tx = self.job_tx.clone();
+        self.running_jobs += 1;
+        std::thread::spawn(move || {
+            let _ = tx.send(work());
+        });
+    }
+
+    /// ペインの内容を `detector_command` に渡す。結果は次のポーリングから使う
+    /// (スクリプトが遅くても UI を止めないよう、ペインごとに 1 つずつ裏で実行する)
+    fn run_detectors(&mut self, requests: Vec<(String, String)>) {
+        let Some(command) = self.config.detection.detector_command.clone() else {
+            return;
+        };
+        let timeout = std::time::Duration::from_millis(self.config.detection.detector_timeout_ms);
+        for (pane, output) in requests {
+            if !self.detectors_running.insert(pane.clone()) {
+                continue;
+            }
+            let command = command.clone();
+            self.spawn_job(move || {
+                let status = crate::pod::detector::run_external_detector(&command, &output, &pane, timeout);
+                Box::new(move |app: &mut App| {
+                    app.detectors_running.remove(&pane);
+                    app.external_statuses.insert(pane, status);
+                })
+            });
+        }
+    }
+
+    /// 終わったバックグラウンドの処理の結果を反映する
+    pub fn poll_jobs(&mut self) {
+        while let Ok(apply) = self.job_rx.try_recv() {
+            self.running_jobs -= 1;
+            apply(self);
+        }
+    }
+
+    /// バックグラウンドの処理がすべて終わるまで待って反映する (CLI の終了前・TUI の終了時)
+    pub fn finish_jobs(&mut self) {
+        while self.running_jobs > 0 {
+            match self.job_rx.recv_timeout(std::time::Duration::from_secs(60)) {
+                Ok(apply) => {
+                    self.running_jobs -= 1;
+                    apply(self);
+                }
+                Err(_) => {
+                    tracing::warn!("Gave up waiting for {} background job(s)", self.running_jobs);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// イベントログに追記 (失敗しても操作は止めない)
+    fn record_event(&self, pod: &Pod, kind: EventKind) {
+        if let Err(e) = self.events.append(&EventRecord::new(pod, kind)) {
+            tracing::warn!("Failed to record event: {}", e);
+        }
+    }
+
+    /// 前回からの状態変化を処理する: `[lifecycle]` のコマンドを起動し、
+    /// アクセシビリティモードでは通知行に出す
+    pub fn handle_status_changes(&mut self) {
+        let changes = crate::pod::status_changes(&self.last_statuses, &self.state.pods);
+        let mut finished = Vec::new();
+        // (Pod 名, リセット時刻)。RateLimited になった・抜けた Pod のものを最後に Pod に書いて保存する
+        let mut resets = Vec::new();
+        for (pod, before) in &changes {
+            self.record_event(pod, EventKind::Status { from: before.clone(), to: pod.status.clone() });
+            // 優先度 Low の Pod は許可待ち以外の通知を出さず、High の Pod は作業完了も通知する
+            let alerts = self.alerts_enabled(pod);
+            let notify = alerts && self.config.notification.enabled && pod.priority != PodPriority::Low;
+            if let Some(event) = LifecycleEvent::from_transition(before, &pod.status) {
+                lifecycle::fire(&self.config.lifecycle, event, pod, Some(before));
+                if event == LifecycleEvent::Done && pod.on_done.is_some() {
+                    finished.push(pod.name.clone());
+                }
+                if event == LifecycleEvent::Done && notify && pod.priority == PodPriority::High {
+                    crate::notify::notify("Apiary: Done", &format!("Pod '{}' finished working", pod.name));
+                }
+            }
+            if pod.status == PodStatus::RateLimited {
+                let limited: Vec<&str> = pod
+                    .members
+                    .iter()
+                    .filter(|m| m.status == MemberStatus::RateLimited)
+                    .map(|m| m.last_output.as_str())
+                    .collect();
+                if let Some(at) = limited.iter().find_map(|o| parse_rate_limit_reset_at(o, chrono::Local::now())) {
+                    resets.push((pod.name.clone(), Some(at)));
+                }
+                if notify {
+                    let body = match limited.iter().find_map(|o| parse_rate_limit_reset(o)) {
+                        Some(at) => format!("Pod '{}' hit the usage limit (resets {})", pod.name, at),
+                        None => format!("Pod '{}' hit the usage limit", pod.name),
+                    };
+                    crate::notify::notify("Apiary: Rate Limited", &body);
+                }
+            } else if *before == PodStatus::RateLimited {
+                resets.push((pod.name.clone(), None));
+            }
+            // 許可待ちは優先度に関係なく、エラーは Low 以外でベル / フラッシュ
+            let alarming = pod.status == PodStatus::Permission
+                || (pod.status == PodStatus::Error && pod.priority != PodPriority::Low);
+            if alarming && alerts {
+                self.bell_pending |= self.config.notification.bell;
+                if self.config.notification.flash {
+                    self.flash_until = Some(std::time::Instant::now() + FLASH_DURATION);
+                }
+            }
+            if pod.status == PodStatus::AwaitingInput && notify {
+                let question = pod.members.iter().find_map(|m| parse_question(&m.last_output));
+                let body = match question {
+                    Some(q) => format!("Pod '{}' asks: {}", pod.name, q),
+                    None => format!("Pod '{}' is waiting for your answer", pod.name),
+                };
+                crate::notify::notify("Apiary: Question", &body);
+            }
+        }
+        if self.config.ui.accessible && !changes.is_empty() {
+            let text: Vec<String> = changes
+                .iter()
+                .map(|(pod, before)| format!("{}: {} -> {}", pod.name, before.label(), pod.status.label()))
+                .collect();
+            self.state.announcement = Some(text.join("; "));
+        }
+        self.last_statuses = self
+            .state
+            .pods
+            .iter()
+            .map(|p| (p.name.clone(), p.status.clone()))
+            .collect();
+        if !resets.is_empty() {
+            for (name, at) in resets {
+                if let Some(pod) = self.state.pods.iter_mut().find(|p| p.name == name) {
+                    pod.rate_limit_reset = at;
+                }
+            }
+            if let Err(e) = self.save() {
+                tracing::warn!("Failed to save usage limit resets: {}", e);
+            }
+        }
+        for name in finished {
+            if let Err(e) = self.run_on_done(&name) {
+                self.state.status_message = Some(format!("{}: on-done failed: {}", name, e));
+            }
+        }
+    }
+
+    /// Pod の通知・ベル・フラッシュを出してよいか (おやすみモードでも消音中でもない)
+    fn alerts_enabled(&self, pod: &Pod) -> bool {
+        !self.state.dnd && !pod.is_muted(Utc::now())
+    }
+
+    /// フォーカス中の Pod の通知を `[notification] snooze_minutes` だけ止める (消音中なら解除)
+    pub fn toggle_mute(&mut self) -> Result<()> {
+        let minutes = self.config.notification.snooze_minutes;
+        let Some(pod) = self.state.focus.and_then(|i| self.state.pods.get_mut(i)) else {
+            return Ok(());
+        };
+        let now = Utc::now();
+        self.state.status_message = Some(if pod.is_muted(now) {
+            pod.muted_until = None;
+            format!("Unmuted '{}'", pod.name)
+        } else {
+            let until = now + chrono::Duration::minutes(minutes as i64);
+            pod.muted_until = Some(until);
+            format!("Muted '{}' until {}", pod.name, until.with_timezone(&chrono::Local).format("%H:%M"))
+        });
+        self.save()
+    }
+
+    /// おやすみモード (全 Pod の通知を止める) を切り替える
+    pub fn toggle_dnd(&mut self) {
+        self.state.dnd = !self.state.dnd;
+        self.state.status_message = Some(
+            if self.state.dnd { "Do not disturb: notifications paused" } else { "Do not disturb off" }.to_string(),
+        );
+        let _ = self.save_ui_state();
+    }
+
+    /// 鳴らすべきベルがあれば true を返して取り消す
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell_pending)
+    }
+
+    /// ステータスバーを反転表示中か
+    pub fn flashing(&self) -> bool {
+        self.flash_until.is_some_and(|t| std::time::Instant::now() < t)
+    }
+
+    /// Pod が利用上限のリセット後に自動で再開する設定か
+    pub fn auto_resume_enabled(&self, pod: &Pod) -> bool {
+        pod.auto_resume.unwrap_or(self.config.rate_limit.auto_resume)
+    }
+
+    /// リセット時刻 (+ delay_secs) を過ぎた RateLimited の Pod に再開のメッセージを送る。
+    /// 送るのはリセット 1 回につき 1 度だけ
+    pub fn resume_rate_limited(&mut self) {
+        let now = Utc::now();
+        let delay = chrono::Duration::seconds(self.config.rate_limit.delay_secs as i64);
+        let due: Vec<String> = self
+            .state
+            .pods
+            .iter()
+            .filter(|p| p.rate_limit_reset.is_some_and(|at| now >= at + delay))
+            .map(|p| p.name.clone())
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+        for pod in self.state.pods.iter_mut().filter(|p| due.contains(&p.name)) {
+            pod.rate_limit_reset = None;
+        }
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save usage limit resets: {}", e);
+        }
+        for name in due {
+            let Some(pod) = self.state.pods.iter().find(|p| p.name == name) else {
+                continue;
+            };
+            if pod.status != PodStatus::RateLimited || !self.auto_resume_enabled(pod) {
+                continue;
+            }
+            let message = match pod.last_prompt {
+                Some(ref prompt) if self.config.rate_limit.resubmit_prompt => prompt.clone(),
+                _ => self.config.rate_limit.message.clone(),
+            };
+            let panes: Vec<String> = pod
+                .members
+                .iter()
+                .filter(|m| m.status == MemberStatus::RateLimited)
+                .map(|m| m.tmux_pane.clone())
+                .collect();
+            let result = panes.iter().try_for_each(|pane| self.tmux.send_keys(pane, &message));
+            self.state.status_message = Some(match result {
+                Ok(()) => format!("{}: resumed after the usage limit reset", name),
+                Err(e) => format!("{}: auto-resume failed: {}", name, e),
+            });
+        }
+    }
+
+    /// リードメンバーに Claude Code のスラッシュコマンド (/compact, /clear) を送る。
+    /// /clear なら会話から読み取った進捗・コンテキスト・TODO も捨てる
+    pub fn send_claude_command(&mut self, name: &str, command: &str) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        let lead = pod.members.first_mut().ok_or_else(|| anyhow::anyhow!("Pod '{}' has no members", name))?;
+        self.tmux.send_keys(&lead.tmux_pane, command)?;
+        if command == "/clear" {
+            lead.progress = None;
+            lead.context = None;
+            pod.todos = None;
+        }
+        pod.touch_input();
+        Ok(())
+    }
+
+    /// セッションにアタッチしたことを記録して保存する
+    pub fn mark_attached(&mut self, session: &str) -> Result<()> {
+        if let Some(pod) = self.state.pods.iter_mut().find(|p| p.tmux_session == session) {
+            pod.last_attached = Some(Utc::now());
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// リードメンバーに `/model <model>` を送ってモデルを切り替える。
+    /// transcript から読んだモデルで戻らないよう、次の応答までは送った名前を表示する
+    pub fn switch_model(&mut self, name: &str, model: &str) -> Result<()> {
+        let model = model.trim();
+        if model.is_empty() || model.contains(char::is_whitespace) {
+            anyhow::bail!("Invalid model name '{}'", model);
+        }
+        self.send_claude_command(name, &format!("/model {}", model))?;
+        if let Some(pod) = self.state.pods.iter_mut().find(|p| p.name == name) {
+            pod.model = Some(model.to_string());
+        }
+        self.transcript_models.entry(name.to_string()).or_default().switched = true;
+        self.save()
+    }
+
+    /// pod_name の role のメンバー (None ならリード) を探し、Pod とメンバーの位置を返す
+    fn find_member(&self, pod_name: &str, member: Option<&str>) -> Result<(&Pod, usize)> {
+        let pod = self.state.pods.iter()
+            .find(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+        let index = match member {
+            Some(role) => pod.members.iter().position(|m| m.role == role).ok_or_else(|| {
+                let roles: Vec<&str> = pod.members.iter().map(|m| m.role.as_str()).collect();
+                anyhow::anyhow!("Pod '{}' has no member '{}' (members: {})", pod_name, role, roles.join(", "))
+            })?,
+            None if pod.members.is_empty() => anyhow::bail!("Pod '{}' has no members", pod_name),
+            None => 0,
+        };
+        Ok((pod, index))
+    }
+
+    /// メンバーのペイン出力の録画を始める。output がなければ recordings/<pod>-<日時>.cast。
+    /// 録画は `apiary record-sink` が tmux pipe-pane の先で書き、stop_recording で止まる
+    pub fn start_recording(&self, pod_name: &str, member: Option<&str>, output: Option<&Path>) -> Result<(String, PathBuf)> {
+        let (pod, index) = self.find_member(pod_name, member)?;
+        let pane = pod.members[index].tmux_pane.clone();
+        let title = if pod.members.len() > 1 {
+            format!("{}/{}", pod.name, pod.members[index].role)
+        } else {
+            pod.name.clone()
+        };
+        if self.tmux.pane_pipe_owner(&pane).is_some_and(|owner| owner != "log") {
+            anyhow::bail!("'{}' is already being recorded or streamed (apiary record {} --stop)", title, pod_name);
+        }
+        let path = match output {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+                crate::record::recordings_dir()?.join(format!("{}-{}.cast", title.replace('/', "-"), stamp))
+            }
+        };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+
+        // 録画開始時点の画面から始める
+        let (width, height) = self.tmux.get_pane_size(&pane)?;
+        let initial = self.tmux.capture_pane_ansi(&pane)
+            .map(|capture| {
+                let cursor = self.tmux.get_cursor_position(&pane).ok().map(|(x, y)| (x, height.saturating_sub(y)));
+                let seed = String::from_utf8_lossy(&seed_bytes(&capture, height, cursor)).into_owned();
+                self.redactor.redact(&seed).into_owned()
+            })
+            .unwrap_or_default();
+        let header = crate::record::CastHeader::new(width, height, &format!("apiary: {}", title));
+        crate::record::create_cast(&path, &header, &initial)?;
+
+        let exe = std::env::current_exe().context("Failed to locate the apiary executable")?;
+        let path = std::fs::canonicalize(&path).unwrap_or(path);
+        let mut cmd = format!(
+            "{} record-sink {}",
+            crate::tmux::shell_quote(&exe.to_string_lossy()),
+            crate::tmux::shell_quote(&path.to_string_lossy())
+        );
+        // ログを取っているペインは録画中も log-sink を通す
+        if let Some(log) = self.log_command(pod, index) {
+            cmd = format!("{} --tee | {}", log, cmd);
+        }
+        self.tmux.pipe_pane_owned(&pane, &cmd, "record")?;
+        Ok((pane, path))
+    }
+
+    /// start_recording で始めた録画を止める (pipe-pane を閉じると record-sink が終わる)
+    pub fn stop_recording(&self, pod_name: &str, member: Option<&str>) -> Result<String> {
+        let (pod, index) = self.find_member(pod_name, member)?;
+        let pane = pod.members[index].tmux_pane.clone();
+        if self.tmux.pane_pipe_owner(&pane).is_none_or(|owner| owner == "log") {
+            anyhow::bail!("'{}' is not being recorded", pod_name);
+        }
+        match self.log_command(pod, index) {
+            Some(log) => self.tmux.pipe_pane_owned(&pane, &log, "log")?,
+            None => self.tmux.pipe_pane_stop(&pane)?,
+        }
+        Ok(pane)
+    }
+
+    /// Pod のペイン出力をログに書くか (None なら設定 `[logs] enabled` に従う)
+    pub fn logs_enabled(&self, pod: &Pod) -> bool {
+        pod.logs.unwrap_or(self.config.logs.enabled)
+    }
+
+    /// ログを取るメンバーの log-sink コマンド
+    fn log_command(&self, pod: &Pod, member: usize) -> Option<String> {
+        let member = pod.members.get(member).filter(|_| self.logs_enabled(pod))?;
+        crate::logs::sink_command(&pod.name, &member.role)
+            .map_err(|e| tracing::warn!("logs for {}: {}", pod.name, e))
+            .ok()
+    }
+
+    /// ログを取る Pod のうち、pipe-pane が止まっているペインで log-sink を起動する。
+    /// 止めたペイン (`apiary logs --off`) は pipe を閉じる
+    pub fn ensure_output_logs(&mut self) {
+        self.last_log_check = Some(std::time::Instant::now());
+        if !self.tmux.can_stream() {
+            return;
+        }
+        let streaming = self.detail_pty_stream.as_ref().map(|s| s.pane_id.clone());
+        for pod in self.state.pods.iter().filter(|p| p.status != PodStatus::Dead) {
+            for (i, member) in pod.members.iter().enumerate() {
+                if streaming.as_ref() == Some(&member.tmux_pane) {
+                    continue;
+                }
+                let owner = self.tmux.pane_pipe_owner(&member.tmux_pane);
+                match (self.log_command(pod, i), owner.as_deref()) {
+                    (Some(cmd), None) => {
+                        if let Err(e) = self.tmux.pipe_pane_owned(&member.tmux_pane, &cmd, "log") {
+                            tracing::warn!("Failed to start the log for {}/{}: {}", pod.name, member.role, e);
+                        }
+                    }
+                    (None, Some("log")) => {
+                        let _ = self.tmux.pipe_pane_stop(&member.tmux_pane);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Pod ごとにペイン出力のログを取るかを設定し、すぐに反映する
+    pub fn set_logs(&mut self, name: &str, enabled: Option<bool>) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.logs = enabled;
+        self.save()?;
+        self.ensure_output_logs();
+        Ok(())
+    }
+
+    /// メンバーのペインを respawn-pane でシェルから起動し直し、Claude を起動し直す
+    /// (tmux セッションや worktree はそのまま)。resume なら --continue で直前の会話を引き継ぐ。
+    /// Claude の終了を待たないので UI は止まらない。起動し直したペインの ID を返す
+    pub fn respawn_agent(&mut self, pod_name: &str, member: Option<&str>, resume: bool) -> Result<String> {
+        let (pod, index) = self.find_member(pod_name, member)?;
+        let pane = pod.members[index].tmux_pane.clone();
+        let project_path = match pod.project.as_deref() {
+            Some(p) => self.project_store.find_by_name(p)?.map(|p| p.path),
+            None => None,
+        };
+        let mut command = launch_command(pod, project_path.as_deref());
+        if resume {
+            command.push_str(" --continue");
+        }
+        // docker run のコンテナはクライアントを止めても残ることがあるので、同じ名前で起動し直す前に消す
+        if let Some(sandbox) = pod.sandbox.as_ref().filter(|s| s.image.is_some()) {
+            command = format!("docker rm -f {} >/dev/null 2>&1; {}", crate::tmux::shell_quote(&sandbox.container), command);
+        }
+
+        // ペインのプロセスツリーごと止めて新しいシェルにし、そこに起動コマンドを打つ
+        // (シェルはプロンプトを出す前に打たれた入力も読む)
+        self.tmux.respawn_pane(&pane, project_path.as_deref())?;
+        self.tmux.send_keys(&pane, &command)?;
+        let pod = self.state.pods.iter_mut().find(|p| p.name == pod_name).expect("pod checked above");
+        let m = &mut pod.members[index];
+        m.set_status(MemberStatus::Idle);
+        m.sub_agents.clear();
+        if !resume {
+            m.progress = None;
+            m.context = None;
+            if index == 0 {
+                pod.todos = None;
+            }
+        }
+        self.save()?;
+        Ok(pane)
+    }
+
+    /// Pod ごとの自動再開の設定 (None で `[rate_limit] auto_resume` に従う)
+    pub fn set_auto_resume(&mut self, name: &str, enabled: Option<bool>) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.auto_resume = enabled;
+        self.save()
+    }
+
+    /// Pod の優先度を設定する
+    pub fn set_priority(&mut self, name: &str, priority: PodPriority) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.priority = priority;
+        self.save()
+    }
+
+    /// Pod のアクセントカラーを設定する (None で名前から決める色に戻す)
+    pub fn set_accent(&mut self, name: &str, accent: Option<&str>) -> Result<()> {
+        if let Some(color) = accent {
+            if crate::pod::parse_accent(color).is_none() {
+                let names: Vec<&str> = crate::pod::ACCENT_COLORS.iter().map(|(n, _)| *n).collect();
+                anyhow::bail!("Unknown color '{}' (use #rrggbb or one of: {})", color, names.join(", "));
+            }
+        }
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.accent = accent.map(|c| c.trim().to_lowercase());
+        self.save()
+    }
+
+    /// 各 Pod のリードメンバーの作業ディレクトリから git ブランチを取得
+    pub fn refresh_branches(&mut self) {
+        self.last_branch_refresh = Some(std::time::Instant::now());
+        let pane_paths: std::collections::HashMap<String, String> = self.tmux.list_all_panes()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|p| p.current_path.map(|path| (p.id, path)))
+            .collect();
+
+        for pod in &mut self.state.pods {
+            if pod.status == PodStatus::Dead {
+                continue;
+            }
+            let path = pod.members.first().and_then(|m| pane_paths.get(&m.tmux_pane));
+            pod.branch = path.and_then(|p| crate::project::current_branch(p));
+        }
+    }
+
+    /// Home の左ペインにプロジェクトの概要を出すか (ワークスペースがあり、Pod にフォーカスしていない)
+    pub fn shows_project_health(&self) -> bool {
+        self.state.mode == Mode::Home
+            && self.state.current_project.is_some()
+            && (self.state.focused_pod().is_none() || self.state.pane_focus == PaneFocus::Left)
+    }
+
+    /// ワークスペースのプロジェクトのブランチ・変更・worktree・最近の出来事を集め直す。
+    /// git を何度も起動するので裏で集め、終わったときのワークスペースのものなら反映する
+    pub fn refresh_project_health(&mut self) {
+        self.last_health_refresh = Some(std::time::Instant::now());
+        if self.health_refresh_running {
+            return;
+        }
+        let Some(project) = self.state.current_project.clone() else {
+            self.project_health = None;
+            return;
+        };
+        let projects = self.project_store.list().unwrap_or_default();
+        let events = self.events.clone();
+        self.health_refresh_running = true;
+        self.spawn_job(move || {
+            let health = crate::tui::health::ProjectHealth::collect(&project, &projects, &events);
+            Box::new(move |app: &mut App| {
+                app.health_refresh_running = false;
+                if app.state.current_project.as_ref().is_some_and(|p| p.name == health.project) {
+                    app.project_health = Some(health);
+                }
+            })
+        });
+    }
+
+    /// Working 中のメンバーに「まだ作業中」の時刻を記録して保存する。
+    /// 次の起動時にはこの時刻までが作業時間として確定する
+    pub fn checkpoint_working_time(&mut self) {
+        self.last_working_checkpoint = Some(std::time::Instant::now());
+        let now = Utc::now();
+        let mut working = false;
+        for member in self.state.pods.iter_mut().flat_map(|p| p.members.iter_mut()) {
+            if member.status == MemberStatus::Working {
+                member.working_since.get_or_insert(member.last_change);
+                member.working_seen = Some(now);
+                working = true;
+            }
+        }
+        if working {
+            if let Err(e) = self.save() {
+                tracing::warn!("Failed to save working-time checkpoint: {}", e);
+            }
+        }
+    }
+
+    /// 各 Pod のリードメンバーのセッションを特定し、Claude Code の TODO リストを読み直す。
+    /// TODO があればカードの進捗は画面の読み取りよりこちらを優先する。
+    /// セッションは Stop の hooks で分かったものを使い、無ければディレクトリの最新の transcript にする。
+    /// ただし同じディレクトリで複数の Pod が動いていると最新の transcript がどれのものか分からないので、
+    /// そのときは hooks で分かるまでセッション (と TODO による Done) を持たない
+    pub fn refresh_todos(&mut self) {
+        self.last_todos_refresh = Some(std::time::Instant::now());
+        let pane_paths: std::collections::HashMap<String, String> = self.tmux.list_all_panes()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|p| p.current_path.map(|path| (p.id, path)))
+            .collect();
+        let mut pods_per_dir: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        for pod in self.state.pods.iter().filter(|p| p.status != PodStatus::Dead) {
+            if let Some(path) = pod.members.first().and_then(|m| pane_paths.get(&m.tmux_pane)) {
+                *pods_per_dir.entry(path.as_str()).or_default() += 1;
+            }
+        }
+
+        for pod in &mut self.state.pods {
+            if pod.status == PodStatus::Dead {
+                continue;
+            }
+            let path = pod.members.first().and_then(|m| pane_paths.get(&m.tmux_pane));
+            let hooked = pod.members.first().and_then(|m| self.hook_sessions.get(&m.tmux_pane));
+            let shared = path.is_some_and(|p| pods_per_dir.get(p.as_str()).copied().unwrap_or(0) > 1);
+            match hooked {
+                Some(session) => pod.session_id = Some(session.clone()),
+                None if shared => pod.session_id = None,
+                None => {
+                    if let Some(session) = path.and_then(|p| crate::todos::latest_session(p)) {
+                        pod.session_id = Some(session);
+                    }
+                }
+            }
+            let transcript = path.zip(pod.session_id.as_deref()).and_then(|(p, s)| crate::cost::transcript_path(p, s));
+            if let Some(transcript) = transcript {
+                let size = std::fs::metadata(&transcript).map(|m| m.len()).unwrap_or(0);
+                let seen = self.transcript_models.entry(pod.name.clone()).or_default();
+                if seen.size != size {
+                    seen.size = size;
+                    if let Some(model) = crate::cost::last_model(&transcript) {
+                        if !(seen.switched && seen.model.as_ref() == Some(&model)) {
+                            seen.switched = false;
+                            pod.model = Some(model.clone());
+                        }
+                        seen.model = Some(model);
+                    }
+                }
+            }
+            pod.todos = pod.session_id.as_deref().and_then(crate::todos::load);
+            if let (Some(todos), Some(lead)) = (pod.todos.as_ref(), pod.members.first_mut()) {
+                lead.progress = Some(todos.progress());
+            }
+        }
+    }
+
+    /// 各メンバーの CPU / メモリ使用量を更新し、新たにしきい値を超えたら通知
+    pub fn sample_resources(&mut self) {
+        self.last_resource_sample = Some(std::time::Instant::now());
+        let mut probe = AgentProbe::new(&self.config.detection.agent_processes);
+        let limits = &self.config.resources;
+        let mut alerts = Vec::new();
+
+        for pod in &mut self.state.pods {
+            if pod.status == PodStatus::Dead {
+                continue;
+            }
+            let muted = pod.is_muted(Utc::now());
+            for member in &mut pod.members {
+                let usage = probe.pane_usage(&member.tmux_pane);
+                let was_over = member.usage.is_some_and(|u| limits.exceeds(&u));
+                if let Some(u) = usage.filter(|u| limits.exceeds(u) && !was_over) {
+                    alerts.push((format!("{} ({}) is using {}", pod.name, member.role, u.format()), muted));
+                }
+                member.usage = usage;
+            }
+        }
+
+        for (alert, muted) in alerts {
+            if self.config.notification.enabled && !self.state.dnd && !muted {
+                crate::notify::notify("Apiary: Resource Warning", &alert);
+            }
+            self.state.status_message = Some(alert);
+        }
+    }
+
+    /// `[reaper]`: 放置された Pod を猶予リストに載せて通知し、猶予が過ぎても放置されていれば片付ける。
+    /// 片付けた Pod 名を返す
+    pub fn reap_stale_pods(&mut self) -> Vec<String> {
+        self.last_reap_check = Some(std::time::Instant::now());
+        let now = Utc::now();
+        let reaper = self.config.reaper.clone();
+        let stale = crate::gc::stale_pods(&self.state.pods, &reaper, now);
+        // 触られたり keep に入ったりして対象から外れた Pod は猶予リストからも外す
+        self.reap_pending.retain(|name, _| stale.contains(name));
+
+        let grace = chrono::Duration::minutes(reaper.grace_minutes as i64);
+        let mut warned = Vec::new();
+        let mut reaped = Vec::new();
+        for name in stale {
+            match self.reap_pending.get(&name) {
+                None if reaper.grace_minutes > 0 => {
+                    self.reap_pending.insert(name.clone(), now + grace);
+                    warned.push(name);
+                }
+                Some(deadline) if *deadline > now => {}
+                _ => {
+                    self.reap_pending.remove(&name);
+                    let result = self.drop_pod(&name).and_then(|()| match reaper.action {
+                        ReapAction::Archive => Ok(()),
+                        ReapAction::Drop => self.trash.take(&name).map(|_| ()),
+                    });
+                    match result {
+                        Ok(()) => reaped.push(name),
+                        Err(e) => self.state.status_message = Some(format!("Failed to clean up '{}': {}", name, e)),
+                    }
+                }
+            }
+        }
+
+        let notify = self.config.notification.enabled && !self.state.dnd;
+        if !warned.is_empty() {
+            let verb = match reaper.action {
+                ReapAction::Archive => "archived",
+                ReapAction::Drop => "dropped",
+            };
+            let message = format!(
+                "{} will be {} in {}m unless attached or pinned",
+                warned.join(", "),
+                verb,
+                reaper.grace_minutes
+            );
+            if notify {
+                crate::notify::notify("Apiary: Stale Pods", &message);
+            }
+            self.state.status_message = Some(message);
+        }
+        if !reaped.is_empty() {
+            let message = match reaper.action {
+                ReapAction::Archive => format!("Archived {} (apiary restore brings them back)", reaped.join(", ")),
+                ReapAction::Drop => format!("Dropped {}", reaped.join(", ")),
+            };
+            if notify {
+                crate::notify::notify("Apiary: Stale Pods", &message);
+            }
+            self.state.status_message = Some(message);
+        }
+        reaped
+    }
+
+    /// 今日の推定コストを予算と比べ、警告を更新する。しきい値を新たに超えたら通知。
+    /// transcript をすべて読むので集計は裏で行い、終わったら反映する
+    pub fn check_budget(&mut self) {
+        self.last_budget_check = Some(std::time::Instant::now());
+        if self.budget_check_running {
+            return;
+        }
+        let since = crate::report::parse_since("today", chrono::Local::now()).unwrap_or_else(|_| Utc::now());
+
+        // (キー, 表示名, プロジェクトのパス (None は全体), 予算)
+        let mut targets = Vec::new();
+        if let Some(limit) = self.config.budget.daily_usd {
+            targets.push((String::new(), "Today".to_string(), None, limit));
+        }
+        for (name, limit) in &self.config.budget.projects {
+            let Ok(Some(project)) = self.project_store.find_by_name(name) else {
+                continue;
+            };
+            targets.push((name.clone(), name.clone(), Some(project.path), *limit));
+        }
+
+        self.budget_check_running = true;
+        self.spawn_job(move || {
+            let checks: Vec<_> = targets
+                .into_iter()
+                .map(|(key, label, path, limit)| {
+                    let spent = match path {
+                        Some(path) => crate::cost::project_spend(&path, since),
+                        None => crate::cost::total_spend(since),
+                    };
+                    (key, label, spent, limit)
+                })
+                .collect();
+            Box::new(move |app: &mut App| {
+                app.budget_check_running = false;
+                app.apply_budget(checks);
+            })
+        });
+    }
+
+    /// 集計した (キー, 表示名, 推定コスト, 予算) を予算の警告に反映する
+    fn apply_budget(&mut self, checks: Vec<(String, String, f64, f64)>) {
+        let warn_percent = self.config.budget.warn_percent;
+        let mut warnings = Vec::new();
+        for (key, label, spent, limit) in checks {
+            let level = BudgetLevel::of(spent, limit, warn_percent);
+            let message = format!("{} ${:.2} / ${:.2}", label, spent, limit);
+            let previous = self.budget_levels.insert(key, level).unwrap_or(BudgetLevel::Ok);
+            if level > previous && self.config.notification.enabled && !self.state.dnd {
+                let title = if level == BudgetLevel::Exceeded {
+                    "Apiary: Budget Exceeded"
+                } else {
+                    "Apiary: Budget Warning"
+                };
+                crate::notify::notify(title, &message);
+            }
+            if level != BudgetLevel::Ok {
+                warnings.push(message);
+            }
+        }
+        self.state.budget_warning = (!warnings.is_empty()).then(|| warnings.join(", "));
+    }
+
+    /// 予算超過で auto_adopt を止めているか
+    fn auto_adopt_paused(&self) -> bool {
+        self.config.budget.pause_auto_adopt && self.budget_levels.values().any(|l| *l == BudgetLevel::Exceeded)
+    }
+
+    /// どの Pod にも属さない Claude Code セッションを検出
+    pub fn scan_unmanaged_sessions(&mut self) {
+        self.last_unmanaged_scan = Some(std::time::Instant::now());
+        let mut probe = AgentProbe::new(&self.config.detection.agent_processes);
+        self.state.unmanaged_sessions = discovery::discover_unmanaged_sessions(self.tmux.as_ref(), &self.state.pods, &mut probe);
+        if self.config.discovery.auto_adopt && !self.auto_adopt_paused() {
+            self.auto_adopt_unmanaged();
+        }
+    }
+
+    /// auto_adopt 有効時: 管理外セッションを検出したプロジェクトのグループで取り込む
+    fn auto_adopt_unmanaged(&mut self) {
+        let candidates: Vec<discovery::UnmanagedSession> = self
+            .state
+            .unmanaged_sessions
+            .iter()
+            .filter(|s| !self.auto_adopt_ignored.contains(&s.session))
+            .cloned()
+            .collect();
+
+        for unmanaged in candidates {
+            let project = unmanaged
+                .path
+                .as_deref()
+                .and_then(|p| crate::project::resolve_project(&self.project_store, p).ok());
+            let group = project.as_ref().map(|p| p.name.clone());
+            match self.adopt_session(&unmanaged.session, None, group.as_deref()) {
+                Ok(()) => {
+                    if let Some(pod) = self.state.pods.iter_mut().find(|p| p.tmux_session == unmanaged.session) {
+                        pod.project = group;
+                    }
+                    self.save().ok();
+                    self.state.unmanaged_sessions.retain(|s| s.session != unmanaged.session);
+                    self.state.status_message = Some(format!("Auto-adopted session '{}'", unmanaged.session));
+                }
+                Err(e) => {
+                    // 名前衝突などで取り込めないものは以後スキップ (Unmanaged に残す)
+                    tracing::warn!("Failed to auto-adopt '{}': {}", unmanaged.session, e);
+                    self.ignore_for_auto_adopt(unmanaged.session);
+                }
+            }
+        }
+    }
+
+    /// セッションを自動取り込みの対象から外し、再起動後も外したままにする。
+    /// CLI から呼ばれても他の UI 状態を上書きしないよう、この項目だけを書き換える
+    fn ignore_for_auto_adopt(&mut self, session: String) {
+        if !self.auto_adopt_ignored.insert(session) {
+            return;
+        }
+        let mut saved = self.ui_state_store.load();
+        saved.auto_adopt_ignored = self.sorted_auto_adopt_ignored();
+        if let Err(e) = self.ui_state_store.save(&saved) {
+            tracing::warn!("Failed to save the auto-adopt ignore list: {}", e);
+        }
+    }
+
+    fn sorted_auto_adopt_ignored(&self) -> Vec<String> {
+        let mut sessions: Vec<String> = self.auto_adopt_ignored.iter().cloned().collect();
+        sessions.sort();
+        sessions
+    }
+
+    /// `a` のセッションピッカーを開く。取り込めるセッションが無ければ false
+    pub fn open_adopt_picker(&mut self) -> Result<bool> {
+        let mut probe = AgentProbe::new(&self.config.detection.agent_processes);
+        let sessions = discovery::list_adoptable_sessions(self.tmux.as_ref(), &self.state.pods, &mut probe)?;
+        if sessions.is_empty() {
+            return Ok(false);
+        }
+        self.state.adopt_picker = Some(crate::tui::adopt::AdoptPicker::new(sessions));
+        self.state.completion = None;
+        self.refresh_adopt_preview();
+        Ok(true)
+    }
+
+    /// ピッカーで選択中のセッションのアクティブペインを取り込み直す
+    pub fn refresh_adopt_preview(&mut self) {
+        let Some(picker) = self.state.adopt_picker.as_mut() else {
+            return;
+        };
+        picker.preview = picker.selected_session().and_then(|s| s.active_pane.clone()).map(|pane| {
+            let output = self.tmux.capture_pane_lines(&pane, ADOPT_PREVIEW_LINES).unwrap_or_default();
+            (pane, output)
+        });
+    }
+
+    /// ピッカーで選んだセッションを Pod として取り込んでフォーカスし、セッション名を返す
+    pub fn adopt_picked(&mut self) -> Result<Option<String>> {
+        let Some(session) = self.state.adopt_picker.as_ref().and_then(|p| p.selected_session()).map(|s| s.session.clone())
+        else {
+            return Ok(None);
+        };
+        self.state.adopt_picker = None;
+        self.adopt_session(&session, None, None)?;
+        self.state.unmanaged_sessions.retain(|s| s.session != session);
+        self.state.focus = self.state.pods.iter().position(|p| p.tmux_session == session);
+        Ok(Some(session))
+    }
+
+    /// Unmanaged 一覧の先頭セッションを Pod として取り込み、セッション名を返す
+    pub fn adopt_first_unmanaged(&mut self) -> Result<Option<String>> {
+        if self.state.unmanaged_sessions.is_empty() {
+            return Ok(None);
+        }
+        let session = self.state.unmanaged_sessions.remove(0).session;
+        self.adopt_session(&session, None, None)?;
+        self.state.focus = self.state.pods.iter().position(|p| p.tmux_session == session);
+        Ok(Some(session))
+    }
+
+    /// ゴミ箱から Pod を復元し、プロジェクトディレクトリでセッションを作り直す
+    pub fn restore_pod(&mut self, name: &str) -> Result<()> {
+        if self.state.pods.iter().any(|p| p.name == name) {
+            anyhow::bail!("Pod '{}' already exists", name);
+        }
+        if self.tmux.session_exists(name) {
+            anyhow::bail!("tmux session '{}' already exists", name);
+        }
+        let entry = self
+            .trash
+            .take(name)?
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found in trash", name))?;
+
+        let project_path = match entry.pod.project.as_deref() {
+            Some(p) => self.project_store.find_by_name(p)?.map(|p| p.path),
+            None => None,
+        };
+        if let Err(e) = self.tmux.new_session(name, project_path.as_deref(), &entry.pod.env) {
+            // セッションを作れなければゴミ箱に戻す
+            self.trash.put(&entry)?;
+            return Err(e);
+        }
+        // drop で消した設定ファイルを書き戻す
+        if let Err(e) = restore_settings(&entry) {
+            let _ = self.tmux.kill_session(name);
+            self.trash.put(&entry)?;
+            return Err(e);
+        }
+
+        let container = start_devcontainer(&entry.pod, project_path.as_deref());
+        let (pod, command) = match self.rebuild_in_session(entry.pod.clone(), name, project_path.as_deref(), container) {
+            Ok(rebuilt) => rebuilt,
+            Err(e) => {
+                let _ = self.tmux.kill_session(name);
+                self.trash.put(&entry)?;
+                return Err(e);
+            }
+        };
+        self.state.pods.push(pod);
+        if let Err(e) = self.save() {
+            self.state.pods.pop();
+            let _ = self.tmux.kill_session(name);
+            self.trash.put(&entry)?;
+            return Err(e);
+        }
+
+        // drop 時の画面をペインに流してから Claude を起動する (ペインの履歴から遡れる)
+        if let Some(scrollback) = entry.scrollback.first().filter(|s| !s.trim().is_empty()) {
+            if let Err(e) = self.replay_scrollback(name, scrollback, entry.dropped_at) {
+                tracing::warn!("Failed to replay the scrollback of '{}': {}", name, e);
+            }
+        }
+        self.tmux.start_agent_in_session(name, &command, None)?;
+        Ok(())
+    }
+
+    /// ゴミ箱に退避した scrollback を cat でペインに出す。ファイルは自分専用のディレクトリに
+    /// 推測できない名前・0600 で置き、出し終えたらシェルが消す
+    fn replay_scrollback(&self, session: &str, scrollback: &str, dropped_at: DateTime<Utc>) -> Result<()> {
+        let dir = pty_stream_dir();
+        ensure_private_dir(&dir)?;
+        let mut file = tempfile::Builder::new()
+            .prefix("apiary-scrollback-")
+            .suffix(".txt")
+            .tempfile_in(&dir)
+            .with_context(|| format!("Failed to create a temporary file in {:?}", dir))?;
+        let footer = format!(
+            "\n--- restored from the trash (dropped {}) ---\n",
+            dropped_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        );
+        std::io::Write::write_all(&mut file, format!("{}{}", scrollback.trim_end(), footer).as_bytes())
+            .with_context(|| format!("Failed to write {:?}", file.path()))?;
+        let path = file.into_temp_path().keep().context("Failed to keep the scrollback file")?;
+        let path = crate::tmux::shell_quote(&path.to_string_lossy());
+        self.tmux.send_keys(session, &format!("cat {}; rm -f {}", path, path))
+    }
+
+    /// tmux セッションを失った Dead Pod を、記録しているプロジェクトディレクトリで作り直して
+    /// Claude を起動し直す。resume なら前の会話を続ける (claude --continue)
+    pub fn resurrect_pod(&mut self, name: &str, resume: bool) -> Result<()> {
+        let (pod, project_path) = self.resurrect_target(name)?;
+        let container = start_devcontainer(&pod, project_path.as_deref());
+        self.finish_resurrect(name, resume, container)
+    }
+
+    /// tmux サーバーごと失った Pod をすべて resurrect する。(Pod 名, 結果) を返す
+    pub fn resurrect_lost_pods(&mut self, resume: bool) -> Vec<(String, Result<()>)> {
+        self.lost_pod_names()
+            .into_iter()
+            .map(|name| {
+                let result = self.resurrect_pod(&name, resume);
+                (name, result)
+            })
+            .collect()
+    }
+
+    /// tmux サーバーごと失った Dead Pod の名前
+    pub fn lost_pod_names(&self) -> Vec<String> {
+        self.state.pods.iter()
+            .filter(|p| p.status == PodStatus::Dead && p.server_lost)
+            .map(|p| p.name.clone())
+            .collect()
+    }
+
+    /// TUI 用の resurrect。devcontainer の起動 (数分かかりうる) を裏で済ませてから
+    /// セッションを作り直し、結果をステータスバーに出す
+    pub fn resurrect_in_background(&mut self, names: Vec<String>, resume: bool) -> Result<()> {
+        let mut targets = Vec::new();
+        for name in names {
+            let (pod, project_path) = self.resurrect_target(&name)?;
+            targets.push((name, pod, project_path));
+        }
+        self.state.status_message = Some(format!(
+            "Resurrecting {}...",
+            targets.iter().map(|(n, _, _)| n.as_str()).collect::<Vec<_>>().join(", ")
+        ));
+        self.spawn_job(move || {
+            let containers: Vec<(String, Option<String>)> = targets.into_iter()
+                .map(|(name, pod, project_path)| {
+                    let container = start_devcontainer(&pod, project_path.as_deref());
+                    (name, container)
+                })
+                .collect();
+            Box::new(move |app: &mut App| {
+                let results: Vec<(String, Result<()>)> = containers.into_iter()
+                    .map(|(name, container)| {
+                        let result = app.finish_resurrect(&name, resume, container);
+                        (name, result)
+                    })
+                    .collect();
+                let (ok, failed): (Vec<_>, Vec<_>) = results.into_iter().partition(|(_, r)| r.is_ok());
+                app.state.status_message = Some(match failed.first() {
+                    Some((name, Err(e))) => format!("Resurrect error: {}: {}", name, e),
+                    _ => format!("Resurrected {}", ok.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>().join(", ")),
+                });
+            })
+        });
+        Ok(())
+    }
+
+    /// resurrect できる Pod か確かめ、Pod とプロジェクトのパスを返す
+    fn resurrect_target(&self, name: &str) -> Result<(Pod, Option<String>)> {
+        let pod = self.state.pods.iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        if pod.status != PodStatus::Dead {
+            anyhow::bail!("Pod '{}' is not dead", name);
+        }
+        if self.tmux.session_exists(&pod.tmux_session) {
+            anyhow::bail!("tmux session '{}' already exists", pod.tmux_session);
+        }
+        let project_path = match pod.project.as_deref() {
+            Some(p) => self.project_store.find_by_name(p)?.map(|p| p.path),
+            None => None,
+        };
+        Ok((pod.clone(), project_path))
+    }
+
+    /// devcontainer を起動し終えた Pod のセッションを作り直して Claude を起動する。
+    /// 裏で待っている間に状態が変わりうるので確認し直し、途中で失敗したら作ったセッションを消す
+    fn finish_resurrect(&mut self, name: &str, resume: bool, container: Option<String>) -> Result<()> {
+        let (pod, project_path) = self.resurrect_target(name)?;
+        let session = pod.tmux_session.clone();
+        self.tmux.new_session(&session, project_path.as_deref(), &pod.env)?;
+
+        let (rebuilt, mut command) = match self.rebuild_in_session(pod, &session, project_path.as_deref(), container) {
+            Ok(rebuilt) => rebuilt,
+            Err(e) => {
+                let _ = self.tmux.kill_session(&session);
+                return Err(e);
+            }
+        };
+        let Some(idx) = self.state.pods.iter().position(|p| p.name == name) else {
+            let _ = self.tmux.kill_session(&session);
+            anyhow::bail!("Pod '{}' not found", name);
+        };
+        let dead = std::mem::replace(&mut self.state.pods[idx], rebuilt);
+        if let Err(e) = self.save() {
+            self.state.pods[idx] = dead;
+            let _ = self.tmux.kill_session(&session);
+            return Err(e);
+        }
+        if resume {
+            command.push_str(" --continue");
+        }
+        self.tmux.start_agent_in_session(&session, &command, None)?;
+        Ok(())
+    }
+
+    /// 作り直したセッションの最初のペインをリードにして Pod を Solo に組み直す (メタデータは引き継ぐ)。
+    /// container は起動し直した devcontainer の ID。Claude の起動コマンドと一緒に返す
+    fn rebuild_in_session(&self, pod: Pod, session: &str, project_path: Option<&str>, container: Option<String>) -> Result<(Pod, String)> {
+        self.mark_pod_session(&pod.name, session);
+        let pane_id = self.tmux.list_panes(session)?
+            .first()
+            .map(|p| p.id.clone())
+            .unwrap_or_else(|| "%0".to_string());
+        let member = Member::new("claude", &pane_id);
+
+        // メンバー構成は復元せず Solo として作り直す
+        let mut pod = Pod {
+            pod_type: PodType::Solo,
+            members: vec![member],
+            status: PodStatus::Idle,
+            tmux_session: session.to_string(),
+            server_lost: false,
+            ..pod
+        };
+        if let (Some(sandbox), Some(container)) = (pod.sandbox.as_mut(), container) {
+            sandbox.container = container;
+        }
+        let command = launch_command(&pod, project_path);
+        Ok((pod, command))
+    }
+
+    /// `apiary gc`: 孤立 PTY ファイル・肥大化した hooks ログ・期限切れ Dead Pod・不要 worktree を掃除
+    pub fn gc(&mut self, dry_run: bool) -> Result<crate::gc::GcReport> {
+        let mut report = crate::gc::GcReport::default();
+
+        let live_panes: std::collections::HashSet<String> =
+            self.tmux.list_all_panes().unwrap_or_default().into_iter().map(|p| p.id).collect();
+        report.pty_files = crate::gc::orphan_pty_files(&pty_stream_dir(), &live_panes);
+        if !dry_run {
+            for path in &report.pty_files {
+                if let Err(e) = std::fs::remove_file(path) {
+                    report.skipped.push(format!("{}: {}", path.display(), e));
+                }
+            }
+        }
+
+        report.hooks_rotated = crate::gc::rotate_hooks_file(
+            Path::new(crate::hooks::HOOKS_FILE),
+            crate::gc::HOOKS_ROTATE_BYTES,
+            dry_run,
+        )?;
+
+        let ttl = chrono::Duration::hours(self.config.gc.dead_pod_ttl_hours as i64);
+        report.dead_pods = crate::gc::expired_dead_pods(&self.state.pods, ttl);
+        if !dry_run {
+            for name in &report.dead_pods {
+                self.drop_pod(name)?;
+            }
+        }
+
+        let trashed = self.trash.list()?;
+        crate::gc::remove_stale_worktrees(&trashed, &self.state.pods, &self.project_store, dry_run, &mut report);
+
+        Ok(report)
+    }
+
+    /// Pod を削除 (tmux セッションは残す)
+    pub fn forget_pod(&mut self, name: &str) -> Result<()> {
+        let idx = self
+            .state
+            .pods
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+
+        let pod = self.state.pods.remove(idx);
+        self.ignore_for_auto_adopt(pod.tmux_session.clone());
+        // セッションは残るが Pod ではなくなるので、prefix+d は普通の detach に戻す
+        if !self.state.pods.iter().any(|p| p.tmux_session == pod.tmux_session) {
+            let _ = self.tmux.set_session_option(&pod.tmux_session, crate::tmux::integration::POD_OPTION, None);
+        }
+        self.save()?;
+
+        // focus の調整
+        if let Some(focus) = self.state.focus {
+            if focus >= self.state.pods.len() {
+                self.state.focus = if self.state.pods.is_empty() {
+                    None
+                } else {
+                    Some(self.state.pods.len() - 1)
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 状態を保存
+    pub fn save(&self) -> Result<()> {
+        self.store.save(&self.state.pods)
+    }
+
+    /// 現在の UI 状態 (折りたたみ・フォーカス・フィルタ等) のスナップショット
+    pub fn ui_state(&self) -> UiState {
+        let mut collapsed_groups: Vec<String> = self.state.collapsed_groups.iter().cloned().collect();
+        collapsed_groups.sort();
+        UiState {
+            collapsed_groups,
+            focused_pod: self.state.focused_pod().map(|p| p.name.clone()),
+            tag_filter: self.state.tag_filter.clone(),
+            view_mode: Some(self.state.view_mode),
+            workspace: self.state.current_project.as_ref().map(|p| p.name.clone()),
+            workspace_filter: self.state.workspace_filter,
+            recent_workspaces: self.state.recent_workspaces.clone(),
+            mode: Some(self.state.mode.clone()),
+            left_pane_hidden: self.state.left_pane_hidden,
+            layout: Some(self.state.layout),
+            dnd: self.state.dnd,
+            auto_adopt_ignored: self.sorted_auto_adopt_ignored(),
+        }
+    }
+
+    /// UI 状態を保存
+    pub fn save_ui_state(&self) -> Result<()> {
+        self.ui_state_store.save(&self.ui_state())
+    }
+
+    /// 保存済みの UI 状態を反映 (消えた Pod / プロジェクトは無視)。
+    /// モードは TUI 起動時だけ restore_mode で戻す
+    pub fn restore_ui_state(&mut self, saved: UiState) {
+        self.state.collapsed_groups = saved.collapsed_groups.into_iter().collect();
+        if let Some(view_mode) = saved.view_mode {
+            self.state.view_mode = view_mode;
+        }
+        self.state.tag_filter = saved.tag_filter;
+        self.state.left_pane_hidden = saved.left_pane_hidden;
+        self.state.dnd = saved.dnd;
+        // 消えたセッションは忘れる (同じ名前で新しく作られたものは取り込む)
+        self.auto_adopt_ignored = saved
+            .auto_adopt_ignored
+            .into_iter()
+            .filter(|session| self.tmux.session_exists(session))
+            .collect();
+        if let Some(layout) = saved.layout {
+            self.state.layout = layout;
+        }
+        // 登録が消えたプロジェクトは忘れる
+        self.state.recent_workspaces = saved
+            .recent_workspaces
+            .into_iter()
+            .filter(|name| self.project_store.find_by_name(name).ok().flatten().is_some())
+            .collect();
+        if let Some(project) = saved
+            .workspace
+            .and_then(|name| self.project_store.find_by_name(&name).ok().flatten())
+        {
+            self.state.current_project = Some(project);
+            self.state.workspace_filter = saved.workspace_filter;
+        }
+        if let Some(idx) = saved
+            .focused_pod
+            .and_then(|name| self.state.pods.iter().position(|p| p.name == name))
+        {
+            self.state.focus = Some(idx);
+        }
+    }
+
+    /// 前回のモードを復元 (Detail / Chat のみ、フォーカス Pod が生きている場合)
+    pub fn restore_mode(&mut self, mode: Option<Mode>) {
+        let alive = self.state.focused_pod().is_some_and(|p| p.status != PodStatus::Dead);
+        match mode {
+            Some(Mode::Detail) if alive => {
+                self.state.mode = Mode::Detail;
+                self.state.selected_member = Some(0);
+                self.start_detail_pty_stream();
+            }
+            Some(Mode::Chat) if alive => {
+                self.state.mode = Mode::Chat;
+                self.state.selected_member = Some(0);
+            }
+            _ => {}
+        }
+    }
+
+    /// 左ペイン入力を $VISUAL / $EDITOR (なければ vi) で編集し、保存内容で置き換える。
+    /// TUI の一時停止・復帰は呼び出し側 (main) で行う
+    pub fn edit_input_in_editor(&mut self) -> Result<()> {
+        // 入力に秘密情報が入りうるので、自分専用のディレクトリに推測できない名前・0600 で作る
+        let dir = pty_stream_dir();
+        ensure_private_dir(&dir)?;
+        let file = tempfile::Builder::new()
+            .prefix("apiary-instruction-")
+            .suffix(".md")
+            .tempfile_in(&dir)
+            .with_context(|| format!("Failed to create a temporary file in {:?}", dir))?;
+        let path = file.path().to_path_buf();
+        std::fs::write(&path, self.state.inline_input.as_str())
+            .with_context(|| format!("Failed to write {:?}", path))?;
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        // "code --wait" のような引数付き指定も通るよう sh 経由で起動
+        let status = std::process::Command::new("sh")
+            .args(["-c", &format!("{} \"$1\"", editor), "sh"])
+            .arg(&path)
+            .status();
+        let content = std::fs::read_to_string(&path);
+        drop(file);
+
+        let status = status.with_context(|| format!("Failed to run editor '{}'", editor))?;
+        if !status.success() {
+            anyhow::bail!("Editor '{}' exited with {}", editor, status);
+        }
+        let content = content.with_context(|| format!("Failed to read {:?}", path))?;
+        self.state.inline_input.set(content.trim_end());
+        self.state.completion = None;
+        Ok(())
+    }
+
+    /// 左ペインで確定した入力を履歴に追加して保存
+    pub fn record_input(&mut self, input: &str) {
+        self.input_history.push(input);
+        if let Err(e) = self.history_store.save(&self.input_history) {
+            tracing::warn!("Failed to save input history: {}", e);
+        }
+    }
+
+    /// フォーカス中 Pod のグループの折りたたみを切り替え
+    pub fn toggle_group_collapse(&mut self) -> Result<()> {
+        let group = match self.state.focused_pod().and_then(|p| self.state.display_group(p)).map(str::to_string) {
+            Some(g) => g,
+            None => return Ok(()),
+        };
+        if !self.state.collapsed_groups.remove(&group) {
+            self.state.collapsed_groups.insert(group);
+        }
+        self.save_ui_state()
+    }
+
+    /// フォーカス中 Pod のピン留めを切り替え
+    pub fn toggle_pin(&mut self) -> Result<()> {
+        let pod = match self.state.focus.and_then(|i| self.state.pods.get_mut(i)) {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        pod.pinned = !pod.pinned;
+        let verb = if pod.pinned { "Pinned" } else { "Unpinned" };
+        self.state.status_message = Some(format!("{} '{}'", verb, pod.name));
+        self.save()
+    }
+
+    /// Pod のタグを設定
+    pub fn set_tags(&mut self, name: &str, tags: Vec<String>) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.tags = tags;
+        self.save()
+    }
+
+    /// 次に作業が終わったときのアクションを設定 (None で解除)
+    pub fn set_on_done(&mut self, name: &str, action: Option<OnDone>) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.on_done = action;
+        self.save()
+    }
+
+    /// 作成元の GitHub issue を記録 (PR 作成時に参照する)
+    pub fn set_issue(&mut self, name: &str, issue: Option<String>) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.issue = issue;
+        self.save()
+    }
+
+    /// Pod のブランチを push して PR を作成し、URL を Pod に記録する
+    pub fn create_pr(&mut self, name: &str) -> Result<String> {
+        let (dir, issue) = self.pr_target(name)?;
+        let url = crate::github::create_pr(&dir, issue.as_deref())?;
+        self.record_pr(name, &url)?;
+        Ok(url)
+    }
+
+    /// create_pr を裏で行う (push と gh は数秒かかるので TUI を止めない)。結果は status_message に出す
+    pub fn create_pr_in_background(&mut self, name: &str) -> Result<()> {
+        let (dir, issue) = self.pr_target(name)?;
+        let name = name.to_string();
+        self.spawn_job(move || {
+            let result = crate::github::create_pr(&dir, issue.as_deref());
+            Box::new(move |app: &mut App| {
+                let result = result.and_then(|url| app.record_pr(&name, &url).map(|_| url));
+                app.state.status_message = Some(match result {
+                    Ok(url) => format!("PR: {}", url),
+                    Err(e) => format!("PR failed: {}", e),
+                });
+            })
+        });
+        Ok(())
+    }
+
+    fn record_pr(&mut self, name: &str, url: &str) -> Result<()> {
+        if let Some(pod) = self.state.pods.iter_mut().find(|p| p.name == name) {
+            pod.pr_url = Some(url.to_string());
+        }
+        self.save()
+    }
+
+    /// PR を作る作業ディレクトリ (リードペインの現在地、取れなければプロジェクトのパス) と Pod の issue
+    fn pr_target(&self, name: &str) -> Result<(String, Option<String>)> {
+        let pod = self.state.pods.iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        let lead_pane = pod.members.first().map(|m| m.tmux_pane.clone());
+        let pane_path = self.tmux.list_all_panes()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|p| Some(&p.id) == lead_pane.as_ref())
+            .and_then(|p| p.current_path);
+        let project_path = match pod.project {
+            Some(ref project) => self.project_store.find_by_name(project)?.map(|p| p.path),
+            None => None,
+        };
+        let dir = pane_path
+            .or(project_path)
+            .ok_or_else(|| anyhow::anyhow!("Cannot tell where Pod '{}' works", name))?;
+        Ok((dir, pod.issue.clone()))
+    }
+
+    /// 作業完了時のアクションを一度だけ実行する (実行後は解除して保存)
+    fn run_on_done(&mut self, name: &str) -> Result<()> {
+        let Some(pod) = self.state.pods.iter_mut().find(|p| p.name == name) else {
+            return Ok(());
+        };
+        let Some(action) = pod.on_done.take() else {
+            return Ok(());
+        };
+        let lead = pod.members.first().map(|m| m.role.clone()).unwrap_or_default();
+        let claude_waiting = pod.status == PodStatus::Idle;
+        self.save()?;
+        // Commit / Prompt は Claude のプロンプトに打ち込むので、終了済み (素のシェル) には送らない
+        if !claude_waiting && !matches!(action, OnDone::Run(_)) {
+            anyhow::bail!("Claude is no longer running, skipped {}", action.describe());
+        }
+        match action {
+            OnDone::Run(ref cmd) => {
+                self.exec_in_split(name, cmd, false, None)?;
+            }
+            OnDone::Commit => {
+                self.exec_in_member(name, &lead, OnDone::COMMIT_PROMPT)?;
+            }
+            OnDone::Prompt(ref text) => {
+                self.exec_in_member(name, &lead, text)?;
+            }
+        }
+        self.state.status_message = Some(format!("{}: on-done {}", name, action.describe()));
+        Ok(())
+    }
+
+    /// Pod のメモを設定
+    pub fn set_note(&mut self, name: &str, note: &str) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.note = note.to_string();
+        self.save()
+    }
+
+    /// Pod のグループを設定 (None で解除)
+    pub fn set_group(&mut self, name: &str, group: Option<String>) -> Result<()> {
+        let pod = self
+            .state
+            .pods
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+        pod.group = group;
+        self.save()
+    }
+
+    /// Pod をまとめてグループに入れる。知らない名前が 1 つでもあれば何も変えない。対象 Pod 数を返す
+    pub fn add_to_group(&mut self, group: &str, names: &[String]) -> Result<usize> {
+        let missing: Vec<&str> = names
+            .iter()
+            .filter(|name| !self.state.pods.iter().any(|p| &p.name == *name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("Pod not found: {}", missing.join(", "));
+        }
+        let mut count = 0;
+        for pod in self.state.pods.iter_mut().filter(|p| names.contains(&p.name)) {
+            pod.group = Some(group.to_string());
+            count += 1;
+        }
+        self.save()?;
+        Ok(count)
+    }
+
+    /// グループに属する Pod 名 (pods の並び順)
+    pub fn pods_in_group(&self, group: &str) -> Vec<String> {
+        self.state
+            .pods
+            .iter()
+            .filter(|p| self.state.display_group(p) == Some(group))
+            .map(|p| p.name.clone())
+            .collect()
+    }
 
-        let pod_type = if members.len() > 1 {
-            PodType::Team
-        } else {
-            PodType::Solo
-        };
+    fn existing_group_pods(&self, group: &str) -> Result<Vec<String>> {
+        let names = self.pods_in_group(group);
+        if names.is_empty() {
+            anyhow::bail!("Group '{}' not found", group);
+        }
+        Ok(names)
+    }
 
-        let pod = Pod {
-            name: pod_name.to_string(),
-            pod_type,
-            members,
-            status: PodStatus::Idle,
-            tmux_session: session.to_string(),
-            project: None,
-            group: group.map(|s| s.to_string()),
-            created_at: Utc::now(),
-            total_working_secs: 0,
-        };
+    /// グループ名を変更 (既存グループへの変更は統合になるので拒否)。変更した Pod 数を返す
+    pub fn rename_group(&mut self, old: &str, new: &str) -> Result<usize> {
+        let names = self.existing_group_pods(old)?;
+        if !self.pods_in_group(new).is_empty() {
+            anyhow::bail!("Group '{}' already exists", new);
+        }
+        for pod in self.state.pods.iter_mut().filter(|p| names.contains(&p.name)) {
+            pod.group = Some(new.to_string());
+        }
+        if self.state.collapsed_groups.remove(old) {
+            self.state.collapsed_groups.insert(new.to_string());
+            let _ = self.save_ui_state();
+        }
+        self.save()?;
+        Ok(names.len())
+    }
 
-        self.state.pods.push(pod);
+    /// グループを解散 (Pod は残し、グループだけ外す)。対象 Pod 数を返す
+    pub fn dissolve_group(&mut self, group: &str) -> Result<usize> {
+        let names = self.existing_group_pods(group)?;
+        for pod in self.state.pods.iter_mut().filter(|p| names.contains(&p.name)) {
+            pod.group = None;
+        }
+        if self.state.collapsed_groups.remove(group) {
+            let _ = self.save_ui_state();
+        }
         self.save()?;
+        Ok(names.len())
+    }
 
-        Ok(())
+    /// グループの全 Pod をセッションごと drop (ゴミ箱へ)。drop した Pod 名を返す
+    pub fn drop_group(&mut self, group: &str) -> Result<Vec<String>> {
+        let names = self.existing_group_pods(group)?;
+        for name in &names {
+            self.drop_pod(name)?;
+        }
+        if self.state.collapsed_groups.remove(group) {
+            let _ = self.save_ui_state();
+        }
+        Ok(names)
     }
 
-    /// Pod を削除 (同一 session を共有する Pod がなければ session ごと kill、あれば pane 単位で kill)
-    pub fn drop_pod(&mut self, name: &str) -> Result<()> {
-        let idx = self
-            .state
+    /// フォーカス中 Pod の選択 (一括操作用) を切り替え
+    pub fn toggle_selection(&mut self) {
+        let Some(name) = self.state.focused_pod().map(|p| p.name.clone()) else {
+            return;
+        };
+        if !self.state.selected_pods.remove(&name) {
+            self.state.selected_pods.insert(name);
+        }
+    }
+
+    /// 選択中の Pod 名 (pods の並び順、既に消えた Pod は除く)
+    pub fn selected_pod_names(&self) -> Vec<String> {
+        self.state
             .pods
             .iter()
-            .position(|p| p.name == name)
-            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
-
-        let pod = &self.state.pods[idx];
-        let session = pod.tmux_session.clone();
-        let pane_ids: Vec<String> = pod.members.iter().map(|m| m.tmux_pane.clone()).collect();
-
-        // 同一 session を使う他の Pod があるか
-        let shared = self.state.pods.iter()
-            .any(|p| p.name != name && p.tmux_session == session);
+            .filter(|p| self.state.selected_pods.contains(&p.name))
+            .map(|p| p.name.clone())
+            .collect()
+    }
 
-        if shared {
-            // pane 単位で kill（session は残す）
-            for pane_id in &pane_ids {
-                let _ = Tmux::kill_pane(pane_id);
+    /// 選択中の全 Pod に f を適用して選択を解除し、結果を 1 行にまとめて返す
+    fn apply_to_selected(&mut self, verb: &str, mut f: impl FnMut(&mut Self, &str) -> Result<()>) -> String {
+        let names = self.selected_pod_names();
+        self.state.selected_pods.clear();
+        let mut failed = Vec::new();
+        for name in &names {
+            if let Err(e) = f(self, name) {
+                failed.push(format!("{}: {}", name, e));
             }
+        }
+        let done = names.len() - failed.len();
+        if failed.is_empty() {
+            format!("{} pods {}", done, verb)
         } else {
-            // 最後の Pod → session ごと kill
-            if Tmux::session_exists(&session) {
-                Tmux::kill_session(&session)?;
-            }
+            format!("{} pods {}, {} failed ({})", done, verb, failed.len(), failed.join("; "))
         }
+    }
 
-        self.state.pods.remove(idx);
-        self.save()?;
+    /// 選択中の Pod をまとめて drop (ゴミ箱へ)
+    pub fn drop_selected(&mut self) -> String {
+        self.apply_to_selected("dropped", |app, name| app.drop_pod(name))
+    }
 
-        // focus の調整
-        if let Some(focus) = self.state.focus {
-            if focus >= self.state.pods.len() {
-                self.state.focus = if self.state.pods.is_empty() {
-                    None
-                } else {
-                    Some(self.state.pods.len() - 1)
-                };
+    /// 選択中の Pod をまとめてアーカイブ (reaper の archive と同じくゴミ箱へ入れる。戻せるので確認しない)
+    pub fn archive_selected(&mut self) -> String {
+        format!("{} (apiary restore brings them back)", self.apply_to_selected("archived", |app, name| app.drop_pod(name)))
+    }
+
+    /// 選択中の Pod をまとめて管理対象から外す
+    pub fn forget_selected(&mut self) -> String {
+        self.apply_to_selected("forgotten", |app, name| app.forget_pod(name))
+    }
+
+    /// 選択中の Pod のグループをまとめて変更 (None で解除)
+    pub fn move_selected_to_group(&mut self, group: Option<String>) -> String {
+        let verb = match group {
+            Some(ref g) => format!("moved to '{}'", g),
+            None => "ungrouped".to_string(),
+        };
+        self.apply_to_selected(&verb, |app, name| app.set_group(name, group.clone()))
+    }
+
+    /// 選択中の全 Pod の lead/solo member にメッセージを送信
+    pub fn broadcast_to_selected(&mut self, message: &str) -> String {
+        self.apply_to_selected("messaged", |app, name| {
+            let pane_id = app
+                .state
+                .pods
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.members.first())
+                .map(|m| m.tmux_pane.clone())
+                .ok_or_else(|| anyhow::anyhow!("no member"))?;
+            app.tmux.send_keys(&pane_id, message)?;
+            if let Some(pod) = app.state.pods.iter_mut().find(|p| p.name == name) {
+                pod.record_prompt(message);
             }
-        }
+            app.save()
+        })
+    }
 
-        Ok(())
+    /// タグフィルタを設定 (None で解除)。フォーカスが隠れたら先頭の表示 Pod へ移す
+    pub fn set_tag_filter(&mut self, tag: Option<String>) {
+        self.state.tag_filter = tag;
+        self.keep_focus_visible();
     }
 
-    /// Pod を削除 (tmux セッションは残す)
-    pub fn forget_pod(&mut self, name: &str) -> Result<()> {
-        let idx = self
-            .state
-            .pods
-            .iter()
-            .position(|p| p.name == name)
-            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", name))?;
+    /// ワークスペースを切り替える。filter ならグリッドをそのプロジェクトの Pod に絞る
+    pub fn set_workspace(&mut self, project: Project, filter: bool) {
+        self.state.recent_workspaces.retain(|name| *name != project.name);
+        self.state.recent_workspaces.insert(0, project.name.clone());
+        self.state.recent_workspaces.truncate(RECENT_WORKSPACES);
+        self.state.current_project = Some(project);
+        self.state.workspace_filter = filter;
+        self.keep_focus_visible();
+        let _ = self.save_ui_state();
+    }
 
-        self.state.pods.remove(idx);
-        self.save()?;
+    /// グリッドをワークスペースの Pod に絞るかを切り替える
+    pub fn toggle_workspace_filter(&mut self) -> Result<bool> {
+        if self.state.current_project.is_none() {
+            anyhow::bail!("No workspace set (w to pick one)");
+        }
+        self.state.workspace_filter = !self.state.workspace_filter;
+        self.keep_focus_visible();
+        self.save_ui_state()?;
+        Ok(self.state.workspace_filter)
+    }
 
-        // focus の調整
-        if let Some(focus) = self.state.focus {
-            if focus >= self.state.pods.len() {
-                self.state.focus = if self.state.pods.is_empty() {
-                    None
-                } else {
-                    Some(self.state.pods.len() - 1)
-                };
+    /// フィルタでフォーカス中の Pod が隠れたら先頭の表示 Pod へ移す
+    fn keep_focus_visible(&mut self) {
+        if !self.state.focus.is_some_and(|i| self.state.is_visible(i)) {
+            if let Some(&first) = self.state.visible_pods().first() {
+                self.state.focus = Some(first);
             }
         }
+    }
 
-        Ok(())
+    /// フォーカス中 Pod が折りたたまれたグループ内にあるか
+    pub fn focused_group_collapsed(&self) -> bool {
+        self.state.focus.is_some_and(|i| self.state.is_in_collapsed_group(i))
     }
 
-    /// 状態を保存
-    pub fn save(&self) -> Result<()> {
-        self.store.save(&self.state.pods)
+    /// 折りたたまれたグループ上で Enter: 展開し、要対応 Pod があればそこへフォーカス
+    pub fn open_collapsed_group(&mut self) -> Result<()> {
+        let Some(group) = self.state.focused_pod().and_then(|p| self.state.display_group(p)).map(str::to_string) else {
+            return Ok(());
+        };
+        self.toggle_group_collapse()?;
+        if let Some(idx) = self.state.first_warning_in_group(&group) {
+            self.state.focus = Some(idx);
+        }
+        Ok(())
     }
 
     /// 全 Pod の状態を更新 (discovery + capture-pane + detect)
     pub fn refresh_pod_states(&mut self) {
+        self.poll_jobs();
         let mut new_pods: Vec<Pod> = Vec::new();
+        let mut detector_requests = Vec::new();
         let pod_count = self.state.pods.len();
+        let mut probe = AgentProbe::new(&self.config.detection.agent_processes);
+        // セッションが消えた Pod があったときだけ確かめる
+        let mut server_up = None;
 
         for idx in 0..pod_count {
             let pod = &mut self.state.pods[idx];
 
             // セッションが生きているか確認
-            if !Tmux::session_exists(&pod.tmux_session) {
-                if pod.status != PodStatus::Dead {
-                    pod.status = PodStatus::Dead;
-                    for member in &mut pod.members {
-                        member.status = MemberStatus::Dead;
-                    }
-                }
+            if !self.tmux.session_exists(&pod.tmux_session) {
+                pod.mark_dead(*server_up.get_or_insert_with(|| self.tmux.has_server()));
                 continue;
             } else if pod.status == PodStatus::Dead {
+                pod.server_lost = false;
                 // セッションが復活した場合、Dead から復帰
                 for member in &mut pod.members {
                     if member.status == MemberStatus::Dead {
@@ -340,7 +2749,7 @@ impl App {
             }
 
             // --- Discovery: 消えた member を除外 ---
-            discovery::remove_stale_members(pod);
+            discovery::remove_stale_members(self.tmux.as_ref(), pod);
 
             // --- Discovery: 新しい pane を検出 → 子 Pod 作成 ---
             {
@@ -349,7 +2758,7 @@ impl App {
                     .cloned()
                     .collect();
                 let pod = &self.state.pods[idx];
-                let discovered = discovery::discover_new_members(pod, &all_known);
+                let discovered = discovery::discover_new_members(self.tmux.as_ref(), pod, &all_known);
 
                 let pod = &mut self.state.pods[idx];
                 let children = discovery::create_child_pods(pod, discovered);
@@ -358,30 +2767,40 @@ impl App {
 
             // --- 既存メンバーの状態検出 ---
             let pod = &mut self.state.pods[idx];
+            let todos_done_pane = todos_done_pane(pod);
+            // サンドボックスの claude はコンテナ内で動くので、ホストのプロセス表には出てこない
+            let sandboxed = pod.sandbox.is_some();
             for member in &mut pod.members {
-                if let Ok(output) = Tmux::capture_pane(&member.tmux_pane) {
-                    let new_status = detect_member_status_with_config(
-                        &output,
-                        &self.config.detection.permission_patterns,
-                        &self.config.detection.error_patterns,
-                        &self.config.detection.idle_patterns,
-                    );
-                    if new_status != member.status {
-                        // Working -> 他の状態: working_secs に差分を加算
-                        if member.status == MemberStatus::Working {
-                            let secs = Utc::now().signed_duration_since(member.last_change).num_seconds().max(0) as u64;
-                            member.working_secs += secs;
-                        }
-                        member.status = new_status;
-                        member.last_change = Utc::now();
+                if let Ok(output) = self.tmux.capture_pane(&member.tmux_pane) {
+                    let external = self.external_statuses.get(&member.tmux_pane).cloned().flatten();
+                    let mut new_status = detect_member_status_for(&output, &self.config.detection, external);
+                    if self.config.detection.detector_command.is_some() {
+                        detector_requests.push((member.tmux_pane.clone(), output.clone()));
                     }
+                    // プロンプトが出ていても claude プロセスが無ければ素のシェル (終了済み)。
+                    // TODO がすべて完了して (質問せずに) 入力待ちなら、それも完了とみなす
+                    if !sandboxed
+                        && matches!(new_status, MemberStatus::Idle | MemberStatus::AwaitingInput)
+                        && probe.pane_has_agent(&member.tmux_pane) == Some(false)
+                        || new_status == MemberStatus::Idle && todos_done_pane.as_ref() == Some(&member.tmux_pane)
+                    {
+                        new_status = MemberStatus::Done;
+                    }
+                    // Working を抜けるときは作業時間に加算される
+                    member.set_status(new_status);
                     // Subagent 検出 (pane 出力から)
                     member.sub_agents = parse_sub_agents(&output);
-                    member.last_output = output;
+                    // タスク進捗は画面から消えても最後に見えたものを残す
+                    if let Some(progress) = parse_task_progress(&output) {
+                        member.progress = Some(progress);
+                    }
+                    member.context = parse_context_pressure(&output);
+                    member.last_output = self.redactor.redact(&output).into_owned();
                 }
             }
             pod.rollup_status();
         }
+        self.run_detectors(detector_requests);
 
         // 新 Pod を state に追加
         if !new_pods.is_empty() {
@@ -419,14 +2838,22 @@ impl App {
             .map(|p| p.name.clone())
             .collect();
 
+        let muted: std::collections::HashSet<&str> = self
+            .state
+            .pods
+            .iter()
+            .filter(|p| !self.alerts_enabled(p))
+            .map(|p| p.name.as_str())
+            .collect();
         for pod_name in &current_perm_pods {
-            if !self.state.previous_permission_pods.contains(pod_name) {
-                if self.config.notification.enabled {
-                    crate::notify::notify(
-                        "Apiary: Permission Required",
-                        &format!("Pod '{}' needs your approval", pod_name),
-                    );
-                }
+            if !self.state.previous_permission_pods.contains(pod_name)
+                && self.config.notification.enabled
+                && !muted.contains(pod_name.as_str())
+            {
+                crate::notify::notify(
+                    "Apiary: Permission Required",
+                    &format!("Pod '{}' needs your approval", pod_name),
+                );
             }
         }
         self.state.previous_permission_pods = current_perm_pods;
@@ -436,10 +2863,15 @@ impl App {
     pub fn selective_refresh(&mut self) {
         use std::time::{Duration, Instant};
 
+        self.poll_jobs();
+
         // hooks イベントを確認
         let hook_events = self.hooks.poll_events();
         for event in &hook_events {
             tracing::debug!("Hook event: {:?}", event);
+            if let (Some(pane), Some(session)) = (&event.pane, event.session_id.as_ref().filter(|s| !s.is_empty())) {
+                self.hook_sessions.insert(pane.clone(), session.clone());
+            }
         }
 
         if !hook_events.is_empty() {
@@ -456,17 +2888,9 @@ impl App {
                         };
                         if matches {
                             for member in &mut pod.members {
-                                if member.status != hook_status {
-                                    // Working -> 他の状態: working_secs に差分を加算
-                                    if member.status == MemberStatus::Working {
-                                        let secs = chrono::Utc::now()
-                                            .signed_duration_since(member.last_change)
-                                            .num_seconds()
-                                            .max(0) as u64;
-                                        member.working_secs += secs;
-                                    }
-                                    member.status = hook_status.clone();
-                                    member.last_change = chrono::Utc::now();
+                                // watcher の警告は確認されるまで Error のまま
+                                if member.alert.is_none() {
+                                    member.set_status(hook_status.clone());
                                 }
                                 member.last_polled = None;
                             }
@@ -493,14 +2917,12 @@ impl App {
 
                     for member in &mut pod.members {
                         match event.event.as_str() {
-                            "subagent_start" => {
-                                // 既存の同一 agent_id がなければ追加
-                                if !member.sub_agents.iter().any(|a| a.description == agent_id) {
-                                    member.sub_agents.push(crate::pod::SubAgent {
-                                        agent_type: agent_type.clone(),
-                                        description: agent_id.clone(),
-                                    });
-                                }
+                            // 既存の同一 agent_id がなければ追加
+                            "subagent_start" if !member.sub_agents.iter().any(|a| a.description == agent_id) => {
+                                member.sub_agents.push(crate::pod::SubAgent {
+                                    agent_type: agent_type.clone(),
+                                    description: agent_id.clone(),
+                                });
                             }
                             "subagent_stop" => {
                                 member.sub_agents.retain(|a| a.description != agent_id);
@@ -553,10 +2975,10 @@ impl App {
                 for idx in 0..pod_count {
                     {
                         let pod = &mut self.state.pods[idx];
-                        if !Tmux::session_exists(&pod.tmux_session) {
+                        if !self.tmux.session_exists(&pod.tmux_session) {
                             continue;
                         }
-                        discovery::remove_stale_members(pod);
+                        discovery::remove_stale_members(self.tmux.as_ref(), pod);
                     }
 
                     // all_known: 既存の全 Pod + 今回の新 Pod
@@ -564,7 +2986,7 @@ impl App {
                         .chain(new_pods.iter())
                         .cloned()
                         .collect();
-                    let discovered = discovery::discover_new_members(&self.state.pods[idx], &all_known);
+                    let discovered = discovery::discover_new_members(self.tmux.as_ref(), &self.state.pods[idx], &all_known);
 
                     let pod = &mut self.state.pods[idx];
                     let children = discovery::create_child_pods(pod, discovered);
@@ -580,19 +3002,82 @@ impl App {
             }
         }
 
+        // --- 管理外 Claude セッションの検出 ---
+        if self.last_unmanaged_scan.is_none_or(|t| t.elapsed() >= UNMANAGED_SCAN_INTERVAL) {
+            self.scan_unmanaged_sessions();
+        }
+
+        // --- git ブランチの更新 ---
+        if self.config.ui.show_project
+            && self.last_branch_refresh.is_none_or(|t| t.elapsed() >= BRANCH_REFRESH_INTERVAL)
+        {
+            self.refresh_branches();
+        }
+
+        // --- 利用上限のリセット後の自動再開 ---
+        if self.state.pods.iter().any(|p| p.rate_limit_reset.is_some()) {
+            self.resume_rate_limited();
+        }
+
+        // --- Claude Code の TODO リスト ---
+        if self.last_todos_refresh.is_none_or(|t| t.elapsed() >= TODOS_REFRESH_INTERVAL) {
+            self.refresh_todos();
+        }
+
+        // --- 作業中の区間を保存 ---
+        if self.last_working_checkpoint.is_none_or(|t| t.elapsed() >= WORKING_CHECKPOINT_INTERVAL) {
+            self.checkpoint_working_time();
+        }
+
+        // --- ペイン出力のログ (新しいペインや Detail・録画のあとに log-sink を起動し直す) ---
+        if self.last_log_check.is_none_or(|t| t.elapsed() >= LOG_CHECK_INTERVAL)
+            && self.state.pods.iter().any(|p| self.logs_enabled(p))
+        {
+            self.ensure_output_logs();
+        }
+
+        // --- セッションピッカーのプレビュー (開いている間は毎回) ---
+        self.refresh_adopt_preview();
+
+        // --- Home のプロジェクト概要 (見えているときだけ、ワークスペースを変えたらすぐに) ---
+        if self.shows_project_health()
+            && (self.last_health_refresh.is_none_or(|t| t.elapsed() >= HEALTH_REFRESH_INTERVAL)
+                || self.project_health.as_ref().map(|h| &h.project) != self.state.current_project.as_ref().map(|p| &p.name))
+        {
+            self.refresh_project_health();
+        }
+
+        // --- 推定コストと予算 ---
+        let budget_interval = Duration::from_secs(self.config.budget.check_interval_secs.max(1));
+        if self.config.budget.is_enabled() && self.last_budget_check.is_none_or(|t| t.elapsed() >= budget_interval) {
+            self.check_budget();
+        }
+
+        // --- 放置された Pod の片付け ---
+        let reap_interval = Duration::from_secs(self.config.reaper.check_interval_secs.max(1));
+        if self.config.reaper.is_enabled() && self.last_reap_check.is_none_or(|t| t.elapsed() >= reap_interval) {
+            self.reap_stale_pods();
+        }
+
+        // --- CPU / メモリのサンプリング ---
+        let sample_interval = Duration::from_secs(self.config.resources.sample_interval_secs.max(1));
+        if self.last_resource_sample.is_none_or(|t| t.elapsed() >= sample_interval) {
+            self.sample_resources();
+        }
+
         let now = Instant::now();
         let focus_idx = self.state.focus;
+        let mut probe = AgentProbe::new(&self.config.detection.agent_processes);
+        let mut watch_hits = Vec::new();
+        let mut detector_requests = Vec::new();
+        let mut server_up = None;
 
         for (pod_idx, pod) in self.state.pods.iter_mut().enumerate() {
-            if !Tmux::session_exists(&pod.tmux_session) {
-                if pod.status != PodStatus::Dead {
-                    pod.status = PodStatus::Dead;
-                    for member in &mut pod.members {
-                        member.status = MemberStatus::Dead;
-                    }
-                }
+            if !self.tmux.session_exists(&pod.tmux_session) {
+                pod.mark_dead(*server_up.get_or_insert_with(|| self.tmux.has_server()));
                 continue;
             } else if pod.status == PodStatus::Dead {
+                pod.server_lost = false;
                 // セッションが復活した場合、Dead から復帰
                 for member in &mut pod.members {
                     if member.status == MemberStatus::Dead {
@@ -604,20 +3089,27 @@ impl App {
             }
 
             let is_focused = focus_idx == Some(pod_idx);
+            let todos_done_pane = todos_done_pane(pod);
+            let sandboxed = pod.sandbox.is_some();
+            let watchers: Vec<usize> = (0..self.config.watch.len())
+                .filter(|&i| crate::watch::applies_to(&self.config.watch[i], pod))
+                .collect();
 
             for member in &mut pod.members {
                 // ポーリング間隔を状態に応じて決定
                 let interval = if is_focused {
                     Duration::from_millis(self.config.polling.focused_interval_ms)
                 } else {
-                    match member.status {
-                        MemberStatus::Permission => Duration::from_millis(self.config.polling.permission_interval_ms),
-                        MemberStatus::Working => Duration::from_millis(self.config.polling.working_interval_ms),
-                        MemberStatus::Error => Duration::from_millis(self.config.polling.error_interval_ms),
-                        MemberStatus::Idle => Duration::from_millis(self.config.polling.idle_interval_ms),
-                        MemberStatus::Done => Duration::from_millis(self.config.polling.idle_interval_ms),
-                        MemberStatus::Dead => Duration::from_millis(self.config.polling.idle_interval_ms),
-                    }
+                    let interval_ms = match member.status {
+                        MemberStatus::Permission => self.config.polling.permission_interval_ms,
+                        MemberStatus::Working => self.config.polling.working_interval_ms,
+                        MemberStatus::Error => self.config.polling.error_interval_ms,
+                        MemberStatus::RateLimited => self.config.polling.rate_limited_interval_ms,
+                        MemberStatus::Idle | MemberStatus::AwaitingInput => self.config.polling.idle_interval_ms,
+                        MemberStatus::Done => self.config.polling.idle_interval_ms,
+                        MemberStatus::Dead => self.config.polling.idle_interval_ms,
+                    };
+                    self.config.polling.scaled(interval_ms, pod.priority)
                 };
 
                 // 前回のポーリングから十分時間が経っているかチェック
@@ -632,22 +3124,38 @@ impl App {
 
                 member.last_polled = Some(now);
 
-                if let Ok(output) = Tmux::capture_pane(&member.tmux_pane) {
-                    let new_status = detect_member_status_with_config(
-                        &output,
-                        &self.config.detection.permission_patterns,
-                        &self.config.detection.error_patterns,
-                        &self.config.detection.idle_patterns,
-                    );
-                    if new_status != member.status {
-                        // Working -> 他の状態: working_secs に差分を加算
-                        if member.status == MemberStatus::Working {
-                            let secs = chrono::Utc::now().signed_duration_since(member.last_change).num_seconds().max(0) as u64;
-                            member.working_secs += secs;
+                if let Ok(output) = self.tmux.capture_pane(&member.tmux_pane) {
+                    // 状態の検出は生の出力で、保存と watcher (webhook に行を載せる) は伏せ字にしたもので
+                    let redacted = self.redactor.redact(&output).into_owned();
+                    // 前回の画面になかった行を watcher で調べる
+                    if !watchers.is_empty() {
+                        let lines = crate::watch::new_lines(&member.last_output, &redacted);
+                        let hits = crate::watch::evaluate(&self.config.watch, &watchers, &pod.name, &member.role, &lines);
+                        for hit in &hits {
+                            let rule = &self.config.watch[hit.rule];
+                            if rule.action == crate::config::WatchAction::Error {
+                                member.alert = Some(rule.name.clone());
+                            }
                         }
-                        member.status = new_status;
-                        member.last_change = chrono::Utc::now();
+                        watch_hits.extend(hits);
                     }
+                    let external = self.external_statuses.get(&member.tmux_pane).cloned().flatten();
+                    let mut new_status = detect_member_status_for(&output, &self.config.detection, external);
+                    if self.config.detection.detector_command.is_some() {
+                        detector_requests.push((member.tmux_pane.clone(), output.clone()));
+                    }
+                    if !sandboxed
+                        && matches!(new_status, MemberStatus::Idle | MemberStatus::AwaitingInput)
+                        && probe.pane_has_agent(&member.tmux_pane) == Some(false)
+                        || new_status == MemberStatus::Idle && todos_done_pane.as_ref() == Some(&member.tmux_pane)
+                    {
+                        new_status = MemberStatus::Done;
+                    }
+                    if member.alert.is_some() {
+                        new_status = MemberStatus::Error;
+                    }
+                    // Working を抜けるときは作業時間に加算される
+                    member.set_status(new_status);
                     // Subagent / Agent Teams 検出 (pane 出力から)
                     let detected = parse_sub_agents(&output);
                     if !detected.is_empty() || !member.sub_agents.is_empty() {
@@ -658,7 +3166,11 @@ impl App {
                         );
                     }
                     member.sub_agents = detected;
-                    member.last_output = output;
+                    if let Some(progress) = parse_task_progress(&output) {
+                        member.progress = Some(progress);
+                    }
+                    member.context = parse_context_pressure(&output);
+                    member.last_output = redacted;
                 }
 
                 // Detail モード: ストリームがあればそこから drain + リサイズ追従
@@ -678,6 +3190,8 @@ impl App {
             }
             pod.rollup_status();
         }
+        self.handle_watch_hits(watch_hits);
+        self.run_detectors(detector_requests);
 
         // Detail モードで focused pod が Dead になったら自動で Home に戻る
         if self.state.mode == Mode::Detail {
@@ -692,6 +3206,36 @@ impl App {
         }
     }
 
+    /// `[[watch]]` が一致したときの通知・webhook (Error にするものはポーリング中に印を付けてある)
+    fn handle_watch_hits(&mut self, hits: Vec<crate::watch::WatchHit>) {
+        for hit in hits {
+            let rule = &self.config.watch[hit.rule];
+            let message = format!("Watcher '{}' matched in {}: {}", rule.name, hit.pod, hit.line);
+            tracing::info!(watcher = %rule.name, pod = %hit.pod, member = %hit.member, "watch matched");
+            match (rule.action, rule.url.as_deref()) {
+                (crate::config::WatchAction::Notify, _) => {
+                    let enabled = self.state.pods.iter().find(|p| p.name == hit.pod).is_some_and(|p| self.alerts_enabled(p));
+                    if enabled && self.config.notification.enabled {
+                        crate::notify::notify("Apiary: Watch", &message);
+                    }
+                }
+                (crate::config::WatchAction::Webhook, Some(url)) => crate::watch::post_webhook(url, rule, &hit),
+                _ => {}
+            }
+            self.state.status_message = Some(message);
+        }
+    }
+
+    /// フォーカス中の Pod の watcher による Error 表示を解除する (Detail を開いたとき)
+    pub fn acknowledge_alerts(&mut self) {
+        if let Some(pod) = self.state.focused_pod_mut() {
+            for member in pod.members.iter_mut().filter(|m| m.alert.is_some()) {
+                member.alert = None;
+                member.last_polled = None;
+            }
+        }
+    }
+
     /// Detail モード開始時に PTY ストリームを開始
     pub fn start_detail_pty_stream(&mut self) {
         let selected = self.state.selected_member.unwrap_or(0);
@@ -714,12 +3258,23 @@ impl App {
 
         // オリジナル window サイズを保存
         if self.state.detail_original_window_size.is_none() {
-            if let Ok(orig) = Tmux::get_window_size(&pane_id) {
+            if let Ok(orig) = self.tmux.get_window_size(&pane_id) {
                 self.state.detail_original_window_size = Some((pane_id.clone(), orig.0, orig.1));
             }
         }
 
-        match DetailPtyStream::start(&pane_id, cols, rows) {
+        if !self.tmux.can_stream() {
+            return;
+        }
+        // pipe-pane は 1 ペインに 1 つなので、録画中はストリームせずキャプチャで表示する
+        // (ログとは log-sink --tee で分け合う)
+        self.stop_detail_pty_stream();
+        if self.tmux.pane_pipe_owner(&pane_id).is_some_and(|owner| owner != "log") {
+            self.state.status_message = Some("Pane is being recorded; Detail shows snapshots".to_string());
+            return;
+        }
+        let log_cmd = self.state.focused_pod().and_then(|pod| self.log_command(pod, selected));
+        match DetailPtyStream::start(&pane_id, cols, rows, log_cmd) {
             Ok(stream) => {
                 self.detail_pty_stream = Some(stream);
             }
@@ -740,7 +3295,7 @@ impl App {
     pub fn restore_detail_window_size(&mut self) {
         self.stop_detail_pty_stream();
         if let Some((pane_id, cols, rows)) = self.state.detail_original_window_size.take() {
-            let _ = Tmux::resize_window(&pane_id, cols, rows);
+            let _ = self.tmux.resize_window(&pane_id, cols, rows);
         }
         self.state.detail_just_resized = false;
     }
@@ -770,48 +3325,187 @@ impl App {
             return;
         }
 
-        let total = self.state.pods.len();
-        let cols = self.state.grid_columns.max(1);
-        let current = self.state.focus.unwrap_or(0);
+        let view_mode = self.state.effective_view_mode();
+        if view_mode == ViewMode::Kanban {
+            self.move_focus_kanban(direction);
+            return;
+        }
+
+        // Grid 表示では折りたたまれたグループを 1 つの項目として扱う
+        let nav: Vec<usize> = match view_mode {
+            ViewMode::Grid => self.state.grid_navigable_pods(),
+            _ => self.state.visible_pods(),
+        };
+        let total = nav.len();
+        if total == 0 {
+            return;
+        }
+        // List 表示では上下も 1 行ずつ移動
+        let cols = match view_mode {
+            ViewMode::List => 1,
+            _ => self.state.grid_columns.max(1),
+        };
+        let focus = self.state.focus.unwrap_or(0);
+        let current = nav.iter().position(|&i| i == focus).unwrap_or_else(|| {
+            // 折りたたまれたグループ内の Pod → グループ代表の位置
+            let group = self.state.pods.get(focus).and_then(|p| self.state.display_group(p));
+            nav.iter()
+                .position(|&i| group.is_some() && self.state.display_group(&self.state.pods[i]) == group)
+                .unwrap_or(0)
+        });
+
+        let new_focus = match direction {
+            Direction::Right => {
+                if current + 1 < total {
+                    current + 1
+                } else {
+                    current
+                }
+            }
+            Direction::Left => {
+                if current > 0 {
+                    current - 1
+                } else {
+                    current
+                }
+            }
+            Direction::Down => {
+                let next = current + cols;
+                if next < total {
+                    next
+                } else {
+                    current
+                }
+            }
+            Direction::Up => {
+                if current >= cols {
+                    current - cols
+                } else {
+                    current
+                }
+            }
+        };
+
+        self.state.focus = Some(nav[new_focus]);
+    }
+
+    /// Kanban 表示: 上下はカラム内、左右は隣の空でないカラムへ移動
+    fn move_focus_kanban(&mut self, direction: Direction) {
+        let columns: Vec<Vec<usize>> = self
+            .state
+            .kanban_columns()
+            .into_iter()
+            .map(|(_, indices)| indices)
+            .filter(|indices| !indices.is_empty())
+            .collect();
+        if columns.is_empty() {
+            return;
+        }
+
+        let current = self.state.focus.unwrap_or(columns[0][0]);
+        let (col, row) = columns
+            .iter()
+            .enumerate()
+            .find_map(|(c, indices)| indices.iter().position(|&i| i == current).map(|r| (c, r)))
+            .unwrap_or((0, 0));
+
+        let (new_col, new_row) = match direction {
+            Direction::Up => (col, row.saturating_sub(1)),
+            Direction::Down => (col, (row + 1).min(columns[col].len() - 1)),
+            Direction::Left => {
+                let c = col.saturating_sub(1);
+                (c, row.min(columns[c].len() - 1))
+            }
+            Direction::Right => {
+                let c = (col + 1).min(columns.len() - 1);
+                (c, row.min(columns[c].len() - 1))
+            }
+        };
+
+        self.state.focus = Some(columns[new_col][new_row]);
+    }
+
+    /// 左ペインの表示 / 非表示を切り替え (状態は ui_state.json に保存)
+    pub fn toggle_left_pane(&mut self) {
+        self.state.left_pane_hidden = !self.state.left_pane_hidden;
+        self.state.status_message = Some(if self.state.left_pane_hidden {
+            "Left pane hidden (\\ to show)".to_string()
+        } else {
+            "Left pane shown".to_string()
+        });
+        let _ = self.save_ui_state();
+    }
+
+    /// 指定 Pod にフォーカス。フィルタや折りたたみで隠れていれば見えるようにする
+    pub fn focus_pod(&mut self, idx: usize) {
+        if idx >= self.state.pods.len() {
+            return;
+        }
+        if !self.state.is_visible(idx) {
+            self.state.tag_filter = None;
+            self.state.workspace_filter = false;
+        }
+        if self.state.is_in_collapsed_group(idx) {
+            if let Some(group) = self.state.display_group(&self.state.pods[idx]).map(str::to_string) {
+                self.state.collapsed_groups.remove(&group);
+                let _ = self.save_ui_state();
+            }
+        }
+        self.state.focus = Some(idx);
+        self.state.pane_focus = PaneFocus::Right;
+    }
+
+    /// 表示順で n 番目 (1 始まり) の Pod にフォーカス
+    pub fn quick_focus(&mut self, n: usize) {
+        match n.checked_sub(1).and_then(|i| self.state.quick_focus_targets().get(i).copied()) {
+            Some(idx) => self.state.focus = Some(idx),
+            None => self.state.status_message = Some(format!("No pod #{}", n)),
+        }
+    }
+
+    /// 表示順で n 番目 (1 始まり) のグループの先頭 Pod にフォーカス
+    pub fn jump_to_group(&mut self, n: usize) {
+        let Some(group) = n.checked_sub(1).and_then(|i| self.state.group_order().into_iter().nth(i)) else {
+            self.state.status_message = Some(format!("No group #{}", n));
+            return;
+        };
+        let first = self.state.grid_navigable_pods().into_iter().find(|&i| {
+            self.state.display_group(&self.state.pods[i]) == Some(group.as_str())
+        });
+        if let Some(idx) = first {
+            self.state.focus = Some(idx);
+            self.state.status_message = Some(format!("Group: {}", group));
+        }
+    }
+
+    /// ペイン配置 (左右 / 上下) を切り替え
+    pub fn toggle_layout(&mut self) {
+        self.state.layout = self.state.layout.toggle();
+        self.state.status_message = Some(format!("Layout: {}", self.state.layout.label()));
+        let _ = self.save_ui_state();
+    }
 
-        let new_focus = match direction {
-            Direction::Right => {
-                if current + 1 < total {
-                    current + 1
-                } else {
-                    current
-                }
-            }
-            Direction::Left => {
-                if current > 0 {
-                    current - 1
-                } else {
-                    current
-                }
-            }
-            Direction::Down => {
-                let next = current + cols;
-                if next < total {
-                    next
-                } else {
-                    current
-                }
-            }
-            Direction::Up => {
-                if current >= cols {
-                    current - cols
-                } else {
-                    current
-                }
-            }
-        };
+    /// グリッド / リスト / Kanban 表示を切り替え
+    pub fn toggle_view_mode(&mut self) {
+        self.state.view_mode = self.state.view_mode.next();
+        self.state.status_message = Some(format!("View: {}", self.state.view_mode.label()));
+    }
 
-        self.state.focus = Some(new_focus);
+    /// 左ペイン入力に合わせて補完ポップアップを更新
+    pub fn update_completion(&mut self) {
+        let projects: Vec<(String, String)> = self
+            .project_store
+            .list()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| (p.name, p.path))
+            .collect();
+        self.state.completion = Completion::build(&self.state.inline_input, SLASH_COMMANDS, &projects);
     }
 
     /// コマンド文字列をパースして実行
     pub fn execute_command(&mut self, cmd: &str) -> Result<String> {
-        let parts: Vec<&str> = cmd.trim().split_whitespace().collect();
+        let parts: Vec<&str> = cmd.split_whitespace().collect();
 
         if parts.is_empty() {
             return Ok(String::new());
@@ -821,7 +3515,7 @@ impl App {
         let parts = if parts[0] == "pod" { &parts[1..] } else { &parts };
 
         if parts.is_empty() {
-            return Ok("Available: create, adopt, drop, forget, list, project, browse".to_string());
+            return Ok(format!("Available: {}", slash_command_names()));
         }
 
         match parts[0] {
@@ -840,7 +3534,7 @@ impl App {
                     .position(|&p| p == "--group")
                     .and_then(|i| parts.get(i + 1))
                     .copied();
-                self.create_pod(name, project, group, None)?;
+                self.create_pod(name, project, group, None, None)?;
                 Ok(format!("Pod '{}' created", name))
             }
             "adopt" => {
@@ -866,7 +3560,22 @@ impl App {
                     return Ok("Usage: drop <name>".to_string());
                 }
                 self.drop_pod(parts[1])?;
-                Ok(format!("Pod '{}' dropped", parts[1]))
+                Ok(format!("Pod '{}' dropped (/restore {} to undo)", parts[1], parts[1]))
+            }
+            "restore" => {
+                if parts.len() < 2 {
+                    let trashed = self.trash.list()?;
+                    if trashed.is_empty() {
+                        return Ok("Trash is empty".to_string());
+                    }
+                    let list: Vec<String> = trashed
+                        .iter()
+                        .map(|t| format!("  {} (dropped {})", t.pod.name, t.dropped_at.with_timezone(&chrono::Local).format("%m-%d %H:%M")))
+                        .collect();
+                    return Ok(format!("Usage: restore <name>\nTrash:\n{}", list.join("\n")));
+                }
+                self.restore_pod(parts[1])?;
+                Ok(format!("Pod '{}' restored", parts[1]))
             }
             "forget" => {
                 if parts.len() < 2 {
@@ -886,7 +3595,7 @@ impl App {
                     .map(|p| {
                         format!(
                             "{} {} ({}, {} members)",
-                            p.status_icon(),
+                            p.status_icon(self.config.ui.icon_set()),
                             p.name,
                             p.elapsed_time(),
                             p.members.len()
@@ -922,10 +3631,7 @@ impl App {
                             .and_then(|i| parts.get(i + 1))
                             .copied();
                         if let Some(name) = name {
-                            let project = crate::project::Project {
-                                name: name.to_string(),
-                                path: path.to_string(),
-                            };
+                            let project = crate::project::Project::new(name, path);
                             self.project_store.register(&project)?;
                             Ok(format!("Project '{}' registered → {}", name, path))
                         } else {
@@ -951,13 +3657,13 @@ impl App {
                 self.open_browser(None);
                 Ok(String::new())
             }
-            _ => Ok(format!("Unknown command: '{}'. Try: create, adopt, drop, forget, list, project, browse", parts[0])),
+            _ => Ok(format!("Unknown command: '{}'. Try: {}", parts[0], slash_command_names())),
         }
     }
 
     /// Detail モードから pane にテキストを送信
     pub fn send_input_to_pane(&mut self) -> Result<()> {
-        let input = self.state.chat_input.clone();
+        let input = self.state.chat_input.as_str().to_string();
         if input.is_empty() {
             return Ok(());
         }
@@ -970,11 +3676,17 @@ impl App {
             .map(|m| m.tmux_pane.clone())
             .ok_or_else(|| anyhow::anyhow!("No focused pod or member"))?;
 
-        Tmux::send_keys(&pane_id, &input)?;
+        self.tmux.send_keys(&pane_id, &input)?;
         self.state.chat_input.clear();
+        if selected == 0 {
+            if let Some(pod) = self.state.focused_pod_mut() {
+                pod.record_prompt(&input);
+            }
+        }
 
         // 送信後すぐに pane 出力を更新（即時フィードバック）
-        if let Ok(output) = Tmux::capture_pane(&pane_id) {
+        if let Ok(output) = self.tmux.capture_pane(&pane_id) {
+            let output = self.redactor.redact(&output).into_owned();
             if let Some(pod) = self.state.focused_pod_mut() {
                 if let Some(member) = pod.members.get_mut(selected) {
                     member.last_output = output;
@@ -996,26 +3708,29 @@ impl App {
         match key.code {
             KeyCode::Char(c) => {
                 if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    Tmux::send_keys_raw(&pane_id, &format!("C-{}", c))?;
+                    self.tmux.send_keys_raw(&pane_id, &format!("C-{}", c))?;
                 } else {
-                    Tmux::send_keys_literal(&pane_id, &c.to_string())?;
+                    self.tmux.send_keys_literal(&pane_id, &c.to_string())?;
                 }
             }
-            KeyCode::Enter => Tmux::send_keys_raw(&pane_id, "Enter")?,
-            KeyCode::Esc => Tmux::send_keys_raw(&pane_id, "Escape")?,
-            KeyCode::Backspace => Tmux::send_keys_raw(&pane_id, "BSpace")?,
-            KeyCode::Tab => Tmux::send_keys_raw(&pane_id, "Tab")?,
-            KeyCode::Up => Tmux::send_keys_raw(&pane_id, "Up")?,
-            KeyCode::Down => Tmux::send_keys_raw(&pane_id, "Down")?,
-            KeyCode::Left => Tmux::send_keys_raw(&pane_id, "Left")?,
-            KeyCode::Right => Tmux::send_keys_raw(&pane_id, "Right")?,
-            KeyCode::Delete => Tmux::send_keys_raw(&pane_id, "DC")?,
-            KeyCode::Home => Tmux::send_keys_raw(&pane_id, "Home")?,
-            KeyCode::End => Tmux::send_keys_raw(&pane_id, "End")?,
-            KeyCode::PageUp => Tmux::send_keys_raw(&pane_id, "PPage")?,
-            KeyCode::PageDown => Tmux::send_keys_raw(&pane_id, "NPage")?,
+            KeyCode::Enter => self.tmux.send_keys_raw(&pane_id, "Enter")?,
+            KeyCode::Esc => self.tmux.send_keys_raw(&pane_id, "Escape")?,
+            KeyCode::Backspace => self.tmux.send_keys_raw(&pane_id, "BSpace")?,
+            KeyCode::Tab => self.tmux.send_keys_raw(&pane_id, "Tab")?,
+            KeyCode::Up => self.tmux.send_keys_raw(&pane_id, "Up")?,
+            KeyCode::Down => self.tmux.send_keys_raw(&pane_id, "Down")?,
+            KeyCode::Left => self.tmux.send_keys_raw(&pane_id, "Left")?,
+            KeyCode::Right => self.tmux.send_keys_raw(&pane_id, "Right")?,
+            KeyCode::Delete => self.tmux.send_keys_raw(&pane_id, "DC")?,
+            KeyCode::Home => self.tmux.send_keys_raw(&pane_id, "Home")?,
+            KeyCode::End => self.tmux.send_keys_raw(&pane_id, "End")?,
+            KeyCode::PageUp => self.tmux.send_keys_raw(&pane_id, "PPage")?,
+            KeyCode::PageDown => self.tmux.send_keys_raw(&pane_id, "NPage")?,
             _ => return Ok(()),
         }
+        if let Some(pod) = self.state.focused_pod_mut() {
+            pod.touch_input();
+        }
 
         // ストリームがあれば drain で即時反映
         if let Some(ref mut stream) = self.detail_pty_stream {
@@ -1033,7 +3748,11 @@ impl App {
             .map(|m| m.tmux_pane.clone())
             .ok_or_else(|| anyhow::anyhow!("No focused pod or member"))?;
 
-        Tmux::send_keys_literal(&pane_id, text)?;
+        // bracketed paste として 1 イベントで届ける
+        self.tmux.paste_text(&pane_id, text)?;
+        if let Some(pod) = self.state.focused_pod_mut() {
+            pod.touch_input();
+        }
 
         if let Some(ref mut stream) = self.detail_pty_stream {
             std::thread::sleep(std::time::Duration::from_millis(10));
@@ -1042,61 +3761,79 @@ impl App {
         Ok(())
     }
 
-    /// Chat メッセージを送信
+    /// Chat の相手: フォーカス中 Pod の selected_member (無ければリード) の (Pod 名, role, ペイン)
+    fn chat_target(&self) -> Option<(String, String, String)> {
+        let pod = self.state.focused_pod()?;
+        let member = self
+            .state
+            .selected_member
+            .and_then(|i| pod.members.get(i))
+            .or(pod.members.first())?;
+        Some((pod.name.clone(), member.role.clone(), member.tmux_pane.clone()))
+    }
+
+    /// Chat の相手を次のメンバーに切り替える
+    pub fn cycle_chat_member(&mut self) {
+        let count = self.state.focused_pod().map_or(0, |p| p.members.len());
+        if count > 0 {
+            self.state.selected_member = Some((self.state.selected_member.unwrap_or(0) + 1) % count);
+        }
+    }
+
+    /// Chat メッセージを選択中のメンバーに送信
     pub fn send_chat_message(&mut self) -> Result<()> {
-        let input = self.state.chat_input.clone();
+        let input = self.state.chat_input.as_str().to_string();
         if input.is_empty() {
             return Ok(());
         }
 
-        // focused pod の lead/solo member を取得
-        let pane_id = self
-            .state
-            .focused_pod()
-            .and_then(|pod| pod.members.first())
-            .map(|m| m.tmux_pane.clone())
+        let (pod_name, role, pane_id) = self
+            .chat_target()
             .ok_or_else(|| anyhow::anyhow!("No focused pod or member"))?;
 
-        // スナップショット保存
-        if let Ok(snapshot) = Tmux::capture_pane_lines(&pane_id, 100) {
-            self.state.capture_snapshot = Some(snapshot);
+        // スナップショット保存 (メンバーごと)
+        if let Ok(snapshot) = self.tmux.capture_pane_lines(&pane_id, 100) {
+            self.state.chat_snapshots.insert(pane_id.clone(), snapshot);
         }
 
         // pane に送信
-        Tmux::send_keys(&pane_id, &input)?;
+        self.tmux.send_keys(&pane_id, &input)?;
+        if let Some(pod) = self.state.focused_pod_mut() {
+            pod.record_prompt(&input);
+        }
+        self.save()?;
 
         // chat_history に追加
         self.state.chat_history.push(ChatMessage {
             sender: "you".to_string(),
             content: input,
             timestamp: Utc::now(),
+            pod: pod_name,
+            member: role,
         });
 
-        // 入力をクリア
+        self.chat_store.save(&self.state.chat_history)?;
+
+        // 入力をクリアし、最新の位置に戻る
         self.state.chat_input.clear();
+        self.state.chat_scroll = 0;
+        self.state.chat_unread = 0;
 
         Ok(())
     }
 
-    /// Chat モードで Claude の応答を差分検出して chat_history に追加
+    /// Chat モードで選択中のメンバーの応答を差分検出して chat_history に追加
     pub fn refresh_chat_output(&mut self) {
-        // スナップショットがない場合はスキップ
-        let snapshot = match &self.state.capture_snapshot {
-            Some(s) => s.clone(),
-            None => return,
+        let Some((pod_name, role, pane_id)) = self.chat_target() else {
+            return;
         };
-
-        let pane_id = match self
-            .state
-            .focused_pod()
-            .and_then(|pod| pod.members.first())
-            .map(|m| m.tmux_pane.clone())
-        {
-            Some(id) => id,
+        // スナップショットがない (まだ送信していない) メンバーはスキップ
+        let snapshot = match self.state.chat_snapshots.get(&pane_id) {
+            Some(s) => s.clone(),
             None => return,
         };
 
-        let current = match Tmux::capture_pane_lines(&pane_id, 100) {
+        let current = match self.tmux.capture_pane_lines(&pane_id, 100) {
             Ok(c) => c,
             Err(_) => return,
         };
@@ -1108,33 +3845,91 @@ impl App {
         // スナップショットの行数より多い行が新しい出力
         if current_lines.len() > snapshot_lines.len() {
             let new_lines = &current_lines[snapshot_lines.len()..];
-            let new_output = new_lines.join("\n").trim().to_string();
-
-            if !new_output.is_empty() {
-                // 既に同じ内容の応答がないか確認
-                let already_added = self
-                    .state
-                    .chat_history
-                    .last()
-                    .map(|m| m.sender == "claude" && m.content == new_output)
-                    .unwrap_or(false);
-
-                if !already_added {
-                    // 前回の claude メッセージを更新（差分が増えていく場合）
-                    if let Some(last) = self.state.chat_history.last_mut() {
-                        if last.sender == "claude" {
-                            last.content = new_output;
-                            return;
-                        }
-                    }
+            let new_output = self.redactor.redact(new_lines.join("\n").trim()).into_owned();
+            if new_output.is_empty() {
+                return;
+            }
 
+            // このメンバーとの最後のメッセージが応答なら更新 (差分が増えていく場合)
+            let last = self
+                .state
+                .chat_history
+                .iter_mut()
+                .rev()
+                .find(|m| m.pod == pod_name && m.member == role);
+            let (old_lines, new_lines) = match last {
+                Some(last) if last.sender != "you" && last.content == new_output => return,
+                Some(last) if last.sender != "you" => {
+                    let old_lines = last.content.lines().count();
+                    last.content = new_output;
+                    (old_lines, last.content.lines().count())
+                }
+                _ => {
+                    let new_lines = new_output.lines().count();
                     self.state.chat_history.push(ChatMessage {
-                        sender: "claude".to_string(),
+                        sender: role.clone(),
                         content: new_output,
                         timestamp: Utc::now(),
+                        pod: pod_name,
+                        member: role,
                     });
+                    if self.state.chat_scroll > 0 {
+                        self.state.chat_unread += 1;
+                    }
+                    (0, new_lines)
                 }
+            };
+            // さかのぼって読んでいる間は、末尾に行が増えても表示位置を動かさない
+            if self.state.chat_scroll > 0 {
+                self.state.chat_scroll = (self.state.chat_scroll + new_lines).saturating_sub(old_lines).max(1);
             }
+            if let Err(e) = self.chat_store.save(&self.state.chat_history) {
+                tracing::warn!("Failed to save chat history: {}", e);
+            }
+        }
+    }
+
+    /// pod とのやり取りを Markdown で path に書き出す。書き出したメッセージ数を返す
+    pub fn export_chat(&self, pod: &str, path: &std::path::Path) -> Result<usize> {
+        let count = self.state.chat_history.iter().filter(|m| m.pod == pod).count();
+        if count == 0 {
+            anyhow::bail!("No chat history for pod '{}'", pod);
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+        let markdown = crate::store::chat::transcript_markdown(pod, &self.state.chat_history);
+        std::fs::write(path, markdown).with_context(|| format!("Failed to write transcript: {:?}", path))?;
+        Ok(count)
+    }
+
+    /// Chat 中の Pod のやり取りを transcripts/<pod>-<日時>.md に書き出す
+    pub fn export_focused_chat(&mut self) {
+        let Some(pod) = self.state.focused_pod().map(|p| p.name.clone()) else {
+            return;
+        };
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let path = self.chat_store.transcript_dir().join(format!("{}-{}.md", pod, stamp));
+        self.state.status_message = Some(match self.export_chat(&pod, &path) {
+            Ok(count) => format!("Exported {} messages to {}", count, path.display()),
+            Err(e) => format!("Export error: {}", e),
+        });
+    }
+
+    /// Chat 履歴をスクロールする (正で古い方へ、負で新しい方へ)。最新まで戻ると未読数を消す
+    pub fn scroll_chat(&mut self, lines: isize) {
+        let pod_name = self.state.focused_pod().map(|p| p.name.clone()).unwrap_or_default();
+        let total: usize = self
+            .state
+            .chat_history
+            .iter()
+            .filter(|m| m.pod == pod_name)
+            .map(|m| m.content.lines().count())
+            .sum();
+        let scroll = self.state.chat_scroll.saturating_add_signed(lines);
+        self.state.chat_scroll = scroll.min(total.saturating_sub(1));
+        if self.state.chat_scroll == 0 {
+            self.state.chat_unread = 0;
         }
     }
 
@@ -1144,22 +3939,134 @@ impl App {
             .find_permission_member_pane()
             .ok_or_else(|| anyhow::anyhow!("No member awaiting permission"))?;
 
-        Tmux::send_keys_raw(&pane_id, "y")?;
+        self.tmux.send_keys_raw(&pane_id, "y")?;
         self.state.current_permission = None;
+        if let Some(pod) = self.state.focused_pod_mut() {
+            pod.touch_input();
+        }
+        if let Some(pod) = self.state.focused_pod() {
+            self.record_event(pod, EventKind::PermissionApproved);
+        }
         Ok(())
     }
 
+    /// `apiary exec --member`: メンバーのペインにコマンドを打ち込む。送ったペイン ID を返す
+    pub fn exec_in_member(&self, pod_name: &str, member: &str, command: &str) -> Result<String> {
+        let pod = self.state.pods.iter()
+            .find(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+        let pane = pod.members.iter()
+            .find(|m| m.role == member)
+            .map(|m| m.tmux_pane.clone())
+            .ok_or_else(|| {
+                let roles: Vec<&str> = pod.members.iter().map(|m| m.role.as_str()).collect();
+                anyhow::anyhow!("Pod '{}' has no member '{}' (members: {})", pod_name, member, roles.join(", "))
+            })?;
+        self.tmux.send_keys_literal(&pane, command)?;
+        self.tmux.send_keys_raw(&pane, "Enter")?;
+        Ok(pane)
+    }
+
+    /// `apiary exec`: Pod のリードペインの隣に一時ペインを開いて作業ディレクトリでコマンドを実行。
+    /// wait なら終了まで待って (出力, 終了コード) を返しペインを閉じる。
+    /// wait しない場合は結果を確認できるよう Enter を押すまでペインを残す
+    pub fn exec_in_split(&self, pod_name: &str, command: &str, wait: bool, timeout: Option<std::time::Duration>) -> Result<Option<(String, i32)>> {
+        let pod = self.state.pods.iter()
+            .find(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+        let target = pod.members.first()
+            .map(|m| m.tmux_pane.clone())
+            .unwrap_or_else(|| pod.tmux_session.clone());
+
+        // TUI からは複数同時に開きうるので時刻も含めて一意にする
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        // 出力にはコマンドの結果がそのまま入るので、共有の /tmp ではなく自分専用のディレクトリに 0600 で作る
+        let dir = pty_stream_dir();
+        ensure_private_dir(&dir)?;
+        let base = dir.join(format!("apiary-exec-{}-{}", std::process::id(), nanos));
+        let out_path = base.with_extension("out");
+        let exit_path = base.with_extension("exit");
+        create_new_private_file(&out_path)?;
+        if let Err(e) = create_new_private_file(&exit_path) {
+            let _ = std::fs::remove_file(&out_path);
+            return Err(e);
+        }
+
+        // 出力を tee で記録し、終了コードは別ファイルへ (パイプで失われないよう { } 内で書く。
+        // コマンドの exit で記録が飛ばされないようサブシェルで実行)
+        let mut script = format!("{{ ( {}\n); echo $? > \"$2\"; }} 2>&1 | tee \"$1\"", command);
+        if !wait {
+            script.push_str("; printf '\\n[apiary] exit %s - press Enter to close' \"$(cat \"$2\")\"; read -r _; rm -f \"$1\" \"$2\"");
+        }
+        let out_str = out_path.to_string_lossy().to_string();
+        let exit_str = exit_path.to_string_lossy().to_string();
+        let pane = match self.tmux.split_window(&target, &["sh", "-c", &script, "apiary-exec", &out_str, &exit_str]) {
+            Ok(pane) => pane,
+            Err(e) => {
+                let _ = std::fs::remove_file(&out_path);
+                let _ = std::fs::remove_file(&exit_path);
+                return Err(e);
+            }
+        };
+        if !wait {
+            return Ok(None);
+        }
+
+        let started = std::time::Instant::now();
+        let code = loop {
+            if let Some(code) = std::fs::read_to_string(&exit_path).ok().and_then(|c| c.trim().parse::<i32>().ok()) {
+                break code;
+            }
+            if timeout.is_some_and(|t| started.elapsed() >= t) {
+                let _ = self.tmux.kill_pane(&pane);
+                let _ = std::fs::remove_file(&out_path);
+                let _ = std::fs::remove_file(&exit_path);
+                anyhow::bail!("Timed out waiting for command in '{}'", pod_name);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        };
+        // tee が書き終えるのを少し待つ
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let output = std::fs::read_to_string(&out_path).unwrap_or_default();
+        let _ = self.tmux.kill_pane(&pane);
+        let _ = std::fs::remove_file(&out_path);
+        let _ = std::fs::remove_file(&exit_path);
+        Ok(Some((output, code)))
+    }
+
     /// Permission を deny
     pub fn deny_permission(&mut self) -> Result<()> {
         let pane_id = self
             .find_permission_member_pane()
             .ok_or_else(|| anyhow::anyhow!("No member awaiting permission"))?;
 
-        Tmux::send_keys_raw(&pane_id, "n")?;
+        self.tmux.send_keys_raw(&pane_id, "n")?;
         self.state.current_permission = None;
+        if let Some(pod) = self.state.focused_pod_mut() {
+            pod.touch_input();
+        }
+        if let Some(pod) = self.state.focused_pod() {
+            self.record_event(pod, EventKind::PermissionDenied);
+        }
         Ok(())
     }
 
+    /// 名前で指定した Pod の許可待ちに答える (`apiary serve` のダッシュボードから)
+    pub fn answer_permission(&mut self, pod_name: &str, approve: bool) -> Result<()> {
+        let index = self.state.pods.iter()
+            .position(|p| p.name == pod_name)
+            .ok_or_else(|| anyhow::anyhow!("Pod '{}' not found", pod_name))?;
+        self.state.focus = Some(index);
+        if approve {
+            self.approve_permission()
+        } else {
+            self.deny_permission()
+        }
+    }
+
     /// Permission 状態の member の pane_id を取得
     fn find_permission_member_pane(&self) -> Option<String> {
         self.state.focused_pod().and_then(|pod| {
@@ -1181,28 +4088,35 @@ impl App {
                     .unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/")))
             }
         };
-        let entries = Self::read_directory(&path);
         self.state.browser_state = Some(BrowserState {
-            current_path: path,
-            entries,
+            current_path: path.clone(),
+            entries: Vec::new(),
             selected: 0,
             scroll_offset: 0,
+            show_hidden: false,
+            input: None,
+            input_text: crate::tui::input::LineEditor::new(),
+            recent: self.recent_workspaces(),
         });
+        self.browser_load(path, None);
         self.state.inline_prompt = InlinePrompt::Browse;
         self.state.pane_focus = PaneFocus::Left;
     }
 
-    /// ディレクトリの内容を読み取り（隠しファイル除外、ディレクトリ優先ソート）
-    pub fn read_directory(path: &Path) -> Vec<BrowserEntry> {
+    /// ディレクトリの内容を読み取り（show_hidden でなければ隠しファイル除外、ディレクトリ優先ソート）
+    pub fn read_directory(path: &Path, show_hidden: bool) -> Vec<BrowserEntry> {
         let mut entries = Vec::new();
         if let Ok(read_dir) = std::fs::read_dir(path) {
             for entry in read_dir.flatten() {
                 let name = entry.file_name().to_string_lossy().to_string();
-                if name.starts_with('.') {
+                if name.starts_with('.') && !show_hidden {
                     continue;
                 }
-                let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
-                entries.push(BrowserEntry { name, is_dir });
+                // ディレクトリへのシンボリックリンクもディレクトリとして扱う
+                let path = entry.path();
+                let is_dir = path.is_dir();
+                let is_repo = is_dir && crate::project::is_git_repo(&path);
+                entries.push(BrowserEntry { name, is_dir, is_repo, project: None });
             }
         }
         // ディレクトリ優先、名前順ソート
@@ -1212,53 +4126,82 @@ impl App {
         entries
     }
 
+    /// ブラウザで path を開く。select があればその名前のエントリを選ぶ。
+    /// 登録済みプロジェクトのディレクトリにはプロジェクト名を付ける
+    fn browser_load(&mut self, path: PathBuf, select: Option<&str>) {
+        let projects = self.project_store.list().unwrap_or_default();
+        let Some(bs) = &mut self.state.browser_state else {
+            return;
+        };
+        bs.entries = Self::read_directory(&path, bs.show_hidden);
+        for entry in bs.entries.iter_mut().filter(|e| e.is_dir) {
+            let entry_path = path.join(&entry.name);
+            entry.project = projects.iter().find(|p| Path::new(&p.path) == entry_path).map(|p| p.name.clone());
+        }
+        bs.current_path = path;
+        bs.selected = select.and_then(|name| bs.entries.iter().position(|e| e.name == name)).unwrap_or(0);
+        bs.scroll_offset = 0;
+    }
+
     /// ブラウザ: 選択中のディレクトリに入る
     pub fn browser_enter_dir(&mut self) {
-        let (new_path, is_dir) = {
-            let bs = match &self.state.browser_state {
-                Some(bs) => bs,
-                None => return,
-            };
-            let entry = match bs.entries.get(bs.selected) {
-                Some(e) => e,
-                None => return,
-            };
-            if !entry.is_dir {
-                return;
-            }
-            (bs.current_path.join(&entry.name), entry.is_dir)
-        };
-        if !is_dir {
+        let Some(bs) = &self.state.browser_state else {
             return;
-        }
-        let entries = Self::read_directory(&new_path);
-        if let Some(bs) = &mut self.state.browser_state {
-            bs.current_path = new_path;
-            bs.entries = entries;
-            bs.selected = 0;
-            bs.scroll_offset = 0;
+        };
+        match bs.entries.get(bs.selected) {
+            Some(entry) if entry.is_dir => {
+                let path = bs.current_path.join(&entry.name);
+                self.browser_load(path, None);
+            }
+            _ => {}
         }
     }
 
-    /// ブラウザ: 親ディレクトリへ移動
+    /// ブラウザ: 親ディレクトリへ移動 (今いたディレクトリを選んだ状態にする)
     pub fn browser_go_parent(&mut self) {
-        let parent = {
-            let bs = match &self.state.browser_state {
-                Some(bs) => bs,
-                None => return,
-            };
-            match bs.current_path.parent() {
-                Some(p) => p.to_path_buf(),
-                None => return,
-            }
+        let Some(bs) = &self.state.browser_state else {
+            return;
+        };
+        if let Some(parent) = bs.current_path.parent().map(Path::to_path_buf) {
+            let child = bs.current_path.file_name().map(|n| n.to_string_lossy().to_string());
+            self.browser_load(parent, child.as_deref());
+        }
+    }
+
+    /// ブラウザ: 隠しエントリの表示を切り替える (選択中のエントリは保つ)
+    pub fn browser_toggle_hidden(&mut self) {
+        let Some(bs) = &mut self.state.browser_state else {
+            return;
         };
-        let entries = Self::read_directory(&parent);
-        if let Some(bs) = &mut self.state.browser_state {
-            bs.current_path = parent;
-            bs.entries = entries;
-            bs.selected = 0;
-            bs.scroll_offset = 0;
+        bs.show_hidden = !bs.show_hidden;
+        let selected = bs.entries.get(bs.selected).map(|e| e.name.clone());
+        let path = bs.current_path.clone();
+        self.browser_load(path, selected.as_deref());
+    }
+
+    /// ブラウザ: 今のディレクトリに name のディレクトリを作って選ぶ
+    pub fn browser_make_dir(&mut self, name: &str) -> Result<String> {
+        let bs = self.state.browser_state.as_ref().ok_or_else(|| anyhow::anyhow!("No browser state"))?;
+        let name = name.trim();
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            anyhow::bail!("Invalid directory name '{}'", name);
+        }
+        let path = bs.current_path.join(name);
+        std::fs::create_dir(&path).with_context(|| format!("Failed to create directory: {:?}", path))?;
+        let current = bs.current_path.clone();
+        self.browser_load(current, Some(name));
+        Ok(format!("Created {}", path.display()))
+    }
+
+    /// ブラウザ: 入力したパスへ移動 (~/ と今のディレクトリからの相対パス可)
+    pub fn browser_go_to(&mut self, input: &str) -> Result<()> {
+        let bs = self.state.browser_state.as_ref().ok_or_else(|| anyhow::anyhow!("No browser state"))?;
+        let path = resolve_browser_path(&bs.current_path, input.trim());
+        if !path.is_dir() {
+            anyhow::bail!("Not a directory: {}", path.display());
         }
+        self.browser_load(path, None);
+        Ok(())
     }
 
     /// ブラウザ: 現在のディレクトリをワークスペースとして設定
@@ -1270,11 +4213,34 @@ impl App {
         };
         let project = crate::project::resolve_project(&self.project_store, &path_str)?;
         let msg = format!("Workspace set → {}", project.path);
-        self.state.current_project = Some(project);
+        self.set_workspace(project, self.state.workspace_filter);
+        self.browser_cancel();
+        Ok(msg)
+    }
+
+    /// ブラウザ: 最近のワークスペースの n 番目 (0 始まり) に切り替える
+    pub fn browser_select_recent(&mut self, n: usize) -> Result<String> {
+        let project = self
+            .state
+            .browser_state
+            .as_ref()
+            .and_then(|bs| bs.recent.get(n).cloned())
+            .ok_or_else(|| anyhow::anyhow!("No recent workspace {}", n + 1))?;
+        let msg = format!("Workspace set → {}", project.path);
+        self.set_workspace(project, self.state.workspace_filter);
         self.browser_cancel();
         Ok(msg)
     }
 
+    /// 最近のワークスペース (登録が残っているもの、新しい順)
+    pub fn recent_workspaces(&self) -> Vec<Project> {
+        self.state
+            .recent_workspaces
+            .iter()
+            .filter_map(|name| self.project_store.find_by_name(name).ok().flatten())
+            .collect()
+    }
+
     /// ブラウザ: キャンセル
     pub fn browser_cancel(&mut self) {
         self.state.browser_state = None;
@@ -1374,3 +4340,851 @@ fn deduplicate_name(base: &str, existing_names: &[String]) -> String {
     }
     format!("{}-{}", base, chrono::Utc::now().timestamp())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    fn test_app() -> (App, FakeTmux, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let tmux = FakeTmux::new();
+        let app = App::for_test(dir.path(), Box::new(tmux.clone()));
+        (app, tmux, dir)
+    }
+
+    #[test]
+    fn test_create_and_drop_pod() {
+        let (mut app, tmux, dir) = test_app();
+        let project = dir.path().to_string_lossy().to_string();
+        app.create_pod("fix-login", Some(&project), None, Some("Fix the login bug"), None).unwrap();
+
+        assert_eq!(tmux.sessions(), vec!["fix-login".to_string()]);
+        let pod = &app.state.pods[0];
+        assert_eq!(pod.members[0].tmux_pane, "%0");
+        assert_eq!(pod.last_prompt.as_deref(), Some("Fix the login bug"));
+        let sent = tmux.sent_to("%0");
+        assert_eq!(sent.first().map(String::as_str), Some("claude"));
+        assert!(sent.iter().any(|k| k == "Fix the login bug"));
+        assert!(app.create_pod("fix-login", Some(&project), None, None, None).is_err());
+
+        tmux.set_output("%0", "$ cargo test\ntest result: ok. 12 passed");
+        app.drop_pod("fix-login").unwrap();
+        assert!(tmux.sessions().is_empty());
+        assert!(app.state.pods.is_empty());
+        assert_eq!(app.trash.list().unwrap().len(), 1);
+
+        // restore では drop 時の画面を流してから Claude を起動する
+        app.restore_pod("fix-login").unwrap();
+        let pane = app.state.pods[0].members[0].tmux_pane.clone();
+        let sent = tmux.sent_to(&pane);
+        let replay = sent[0].strip_prefix("cat ").unwrap();
+        let (quoted, _) = replay.split_once("; rm -f ").unwrap();
+        let path = PathBuf::from(quoted.trim_matches('\''));
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(content.starts_with("$ cargo test\ntest result: ok. 12 passed\n"), "{}", content);
+        assert!(content.contains("restored from the trash"));
+        assert_eq!(sent[2], "claude");
+    }
+
+    #[test]
+    fn test_permission_flow() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        tmux.set_output("%0", "Bash command\n  rm -rf build\nDo you want to proceed?\n❯ 1. Yes\n  2. No");
+        app.refresh_pod_states();
+        assert_eq!(app.state.pods[0].status, PodStatus::Permission);
+
+        app.state.focus = Some(0);
+        app.approve_permission().unwrap();
+        assert_eq!(tmux.sent_to("%0").last().map(String::as_str), Some("y"));
+    }
+
+    #[test]
+    fn test_external_detector_runs_in_background() {
+        let (mut app, tmux, dir) = test_app();
+        app.config.detection.detector_command = Some("grep -q 'lint: 3 problems' && echo error".to_string());
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        tmux.set_output("%0", "lint: 3 problems\n  compiling…");
+
+        // 初回は結果がまだ無いので組み込み検出のまま
+        app.refresh_pod_states();
+        assert_eq!(app.state.pods[0].status, PodStatus::Working);
+        app.finish_jobs();
+        app.refresh_pod_states();
+        assert_eq!(app.state.pods[0].status, PodStatus::Error);
+    }
+
+    #[test]
+    fn test_budget_is_summed_in_background() {
+        let (mut app, _tmux, _dir) = test_app();
+        app.config.budget.daily_usd = Some(10.0);
+        app.check_budget();
+        assert!(app.budget_check_running);
+        // 集計中に呼ばれても重ねて走らせない
+        app.check_budget();
+        assert_eq!(app.running_jobs, 1);
+        app.finish_jobs();
+        assert!(!app.budget_check_running);
+
+        app.apply_budget(vec![(String::new(), "Today".to_string(), 9.0, 10.0)]);
+        assert_eq!(app.state.budget_warning.as_deref(), Some("Today $9.00 / $10.00"));
+        assert_eq!(app.budget_levels[""], BudgetLevel::Warning);
+    }
+
+    #[test]
+    fn test_session_gone_marks_dead() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        tmux.remove_session("api");
+        app.refresh_pod_states();
+        assert_eq!(app.state.pods[0].status, PodStatus::Dead);
+        assert!(app.state.pods[0].members.iter().all(|m| m.status == MemberStatus::Dead));
+    }
+
+    #[test]
+    fn test_bell_and_flash_on_permission() {
+        let (mut app, tmux, dir) = test_app();
+        app.config.notification.enabled = false;
+        app.config.notification.bell = true;
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        assert!(!app.take_bell());
+
+        tmux.set_output("%0", "Do you want to proceed?\n❯ 1. Yes\n  2. No");
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        assert!(app.take_bell());
+        assert!(!app.take_bell());
+        assert!(!app.flashing());
+
+        app.config.notification.flash = true;
+        tmux.set_output("%0", "❯ ");
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        tmux.set_output("%0", "Do you want to proceed?\n❯ 1. Yes\n  2. No");
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        assert!(app.flashing());
+    }
+
+    #[test]
+    fn test_respawn_agent_keeps_pane() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        app.state.pods[0].members[0].set_status(MemberStatus::Working);
+
+        let pane = app.respawn_agent("api", None, true).unwrap();
+        assert_eq!(pane, "%0");
+        assert!(tmux.sessions().contains(&"api".to_string()));
+        assert_eq!(tmux.respawned(), ["%0"]);
+        let sent = tmux.sent_to("%0");
+        assert_eq!(&sent[sent.len() - 2..], ["claude --continue", "Enter"]);
+        assert_eq!(app.state.pods[0].members[0].status, MemberStatus::Idle);
+        assert!(app.respawn_agent("api", Some("nobody"), false).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_reset_survives_restart() {
+        let (mut app, tmux, dir) = test_app();
+        app.config.notification.enabled = false;
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        app.set_auto_resume("api", Some(true)).unwrap();
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        let reset = Utc::now() - chrono::Duration::minutes(1);
+        tmux.set_output("%0", &format!("Claude AI usage limit reached|{}", reset.timestamp()));
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        assert_eq!(app.state.pods[0].rate_limit_reset.map(|at| at.timestamp()), Some(reset.timestamp()));
+
+        // 再起動しても保存したリセット時刻で再開する
+        let mut app = App::for_test(dir.path(), Box::new(tmux.clone()));
+        assert_eq!(app.state.pods[0].status, PodStatus::RateLimited);
+        app.resume_rate_limited();
+        let sent = tmux.sent_to("%0");
+        assert_eq!(&sent[sent.len() - 2..], [app.config.rate_limit.message.as_str(), "Enter"]);
+        assert_eq!(app.state.pods[0].rate_limit_reset, None);
+        assert_eq!(App::for_test(dir.path(), Box::new(tmux.clone())).state.pods[0].rate_limit_reset, None);
+    }
+
+    #[test]
+    fn test_mute_and_dnd_silence_bell() {
+        let (mut app, tmux, dir) = test_app();
+        app.config.notification.enabled = false;
+        app.config.notification.bell = true;
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        app.state.focus = Some(0);
+        app.toggle_mute().unwrap();
+        assert!(app.state.pods[0].is_muted(Utc::now()));
+
+        let permission = "Do you want to proceed?\n❯ 1. Yes\n  2. No";
+        tmux.set_output("%0", permission);
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        assert!(!app.take_bell());
+
+        app.toggle_mute().unwrap();
+        app.toggle_dnd();
+        tmux.set_output("%0", "❯ ");
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        tmux.set_output("%0", permission);
+        app.refresh_pod_states();
+        app.handle_status_changes();
+        assert!(!app.take_bell());
+        assert!(app.ui_state().dnd);
+    }
+
+    #[test]
+    fn test_chat_with_selected_member() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("team", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        let reviewer = tmux.add_pane("team");
+        app.add_member("team", &reviewer, Some("reviewer")).unwrap();
+        app.state.focus = Some(0);
+        app.state.selected_member = Some(0);
+        app.cycle_chat_member();
+        assert_eq!(app.state.selected_member, Some(1));
+
+        tmux.set_output(&reviewer, "❯ ");
+        app.state.chat_input.set("review the diff".to_string());
+        app.send_chat_message().unwrap();
+        assert_eq!(tmux.sent_to(&reviewer), ["review the diff", "Enter"]);
+
+        tmux.set_output(&reviewer, "❯ \n> review the diff\nLooks good");
+        app.refresh_chat_output();
+        tmux.set_output(&reviewer, "❯ \n> review the diff\nLooks good\nOne nit");
+        app.refresh_chat_output();
+        let history = &app.state.chat_history;
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].sender.as_str(), history[0].member.as_str()), ("you", "reviewer"));
+        assert_eq!(history[1].sender, "reviewer");
+        assert!(history[1].content.ends_with("One nit"));
+
+        // リードにはまだ送っていないので差分を拾わない
+        app.cycle_chat_member();
+        app.refresh_chat_output();
+        assert_eq!(app.state.chat_history.len(), 2);
+    }
+
+    #[test]
+    fn test_chat_history_persists_and_exports() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        app.state.focus = Some(0);
+        tmux.set_output("%0", "❯ ");
+        app.state.chat_input.set("summarize".to_string());
+        app.send_chat_message().unwrap();
+        tmux.set_output("%0", "❯ \n**Done**");
+        app.refresh_chat_output();
+
+        // 別の App (CLI) からも読める
+        let cli = App::for_test(dir.path(), Box::new(tmux.clone()));
+        assert_eq!(cli.state.chat_history.len(), 2);
+        let path = dir.path().join("out/transcript.md");
+        assert_eq!(cli.export_chat("api", &path).unwrap(), 2);
+        let markdown = std::fs::read_to_string(&path).unwrap();
+        assert!(markdown.contains("### you"));
+        assert!(markdown.contains("**Done**"));
+        assert!(cli.export_chat("web", &path).is_err());
+
+        app.export_focused_chat();
+        let exported = std::fs::read_dir(dir.path().join("transcripts")).unwrap().count();
+        assert_eq!(exported, 1);
+    }
+
+    /// api / web / docs を作り、api と docs を選択した App
+    fn app_with_selection() -> (App, FakeTmux, TempDir) {
+        let (mut app, tmux, dir) = test_app();
+        for name in ["api", "web", "docs"] {
+            app.create_pod(name, Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        }
+        for name in ["api", "docs"] {
+            app.state.focus = app.state.pods.iter().position(|p| p.name == name);
+            app.toggle_selection();
+        }
+        (app, tmux, dir)
+    }
+
+    fn pod_names(app: &App) -> Vec<&str> {
+        app.state.pods.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_bulk_drop_archive_and_forget() {
+        let (mut app, tmux, _dir) = app_with_selection();
+        // もう一度 Space で外れる
+        app.toggle_selection();
+        assert_eq!(app.selected_pod_names(), ["api"]);
+        app.toggle_selection();
+        assert_eq!(app.selected_pod_names(), ["api", "docs"]);
+
+        assert_eq!(app.drop_selected(), "2 pods dropped");
+        assert_eq!(pod_names(&app), ["web"]);
+        assert!(app.state.selected_pods.is_empty());
+        assert_eq!(tmux.sessions(), ["web"]);
+        assert_eq!(app.trash.list().unwrap().len(), 2);
+
+        let (mut app, tmux, _dir) = app_with_selection();
+        assert_eq!(app.archive_selected(), "2 pods archived (apiary restore brings them back)");
+        assert_eq!(pod_names(&app), ["web"]);
+        app.restore_pod("docs").unwrap();
+        assert_eq!(pod_names(&app), ["web", "docs"]);
+        assert_eq!(tmux.sessions(), ["docs", "web"]);
+
+        let (mut app, tmux, _dir) = app_with_selection();
+        // 選択した後に消えた Pod は数えない
+        app.forget_pod("docs").unwrap();
+        assert_eq!(app.forget_selected(), "1 pods forgotten");
+        assert_eq!(pod_names(&app), ["web"]);
+        // forget はセッションを残す
+        assert_eq!(tmux.sessions(), ["api", "docs", "web"]);
+    }
+
+    #[test]
+    fn test_forgotten_sessions_stay_out_of_auto_adopt() {
+        let (mut app, tmux, dir) = test_app();
+        for name in ["api", "web"] {
+            app.create_pod(name, Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        }
+        app.state.view_mode = ViewMode::Kanban;
+        app.save_ui_state().unwrap();
+        app.forget_pod("api").unwrap();
+        app.forget_pod("web").unwrap();
+
+        // 他の UI 状態はそのまま、再起動後も取り込まない
+        let saved = app.ui_state_store.load();
+        assert_eq!(saved.auto_adopt_ignored, ["api", "web"]);
+        assert_eq!(saved.view_mode, Some(ViewMode::Kanban));
+        tmux.remove_session("web");
+        let mut restarted = App::for_test(dir.path(), Box::new(tmux.clone()));
+        restarted.restore_ui_state(saved);
+        assert_eq!(restarted.sorted_auto_adopt_ignored(), ["api"]);
+        assert_eq!(restarted.ui_state().auto_adopt_ignored, ["api"]);
+    }
+
+    #[test]
+    fn test_group_commands() {
+        let (mut app, tmux, dir) = test_app();
+        for name in ["api", "web", "docs"] {
+            app.create_pod(name, Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        }
+        // 知らない Pod が混じっていれば誰も動かさない
+        let err = app.add_to_group("backend", &["api".to_string(), "nope".to_string()]).unwrap_err();
+        assert_eq!(err.to_string(), "Pod not found: nope");
+        assert!(app.pods_in_group("backend").is_empty());
+        let saved = App::for_test(dir.path(), Box::new(tmux.clone()));
+        assert!(saved.state.pods.iter().all(|p| p.group.as_deref() != Some("backend")));
+
+        assert_eq!(app.add_to_group("backend", &["api".to_string(), "web".to_string()]).unwrap(), 2);
+        assert_eq!(app.pods_in_group("backend"), ["api", "web"]);
+        app.set_group("docs", Some("frontend".to_string())).unwrap();
+
+        // 既存のグループへの rename は統合になるので断る
+        assert!(app.rename_group("backend", "frontend").is_err());
+        app.state.collapsed_groups.insert("backend".to_string());
+        assert_eq!(app.rename_group("backend", "server").unwrap(), 2);
+        assert_eq!(app.pods_in_group("server"), ["api", "web"]);
+        assert!(app.state.collapsed_groups.contains("server"));
+        assert!(app.rename_group("backend", "x").is_err());
+
+        assert_eq!(app.dissolve_group("frontend").unwrap(), 1);
+        assert_eq!(app.state.pods[2].group, None);
+        assert!(tmux.session_exists("docs"));
+
+        assert_eq!(app.drop_group("server").unwrap(), ["api", "web"]);
+        assert_eq!(pod_names(&app), ["docs"]);
+        assert_eq!(tmux.sessions(), ["docs"]);
+        assert!(app.drop_group("server").is_err());
+    }
+
+    #[test]
+    fn test_bulk_group_and_broadcast() {
+        let (mut app, tmux, _dir) = app_with_selection();
+        assert_eq!(app.move_selected_to_group(Some("backend".to_string())), "2 pods moved to 'backend'");
+        let groups = |app: &App| app.state.pods.iter().map(|p| p.group.clone()).collect::<Vec<_>>();
+        let before = groups(&app);
+        assert_eq!(before[0].as_deref(), Some("backend"));
+        assert_ne!(before[1].as_deref(), Some("backend"));
+        assert_eq!(before[2].as_deref(), Some("backend"));
+        assert!(app.state.selected_pods.is_empty());
+
+        for name in ["api", "web"] {
+            app.state.selected_pods.insert(name.to_string());
+        }
+        assert_eq!(app.move_selected_to_group(None), "2 pods ungrouped");
+        assert_eq!(groups(&app), [None, None, Some("backend".to_string())]);
+
+        for name in ["api", "web"] {
+            app.state.selected_pods.insert(name.to_string());
+        }
+        tmux.remove_session("web");
+        let message = app.broadcast_to_selected("git pull");
+        assert!(message.starts_with("1 pods messaged, 1 failed (web: "), "{}", message);
+        let sent = tmux.sent_to("%0");
+        assert_eq!(sent[sent.len() - 2..], ["git pull", "Enter"]);
+        assert!(tmux.sent_to("%2").iter().all(|k| k != "git pull"));
+    }
+
+    #[test]
+    fn test_recording_owns_the_pane_pipe() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        tmux.set_output("%0", "$ cargo test");
+        let cast = dir.path().join("api.cast");
+        let (pane, path) = app.start_recording("api", None, Some(&cast)).unwrap();
+        assert_eq!(pane, "%0");
+        assert_eq!(tmux.pane_pipe_owner("%0").as_deref(), Some("record"));
+        assert!(tmux.pipe_command("%0").unwrap().contains("record-sink"));
+        assert!(std::fs::read_to_string(&path).unwrap().contains("cargo test"));
+        assert!(app.start_recording("api", None, Some(&cast)).is_err());
+
+        app.stop_recording("api", None).unwrap();
+        assert_eq!(tmux.pane_pipe_owner("%0"), None);
+        assert!(app.stop_recording("api", None).is_err());
+    }
+
+    #[test]
+    fn test_watch_marks_error_until_acknowledged() {
+        let (mut app, tmux, dir) = test_app();
+        app.config = toml::from_str("[[watch]]\nname = \"sql\"\npattern = \"(?i)drop table\"\naction = \"error\"\n").unwrap();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        app.state.focus = Some(0);
+        tmux.set_output("%0", "DROP TABLE old;\n❯ ");
+        app.refresh_pod_states();
+        app.selective_refresh();
+        // 最初に取り込んだ画面は対象にしない
+        assert!(app.state.pods[0].members[0].alert.is_none());
+
+        tmux.set_output("%0", "DROP TABLE old;\nrunning: drop table users;\n❯ ");
+        app.state.pods[0].members[0].last_polled = None;
+        app.selective_refresh();
+        assert_eq!(app.state.pods[0].members[0].alert.as_deref(), Some("sql"));
+        assert_eq!(app.state.pods[0].status, PodStatus::Error);
+        assert!(app.state.status_message.as_deref().unwrap().contains("running: drop table users;"));
+
+        // hooks のイベントでも警告の Error は動かない
+        let since = app.state.pods[0].members[0].last_change;
+        std::fs::write(dir.path().join("hooks.jsonl"), "{\"event\":\"tool_start\",\"session\":\"api\"}\n").unwrap();
+        app.selective_refresh();
+        assert_eq!(app.state.pods[0].members[0].status, MemberStatus::Error);
+        assert_eq!(app.state.pods[0].members[0].last_change, since);
+
+        app.acknowledge_alerts();
+        app.selective_refresh();
+        assert_ne!(app.state.pods[0].status, PodStatus::Error);
+    }
+
+    #[test]
+    fn test_create_new_private_file_refuses_existing_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("apiary-exec.out");
+        create_new_private_file(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert!(create_new_private_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_redacts_output_before_storing() {
+        let (mut app, tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        tmux.set_output("%0", "$ env\nAWS_SECRET_ACCESS_KEY=wJalrXUtnFEMI/K7MDENG\n❯ ");
+        app.selective_refresh();
+        assert_eq!(app.state.pods[0].members[0].last_output, "$ env\nAWS_SECRET_ACCESS_KEY=[REDACTED]\n❯ ");
+
+        app.drop_pod("api").unwrap();
+        let trashed = app.trash.list().unwrap();
+        assert!(trashed[0].scrollback[0].contains("AWS_SECRET_ACCESS_KEY=[REDACTED]"));
+    }
+
+    #[test]
+    fn test_create_with_template_and_env() {
+        let (mut app, tmux, dir) = test_app();
+        app.config = toml::from_str("[templates.work]\nenv = { CLAUDE_CONFIG_DIR = \"/w\", FLAG = \"1\" }\n").unwrap();
+        let project = dir.path().to_string_lossy().to_string();
+        let options = CreateOptions {
+            template: Some("work".to_string()),
+            env: vec![("FLAG".to_string(), "2".to_string())],
+            ..Default::default()
+        };
+        app.create_pod_with("api", Some(&project), None, &options).unwrap();
+        let expected = BTreeMap::from([("CLAUDE_CONFIG_DIR".to_string(), "/w".to_string()), ("FLAG".to_string(), "2".to_string())]);
+        assert_eq!(tmux.session_env("api"), expected);
+        assert_eq!(app.state.pods[0].env, expected);
+
+        // restore しても同じ環境で起動し直す
+        app.drop_pod("api").unwrap();
+        app.restore_pod("api").unwrap();
+        assert_eq!(tmux.session_env("api"), expected);
+
+        let options = CreateOptions { template: Some("missing".to_string()), ..Default::default() };
+        assert!(app.create_pod_with("web", Some(&project), None, &options).is_err());
+    }
+
+    #[test]
+    fn test_on_done_from_template() {
+        let (mut app, _tmux, dir) = test_app();
+        app.config =
+            toml::from_str("[templates.tested]\non_done = { run = \"cargo test\" }\n\n[templates.committed]\non_done = \"commit\"\n").unwrap();
+        let project = dir.path().to_string_lossy().to_string();
+        for (name, template) in [("api", "tested"), ("web", "committed")] {
+            let options = CreateOptions { template: Some(template.to_string()), ..Default::default() };
+            app.create_pod_with(name, Some(&project), None, &options).unwrap();
+        }
+        assert_eq!(app.state.pods[0].on_done, Some(OnDone::Run("cargo test".to_string())));
+        assert_eq!(app.state.pods[1].on_done, Some(OnDone::Commit));
+
+        // create のフラグ (set_on_done) がテンプレートより優先される
+        app.set_on_done("web", Some(OnDone::Prompt("Summarize".to_string()))).unwrap();
+        assert_eq!(app.state.pods[1].on_done, Some(OnDone::Prompt("Summarize".to_string())));
+    }
+
+    #[test]
+    fn test_template_settings_passed_to_claude() {
+        let (mut app, tmux, dir) = test_app();
+        app.config = toml::from_str("[templates.locked]\npermission_mode = \"plan\"\ndenied_tools = [\"WebFetch\"]\n").unwrap();
+        let options = CreateOptions { template: Some("locked".to_string()), ..Default::default() };
+        app.create_pod_with("risky", Some(&dir.path().to_string_lossy()), None, &options).unwrap();
+
+        let settings = dir.path().join("settings").join("risky.json");
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(written["permissions"]["defaultMode"], "plan");
+        assert_eq!(app.state.pods[0].settings.as_deref(), Some(settings.as_path()));
+        assert_eq!(app.state.pods[0].template.as_deref(), Some("locked"));
+        let command = format!("claude --settings {}", crate::tmux::shell_quote(&settings.to_string_lossy()));
+        assert!(tmux.sent().iter().any(|(_, keys)| *keys == command));
+
+        // drop で消え、restore で戻る
+        app.drop_pod("risky").unwrap();
+        assert!(!settings.exists());
+        // 書き戻せなければセッションを残さず、ゴミ箱にも戻す
+        std::fs::remove_dir(dir.path().join("settings")).unwrap();
+        std::fs::write(dir.path().join("settings"), "").unwrap();
+        assert!(app.restore_pod("risky").is_err());
+        assert!(tmux.sessions().is_empty());
+        assert_eq!(app.trash.list().unwrap().len(), 1);
+        std::fs::remove_file(dir.path().join("settings")).unwrap();
+        app.restore_pod("risky").unwrap();
+        assert_eq!(std::fs::read_to_string(&settings).unwrap(), serde_json::to_string_pretty(&written).unwrap());
+        // ゴミ箱から消えれば写しも残らない
+        app.drop_pod("risky").unwrap();
+        app.trash.purge(chrono::Duration::zero()).unwrap();
+        assert!(std::fs::read_dir(dir.path().join("trash")).unwrap().next().is_none());
+
+        // ファイル名に使うので、ディレクトリを抜ける名前は作らせない
+        for name in ["../escape", "a/b", ".."] {
+            assert!(app.create_pod_with(name, Some(&dir.path().to_string_lossy()), None, &options).is_err());
+        }
+        assert!(!dir.path().join("escape.json").exists());
+    }
+
+    #[test]
+    fn test_model_from_template_and_switch() {
+        let (mut app, tmux, dir) = test_app();
+        app.config = toml::from_str("[templates.big]\nmodel = \"opus\"\n").unwrap();
+        let options = CreateOptions { template: Some("big".to_string()), ..Default::default() };
+        app.create_pod_with("api", Some(&dir.path().to_string_lossy()), None, &options).unwrap();
+        assert_eq!(app.state.pods[0].model.as_deref(), Some("opus"));
+        let command = format!("claude --model {}", crate::tmux::shell_quote("opus"));
+        assert!(tmux.sent().iter().any(|(_, keys)| *keys == command));
+
+        app.switch_model("api", "sonnet").unwrap();
+        assert!(tmux.sent().iter().any(|(pane, keys)| pane == "%0" && keys == "/model sonnet"));
+        assert_eq!(app.state.pods[0].model.as_deref(), Some("sonnet"));
+        assert!(app.switch_model("api", "opus; rm -rf").is_err());
+        assert!(app.switch_model("missing", "opus").is_err());
+    }
+
+    #[test]
+    fn test_create_with_project_defaults() {
+        let (mut app, tmux, dir) = test_app();
+        app.config = toml::from_str("[templates.work]\nenv = { B = \"2\" }\nmodel = \"opus\"\n").unwrap();
+        let repo = dir.path().join("api");
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        app.project_store.register(&Project::new("api", repo.to_string_lossy())).unwrap();
+        let defaults = crate::project::ProjectDefaults {
+            group: Some("backend".to_string()),
+            template: Some("work".to_string()),
+            model: Some("sonnet".to_string()),
+            env: BTreeMap::from([("A".to_string(), "1".to_string()), ("B".to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+        app.project_store.set_defaults("api", defaults.clone()).unwrap();
+
+        // プロジェクトの既定値 < テンプレート
+        app.create_pod_with("p1", Some("api"), None, &CreateOptions::default()).unwrap();
+        let pod = &app.state.pods[0];
+        assert_eq!(pod.group.as_deref(), Some("backend"));
+        assert_eq!(pod.template.as_deref(), Some("work"));
+        assert_eq!(pod.model.as_deref(), Some("opus"));
+        assert_eq!(tmux.session_env("p1"), BTreeMap::from([("A".to_string(), "1".to_string()), ("B".to_string(), "2".to_string())]));
+
+        // 明示した指定がいちばん強い
+        let options = CreateOptions { model: Some("haiku".to_string()), env: vec![("A".to_string(), "3".to_string())], ..Default::default() };
+        app.create_pod_with("p2", Some("api"), Some("misc"), &options).unwrap();
+        let pod = &app.state.pods[1];
+        assert_eq!((pod.group.as_deref(), pod.model.as_deref()), (Some("misc"), Some("haiku")));
+        assert_eq!(tmux.session_env("p2")["A"], "3");
+
+        // worktree = per-pod なら Pod ごとの worktree で動く
+        let defaults = crate::project::ProjectDefaults { worktree: WorktreePolicy::PerPod, ..defaults };
+        app.project_store.set_defaults("api", defaults).unwrap();
+        app.create_pod_with("p3", Some("api"), None, &CreateOptions::default()).unwrap();
+        let pod = &app.state.pods[2];
+        assert_eq!((pod.project.as_deref(), pod.group.as_deref()), (Some("api-p3"), Some("backend")));
+        let worktree = app.project_store.find_by_name("api-p3").unwrap().unwrap();
+        assert_eq!(worktree.worktree_of.as_deref(), Some("api"));
+        assert_eq!(Path::new(&worktree.path), dir.path().join("api-p3"));
+        assert_eq!(crate::project::current_branch(&worktree.path).as_deref(), Some("p3"));
+
+        // セッションを作れなければ worktree と設定ファイルを残さない
+        tmux.add_session("p4", None);
+        assert!(app.create_pod_with("p4", Some("api"), None, &CreateOptions::default()).is_err());
+        assert!(app.project_store.find_by_name("api-p4").unwrap().is_none());
+        assert!(!dir.path().join("api-p4").exists());
+        assert!(!app.store.dir().join("settings").join("p4.json").exists());
+        assert_eq!(app.state.pods.len(), 3);
+    }
+
+    #[test]
+    fn test_browser_hidden_mkdir_and_go_to() {
+        let (mut app, _tmux, dir) = test_app();
+        let root = dir.path().join("browse");
+        std::fs::create_dir_all(root.join("alpha")).unwrap();
+        std::fs::create_dir_all(root.join("alps/deep")).unwrap();
+        std::fs::create_dir_all(root.join(".config")).unwrap();
+        app.open_browser(Some(&root.to_string_lossy()));
+        let names = |app: &App| -> Vec<String> {
+            app.state.browser_state.as_ref().unwrap().entries.iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(&app), ["alpha", "alps"]);
+
+        app.state.browser_state.as_mut().unwrap().selected = 1;
+        app.browser_toggle_hidden();
+        assert_eq!(names(&app), [".config", "alpha", "alps"]);
+        assert_eq!(app.state.browser_state.as_ref().unwrap().selected, 2);
+        app.browser_toggle_hidden();
+
+        app.browser_make_dir("beta").unwrap();
+        assert!(root.join("beta").is_dir());
+        assert_eq!(names(&app), ["alpha", "alps", "beta"]);
+        assert_eq!(app.state.browser_state.as_ref().unwrap().selected, 2);
+        assert!(app.browser_make_dir("beta").is_err());
+        assert!(app.browser_make_dir("a/b").is_err());
+
+        assert_eq!(complete_dir_path(&root, "al").as_deref(), Some("alp"));
+        assert_eq!(complete_dir_path(&root, "alp"), None);
+        assert_eq!(complete_dir_path(&root, "alph").as_deref(), Some("alpha/"));
+        assert_eq!(complete_dir_path(&root, "alps/d").as_deref(), Some("alps/deep/"));
+        assert_eq!(complete_dir_path(&root, ".c").as_deref(), Some(".config/"));
+        assert_eq!(complete_dir_path(&root, "zz"), None);
+
+        app.browser_go_to("alps/deep").unwrap();
+        assert_eq!(app.state.browser_state.as_ref().unwrap().current_path, root.join("alps/deep"));
+        app.browser_go_parent();
+        assert_eq!(app.state.browser_state.as_ref().unwrap().current_path, root.join("alps"));
+        assert!(app.browser_go_to("missing").is_err());
+    }
+
+    #[test]
+    fn test_reap_stale_pods() {
+        let (mut app, tmux, dir) = test_app();
+        let project = dir.path().to_string_lossy().to_string();
+        for name in ["stale", "fresh", "gone"] {
+            app.create_pod(name, Some(&project), None, None, None).unwrap();
+        }
+        let long_ago = Utc::now() - chrono::Duration::days(10);
+        for pod in &mut app.state.pods {
+            pod.status = PodStatus::Done;
+            pod.created_at = long_ago;
+            pod.last_input = None;
+            for member in &mut pod.members {
+                member.status = MemberStatus::Done;
+                member.last_change = long_ago;
+            }
+        }
+        app.state.pods[1].last_attached = Some(Utc::now());
+        app.state.pods[2].status = PodStatus::Dead;
+        app.config.reaper.done_after_days = 3;
+        app.config.reaper.dead_after_hours = 1;
+
+        // 猶予中は片付けず、通知だけ
+        assert!(app.reap_stale_pods().is_empty());
+        assert_eq!(app.reap_pending.len(), 2);
+        assert!(app.state.status_message.as_deref().is_some_and(|m| m.starts_with("stale, gone will be archived")));
+
+        // 猶予中に触られた Pod は外れる
+        app.state.pods[0].touch_input();
+        for deadline in app.reap_pending.values_mut() {
+            *deadline = Utc::now() - chrono::Duration::minutes(1);
+        }
+        assert_eq!(app.reap_stale_pods(), vec!["gone"]);
+        assert!(app.reap_pending.is_empty());
+        assert_eq!(app.trash.list().unwrap().len(), 1);
+
+        // 猶予なし + drop はゴミ箱にも残さない
+        app.config.reaper.grace_minutes = 0;
+        app.config.reaper.action = ReapAction::Drop;
+        app.state.pods[0].last_input = Some(long_ago);
+        assert_eq!(app.reap_stale_pods(), vec!["stale"]);
+        assert_eq!(tmux.sessions(), vec!["fresh".to_string()]);
+        assert_eq!(app.trash.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_resurrect_lost_pods() {
+        let (mut app, tmux, dir) = test_app();
+        let project = dir.path().to_string_lossy().to_string();
+        app.create_pod("api", Some(&project), None, None, None).unwrap();
+        app.create_pod("web", Some(&project), None, None, None).unwrap();
+        app.state.pods[1].env.insert("A".to_string(), "1".to_string());
+        app.save().unwrap();
+
+        // tmux サーバーが止まった (セッションが全部無くなった)
+        tmux.remove_session("api");
+        tmux.remove_session("web");
+        let mut app = App::for_test(dir.path(), Box::new(tmux.clone()));
+        assert!(app.state.pods.iter().all(|p| p.status == PodStatus::Dead && p.server_lost));
+        assert!(app.gc(true).unwrap().dead_pods.is_empty());
+        assert!(app.resurrect_pod("api", false).is_ok());
+        assert!(app.resurrect_pod("api", false).unwrap_err().to_string().contains("is not dead"));
+
+        let results = app.resurrect_lost_pods(true);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].1.is_ok());
+        assert_eq!(tmux.sessions(), vec!["api".to_string(), "web".to_string()]);
+        let web = app.state.pods.iter().find(|p| p.name == "web").unwrap();
+        assert!(!web.server_lost);
+        assert_eq!(web.status, PodStatus::Idle);
+        assert!(tmux.sent_to(&web.members[0].tmux_pane).iter().any(|k| k.ends_with("--continue")));
+        assert_eq!(tmux.session_env("web").get("A").map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn test_exec_in_split_timeout_leaves_no_files() {
+        let (mut app, _tmux, dir) = test_app();
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        // FakeTmux はコマンドを実行しないので終了コードは書かれない
+        let err = app.exec_in_split("api", "true", true, Some(std::time::Duration::ZERO)).unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+        let prefix = format!("apiary-exec-{}-", std::process::id());
+        let leftovers: Vec<_> = std::fs::read_dir(pty_stream_dir()).unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+    }
+
+    #[test]
+    fn test_resurrect_in_background() {
+        let (mut app, tmux, dir) = test_app();
+        let project = dir.path().to_string_lossy().to_string();
+        app.create_pod("api", Some(&project), None, None, None).unwrap();
+        app.create_pod("web", Some(&project), None, None, None).unwrap();
+        tmux.remove_session("api");
+        tmux.remove_session("web");
+        let mut app = App::for_test(dir.path(), Box::new(tmux.clone()));
+        assert_eq!(app.lost_pod_names(), vec!["api", "web"]);
+
+        app.resurrect_in_background(app.lost_pod_names(), false).unwrap();
+        assert_eq!(app.state.status_message.as_deref(), Some("Resurrecting api, web..."));
+        app.finish_jobs();
+        assert_eq!(app.state.status_message.as_deref(), Some("Resurrected api, web"));
+        assert_eq!(tmux.sessions(), vec!["api".to_string(), "web".to_string()]);
+        assert!(app.state.pods.iter().all(|p| p.status == PodStatus::Idle));
+        assert!(app.resurrect_in_background(vec!["api".to_string()], false).unwrap_err().to_string().contains("is not dead"));
+    }
+
+    #[test]
+    fn test_resurrect_drops_the_session_when_saving_fails() {
+        let (mut app, tmux, dir) = test_app();
+        let project = dir.path().to_string_lossy().to_string();
+        app.create_pod("api", Some(&project), None, None, None).unwrap();
+        tmux.remove_session("api");
+        let mut app = App::for_test(dir.path(), Box::new(tmux.clone()));
+
+        // pods.json を書けなくする
+        std::fs::remove_file(dir.path().join("pods.json")).unwrap();
+        std::fs::create_dir(dir.path().join("pods.json")).unwrap();
+        assert!(app.resurrect_pod("api", false).is_err());
+        assert!(tmux.sessions().is_empty());
+        assert_eq!(app.state.pods[0].status, PodStatus::Dead);
+    }
+
+    #[test]
+    fn test_background_jobs_apply_on_poll() {
+        let (mut app, _tmux, _dir) = test_app();
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        app.spawn_job(move || {
+            let _ = wait.recv();
+            Box::new(|app: &mut App| app.state.status_message = Some("stopped".to_string()))
+        });
+        app.poll_jobs();
+        assert_eq!(app.state.status_message, None);
+        release.send(()).unwrap();
+        app.finish_jobs();
+        assert_eq!(app.state.status_message.as_deref(), Some("stopped"));
+        assert_eq!(app.running_jobs, 0);
+    }
+
+    #[test]
+    fn test_pod_sessions_are_marked_for_tmux_integration() {
+        let (mut app, tmux, dir) = test_app();
+        let option = crate::tmux::integration::POD_OPTION;
+        app.create_pod("api", Some(&dir.path().to_string_lossy()), None, None, None).unwrap();
+        assert_eq!(tmux.session_option("api", option).as_deref(), Some("api"));
+
+        tmux.add_session("scratch", None);
+        app.adopt_session("scratch", Some("notes"), None).unwrap();
+        assert_eq!(tmux.session_option("scratch", option).as_deref(), Some("notes"));
+        app.forget_pod("notes").unwrap();
+        assert_eq!(tmux.session_option("scratch", option), None);
+    }
+
+    #[test]
+    fn test_shared_directory_uses_hook_sessions_only() {
+        let (mut app, _tmux, dir) = test_app();
+        let project = dir.path().to_string_lossy().to_string();
+        app.create_pod("api", Some(&project), None, None, None).unwrap();
+        app.create_pod("web", Some(&project), None, None, None).unwrap();
+        for pod in &mut app.state.pods {
+            pod.session_id = Some("latest-in-dir".to_string());
+        }
+        app.refresh_todos();
+        assert!(app.state.pods.iter().all(|p| p.session_id.is_none()));
+
+        let pane = app.state.pods[1].members[0].tmux_pane.clone();
+        std::fs::write(
+            dir.path().join("hooks.jsonl"),
+            format!("{{\"event\":\"stop\",\"pane\":\"{}\",\"session_id\":\"s-web\"}}\n", pane),
+        )
+        .unwrap();
+        app.selective_refresh();
+        app.refresh_todos();
+        assert_eq!(app.state.pods[0].session_id, None);
+        assert_eq!(app.state.pods[1].session_id.as_deref(), Some("s-web"));
+    }
+}
@@ -1,12 +1,25 @@
-use crate::pod::{InlinePrompt, Mode, PaneFocus};
+use crate::config::AttachMode;
+use crate::pod::{parse_tags, short_model_name, BrowserInput, InlinePrompt, Mode, PaneFocus, ViewMode};
 use crate::tui::app::{App, Direction, generate_pod_name};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::tui::palette::{Palette, PaletteAction};
+use crate::tui::switcher::PodSwitcher;
+use crate::tui::workspaces::WorkspaceSwitcher;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+
+/// Chat 履歴を PageUp / PageDown で動かす行数
+const CHAT_PAGE: usize = 10;
+/// マウスホイール 1 目盛りで動かす行数
+const CHAT_WHEEL: isize = 3;
 
 pub enum Action {
     None,
     Quit,
     Render,
     AttachTmux(String),
+    /// tmux の display-popup の中でアタッチ (tmux 外なら AttachTmux と同じ)
+    AttachPopup(String),
+    /// TUI を一時停止して左ペイン入力を $EDITOR で編集
+    EditInput,
 }
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) -> Action {
@@ -18,6 +31,41 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) -> Action {
         }
     }
 
+    if app.state.palette.is_some() {
+        return handle_palette_keys(app, key);
+    }
+    if app.state.switcher.is_some() {
+        return handle_switcher_keys(app, key);
+    }
+    if app.state.workspace_switcher.is_some() {
+        return handle_workspace_switcher_keys(app, key);
+    }
+    if app.state.adopt_picker.is_some() {
+        return handle_adopt_picker_keys(app, key);
+    }
+
+    // Ctrl+P: コマンドパレット (Home でプロンプト入力中でなければ)
+    if app.state.mode == Mode::Home
+        && app.state.inline_prompt == InlinePrompt::None
+        && key.modifiers.contains(KeyModifiers::CONTROL)
+        && key.code == KeyCode::Char('p')
+    {
+        app.state.palette = Some(Palette::default());
+        app.state.completion = None;
+        return Action::Render;
+    }
+
+    // Ctrl+F: Pod スイッチャー
+    if app.state.mode == Mode::Home
+        && app.state.inline_prompt == InlinePrompt::None
+        && key.modifiers.contains(KeyModifiers::CONTROL)
+        && key.code == KeyCode::Char('f')
+    {
+        app.state.switcher = Some(PodSwitcher::default());
+        app.state.completion = None;
+        return Action::Render;
+    }
+
     // ? キーは全モードで Help トグル (ただし Chat/Home の入力モード中/Detail パススルー中は除く)
     if key.code == KeyCode::Char('?') {
         match app.state.mode {
@@ -50,7 +98,50 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) -> Action {
     }
 }
 
+/// マウスイベント。キャプチャは Chat モードの間だけ有効で、ホイールで履歴をスクロールする
+pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Action {
+    if app.state.mode != Mode::Chat || app.state.palette.is_some() || app.state.switcher.is_some() || app.state.workspace_switcher.is_some()
+        || app.state.adopt_picker.is_some()
+    {
+        return Action::None;
+    }
+    match mouse.kind {
+        MouseEventKind::ScrollUp => app.scroll_chat(CHAT_WHEEL),
+        MouseEventKind::ScrollDown => app.scroll_chat(-CHAT_WHEEL),
+        _ => return Action::None,
+    }
+    Action::Render
+}
+
 pub fn handle_paste_event(app: &mut App, text: &str) {
+    if let Some(palette) = app.state.palette.as_mut() {
+        palette.query.insert_str(text);
+        palette.selected = 0;
+        return;
+    }
+    if let Some(switcher) = app.state.switcher.as_mut() {
+        switcher.query.insert_str(text);
+        switcher.selected = 0;
+        switcher.list_mode = false;
+        return;
+    }
+    if let Some(switcher) = app.state.workspace_switcher.as_mut() {
+        switcher.query.insert_str(text);
+        switcher.selected = 0;
+        return;
+    }
+    if let Some(picker) = app.state.adopt_picker.as_mut() {
+        picker.query.insert_str(text);
+        picker.selected = 0;
+        app.refresh_adopt_preview();
+        return;
+    }
+
+    if let Some(bs) = app.state.browser_state.as_mut().filter(|bs| bs.input.is_some()) {
+        bs.input_text.insert_str(text);
+        return;
+    }
+
     match app.state.mode {
         Mode::Home => {
             if app.state.inline_prompt == InlinePrompt::None {
@@ -59,14 +150,28 @@ pub fn handle_paste_event(app: &mut App, text: &str) {
                     app.state.status_message = None;
                 }
             }
-            if app.state.inline_prompt == InlinePrompt::None
-                || matches!(app.state.inline_prompt, InlinePrompt::AdoptSession)
-            {
-                app.state.inline_input.push_str(text);
+            if matches!(
+                app.state.inline_prompt,
+                InlinePrompt::None
+                    | InlinePrompt::EditTags(_)
+                    | InlinePrompt::TagFilter
+                    | InlinePrompt::EditNote(_)
+                    | InlinePrompt::Broadcast
+                    | InlinePrompt::MoveToGroup
+                    | InlinePrompt::MovePod(_)
+                    | InlinePrompt::AddMember(_)
+                    | InlinePrompt::SplitMember(_)
+                    | InlinePrompt::RenameMember(_)
+                    | InlinePrompt::SwitchModel(_)
+            ) {
+                app.state.inline_input.insert_str(text);
+            }
+            if app.state.inline_prompt == InlinePrompt::None {
+                app.update_completion();
             }
         }
         Mode::Chat => {
-            app.state.chat_input.push_str(text);
+            app.state.chat_input.insert_str(text);
         }
         Mode::Detail => {
             if let Err(e) = app.forward_paste_to_pane(text) {
@@ -77,6 +182,178 @@ pub fn handle_paste_event(app: &mut App, text: &str) {
     }
 }
 
+/// コマンドパレットのキー処理
+fn handle_palette_keys(app: &mut App, key: KeyEvent) -> Action {
+    let Some(palette) = app.state.palette.as_mut() else {
+        return Action::None;
+    };
+    match key.code {
+        KeyCode::Esc => app.state.palette = None,
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => app.state.palette = None,
+        KeyCode::Down | KeyCode::Tab => palette.select_next(),
+        KeyCode::Up | KeyCode::BackTab => palette.select_prev(),
+        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => palette.select_next(),
+        KeyCode::Enter => {
+            let selected = palette.selected_entry();
+            app.state.palette = None;
+            if let Some(entry) = selected {
+                return run_palette_action(app, entry.action);
+            }
+        }
+        _ => {
+            if !palette.query.handle_key(&key) {
+                return Action::None;
+            }
+            palette.selected = 0;
+        }
+    }
+    Action::Render
+}
+
+/// Pod スイッチャーのキー処理
+fn handle_switcher_keys(app: &mut App, key: KeyEvent) -> Action {
+    let Some(switcher) = app.state.switcher.as_mut() else {
+        return Action::None;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let pods = &app.state.pods;
+    match key.code {
+        KeyCode::Esc => app.state.switcher = None,
+        KeyCode::Char('f') if ctrl => app.state.switcher = None,
+        KeyCode::Tab => switcher.list_mode = !switcher.list_mode,
+        KeyCode::Down => switcher.select_next(pods),
+        KeyCode::Up => switcher.select_prev(pods),
+        KeyCode::Char('n') if ctrl => switcher.select_next(pods),
+        KeyCode::Char('p') if ctrl => switcher.select_prev(pods),
+        KeyCode::Char('j') if switcher.list_mode => switcher.select_next(pods),
+        KeyCode::Char('k') if switcher.list_mode => switcher.select_prev(pods),
+        KeyCode::Enter => {
+            if let Some(idx) = switcher.selected_pod(pods) {
+                app.state.switcher = None;
+                app.focus_pod(idx);
+            }
+        }
+        KeyCode::Char('t') if ctrl || switcher.list_mode => {
+            if let Some(idx) = switcher.selected_pod(pods) {
+                app.state.switcher = None;
+                app.focus_pod(idx);
+                return attach_action(app, app.state.pods[idx].tmux_session.clone());
+            }
+        }
+        _ => {
+            // リスト操作中に文字を打ったらクエリ入力に戻る
+            switcher.list_mode = false;
+            if !switcher.query.handle_key(&key) {
+                return Action::None;
+            }
+            switcher.selected = 0;
+        }
+    }
+    Action::Render
+}
+
+/// ワークスペーススイッチャーのキー処理。Enter で切り替え、Ctrl+F で切り替えてグリッドを絞る
+fn handle_workspace_switcher_keys(app: &mut App, key: KeyEvent) -> Action {
+    let Some(switcher) = app.state.workspace_switcher.as_mut() else {
+        return Action::None;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.state.workspace_switcher = None,
+        KeyCode::Down => switcher.select_next(),
+        KeyCode::Up => switcher.select_prev(),
+        KeyCode::Char('n') if ctrl => switcher.select_next(),
+        KeyCode::Char('p') if ctrl => switcher.select_prev(),
+        KeyCode::Enter => switch_workspace(app, false),
+        KeyCode::Char('f') if ctrl => switch_workspace(app, true),
+        _ => {
+            if !switcher.query.handle_key(&key) {
+                return Action::None;
+            }
+            switcher.selected = 0;
+        }
+    }
+    Action::Render
+}
+
+/// `t` のアタッチ (ui.attach に従う)
+fn attach_action(app: &App, session: String) -> Action {
+    match app.config.ui.attach {
+        AttachMode::Switch => Action::AttachTmux(session),
+        AttachMode::Popup => Action::AttachPopup(session),
+    }
+}
+
+/// スイッチャーで選んだプロジェクトをワークスペースにして閉じる
+fn switch_workspace(app: &mut App, filter: bool) {
+    let Some(project) = app.state.workspace_switcher.as_ref().and_then(|s| s.selected_project().cloned()) else {
+        return;
+    };
+    app.state.workspace_switcher = None;
+    app.state.status_message = Some(if filter {
+        format!("Workspace set → {} (showing its pods only)", project.path)
+    } else {
+        format!("Workspace set → {}", project.path)
+    });
+    app.set_workspace(project, filter);
+}
+
+/// セッションピッカーのキー処理。Enter で選んだセッションを Pod として取り込む
+fn handle_adopt_picker_keys(app: &mut App, key: KeyEvent) -> Action {
+    let Some(picker) = app.state.adopt_picker.as_mut() else {
+        return Action::None;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.state.adopt_picker = None,
+        KeyCode::Down => picker.select_next(),
+        KeyCode::Up => picker.select_prev(),
+        KeyCode::Char('n') if ctrl => picker.select_next(),
+        KeyCode::Char('p') if ctrl => picker.select_prev(),
+        KeyCode::Enter => {
+            app.state.status_message = match app.adopt_picked() {
+                Ok(Some(session)) => Some(format!("Session '{}' adopted", session)),
+                Ok(None) => None,
+                Err(e) => Some(format!("Error: {}", e)),
+            };
+        }
+        _ => {
+            if !picker.query.handle_key(&key) {
+                return Action::None;
+            }
+            picker.selected = 0;
+        }
+    }
+    app.refresh_adopt_preview();
+    Action::Render
+}
+
+/// パレットで選んだアクションを実行
+fn run_palette_action(app: &mut App, action: PaletteAction) -> Action {
+    match action {
+        PaletteAction::Key(code) => {
+            // 右ペインで該当キーを押したのと同じ扱い
+            app.state.pane_focus = PaneFocus::Right;
+            handle_key_event(app, KeyEvent::new(code, KeyModifiers::NONE))
+        }
+        PaletteAction::Input(text) => {
+            app.state.pane_focus = PaneFocus::Left;
+            app.state.inline_input.set(text);
+            app.state.status_message = None;
+            app.update_completion();
+            Action::Render
+        }
+        PaletteAction::Command(cmd) => {
+            app.state.status_message = match app.execute_command(cmd) {
+                Ok(msg) if msg.is_empty() => None,
+                Ok(msg) => Some(msg),
+                Err(e) => Some(format!("Error: {}", e)),
+            };
+            Action::Render
+        }
+    }
+}
+
 fn handle_home_keys(app: &mut App, key: KeyEvent) -> Action {
     // インラインプロンプト中 (drop 確認, adopt)
     if app.state.inline_prompt != InlinePrompt::None {
@@ -89,14 +366,32 @@ fn handle_home_keys(app: &mut App, key: KeyEvent) -> Action {
     }
 }
 
+/// 修飾なしの 1-9 キーならその数字
+fn quick_digit(key: &KeyEvent) -> Option<usize> {
+    match key.code {
+        KeyCode::Char(c @ '1'..='9') if key.modifiers.is_empty() => c.to_digit(10).map(|d| d as usize),
+        _ => None,
+    }
+}
+
 /// 右ペインフォーカス時: Pod ナビゲーション + ショートカット
 fn handle_home_right_keys(app: &mut App, key: KeyEvent) -> Action {
+    // g の次の数字でグループへジャンプ (数字以外なら g を取り消して通常処理)
+    if std::mem::take(&mut app.state.pending_group_jump) {
+        app.state.status_message = None;
+        if let Some(n) = quick_digit(&key) {
+            app.jump_to_group(n);
+            return Action::Render;
+        }
+    }
+
     match key.code {
         KeyCode::Char('q') => Action::Quit,
         KeyCode::Tab | KeyCode::Char('n') => {
             // 左ペインにフォーカス切り替え
             app.state.pane_focus = PaneFocus::Left;
             app.state.inline_input.clear();
+            app.state.completion = None;
             Action::Render
         }
         KeyCode::Left => {
@@ -131,8 +426,115 @@ fn handle_home_right_keys(app: &mut App, key: KeyEvent) -> Action {
             app.move_focus(Direction::Down);
             Action::Render
         }
+        KeyCode::Enter if app.state.effective_view_mode() == ViewMode::Grid && app.focused_group_collapsed() => {
+            // グリッドで折りたたまれたグループのヘッダー上 → 展開して要対応 Pod へ
+            // (List / Kanban では折りたたみが見えないので、そのまま詳細を開く)
+            if let Err(e) = app.open_collapsed_group() {
+                app.state.status_message = Some(format!("Error: {}", e));
+            }
+            Action::Render
+        }
+        KeyCode::Char(' ') => {
+            // 一括操作用に選択 / 解除
+            app.toggle_selection();
+            Action::Render
+        }
+        KeyCode::Esc if !app.state.selected_pods.is_empty() => {
+            app.state.selected_pods.clear();
+            Action::Render
+        }
+        KeyCode::Char('a') if !app.state.selected_pods.is_empty() => {
+            // 選択中の Pod をアーカイブ (ゴミ箱へ。restore で戻せる)
+            app.state.status_message = Some(app.archive_selected());
+            Action::Render
+        }
+        KeyCode::Char('d') | KeyCode::Char('X') | KeyCode::Char('b') | KeyCode::Char('g') | KeyCode::Char('M')
+            if !app.state.selected_pods.is_empty() =>
+        {
+            // 選択中の Pod への一括操作 (インラインプロンプト)
+            app.state.inline_prompt = match key.code {
+                KeyCode::Char('d') => InlinePrompt::BulkDropConfirm,
+                KeyCode::Char('X') => InlinePrompt::BulkForgetConfirm,
+                KeyCode::Char('b') => InlinePrompt::Broadcast,
+                KeyCode::Char('M') => InlinePrompt::BulkMergeConfirm,
+                _ => InlinePrompt::MoveToGroup,
+            };
+            app.state.inline_input.clear();
+            app.state.status_message = None;
+            Action::Render
+        }
+        KeyCode::Char('d') if app.state.effective_view_mode() == ViewMode::Grid && app.focused_group_collapsed() => {
+            // グリッドで折りたたまれたグループ上の d はグループごと drop (D と同じく確認する)。
+            // List / Kanban では折りたたみが見えないので、フォーカス中の Pod だけを drop する
+            if let Some(group) = app.state.focused_pod().and_then(|p| app.state.display_group(p)).map(str::to_string) {
+                app.state.inline_prompt = InlinePrompt::DropGroupConfirm(group);
+                app.state.inline_input.clear();
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('G') => {
+            // フォーカス中 Pod のグループ変更 (現在のグループをプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                let name = pod.name.clone();
+                app.state.inline_input.set(pod.group.clone().unwrap_or_default());
+                app.state.inline_prompt = InlinePrompt::MovePod(name);
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('D') => {
+            // フォーカス中 Pod のグループごと drop (確認)
+            match app.state.focused_pod().and_then(|p| app.state.display_group(p)).map(str::to_string) {
+                Some(group) => {
+                    app.state.inline_prompt = InlinePrompt::DropGroupConfirm(group);
+                    app.state.inline_input.clear();
+                    app.state.status_message = None;
+                }
+                None => app.state.status_message = Some("Focused pod has no group".to_string()),
+            }
+            Action::Render
+        }
+        KeyCode::Char('*') => {
+            // フォーカス中 Pod をピン留め / 解除
+            if let Err(e) = app.toggle_pin() {
+                app.state.status_message = Some(format!("Error: {}", e));
+            }
+            Action::Render
+        }
+        KeyCode::Char('m') => {
+            // フォーカス中 Pod の通知を一定時間止める / 再開
+            if let Err(e) = app.toggle_mute() {
+                app.state.status_message = Some(format!("Error: {}", e));
+            }
+            Action::Render
+        }
+        KeyCode::Char('Q') => {
+            // おやすみモード (全 Pod の通知を止める)
+            app.toggle_dnd();
+            Action::Render
+        }
+        KeyCode::Char('o') => {
+            // フォーカス中 Pod の優先度を切り替え (Normal → High → Low)
+            if let Some(pod) = app.state.focused_pod() {
+                let (name, priority) = (pod.name.clone(), pod.priority.cycle());
+                app.state.status_message = Some(match app.set_priority(&name, priority) {
+                    Ok(()) => format!("'{}' priority: {}", name, priority.label()),
+                    Err(e) => format!("Error: {}", e),
+                });
+            }
+            Action::Render
+        }
+        KeyCode::Char('z') => {
+            // フォーカス中 Pod のグループを折りたたみ / 展開
+            if let Err(e) = app.toggle_group_collapse() {
+                app.state.status_message = Some(format!("Error: {}", e));
+            }
+            Action::Render
+        }
         KeyCode::Enter | KeyCode::Char('i') => {
-            // Detail モード (Permission 状態なら Permission モードへ)
+            // Detail モード (Permission 状態なら Permission モードへ)。watcher の Error 表示はここで解除
+            app.acknowledge_alerts();
             if let Some(pod) = app.state.focused_pod() {
                 if pod.status == crate::pod::PodStatus::Permission {
                     app.state.mode = Mode::Permission;
@@ -150,7 +552,29 @@ fn handle_home_right_keys(app: &mut App, key: KeyEvent) -> Action {
             // tmux セッションにアタッチ
             if let Some(pod) = app.state.focused_pod() {
                 let session = pod.tmux_session.clone();
-                return Action::AttachTmux(session);
+                return attach_action(app, session);
+            }
+            Action::Render
+        }
+        KeyCode::Char('T') => {
+            // tmux のポップアップでアタッチ (apiary は下に残る)
+            if let Some(pod) = app.state.focused_pod() {
+                return Action::AttachPopup(pod.tmux_session.clone());
+            }
+            Action::Render
+        }
+        KeyCode::Char('U') => {
+            // Dead の Pod のセッションを作り直す (フォーカスが Dead でなければ tmux サーバーごと失った Pod すべて)
+            // (devcontainer の起動を待つので裏で動かし、終わったらステータスバーに出す)
+            let focused = app.state.focused_pod().filter(|p| p.status == crate::pod::PodStatus::Dead).map(|p| p.name.clone());
+            let names = match focused {
+                Some(name) => vec![name],
+                None => app.lost_pod_names(),
+            };
+            if names.is_empty() {
+                app.state.status_message = Some("No dead pods to resurrect".to_string());
+            } else if let Err(e) = app.resurrect_in_background(names, false) {
+                app.state.status_message = Some(format!("Resurrect error: {}", e));
             }
             Action::Render
         }
@@ -162,10 +586,12 @@ fn handle_home_right_keys(app: &mut App, key: KeyEvent) -> Action {
             Action::Render
         }
         KeyCode::Char('a') => {
-            // Adopt セッション (インラインプロンプト)
-            app.state.inline_prompt = InlinePrompt::AdoptSession;
-            app.state.inline_input.clear();
-            app.state.status_message = None;
+            // Pod になっていない tmux セッションから選んで取り込む
+            match app.open_adopt_picker() {
+                Ok(true) => app.state.status_message = None,
+                Ok(false) => app.state.status_message = Some("No tmux sessions to adopt".to_string()),
+                Err(e) => app.state.status_message = Some(format!("Error: {}", e)),
+            }
             Action::Render
         }
         KeyCode::Char('d') => {
@@ -178,16 +604,176 @@ fn handle_home_right_keys(app: &mut App, key: KeyEvent) -> Action {
             }
             Action::Render
         }
+        KeyCode::Char('A') => {
+            // Unmanaged セクション先頭のセッションを取り込み
+            app.state.status_message = match app.adopt_first_unmanaged() {
+                Ok(Some(session)) => Some(format!("Session '{}' adopted", session)),
+                Ok(None) => Some("No unmanaged Claude sessions".to_string()),
+                Err(e) => Some(format!("Error: {}", e)),
+            };
+            Action::Render
+        }
+        KeyCode::Char('#') => {
+            // タグ編集 (インラインプロンプト、現在のタグをプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                let name = pod.name.clone();
+                app.state.inline_input.set(pod.tags.join(", "));
+                app.state.inline_prompt = InlinePrompt::EditTags(name);
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('e') => {
+            // メモ編集 (インラインプロンプト、現在のメモをプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                let name = pod.name.clone();
+                app.state.inline_input.set(pod.note.clone());
+                app.state.inline_prompt = InlinePrompt::EditNote(name);
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('M') => {
+            // ペインを member として追加 (インラインプロンプト、未所属のペインをプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                let name = pod.name.clone();
+                app.state.inline_input.set(app.first_unowned_pane().unwrap_or_default());
+                app.state.inline_prompt = InlinePrompt::AddMember(name);
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('S') => {
+            // member を独立した Pod に (インラインプロンプト、最後の member をプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                if pod.members.len() < 2 {
+                    app.state.status_message = Some(format!("'{}' has only one member", pod.name));
+                } else {
+                    let name = pod.name.clone();
+                    app.state.inline_input.set(pod.members.last().map(|m| m.role.clone()).unwrap_or_default());
+                    app.state.inline_prompt = InlinePrompt::SplitMember(name);
+                    app.state.status_message = None;
+                }
+            }
+            Action::Render
+        }
+        KeyCode::Char('R') => {
+            // member の名前を変更 (インラインプロンプト、"<現在の名前> " をプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                let name = pod.name.clone();
+                let member = app.state.selected_member.and_then(|i| pod.members.get(i)).or(pod.members.last());
+                app.state.inline_input.set(member.map(|m| format!("{} ", m.role)).unwrap_or_default());
+                app.state.inline_prompt = InlinePrompt::RenameMember(name);
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('c') => {
+            // リードメンバーに /compact を送る
+            if let Some(name) = app.state.focused_pod().map(|p| p.name.clone()) {
+                app.state.status_message = Some(match app.send_claude_command(&name, "/compact") {
+                    Ok(()) => format!("Sent /compact to '{}'", name),
+                    Err(e) => format!("Error: {}", e),
+                });
+            }
+            Action::Render
+        }
+        KeyCode::Char('C') => {
+            // /clear は会話を捨てるので確認する
+            if let Some(pod) = app.state.focused_pod() {
+                app.state.inline_prompt = InlinePrompt::ClearConfirm(pod.name.clone());
+                app.state.inline_input.clear();
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('O') => {
+            // モデルの切り替え (インラインプロンプト、今のモデルをプリフィル)
+            if let Some(pod) = app.state.focused_pod() {
+                let name = pod.name.clone();
+                app.state.inline_input.set(pod.model.as_deref().map(short_model_name).unwrap_or_default().to_string());
+                app.state.inline_prompt = InlinePrompt::SwitchModel(name);
+                app.state.status_message = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('f') => {
+            // タグフィルタ (インラインプロンプト)
+            app.state.inline_input.set(app.state.tag_filter.clone().unwrap_or_default());
+            app.state.inline_prompt = InlinePrompt::TagFilter;
+            app.state.status_message = None;
+            Action::Render
+        }
         KeyCode::Char('p') => {
             // ディレクトリブラウザを開く
             app.open_browser(None);
             Action::Render
         }
+        KeyCode::Char('W') => {
+            // グリッドをワークスペースの Pod に絞る / 戻す
+            app.state.status_message = Some(match app.toggle_workspace_filter() {
+                Ok(true) => format!(
+                    "Showing pods of {} only",
+                    app.state.current_project.as_ref().map(|p| p.name.as_str()).unwrap_or_default()
+                ),
+                Ok(false) => "Showing pods of all projects".to_string(),
+                Err(e) => format!("Error: {}", e),
+            });
+            Action::Render
+        }
+        KeyCode::Char('w') => {
+            // 登録済みプロジェクトからワークスペースを選ぶ
+            let projects = app.project_store.list().unwrap_or_default();
+            if projects.is_empty() {
+                app.state.status_message = Some("No projects registered (p to browse, or apiary project scan)".to_string());
+            } else {
+                let current = app.state.current_project.as_ref().map(|p| p.name.as_str());
+                let switcher = WorkspaceSwitcher::new(projects, &app.state.recent_workspaces, current);
+                app.state.workspace_switcher = Some(switcher);
+                app.state.completion = None;
+            }
+            Action::Render
+        }
+        KeyCode::Char('g') => {
+            app.state.pending_group_jump = true;
+            app.state.status_message = Some("g: press 1-9 to jump to a group".to_string());
+            Action::Render
+        }
+        KeyCode::Char('1'..='9') if key.modifiers.is_empty() => {
+            if let Some(n) = quick_digit(&key) {
+                app.quick_focus(n);
+            }
+            Action::Render
+        }
+        KeyCode::Char('\\') => {
+            app.toggle_left_pane();
+            Action::Render
+        }
+        KeyCode::Char('L') => {
+            app.toggle_layout();
+            Action::Render
+        }
+        KeyCode::Char('P') => {
+            // ブランチを push して gh で PR を作成 (裏で行い、終わったら URL を出す)
+            if let Some(name) = app.state.focused_pod().map(|p| p.name.clone()) {
+                app.state.status_message = Some(match app.create_pr_in_background(&name) {
+                    Ok(()) => format!("Creating a PR for {}...", name),
+                    Err(e) => format!("PR failed: {}", e),
+                });
+            }
+            Action::Render
+        }
+        KeyCode::Char('v') => {
+            // グリッド / リスト / Kanban 表示切り替え
+            app.toggle_view_mode();
+            Action::Render
+        }
         KeyCode::Char(c) => {
             // ショートカットに該当しない文字 → 左ペインに切り替えて1文字目として入力
             app.state.pane_focus = PaneFocus::Left;
             app.state.inline_input.clear();
-            app.state.inline_input.push(c);
+            app.state.inline_input.insert_char(c);
+            app.update_completion();
             Action::Render
         }
         _ => Action::None,
@@ -196,18 +782,26 @@ fn handle_home_right_keys(app: &mut App, key: KeyEvent) -> Action {
 
 /// 左ペインフォーカス時: 指示入力 + スラッシュコマンド
 fn handle_home_left_keys(app: &mut App, key: KeyEvent) -> Action {
+    if app.state.completion.is_some() {
+        if let Some(action) = handle_completion_keys(app, key) {
+            return action;
+        }
+    }
+
     match key.code {
         KeyCode::Esc | KeyCode::Tab => {
             app.state.pane_focus = PaneFocus::Right;
             Action::Render
         }
         KeyCode::Enter => {
-            let input = app.state.inline_input.trim().to_string();
+            let input = app.state.inline_input.as_str().trim().to_string();
             app.state.inline_input.clear();
+            app.state.completion = None;
 
             if input.is_empty() {
                 return Action::Render;
             }
+            app.record_input(&input);
 
             if let Some(cmd) = input.strip_prefix('/') {
                 // スラッシュコマンド: 先頭の / を取り除いて execute_command に渡す
@@ -228,7 +822,7 @@ fn handle_home_left_keys(app: &mut App, key: KeyEvent) -> Action {
                 let (instruction, project_input) = parse_at_project(&input);
                 let names: Vec<String> = app.state.pods.iter().map(|p| p.name.clone()).collect();
                 let name = generate_pod_name(&instruction, &names);
-                match app.create_pod(&name, project_input.as_deref(), None, Some(&instruction)) {
+                match app.create_pod(&name, project_input.as_deref(), None, Some(&instruction), None) {
                     Ok(()) => {
                         // 新しい Pod にフォーカス
                         let new_idx = app.state.pods.len().saturating_sub(1);
@@ -243,22 +837,61 @@ fn handle_home_left_keys(app: &mut App, key: KeyEvent) -> Action {
 
             Action::Render
         }
-        KeyCode::Backspace => {
-            app.state.inline_input.pop();
+        KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::EditInput,
+        KeyCode::Up => {
+            // 履歴を遡る
+            let current = app.state.inline_input.as_str().to_string();
+            if let Some(entry) = app.input_history.older(&current) {
+                let entry = entry.to_string();
+                app.state.inline_input.set(entry);
+                app.state.completion = None;
+            }
             Action::Render
         }
-        KeyCode::Char(c) => {
+        KeyCode::Down => {
+            if let Some(entry) = app.input_history.newer() {
+                let entry = entry.to_string();
+                app.state.inline_input.set(entry);
+                app.state.completion = None;
+            }
+            Action::Render
+        }
+        _ => {
             // 入力開始時に前回の結果メッセージをクリア
             if app.state.inline_input.is_empty() {
                 app.state.status_message = None;
             }
-            app.state.inline_input.push(c);
-            Action::Render
+            if app.state.inline_input.handle_key(&key) {
+                app.update_completion();
+                Action::Render
+            } else {
+                Action::None
+            }
         }
-        _ => Action::None,
     }
 }
 
+/// 補完ポップアップ表示中のキー処理。処理しなかったキーは None を返して通常の入力へ
+fn handle_completion_keys(app: &mut App, key: KeyEvent) -> Option<Action> {
+    let completion = app.state.completion.as_mut()?;
+    match key.code {
+        KeyCode::Tab | KeyCode::Down => completion.select_next(),
+        KeyCode::BackTab | KeyCode::Up => completion.select_prev(),
+        KeyCode::Esc => app.state.completion = None,
+        KeyCode::Enter => {
+            // 既に候補どおり入力済みなら Enter はそのまま確定 (実行) に回す
+            if completion.is_exact(&app.state.inline_input) {
+                app.state.completion = None;
+                return None;
+            }
+            completion.apply(&mut app.state.inline_input);
+            app.update_completion();
+        }
+        _ => return None,
+    }
+    Some(Action::Render)
+}
+
 /// "instruction @project" 構文をパース
 fn parse_at_project(input: &str) -> (String, Option<String>) {
     if let Some(at_pos) = input.rfind('@') {
@@ -287,38 +920,17 @@ fn handle_inline_prompt(app: &mut App, key: KeyEvent) -> Action {
             Action::Render
         }
         KeyCode::Enter => {
-            let input = app.state.inline_input.trim().to_string();
+            let input = app.state.inline_input.as_str().trim().to_string();
             let prompt = app.state.inline_prompt.clone();
             app.state.inline_prompt = InlinePrompt::None;
             app.state.inline_input.clear();
 
             match prompt {
-                InlinePrompt::AdoptSession => {
-                    if input.is_empty() {
-                        return Action::Render;
-                    }
-                    let parts: Vec<&str> = input.split_whitespace().collect();
-                    let session = parts[0];
-                    let group = parts
-                        .iter()
-                        .position(|&p| p == "--group")
-                        .and_then(|i| parts.get(i + 1))
-                        .copied();
-                    match app.adopt_session(session, None, group) {
-                        Ok(()) => {
-                            app.state.status_message =
-                                Some(format!("Session '{}' adopted", session));
-                        }
-                        Err(e) => {
-                            app.state.status_message = Some(format!("Error: {}", e));
-                        }
-                    }
-                }
                 InlinePrompt::DropConfirm(name) => {
                     if input == "y" || input == "yes" {
                         match app.drop_pod(&name) {
                             Ok(()) => {
-                                app.state.status_message = Some(format!("Pod '{}' dropped", name));
+                                app.state.status_message = Some(format!("Pod '{}' dropped (/restore {} to undo)", name, name));
                             }
                             Err(e) => {
                                 app.state.status_message = Some(format!("Error: {}", e));
@@ -328,25 +940,147 @@ fn handle_inline_prompt(app: &mut App, key: KeyEvent) -> Action {
                         app.state.status_message = Some("Drop cancelled".to_string());
                     }
                 }
+                InlinePrompt::EditTags(name) => {
+                    let tags = parse_tags(&input);
+                    match app.set_tags(&name, tags) {
+                        Ok(()) => {
+                            app.state.status_message = Some(format!("Tags updated for '{}'", name));
+                        }
+                        Err(e) => {
+                            app.state.status_message = Some(format!("Error: {}", e));
+                        }
+                    }
+                }
+                InlinePrompt::EditNote(name) => match app.set_note(&name, &input) {
+                    Ok(()) => {
+                        app.state.status_message = Some(format!("Note updated for '{}'", name));
+                    }
+                    Err(e) => {
+                        app.state.status_message = Some(format!("Error: {}", e));
+                    }
+                },
+                InlinePrompt::TagFilter => {
+                    let tag = input.trim_start_matches('#').to_string();
+                    if tag.is_empty() {
+                        app.set_tag_filter(None);
+                        app.state.status_message = Some("Tag filter cleared".to_string());
+                    } else {
+                        app.state.status_message = Some(format!("Filtering by #{}", tag));
+                        app.set_tag_filter(Some(tag));
+                    }
+                }
+                InlinePrompt::AddMember(name) => {
+                    let mut parts = input.split_whitespace();
+                    let (pane, role) = (parts.next().unwrap_or_default(), parts.next());
+                    app.state.status_message = Some(match app.add_member(&name, pane, role) {
+                        Ok(role) => format!("Added {} to '{}' as {}", pane, name, role),
+                        Err(e) => format!("Error: {}", e),
+                    });
+                }
+                InlinePrompt::SplitMember(name) => {
+                    app.state.status_message = Some(match app.split_member(&name, input.trim()) {
+                        Ok(child) => format!("Split '{}' into its own pod", child),
+                        Err(e) => format!("Error: {}", e),
+                    });
+                }
+                InlinePrompt::RenameMember(name) => {
+                    let parts: Vec<&str> = input.split_whitespace().collect();
+                    app.state.status_message = Some(match parts.as_slice() {
+                        [old, new] => match app.rename_member(&name, old, new) {
+                            Ok(Some(pod)) => format!("Renamed {} to {} (pod is now '{}')", old, new, pod),
+                            Ok(None) => format!("Renamed {} to {}", old, new),
+                            Err(e) => format!("Error: {}", e),
+                        },
+                        _ => "Usage: <old name> <new name>".to_string(),
+                    });
+                }
+                InlinePrompt::SwitchModel(name) => {
+                    let model = input.trim();
+                    app.state.status_message = Some(match app.switch_model(&name, model) {
+                        Ok(()) => format!("Sent /model {} to '{}'", model, name),
+                        Err(e) => format!("Error: {}", e),
+                    });
+                }
+                InlinePrompt::MovePod(name) => {
+                    let group = Some(input).filter(|g| !g.is_empty());
+                    app.state.status_message = Some(match app.set_group(&name, group.clone()) {
+                        Ok(()) => match group {
+                            Some(g) => format!("'{}' moved to group '{}'", name, g),
+                            None => format!("'{}' ungrouped", name),
+                        },
+                        Err(e) => format!("Error: {}", e),
+                    });
+                }
+                InlinePrompt::DropGroupConfirm(group) => {
+                    app.state.status_message = Some(if input == "y" || input == "yes" {
+                        match app.drop_group(&group) {
+                            Ok(names) => format!("Group '{}' dropped ({} pods, /restore to undo)", group, names.len()),
+                            Err(e) => format!("Error: {}", e),
+                        }
+                    } else {
+                        "Drop cancelled".to_string()
+                    });
+                }
+                InlinePrompt::BulkDropConfirm => {
+                    app.state.status_message = Some(if input == "y" || input == "yes" {
+                        format!("{} (/restore to undo)", app.drop_selected())
+                    } else {
+                        "Drop cancelled".to_string()
+                    });
+                }
+                InlinePrompt::BulkForgetConfirm => {
+                    app.state.status_message = Some(if input == "y" || input == "yes" {
+                        app.forget_selected()
+                    } else {
+                        "Forget cancelled".to_string()
+                    });
+                }
+                InlinePrompt::ClearConfirm(name) => {
+                    app.state.status_message = Some(if input == "y" || input == "yes" {
+                        match app.send_claude_command(&name, "/clear") {
+                            Ok(()) => format!("Sent /clear to '{}'", name),
+                            Err(e) => format!("Error: {}", e),
+                        }
+                    } else {
+                        "Clear cancelled".to_string()
+                    });
+                }
+                InlinePrompt::BulkMergeConfirm => {
+                    app.state.status_message = Some(if input == "y" || input == "yes" {
+                        app.merge_selected_into_focused()
+                    } else {
+                        "Merge cancelled".to_string()
+                    });
+                }
+                InlinePrompt::Broadcast => {
+                    if !input.is_empty() {
+                        app.state.status_message = Some(app.broadcast_to_selected(&input));
+                    }
+                }
+                InlinePrompt::MoveToGroup => {
+                    let group = Some(input).filter(|g| !g.is_empty());
+                    app.state.status_message = Some(app.move_selected_to_group(group));
+                }
                 InlinePrompt::Browse => {} // handled above
                 InlinePrompt::None => {}
             }
             Action::Render
         }
-        KeyCode::Backspace => {
-            app.state.inline_input.pop();
-            Action::Render
-        }
-        KeyCode::Char(c) => {
-            app.state.inline_input.push(c);
-            Action::Render
+        _ => {
+            if app.state.inline_input.handle_key(&key) {
+                Action::Render
+            } else {
+                Action::None
+            }
         }
-        _ => Action::None,
     }
 }
 
 /// ブラウザモードのキー処理
 fn handle_browser_keys(app: &mut App, key: KeyEvent) -> Action {
+    if app.state.browser_state.as_ref().is_some_and(|bs| bs.input.is_some()) {
+        return handle_browser_input_keys(app, key);
+    }
     match key.code {
         KeyCode::Esc => {
             app.browser_cancel();
@@ -376,6 +1110,30 @@ fn handle_browser_keys(app: &mut App, key: KeyEvent) -> Action {
             app.browser_go_parent();
             Action::Render
         }
+        KeyCode::Char('.') => {
+            app.browser_toggle_hidden();
+            Action::Render
+        }
+        KeyCode::Char(c @ '1'..='9') => {
+            let n = c as usize - '1' as usize;
+            app.state.status_message = Some(match app.browser_select_recent(n) {
+                Ok(msg) => msg,
+                Err(e) => format!("Error: {}", e),
+            });
+            Action::Render
+        }
+        KeyCode::Char('n') => {
+            open_browser_input(app, BrowserInput::NewDir, "");
+            Action::Render
+        }
+        KeyCode::Char(':') => {
+            open_browser_input(app, BrowserInput::GoTo, "");
+            Action::Render
+        }
+        KeyCode::Char(c @ ('/' | '~')) => {
+            open_browser_input(app, BrowserInput::GoTo, &c.to_string());
+            Action::Render
+        }
         KeyCode::Char(' ') => {
             match app.browser_select_current() {
                 Ok(msg) => {
@@ -391,6 +1149,60 @@ fn handle_browser_keys(app: &mut App, key: KeyEvent) -> Action {
     }
 }
 
+fn open_browser_input(app: &mut App, input: BrowserInput, text: &str) {
+    if let Some(bs) = &mut app.state.browser_state {
+        bs.input = Some(input);
+        bs.input_text.set(text);
+    }
+}
+
+/// ブラウザの入力欄 (新規ディレクトリ名 / 移動先パス)
+fn handle_browser_input_keys(app: &mut App, key: KeyEvent) -> Action {
+    let Some(bs) = &mut app.state.browser_state else {
+        return Action::None;
+    };
+    match key.code {
+        KeyCode::Esc => {
+            bs.input = None;
+            bs.input_text.clear();
+            Action::Render
+        }
+        KeyCode::Tab => {
+            if bs.input == Some(BrowserInput::GoTo) {
+                if let Some(completed) = crate::tui::app::complete_dir_path(&bs.current_path, bs.input_text.as_str()) {
+                    bs.input_text.set(completed);
+                }
+            }
+            Action::Render
+        }
+        KeyCode::Enter => {
+            let text = bs.input_text.as_str().to_string();
+            let input = bs.input.take();
+            bs.input_text.clear();
+            if text.trim().is_empty() {
+                return Action::Render;
+            }
+            let result = match input {
+                Some(BrowserInput::NewDir) => app.browser_make_dir(&text).map(Some),
+                Some(BrowserInput::GoTo) => app.browser_go_to(&text).map(|_| None),
+                None => Ok(None),
+            };
+            match result {
+                Ok(msg) => app.state.status_message = msg,
+                Err(e) => app.state.status_message = Some(format!("Error: {}", e)),
+            }
+            Action::Render
+        }
+        _ => {
+            if bs.input_text.handle_key(&key) {
+                Action::Render
+            } else {
+                Action::None
+            }
+        }
+    }
+}
+
 fn handle_detail_keys(app: &mut App, key: KeyEvent) -> Action {
     // Esc でパススルー終了 → Home に戻る
     if key.code == KeyCode::Esc {
@@ -411,6 +1223,29 @@ fn handle_detail_keys(app: &mut App, key: KeyEvent) -> Action {
         return Action::Render;
     }
 
+    // F2: 表示中のメンバーと Chat
+    if key.code == KeyCode::F(2) {
+        app.state.mode = Mode::Chat;
+        app.state.chat_input.clear();
+        app.state.chat_scroll = 0;
+        app.state.chat_unread = 0;
+        return Action::Render;
+    }
+
+    // F5: 固まった Claude だけを同じペインで起動し直す (Shift+F5 は会話を引き継ぐ)
+    if key.code == KeyCode::F(5) {
+        if let Some(pod) = app.state.focused_pod() {
+            let name = pod.name.clone();
+            let member = app.state.selected_member.and_then(|i| pod.members.get(i)).map(|m| m.role.clone());
+            let resume = key.modifiers.contains(KeyModifiers::SHIFT);
+            app.state.status_message = Some(match app.respawn_agent(&name, member.as_deref(), resume) {
+                Ok(_) => format!("Restarted Claude in '{}'", name),
+                Err(e) => format!("Respawn error: {}", e),
+            });
+        }
+        return Action::Render;
+    }
+
     // 全キーを pane に転送 (パススルーモード)
     if let Err(e) = app.forward_key_to_pane(&key) {
         app.state.status_message = Some(format!("Key error: {}", e));
@@ -424,6 +1259,29 @@ fn handle_chat_keys(app: &mut App, key: KeyEvent) -> Action {
             app.state.mode = Mode::Detail;
             Action::Render
         }
+        KeyCode::Tab => {
+            // 話す相手を次のメンバーに切り替え
+            app.cycle_chat_member();
+            Action::Render
+        }
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            // 応答の Markdown 描画と原文表示を切り替え
+            app.state.chat_raw = !app.state.chat_raw;
+            Action::Render
+        }
+        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            // やり取りを Markdown に書き出す
+            app.export_focused_chat();
+            Action::Render
+        }
+        KeyCode::PageUp => {
+            app.scroll_chat(CHAT_PAGE as isize);
+            Action::Render
+        }
+        KeyCode::PageDown => {
+            app.scroll_chat(-(CHAT_PAGE as isize));
+            Action::Render
+        }
         KeyCode::Enter => {
             if !app.state.chat_input.is_empty() {
                 if let Err(e) = app.send_chat_message() {
@@ -432,15 +1290,13 @@ fn handle_chat_keys(app: &mut App, key: KeyEvent) -> Action {
             }
             Action::Render
         }
-        KeyCode::Backspace => {
-            app.state.chat_input.pop();
-            Action::Render
-        }
-        KeyCode::Char(c) => {
-            app.state.chat_input.push(c);
-            Action::Render
+        _ => {
+            if app.state.chat_input.handle_key(&key) {
+                Action::Render
+            } else {
+                Action::None
+            }
         }
-        _ => Action::None,
     }
 }
 
@@ -0,0 +1,585 @@
+//! 画面を持たずに TUI を動かすテスト用のハーネス。
+//! FakeTmux と TestBackend の上で App を作り、キー / 貼り付けを handle_key_event に流して
+//! 描画結果を文字列として確かめる
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use regex::Regex;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+use super::app::App;
+use super::handler::{handle_key_event, handle_paste_event, Action};
+use super::ui::draw;
+use crate::tmux::fake::FakeTmux;
+
+pub(crate) struct Harness {
+    pub app: App,
+    pub tmux: FakeTmux,
+    terminal: Terminal<TestBackend>,
+    /// Pod の作業ディレクトリ (スナップショットが一時ディレクトリ名に左右されないよう固定の名前にする)
+    project: PathBuf,
+    _dir: TempDir,
+}
+
+impl Harness {
+    /// width x height の仮想端末で空の App を作る
+    pub fn new(width: u16, height: u16) -> Self {
+        let dir = TempDir::new().unwrap();
+        let tmux = FakeTmux::new();
+        let app = App::for_test(dir.path(), Box::new(tmux.clone()));
+        let terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let project = dir.path().join("demo");
+        std::fs::create_dir_all(&project).unwrap();
+        Self { app, tmux, terminal, project, _dir: dir }
+    }
+
+    /// demo プロジェクトに Pod を作り、画面の内容を決める。リードのペイン ID を返す
+    pub fn pod(&mut self, name: &str, output: &str) -> String {
+        let project = self.project.to_string_lossy().to_string();
+        self.app.create_pod(name, Some(&project), None, None, None).unwrap();
+        let pane = self.app.state.pods.iter().find(|p| p.name == name).unwrap().members[0].tmux_pane.clone();
+        self.tmux.set_output(&pane, output);
+        pane
+    }
+
+    /// 全 Pod の状態を読み直す (ポーリング 1 回分)
+    pub fn refresh(&mut self) {
+        self.app.refresh_pod_states();
+        self.app.handle_status_changes();
+    }
+
+    pub fn key(&mut self, code: KeyCode) -> Action {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    pub fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Action {
+        handle_key_event(&mut self.app, KeyEvent::new(code, modifiers))
+    }
+
+    /// 文字を 1 つずつ打ち込む
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.key(KeyCode::Char(c));
+        }
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        handle_paste_event(&mut self.app, text);
+    }
+
+    /// 描画して画面を行ごとの文字列で返す (行末の空白は落とす)
+    pub fn render(&mut self) -> String {
+        let app = &self.app;
+        self.terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| {
+                let mut line = String::new();
+                let mut skip = 0;
+                for cell in row {
+                    // 全角文字の後ろのセルは空なので飛ばす
+                    if skip > 0 {
+                        skip -= 1;
+                        continue;
+                    }
+                    let symbol = cell.symbol();
+                    skip = unicode_width::UnicodeWidthStr::width(symbol).saturating_sub(1);
+                    line.push_str(symbol);
+                }
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 画面にテキストが出ているか確かめる (失敗時は画面全体を出す)
+    #[track_caller]
+    pub fn assert_shows(&mut self, needle: &str) {
+        let screen = self.render();
+        assert!(screen.contains(needle), "{:?} not on screen:\n{}", needle, screen);
+    }
+
+    #[track_caller]
+    pub fn assert_hides(&mut self, needle: &str) {
+        let screen = self.render();
+        assert!(!screen.contains(needle), "{:?} unexpectedly on screen:\n{}", needle, screen);
+    }
+
+    /// 画面全体を src/tui/snapshots/<name>.txt と比べる。経過時間 ("12s" など) は "0s" に揃える。
+    /// UPDATE_SNAPSHOTS=1 で実行するとファイルを書き (直す)。ファイルが無ければ失敗する
+    #[track_caller]
+    pub fn assert_snapshot(&mut self, name: &str) {
+        let elapsed = Regex::new(r"\b\d+[smhd]\b").unwrap();
+        let screen = elapsed.replace_all(&self.render(), "0s").into_owned() + "\n";
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tui/snapshots").join(format!("{}.txt", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &screen).unwrap();
+            return;
+        }
+        let Ok(expected) = std::fs::read_to_string(&path) else {
+            panic!("snapshot {} is missing (UPDATE_SNAPSHOTS=1 to create {:?})\n--- actual\n{}", name, path, screen);
+        };
+        assert!(
+            expected == screen,
+            "snapshot {} differs (UPDATE_SNAPSHOTS=1 to accept)\n--- expected\n{}--- actual\n{}",
+            name,
+            expected,
+            screen
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pod::PodStatus;
+    use crate::pod::{InlinePrompt, Mode, ViewMode};
+
+    const PERMISSION: &str = "Bash(rm -rf target)\nDo you want to proceed?\n❯ 1. Yes\n  2. No";
+
+    #[test]
+    fn test_grid_snapshot() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "✻ Working… (esc to interrupt)");
+        h.pod("web", PERMISSION);
+        h.refresh();
+        h.assert_snapshot("grid");
+    }
+
+    #[test]
+    fn test_open_and_close_detail() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "hello from api\n❯ ");
+        h.refresh();
+        h.key(KeyCode::Char('1'));
+        h.key(KeyCode::Enter);
+        assert!(matches!(h.app.state.mode, Mode::Detail));
+        h.assert_shows("hello from api");
+        h.assert_hides("Give an instruction");
+        h.key(KeyCode::Esc);
+        assert!(matches!(h.app.state.mode, Mode::Home));
+    }
+
+    #[test]
+    fn test_working_time_in_titles_follows_the_setting() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.refresh();
+        h.app.state.pods[0].total_working_secs = 720;
+        h.assert_shows("\u{23f1}12m");
+        h.key(KeyCode::Char('1'));
+        h.key(KeyCode::Enter);
+        assert!(matches!(h.app.state.mode, Mode::Detail));
+        h.assert_shows("\u{23f1}12m");
+
+        h.app.config.ui.show_working_time = false;
+        h.assert_hides("\u{23f1}");
+        h.key(KeyCode::Esc);
+        h.assert_hides("\u{23f1}");
+    }
+
+    #[test]
+    fn test_permission_approve() {
+        let mut h = Harness::new(100, 24);
+        let pane = h.pod("web", PERMISSION);
+        h.refresh();
+        assert_eq!(h.app.state.pods[0].status, PodStatus::Permission);
+        h.key(KeyCode::Char('1'));
+        h.key(KeyCode::Enter);
+        assert!(matches!(h.app.state.mode, Mode::Permission));
+        h.assert_snapshot("permission");
+        h.key(KeyCode::Char('a'));
+        assert!(matches!(h.app.state.mode, Mode::Detail));
+        assert!(h.tmux.sent_to(&pane).iter().any(|k| k == "y"), "sent: {:?}", h.tmux.sent_to(&pane));
+    }
+
+    #[test]
+    fn test_project_health_when_no_pod_is_focused() {
+        let mut h = Harness::new(100, 24);
+        h.app.state.current_project = Some(crate::project::Project::new("demo", h.project.to_string_lossy()));
+        h.pod("api", "✻ Working… (esc to interrupt)");
+        h.refresh();
+        h.app.refresh_project_health();
+        h.app.finish_jobs();
+        // グリッドの Pod にフォーカスしている間はいつものガイド
+        h.app.state.focus = Some(0);
+        h.assert_hides("1 Working");
+        h.app.state.pane_focus = crate::pod::PaneFocus::Left;
+        h.assert_shows("1 Working");
+        h.assert_shows("api created");
+        h.assert_shows("Give an instruction");
+    }
+
+    #[test]
+    fn test_enter_on_collapsed_group_only_expands_in_grid() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.app.state.pods[0].group = Some("backend".to_string());
+        h.app.state.collapsed_groups.insert("backend".to_string());
+        h.app.state.focus = Some(0);
+        h.app.state.view_mode = ViewMode::List;
+        h.key(KeyCode::Enter);
+        assert!(matches!(h.app.state.mode, Mode::Detail));
+        assert!(h.app.state.collapsed_groups.contains("backend"));
+        h.key(KeyCode::Esc);
+
+        h.app.state.view_mode = ViewMode::Grid;
+        h.key(KeyCode::Enter);
+        assert!(matches!(h.app.state.mode, Mode::Home));
+        assert!(!h.app.state.collapsed_groups.contains("backend"));
+    }
+
+    #[test]
+    fn test_drop_on_collapsed_group_only_in_grid() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.pod("web", "❯ ");
+        for pod in &mut h.app.state.pods {
+            pod.group = Some("backend".to_string());
+        }
+        h.app.state.collapsed_groups.insert("backend".to_string());
+        h.app.state.focus = Some(0);
+        h.key(KeyCode::Char('d'));
+        assert_eq!(h.app.state.inline_prompt, InlinePrompt::DropGroupConfirm("backend".to_string()));
+        h.key(KeyCode::Esc);
+        assert_eq!(h.app.state.inline_prompt, InlinePrompt::None);
+
+        h.app.state.view_mode = ViewMode::List;
+        h.key(KeyCode::Char('d'));
+        assert_eq!(h.app.state.inline_prompt, InlinePrompt::DropConfirm("api".to_string()));
+    }
+
+    #[test]
+    fn test_bulk_keys_act_on_the_selection() {
+        let mut h = Harness::new(100, 24);
+        for name in ["api", "web", "docs"] {
+            h.pod(name, "❯ ");
+        }
+        h.app.state.focus = Some(0);
+        h.key(KeyCode::Char(' '));
+        h.app.state.focus = Some(1);
+        h.key(KeyCode::Char(' '));
+        h.assert_shows("2 selected");
+        h.assert_shows("[a]Archive");
+
+        // d は確認してから
+        h.key(KeyCode::Char('d'));
+        assert_eq!(h.app.state.inline_prompt, InlinePrompt::BulkDropConfirm);
+        h.key(KeyCode::Esc);
+        assert_eq!(h.app.state.selected_pods.len(), 2);
+
+        // a はすぐにゴミ箱へ
+        h.key(KeyCode::Char('a'));
+        assert_eq!(h.app.state.pods.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["docs"]);
+        h.assert_shows("2 pods archived");
+        h.assert_hides("selected");
+    }
+
+    #[test]
+    fn test_list_view() {
+        let mut h = Harness::new(120, 24);
+        h.app.state.left_pane_hidden = true;
+        h.pod("api", "cargo build\n   Compiling api v0.1.0\n✻ Working… (esc to interrupt)");
+        h.pod("web", "All 12 tests passed\n");
+        h.refresh();
+        h.key(KeyCode::Char('v'));
+        assert_eq!(h.app.state.view_mode, ViewMode::List);
+        let screen = h.render();
+        for column in ["NAME", "PROJECT", "GROUP", "MEM", "WORK", "LAST OUTPUT"] {
+            assert!(screen.contains(column), "{} missing:\n{}", column, screen);
+        }
+        // 1 Pod 1 行で、最後の出力行を出す
+        let row = screen.lines().find(|l| l.contains("web")).unwrap();
+        assert!(row.contains("demo") && row.contains("All 12 tests passed"), "{}", row);
+
+        // 上下は 1 行ずつ、Enter で Detail を開く
+        h.app.state.focus = Some(0);
+        h.key(KeyCode::Down);
+        assert_eq!(h.app.state.focus, Some(1));
+        h.key(KeyCode::Up);
+        assert_eq!(h.app.state.focus, Some(0));
+        h.key(KeyCode::Enter);
+        assert!(matches!(h.app.state.mode, Mode::Detail));
+    }
+
+    #[test]
+    fn test_list_view_drops_columns_when_cramped() {
+        let mut h = Harness::new(66, 24);
+        h.app.state.left_pane_hidden = true;
+        h.app.state.view_mode = ViewMode::List;
+        h.pod("web", "All 12 tests passed\n");
+        h.refresh();
+        h.assert_shows("LAST OUTPUT");
+        h.assert_shows("All 12 tests passed");
+        h.assert_hides("PROJECT");
+    }
+
+    #[test]
+    fn test_pods_pane_width_on_wide_terminals() {
+        let h = Harness::new(80, 24);
+        let percent = u32::from(100 - h.app.config.ui.left_pane_percent());
+        for width in [1000, u16::MAX] {
+            let expected = (u32::from(width) * percent / 100) as u16;
+            assert_eq!(crate::tui::ui::pods_pane_width(&h.app, width), expected);
+        }
+    }
+
+    #[test]
+    fn test_kanban_view() {
+        let mut h = Harness::new(200, 30);
+        h.app.state.left_pane_hidden = true;
+        let api = h.pod("api", "✻ Working… (esc to interrupt)");
+        h.pod("web", PERMISSION);
+        h.pod("docs", "✻ Working… (esc to interrupt)");
+        h.refresh();
+        h.key(KeyCode::Char('v'));
+        h.key(KeyCode::Char('v'));
+        assert_eq!(h.app.state.view_mode, ViewMode::Kanban);
+        h.assert_shows("Permission (1)");
+        h.assert_shows("Working (2)");
+        h.assert_shows("Dead (0)");
+
+        // 左右は空でない隣のカラムへ、上下はカラムの中を動く
+        h.app.state.focus = Some(1);
+        h.key(KeyCode::Right);
+        assert_eq!(h.app.state.focus, Some(0));
+        h.key(KeyCode::Down);
+        assert_eq!(h.app.state.focus, Some(2));
+        h.key(KeyCode::Right);
+        assert_eq!(h.app.state.focus, Some(2));
+        h.key(KeyCode::Left);
+        assert_eq!(h.app.state.focus, Some(1));
+
+        // 状態が変わるとカードも移る
+        h.tmux.set_output(&api, "Bash(cargo publish)\nDo you want to proceed?\n❯ 1. Yes\n  2. No");
+        h.refresh();
+        h.assert_shows("Permission (2)");
+        h.assert_shows("Working (1)");
+    }
+
+    #[test]
+    fn test_workspace_switcher() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.app.project_store.register(&crate::project::Project::new("other", "/src/other")).unwrap();
+        h.key(KeyCode::Char('w'));
+        h.assert_shows("Switch Workspace (2/2)");
+        // demo のパスは一時ディレクトリで文字が読めないので、other にしか合わないクエリにする
+        h.type_text("src/other");
+        h.assert_shows("Switch Workspace (1/2)");
+        h.key_with(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        assert!(h.app.state.workspace_switcher.is_none());
+        assert_eq!(h.app.state.current_project.as_ref().map(|p| p.name.as_str()), Some("other"));
+        assert!(h.app.state.visible_pods().is_empty());
+        h.assert_shows("@other");
+
+        // Enter は切り替えるだけで絞り込まない
+        h.key(KeyCode::Char('w'));
+        h.type_text("demo");
+        h.key(KeyCode::Enter);
+        assert_eq!(h.app.state.current_project.as_ref().map(|p| p.name.as_str()), Some("demo"));
+        assert_eq!(h.app.state.visible_pods().len(), 1);
+    }
+
+    #[test]
+    fn test_workspace_filter_toggle() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.app.create_pod("web", Some("/src/web"), None, None, None).unwrap();
+        h.key(KeyCode::Char('W'));
+        h.assert_shows("No workspace set");
+
+        h.app.state.current_project = h.app.project_store.find_by_name("demo").unwrap();
+        h.key(KeyCode::Char('W'));
+        h.assert_shows("Pods (1 hidden)");
+        h.assert_shows("Showing pods of demo only");
+        assert!(h.app.ui_state().workspace_filter);
+        h.key(KeyCode::Char('W'));
+        h.assert_hides("hidden");
+    }
+
+    #[test]
+    fn test_paste_into_new_task_input() {
+        let mut h = Harness::new(100, 24);
+        h.paste("fix the flaky test");
+        h.assert_shows("fix the flaky test");
+        h.type_text("!");
+        h.assert_shows("fix the flaky test!");
+    }
+
+    #[test]
+    fn test_chat_markdown_and_raw_toggle() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.refresh();
+        h.key(KeyCode::Char('1'));
+        h.key(KeyCode::Enter);
+        h.key(KeyCode::F(2));
+        assert!(matches!(h.app.state.mode, Mode::Chat));
+        h.app.state.chat_history.push(crate::pod::ChatMessage {
+            sender: "lead".to_string(),
+            content: "Run **cargo test**:\n- first\n```sh\nmake\n```".to_string(),
+            timestamp: chrono::Utc::now(),
+            pod: "api".to_string(),
+            member: "lead".to_string(),
+        });
+        h.assert_shows("[lead] Run cargo test:");
+        h.assert_shows("\u{2022} first");
+        h.key_with(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        h.assert_shows("[lead] Run **cargo test**:");
+        h.assert_shows("- first");
+    }
+
+    #[test]
+    fn test_chat_scroll_and_new_messages() {
+        let mut h = Harness::new(100, 24);
+        let pane = h.pod("api", "❯ ");
+        h.refresh();
+        h.key(KeyCode::Char('1'));
+        h.key(KeyCode::Enter);
+        h.key(KeyCode::F(2));
+        h.type_text("explain");
+        h.key(KeyCode::Enter);
+        let answer: Vec<String> = (1..=40).map(|i| format!("line {}", i)).collect();
+        h.tmux.set_output(&pane, &format!("❯ \n{}", answer.join("\n")));
+        h.app.refresh_chat_output();
+        h.assert_shows("line 40");
+        h.assert_hides("line 1\n");
+
+        h.key(KeyCode::PageUp);
+        h.key(KeyCode::PageUp);
+        h.assert_hides("line 40");
+        h.assert_shows("more below");
+
+        // 次の質問を送ると最新に戻る
+        h.type_text("more");
+        h.key(KeyCode::Enter);
+        assert_eq!(h.app.state.chat_scroll, 0);
+
+        // さかのぼっている間に届いた応答は表示位置を動かさず件数だけ出す
+        h.key(KeyCode::PageUp);
+        h.tmux.set_output(&pane, &format!("❯ \n{}\nsecond\nanswer", answer.join("\n")));
+        h.app.refresh_chat_output();
+        assert_eq!(h.app.state.chat_scroll, 12);
+        h.assert_shows("1 new message");
+        h.assert_hides("answer");
+
+        h.key(KeyCode::PageDown);
+        h.key(KeyCode::PageDown);
+        h.key(KeyCode::PageDown);
+        assert_eq!(h.app.state.chat_unread, 0);
+        h.assert_hides("new message");
+    }
+
+    #[test]
+    fn test_browser_marks_repositories() {
+        let mut h = Harness::new(120, 20);
+        let root = h.project.join("repos");
+        for (name, head) in [("api", "ref: refs/heads/main\n"), ("web", "0123456789abcdef\n")] {
+            std::fs::create_dir_all(root.join(name).join(".git")).unwrap();
+            std::fs::write(root.join(name).join(".git/HEAD"), head).unwrap();
+        }
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        let api = root.join("api");
+        h.app.project_store.register(&crate::project::Project::new("api", api.to_string_lossy())).unwrap();
+        h.app.open_browser(Some(&root.to_string_lossy()));
+        h.assert_shows("\u{25cf} api");
+        h.assert_shows("\u{2387} web");
+        h.assert_shows("/ notes");
+        h.assert_shows("\u{2387} main  \u{25cf} project api");
+
+        // 普通のディレクトリではフッターを出さない
+        h.key(KeyCode::Char('j'));
+        h.assert_hides("project api");
+        h.key(KeyCode::Char('j'));
+        h.assert_shows("\u{2387} 0123456  not registered");
+
+        // n で作ったディレクトリが選ばれる
+        h.key(KeyCode::Char('n'));
+        h.assert_shows("New dir:");
+        h.type_text("zeta");
+        h.key(KeyCode::Enter);
+        assert!(root.join("zeta").is_dir());
+        assert_eq!(h.app.state.browser_state.as_ref().unwrap().selected, 3);
+    }
+
+    #[test]
+    fn test_recent_workspaces() {
+        let mut h = Harness::new(120, 20);
+        for name in ["api", "web", "docs"] {
+            h.app.project_store.register(&crate::project::Project::new(name, format!("/src/{}", name))).unwrap();
+        }
+        let current = |h: &Harness| h.app.state.current_project.as_ref().map(|p| p.name.clone());
+        for name in ["web", "api"] {
+            h.key(KeyCode::Char('w'));
+            h.type_text(name);
+            h.key(KeyCode::Enter);
+        }
+        assert_eq!(h.app.state.recent_workspaces, ["api", "web"]);
+        assert_eq!(h.app.ui_state().recent_workspaces, ["api", "web"]);
+
+        // w Enter で 1 つ前のワークスペースに戻る
+        h.key(KeyCode::Char('w'));
+        h.assert_shows("\u{21ba} web");
+        h.key(KeyCode::Enter);
+        assert_eq!(current(&h).as_deref(), Some("web"));
+        assert_eq!(h.app.state.recent_workspaces, ["web", "api"]);
+
+        // ブラウザの上端に並び、数字で選べる
+        h.key(KeyCode::Char('p'));
+        h.assert_shows("Recent 1 web 2 api");
+        h.key(KeyCode::Char('2'));
+        assert!(h.app.state.browser_state.is_none());
+        assert_eq!(current(&h).as_deref(), Some("api"));
+    }
+
+    #[test]
+    fn test_adopt_picker() {
+        let mut h = Harness::new(120, 20);
+        h.key(KeyCode::Char('a'));
+        assert!(h.app.state.adopt_picker.is_none());
+        h.assert_shows("No tmux sessions to adopt");
+
+        h.pod("api", "❯ ");
+        h.tmux.add_session("scratch", Some("/tmp/scratch"));
+        let pane = h.tmux.add_session("review", Some("/src/review"));
+        h.tmux.set_output(&pane, "✻ Thinking… (esc to interrupt)\n❯ ");
+        h.key(KeyCode::Char('a'));
+        h.assert_shows("Adopt Session (2/2)");
+        // Claude Code らしいセッションが先頭で、Pod のセッションは出ない
+        h.assert_shows("\u{273b} review");
+        h.assert_hides(" api ");
+        // 左に選択中のセッションの画面
+        h.assert_shows(&format!("Preview {}", pane));
+        h.assert_shows("Thinking… (esc to interrupt)");
+        h.key(KeyCode::Down);
+        h.assert_hides("Thinking…");
+        h.tmux.set_output(&pane, "$ make test");
+        h.key(KeyCode::Up);
+        h.assert_shows("$ make test");
+
+        h.type_text("scra");
+        h.assert_shows("Adopt Session (1/2)");
+        h.key(KeyCode::Enter);
+        assert!(h.app.state.adopt_picker.is_none());
+        assert_eq!(h.app.state.focused_pod().map(|p| p.name.as_str()), Some("scratch"));
+        h.assert_shows("Session 'scratch' adopted");
+    }
+
+    #[test]
+    fn test_attach_modes() {
+        let mut h = Harness::new(100, 24);
+        h.pod("api", "❯ ");
+        h.app.state.focus = Some(0);
+        assert!(matches!(h.key(KeyCode::Char('t')), Action::AttachTmux(s) if s == "api"));
+        assert!(matches!(h.key(KeyCode::Char('T')), Action::AttachPopup(s) if s == "api"));
+        h.app.config.ui.attach = crate::config::AttachMode::Popup;
+        assert!(matches!(h.key(KeyCode::Char('t')), Action::AttachPopup(s) if s == "api"));
+    }
+}
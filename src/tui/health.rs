@@ -0,0 +1,137 @@
+//! ワークスペースのプロジェクトの概要 (Home の左ペイン、Pod にフォーカスしていないとき)。
+//! git を起動するので App が間隔をあけて集め、描画ではそれを使う
+
+use chrono::{Duration, Utc};
+
+use crate::pod::{Pod, PodStatus};
+use crate::project::Project;
+use crate::store::events::{EventKind, EventRecord, EventStore};
+
+/// 最近の出来事として出す数
+const RECENT_EVENTS: usize = 5;
+
+/// 最近の出来事を探す範囲
+const RECENT_DAYS: i64 = 7;
+
+/// 数える順 (手のかかる状態を先に)
+const STATUS_ORDER: [PodStatus; 8] = [
+    PodStatus::Permission,
+    PodStatus::Error,
+    PodStatus::AwaitingInput,
+    PodStatus::RateLimited,
+    PodStatus::Working,
+    PodStatus::Idle,
+    PodStatus::Done,
+    PodStatus::Dead,
+];
+
+/// apiary が Pod のために作った worktree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeInfo {
+    /// 登録されたプロジェクト名 (<project>-<pod>)
+    pub name: String,
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectHealth {
+    pub project: String,
+    pub branch: Option<String>,
+    /// 変更のあるファイル数 (git リポジトリでなければ None)
+    pub dirty: Option<usize>,
+    pub worktrees: Vec<WorktreeInfo>,
+    /// 古い順
+    pub recent: Vec<EventRecord>,
+}
+
+impl ProjectHealth {
+    /// project の状態を集める。projects は登録済みのプロジェクト (worktree を探す)
+    pub fn collect(project: &Project, projects: &[Project], events: &EventStore) -> Self {
+        let worktrees: Vec<WorktreeInfo> = projects
+            .iter()
+            .filter(|p| p.worktree_of.as_deref() == Some(project.name.as_str()))
+            .map(|p| WorktreeInfo { name: p.name.clone(), branch: crate::project::current_branch(&p.path) })
+            .collect();
+        let mut recent: Vec<EventRecord> = events
+            .load_since(Utc::now() - Duration::days(RECENT_DAYS))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|e| {
+                e.project.as_deref().is_some_and(|p| p == project.name || worktrees.iter().any(|w| w.name == p))
+            })
+            .collect();
+        recent.drain(..recent.len().saturating_sub(RECENT_EVENTS));
+        Self {
+            project: project.name.clone(),
+            branch: crate::project::current_branch(&project.path),
+            dirty: crate::project::dirty_files(&project.path),
+            worktrees,
+            recent,
+        }
+    }
+
+    /// pod がこのプロジェクト (か、その worktree) のものか
+    pub fn owns(&self, pod: &Pod) -> bool {
+        pod.project.as_deref().is_some_and(|p| p == self.project || self.worktrees.iter().any(|w| w.name == p))
+    }
+
+    /// 状態ごとの Pod の数 (0 の状態は除く)
+    pub fn pod_counts(&self, pods: &[Pod]) -> Vec<(PodStatus, usize)> {
+        STATUS_ORDER
+            .iter()
+            .map(|status| (status.clone(), pods.iter().filter(|p| self.owns(p) && p.status == *status).count()))
+            .filter(|(_, n)| *n > 0)
+            .collect()
+    }
+}
+
+/// 出来事の短い説明 ("working → idle")
+pub fn describe_event(kind: &EventKind) -> String {
+    match kind {
+        EventKind::Created => "created".to_string(),
+        EventKind::Dropped => "dropped".to_string(),
+        EventKind::Status { from, to } => {
+            format!("{} \u{2192} {}", from.label().to_lowercase(), to.label().to_lowercase())
+        }
+        EventKind::PermissionApproved => "permission approved".to_string(),
+        EventKind::PermissionDenied => "permission denied".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod(name: &str, project: &str, status: PodStatus) -> Pod {
+        Pod { status, project: Some(project.to_string()), ..Pod::named(name) }
+    }
+
+    #[test]
+    fn test_collect_and_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = EventStore::with_path(dir.path().join("events.jsonl"));
+        let api = Project::new("api", dir.path().to_string_lossy());
+        let worktree = Project { worktree_of: Some("api".to_string()), ..Project::new("api-fix", "/nonexistent/api-fix") };
+        let projects = vec![api.clone(), worktree, Project::new("web", "/nonexistent/web")];
+        for (name, project) in [("a", "api"), ("b", "web"), ("c", "api-fix")] {
+            events.append(&EventRecord::new(&pod(name, project, PodStatus::Idle), EventKind::Created)).unwrap();
+        }
+
+        let health = ProjectHealth::collect(&api, &projects, &events);
+        assert_eq!(health.worktrees, [WorktreeInfo { name: "api-fix".to_string(), branch: None }]);
+        let pods: Vec<&str> = health.recent.iter().map(|e| e.pod.as_str()).collect();
+        assert_eq!(pods, ["a", "c"]);
+
+        let pods = [
+            pod("a", "api", PodStatus::Working),
+            pod("b", "web", PodStatus::Working),
+            pod("c", "api-fix", PodStatus::Permission),
+            pod("d", "api", PodStatus::Working),
+        ];
+        assert_eq!(health.pod_counts(&pods), [(PodStatus::Permission, 1), (PodStatus::Working, 2)]);
+        assert_eq!(
+            describe_event(&EventKind::Status { from: PodStatus::Working, to: PodStatus::Idle }),
+            "working \u{2192} idle"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! `[[highlight]]` ルールの適用。行の文字列全体で正規表現を探し、
+//! 一致した範囲の Span を分割して色 (と太字) を付ける
+
+use ratatui::style::{Color, Modifier};
+use ratatui::text::{Line, Span};
+
+use crate::config::HighlightRule;
+
+/// line に rules を適用する。範囲が重なるときは先に書いたルールが勝つ
+pub fn apply(line: Line<'static>, rules: &[HighlightRule]) -> Line<'static> {
+    if rules.is_empty() {
+        return line;
+    }
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    // バイトごとに一致したルールの番号 (一致は文字境界で始まり終わるので、切れ目も文字境界になる)
+    let mut marks: Vec<Option<usize>> = vec![None; text.len()];
+    for (i, rule) in rules.iter().enumerate() {
+        for m in rule.regex.find_iter(&text) {
+            for mark in &mut marks[m.range()] {
+                mark.get_or_insert(i);
+            }
+        }
+    }
+    if marks.iter().all(Option::is_none) {
+        return line;
+    }
+
+    let Line { spans: original, style, alignment } = line;
+    let mut spans = Vec::with_capacity(original.len());
+    let mut offset = 0;
+    for span in original {
+        let content = span.content.as_ref();
+        let mut start = 0;
+        while start < content.len() {
+            let mark = marks[offset + start];
+            let mut end = start + 1;
+            while end < content.len() && marks[offset + end] == mark {
+                end += 1;
+            }
+            let mut piece_style = span.style;
+            if let Some(rule) = mark.map(|i| &rules[i]) {
+                let (r, g, b) = rule.color;
+                piece_style = piece_style.fg(Color::Rgb(r, g, b));
+                if rule.bold {
+                    piece_style = piece_style.add_modifier(Modifier::BOLD);
+                }
+            }
+            spans.push(Span::styled(content[start..end].to_string(), piece_style));
+            start = end;
+        }
+        offset += content.len();
+    }
+    Line { spans, style, alignment }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Style;
+
+    fn rule(pattern: &str, color: (u8, u8, u8), bold: bool) -> HighlightRule {
+        HighlightRule { regex: regex::Regex::new(pattern).unwrap(), color, bold }
+    }
+
+    #[test]
+    fn test_apply_splits_spans() {
+        let rules = [rule("FAIL", (255, 0, 0), true), rule(r"src/\S+", (0, 0, 255), false)];
+        // Detail の行のように 1 文字ずつの Span でも、カードのように 1 つの Span でも同じ結果
+        let cells: Vec<Span> = "ok FAIL src/a.rs".chars().map(|c| Span::raw(c.to_string())).collect();
+        for line in [Line::from(cells), Line::from("ok FAIL src/a.rs")] {
+            let line = apply(line, &rules);
+            let red: String = line.spans.iter().filter(|s| s.style.fg == Some(Color::Rgb(255, 0, 0))).map(|s| s.content.as_ref()).collect();
+            let blue: String = line.spans.iter().filter(|s| s.style.fg == Some(Color::Rgb(0, 0, 255))).map(|s| s.content.as_ref()).collect();
+            assert_eq!(red, "FAIL");
+            assert_eq!(blue, "src/a.rs");
+            let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+            assert_eq!(text, "ok FAIL src/a.rs");
+        }
+    }
+
+    #[test]
+    fn test_apply_keeps_background_and_first_rule_wins() {
+        let rules = [rule("warn", (1, 1, 1), false), rule("warning:", (2, 2, 2), false)];
+        let line = apply(Line::from(Span::styled("日本 warning: x", Style::default().bg(Color::Blue))), &rules);
+        let parts: Vec<(&str, Option<Color>)> = line.spans.iter().map(|s| (s.content.as_ref(), s.style.fg)).collect();
+        assert_eq!(
+            parts,
+            [("日本 ", None), ("warn", Some(Color::Rgb(1, 1, 1))), ("ing:", Some(Color::Rgb(2, 2, 2))), (" x", None)]
+        );
+        assert!(line.spans.iter().all(|s| s.style.bg == Some(Color::Blue)));
+    }
+}
@@ -0,0 +1,396 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
+
+/// 1 行テキスト入力のエディタ (inline_input / chat_input 共通)
+///
+/// cursor はバイト位置で、常に char 境界を指す。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// カーソル位置 (バイト)
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// カーソル手前までの表示幅 (CJK は 2 カラム)
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.cursor].width()
+    }
+
+    /// (カーソル手前, カーソル位置の文字, カーソル以降) に分割 (描画用)
+    pub fn split_at_cursor(&self) -> (&str, Option<&str>, &str) {
+        let (before, rest) = self.text.split_at(self.cursor);
+        match rest.chars().next() {
+            Some(c) => {
+                let (at, after) = rest.split_at(c.len_utf8());
+                (before, Some(at), after)
+            }
+            None => (before, None, ""),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// テキストを置き換えてカーソルを末尾へ
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.len();
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(prev) = self.prev_boundary() {
+            self.text.replace_range(prev..self.cursor, "");
+            self.cursor = prev;
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if let Some(next) = self.next_boundary() {
+            self.text.replace_range(self.cursor..next, "");
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if let Some(prev) = self.prev_boundary() {
+            self.cursor = prev;
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if let Some(next) = self.next_boundary() {
+            self.cursor = next;
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// 前の単語の先頭へ (空白区切り)
+    pub fn word_left(&mut self) {
+        self.cursor = self.word_start_before();
+    }
+
+    /// 次の単語の末尾へ (空白区切り)
+    pub fn word_right(&mut self) {
+        let rest = &self.text[self.cursor..];
+        let skipped_ws = rest.len() - rest.trim_start().len();
+        let word = rest[skipped_ws..]
+            .find(char::is_whitespace)
+            .unwrap_or(rest.len() - skipped_ws);
+        self.cursor += skipped_ws + word;
+    }
+
+    /// Ctrl+W: カーソル手前の単語を削除
+    pub fn delete_word_before(&mut self) {
+        let start = self.word_start_before();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Ctrl+U: カーソル手前を全て削除
+    pub fn delete_to_start(&mut self) {
+        self.text.replace_range(..self.cursor, "");
+        self.cursor = 0;
+    }
+
+    /// Ctrl+K: カーソル以降を全て削除
+    pub fn delete_to_end(&mut self) {
+        self.text.truncate(self.cursor);
+    }
+
+    /// start からカーソルまでを text で置き換える (補完用)
+    pub fn replace_before_cursor(&mut self, start: usize, text: &str) {
+        self.text.replace_range(start..self.cursor, text);
+        self.cursor = start + text.len();
+    }
+
+    /// 編集キーを処理。処理した場合 true (Enter / Esc などは呼び出し側で扱う)
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Left if ctrl || alt => self.word_left(),
+            KeyCode::Right if ctrl || alt => self.word_right(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            KeyCode::Backspace if ctrl || alt => self.delete_word_before(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Char('w') if ctrl => self.delete_word_before(),
+            KeyCode::Char('u') if ctrl => self.delete_to_start(),
+            KeyCode::Char('k') if ctrl => self.delete_to_end(),
+            KeyCode::Char('b') if alt => self.word_left(),
+            KeyCode::Char('f') if alt => self.word_right(),
+            KeyCode::Char(c) if !ctrl && !alt => self.insert_char(c),
+            _ => return false,
+        }
+        true
+    }
+
+    fn prev_boundary(&self) -> Option<usize> {
+        self.text[..self.cursor].char_indices().next_back().map(|(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> Option<usize> {
+        self.text[self.cursor..].chars().next().map(|c| self.cursor + c.len_utf8())
+    }
+
+    fn word_start_before(&self) -> usize {
+        let before = self.text[..self.cursor].trim_end();
+        before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0)
+    }
+}
+
+/// 補完の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// 先頭の `/command`
+    Command,
+    /// 末尾の `@project`
+    Project,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    /// 説明 (コマンドの概要 / プロジェクトのパス)
+    pub detail: String,
+}
+
+/// 左ペイン入力の補完ポップアップ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    /// 置き換える token の開始位置 (バイト、`/` `@` の直後)
+    pub start: usize,
+    pub items: Vec<CompletionItem>,
+    pub selected: usize,
+}
+
+impl Completion {
+    /// カーソル手前のテキストから補完対象を取り出す: (種類, token 開始位置, 入力済みの prefix)
+    pub fn target(before_cursor: &str) -> Option<(CompletionKind, usize, &str)> {
+        if let Some(rest) = before_cursor.strip_prefix('/') {
+            if !rest.contains(char::is_whitespace) {
+                return Some((CompletionKind::Command, 1, rest));
+            }
+        }
+        let at = before_cursor.rfind('@')?;
+        let prefix = &before_cursor[at + 1..];
+        // "@" の直前は行頭か空白 (メールアドレス等は対象外)
+        let at_word_start = before_cursor[..at].chars().next_back().is_none_or(char::is_whitespace);
+        if at_word_start && !prefix.contains(char::is_whitespace) {
+            return Some((CompletionKind::Project, at + 1, prefix));
+        }
+        None
+    }
+
+    /// 候補を組み立てる。前方一致を先に、その後に部分一致 (大文字小文字は無視)。
+    /// 候補が無ければ None
+    pub fn build(editor: &LineEditor, commands: &[(&str, &str)], projects: &[(String, String)]) -> Option<Self> {
+        let (kind, start, prefix) = Self::target(&editor.as_str()[..editor.cursor()])?;
+        let candidates: Vec<(&str, &str)> = match kind {
+            CompletionKind::Command => commands.to_vec(),
+            CompletionKind::Project => projects.iter().map(|(n, p)| (n.as_str(), p.as_str())).collect(),
+        };
+        let needle = prefix.to_lowercase();
+        let (mut items, contains): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .filter(|(label, _)| label.to_lowercase().contains(&needle))
+            .map(|(label, detail)| CompletionItem { label: label.to_string(), detail: detail.to_string() })
+            .partition(|item| item.label.to_lowercase().starts_with(&needle));
+        items.extend(contains);
+        if items.is_empty() {
+            return None;
+        }
+        Some(Self { kind, start, items, selected: 0 })
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len();
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+    }
+
+    /// 入力済みの token が選択中の候補と完全に一致しているか (Enter をそのまま確定に回す)
+    pub fn is_exact(&self, editor: &LineEditor) -> bool {
+        editor.as_str().get(self.start..editor.cursor()) == Some(self.items[self.selected].label.as_str())
+    }
+
+    /// 選択中の候補で token を置き換える (コマンドは後ろに空白を足して引数入力へ)
+    pub fn apply(&self, editor: &mut LineEditor) {
+        let label = &self.items[self.selected].label;
+        let text = match self.kind {
+            CompletionKind::Command => format!("{} ", label),
+            CompletionKind::Project => label.clone(),
+        };
+        editor.replace_before_cursor(self.start, &text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> LineEditor {
+        let mut e = LineEditor::new();
+        e.set(text);
+        e
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_insert_in_middle() {
+        let mut e = editor("held");
+        e.move_left();
+        e.move_left();
+        e.insert_str("llo wor");
+        assert_eq!(e.as_str(), "hello world");
+        assert_eq!(e.cursor(), "hello wor".len());
+    }
+
+    #[test]
+    fn test_backspace_and_delete_respect_char_boundaries() {
+        let mut e = editor("日本語");
+        e.move_left();
+        e.backspace();
+        assert_eq!(e.as_str(), "日語");
+        e.delete();
+        assert_eq!(e.as_str(), "日");
+        assert_eq!(e.cursor_width(), 2);
+    }
+
+    #[test]
+    fn test_word_motion() {
+        let mut e = editor("fix the  login bug");
+        e.word_left();
+        assert_eq!(&e.as_str()[e.cursor()..], "bug");
+        e.word_left();
+        assert_eq!(&e.as_str()[e.cursor()..], "login bug");
+        e.home();
+        e.word_right();
+        assert_eq!(&e.as_str()[..e.cursor()], "fix");
+        e.word_right();
+        assert_eq!(&e.as_str()[..e.cursor()], "fix the");
+    }
+
+    #[test]
+    fn test_kill_commands() {
+        let mut e = editor("fix the bug ");
+        e.handle_key(&key(KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(e.as_str(), "fix the ");
+
+        e.home();
+        e.word_right();
+        e.handle_key(&key(KeyCode::Char('k'), KeyModifiers::CONTROL));
+        assert_eq!(e.as_str(), "fix");
+
+        e.move_left();
+        e.handle_key(&key(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(e.as_str(), "x");
+        assert_eq!(e.cursor(), 0);
+    }
+
+    #[test]
+    fn test_handle_key_ignores_unbound_control_chars() {
+        let mut e = editor("abc");
+        assert!(!e.handle_key(&key(KeyCode::Char('e'), KeyModifiers::CONTROL)));
+        assert!(!e.handle_key(&key(KeyCode::Enter, KeyModifiers::NONE)));
+        assert!(e.handle_key(&key(KeyCode::Char('d'), KeyModifiers::SHIFT)));
+        assert_eq!(e.as_str(), "abcd");
+    }
+
+    #[test]
+    fn test_split_at_cursor() {
+        let mut e = editor("aあb");
+        e.move_left();
+        e.move_left();
+        assert_eq!(e.split_at_cursor(), ("a", Some("あ"), "b"));
+        e.end();
+        assert_eq!(e.split_at_cursor(), ("aあb", None, ""));
+    }
+
+    #[test]
+    fn test_completion_target() {
+        assert_eq!(Completion::target("/pro"), Some((CompletionKind::Command, 1, "pro")));
+        assert_eq!(Completion::target("/project list"), None);
+        assert_eq!(Completion::target("fix login @ap"), Some((CompletionKind::Project, 11, "ap")));
+        assert_eq!(Completion::target("@"), Some((CompletionKind::Project, 1, "")));
+        assert_eq!(Completion::target("mail foo@example"), None);
+        assert_eq!(Completion::target("fix @api now"), None);
+    }
+
+    #[test]
+    fn test_completion_build_and_apply() {
+        let commands = [("create", "Create a pod"), ("restore", "Restore"), ("drop", "Drop")];
+        let projects = vec![
+            ("web".to_string(), "/src/web".to_string()),
+            ("apiary".to_string(), "/src/apiary".to_string()),
+            ("my-api".to_string(), "/src/my-api".to_string()),
+        ];
+
+        let mut e = editor("/re");
+        let completion = Completion::build(&e, &commands, &projects).unwrap();
+        // 前方一致 (restore) が部分一致 (create) より先
+        let labels: Vec<&str> = completion.items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["restore", "create"]);
+        completion.apply(&mut e);
+        assert_eq!(e.as_str(), "/restore ");
+
+        let mut e = editor("add tests @API");
+        let mut completion = Completion::build(&e, &commands, &projects).unwrap();
+        assert_eq!(completion.items.len(), 2);
+        completion.select_next();
+        completion.apply(&mut e);
+        assert_eq!(e.as_str(), "add tests @my-api");
+        assert!(Completion::build(&e, &commands, &projects).unwrap().is_exact(&e));
+
+        assert!(Completion::build(&editor("/zzz"), &commands, &projects).is_none());
+    }
+}
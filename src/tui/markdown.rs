@@ -0,0 +1,141 @@
+//! Chat の応答向けの軽量な Markdown 描画。
+//! 太字 (**x**)、コードスパン (`x`)、見出し、箇条書き、フェンス付きコードブロックだけを扱い、
+//! それ以外の記法はそのまま表示する
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// コードブロックの背景
+const CODE_BG: Color = Color::Rgb(38, 40, 52);
+
+/// text を行ごとの Line にする
+pub fn render(text: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            lines.push(Line::from(Span::styled(
+                line.to_string(),
+                Style::default().fg(Color::DarkGray),
+            )));
+        } else if in_code {
+            lines.push(Line::from(Span::styled(
+                line.to_string(),
+                Style::default().fg(Color::White).bg(CODE_BG),
+            )));
+        } else if let Some(heading) = heading(trimmed) {
+            lines.push(Line::from(Span::styled(
+                heading.to_string(),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            let indent = &line[..line.len() - trimmed.len()];
+            let mut spans = vec![Span::raw(format!("{}\u{2022} ", indent))];
+            spans.extend(inline(item));
+            lines.push(Line::from(spans));
+        } else {
+            lines.push(Line::from(inline(line)));
+        }
+    }
+    lines
+}
+
+/// "# Title" .. "###### Title" の見出し本文
+fn heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level) {
+        line[level..].strip_prefix(' ')
+    } else {
+        None
+    }
+}
+
+/// 1 行の中の **太字** と `コード` を Span に分ける (閉じていない記号はそのまま)
+fn inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (marker, style) = if rest.starts_with("**") {
+            ("**", Style::default().add_modifier(Modifier::BOLD))
+        } else if rest.starts_with('`') {
+            ("`", Style::default().fg(Color::Yellow).bg(CODE_BG))
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+        let body = &rest[marker.len()..];
+        match body.find(marker).filter(|&end| end > 0) {
+            Some(end) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(body[..end].to_string(), style));
+                rest = &body[end + marker.len()..];
+            }
+            None => {
+                plain.push_str(marker);
+                rest = body;
+            }
+        }
+    }
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_inline() {
+        let spans = inline("run **cargo test** then `git push` and a*b");
+        let parts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(
+            parts,
+            ["run ", "cargo test", " then ", "git push", " and a*b"]
+        );
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans[3].style.bg, Some(CODE_BG));
+        // 閉じていない記号はそのまま
+        let parts: Vec<String> = inline("2 ** 3 and `x")
+            .iter()
+            .map(|s| s.content.to_string())
+            .collect();
+        assert_eq!(parts.concat(), "2 ** 3 and `x");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let lines = render("## Plan\n- fix **bug**\n```rust\nlet x = `a`;\n```\ndone");
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            texts,
+            [
+                "Plan",
+                "\u{2022} fix bug",
+                "```rust",
+                "let x = `a`;",
+                "```",
+                "done"
+            ]
+        );
+        assert_eq!(lines[3].spans[0].style.bg, Some(CODE_BG));
+        assert_eq!(lines[5].spans[0].style.bg, None);
+    }
+}
@@ -1,3 +1,15 @@
+pub mod adopt;
 pub mod app;
 pub mod handler;
+pub mod health;
+pub mod highlight;
+#[cfg(test)]
+pub(crate) mod harness;
+pub mod input;
+pub mod markdown;
+pub mod palette;
+pub mod popup;
+pub mod replay;
+pub mod switcher;
 pub mod ui;
+pub mod workspaces;
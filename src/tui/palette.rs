@@ -0,0 +1,185 @@
+use crossterm::event::KeyCode;
+
+use crate::tui::input::LineEditor;
+
+/// パレットから実行するアクション
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Home (右ペイン) でこのキーを押したのと同じ動作
+    Key(KeyCode),
+    /// 左ペイン入力にテキストをプリフィルして入力待ち
+    Input(&'static str),
+    /// スラッシュコマンドを即実行
+    Command(&'static str),
+}
+
+pub struct PaletteEntry {
+    pub label: &'static str,
+    /// 表示用の割り当てキー (無ければ空)
+    pub key: &'static str,
+    pub action: PaletteAction,
+}
+
+const fn entry(label: &'static str, key: &'static str, action: PaletteAction) -> PaletteEntry {
+    PaletteEntry { label, key, action }
+}
+
+/// パレットに並べる全アクション
+pub const PALETTE_ENTRIES: &[PaletteEntry] = &[
+    entry("New task (instruction)", "Tab", PaletteAction::Key(KeyCode::Tab)),
+    entry("Create pod…", "/create", PaletteAction::Input("/create ")),
+    entry("Open pod detail", "Enter", PaletteAction::Key(KeyCode::Enter)),
+    entry("Attach tmux session", "t", PaletteAction::Key(KeyCode::Char('t'))),
+    entry("Attach in a tmux popup", "T", PaletteAction::Key(KeyCode::Char('T'))),
+    entry("Next warning pod", "N", PaletteAction::Key(KeyCode::Char('N'))),
+    entry("Switch workspace…", "w", PaletteAction::Key(KeyCode::Char('w'))),
+    entry("Toggle workspace filter", "W", PaletteAction::Key(KeyCode::Char('W'))),
+    entry("Adopt session…", "a", PaletteAction::Key(KeyCode::Char('a'))),
+    entry("Adopt first unmanaged session", "A", PaletteAction::Key(KeyCode::Char('A'))),
+    entry("Drop focused pod", "d", PaletteAction::Key(KeyCode::Char('d'))),
+    entry("Restore dropped pod…", "/restore", PaletteAction::Input("/restore ")),
+    entry("Resurrect dead pods", "U", PaletteAction::Key(KeyCode::Char('U'))),
+    entry("Forget pod…", "/forget", PaletteAction::Input("/forget ")),
+    entry("Edit pod tags", "#", PaletteAction::Key(KeyCode::Char('#'))),
+    entry("Edit pod note", "e", PaletteAction::Key(KeyCode::Char('e'))),
+    entry("Add pane as member", "M", PaletteAction::Key(KeyCode::Char('M'))),
+    entry("Split member into own pod", "S", PaletteAction::Key(KeyCode::Char('S'))),
+    entry("Rename member", "R", PaletteAction::Key(KeyCode::Char('R'))),
+    entry("Send /compact to pod", "c", PaletteAction::Key(KeyCode::Char('c'))),
+    entry("Send /clear to pod…", "C", PaletteAction::Key(KeyCode::Char('C'))),
+    entry("Switch model…", "O", PaletteAction::Key(KeyCode::Char('O'))),
+    entry("Create pull request", "P", PaletteAction::Key(KeyCode::Char('P'))),
+    entry("Filter pods by tag", "f", PaletteAction::Key(KeyCode::Char('f'))),
+    entry("Pin / unpin pod", "*", PaletteAction::Key(KeyCode::Char('*'))),
+    entry("Cycle pod priority", "o", PaletteAction::Key(KeyCode::Char('o'))),
+    entry("Mute / unmute pod notifications", "m", PaletteAction::Key(KeyCode::Char('m'))),
+    entry("Toggle do not disturb", "Q", PaletteAction::Key(KeyCode::Char('Q'))),
+    entry("Select / unselect pod", "Space", PaletteAction::Key(KeyCode::Char(' '))),
+    entry("Collapse / expand group", "z", PaletteAction::Key(KeyCode::Char('z'))),
+    entry("Move pod to group…", "G", PaletteAction::Key(KeyCode::Char('G'))),
+    entry("Drop whole group", "D", PaletteAction::Key(KeyCode::Char('D'))),
+    entry("Cycle grid / list / kanban view", "v", PaletteAction::Key(KeyCode::Char('v'))),
+    entry("Toggle side-by-side / stacked layout", "L", PaletteAction::Key(KeyCode::Char('L'))),
+    entry("Hide / show left pane", "\\", PaletteAction::Key(KeyCode::Char('\\'))),
+    entry("Browse directories", "p", PaletteAction::Key(KeyCode::Char('p'))),
+    entry("List pods", "/list", PaletteAction::Command("list")),
+    entry("List projects", "/project list", PaletteAction::Command("project list")),
+    entry("Add project…", "/project add", PaletteAction::Input("/project add ")),
+    entry("Toggle help", "?", PaletteAction::Key(KeyCode::Char('?'))),
+    entry("Quit", "q", PaletteAction::Key(KeyCode::Char('q'))),
+];
+
+/// Ctrl+P のコマンドパレット
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub query: LineEditor,
+    pub selected: usize,
+}
+
+impl Palette {
+    /// クエリに合う項目をスコア順に返す (同点は定義順)
+    pub fn matches(&self) -> Vec<&'static PaletteEntry> {
+        let query = self.query.as_str();
+        let mut scored: Vec<(i64, &'static PaletteEntry)> = PALETTE_ENTRIES
+            .iter()
+            .filter_map(|e| fuzzy_score(query, e.label).map(|score| (score, e)))
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, e)| e).collect()
+    }
+
+    pub fn selected_entry(&self) -> Option<&'static PaletteEntry> {
+        self.matches().get(self.selected).copied()
+    }
+
+    pub fn select_next(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+}
+
+/// あいまい一致のスコア。query の文字が candidate に順番どおり現れなければ None。
+/// 連続一致と単語先頭での一致を高く評価する (大文字小文字は無視)
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if c != query[qi] {
+            continue;
+        }
+        score += 1;
+        if prev_match.is_some_and(|p| p + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !chars[i - 1].is_alphanumeric() {
+            score += 8;
+        }
+        prev_match = Some(i);
+        qi += 1;
+    }
+    if qi < query.len() {
+        return None;
+    }
+    // 同程度なら短い候補を優先
+    Some(score * 100 - chars.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_subsequence() {
+        assert!(fuzzy_score("atm", "Attach tmux session").is_some());
+        assert!(fuzzy_score("xyz", "Attach tmux session").is_none());
+        assert!(fuzzy_score("", "Quit").is_some());
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_word_starts_and_runs() {
+        let word_start = fuzzy_score("ep", "Edit pod tags").unwrap();
+        let scattered = fuzzy_score("ep", "Create pod…").unwrap();
+        assert!(word_start > scattered);
+
+        let run = fuzzy_score("view", "Cycle grid / list / kanban view").unwrap();
+        let spread = fuzzy_score("view", "Browse directories via tmux").unwrap_or(i64::MIN);
+        assert!(run > spread);
+    }
+
+    #[test]
+    fn test_palette_matches_and_selection() {
+        let mut palette = Palette::default();
+        // 空クエリは定義順のまま全件
+        assert_eq!(palette.matches().len(), PALETTE_ENTRIES.len());
+        assert_eq!(palette.selected_entry().unwrap().label, PALETTE_ENTRIES[0].label);
+
+        palette.query.set("attach");
+        assert_eq!(palette.selected_entry().unwrap().key, "t");
+
+        palette.query.set("pod");
+        let first = palette.selected_entry().unwrap().label;
+        palette.select_next();
+        assert_ne!(palette.selected_entry().unwrap().label, first);
+        palette.select_prev();
+        assert_eq!(palette.selected_entry().unwrap().label, first);
+    }
+}
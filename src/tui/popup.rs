@@ -0,0 +1,166 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+use crate::pod::{IconSet, Pod, PodStatus};
+use crate::tui::app::App;
+
+/// `apiary popup` のキー処理結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PopupAction {
+    None,
+    Render,
+    Close,
+    /// このセッションへ switch-client して閉じる
+    Switch(String),
+}
+
+/// tmux の display-popup 内で動く最小限の Pod ピッカー
+#[derive(Debug, Clone, Default)]
+pub struct Popup {
+    pub selected: usize,
+    pub message: Option<String>,
+}
+
+impl Popup {
+    /// 表示順: 要対応 (Permission / Error) を先頭に、状態の優先度 → 名前順
+    pub fn order(pods: &[Pod]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..pods.len()).collect();
+        order.sort_by(|&a, &b| {
+            pods[b].status.priority().cmp(&pods[a].status.priority())
+                .then_with(|| (pods[a].status == PodStatus::Dead).cmp(&(pods[b].status == PodStatus::Dead)))
+                .then_with(|| pods[a].name.cmp(&pods[b].name))
+        });
+        order
+    }
+
+    fn selected_pod(&self, pods: &[Pod]) -> Option<usize> {
+        Self::order(pods).get(self.selected).copied()
+    }
+
+    pub fn handle_key(&mut self, app: &mut App, key: KeyEvent) -> PopupAction {
+        let len = app.state.pods.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => PopupAction::Close,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => PopupAction::Close,
+            KeyCode::Down | KeyCode::Char('j') => {
+                if len > 0 {
+                    self.selected = (self.selected + 1) % len;
+                }
+                PopupAction::Render
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                if len > 0 {
+                    self.selected = (self.selected + len - 1) % len;
+                }
+                PopupAction::Render
+            }
+            KeyCode::Enter => match self.selected_pod(&app.state.pods) {
+                Some(idx) => PopupAction::Switch(app.state.pods[idx].tmux_session.clone()),
+                None => PopupAction::Render,
+            },
+            KeyCode::Char('a') | KeyCode::Char('d') => {
+                let Some(idx) = self.selected_pod(&app.state.pods) else {
+                    return PopupAction::Render;
+                };
+                let name = app.state.pods[idx].name.clone();
+                app.state.focus = Some(idx);
+                let approve = key.code == KeyCode::Char('a');
+                let result = if approve { app.approve_permission() } else { app.deny_permission() };
+                self.message = Some(match result {
+                    Ok(()) if approve => format!("Approved {}", name),
+                    Ok(()) => format!("Denied {}", name),
+                    Err(e) => format!("{}: {}", name, e),
+                });
+                PopupAction::Render
+            }
+            KeyCode::Char('r') => {
+                app.refresh_pod_states();
+                PopupAction::Render
+            }
+            _ => PopupAction::None,
+        }
+    }
+}
+
+pub fn draw(frame: &mut Frame, app: &App, popup: &Popup) {
+    let area = frame.area();
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let pods = &app.state.pods;
+    let order = Popup::order(pods);
+    let block = Block::default()
+        .title(format!(" apiary ({} pods) ", pods.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(rows[0]);
+    frame.render_widget(block, rows[0]);
+
+    let lines: Vec<Line> = if order.is_empty() {
+        vec![Line::from(Span::styled(" No pods", Style::default().fg(Color::DarkGray)))]
+    } else {
+        let visible = inner.height as usize;
+        let offset = popup.selected.saturating_sub(visible.saturating_sub(1));
+        order
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(row, &i)| pod_line(&pods[i], row == popup.selected, inner, app.config.ui.icon_set()))
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let footer = match popup.message {
+        Some(ref msg) => Line::from(Span::styled(format!(" {}", msg), Style::default().fg(Color::Yellow))),
+        None => Line::from(Span::styled(
+            " ↑↓ select  Enter switch  a/d approve/deny  r refresh  q close",
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    frame.render_widget(Paragraph::new(footer), rows[1]);
+}
+
+fn pod_line(pod: &Pod, selected: bool, area: Rect, icons: IconSet) -> Line<'static> {
+    let style = if selected {
+        Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let text = format!(
+        " {} {}  {}  {}",
+        icons.pad(pod.status_icon(icons)),
+        pod.name,
+        pod.status.label(),
+        pod.elapsed_time(),
+    );
+    let pad = (area.width as usize).saturating_sub(text.width());
+    Line::from(Span::styled(format!("{}{}", text, " ".repeat(pad)), style))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod(name: &str, status: PodStatus) -> Pod {
+        Pod { status, ..Pod::named(name) }
+    }
+
+    #[test]
+    fn test_order_puts_permission_first_and_dead_last() {
+        let pods = vec![
+            pod("dead", PodStatus::Dead),
+            pod("idle", PodStatus::Idle),
+            pod("ask", PodStatus::Permission),
+            pod("done", PodStatus::Done),
+        ];
+        assert_eq!(Popup::order(&pods), vec![2, 1, 3, 0]);
+    }
+}
@@ -0,0 +1,86 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+
+use crate::record::Player;
+use crate::tui::ui::render_vt100_row;
+
+/// 再生速度の上限・下限
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 16.0;
+
+/// `apiary replay` のキー処理結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayAction {
+    None,
+    Render,
+    Close,
+}
+
+/// Space で一時停止、+/- で速度を倍 / 半分、q で終了
+pub fn handle_key(player: &mut Player, key: KeyEvent) -> ReplayAction {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => ReplayAction::Close,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => ReplayAction::Close,
+        KeyCode::Char(' ') => {
+            player.paused = !player.paused;
+            ReplayAction::Render
+        }
+        KeyCode::Char('+') | KeyCode::Char('=') => {
+            player.speed = (player.speed * 2.0).min(MAX_SPEED);
+            ReplayAction::Render
+        }
+        KeyCode::Char('-') => {
+            player.speed = (player.speed / 2.0).max(MIN_SPEED);
+            ReplayAction::Render
+        }
+        _ => ReplayAction::None,
+    }
+}
+
+/// 録画の画面と、進み具合・操作のフッター
+pub fn draw(frame: &mut Frame, player: &Player) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.area());
+
+    let title = player.header.title.as_deref().unwrap_or("recording");
+    let block = Block::default()
+        .title(format!(" {} ", title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(rows[0]);
+    frame.render_widget(block, rows[0]);
+
+    // 端末が録画より小さいときはカーソルの行が見える位置までずらす
+    let (cursor_row, _) = player.screen().cursor_position();
+    let start_row = (cursor_row + 1).saturating_sub(inner.height);
+    let display_cols = inner.width.min(player.header.width);
+    let lines: Vec<Line> = (0..inner.height.min(player.header.height))
+        .map(|r| render_vt100_row(player.screen(), start_row + r, display_cols))
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+
+    let state = if player.finished() {
+        "done"
+    } else if player.paused {
+        "paused"
+    } else {
+        "playing"
+    };
+    let footer = format!(
+        " {} {:.0}s / {:.0}s  x{}  Space pause  +/- speed  q quit",
+        state,
+        player.position(),
+        player.duration(),
+        player.speed
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(footer, Style::default().fg(Color::DarkGray))),
+        rows[1],
+    );
+}
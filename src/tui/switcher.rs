@@ -0,0 +1,107 @@
+use crate::pod::Pod;
+use crate::tui::input::LineEditor;
+use crate::tui::palette::fuzzy_score;
+
+/// Ctrl+F の Pod スイッチャー (fzf 風)
+///
+/// 通常はクエリ入力中。Tab でリスト操作に切り替えると j/k で移動、t でアタッチできる。
+#[derive(Debug, Clone, Default)]
+pub struct PodSwitcher {
+    pub query: LineEditor,
+    pub selected: usize,
+    /// リスト操作中 (文字キーをクエリではなくコマンドとして扱う)
+    pub list_mode: bool,
+}
+
+impl PodSwitcher {
+    /// クエリに合う Pod の index を返す。スコア順、同点は最近使った (アタッチ・入力した) 順、
+    /// 使ったことがなければ最近動きのあった順
+    pub fn matches(&self, pods: &[Pod]) -> Vec<usize> {
+        let query = self.query.as_str();
+        let mut scored: Vec<(i64, usize)> = pods
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pod)| fuzzy_score(query, &haystack(pod)).map(|score| (score, i)))
+            .collect();
+        scored.sort_by(|(sa, a), (sb, b)| {
+            sb.cmp(sa)
+                .then_with(|| pods[*b].last_used().cmp(&pods[*a].last_used()))
+                .then_with(|| pods[*b].last_activity().cmp(&pods[*a].last_activity()))
+        });
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    pub fn selected_pod(&self, pods: &[Pod]) -> Option<usize> {
+        self.matches(pods).get(self.selected).copied()
+    }
+
+    pub fn select_next(&mut self, pods: &[Pod]) {
+        let len = self.matches(pods).len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    pub fn select_prev(&mut self, pods: &[Pod]) {
+        let len = self.matches(pods).len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+}
+
+/// 検索対象の文字列: 名前 + グループ + プロジェクト
+fn haystack(pod: &Pod) -> String {
+    [Some(pod.name.as_str()), pod.group.as_deref(), pod.project.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn pod(name: &str, group: Option<&str>, minutes_ago: i64) -> Pod {
+        Pod {
+            project: Some("apiary".to_string()),
+            group: group.map(String::from),
+            created_at: Utc::now() - Duration::minutes(minutes_ago),
+            ..Pod::named(name)
+        }
+    }
+
+    #[test]
+    fn test_empty_query_ranks_by_recency() {
+        let pods = vec![pod("old", None, 30), pod("new", None, 1), pod("mid", None, 10)];
+        let switcher = PodSwitcher::default();
+        assert_eq!(switcher.matches(&pods), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_query_filters_by_name_and_group() {
+        let pods = vec![pod("fix-login", Some("auth"), 5), pod("add-docs", None, 1), pod("token", Some("auth"), 2)];
+        let mut switcher = PodSwitcher::default();
+        switcher.query.set("auth");
+        assert_eq!(switcher.matches(&pods), vec![2, 0]);
+
+        switcher.query.set("login");
+        assert_eq!(switcher.selected_pod(&pods), Some(0));
+
+        switcher.query.set("zzz");
+        assert!(switcher.matches(&pods).is_empty());
+        switcher.select_next(&pods);
+        assert_eq!(switcher.selected, 0);
+    }
+
+    #[test]
+    fn test_recently_used_ranks_first() {
+        let mut pods = vec![pod("old", None, 30), pod("new", None, 1), pod("mid", None, 10)];
+        pods[0].last_attached = Some(Utc::now() - Duration::minutes(5));
+        pods[2].touch_input();
+        let switcher = PodSwitcher::default();
+        assert_eq!(switcher.matches(&pods), vec![2, 0, 1]);
+    }
+}
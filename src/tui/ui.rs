@@ -1,15 +1,23 @@
-use crate::pod::{format_duration, BrowserState, InlinePrompt, MemberStatus, Mode, PaneFocus, PodStatus};
+use crate::config::Config;
+use crate::pod::{format_duration, BrowserInput, BrowserState, ContextPressure, IconSet, InlinePrompt, MemberStatus, Mode, PaneFocus, PaneLayout, PodStatus, ViewMode, KANBAN_COLUMNS};
+use crate::todos::TodoStatus;
+use crate::tui::adopt::AdoptPicker;
 use crate::tui::app::App;
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use crate::tui::input::{Completion, CompletionKind, LineEditor};
+use crate::tui::highlight;
+use crate::tui::markdown;
+use crate::tui::palette::Palette;
+use crate::tui::switcher::PodSwitcher;
+use crate::tui::workspaces::WorkspaceSwitcher;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
+use ratatui::widgets::{Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Table, Wrap};
 use ratatui::Frame;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-const CARD_WIDTH: u16 = 20;
-const CARD_HEIGHT: u16 = 8;
-const CARD_GAP: u16 = 1;
+pub const CARD_GAP: u16 = 1;
 const DEAD_CARD_HEIGHT: u16 = 4;
 
 /// 文字列を指定した表示幅に切り詰める（CJK文字対応）
@@ -34,29 +42,518 @@ fn truncate_to_width(s: &str, max_width: usize) -> String {
     result
 }
 
+/// この幅未満では左右ペインを並べず、どちらか一方だけを表示する
+pub const NARROW_WIDTH: u16 = 60;
+
+/// List 表示でこの幅未満なら NAME / WORK / LAST OUTPUT 以外の列を省く
+const COMPACT_LIST_WIDTH: u16 = 70;
+
+pub fn is_narrow(width: u16) -> bool {
+    width < NARROW_WIDTH
+}
+
+/// モードごとの最低限必要な端末サイズ (cols, rows)。下回ると "too small" を表示する
+pub fn min_size(mode: &Mode) -> (u16, u16) {
+    match mode {
+        Mode::Home => (24, 8),
+        Mode::Chat => (36, 10),
+        Mode::Detail | Mode::Permission | Mode::Help => (40, 12),
+    }
+}
+
 pub fn draw(frame: &mut Frame, app: &App) {
     let area = frame.area();
 
-    // ステータスバー用に最下2行を確保
+    let (min_width, min_height) = min_size(&app.state.mode);
+    if area.width < min_width || area.height < min_height {
+        render_too_small(frame, area, min_width, min_height);
+        return;
+    }
+
+    // ステータスバー用に最下2行を確保 (アクセシビリティモードでは通知行を加えて 3 行)
+    let accessible = app.config.ui.accessible;
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(2)])
+        .constraints([Constraint::Min(3), Constraint::Length(if accessible { 3 } else { 2 })])
         .split(area);
 
-    // 左右分割 (35% / 65%)
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
-        .split(main_chunks[0]);
+    if is_narrow(area.width) || app.state.left_pane_hidden {
+        // 1 ペイン表示: 入力中・Home 以外のモードでは左ペイン、それ以外は Pod 表示を全幅で
+        let show_context = app.state.mode != Mode::Home
+            || app.state.pane_focus == PaneFocus::Left
+            || app.state.inline_prompt != InlinePrompt::None;
+        if show_context {
+            render_context_panel(frame, app, main_chunks[0]);
+        } else {
+            render_pods(frame, app, main_chunks[0]);
+        }
+    } else if app.state.layout == PaneLayout::Horizontal {
+        // 上下分割: 上に Pod 表示、下に Context Panel (高さの比率は ui.left_pane_percent)
+        let bottom = app.config.ui.left_pane_percent();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(100 - bottom), Constraint::Percentage(bottom)])
+            .split(main_chunks[0]);
+        render_pods(frame, app, chunks[0]);
+        render_context_panel(frame, app, chunks[1]);
+    } else {
+        // 左右分割 (比率は config の ui.left_pane_percent)
+        let left = app.config.ui.left_pane_percent();
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(left), Constraint::Percentage(100 - left)])
+            .split(main_chunks[0]);
+
+        // 左ペイン: Context Panel
+        render_context_panel(frame, app, chunks[0]);
+
+        // 右ペイン: Pods Grid / List
+        render_pods(frame, app, chunks[1]);
+    }
 
-    // 左ペイン: Context Panel
-    render_context_panel(frame, app, chunks[0]);
+    // ステータスバー
+    if accessible {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Length(1)])
+            .split(main_chunks[1]);
+        render_status_bar(frame, app, rows[0]);
+        render_announcement(frame, app, rows[1]);
+    } else {
+        render_status_bar(frame, app, main_chunks[1]);
+    }
+
+    // コマンドパレット / Pod スイッチャー (最前面)
+    if let Some(ref palette) = app.state.palette {
+        render_palette(frame, palette, area);
+    }
+    if let Some(ref switcher) = app.state.switcher {
+        render_switcher(frame, app, switcher, area);
+    }
+    if let Some(ref switcher) = app.state.workspace_switcher {
+        render_workspace_switcher(frame, app, switcher, area);
+    }
+    if let Some(ref picker) = app.state.adopt_picker {
+        render_adopt_picker(frame, picker, area);
+    }
 
-    // 右ペイン: Pods Grid
-    render_pods_grid(frame, app, chunks[1]);
+    if accessible {
+        ascii_decorations(frame.buffer_mut());
+    }
+}
 
-    // ステータスバー
-    render_status_bar(frame, app, main_chunks[1]);
+/// アクセシビリティモードの通知行: 直近の状態変化を 1 行の文章で出す
+fn render_announcement(frame: &mut Frame, app: &App, area: Rect) {
+    let text = match app.state.announcement {
+        Some(ref msg) => format!(" Status: {}", msg),
+        None => " Status: no changes".to_string(),
+    };
+    frame.render_widget(Paragraph::new(truncate_to_width(&text, area.width as usize)), area);
+}
+
+/// 罫線や装飾記号を ASCII に置き換える (スクリーンリーダー / dumb 端末向け)
+fn ascii_decorations(buf: &mut Buffer) {
+    let area = buf.area;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buf[(x, y)];
+            let mut chars = cell.symbol().chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                continue;
+            };
+            if let Some(ascii) = ascii_fallback(c) {
+                cell.set_char(ascii);
+            }
+        }
+    }
+}
+
+fn ascii_fallback(c: char) -> Option<char> {
+    match c {
+        '─' | '━' | '═' | '╌' | '╍' | '┄' | '┅' | '┈' | '┉' => Some('-'),
+        '│' | '┃' | '║' | '╎' | '╏' | '┆' | '┇' | '┊' | '┋' => Some('|'),
+        '\u{2500}'..='\u{257f}' => Some('+'),
+        '\u{2580}'..='\u{259f}' => Some('#'),
+        '▶' | '▸' | '›' => Some('>'),
+        '▾' => Some('v'),
+        '★' => Some('*'),
+        '✓' => Some('x'),
+        '…' => Some('.'),
+        _ => None,
+    }
+}
+
+/// Pod 表示 (Grid / List / Kanban) を描画する領域の幅
+pub fn pods_pane_width(app: &App, width: u16) -> u16 {
+    if is_narrow(width) || app.state.left_pane_hidden || app.state.layout == PaneLayout::Horizontal {
+        width
+    } else {
+        (u32::from(width) * u32::from(100 - app.config.ui.left_pane_percent()) / 100) as u16
+    }
+}
+
+/// Pod 表示を表示形式に応じて描画
+fn render_pods(frame: &mut Frame, app: &App, area: Rect) {
+    match app.state.effective_view_mode() {
+        ViewMode::Grid => render_pods_grid(frame, app, area),
+        ViewMode::List => render_pods_list(frame, app, area),
+        ViewMode::Kanban => render_pods_kanban(frame, app, area),
+    }
+}
+
+/// 端末が小さすぎるときの案内 (必要サイズと現在サイズ)
+fn render_too_small(frame: &mut Frame, area: Rect, min_width: u16, min_height: u16) {
+    let lines = vec![
+        Line::from(Span::styled("Terminal too small", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!("need {}x{}, have {}x{}", min_width, min_height, area.width, area.height),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let text_area = Rect { y: area.y + top, height: area.height - top, ..area };
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center).wrap(Wrap { trim: true }), text_area);
+}
+
+/// Ctrl+P のコマンドパレット: 画面上部中央にクエリ + 候補一覧を重ねる
+fn render_palette(frame: &mut Frame, palette: &Palette, area: Rect) {
+    let matches = palette.matches();
+    let width = area.width.saturating_sub(4).min(60);
+    let height = (matches.len() as u16 + 4).min(area.height.saturating_sub(2)).max(5);
+    if width < 20 || area.height < 5 {
+        return;
+    }
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 1,
+        width,
+        height: height.min(area.height - 1),
+    };
+
+    let block = Block::default()
+        .title(" Command Palette ")
+        .title_bottom(Line::from(" ↑↓ select  Enter run  Esc close ").right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    if inner.height < 2 {
+        return;
+    }
+
+    let mut query = vec![Span::styled("> ", Style::default().fg(Color::Cyan))];
+    query.extend(input_spans(&palette.query, Style::default().fg(Color::White), true));
+    frame.render_widget(Paragraph::new(Line::from(query)), Rect { height: 1, ..inner });
+
+    let list_area = Rect { y: inner.y + 1, height: inner.height - 1, ..inner };
+    let visible = list_area.height as usize;
+    let offset = palette.selected.saturating_sub(visible.saturating_sub(1));
+    let lines: Vec<Line> = if matches.is_empty() {
+        vec![Line::from(Span::styled(" No matching actions", Style::default().fg(Color::DarkGray)))]
+    } else {
+        matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(i, entry)| {
+                let selected = i == palette.selected;
+                let style = if selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let key_style = if selected { style } else { Style::default().fg(Color::DarkGray) };
+                let pad = (list_area.width as usize).saturating_sub(entry.label.width() + entry.key.width() + 3);
+                Line::from(vec![
+                    Span::styled(format!(" {}{}", entry.label, " ".repeat(pad)), style),
+                    Span::styled(format!(" {} ", entry.key), key_style),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), list_area);
+}
+
+/// Ctrl+F の Pod スイッチャー: 名前・グループ・状態・最終出力 1 行を一覧
+fn render_switcher(frame: &mut Frame, app: &App, switcher: &PodSwitcher, area: Rect) {
+    let pods = &app.state.pods;
+    let matches = switcher.matches(pods);
+    let width = area.width.saturating_sub(4).min(100);
+    let height = (matches.len() as u16 + 4).min(area.height.saturating_sub(2)).max(5);
+    if width < 30 || area.height < 5 {
+        return;
+    }
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 1,
+        width,
+        height: height.min(area.height - 1),
+    };
+
+    let hint = if switcher.list_mode {
+        " j/k select  Enter focus  t attach  Tab search  Esc close "
+    } else {
+        " ↑↓ select  Enter focus  ^T attach  Tab list  Esc close "
+    };
+    let block = Block::default()
+        .title(format!(" Switch Pod ({}/{}) ", matches.len(), pods.len()))
+        .title_bottom(Line::from(hint).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    if inner.height < 2 {
+        return;
+    }
+
+    let mut query = vec![Span::styled("> ", Style::default().fg(Color::Cyan))];
+    query.extend(input_spans(&switcher.query, Style::default().fg(Color::White), !switcher.list_mode));
+    frame.render_widget(Paragraph::new(Line::from(query)), Rect { height: 1, ..inner });
+
+    let list_area = Rect { y: inner.y + 1, height: inner.height - 1, ..inner };
+    let visible = list_area.height as usize;
+    let offset = switcher.selected.saturating_sub(visible.saturating_sub(1));
+    let name_width = (list_area.width as usize / 4).clamp(10, 28);
+    let group_width = (list_area.width as usize / 6).clamp(6, 16);
+    let lines: Vec<Line> = if matches.is_empty() {
+        vec![Line::from(Span::styled(" No matching pods", Style::default().fg(Color::DarkGray)))]
+    } else {
+        matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(row, &i)| {
+                let pod = &pods[i];
+                let selected = row == switcher.selected;
+                let base = if selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let dim = if selected { base } else { Style::default().fg(Color::DarkGray) };
+                let name = truncate_to_width(&pod.name, name_width);
+                let group = truncate_to_width(pod.group.as_deref().unwrap_or("-"), group_width);
+                let status = format!("{:<10}", pod.status.label());
+                let fixed = 2 + app.config.ui.icon_set().width() + name_width + 1 + group_width + 1 + status.width() + 1;
+                let preview = truncate_to_width(pod.last_output_line(), (list_area.width as usize).saturating_sub(fixed));
+                let pad = |text: &str, w: usize| format!("{}{}", text, " ".repeat(w.saturating_sub(text.width())));
+                let tail = (list_area.width as usize).saturating_sub(fixed + preview.width());
+                Line::from(vec![
+                    Span::styled(format!(" {} ", app.config.ui.icon_set().pad(pod.status_icon(app.config.ui.icon_set()))), if selected { base } else { Style::default().fg(status_color(&pod.status)) }),
+                    Span::styled(format!("{} ", pad(&name, name_width)), if selected { base } else { base.fg(accent_color(pod)) }.add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{} ", pad(&group, group_width)), dim),
+                    Span::styled(format!("{} ", status), if selected { base } else { Style::default().fg(status_color(&pod.status)) }),
+                    Span::styled(format!("{}{}", preview, " ".repeat(tail)), dim),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), list_area);
+}
+
+/// `w` のワークスペーススイッチャー: 名前・Pod 数・パス。今のワークスペースに印を付ける
+fn render_workspace_switcher(frame: &mut Frame, app: &App, switcher: &WorkspaceSwitcher, area: Rect) {
+    let matches = switcher.matches();
+    let width = area.width.saturating_sub(4).min(100);
+    let height = (matches.len() as u16 + 4).min(area.height.saturating_sub(2)).max(5);
+    if width < 30 || area.height < 5 {
+        return;
+    }
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 1,
+        width,
+        height: height.min(area.height - 1),
+    };
+
+    let block = Block::default()
+        .title(format!(" Switch Workspace ({}/{}) ", matches.len(), switcher.projects.len()))
+        .title_bottom(Line::from(" ↑↓ select  Enter switch  ^F switch + filter  Esc close ").right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    if inner.height < 2 {
+        return;
+    }
+
+    let mut query = vec![Span::styled("> ", Style::default().fg(Color::Cyan))];
+    query.extend(input_spans(&switcher.query, Style::default().fg(Color::White), true));
+    frame.render_widget(Paragraph::new(Line::from(query)), Rect { height: 1, ..inner });
+
+    let list_area = Rect { y: inner.y + 1, height: inner.height - 1, ..inner };
+    let visible = list_area.height as usize;
+    let offset = switcher.selected.saturating_sub(visible.saturating_sub(1));
+    let name_width = (list_area.width as usize / 4).clamp(10, 28);
+    let current = app.state.current_project.as_ref().map(|p| p.name.as_str());
+    let lines: Vec<Line> = if matches.is_empty() {
+        vec![Line::from(Span::styled(" No matching projects", Style::default().fg(Color::DarkGray)))]
+    } else {
+        matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(row, &i)| {
+                let project = &switcher.projects[i];
+                let selected = row == switcher.selected;
+                let base = if selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let dim = if selected { base } else { Style::default().fg(Color::DarkGray) };
+                let marker = if current == Some(project.name.as_str()) {
+                    " \u{25cf} "
+                } else if switcher.is_recent(i) {
+                    " \u{21ba} "
+                } else {
+                    "   "
+                };
+                let name = truncate_to_width(&project.name, name_width);
+                let pods = app.state.pods.iter().filter(|p| p.project.as_deref() == Some(project.name.as_str())).count();
+                let pods = format!("{:>3} pods ", pods);
+                let fixed = 3 + name_width + 1 + pods.width();
+                let path = truncate_to_width(&project.path, (list_area.width as usize).saturating_sub(fixed));
+                let tail = (list_area.width as usize).saturating_sub(fixed + path.width());
+                Line::from(vec![
+                    Span::styled(marker, if selected { base } else { Style::default().fg(Color::Cyan) }),
+                    Span::styled(format!("{}{} ", name, " ".repeat(name_width.saturating_sub(name.width()))), base.add_modifier(Modifier::BOLD)),
+                    Span::styled(pods, dim),
+                    Span::styled(format!("{}{}", path, " ".repeat(tail)), dim),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), list_area);
+}
+
+/// `a` のセッションピッカー。左に選択中のセッションの画面、右にセッション一覧
+fn render_adopt_picker(frame: &mut Frame, picker: &AdoptPicker, area: Rect) {
+    let matches = picker.matches();
+    let width = area.width.saturating_sub(4).min(160);
+    if width < 30 || area.height < 5 {
+        return;
+    }
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 1,
+        width,
+        height: area.height.saturating_sub(2).max(4),
+    };
+
+    let block = Block::default()
+        .title(format!(" Adopt Session ({}/{}) ", matches.len(), picker.sessions.len()))
+        .title_bottom(Line::from(" ↑↓ select  Enter adopt  Esc close ").right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    if inner.height < 2 {
+        return;
+    }
+
+    let mut query = vec![Span::styled("> ", Style::default().fg(Color::Cyan))];
+    query.extend(input_spans(&picker.query, Style::default().fg(Color::White), true));
+    frame.render_widget(Paragraph::new(Line::from(query)), Rect { height: 1, ..inner });
+
+    // 幅が足りなければプレビューは出さず一覧だけ
+    let body = Rect { y: inner.y + 1, height: inner.height - 1, ..inner };
+    let list_area = if body.width >= 80 {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(body);
+        render_adopt_preview(frame, picker, columns[0]);
+        columns[1]
+    } else {
+        body
+    };
+
+    let visible = list_area.height as usize;
+    let offset = picker.selected.saturating_sub(visible.saturating_sub(1));
+    let name_width = (list_area.width as usize / 4).clamp(8, 24);
+    let now = chrono::Utc::now();
+    let lines: Vec<Line> = if matches.is_empty() {
+        vec![Line::from(Span::styled(" No matching sessions", Style::default().fg(Color::DarkGray)))]
+    } else {
+        matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(row, &i)| {
+                let session = &picker.sessions[i];
+                let selected = row == picker.selected;
+                let base = if selected {
+                    Style::default().fg(Color::Black).bg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let dim = if selected { base } else { Style::default().fg(Color::DarkGray) };
+                // Claude Code らしいペインがあるセッションに印を付ける
+                let (marker, marker_style) = if session.claude_panes > 0 {
+                    (" \u{273b} ", if selected { base } else { Style::default().fg(Color::Yellow) })
+                } else {
+                    ("   ", base)
+                };
+                let name = truncate_to_width(&session.session, name_width);
+                let created = session
+                    .created
+                    .map(|t| format!("{} ago", format_duration((now - t).num_seconds().max(0) as u64)))
+                    .unwrap_or_else(|| "-".to_string());
+                let info = format!(
+                    "{:>2} win {:>2} pane{} {:>9} ",
+                    session.windows,
+                    session.panes,
+                    if session.panes == 1 { " " } else { "s" },
+                    created,
+                );
+                let fixed = 3 + name_width + 1 + info.width();
+                let path = truncate_to_width(session.path.as_deref().unwrap_or(""), (list_area.width as usize).saturating_sub(fixed));
+                let tail = (list_area.width as usize).saturating_sub(fixed + path.width());
+                Line::from(vec![
+                    Span::styled(marker, marker_style),
+                    Span::styled(format!("{}{} ", name, " ".repeat(name_width.saturating_sub(name.width()))), base.add_modifier(Modifier::BOLD)),
+                    Span::styled(info, dim),
+                    Span::styled(format!("{}{}", path, " ".repeat(tail)), dim),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), list_area);
+}
+
+/// セッションピッカーの左側: 選択中のセッションのアクティブペインの画面 (下端に合わせる)
+fn render_adopt_preview(frame: &mut Frame, picker: &AdoptPicker, area: Rect) {
+    let title = match &picker.preview {
+        Some((pane, _)) => format!(" Preview {} ", pane),
+        None => " Preview ".to_string(),
+    };
+    let block = Block::default()
+        .title(Span::styled(title, Style::default().fg(Color::DarkGray)))
+        .borders(Borders::RIGHT)
+        .border_style(Style::default().fg(Color::DarkGray));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let output = picker.preview.as_ref().map(|(_, output)| output.trim_end()).unwrap_or("");
+    let lines: Vec<Line> = if output.is_empty() {
+        vec![Line::from(Span::styled(" (no output)", Style::default().fg(Color::DarkGray)))]
+    } else {
+        let all: Vec<&str> = output.lines().collect();
+        all[all.len().saturating_sub(inner.height as usize)..]
+            .iter()
+            .map(|line| Line::from(Span::raw(truncate_to_width(line, inner.width as usize))))
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), inner);
 }
 
 /// 左ペイン: モードに応じて内容を切り替え
@@ -98,7 +595,7 @@ fn render_home(frame: &mut Frame, app: &App, area: Rect) {
     let input_width = inner.width.saturating_sub(3) as usize; // "> " prefix + margin
     let input_lines = if input_width > 0 && !app.state.inline_input.is_empty() {
         // Unicode 表示幅ベースで行数を推定（CJK文字は2カラム幅）
-        let text_width = format!("> {}_", app.state.inline_input).width();
+        let text_width = format!("> {}_", app.state.inline_input.as_str()).width();
         (text_width / input_width.max(1)) + 1
     } else {
         1
@@ -133,7 +630,10 @@ fn render_home(frame: &mut Frame, app: &App, area: Rect) {
     let workspace = Paragraph::new(vec![workspace_line, Line::from("")]);
     frame.render_widget(workspace, sections[0]);
 
-    // ガイドテキスト
+    // ガイドテキスト (Pod にフォーカスしていなければプロジェクトの概要)
+    let health = app.project_health.as_ref().filter(|h| {
+        app.shows_project_health() && app.state.current_project.as_ref().is_some_and(|p| p.name == h.project)
+    });
     let guide_lines = if let Some(ref msg) = app.state.status_message {
         vec![
             Line::from(""),
@@ -142,6 +642,8 @@ fn render_home(frame: &mut Frame, app: &App, area: Rect) {
                 Style::default().fg(Color::Yellow),
             )),
         ]
+    } else if let Some(health) = health {
+        project_health_lines(app, health, inner.width as usize)
     } else {
         vec![
             Line::from(""),
@@ -179,15 +681,11 @@ fn render_home(frame: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::DarkGray)
     };
 
-    let input_text = Line::from(vec![
+    let mut input_spans_line = vec![
         Span::styled("> ", Style::default().fg(if is_focused { Color::Cyan } else { Color::DarkGray })),
-        Span::styled(app.state.inline_input.as_str(), cursor_style),
-        if is_focused {
-            Span::styled("_", Style::default().fg(Color::Gray))
-        } else {
-            Span::styled("", Style::default())
-        },
-    ]);
+    ];
+    input_spans_line.extend(input_spans(&app.state.inline_input, cursor_style, is_focused));
+    let input_text = Line::from(input_spans_line);
 
     let input_block = Block::default()
         .borders(Borders::TOP)
@@ -195,6 +693,121 @@ fn render_home(frame: &mut Frame, app: &App, area: Rect) {
     let input_inner = input_block.inner(sections[2]);
     frame.render_widget(input_block, sections[2]);
     frame.render_widget(Paragraph::new(input_text).wrap(Wrap { trim: false }), input_inner);
+
+    if is_focused {
+        if let Some(ref completion) = app.state.completion {
+            render_completion_popup(frame, completion, inner, sections[2].y);
+        }
+    }
+}
+
+/// Home のプロジェクト概要: ブランチと変更、Pod の状態、apiary が作った worktree、最近の出来事
+fn project_health_lines(app: &App, health: &crate::tui::health::ProjectHealth, width: usize) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let heading = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let fit = |text: String| truncate_to_width(&text, width);
+    let mut lines = vec![Line::from("")];
+
+    let mut repo = vec![Span::styled(fit(format!(" {}", health.project)), heading)];
+    if let Some(ref branch) = health.branch {
+        repo.push(Span::styled(format!("  \u{2387} {}", branch), Style::default().fg(Color::Magenta)));
+    }
+    match health.dirty {
+        Some(0) => repo.push(Span::styled("  \u{2713} clean", Style::default().fg(Color::Green))),
+        Some(n) => repo.push(Span::styled(format!("  \u{25cf} {} changed", n), Style::default().fg(Color::Yellow))),
+        None => {}
+    }
+    lines.push(Line::from(repo));
+
+    let counts = health.pod_counts(&app.state.pods);
+    let mut pods = vec![Span::styled(" Pods  ", dim)];
+    if counts.is_empty() {
+        pods.push(Span::styled("none yet", dim));
+    }
+    for (i, (status, n)) in counts.iter().enumerate() {
+        if i > 0 {
+            pods.push(Span::styled(" \u{00b7} ", dim));
+        }
+        pods.push(Span::styled(format!("{} {}", n, status.label()), Style::default().fg(status_color(status))));
+    }
+    lines.push(Line::from(pods));
+
+    if !health.worktrees.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(" Worktrees", dim)));
+        for worktree in &health.worktrees {
+            let branch = worktree.branch.as_deref().map(|b| format!(" \u{2387} {}", b)).unwrap_or_default();
+            lines.push(Line::from(Span::raw(fit(format!("   {}{}", worktree.name, branch)))));
+        }
+    }
+
+    if !health.recent.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(" Recent", dim)));
+        let today = chrono::Local::now().date_naive();
+        for event in health.recent.iter().rev() {
+            let at = event.at.with_timezone(&chrono::Local);
+            let when = if at.date_naive() == today { at.format("%H:%M") } else { at.format("%m-%d") };
+            lines.push(Line::from(vec![
+                Span::styled(format!("   {} ", when), dim),
+                Span::raw(fit(format!("{} {}", event.pod, crate::tui::health::describe_event(&event.kind)))),
+            ]));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("  Give an instruction to start Claude.", Style::default().fg(Color::Rgb(80, 85, 95)))));
+    lines
+}
+
+/// 補完候補のポップアップ。入力欄 (input_top) の直上に重ねて描画
+fn render_completion_popup(frame: &mut Frame, completion: &Completion, area: Rect, input_top: u16) {
+    const MAX_ITEMS: usize = 6;
+
+    let available = input_top.saturating_sub(area.y);
+    let height = (completion.items.len().min(MAX_ITEMS) as u16 + 2).min(available);
+    if height < 3 {
+        return;
+    }
+    let popup = Rect { x: area.x, y: input_top - height, width: area.width, height };
+    let visible = (height - 2) as usize;
+    // 選択中の候補が見えるようにスクロール
+    let offset = completion.selected.saturating_sub(visible - 1);
+
+    let title = match completion.kind {
+        CompletionKind::Command => " Commands ",
+        CompletionKind::Project => " Projects ",
+    };
+    let label_width = completion.items.iter().map(|i| i.label.width()).max().unwrap_or(0);
+    let lines: Vec<Line> = completion
+        .items
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(i, item)| {
+            let style = if i == completion.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let detail_width = (popup.width as usize).saturating_sub(label_width + 5);
+            Line::from(vec![
+                Span::styled(format!(" {:<width$} ", item.label, width = label_width), style),
+                Span::styled(
+                    format!(" {}", truncate_to_width(&item.detail, detail_width)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
 }
 
 /// ディレクトリブラウザ
@@ -221,20 +834,54 @@ fn render_browser(frame: &mut Frame, bs: &BrowserState, area: Rect) {
         return;
     }
 
-    // 本体 + ヒントバー
+    // 選択中のエントリが git リポジトリかプロジェクトならブランチ等をフッターに出す
+    let selected_entry = bs.entries.get(bs.selected).filter(|e| e.is_repo || e.project.is_some());
+    let footer = selected_entry.map(|entry| {
+        let mut spans = Vec::new();
+        if entry.is_repo {
+            let branch = crate::project::head_branch(&bs.current_path.join(&entry.name));
+            spans.push(Span::styled(
+                format!(" \u{2387} {}", branch.as_deref().unwrap_or("(no branch)")),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        match &entry.project {
+            Some(project) => spans.push(Span::styled(format!("  \u{25cf} project {}", project), Style::default().fg(Color::Green))),
+            None => spans.push(Span::styled("  not registered", Style::default().fg(Color::DarkGray))),
+        }
+        Line::from(spans)
+    });
+
+    // 最近のワークスペース (1-9 で選ぶ) + 本体 + フッター + ヒントバー
     let sections = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .constraints([
+            Constraint::Length(!bs.recent.is_empty() as u16),
+            Constraint::Min(1),
+            Constraint::Length(footer.is_some() as u16),
+            Constraint::Length(1),
+        ])
         .split(inner);
+    if !bs.recent.is_empty() {
+        let mut spans = vec![Span::styled(" Recent", Style::default().fg(Color::DarkGray))];
+        for (i, project) in bs.recent.iter().enumerate() {
+            spans.push(Span::styled(format!(" {}", i + 1), Style::default().fg(Color::Cyan)));
+            spans.push(Span::styled(format!(" {}", project.name), Style::default().fg(Color::White)));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), sections[0]);
+    }
+    if let Some(footer) = footer {
+        frame.render_widget(Paragraph::new(footer), sections[2]);
+    }
 
-    let visible_height = sections[0].height as usize;
+    let visible_height = sections[1].height as usize;
 
     if bs.entries.is_empty() {
         let empty = Paragraph::new(Line::from(Span::styled(
             "  (empty directory)",
             Style::default().fg(Color::DarkGray),
         )));
-        frame.render_widget(empty, sections[0]);
+        frame.render_widget(empty, sections[1]);
     } else {
         // スクロールオフセットを計算
         let scroll_offset = if bs.selected >= visible_height {
@@ -248,7 +895,12 @@ fn render_browser(frame: &mut Frame, bs: &BrowserState, area: Rect) {
             .take(visible_height)
             .map(|(i, entry)| {
                 let is_selected = i == bs.selected;
-                let (prefix, name_color) = if entry.is_dir {
+                // 登録済みプロジェクト ● / git リポジトリ ⎇ / ディレクトリ /
+                let (prefix, name_color) = if entry.project.is_some() {
+                    ("\u{25cf} ", Color::Green)
+                } else if entry.is_repo {
+                    ("\u{2387} ", Color::Magenta)
+                } else if entry.is_dir {
                     ("/ ", Color::Blue)
                 } else {
                     ("  ", Color::Rgb(160, 165, 175))
@@ -274,10 +926,21 @@ fn render_browser(frame: &mut Frame, bs: &BrowserState, area: Rect) {
             .collect();
 
         let list = Paragraph::new(lines);
-        frame.render_widget(list, sections[0]);
+        frame.render_widget(list, sections[1]);
     }
 
-    // ヒントバー
+    // 入力中は入力欄、それ以外はヒントバー
+    if let Some(input) = bs.input {
+        let (label, hint) = match input {
+            BrowserInput::NewDir => (" New dir: ", ""),
+            BrowserInput::GoTo => (" Go to: ", "  Tab complete"),
+        };
+        let mut spans = vec![Span::styled(label, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))];
+        spans.extend(input_spans(&bs.input_text, Style::default().fg(Color::White), true));
+        spans.push(Span::styled(hint, Style::default().fg(Color::DarkGray)));
+        frame.render_widget(Paragraph::new(Line::from(spans)), sections[3]);
+        return;
+    }
     let hint = Line::from(vec![
         Span::styled(" ↑↓", Style::default().fg(Color::Cyan)),
         Span::styled("Nav ", Style::default().fg(Color::DarkGray)),
@@ -287,10 +950,16 @@ fn render_browser(frame: &mut Frame, bs: &BrowserState, area: Rect) {
         Span::styled("Parent ", Style::default().fg(Color::DarkGray)),
         Span::styled("Space", Style::default().fg(Color::Cyan)),
         Span::styled("Select ", Style::default().fg(Color::DarkGray)),
+        Span::styled(".", Style::default().fg(Color::Cyan)),
+        Span::styled(if bs.show_hidden { "Hide dotfiles " } else { "Dotfiles " }, Style::default().fg(Color::DarkGray)),
+        Span::styled("n", Style::default().fg(Color::Cyan)),
+        Span::styled("New dir ", Style::default().fg(Color::DarkGray)),
+        Span::styled(":", Style::default().fg(Color::Cyan)),
+        Span::styled("Go to ", Style::default().fg(Color::DarkGray)),
         Span::styled("Esc", Style::default().fg(Color::Cyan)),
         Span::styled("Cancel", Style::default().fg(Color::DarkGray)),
     ]);
-    frame.render_widget(Paragraph::new(hint), sections[1]);
+    frame.render_widget(Paragraph::new(hint), sections[3]);
 }
 
 /// Pod Detail モード: パススルー + ANSI カラー表示
@@ -311,8 +980,11 @@ fn render_detail(frame: &mut Frame, app: &App, area: Rect) {
 
     // タイトル: ステータスアイコン + Pod名 + 経過時間 + subagent数 + Esc exit
     // Pod名をブロック幅に収まるよう切り詰め（CJK対応）
-    let icon = pod.status_icon();
-    let elapsed = pod.elapsed_time();
+    let icon = pod.status_icon(app.config.ui.icon_set());
+    let elapsed = match working_time_label(pod, &app.config.ui) {
+        Some(working) => format!("{} {}", pod.elapsed_time(), working),
+        None => pod.elapsed_time(),
+    };
     let sub_count = pod.total_sub_agents();
     let sub_info = if sub_count > 0 {
         format!(" \u{26a1}{}", sub_count)
@@ -348,6 +1020,92 @@ fn render_detail(frame: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
+    // メモ・リソース使用量があれば上端に 1 行ずつ表示
+    let mut header: Vec<Line> = Vec::new();
+    if !pod.note.is_empty() {
+        let note = truncate_to_width(&format!("\u{1f4dd} {}", pod.note), inner.width as usize);
+        header.push(Line::from(Span::styled(note, Style::default().fg(Color::Yellow))));
+    }
+    if let Some(ref url) = pod.pr_url {
+        header.push(Line::from(vec![
+            Span::styled(" PR ", Style::default().fg(Color::DarkGray)),
+            Span::styled(truncate_to_width(url, (inner.width as usize).saturating_sub(4)), Style::default().fg(Color::Green)),
+        ]));
+    }
+    if let Some(at) = pod.rate_limit_reset.as_ref().filter(|_| pod.status == PodStatus::RateLimited) {
+        let when = at.with_timezone(&chrono::Local).format("%H:%M").to_string();
+        let text = if app.auto_resume_enabled(pod) { format!("{} (auto-resume)", when) } else { when };
+        header.push(Line::from(vec![
+            Span::styled(" Resets ", Style::default().fg(Color::DarkGray)),
+            Span::styled(text, Style::default().fg(Color::Magenta)),
+        ]));
+    }
+    if let Some(ref model) = pod.model {
+        header.push(Line::from(vec![
+            Span::styled(" Model ", Style::default().fg(Color::DarkGray)),
+            Span::styled(crate::pod::short_model_name(model).to_string(), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    if let Some(ref sandbox) = pod.sandbox {
+        header.push(Line::from(vec![
+            Span::styled(" Container ", Style::default().fg(Color::DarkGray)),
+            Span::styled(truncate_to_width(&sandbox.describe(), (inner.width as usize).saturating_sub(11)), Style::default().fg(Color::Blue)),
+        ]));
+    }
+    if let Some(progress) = pod.members.get(selected_member).and_then(|m| m.progress.as_ref()) {
+        let bar = progress.bar(8, app.config.ui.icon_set().is_ascii());
+        let current = progress.current.as_deref().unwrap_or("");
+        let available = (inner.width as usize).saturating_sub(bar.width() + 8);
+        header.push(Line::from(vec![
+            Span::styled(" Tasks ", Style::default().fg(Color::DarkGray)),
+            Span::styled(bar, Style::default().fg(Color::Green)),
+            Span::styled(format!(" {}", truncate_to_width(current, available)), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    // リードメンバーなら Claude Code の TODO リスト (高さの 1/3 まで、進行中の項目が見えるように)
+    if let Some(todos) = pod.todos.as_ref().filter(|_| selected_member == 0) {
+        let ascii = app.config.ui.icon_set().is_ascii();
+        let max_items = (inner.height as usize / 3).max(1);
+        let focus = todos.items.iter().position(|i| i.status != TodoStatus::Completed).unwrap_or(0);
+        let start = focus.saturating_sub(max_items / 2).min(todos.items.len().saturating_sub(max_items));
+        for item in todos.items.iter().skip(start).take(max_items) {
+            let (mark, style) = match item.status {
+                TodoStatus::Completed => (if ascii { "[x]" } else { "\u{2714}" }, Style::default().fg(Color::DarkGray)),
+                TodoStatus::InProgress => (if ascii { "[>]" } else { "\u{25fc}" }, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                TodoStatus::Pending => (if ascii { "[ ]" } else { "\u{25fb}" }, Style::default().fg(Color::Gray)),
+            };
+            let text = format!("   {} {}", mark, item.content);
+            header.push(Line::from(Span::styled(truncate_to_width(&text, inner.width as usize), style)));
+        }
+    }
+    if let Some(context) = pod.members.get(selected_member).and_then(|m| m.context) {
+        let text = match context {
+            ContextPressure::Low(percent) => format!("{}% left until auto-compact (c: /compact)", percent),
+            ContextPressure::Compacting => "compacting\u{2026}".to_string(),
+        };
+        header.push(Line::from(vec![
+            Span::styled(" Context ", Style::default().fg(Color::DarkGray)),
+            Span::styled(text, Style::default().fg(Color::LightRed)),
+        ]));
+    }
+    if let Some(usage) = pod.members.get(selected_member).and_then(|m| m.usage) {
+        let color = if app.config.resources.exceeds(&usage) { Color::LightRed } else { Color::Gray };
+        header.push(Line::from(vec![
+            Span::styled(" CPU/MEM ", Style::default().fg(Color::DarkGray)),
+            Span::styled(usage.format(), Style::default().fg(color)),
+        ]));
+    }
+    let header_height = header.len() as u16;
+    let inner = if header_height > 0 && inner.height > header_height + 1 {
+        frame.render_widget(
+            Paragraph::new(header).style(Style::default().bg(Color::Rgb(30, 30, 35))),
+            Rect::new(inner.x, inner.y, inner.width, header_height),
+        );
+        Rect::new(inner.x, inner.y + header_height, inner.width, inner.height - header_height)
+    } else {
+        inner
+    };
+
     // ストリームがあればその永続パーサーから描画
     if let Some(ref stream) = app.detail_pty_stream {
         let screen = stream.screen();
@@ -355,7 +1113,7 @@ fn render_detail(frame: &mut Frame, app: &App, area: Rect) {
         let start_row = pane_rows.saturating_sub(inner.height);
         let display_cols = inner.width.min(pane_cols);
         let lines: Vec<Line> = (0..inner.height)
-            .map(|r| render_vt100_row(screen, start_row + r, display_cols))
+            .map(|r| highlight::apply(render_vt100_row(screen, start_row + r, display_cols), &app.config.highlight))
             .collect();
         frame.render_widget(Paragraph::new(lines), inner);
         return;
@@ -397,14 +1155,14 @@ fn render_detail(frame: &mut Frame, app: &App, area: Rect) {
     let display_cols = inner.width.min(parse_cols);
 
     let lines: Vec<Line> = (0..inner.height)
-        .map(|r| render_vt100_row(screen, start_row + r, display_cols))
+        .map(|r| highlight::apply(render_vt100_row(screen, start_row + r, display_cols), &app.config.highlight))
         .collect();
 
     frame.render_widget(Paragraph::new(lines), inner);
 }
 
 /// vt100::Screen の 1 行を ratatui::Line に変換するヘルパー
-fn render_vt100_row(screen: &vt100::Screen, row: u16, display_cols: u16) -> Line<'static> {
+pub(crate) fn render_vt100_row(screen: &vt100::Screen, row: u16, display_cols: u16) -> Line<'static> {
     let mut spans: Vec<Span> = Vec::new();
     let mut col: u16 = 0;
     while col < display_cols {
@@ -478,13 +1236,20 @@ fn convert_vt100_color(color: vt100::Color) -> Color {
 
 /// Chat モード
 fn render_chat(frame: &mut Frame, app: &App, area: Rect) {
-    let pod_name = app
-        .state
-        .focused_pod()
-        .map(|p| p.name.as_str())
+    let pod = app.state.focused_pod();
+    let pod_name = pod.map(|p| p.name.as_str()).unwrap_or("?");
+    let team = pod.is_some_and(|p| p.members.len() > 1);
+    let member = pod
+        .and_then(|p| app.state.selected_member.and_then(|i| p.members.get(i)).or(p.members.first()))
+        .map(|m| m.role.as_str())
         .unwrap_or("?");
 
-    let title = format!(" Chat: {} ", pod_name);
+    // チームの Pod では相手のメンバーも出す (Tab で切り替え)
+    let title = if team {
+        format!(" Chat: {} \u{2192} {} ", pod_name, member)
+    } else {
+        format!(" Chat: {} ", pod_name)
+    };
     let block = Block::default()
         .title(title.as_str())
         .borders(Borders::ALL)
@@ -507,52 +1272,68 @@ fn render_chat(frame: &mut Frame, app: &App, area: Rect) {
     let available_height = sections[0].height as usize;
 
     let mut all_lines: Vec<Line> = Vec::new();
-    for msg in &app.state.chat_history {
+    for msg in app.state.chat_history.iter().filter(|m| m.pod == pod_name) {
         let sender_color = if msg.sender == "you" {
             Color::Green
         } else {
             Color::Cyan
         };
-        let prefix = format!("[{}] ", msg.sender);
-        let prefix_len = prefix.len();
+        let prefix = if msg.sender == "you" && team {
+            format!("[you \u{2192} {}] ", msg.member)
+        } else {
+            format!("[{}] ", msg.sender)
+        };
+        let prefix_len = prefix.width();
 
-        for (i, line) in msg.content.lines().enumerate() {
+        // 応答は Markdown として描画する (chat_raw なら原文のまま)
+        let lines: Vec<Line> = if msg.sender == "you" || app.state.chat_raw {
+            msg.content.lines().map(|l| Line::from(l.to_string())).collect()
+        } else {
+            markdown::render(&msg.content)
+        };
+        for (i, mut line) in lines.into_iter().enumerate() {
             if i == 0 {
                 // 最初の行: sender prefix 付き
-                all_lines.push(Line::from(vec![
-                    Span::styled(
-                        prefix.clone(),
-                        Style::default().fg(sender_color),
-                    ),
-                    Span::raw(line.to_string()),
-                ]));
+                line.spans.insert(0, Span::styled(prefix.clone(), Style::default().fg(sender_color)));
             } else {
                 // 続行行: prefix 分の空白でインデント
-                all_lines.push(Line::from(vec![
-                    Span::raw(" ".repeat(prefix_len)),
-                    Span::raw(line.to_string()),
-                ]));
+                line.spans.insert(0, Span::raw(" ".repeat(prefix_len)));
             }
+            all_lines.push(line);
         }
     }
 
-    // 末尾からスクロール表示: 表示可能な行数に収まるように
-    let skip = if all_lines.len() > available_height {
-        all_lines.len() - available_height
-    } else {
-        0
-    };
+    // 末尾からスクロール表示: 表示可能な行数に収まるように (chat_scroll 行だけさかのぼる)
+    let max_skip = all_lines.len().saturating_sub(available_height);
+    let skip = max_skip.saturating_sub(app.state.chat_scroll);
+    let scrolled = skip < max_skip;
     let visible_lines: Vec<Line> = all_lines.into_iter().skip(skip).collect();
 
     let history = Paragraph::new(visible_lines).wrap(Wrap { trim: false });
     frame.render_widget(history, sections[0]);
 
+    // さかのぼっている間は下に続きがあることを示す
+    if scrolled {
+        let label = match app.state.chat_unread {
+            0 => " \u{2193} more below (PgDn) ".to_string(),
+            1 => " \u{2193} 1 new message (PgDn) ".to_string(),
+            n => format!(" \u{2193} {} new messages (PgDn) ", n),
+        };
+        let width = (label.width() as u16).min(sections[0].width);
+        let area = Rect {
+            x: sections[0].x + sections[0].width - width,
+            y: sections[0].y + sections[0].height - 1,
+            width,
+            height: 1,
+        };
+        let style = Style::default().fg(Color::Black).bg(Color::Yellow);
+        frame.render_widget(Paragraph::new(Span::styled(label, style)), area);
+    }
+
     // 入力エリア
-    let input_line = Line::from(vec![
-        Span::styled("> ", Style::default().fg(Color::Cyan)),
-        Span::raw(app.state.chat_input.as_str()),
-        Span::styled("_", Style::default().fg(Color::Gray)),
-    ]);
+    let mut input_spans_line = vec![Span::styled("> ", Style::default().fg(Color::Cyan))];
+    input_spans_line.extend(input_spans(&app.state.chat_input, Style::default(), true));
+    let input_line = Line::from(input_spans_line);
     let input_block = Block::default()
         .borders(Borders::TOP)
         .border_style(Style::default().fg(Color::DarkGray));
@@ -561,6 +1342,28 @@ fn render_chat(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(Paragraph::new(input_line), input_inner);
 }
 
+/// 入力欄のテキストをカーソル付きで描画。カーソル位置の文字は反転表示し、
+/// 末尾では "_" を出す (文字単位で分割するので CJK の 2 カラム幅もずれない)。
+/// $EDITOR で入力した改行は ↵ で表示
+fn input_spans(editor: &LineEditor, style: Style, show_cursor: bool) -> Vec<Span<'static>> {
+    let display = |s: &str| s.replace('\n', "↵");
+    if !show_cursor {
+        return vec![Span::styled(display(editor.as_str()), style)];
+    }
+    let (before, at, after) = editor.split_at_cursor();
+    match at {
+        Some(at) => vec![
+            Span::styled(display(before), style),
+            Span::styled(display(at), style.add_modifier(Modifier::REVERSED)),
+            Span::styled(display(after), style),
+        ],
+        None => vec![
+            Span::styled(display(before), style),
+            Span::styled("_", Style::default().fg(Color::Gray)),
+        ],
+    }
+}
+
 /// Permission モード
 fn render_permission(frame: &mut Frame, app: &App, area: Rect) {
     let block = Block::default()
@@ -677,11 +1480,45 @@ fn render_help(frame: &mut Frame, _app: &App, area: Rect) {
         Line::from("  hjkl/arrows Navigate pods"),
         Line::from("  Enter/i     Open pod detail"),
         Line::from("  t           Attach tmux session"),
+        Line::from("  T           Attach in a tmux popup (Esc closes)"),
         Line::from("  n/Tab       New task (left pane)"),
-        Line::from("  a           Adopt session"),
+        Line::from("  a           Adopt a tmux session (picker)"),
+        Line::from("  A           Adopt first unmanaged session"),
         Line::from("  d           Drop pod"),
+        Line::from("  U           Resurrect dead pod(s) (new session, Claude)"),
         Line::from("  p           Browse directories"),
+        Line::from("              (1-9 recent, . dotfiles, n new dir, : go to path)"),
+        Line::from("  #           Edit pod tags"),
+        Line::from("  e           Edit pod note"),
+        Line::from("  M           Add a tmux pane as a member"),
+        Line::from("  S           Split a member into its own pod"),
+        Line::from("  R           Rename a member"),
+        Line::from("  c / C       Send /compact / /clear (asks)"),
+        Line::from("  O           Switch model (/model)"),
+        Line::from("  o           Cycle pod priority"),
+        Line::from("  m           Mute/unmute pod notifications"),
+        Line::from("  Q           Toggle do not disturb"),
+        Line::from("  P           Push branch and open a PR (gh)"),
+        Line::from("  f           Filter pods by tag"),
+        Line::from("  1-9         Focus the numbered pod"),
+        Line::from("  g 1-9       Jump to the numbered group"),
+        Line::from("  v           Cycle grid/list/kanban view"),
+        Line::from("  \\           Hide/show the left pane"),
+        Line::from("  L           Side-by-side/stacked layout"),
+        Line::from("  z           Collapse/expand focused group"),
+        Line::from("  Enter/d     On a collapsed group: open at the"),
+        Line::from("              first warning pod / drop the group"),
+        Line::from("  G           Move pod to another group"),
+        Line::from("  D           Drop the focused pod's whole group"),
+        Line::from("  *           Pin/unpin pod to the top row"),
+        Line::from("  Space       Select pod (d/a/X/b/g/M act on all"),
+        Line::from("              selected: drop/archive/forget/broadcast/"),
+        Line::from("              group/merge into the focused pod)"),
         Line::from("  N           Next warning pod"),
+        Line::from("  Ctrl+P      Command palette"),
+        Line::from("  Ctrl+F      Fuzzy pod switcher"),
+        Line::from("  w           Switch workspace (Ctrl+F there also filters)"),
+        Line::from("  W           Show only the workspace's pods (toggle)"),
         Line::from("  ?           Toggle this help"),
         Line::from("  q           Quit"),
         Line::from(""),
@@ -695,7 +1532,12 @@ fn render_help(frame: &mut Frame, _app: &App, area: Rect) {
         Line::from("  Enter       Create pod & send"),
         Line::from("  /cmd        Slash commands"),
         Line::from("  @project    Specify project"),
+        Line::from("  Up/Down     Recall previous input"),
+        Line::from("  Tab         Complete /command or @project"),
+        Line::from("  Ctrl+E      Edit input in $EDITOR"),
         Line::from("  Esc/Tab     Back to right pane"),
+        Line::from("  ←/→ Home/End Move cursor (Ctrl/Alt: by word)"),
+        Line::from("  ^W/^U/^K    Delete word / to start / to end"),
         Line::from(""),
         Line::from(Span::styled(
             "Detail Mode (Passthrough):",
@@ -705,6 +1547,13 @@ fn render_help(frame: &mut Frame, _app: &App, area: Rect) {
         )),
         Line::from("  All keys    Forwarded to pane"),
         Line::from("  Esc         Back to Home"),
+        Line::from("  F2          Chat with this member (Tab: next"),
+        Line::from("              member, Ctrl+R: raw/markdown,"),
+        Line::from("              PgUp/PgDn or wheel: scroll,"),
+        Line::from("              Ctrl+S: export transcript,"),
+        Line::from("              Esc: back to Detail)"),
+        Line::from("  F5          Restart Claude in this pane"),
+        Line::from("  Shift+F5    Restart and continue the conversation"),
         Line::from(""),
         Line::from(Span::styled(
             "Permission Mode:",
@@ -740,21 +1589,97 @@ fn render_help(frame: &mut Frame, _app: &App, area: Rect) {
     frame.render_widget(help, inner);
 }
 
+/// 管理外の Claude セッションがあれば Pods ペイン下端に一覧を描画し、残りの領域を返す
+fn render_unmanaged_section(frame: &mut Frame, app: &App, inner: Rect) -> Rect {
+    const MAX_ROWS: usize = 3;
+    let sessions = &app.state.unmanaged_sessions;
+    let height = (sessions.len().min(MAX_ROWS) + 1) as u16;
+    if sessions.is_empty() || inner.height <= height + 2 {
+        return inner;
+    }
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!(" Unmanaged ({}) ", sessions.len()),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("[A]", Style::default().fg(Color::Cyan)),
+        Span::styled(format!("Adopt '{}'", sessions[0].session), dim),
+    ])];
+    for s in sessions.iter().take(MAX_ROWS) {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  \u{25c7} {} ", s.session), Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{} pane(s)  {}", s.panes.len(), s.path.as_deref().unwrap_or("")),
+                dim,
+            ),
+        ]));
+    }
+
+    let area = Rect::new(inner.x, inner.y + inner.height - height, inner.width, height);
+    frame.render_widget(Paragraph::new(lines), area);
+    Rect::new(inner.x, inner.y, inner.width, inner.height - height)
+}
+
+/// タグフィルタ設定中は Pods ペイン上端にフィルタバーを描画し、残りの領域を返す
+fn render_filter_bar(frame: &mut Frame, app: &App, inner: Rect) -> Rect {
+    let workspace = app.state.current_project.as_ref().filter(|_| app.state.workspace_filter);
+    if inner.height <= 1 || (app.state.tag_filter.is_none() && workspace.is_none()) {
+        return inner;
+    }
+    let shown = app.state.visible_pods().len();
+    let mut bar = vec![Span::styled(" Filter: ", Style::default().fg(Color::DarkGray))];
+    if let Some(project) = workspace {
+        bar.push(Span::styled(format!("@{} ", project.name), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+    }
+    if let Some(ref tag) = app.state.tag_filter {
+        bar.push(Span::styled(format!("#{} ", tag), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+    }
+    bar.push(Span::styled(
+        format!("({}/{})  ", shown, app.state.pods.len()),
+        Style::default().fg(Color::DarkGray),
+    ));
+    if app.state.tag_filter.is_some() {
+        bar.push(Span::styled("[f]", Style::default().fg(Color::Cyan)));
+        bar.push(Span::styled("Change ", Style::default().fg(Color::DarkGray)));
+    }
+    if workspace.is_some() {
+        bar.push(Span::styled("[W]", Style::default().fg(Color::Cyan)));
+        bar.push(Span::styled("Show all", Style::default().fg(Color::DarkGray)));
+    }
+    frame.render_widget(Paragraph::new(Line::from(bar)), Rect::new(inner.x, inner.y, inner.width, 1));
+    Rect::new(inner.x, inner.y + 1, inner.width, inner.height - 1)
+}
+
+/// Pods ペインのタイトル。フィルタで隠れている Pod があればその数を出す
+fn pods_title(app: &App) -> String {
+    let hidden = app.state.pods.len() - app.state.visible_pods().len();
+    if hidden > 0 {
+        format!(" Pods ({} hidden) ", hidden)
+    } else {
+        " Pods ".to_string()
+    }
+}
+
 /// 右ペイン: Pod カードのグリッド（グループ / 非グループ / Dead の3セクション）
 fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
     let is_focused = app.state.pane_focus == PaneFocus::Right;
     let border_color = if is_focused { Color::Cyan } else { Color::DarkGray };
 
     let block = Block::default()
-        .title(" Pods ")
+        .title(pods_title(app))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let min_card_height = 4u16;
-    if inner.width < CARD_WIDTH || inner.height < min_card_height {
+    let inner = render_filter_bar(frame, app, inner);
+    let inner = render_unmanaged_section(frame, app, inner);
+    let (card_width, card_height) = app.config.ui.card_size();
+    let min_card_height = card_height.min(4);
+    if inner.width < card_width || inner.height < min_card_height {
         return;
     }
 
@@ -774,24 +1699,37 @@ fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
-    let cols = (inner.width / (CARD_WIDTH + CARD_GAP)).max(1) as usize;
+    let cols = (inner.width / (card_width + CARD_GAP)).max(1) as usize;
+    let dead_card_height = DEAD_CARD_HEIGHT.min(card_height);
     let focus_idx = app.state.focus;
+    let quick = quick_keys(app);
+    let group_numbers = app.state.group_order();
+    let group_key = |name: &str| -> String {
+        match group_numbers.iter().position(|g| g == name) {
+            Some(n) if n < 9 => format!("g{} ", n + 1),
+            _ => String::new(),
+        }
+    };
 
-    // Pod をカテゴリ分け: グループ / 非グループ / Dead
+    // Pod をカテゴリ分け: ピン留め / グループ / 非グループ / Dead
+    let mut pinned: Vec<(usize, &crate::pod::Pod)> = Vec::new();
     let mut group_order: Vec<String> = Vec::new();
     let mut group_map: std::collections::HashMap<String, Vec<(usize, &crate::pod::Pod)>> =
         std::collections::HashMap::new();
     let mut ungrouped: Vec<(usize, &crate::pod::Pod)> = Vec::new();
     let mut dead: Vec<(usize, &crate::pod::Pod)> = Vec::new();
 
-    for (i, pod) in app.state.pods.iter().enumerate() {
-        if pod.status == PodStatus::Dead {
+    for i in app.state.visible_pods() {
+        let pod = &app.state.pods[i];
+        if pod.pinned {
+            pinned.push((i, pod));
+        } else if pod.status == PodStatus::Dead {
             dead.push((i, pod));
-        } else if let Some(ref group) = pod.group {
+        } else if let Some(group) = app.state.display_group(pod) {
             if !group_map.contains_key(group) {
-                group_order.push(group.clone());
+                group_order.push(group.to_string());
             }
-            group_map.entry(group.clone()).or_default().push((i, pod));
+            group_map.entry(group.to_string()).or_default().push((i, pod));
         } else {
             ungrouped.push((i, pod));
         }
@@ -799,13 +1737,73 @@ fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
 
     let mut y_offset: u16 = 0;
 
+    // --- ピン留め Pod 描画 (グループに関係なく先頭行) ---
+    for row_pods in pinned.chunks(cols) {
+        if y_offset + card_height > inner.height {
+            break;
+        }
+
+        for (col_idx, (i, pod)) in row_pods.iter().enumerate() {
+            let x = inner.x + (col_idx as u16) * (card_width + CARD_GAP);
+            let card_area = Rect::new(x, inner.y + y_offset, card_width, card_height);
+            render_pod_card(frame, pod, card_area, focus_idx == Some(*i), app.state.selected_pods.contains(&pod.name), quick.get(i).copied(), &app.config);
+        }
+
+        y_offset += card_height + CARD_GAP;
+    }
+
     // --- グループ描画 ---
     for group_name in &group_order {
         let group_pods = &group_map[group_name];
+        let group_focused = group_pods.iter().any(|(i, _)| focus_idx == Some(*i));
+
+        // 折りたたまれたグループ: ヘッダー + ステータス集計の 1 行だけ
+        if app.state.collapsed_groups.contains(group_name) {
+            let group_height = 3;
+            if y_offset + group_height > inner.height {
+                break;
+            }
+            let group_area = Rect::new(inner.x, inner.y + y_offset, inner.width, group_height);
+            let (border_color, title_style) = if group_focused {
+                (Color::White, Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+            } else {
+                (Color::Rgb(55, 60, 70), Style::default())
+            };
+            let rollup = app.state.group_status(group_name).unwrap_or(PodStatus::Idle);
+            let group_block = Block::default()
+                .title(Line::from(vec![
+                    Span::styled(" ▸ ", title_style),
+                    Span::styled(quick_label(group_pods.iter().find_map(|(i, _)| quick.get(i).copied())), Style::default().fg(Color::DarkGray)),
+                    Span::styled(rollup.icon(app.config.ui.icon_set()).to_string(), Style::default().fg(status_color(&rollup))),
+                    Span::styled(format!(" {} ({}) ", group_name, group_pods.len()), title_style),
+                    Span::styled(group_key(group_name), Style::default().fg(Color::DarkGray)),
+                ]))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(border_color));
+            let group_inner = group_block.inner(group_area);
+            frame.render_widget(group_block, group_area);
+
+            let mut spans = Vec::new();
+            for status in KANBAN_COLUMNS {
+                let count = group_pods.iter().filter(|(_, p)| p.status == *status).count();
+                if count > 0 {
+                    spans.push(Span::styled(
+                        format!(" {} {}", status.icon(app.config.ui.icon_set()), count),
+                        Style::default().fg(status_color(status)),
+                    ));
+                }
+            }
+            frame.render_widget(Paragraph::new(Line::from(spans)), group_inner);
+
+            y_offset += group_height + CARD_GAP;
+            continue;
+        }
+
         // グループ内のカラム数（ボーダー分 2 を引く）
-        let cols_in_group = ((inner.width.saturating_sub(2)) / (CARD_WIDTH + CARD_GAP)).max(1) as usize;
-        let num_rows = (group_pods.len() + cols_in_group - 1) / cols_in_group;
-        let group_height = 2 + (num_rows as u16) * (CARD_HEIGHT + CARD_GAP) - CARD_GAP;
+        let cols_in_group = ((inner.width.saturating_sub(2)) / (card_width + CARD_GAP)).max(1) as usize;
+        let num_rows = group_pods.len().div_ceil(cols_in_group);
+        let group_height = 2 + (num_rows as u16) * (card_height + CARD_GAP) - CARD_GAP;
 
         if y_offset + group_height > inner.height {
             break;
@@ -813,8 +1811,14 @@ fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
 
         let group_area = Rect::new(inner.x, inner.y + y_offset, inner.width, group_height);
 
+        let rollup = app.state.group_status(group_name).unwrap_or(PodStatus::Idle);
         let group_block = Block::default()
-            .title(format!(" {} ", group_name))
+            .title(Line::from(vec![
+                Span::raw(" ▾ "),
+                Span::styled(rollup.icon(app.config.ui.icon_set()).to_string(), Style::default().fg(status_color(&rollup))),
+                Span::raw(format!(" {} ", group_name)),
+                Span::styled(group_key(group_name), Style::default().fg(Color::DarkGray)),
+            ]))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(Color::Rgb(55, 60, 70)));
@@ -825,17 +1829,17 @@ fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
         for (idx, (i, pod)) in group_pods.iter().enumerate() {
             let col = idx % cols_in_group;
             let row = idx / cols_in_group;
-            let x = group_inner.x + (col as u16) * (CARD_WIDTH + CARD_GAP);
-            let y = group_inner.y + (row as u16) * (CARD_HEIGHT + CARD_GAP);
+            let x = group_inner.x + (col as u16) * (card_width + CARD_GAP);
+            let y = group_inner.y + (row as u16) * (card_height + CARD_GAP);
 
-            if x + CARD_WIDTH > group_inner.x + group_inner.width
-                || y + CARD_HEIGHT > group_inner.y + group_inner.height
+            if x + card_width > group_inner.x + group_inner.width
+                || y + card_height > group_inner.y + group_inner.height
             {
                 continue;
             }
 
-            let card_area = Rect::new(x, y, CARD_WIDTH, CARD_HEIGHT);
-            render_pod_card(frame, pod, card_area, focus_idx == Some(*i));
+            let card_area = Rect::new(x, y, card_width, card_height);
+            render_pod_card(frame, pod, card_area, focus_idx == Some(*i), app.state.selected_pods.contains(&pod.name), quick.get(i).copied(), &app.config);
         }
 
         y_offset += group_height + CARD_GAP;
@@ -844,27 +1848,27 @@ fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
     // --- 非グループ Pod 描画 ---
     let ungrouped_rows: Vec<&[(usize, &crate::pod::Pod)]> = ungrouped.chunks(cols).collect();
     for row_pods in &ungrouped_rows {
-        if y_offset + CARD_HEIGHT > inner.height {
+        if y_offset + card_height > inner.height {
             break;
         }
 
         for (col_idx, (i, pod)) in row_pods.iter().enumerate() {
-            let x = inner.x + (col_idx as u16) * (CARD_WIDTH + CARD_GAP);
+            let x = inner.x + (col_idx as u16) * (card_width + CARD_GAP);
             let y = inner.y + y_offset;
 
-            if x + CARD_WIDTH > inner.x + inner.width {
+            if x + card_width > inner.x + inner.width {
                 continue;
             }
 
-            let card_area = Rect::new(x, y, CARD_WIDTH, CARD_HEIGHT);
-            render_pod_card(frame, pod, card_area, focus_idx == Some(*i));
+            let card_area = Rect::new(x, y, card_width, card_height);
+            render_pod_card(frame, pod, card_area, focus_idx == Some(*i), app.state.selected_pods.contains(&pod.name), quick.get(i).copied(), &app.config);
         }
 
-        y_offset += CARD_HEIGHT + CARD_GAP;
+        y_offset += card_height + CARD_GAP;
     }
 
     // --- Dead セクション ---
-    if !dead.is_empty() && y_offset + 1 + DEAD_CARD_HEIGHT <= inner.height {
+    if !dead.is_empty() && y_offset + 1 + dead_card_height <= inner.height {
         // セパレーター
         let sep_area = Rect::new(inner.x, inner.y + y_offset, inner.width, 1);
         let mut sep_text = String::from("\u{2500}\u{2500} Dead ");
@@ -882,50 +1886,319 @@ fn render_pods_grid(frame: &mut Frame, app: &App, area: Rect) {
         // Dead Pod をコンパクトカードで描画
         let dead_rows: Vec<&[(usize, &crate::pod::Pod)]> = dead.chunks(cols).collect();
         for row_pods in &dead_rows {
-            if y_offset + DEAD_CARD_HEIGHT > inner.height {
+            if y_offset + dead_card_height > inner.height {
                 break;
             }
 
             for (col_idx, (i, pod)) in row_pods.iter().enumerate() {
-                let x = inner.x + (col_idx as u16) * (CARD_WIDTH + CARD_GAP);
+                let x = inner.x + (col_idx as u16) * (card_width + CARD_GAP);
                 let y = inner.y + y_offset;
 
-                if x + CARD_WIDTH > inner.x + inner.width {
+                if x + card_width > inner.x + inner.width {
                     continue;
                 }
 
-                let card_area = Rect::new(x, y, CARD_WIDTH, DEAD_CARD_HEIGHT);
-                render_pod_card(frame, pod, card_area, focus_idx == Some(*i));
+                let card_area = Rect::new(x, y, card_width, dead_card_height);
+                render_pod_card(frame, pod, card_area, focus_idx == Some(*i), app.state.selected_pods.contains(&pod.name), quick.get(i).copied(), &app.config);
             }
 
-            y_offset += DEAD_CARD_HEIGHT + CARD_GAP;
+            y_offset += dead_card_height + CARD_GAP;
         }
     }
 }
 
-/// 個々の Pod カードを描画（角丸 + ステータス背景色）
-fn render_pod_card(frame: &mut Frame, pod: &crate::pod::Pod, area: Rect, focused: bool) {
+/// 右ペイン: 1 Pod 1 行のテーブル表示
+fn render_pods_list(frame: &mut Frame, app: &App, area: Rect) {
+    let is_focused = app.state.pane_focus == PaneFocus::Right;
+    let border_color = if is_focused { Color::Cyan } else { Color::DarkGray };
+
+    let block = Block::default()
+        .title(pods_title(app))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let inner = render_filter_bar(frame, app, inner);
+    let inner = render_unmanaged_section(frame, app, inner);
+    if inner.height < 2 || inner.width < 10 {
+        return;
+    }
+
+    if app.state.pods.is_empty() {
+        let empty_msg = Paragraph::new(Line::from(Span::styled(
+            "  No pods. Type an instruction or press n to start.",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(empty_msg, inner);
+        return;
+    }
+
+    // 狭いときはアイコン / NAME / WORK / LAST OUTPUT だけ残す
+    let compact = inner.width < COMPACT_LIST_WIDTH;
+    let pick = |cells: Vec<Cell<'static>>| -> Vec<Cell<'static>> {
+        if !compact {
+            return cells;
+        }
+        cells
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| matches!(i, 0 | 1 | 8 | 9))
+            .map(|(_, c)| c)
+            .collect()
+    };
+
+    let header_style = Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD);
+    let header = Row::new(pick(vec![
+        Cell::from(""),
+        Cell::from("NAME"),
+        Cell::from("PROJECT"),
+        Cell::from("BRANCH"),
+        Cell::from("GROUP"),
+        Cell::from("TAGS"),
+        Cell::from("MEM"),
+        Cell::from("AGE"),
+        Cell::from("WORK"),
+        Cell::from("LAST OUTPUT"),
+    ]))
+    .style(header_style);
+
+    // フォーカス行が見えるようにスクロール (ヘッダー 1 行分を除く)
+    let visible_rows = inner.height.saturating_sub(1) as usize;
+    let visible = app.state.visible_pods();
+    let quick = quick_keys(app);
+    let focus_pos = app
+        .state
+        .focus
+        .and_then(|f| visible.iter().position(|&i| i == f))
+        .unwrap_or(0);
+    let skip = if focus_pos >= visible_rows { focus_pos + 1 - visible_rows } else { 0 };
+
+    let rows: Vec<Row> = visible
+        .iter()
+        .skip(skip)
+        .take(visible_rows)
+        .map(|&i| {
+            let pod = &app.state.pods[i];
+            let focused = app.state.focus == Some(i);
+            let last_line = pod.last_output_line().to_string();
+            let text_color = if pod.status == PodStatus::Dead {
+                Color::Rgb(80, 80, 85)
+            } else {
+                Color::Rgb(200, 205, 215)
+            };
+            let style = if focused {
+                Style::default().fg(Color::White).bg(Color::Rgb(40, 60, 100)).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(text_color)
+            };
+            Row::new(pick(vec![
+                Cell::from(Span::styled(pod.status_icon(app.config.ui.icon_set()), Style::default().fg(status_color(&pod.status)))),
+                Cell::from(format!(
+                    "{}{}{}{}{}",
+                    quick_label(quick.get(&i).copied()),
+                    if app.state.selected_pods.contains(&pod.name) { "\u{2713} " } else { "" },
+                    if pod.pinned { "\u{2605} " } else { "" },
+                    pod.priority.marker(app.config.ui.icon_set().is_ascii()).map(|m| format!("{} ", m)).unwrap_or_default(),
+                    pod.name
+                )),
+                Cell::from(pod.project.clone().unwrap_or_default()),
+                Cell::from(pod.branch.clone().unwrap_or_default()),
+                Cell::from(pod.group.clone().unwrap_or_default()),
+                Cell::from(Span::styled(
+                    pod.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" "),
+                    Style::default().fg(Color::Magenta),
+                )),
+                Cell::from(pod.members.len().to_string()),
+                Cell::from(pod.elapsed_time()),
+                Cell::from(format_duration(pod.live_working_time())),
+                Cell::from(Span::styled(last_line, Style::default().fg(Color::DarkGray))),
+            ]))
+            .style(style)
+        })
+        .collect();
+
+    let icon_width = app.config.ui.icon_set().width() as u16;
+    let widths: Vec<Constraint> = if compact {
+        vec![
+            Constraint::Length(icon_width),
+            Constraint::Min(10),
+            Constraint::Length(6),
+            Constraint::Percentage(40),
+        ]
+    } else {
+        vec![
+            Constraint::Length(icon_width),
+            Constraint::Min(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Percentage(35),
+        ]
+    };
+    let table = Table::new(rows, widths).header(header).column_spacing(1);
+    frame.render_widget(table, inner);
+}
+
+/// 右ペイン: ステータス別カラムの Kanban ボード
+fn render_pods_kanban(frame: &mut Frame, app: &App, area: Rect) {
+    let is_focused = app.state.pane_focus == PaneFocus::Right;
+    let border_color = if is_focused { Color::Cyan } else { Color::DarkGray };
+
+    let block = Block::default()
+        .title(pods_title(app))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let inner = render_filter_bar(frame, app, inner);
+    let inner = render_unmanaged_section(frame, app, inner);
+    let columns = app.state.kanban_columns();
+    if inner.height < 4 || inner.width < columns.len() as u16 * 6 {
+        return;
+    }
+
+    let constraints: Vec<Constraint> = columns
+        .iter()
+        .map(|_| Constraint::Ratio(1, columns.len() as u32))
+        .collect();
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(inner);
+
+    let (_, card_height) = app.config.ui.card_size();
+    // カラム幅が狭いのでカードは最大 5 行 (プレビュー 3 行) に抑える
+    let card_height = card_height.min(5);
+    let quick = quick_keys(app);
+
+    for ((status, indices), col_area) in columns.iter().zip(areas.iter()) {
+        let title = match app.config.ui.icon_set() {
+            IconSet::Text => format!(" {} ({}) ", status.label(), indices.len()),
+            icons => format!(" {} {} ({}) ", status.icon(icons), status.label(), indices.len()),
+        };
+        let col_block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(status_border_color(status)));
+        let col_inner = col_block.inner(*col_area);
+        frame.render_widget(col_block, *col_area);
+
+        if col_inner.height < card_height || col_inner.width < 6 {
+            continue;
+        }
+
+        // フォーカス中のカードが見えるようにスクロール
+        let per_page = ((col_inner.height + CARD_GAP) / (card_height + CARD_GAP)).max(1) as usize;
+        let focus_row = indices.iter().position(|&i| app.state.focus == Some(i)).unwrap_or(0);
+        let skip = if focus_row >= per_page { focus_row + 1 - per_page } else { 0 };
+
+        for (row, &i) in indices.iter().skip(skip).take(per_page).enumerate() {
+            let y = col_inner.y + row as u16 * (card_height + CARD_GAP);
+            let card_area = Rect::new(col_inner.x, y, col_inner.width, card_height);
+            let pod = &app.state.pods[i];
+            render_pod_card(frame, pod, card_area, app.state.focus == Some(i), app.state.selected_pods.contains(&pod.name), quick.get(&i).copied(), &app.config);
+        }
+    }
+}
+
+/// タイトルに付ける作業時間 ("⏱12m")。show_working_time が無効か、まだ作業していなければ None
+fn working_time_label(pod: &crate::pod::Pod, ui: &crate::config::UiConfig) -> Option<String> {
+    let working = pod.live_working_time();
+    (ui.show_working_time && working > 0).then(|| format!("{}{}", ui.icon_set().working_marker(), format_duration(working)))
+}
+
+/// 数字キーで飛べる Pod index → 番号 (1-9)
+fn quick_keys(app: &App) -> std::collections::HashMap<usize, usize> {
+    app.state
+        .quick_focus_targets()
+        .into_iter()
+        .enumerate()
+        .map(|(n, idx)| (idx, n + 1))
+        .collect()
+}
+
+/// タイトル先頭に付ける番号 ("3 ")。番号が無ければ空
+fn quick_label(quick_key: Option<usize>) -> String {
+    quick_key.map(|n| format!("{} ", n)).unwrap_or_default()
+}
+
+/// カード下端に出す "project ⎇ branch · model" (どれも無ければ None)
+fn project_label(pod: &crate::pod::Pod) -> Option<String> {
+    let label = match (pod.project.as_deref(), pod.branch.as_deref()) {
+        (Some(project), Some(branch)) => Some(format!("{} \u{2387} {}", project, branch)),
+        (Some(project), None) => Some(project.to_string()),
+        (None, Some(branch)) => Some(format!("\u{2387} {}", branch)),
+        (None, None) => None,
+    };
+    let label = match pod.pr_url.as_deref().and_then(crate::github::pr_number) {
+        Some(n) => Some(format!("{} PR #{}", label.unwrap_or_default(), n).trim_start().to_string()),
+        None => label,
+    };
+    match pod.model.as_deref().map(crate::pod::short_model_name) {
+        Some(model) => Some(match label {
+            Some(label) => format!("{} \u{00b7} {}", label, model),
+            None => model.to_string(),
+        }),
+        None => label,
+    }
+}
+
+/// 個々の Pod カードを描画（角丸 + ステータス背景色 + アクセントカラーの枠）
+fn render_pod_card(frame: &mut Frame, pod: &crate::pod::Pod, area: Rect, focused: bool, selected: bool, quick_key: Option<usize>, config: &Config) {
+    let ui = &config.ui;
     let is_dead = pod.status == PodStatus::Dead;
     let bg = status_bg_color(&pod.status);
-
-    let border_style = if focused {
+    let usage = pod.total_usage().filter(|_| !is_dead);
+    let over_limit = usage.is_some_and(|u| config.resources.exceeds(&u));
+
+    let border_style = if selected {
+        let style = Style::default().fg(Color::Magenta).bg(bg);
+        if focused { style.add_modifier(Modifier::BOLD) } else { style }
+    } else if over_limit && !focused {
+        Style::default().fg(Color::LightRed).bg(bg)
+    } else if focused {
         Style::default()
             .fg(Color::White)
             .bg(bg)
             .add_modifier(Modifier::BOLD)
-    } else {
+    } else if is_dead {
         Style::default().fg(status_border_color(&pod.status)).bg(bg)
+    } else {
+        Style::default().fg(accent_color(pod)).bg(bg)
     };
 
     // タイトル: ステータスアイコン + 表示名 + 経過時間 + subagent数（カード幅に収める）
-    let icon = pod.status_icon();
-    let elapsed = pod.elapsed_time();
+    let icon = pod.status_icon(ui.icon_set());
+    let mut elapsed = if ui.show_elapsed { pod.elapsed_time() } else { String::new() };
+    if let Some(working) = working_time_label(pod, ui) {
+        if !elapsed.is_empty() {
+            elapsed.push(' ');
+        }
+        elapsed.push_str(&working);
+    }
     let sub_count = pod.total_sub_agents();
-    let sub_suffix = if sub_count > 0 {
+    let mut sub_suffix = if ui.show_subagents && sub_count > 0 {
         format!(" \u{26a1}{}", sub_count)  // ⚡N
     } else {
         String::new()
     };
+    // コンテキスト残量の警告 / compact 中 (いずれかのメンバーで最も逼迫しているもの)
+    if let Some(context) = pod.members.iter().filter_map(|m| m.context).min_by_key(|c| match c {
+        ContextPressure::Compacting => 0,
+        ContextPressure::Low(percent) => u16::from(*percent) + 1,
+    }) {
+        sub_suffix.push(' ');
+        sub_suffix.push_str(&context.badge(ui.icon_set().is_ascii()));
+    }
     let raw_name = if let Some(ref group) = pod.group {
         if pod.name != *group {
             // 子 Pod: グループ名を省略 "../impl"
@@ -942,19 +2215,50 @@ fn render_pod_card(frame: &mut Frame, pod: &crate::pod::Pod, area: Rect, focused
         pod.name.clone()
     };
     let marker = if focused { "\u{25b6} " } else { "" };
-    // 固定部分: " marker icon  elapsed sub_suffix "
-    let fixed_width = format!(" {}{}  {}{} ", marker, icon, elapsed, sub_suffix).width();
+    let pin = if pod.pinned { "\u{2605}" } else { "" }; // ★
+    let check = if selected { "\u{2713}" } else { "" }; // ✓
+    let priority = pod.priority.marker(ui.icon_set().is_ascii()).unwrap_or("");
+    let muted = match (pod.is_muted(chrono::Utc::now()), ui.icon_set().is_ascii()) {
+        (true, false) => "\u{1f515}", // 🔕
+        (true, true) => "~",
+        (false, _) => "",
+    };
+    let pin = format!("{}{}{}", pin, priority, muted);
+    let number = quick_label(quick_key);
+    // 固定部分: " number marker check pin icon  elapsed sub_suffix "
+    let fixed_width = format!(" {}{}{}{}{}  {}{} ", number, marker, check, pin, icon, elapsed, sub_suffix).width();
     let available = (area.width as usize).saturating_sub(fixed_width + 2); // +2 for borders
     let display_name = truncate_to_width(&raw_name, available.max(1));
-    let title = format!(" {}{} {} {}{} ", marker, icon, display_name, elapsed, sub_suffix);
+    let title = format!(" {}{}{}{}{} {} {}{} ", number, marker, check, pin, icon, display_name, elapsed, sub_suffix);
 
-    let block = Block::default()
+    let mut block = Block::default()
         .title(title.as_str())
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(border_style)
         .style(Style::default().bg(bg));
 
+    // 下端: CPU / メモリ (しきい値超過時は常に表示)
+    let mut usage_width = 0;
+    if let Some(u) = usage.filter(|_| config.resources.show_on_cards || over_limit) {
+        let (text, color) = if over_limit {
+            (format!(" \u{1f525}{} ", u.format()), Color::LightRed)
+        } else {
+            (format!(" {} ", u.format()), Color::DarkGray)
+        };
+        usage_width = text.width();
+        block = block.title_bottom(Line::from(Span::styled(text, Style::default().fg(color))).right_aligned());
+    }
+
+    // 下端左: プロジェクト名 + git ブランチ (使用量表示と重ならない幅に収める)
+    if let Some(label) = ui.show_project.then(|| project_label(pod)).flatten() {
+        let available = (area.width as usize).saturating_sub(usage_width + 4);
+        if available >= 3 {
+            let text = format!(" {} ", truncate_to_width(&label, available - 2));
+            block = block.title_bottom(Line::from(Span::styled(text, Style::default().fg(Color::DarkGray))));
+        }
+    }
+
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
@@ -977,21 +2281,35 @@ fn render_pod_card(frame: &mut Frame, pod: &crate::pod::Pod, area: Rect, focused
 
     let available_lines = inner.height as usize;
     let width = inner.width as usize;
+    let mut lines: Vec<Line> = Vec::new();
+
+    // タスク進捗があれば 1 行目に "▰▰▱▱ 2/4 現在のタスク"
+    if let Some(progress) = pod.members.first().and_then(|m| m.progress.as_ref()).filter(|_| !is_dead && available_lines > 1) {
+        let bar = progress.bar(4, ui.icon_set().is_ascii());
+        let current = progress.current.as_deref().unwrap_or("");
+        let title = truncate_to_width(current, width.saturating_sub(bar.width() + 1));
+        lines.push(Line::from(vec![
+            Span::styled(bar, Style::default().fg(Color::Green).bg(bg)),
+            Span::styled(format!(" {}", title), Style::default().fg(Color::Cyan).bg(bg)),
+        ]));
+    }
+
+    // 最後に送った指示 ("› Fix the login bug")
+    if let Some(prompt) = pod.last_prompt.as_deref().filter(|_| available_lines > lines.len() + 1) {
+        let mark = if ui.icon_set().is_ascii() { ">" } else { "\u{203a}" };
+        let text = truncate_to_width(&format!("{} {}", mark, prompt), width);
+        lines.push(Line::from(Span::styled(text, Style::default().fg(Color::Yellow).bg(bg))));
+    }
+
+    let preview_lines = ui.preview_lines().min(available_lines - lines.len());
     let output_lines: Vec<&str> = output.lines().collect();
-    let skip = output_lines.len().saturating_sub(available_lines);
+    let skip = output_lines.len().saturating_sub(preview_lines);
 
-    let mut lines: Vec<Line> = output_lines
-        .iter()
-        .skip(skip)
-        .map(|line| {
-            // カード幅に切り詰め（マルチバイト対応: char 単位で切る）
-            let truncated: String = line.chars().take(width).collect();
-            Line::from(Span::styled(
-                truncated,
-                Style::default().fg(text_color).bg(bg),
-            ))
-        })
-        .collect();
+    lines.extend(output_lines.iter().skip(skip).map(|line| {
+        // カード幅に切り詰め（マルチバイト対応: char 単位で切る）
+        let truncated: String = line.chars().take(width).collect();
+        highlight::apply(Line::from(Span::styled(truncated, Style::default().fg(text_color).bg(bg))), &config.highlight)
+    }));
 
     // 残りの行を背景色で埋める
     while lines.len() < available_lines {
@@ -1014,7 +2332,7 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
 
     // --- 1行目: 統計情報 ---
     let (total_pods, warnings, total_members) = app.state.pods_summary();
-    let total_working: u64 = app.state.pods.iter().map(|p| p.total_working_time()).sum();
+    let total_working: u64 = app.state.pods.iter().map(|p| p.live_working_time()).sum();
     let total_subagents: usize = app.state.pods.iter().map(|p| p.total_sub_agents()).sum();
 
     let mut bar_spans = vec![
@@ -1059,10 +2377,38 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::Blue),
     ));
 
+    if let Some(ref warning) = app.state.budget_warning {
+        bar_spans.push(Span::raw(" "));
+        bar_spans.push(Span::styled(
+            format!(" Budget: {} ", warning),
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if app.state.dnd {
+        bar_spans.push(Span::raw(" "));
+        bar_spans.push(Span::styled(
+            " DND ",
+            Style::default().fg(Color::Black).bg(Color::Gray).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if !app.state.selected_pods.is_empty() {
+        bar_spans.push(Span::raw(" "));
+        bar_spans.push(Span::styled(
+            format!(" \u{2713} {} selected ", app.state.selected_pods.len()),
+            Style::default().fg(Color::Black).bg(Color::Magenta).add_modifier(Modifier::BOLD),
+        ));
+    }
+
     let bar = Line::from(bar_spans);
 
-    let status_bar = Paragraph::new(bar)
-        .style(Style::default().bg(Color::Black));
+    let mut bar_style = Style::default().bg(Color::Black);
+    if app.flashing() {
+        // 許可待ち / エラーを目立たせるため一瞬反転
+        bar_style = bar_style.add_modifier(Modifier::REVERSED);
+    }
+    let status_bar = Paragraph::new(bar).style(bar_style);
     frame.render_widget(status_bar, rows[0]);
 
     // --- 2行目: キーヒント ---
@@ -1085,47 +2431,67 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                         Span::styled("Parent ", label_style),
                         Span::styled("[Space]", key_style),
                         Span::styled("Select ", label_style),
+                        Span::styled("[.]", key_style),
+                        Span::styled("Hidden ", label_style),
+                        Span::styled("[n]", key_style),
+                        Span::styled("New dir ", label_style),
+                        Span::styled("[:]", key_style),
+                        Span::styled("Go to ", label_style),
                         Span::styled("[Esc]", key_style),
                         Span::styled("Cancel", label_style),
                     ])
                 } else {
                 let prompt_label = match &app.state.inline_prompt {
-                    InlinePrompt::AdoptSession => "Session name: ",
-                    InlinePrompt::DropConfirm(_) => "",
-                    InlinePrompt::Browse | InlinePrompt::None => "",
+                    InlinePrompt::EditTags(_) => "Tags (comma separated): ",
+                    InlinePrompt::TagFilter => "Filter by tag (empty clears): ",
+                    InlinePrompt::EditNote(_) => "Note: ",
+                    InlinePrompt::Broadcast => "Send to selected pods: ",
+                    InlinePrompt::MoveToGroup => "Move selected to group (empty ungroups): ",
+                    InlinePrompt::MovePod(_) => "Move to group (empty ungroups): ",
+                    InlinePrompt::AddMember(_) => "Add pane as member (pane [role]): ",
+                    InlinePrompt::SplitMember(_) => "Split member into its own pod (role): ",
+                    InlinePrompt::RenameMember(_) => "Rename member (old new): ",
+                    InlinePrompt::SwitchModel(_) => "Switch model (/model): ",
+                    InlinePrompt::DropConfirm(_)
+                    | InlinePrompt::DropGroupConfirm(_)
+                    | InlinePrompt::BulkDropConfirm
+                    | InlinePrompt::BulkForgetConfirm
+                    | InlinePrompt::BulkMergeConfirm
+                    | InlinePrompt::ClearConfirm(_)
+                    | InlinePrompt::Browse
+                    | InlinePrompt::None => "",
                 };
 
-                // DropConfirm は特別なフォーマット
-                if let InlinePrompt::DropConfirm(ref name) = app.state.inline_prompt {
-                    Line::from(vec![
-                        Span::styled(
-                            format!(" Drop '{}'? (y/yes): ", name),
-                            Style::default().fg(Color::Yellow),
-                        ),
-                        Span::styled(
-                            app.state.inline_input.as_str(),
-                            Style::default().fg(Color::White),
-                        ),
-                        Span::styled("_ ", Style::default().fg(Color::Gray)),
-                        Span::styled("[Enter]", key_style),
-                        Span::styled("OK ", label_style),
-                        Span::styled("[Esc]", key_style),
-                        Span::styled("Cancel", label_style),
-                    ])
-                } else {
-                    Line::from(vec![
-                        Span::styled(format!(" {}", prompt_label), Style::default().fg(Color::Yellow)),
-                        Span::styled(
-                            app.state.inline_input.as_str(),
-                            Style::default().fg(Color::White),
-                        ),
-                        Span::styled("_ ", Style::default().fg(Color::Gray)),
-                        Span::styled("[Enter]", key_style),
-                        Span::styled("OK ", label_style),
-                        Span::styled("[Esc]", key_style),
-                        Span::styled("Cancel", label_style),
-                    ])
-                }
+                // 確認系は特別なフォーマット
+                let selected = app.state.selected_pods.len();
+                let label = match app.state.inline_prompt {
+                    InlinePrompt::DropConfirm(ref name) => format!(" Drop '{}'? (y/yes): ", name),
+                    InlinePrompt::DropGroupConfirm(ref group) => format!(
+                        " Drop group '{}' ({} pods, sessions killed)? (y/yes): ",
+                        group,
+                        app.pods_in_group(group).len()
+                    ),
+                    InlinePrompt::BulkDropConfirm => format!(" Drop {} selected pods? (y/yes): ", selected),
+                    InlinePrompt::BulkForgetConfirm => format!(" Forget {} selected pods? (y/yes): ", selected),
+                    InlinePrompt::ClearConfirm(ref name) => {
+                        format!(" Send /clear to '{}' (drops its conversation)? (y/yes): ", name)
+                    }
+                    InlinePrompt::BulkMergeConfirm => format!(
+                        " Merge selected pods into '{}'? (y/yes): ",
+                        app.state.focused_pod().map(|p| p.name.as_str()).unwrap_or("")
+                    ),
+                    _ => format!(" {}", prompt_label),
+                };
+                let mut spans = vec![Span::styled(label, Style::default().fg(Color::Yellow))];
+                spans.extend(input_spans(&app.state.inline_input, Style::default().fg(Color::White), true));
+                spans.extend([
+                    Span::raw(" "),
+                    Span::styled("[Enter]", key_style),
+                    Span::styled("OK ", label_style),
+                    Span::styled("[Esc]", key_style),
+                    Span::styled("Cancel", label_style),
+                ]);
+                Line::from(spans)
                 } // close Browse else
             } else if app.state.pane_focus == PaneFocus::Left {
                 // 左ペインフォーカス中
@@ -1139,6 +2505,24 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                     Span::styled("[Esc]", key_style),
                     Span::styled("Cancel", label_style),
                 ])
+            } else if !app.state.selected_pods.is_empty() {
+                // 複数選択中: 一括操作
+                Line::from(vec![
+                    Span::styled(" [Space]", key_style),
+                    Span::styled("Toggle ", label_style),
+                    Span::styled("[d]", key_style),
+                    Span::styled("Drop ", label_style),
+                    Span::styled("[a]", key_style),
+                    Span::styled("Archive ", label_style),
+                    Span::styled("[X]", key_style),
+                    Span::styled("Forget ", label_style),
+                    Span::styled("[b]", key_style),
+                    Span::styled("Broadcast ", label_style),
+                    Span::styled("[g]", key_style),
+                    Span::styled("Group ", label_style),
+                    Span::styled("[Esc]", key_style),
+                    Span::styled("Clear selection", label_style),
+                ])
             } else {
                 // 右ペインフォーカス (通常)
                 Line::from(vec![
@@ -1154,6 +2538,8 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                     Span::styled("Adopt ", label_style),
                     Span::styled("[p]", key_style),
                     Span::styled("Browse ", label_style),
+                    Span::styled("[v]", key_style),
+                    Span::styled("View ", label_style),
                     Span::styled("[N]", key_style),
                     Span::styled("Warn ", label_style),
                     Span::styled("[?]", key_style),
@@ -1167,6 +2553,8 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Line::from(vec![
                 Span::styled(" Passthrough ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
                 Span::styled("All keys → pane ", label_style),
+                Span::styled("[F2]", key_style),
+                Span::styled("Chat ", label_style),
                 Span::styled("[Esc]", key_style),
                 Span::styled("Back ", label_style),
             ])
@@ -1175,6 +2563,14 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Line::from(vec![
                 Span::styled(" [Enter]", key_style),
                 Span::styled("Send ", label_style),
+                Span::styled("[Tab]", key_style),
+                Span::styled("Member ", label_style),
+                Span::styled("[PgUp/PgDn]", key_style),
+                Span::styled("Scroll ", label_style),
+                Span::styled("[^R]", key_style),
+                Span::styled(if app.state.chat_raw { "Markdown " } else { "Raw " }, label_style),
+                Span::styled("[^S]", key_style),
+                Span::styled("Export ", label_style),
                 Span::styled("[Esc]", key_style),
                 Span::styled("Back", label_style),
             ])
@@ -1209,6 +2605,8 @@ fn status_color(status: &PodStatus) -> Color {
     match status {
         PodStatus::Permission => Color::Rgb(200, 170, 80),
         PodStatus::Error => Color::Rgb(200, 90, 95),
+        PodStatus::RateLimited => Color::Rgb(160, 110, 200),
+        PodStatus::AwaitingInput => Color::Rgb(80, 190, 200),
         PodStatus::Working => Color::Rgb(80, 130, 200),
         PodStatus::Idle => Color::Rgb(100, 105, 115),
         PodStatus::Done => Color::Rgb(80, 180, 120),
@@ -1222,32 +2620,30 @@ fn status_bg_color(status: &PodStatus) -> Color {
         PodStatus::Working => Color::Rgb(18, 28, 48),
         PodStatus::Permission => Color::Rgb(48, 38, 18),
         PodStatus::Error => Color::Rgb(48, 18, 22),
+        PodStatus::RateLimited => Color::Rgb(36, 24, 48),
+        PodStatus::AwaitingInput => Color::Rgb(16, 40, 44),
         PodStatus::Idle => Color::Rgb(26, 28, 32),
         PodStatus::Done => Color::Rgb(18, 40, 28),
         PodStatus::Dead => Color::Rgb(18, 18, 20),
     }
 }
 
+/// Pod のアクセントカラー (カードの枠と switcher の名前)
+fn accent_color(pod: &crate::pod::Pod) -> Color {
+    let (r, g, b) = pod.accent_rgb();
+    Color::Rgb(r, g, b)
+}
+
 /// Pod カードのボーダー色（背景色より少し明るい）
 fn status_border_color(status: &PodStatus) -> Color {
     match status {
         PodStatus::Working => Color::Rgb(35, 55, 85),
         PodStatus::Permission => Color::Rgb(85, 70, 35),
         PodStatus::Error => Color::Rgb(85, 35, 40),
+        PodStatus::RateLimited => Color::Rgb(65, 45, 85),
+        PodStatus::AwaitingInput => Color::Rgb(32, 72, 78),
         PodStatus::Idle => Color::Rgb(45, 48, 55),
         PodStatus::Done => Color::Rgb(35, 65, 48),
         PodStatus::Dead => Color::Rgb(32, 32, 35),
     }
 }
-
-/// MemberStatus に応じた色を返す
-fn member_status_color(status: &MemberStatus) -> Color {
-    match status {
-        MemberStatus::Permission => Color::Rgb(200, 170, 80),
-        MemberStatus::Error => Color::Rgb(200, 90, 95),
-        MemberStatus::Working => Color::Rgb(80, 130, 200),
-        MemberStatus::Idle => Color::Rgb(100, 105, 115),
-        MemberStatus::Done => Color::Rgb(80, 180, 120),
-        MemberStatus::Dead => Color::Rgb(70, 70, 75),
-    }
-}
@@ -0,0 +1,114 @@
+use crate::project::Project;
+use crate::tui::input::LineEditor;
+use crate::tui::palette::fuzzy_score;
+
+/// `w` のワークスペーススイッチャー。登録済みプロジェクトをあいまい検索して current_project を切り替える
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceSwitcher {
+    pub query: LineEditor,
+    pub selected: usize,
+    /// 開いたときの登録済みプロジェクト (最近使った順、残りは名前順)
+    pub projects: Vec<Project>,
+    /// projects の先頭いくつが最近使ったものか
+    pub recent: usize,
+}
+
+impl WorkspaceSwitcher {
+    /// recent は最近ワークスペースにしたプロジェクト名 (新しい順)。
+    /// 今のワークスペースが先頭なら 1 つ前のものを選んでおく (w Enter で行き来できる)
+    pub fn new(mut projects: Vec<Project>, recent: &[String], current: Option<&str>) -> Self {
+        let rank = |p: &Project| recent.iter().position(|name| *name == p.name).unwrap_or(usize::MAX);
+        projects.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.name.cmp(&b.name)));
+        let recent = projects.iter().take_while(|p| rank(p) != usize::MAX).count();
+        let selected = match projects.first() {
+            Some(first) if projects.len() > 1 && current == Some(first.name.as_str()) => 1,
+            _ => 0,
+        };
+        Self { projects, recent, selected, ..Default::default() }
+    }
+
+    /// projects[i] が最近使ったものか
+    pub fn is_recent(&self, i: usize) -> bool {
+        i < self.recent
+    }
+
+    /// クエリに合うプロジェクトの index を返す。スコア順、同点は名前順
+    pub fn matches(&self) -> Vec<usize> {
+        let query = self.query.as_str();
+        let mut scored: Vec<(i64, usize)> = self
+            .projects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| fuzzy_score(query, &format!("{} {}", p.name, p.path)).map(|score| (score, i)))
+            .collect();
+        scored.sort_by(|(sa, a), (sb, b)| sb.cmp(sa).then(a.cmp(b)));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    pub fn selected_project(&self) -> Option<&Project> {
+        self.matches().get(self.selected).map(|&i| &self.projects[i])
+    }
+
+    pub fn select_next(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        let len = self.matches().len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_name_and_path() {
+        let mut switcher = WorkspaceSwitcher::new(
+            vec![
+                Project::new("web", "/src/frontend/web"),
+                Project::new("api", "/src/backend/api"),
+                Project::new("apiary", "/src/tools/apiary"),
+            ],
+            &[],
+            None,
+        );
+        assert_eq!(switcher.matches(), vec![0, 1, 2]);
+        assert_eq!(switcher.selected_project().map(|p| p.name.as_str()), Some("api"));
+
+        switcher.query.set("front");
+        assert_eq!(switcher.selected_project().map(|p| p.name.as_str()), Some("web"));
+
+        switcher.query.set("zzz");
+        assert!(switcher.matches().is_empty());
+        switcher.select_next();
+        assert_eq!(switcher.selected, 0);
+    }
+
+    #[test]
+    fn test_recent_first() {
+        let projects = vec![
+            Project::new("web", "/src/web"),
+            Project::new("api", "/src/api"),
+            Project::new("docs", "/src/docs"),
+            Project::new("cli", "/src/cli"),
+        ];
+        let recent = ["docs".to_string(), "web".to_string(), "gone".to_string()];
+        let switcher = WorkspaceSwitcher::new(projects.clone(), &recent, Some("docs"));
+        let names: Vec<&str> = switcher.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["docs", "web", "api", "cli"]);
+        assert_eq!(switcher.recent, 2);
+        assert!(switcher.is_recent(1) && !switcher.is_recent(2));
+        // 今のワークスペースの次 (1 つ前に使ったもの) を選んでおく
+        assert_eq!(switcher.selected_project().map(|p| p.name.as_str()), Some("web"));
+
+        let switcher = WorkspaceSwitcher::new(projects, &recent, Some("api"));
+        assert_eq!(switcher.selected_project().map(|p| p.name.as_str()), Some("docs"));
+    }
+}
@@ -0,0 +1,121 @@
+//! `[[watch]]` の評価。ポーリングで取り込んだ画面のうち前回になかった行だけを正規表現で調べる
+
+use std::collections::HashSet;
+use std::process::{Command, Stdio};
+
+use crate::config::WatchRule;
+use crate::pod::Pod;
+
+/// watcher が一致した出来事
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchHit {
+    /// Config::watch の位置
+    pub rule: usize,
+    pub pod: String,
+    pub member: String,
+    pub line: String,
+}
+
+/// current のうち previous になかった (空でない) 行。previous が空なら初回の取り込みなので何も返さない
+pub fn new_lines<'a>(previous: &str, current: &'a str) -> Vec<&'a str> {
+    if previous.is_empty() {
+        return Vec::new();
+    }
+    let seen: HashSet<&str> = previous.lines().map(str::trim_end).collect();
+    current
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty() && !seen.contains(l))
+        .collect()
+}
+
+/// rule が pod を見る対象か (group / project を指定していなければすべての Pod)
+pub fn applies_to(rule: &WatchRule, pod: &Pod) -> bool {
+    rule.group.as_ref().is_none_or(|g| pod.group.as_ref() == Some(g))
+        && rule.project.as_ref().is_none_or(|p| pod.project.as_ref() == Some(p))
+}
+
+/// 新しい行を rules (pod に当てはまるものの位置) で調べる。1 つの watcher は最初に一致した行だけ返す
+pub fn evaluate(rules: &[WatchRule], applicable: &[usize], pod: &str, member: &str, lines: &[&str]) -> Vec<WatchHit> {
+    applicable
+        .iter()
+        .filter_map(|&i| {
+            let line = lines.iter().find(|l| rules[i].regex.is_match(l))?;
+            Some(WatchHit { rule: i, pod: pod.to_string(), member: member.to_string(), line: line.trim().to_string() })
+        })
+        .collect()
+}
+
+/// webhook に一致した内容を JSON で POST する (curl をバックグラウンドで起動、結果は待たない)。
+/// 本文は引数ではなく標準入力で渡す (ps に出さず、`@` で始まる内容をファイル名と解釈させない)
+pub fn post_webhook(url: &str, rule: &WatchRule, hit: &WatchHit) {
+    let payload = serde_json::json!({
+        "watcher": rule.name,
+        "pod": hit.pod,
+        "member": hit.member,
+        "line": hit.line,
+        "at": chrono::Utc::now().to_rfc3339(),
+    });
+    let spawned = Command::new("curl")
+        .args(["-fsS", "-m", "10", "-X", "POST", "-H", "Content-Type: application/json", "--data-binary", "@-"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match spawned {
+        // 本文を書いてから、ゾンビにならないよう別スレッドで回収する
+        Ok(mut child) => {
+            let body = payload.to_string();
+            std::thread::spawn(move || {
+                if let Some(mut stdin) = child.stdin.take() {
+                    let _ = std::io::Write::write_all(&mut stdin, body.as_bytes());
+                }
+                let _ = child.wait();
+            });
+        }
+        Err(e) => tracing::warn!("watch webhook {:?} failed to start: {}", rule.name, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::pod::PodStatus;
+
+    fn pod(group: Option<&str>) -> Pod {
+        Pod {
+            name: "api".to_string(),
+            status: PodStatus::Working,
+            tmux_session: "api".to_string(),
+            project: Some("api".to_string()),
+            group: group.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new_lines() {
+        assert!(new_lines("", "a\nb").is_empty());
+        assert_eq!(new_lines("a\nb\n", "a\nb\nc\n\nd  "), ["c", "d"]);
+        assert!(new_lines("a\nb", "b\na").is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_with_scope() {
+        let config: Config = toml::from_str(
+            "[[watch]]\npattern = \"(?i)drop table\"\n\n[[watch]]\npattern = \"FAIL\"\ngroup = \"backend\"\n",
+        )
+        .unwrap();
+        let solo = pod(None);
+        let applicable: Vec<usize> = (0..config.watch.len()).filter(|&i| applies_to(&config.watch[i], &solo)).collect();
+        assert_eq!(applicable, [0]);
+        assert!(applies_to(&config.watch[1], &pod(Some("backend"))));
+
+        let hits = evaluate(&config.watch, &[0, 1], "api", "lead", &["ok", "  Drop Table users;", "FAIL x", "FAIL y"]);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].line, "Drop Table users;");
+        assert_eq!((hits[1].rule, hits[1].line.as_str()), (1, "FAIL x"));
+    }
+}
@@ -9,7 +9,7 @@ use apiary::pod::discovery::{
     remove_orphan_child_pods, remove_stale_members,
 };
 use apiary::pod::{Member, MemberStatus, Pod, PodStatus, PodType};
-use chrono::Utc;
+use apiary::tmux::Tmux;
 use std::process::Command;
 
 /// tmux が利用可能かチェック
@@ -85,25 +85,13 @@ fn kill_session(name: &str) {
 fn make_test_pod(name: &str, session: &str, pane_id: &str) -> Pod {
     Pod {
         name: name.to_string(),
-        pod_type: PodType::Solo,
         members: vec![Member {
-            role: "lead".to_string(),
             status: MemberStatus::Working,
-            tmux_pane: pane_id.to_string(),
-            last_change: Utc::now(),
-            last_output: String::new(),
-            last_output_ansi: String::new(),
-            pane_size: (80, 24),
-            last_polled: None,
-            working_secs: 0,
-            sub_agents: Vec::new(),
+            ..Member::new("lead", pane_id)
         }],
         status: PodStatus::Working,
         tmux_session: session.to_string(),
-        project: None,
-        group: None,
-        created_at: Utc::now(),
-        total_working_secs: 0,
+        ..Default::default()
     }
 }
 
@@ -158,7 +146,7 @@ fn test_discover_agent_teams_teammates() {
     let all_pods = vec![pod.clone()];
 
     // discover
-    let new_members = discover_new_members(&pod, &all_pods);
+    let new_members = discover_new_members(&Tmux, &pod, &all_pods);
 
     eprintln!("Discovered {} new members:", new_members.len());
     for m in &new_members {
@@ -218,48 +206,21 @@ fn test_remove_stale_agent_teams_members() {
         pod_type: PodType::Team,
         members: vec![
             Member {
-                role: "lead".to_string(),
                 status: MemberStatus::Working,
-                tmux_pane: leader_pane.clone(),
-                last_change: Utc::now(),
-                last_output: String::new(),
-                last_output_ansi: String::new(),
-                pane_size: (80, 24),
-                last_polled: None,
-                working_secs: 0,
-                sub_agents: Vec::new(),
+                ..Member::new("lead", &leader_pane)
             },
             Member {
-                role: "reader-detector".to_string(),
                 status: MemberStatus::Working,
-                tmux_pane: teammate1.clone(),
-                last_change: Utc::now(),
-                last_output: String::new(),
-                last_output_ansi: String::new(),
-                pane_size: (80, 24),
-                last_polled: None,
-                working_secs: 0,
-                sub_agents: Vec::new(),
+                ..Member::new("reader-detector", &teammate1)
             },
             Member {
-                role: "reader-main".to_string(),
                 status: MemberStatus::Working,
-                tmux_pane: teammate2.clone(),
-                last_change: Utc::now(),
-                last_output: String::new(),
-                last_output_ansi: String::new(),
-                pane_size: (80, 24),
-                last_polled: None,
-                working_secs: 0,
-                sub_agents: Vec::new(),
+                ..Member::new("reader-main", &teammate2)
             },
         ],
         status: PodStatus::Working,
         tmux_session: session.clone(),
-        project: None,
-        group: None,
-        created_at: Utc::now(),
-        total_working_secs: 0,
+        ..Default::default()
     };
 
     assert_eq!(pod.members.len(), 3);
@@ -274,7 +235,7 @@ fn test_remove_stale_agent_teams_members() {
     std::thread::sleep(std::time::Duration::from_millis(200));
 
     // stale removal
-    remove_stale_members(&mut pod);
+    remove_stale_members(&Tmux, &mut pod);
 
     eprintln!("After stale removal: {} members", pod.members.len());
     for m in &pod.members {
@@ -336,14 +297,10 @@ fn test_child_pod_creation_from_teammates() {
         let child_name = format!("{}/{}", parent_name, name);
         let child_pod = Pod {
             name: child_name.clone(),
-            pod_type: PodType::Solo,
-            members: Vec::new(),
             status: PodStatus::Working,
             tmux_session: session.clone(),
-            project: None,
             group: Some(parent_name.to_string()),
-            created_at: Utc::now(),
-            total_working_secs: 0,
+            ..Default::default()
         };
         assert_eq!(child_pod.group, Some(parent_name.to_string()));
         assert_eq!(child_pod.tmux_session, session);
@@ -379,7 +336,7 @@ fn test_agent_teams_full_lifecycle() {
     // Phase 1: Solo — leader のみ
     let mut pod = make_test_pod("lifecycle-test", &session, &leader_pane);
     let all_pods = vec![pod.clone()];
-    let new = discover_new_members(&pod, &all_pods);
+    let new = discover_new_members(&Tmux, &pod, &all_pods);
     assert_eq!(new.len(), 0, "No new members expected in solo phase");
     eprintln!("Phase 1 (Solo): {} members", pod.members.len());
 
@@ -399,7 +356,7 @@ fn test_agent_teams_full_lifecycle() {
     std::thread::sleep(std::time::Duration::from_millis(500));
 
     let all_pods = vec![pod.clone()];
-    let new = discover_new_members(&pod, &all_pods);
+    let new = discover_new_members(&Tmux, &pod, &all_pods);
     eprintln!("Phase 2 (Team): discovered {} new members", new.len());
     for m in &new {
         eprintln!("  role={}, pane={}", m.role, m.tmux_pane);
@@ -418,7 +375,7 @@ fn test_agent_teams_full_lifecycle() {
         .status();
     std::thread::sleep(std::time::Duration::from_millis(200));
 
-    remove_stale_members(&mut pod);
+    remove_stale_members(&Tmux, &mut pod);
     eprintln!("Phase 3 (Post-cleanup): {} members", pod.members.len());
 
     assert_eq!(pod.members.len(), 1, "Expected only leader after cleanup");
@@ -471,7 +428,7 @@ fn test_child_pod_lifecycle_with_tmux() {
     std::thread::sleep(std::time::Duration::from_millis(500));
 
     // --- Phase 2: discover → create_child_pods ---
-    let discovered = discover_new_members(&parent, &all_pods);
+    let discovered = discover_new_members(&Tmux, &parent, &all_pods);
     eprintln!("Discovered {} new members", discovered.len());
     assert!(
         discovered.len() >= 2,
@@ -510,7 +467,7 @@ fn test_child_pod_lifecycle_with_tmux() {
 
     // stale removal: 各子 Pod の member を除去
     for pod in &mut all_pods {
-        remove_stale_members(pod);
+        remove_stale_members(&Tmux, pod);
     }
     eprintln!("After stale removal:");
     for pod in &all_pods {